@@ -0,0 +1,356 @@
+//! Anti-spam backtest tool.
+//!
+//! This tool replays archived messages through the anti-spam engine with
+//! candidate configurations, and reports the actions that would have been
+//! taken. It is used to tune the default anti-spam thresholds.
+//!
+//! Messages are read from a file containing one JSON object per line, sorted
+//! by timestamp (`mentions`, `mention_everyone`, `guild_language` and `spam`
+//! are optional):
+//!
+//! ```json
+//! {"guild_id": "1", "author_id": "2", "channel_id": "3", "timestamp": "2022-10-01T12:00:00.000000+00:00", "content": "Hello", "mentions": 0, "mention_everyone": false, "guild_language": "en", "spam": false}
+//! ```
+//!
+//! Messages are checked by the spam engine and the phishing heuristic. If
+//! messages are labeled with the `spam` field, the report includes the
+//! precision and recall of each configuration, computed on the labeled
+//! messages only.
+//!
+//! Candidate configurations are read from a JSON file containing an array of
+//! objects with the `max_messages`, `message_interval` (in seconds),
+//! `max_duplicates`, `max_mentions` and `phishing_threshold` fields. The
+//! default configuration is used if missing. The phishing heuristic is
+//! disabled with a threshold above 100.
+//!
+//! Use `cargo run --features cli --bin anti-spam-backtest -- <messages>` to
+//! run it.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::File,
+    io::{BufRead, BufReader},
+    process::exit,
+    time::Duration,
+};
+
+use argh::FromArgs;
+use raidprotect_anti_spam::{
+    engine::{AntiSpam, SpamConfig, SpamMessage},
+    language::Language,
+    phishing::{phishing_score, PhishingMessage, DEFAULT_THRESHOLD},
+    raid::RaidKind,
+};
+use serde::Deserialize;
+use twilight_model::{
+    id::{
+        marker::{ChannelMarker, GuildMarker, UserMarker},
+        Id,
+    },
+    util::Timestamp,
+};
+
+/// Replay archived messages through the anti-spam engine.
+#[derive(FromArgs, Debug)]
+pub struct BacktestArgs {
+    /// path of the archived messages (JSON lines)
+    #[argh(positional)]
+    messages: String,
+    /// path of the candidate configurations (JSON array)
+    #[argh(option, short = 'c')]
+    config: Option<String>,
+    /// print each action that would have been taken
+    #[argh(switch, short = 'v')]
+    verbose: bool,
+}
+
+/// Archived message.
+#[derive(Debug, Deserialize)]
+struct ArchivedMessage {
+    guild_id: Id<GuildMarker>,
+    author_id: Id<UserMarker>,
+    channel_id: Id<ChannelMarker>,
+    timestamp: Timestamp,
+    content: String,
+    #[serde(default)]
+    mentions: usize,
+    #[serde(default)]
+    mention_everyone: bool,
+    #[serde(default)]
+    guild_language: Option<String>,
+    /// Whether the message is known to be spam.
+    #[serde(default)]
+    spam: Option<bool>,
+}
+
+/// Candidate configuration.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct CandidateConfig {
+    max_messages: usize,
+    message_interval: u64,
+    max_duplicates: usize,
+    max_mentions: usize,
+    phishing_threshold: u8,
+}
+
+impl Default for CandidateConfig {
+    fn default() -> Self {
+        Self {
+            max_messages: 5,
+            message_interval: 5,
+            max_duplicates: 3,
+            max_mentions: 5,
+            phishing_threshold: DEFAULT_THRESHOLD,
+        }
+    }
+}
+
+impl From<&CandidateConfig> for SpamConfig {
+    fn from(config: &CandidateConfig) -> Self {
+        SpamConfig::new(
+            config.max_messages,
+            Duration::from_secs(config.message_interval),
+            config.max_duplicates,
+            config.max_mentions,
+        )
+    }
+}
+
+/// Confusion matrix of the labeled messages.
+#[derive(Debug, Default, Clone, Copy)]
+struct Confusion {
+    true_positives: usize,
+    false_positives: usize,
+    false_negatives: usize,
+    true_negatives: usize,
+}
+
+impl Confusion {
+    /// Record the result for a labeled message.
+    fn record(&mut self, flagged: bool, spam: bool) {
+        match (flagged, spam) {
+            (true, true) => self.true_positives += 1,
+            (true, false) => self.false_positives += 1,
+            (false, true) => self.false_negatives += 1,
+            (false, false) => self.true_negatives += 1,
+        }
+    }
+
+    /// Number of labeled messages.
+    fn total(&self) -> usize {
+        self.true_positives + self.false_positives + self.false_negatives + self.true_negatives
+    }
+
+    /// Share of flagged messages that are spam.
+    fn precision(&self) -> f64 {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_positives,
+        )
+    }
+
+    /// Share of spam messages that are flagged.
+    fn recall(&self) -> f64 {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_negatives,
+        )
+    }
+}
+
+/// Ratio of two counts, `1.0` if the denominator is zero.
+fn ratio(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 1.0;
+    }
+
+    count as f64 / total as f64
+}
+
+fn main() {
+    let args: BacktestArgs = argh::from_env();
+
+    let messages = match read_messages(&args.messages) {
+        Ok(messages) => messages,
+        Err(error) => {
+            eprintln!("failed to read messages: {error}");
+            exit(1);
+        }
+    };
+
+    let candidates = match &args.config {
+        Some(path) => match read_candidates(path) {
+            Ok(candidates) => candidates,
+            Err(error) => {
+                eprintln!("failed to read configurations: {error}");
+                exit(1);
+            }
+        },
+        None => vec![CandidateConfig::default()],
+    };
+
+    println!("replaying {} messages", messages.len());
+
+    for candidate in &candidates {
+        backtest(&messages, candidate, args.verbose);
+    }
+}
+
+/// Replay the messages with a candidate configuration and print the report.
+fn backtest(messages: &[ArchivedMessage], candidate: &CandidateConfig, verbose: bool) {
+    let engine = AntiSpam::new();
+    let config = SpamConfig::from(candidate);
+
+    let mut actions = BTreeMap::<&str, usize>::new();
+    let mut false_positives = BTreeMap::<&str, usize>::new();
+    let mut confusion = Confusion::default();
+    let mut members = HashSet::new();
+    let mut raid_alerts = 0;
+    let mut raid_accounts = 0;
+
+    println!(
+        "\n{} messages in {}s, {} duplicates, {} mentions, phishing score {}",
+        candidate.max_messages,
+        candidate.message_interval,
+        candidate.max_duplicates,
+        candidate.max_mentions,
+        candidate.phishing_threshold
+    );
+
+    for message in messages {
+        let spam_message = SpamMessage {
+            guild_id: message.guild_id,
+            author_id: message.author_id,
+            channel_id: message.channel_id,
+            timestamp: message.timestamp,
+            content: &message.content,
+            mentions: message.mentions,
+            default_language: message
+                .guild_language
+                .as_deref()
+                .and_then(Language::from_code),
+        };
+
+        if let Some(alert) = engine.correlate(spam_message, &config) {
+            raid_alerts += 1;
+            raid_accounts += alert.accounts.len();
+
+            if let (true, RaidKind::Messages { channels, .. }) = (verbose, &alert.kind) {
+                println!(
+                    "  [{}] guild {}: raid of {} accounts in {} channels",
+                    message.timestamp.iso_8601(),
+                    message.guild_id,
+                    alert.accounts.len(),
+                    channels.len()
+                );
+            }
+        }
+
+        let verdict = engine.check(spam_message, &config);
+        let score = phishing_score(PhishingMessage {
+            author_id: message.author_id,
+            timestamp: message.timestamp,
+            content: &message.content,
+            mention_everyone: message.mention_everyone,
+        });
+
+        let kind = match verdict.rule() {
+            Some(rule) => Some(rule),
+            None if score.exceeds(candidate.phishing_threshold) => Some("antispam:phishing"),
+            None => None,
+        };
+
+        if let Some(spam) = message.spam {
+            confusion.record(kind.is_some(), spam);
+
+            if let (Some(kind), false) = (kind, spam) {
+                *false_positives.entry(kind).or_default() += 1;
+            }
+
+            if verbose && kind.is_none() && spam {
+                println!(
+                    "  [{}] guild {} member {}: missed spam",
+                    message.timestamp.iso_8601(),
+                    message.guild_id,
+                    message.author_id
+                );
+            }
+        }
+
+        let kind = match kind {
+            Some(kind) => kind,
+            None => continue,
+        };
+
+        *actions.entry(kind).or_default() += 1;
+        members.insert((message.guild_id, message.author_id));
+
+        if verbose {
+            let label = match message.spam {
+                Some(false) => " (false positive)",
+                _ => "",
+            };
+
+            println!(
+                "  [{}] guild {} member {}: {kind} (score {}){label}",
+                message.timestamp.iso_8601(),
+                message.guild_id,
+                message.author_id,
+                score.score()
+            );
+        }
+    }
+
+    for (kind, count) in &actions {
+        match false_positives.get(kind) {
+            Some(false_positives) => {
+                println!("  {kind}: {count} actions ({false_positives} false positives)")
+            }
+            None => println!("  {kind}: {count} actions"),
+        }
+    }
+    println!(
+        "  total: {} actions on {} members",
+        actions.values().sum::<usize>(),
+        members.len()
+    );
+    println!("  raids: {raid_alerts} alerts on {raid_accounts} accounts");
+
+    if confusion.total() > 0 {
+        println!(
+            "  labeled: {} messages, {} false positives, {} false negatives",
+            confusion.total(),
+            confusion.false_positives,
+            confusion.false_negatives
+        );
+        println!(
+            "  precision: {:.3}, recall: {:.3}",
+            confusion.precision(),
+            confusion.recall()
+        );
+    }
+}
+
+/// Read archived messages.
+fn read_messages(path: &str) -> Result<Vec<ArchivedMessage>, Box<dyn std::error::Error>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut messages = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+
+        if !line.trim().is_empty() {
+            messages.push(serde_json::from_str(&line)?);
+        }
+    }
+
+    Ok(messages)
+}
+
+/// Read candidate configurations.
+fn read_candidates(path: &str) -> Result<Vec<CandidateConfig>, Box<dyn std::error::Error>> {
+    let reader = BufReader::new(File::open(path)?);
+
+    Ok(serde_json::from_reader(reader)?)
+}
@@ -0,0 +1,208 @@
+//! Pluggable spam detectors.
+//!
+//! Each heuristic of the anti-spam engine is implemented as a [`Detector`].
+//! Detectors receive the checked message with the context of its author (see
+//! [`SpamMessage`]) and the guild [`SpamConfig`], and return a [`SpamVerdict`].
+//! Stateful detectors keep their own per-member history.
+//!
+//! The [`DetectorRegistry`] holds the detectors run by the engine, in order.
+//! The built-in detectors are registered by [`DetectorRegistry::builtin`], and
+//! new heuristics can be added as independent modules with
+//! [`DetectorRegistry::register`]. Detectors without a dedicated verdict
+//! return a [`SpamVerdict::Score`].
+//!
+//! Detectors can be individually disabled for a guild with
+//! [`SpamConfig::disabled`], using their [`id`](Detector::id).
+//!
+//! Only the heuristics of the anti-spam engine are detectors. The other
+//! auto-moderation modules of the bot (invites, word filter, attachments,
+//! phishing, blocked domains and webhooks) depend on the guild configuration
+//! and on Discord requests, and are still run separately by the bot after the
+//! engine.
+
+use std::fmt::Debug;
+
+use crate::{
+    engine::{SpamConfig, SpamMessage},
+    language::{self, Language},
+    message::MessageCache,
+    phrases::find_scam_phrase,
+    rate::MessageRateTracker,
+    SpamVerdict,
+};
+
+/// Spam detector.
+///
+/// See the [module documentation](self) for more information.
+pub trait Detector: Debug + Send + Sync {
+    /// Unique identifier of the detector (e.g. `antispam:rate`).
+    ///
+    /// This is the same identifier as the [rule](SpamVerdict::rule) of the
+    /// returned verdicts.
+    fn id(&self) -> &'static str;
+
+    /// Check a message.
+    ///
+    /// Returns [`SpamVerdict::Clean`] if the message is not considered as
+    /// spam by this detector.
+    fn check(&self, message: &SpamMessage, config: &SpamConfig) -> SpamVerdict;
+}
+
+/// Detector of messages with too many mentions.
+#[derive(Debug, Default)]
+pub struct MentionDetector;
+
+impl Detector for MentionDetector {
+    fn id(&self) -> &'static str {
+        "antispam:mentions"
+    }
+
+    fn check(&self, message: &SpamMessage, config: &SpamConfig) -> SpamVerdict {
+        if message.mentions > config.max_mentions {
+            return SpamVerdict::Mentions {
+                count: message.mentions,
+            };
+        }
+
+        SpamVerdict::Clean
+    }
+}
+
+/// Detector of known scam phrases.
+///
+/// Phrases are only searched for the [detected language] of the message. If
+/// it cannot be detected (which is common for short messages), the phrases of
+/// all the languages are searched, starting with the default language of the
+/// guild.
+///
+/// [detected language]: crate::language::detect
+#[derive(Debug, Default)]
+pub struct ScamPhraseDetector;
+
+impl Detector for ScamPhraseDetector {
+    fn id(&self) -> &'static str {
+        "antispam:phrases"
+    }
+
+    fn check(&self, message: &SpamMessage, _config: &SpamConfig) -> SpamVerdict {
+        let languages = match language::detect(message.content) {
+            Some(language) => vec![language],
+            None => {
+                let mut languages = Vec::from(Language::ALL);
+                if let Some(default) = message.default_language {
+                    languages.retain(|language| *language != default);
+                    languages.insert(0, default);
+                }
+
+                languages
+            }
+        };
+
+        languages
+            .into_iter()
+            .find_map(|language| {
+                find_scam_phrase(message.content, language)
+                    .map(|phrase| SpamVerdict::ScamPhrase { language, phrase })
+            })
+            .unwrap_or(SpamVerdict::Clean)
+    }
+}
+
+/// Detector of members sending too many messages.
+///
+/// See the [`rate`](crate::rate) module for more information.
+#[derive(Debug, Default)]
+pub struct RateDetector {
+    tracker: MessageRateTracker,
+}
+
+impl Detector for RateDetector {
+    fn id(&self) -> &'static str {
+        "antispam:rate"
+    }
+
+    fn check(&self, message: &SpamMessage, config: &SpamConfig) -> SpamVerdict {
+        self.tracker.record(
+            message.guild_id,
+            message.author_id,
+            message.timestamp,
+            config.rate,
+        )
+    }
+}
+
+/// Detector of members sending similar messages.
+///
+/// See the [`message`](crate::message) module for more information.
+#[derive(Debug, Default)]
+pub struct DuplicateDetector {
+    cache: MessageCache,
+}
+
+impl Detector for DuplicateDetector {
+    fn id(&self) -> &'static str {
+        "antispam:duplicates"
+    }
+
+    fn check(&self, message: &SpamMessage, config: &SpamConfig) -> SpamVerdict {
+        self.cache.record(
+            message.guild_id,
+            message.author_id,
+            message.timestamp,
+            message.content,
+            config.duplicates,
+        )
+    }
+}
+
+/// Ordered list of the detectors run by the engine.
+#[derive(Debug, Default)]
+pub struct DetectorRegistry {
+    detectors: Vec<Box<dyn Detector>>,
+}
+
+impl DetectorRegistry {
+    /// Initialize an empty [`DetectorRegistry`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Initialize a [`DetectorRegistry`] with the built-in detectors.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+
+        registry.register(MentionDetector);
+        registry.register(ScamPhraseDetector);
+        registry.register(RateDetector::default());
+        registry.register(DuplicateDetector::default());
+
+        registry
+    }
+
+    /// Register a new detector.
+    ///
+    /// The detector is run after the already registered detectors.
+    ///
+    /// # Panics
+    /// This method panics if a detector with the same id is already
+    /// registered.
+    pub fn register(&mut self, detector: impl Detector + 'static) {
+        assert!(
+            !self.ids().any(|id| id == detector.id()),
+            "detector {} already registered",
+            detector.id()
+        );
+
+        self.detectors.push(Box::new(detector));
+    }
+
+    /// Identifiers of the registered detectors.
+    pub fn ids(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.detectors.iter().map(|detector| detector.id())
+    }
+
+    /// Iterate over the registered detectors.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Detector> {
+        self.detectors.iter().map(|detector| &**detector)
+    }
+}
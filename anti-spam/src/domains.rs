@@ -0,0 +1,145 @@
+//! Domain lists.
+//!
+//! Links to phishing websites are one of the most common scams on Discord.
+//! A [`DomainList`] contains domains that are matched against the host of the
+//! links sent in messages. Subdomains of the listed domains are also matched.
+//!
+//! A built-in list of phishing domains is shipped with the crate (see
+//! [`DomainList::builtin`]).
+
+use std::collections::HashSet;
+
+/// Built-in list of phishing domains.
+const BUILTIN_DOMAINS: &str = include_str!("../data/phishing_domains.txt");
+
+/// List of domains.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DomainList {
+    domains: HashSet<String>,
+}
+
+impl DomainList {
+    /// Initialize a new empty [`DomainList`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the built-in list of phishing domains.
+    pub fn builtin() -> Self {
+        Self::parse(BUILTIN_DOMAINS)
+    }
+
+    /// Parse a list of domains.
+    ///
+    /// The list must contain one domain per line. Empty lines and lines
+    /// starting with `#` are ignored, as well as invalid domains.
+    pub fn parse(list: &str) -> Self {
+        let domains = list
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(normalize_domain)
+            .collect();
+
+        Self { domains }
+    }
+
+    /// Number of domains in the list.
+    pub fn len(&self) -> usize {
+        self.domains.len()
+    }
+
+    /// Whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.domains.is_empty()
+    }
+
+    /// Add the domains of another list.
+    pub fn extend(&mut self, other: DomainList) {
+        self.domains.extend(other.domains);
+    }
+
+    /// Find the domain matching a host, if any.
+    ///
+    /// The host is matched if it is in the list or is a subdomain of a domain
+    /// in the list.
+    pub fn find(&self, host: &str) -> Option<&str> {
+        find_domain(host, self.domains.iter().map(String::as_str))
+    }
+}
+
+/// Find the domain of a list matching a host, if any.
+///
+/// The host is matched if it is equal to a domain of the list or is one of its
+/// subdomains. The host is expected to be lowercase.
+pub fn find_domain<'a>(host: &str, domains: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let host = host.trim_end_matches('.');
+
+    domains.into_iter().find(|domain| {
+        host == *domain
+            || host
+                .strip_suffix(*domain)
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    })
+}
+
+/// Normalize a domain entered by a user.
+///
+/// The scheme, path and `www.` prefix are removed and the domain is converted
+/// to lowercase. Returns [`None`] if the domain is invalid.
+pub fn normalize_domain(domain: &str) -> Option<String> {
+    let domain = domain.trim().to_lowercase();
+    let domain = domain.split_once("://").map_or(&*domain, |(_, rest)| rest);
+    let domain = domain.split(['/', '?', '#']).next()?;
+    let domain = domain.trim_end_matches('.');
+    let domain = domain.strip_prefix("www.").unwrap_or(domain);
+
+    let valid = domain.contains('.')
+        && !domain.starts_with(['.', '-'])
+        && !domain.contains("..")
+        && domain
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+
+    valid.then(|| domain.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin() {
+        let list = DomainList::builtin();
+
+        assert!(!list.is_empty());
+        assert_eq!(list.find("dlscord.gift"), Some("dlscord.gift"));
+        assert_eq!(list.find("discord.com"), None);
+    }
+
+    #[test]
+    fn test_find_subdomain() {
+        let list = DomainList::parse("evil.com\n# comment\n\nother.org");
+
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.find("evil.com"), Some("evil.com"));
+        assert_eq!(list.find("login.evil.com"), Some("evil.com"));
+        assert_eq!(list.find("notevil.com"), None);
+        assert_eq!(list.find("evil.com.example"), None);
+    }
+
+    #[test]
+    fn test_normalize_domain() {
+        assert_eq!(normalize_domain("Evil.COM").unwrap(), "evil.com");
+        assert_eq!(
+            normalize_domain("https://www.evil.com/login?a=b").unwrap(),
+            "evil.com"
+        );
+        assert_eq!(normalize_domain("evil.com.").unwrap(), "evil.com");
+        assert!(normalize_domain("localhost").is_none());
+        assert!(normalize_domain("evil..com").is_none());
+        assert!(normalize_domain("evil com.org").is_none());
+    }
+}
@@ -0,0 +1,389 @@
+//! Anti-spam engine.
+//!
+//! The [`AntiSpam`] engine runs all the spam detectors of its
+//! [`DetectorRegistry`] on incoming messages. It is used by the bot, and by the
+//! backtest tool to replay archived messages with different configurations.
+//!
+//! Member joins are recorded separately with [`AntiSpam::record_join`] to
+//! detect join raids.
+
+use std::time::Duration;
+
+use twilight_model::{
+    id::{
+        marker::{ChannelMarker, GuildMarker, UserMarker},
+        Id,
+    },
+    util::Timestamp,
+};
+
+use crate::{
+    detector::DetectorRegistry,
+    language::Language,
+    message::DuplicateLimit,
+    raid::{JoinRaidDetector, JoinRaidLimit, MessageRaidDetector, MessageRaidLimit, RaidAlert},
+    rate::RateLimit,
+    SpamReport, SpamVerdict,
+};
+
+/// Message checked by the anti-spam engine.
+#[derive(Debug, Clone, Copy)]
+pub struct SpamMessage<'a> {
+    /// Guild the message was sent in.
+    pub guild_id: Id<GuildMarker>,
+    /// Author of the message.
+    pub author_id: Id<UserMarker>,
+    /// Channel the message was sent in.
+    pub channel_id: Id<ChannelMarker>,
+    /// Timestamp of when the message was created.
+    pub timestamp: Timestamp,
+    /// Message content.
+    pub content: &'a str,
+    /// Number of users and roles mentioned in the message.
+    ///
+    /// Mentions of everyone are counted as a single mention.
+    pub mentions: usize,
+    /// Language used when the message language cannot be detected.
+    pub default_language: Option<Language>,
+}
+
+/// Member join checked by the anti-spam engine.
+#[derive(Debug, Clone, Copy)]
+pub struct SpamMember<'a> {
+    /// Guild the member joined.
+    pub guild_id: Id<GuildMarker>,
+    /// Id of the member.
+    pub user_id: Id<UserMarker>,
+    /// Username of the member.
+    pub name: &'a str,
+    /// Timestamp of when the member joined the guild.
+    pub joined_at: Timestamp,
+}
+
+/// Configuration of the anti-spam engine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpamConfig {
+    /// Message rate limit.
+    pub rate: RateLimit,
+    /// Duplicate messages limit.
+    pub duplicates: DuplicateLimit,
+    /// Maximum number of mentions in a single message.
+    pub max_mentions: usize,
+    /// Message raid detection limit.
+    pub message_raid: MessageRaidLimit,
+    /// Join raid detection limit.
+    pub join_raid: JoinRaidLimit,
+    /// Identifiers of the disabled detectors.
+    pub disabled: Vec<String>,
+}
+
+impl SpamConfig {
+    /// Interval in which similar messages are counted.
+    pub const DUPLICATE_INTERVAL: Duration = Duration::from_secs(60);
+
+    /// Maximum distance between the fingerprints of two similar messages.
+    pub const DUPLICATE_MAX_DISTANCE: u32 = 10;
+
+    /// Interval in which similar messages of different accounts are counted.
+    pub const RAID_INTERVAL: Duration = Duration::from_secs(30);
+
+    /// Minimum number of accounts sending similar messages to detect a raid.
+    pub const RAID_MIN_ACCOUNTS: usize = 4;
+
+    /// Interval in which the member joins are counted.
+    pub const JOIN_INTERVAL: Duration = Duration::from_secs(60);
+
+    /// Maximum number of members joining during [`JOIN_INTERVAL`].
+    ///
+    /// [`JOIN_INTERVAL`]: Self::JOIN_INTERVAL
+    pub const MAX_JOINS: usize = 10;
+
+    /// Maximum age of an account to be considered as recently created.
+    pub const NEW_ACCOUNT_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+    /// Maximum number of recently created accounts joining during
+    /// [`JOIN_INTERVAL`].
+    ///
+    /// [`JOIN_INTERVAL`]: Self::JOIN_INTERVAL
+    pub const MAX_NEW_ACCOUNTS: usize = 5;
+
+    /// Initialize a new [`SpamConfig`] from the guild limits.
+    ///
+    /// The other parameters use the default values.
+    pub fn new(
+        max_messages: usize,
+        message_interval: Duration,
+        max_duplicates: usize,
+        max_mentions: usize,
+    ) -> Self {
+        Self {
+            rate: RateLimit {
+                max_messages,
+                interval: message_interval,
+            },
+            duplicates: DuplicateLimit {
+                max_duplicates,
+                interval: Self::DUPLICATE_INTERVAL,
+                max_distance: Self::DUPLICATE_MAX_DISTANCE,
+            },
+            max_mentions,
+            message_raid: MessageRaidLimit {
+                min_accounts: Self::RAID_MIN_ACCOUNTS,
+                interval: Self::RAID_INTERVAL,
+                max_distance: Self::DUPLICATE_MAX_DISTANCE,
+            },
+            join_raid: JoinRaidLimit {
+                max_joins: Self::MAX_JOINS,
+                interval: Self::JOIN_INTERVAL,
+                new_account_age: Self::NEW_ACCOUNT_AGE,
+                max_new_accounts: Self::MAX_NEW_ACCOUNTS,
+                min_similar_names: Self::RAID_MIN_ACCOUNTS,
+            },
+            disabled: Vec::new(),
+        }
+    }
+
+    /// Whether a detector is enabled.
+    pub fn is_enabled(&self, id: &str) -> bool {
+        !self.disabled.iter().any(|disabled| disabled == id)
+    }
+}
+
+/// Anti-spam engine.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug)]
+pub struct AntiSpam {
+    detectors: DetectorRegistry,
+    message_raids: MessageRaidDetector,
+    join_raids: JoinRaidDetector,
+}
+
+impl AntiSpam {
+    /// Initialize a new [`AntiSpam`] engine with the built-in detectors.
+    pub fn new() -> Self {
+        Self::with_detectors(DetectorRegistry::builtin())
+    }
+
+    /// Initialize a new [`AntiSpam`] engine with the given detectors.
+    pub fn with_detectors(detectors: DetectorRegistry) -> Self {
+        Self {
+            detectors,
+            message_raids: MessageRaidDetector::default(),
+            join_raids: JoinRaidDetector::default(),
+        }
+    }
+
+    /// Detectors run by the engine.
+    pub fn detectors(&self) -> &DetectorRegistry {
+        &self.detectors
+    }
+
+    /// Check a message for spam.
+    ///
+    /// The detectors are run in order and the first verdict considered as
+    /// spam is returned. Use [`analyze`](Self::analyze) to get the verdicts of
+    /// all the detectors.
+    pub fn check(&self, message: SpamMessage, config: &SpamConfig) -> SpamVerdict {
+        self.analyze(message, config).verdict()
+    }
+
+    /// Run all the enabled detectors on a message.
+    ///
+    /// The returned [`SpamReport`] contains the verdicts of every detector
+    /// that fired, in the order of the registry.
+    pub fn analyze(&self, message: SpamMessage, config: &SpamConfig) -> SpamReport {
+        let mut report = SpamReport::default();
+
+        for detector in self.detectors.iter() {
+            if config.is_enabled(detector.id()) {
+                report.push(detector.check(&message, config));
+            }
+        }
+
+        report
+    }
+
+    /// Correlate a message with the recent messages of other accounts.
+    ///
+    /// This check is independent of [`check`](Self::check), and returns a
+    /// [`RaidAlert`] if similar messages were sent by enough accounts.
+    pub fn correlate(&self, message: SpamMessage, config: &SpamConfig) -> Option<RaidAlert> {
+        self.message_raids.record(
+            message.guild_id,
+            message.author_id,
+            message.channel_id,
+            message.timestamp,
+            message.content,
+            config.message_raid,
+        )
+    }
+
+    /// Record a member join.
+    ///
+    /// Returns a [`RaidAlert`] if the recent joins of the guild look like a
+    /// raid.
+    pub fn record_join(&self, member: SpamMember, config: &SpamConfig) -> Option<RaidAlert> {
+        self.join_raids.record(
+            member.guild_id,
+            member.user_id,
+            member.name,
+            member.joined_at,
+            config.join_raid,
+        )
+    }
+}
+
+impl Default for AntiSpam {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detector::Detector;
+
+    fn message(secs: i64, content: &str, mentions: usize) -> SpamMessage<'_> {
+        SpamMessage {
+            guild_id: Id::new(1),
+            author_id: Id::new(2),
+            channel_id: Id::new(3),
+            timestamp: Timestamp::from_secs(1_600_000_000 + secs).unwrap(),
+            content,
+            mentions,
+            default_language: None,
+        }
+    }
+
+    #[test]
+    fn test_mentions() {
+        let engine = AntiSpam::new();
+        let config = SpamConfig::new(5, Duration::from_secs(5), 3, 5);
+
+        assert_eq!(
+            engine.check(message(0, "hello", 5), &config),
+            SpamVerdict::Clean
+        );
+        assert_eq!(
+            engine.check(message(1, "hello", 6), &config),
+            SpamVerdict::Mentions { count: 6 }
+        );
+    }
+
+    #[test]
+    fn test_scam_phrase() {
+        let engine = AntiSpam::new();
+        let config = SpamConfig::new(5, Duration::from_secs(5), 3, 5);
+
+        assert_eq!(
+            engine.check(
+                message(0, "Hey, I have free nitro for you: dlscord.gift/abc", 0),
+                &config
+            ),
+            SpamVerdict::ScamPhrase {
+                language: Language::English,
+                phrase: "free nitro"
+            }
+        );
+
+        // The phrases of all the languages are searched if the message
+        // language cannot be detected.
+        assert_eq!(
+            engine.check(message(1, "nitro gratuit", 0), &config),
+            SpamVerdict::ScamPhrase {
+                language: Language::French,
+                phrase: "nitro gratuit"
+            }
+        );
+
+        let spam_message = SpamMessage {
+            default_language: Some(Language::French),
+            ..message(2, "nitro gratuit", 0)
+        };
+        assert!(engine.check(spam_message, &config).is_spam());
+    }
+
+    #[test]
+    fn test_message_rate() {
+        let engine = AntiSpam::new();
+        let config = SpamConfig::new(2, Duration::from_secs(5), 3, 5);
+
+        assert!(!engine.check(message(0, "first", 0), &config).is_spam());
+        assert!(!engine.check(message(1, "second", 0), &config).is_spam());
+        assert_eq!(
+            engine.check(message(2, "third", 0), &config).rule(),
+            Some("antispam:rate")
+        );
+    }
+
+    #[test]
+    fn test_analyze() {
+        let engine = AntiSpam::new();
+        let config = SpamConfig::new(2, Duration::from_secs(5), 2, 5);
+
+        engine.analyze(message(0, "buy cheap followers now", 0), &config);
+        engine.analyze(message(1, "buy cheap followers now", 0), &config);
+        let report = engine.analyze(message(2, "buy cheap followers now", 6), &config);
+
+        assert_eq!(
+            report
+                .verdicts
+                .iter()
+                .filter_map(SpamVerdict::rule)
+                .collect::<Vec<_>>(),
+            ["antispam:mentions", "antispam:rate", "antispam:duplicates"]
+        );
+        assert_eq!(report.verdict(), SpamVerdict::Mentions { count: 6 });
+    }
+
+    #[test]
+    fn test_disabled_detector() {
+        let engine = AntiSpam::new();
+        let mut config = SpamConfig::new(5, Duration::from_secs(5), 3, 5);
+        config.disabled.push("antispam:mentions".to_owned());
+
+        assert_eq!(
+            engine.check(message(0, "hello", 6), &config),
+            SpamVerdict::Clean
+        );
+    }
+
+    #[derive(Debug)]
+    struct LengthDetector;
+
+    impl Detector for LengthDetector {
+        fn id(&self) -> &'static str {
+            "test:length"
+        }
+
+        fn check(&self, message: &SpamMessage, _config: &SpamConfig) -> SpamVerdict {
+            match message.content.len() {
+                0..=100 => SpamVerdict::Clean,
+                len => SpamVerdict::Score {
+                    rule: self.id(),
+                    score: len.min(255) as u8,
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_detector() {
+        let mut registry = DetectorRegistry::builtin();
+        registry.register(LengthDetector);
+
+        let engine = AntiSpam::with_detectors(registry);
+        let config = SpamConfig::new(5, Duration::from_secs(5), 3, 5);
+        let content = "a".repeat(150);
+
+        assert_eq!(
+            engine.check(message(0, &content, 0), &config),
+            SpamVerdict::Score {
+                rule: "test:length",
+                score: 150
+            }
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! Word filter.
+//!
+//! A [`WordFilter`] is compiled from a list of rules configured by the guild
+//! moderators. Three kinds of rules are supported (see [`RuleKind`]):
+//!
+//! - **terms**: words or phrases matched on word boundaries, ignoring case and
+//!   punctuation. All the terms are searched at once with an [`AhoCorasick`]
+//!   automaton.
+//! - **wildcards**: terms where `*` matches any part of a word, such as
+//!   `spam*` matching `spammer`.
+//! - **regexes**: regular expressions matched against the raw content,
+//!   ignoring case.
+//!
+//! Wildcards and regexes are each compiled in a single [`RegexSet`], so a
+//! message is checked in a single pass whatever the number of rules. Filters
+//! should be compiled once and reused for all the messages of a guild.
+
+use std::fmt;
+
+use aho_corasick::AhoCorasick;
+use regex::{RegexBuilder, RegexSet, RegexSetBuilder};
+
+use crate::phrases::normalize;
+
+/// Maximum size of a compiled regex (in bytes).
+///
+/// This limit prevents moderators from configuring regexes that would take too
+/// much memory or time to evaluate.
+const REGEX_SIZE_LIMIT: usize = 64 * 1024;
+
+/// Kind of word filter rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleKind {
+    /// Word or phrase matched on word boundaries.
+    Term,
+    /// Term where `*` matches any part of a word.
+    Wildcard,
+    /// Regular expression.
+    Regex,
+}
+
+/// Error returned when a rule is invalid.
+#[derive(Debug)]
+pub enum FilterError {
+    /// The rule does not contain any word.
+    Empty,
+    /// The regex is invalid or too large.
+    Regex(regex::Error),
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterError::Empty => f.write_str("rule does not contain any word"),
+            FilterError::Regex(error) => write!(f, "invalid regex: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+impl From<regex::Error> for FilterError {
+    fn from(error: regex::Error) -> Self {
+        FilterError::Regex(error)
+    }
+}
+
+/// Compiled word filter.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug, Clone)]
+pub struct WordFilter {
+    terms: AhoCorasick,
+    term_rules: Vec<String>,
+    wildcards: RegexSet,
+    wildcard_rules: Vec<String>,
+    regexes: RegexSet,
+    regex_rules: Vec<String>,
+}
+
+impl WordFilter {
+    /// Compile a new [`WordFilter`] from a list of rules.
+    ///
+    /// Rules should be validated with [`WordFilter::validate`] before being
+    /// stored, this method fails if any of the rules is invalid.
+    pub fn new<'a>(
+        rules: impl IntoIterator<Item = (RuleKind, &'a str)>,
+    ) -> Result<Self, FilterError> {
+        let mut terms = Vec::new();
+        let mut term_rules = Vec::new();
+        let mut wildcards = Vec::new();
+        let mut wildcard_rules = Vec::new();
+        let mut regexes = Vec::new();
+        let mut regex_rules = Vec::new();
+
+        for (kind, rule) in rules {
+            match kind {
+                RuleKind::Term => {
+                    terms.push(normalize_term(rule)?);
+                    term_rules.push(rule.to_owned());
+                }
+                RuleKind::Wildcard => {
+                    wildcards.push(wildcard_regex(rule)?);
+                    wildcard_rules.push(rule.to_owned());
+                }
+                RuleKind::Regex => {
+                    regexes.push(rule.to_owned());
+                    regex_rules.push(rule.to_owned());
+                }
+            }
+        }
+
+        Ok(Self {
+            terms: AhoCorasick::new(&terms),
+            term_rules,
+            wildcards: regex_set(&wildcards)?,
+            wildcard_rules,
+            regexes: regex_set(&regexes)?,
+            regex_rules,
+        })
+    }
+
+    /// Validate a single rule.
+    pub fn validate(kind: RuleKind, rule: &str) -> Result<(), FilterError> {
+        match kind {
+            RuleKind::Term => {
+                normalize_term(rule)?;
+            }
+            RuleKind::Wildcard => {
+                regex_builder(&wildcard_regex(rule)?).build()?;
+            }
+            RuleKind::Regex => {
+                regex_builder(rule).build()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Find the rule matching a message content, if any.
+    pub fn find(&self, content: &str) -> Option<&str> {
+        let normalized = normalize(content);
+
+        if let Some(found) = self.terms.find(&normalized) {
+            return Some(&self.term_rules[found.pattern()]);
+        }
+
+        if let Some(index) = self.wildcards.matches(&normalized).iter().next() {
+            return Some(&self.wildcard_rules[index]);
+        }
+
+        self.regexes
+            .matches(content)
+            .iter()
+            .next()
+            .map(|index| &*self.regex_rules[index])
+    }
+}
+
+/// Normalize a term, surrounded with spaces to match on word boundaries.
+fn normalize_term(term: &str) -> Result<String, FilterError> {
+    let normalized = normalize(term);
+
+    match normalized.trim().is_empty() {
+        true => Err(FilterError::Empty),
+        false => Ok(normalized),
+    }
+}
+
+/// Convert a wildcard rule to a regex matching normalized contents.
+fn wildcard_regex(rule: &str) -> Result<String, FilterError> {
+    let parts = rule
+        .split('*')
+        .map(|part| normalize(part).trim().to_owned())
+        .collect::<Vec<_>>();
+
+    if parts.iter().all(String::is_empty) {
+        return Err(FilterError::Empty);
+    }
+
+    let pattern = parts
+        .iter()
+        .map(|part| regex::escape(part))
+        .collect::<Vec<_>>()
+        .join("[^ ]*");
+
+    Ok(format!(" {pattern} "))
+}
+
+fn regex_builder(pattern: &str) -> RegexBuilder {
+    let mut builder = RegexBuilder::new(pattern);
+    builder
+        .case_insensitive(true)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .dfa_size_limit(REGEX_SIZE_LIMIT);
+
+    builder
+}
+
+fn regex_set(patterns: &[String]) -> Result<RegexSet, regex::Error> {
+    RegexSetBuilder::new(patterns)
+        .case_insensitive(true)
+        .size_limit(REGEX_SIZE_LIMIT * patterns.len().max(1))
+        .dfa_size_limit(REGEX_SIZE_LIMIT * patterns.len().max(1))
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terms() {
+        let filter =
+            WordFilter::new([(RuleKind::Term, "bad word"), (RuleKind::Term, "spam")]).unwrap();
+
+        assert_eq!(filter.find("This is a **BAD** word!"), Some("bad word"));
+        assert_eq!(filter.find("spam"), Some("spam"));
+        assert_eq!(filter.find("spammer badword"), None);
+    }
+
+    #[test]
+    fn test_wildcards() {
+        let filter = WordFilter::new([(RuleKind::Wildcard, "spam*")]).unwrap();
+
+        assert_eq!(filter.find("what a Spammer"), Some("spam*"));
+        assert_eq!(filter.find("spam"), Some("spam*"));
+        assert_eq!(filter.find("antispam"), None);
+    }
+
+    #[test]
+    fn test_regexes() {
+        let filter = WordFilter::new([(RuleKind::Regex, r"b[a4]d\s+w[o0]rd")]).unwrap();
+
+        assert_eq!(filter.find("B4D w0rd"), Some(r"b[a4]d\s+w[o0]rd"));
+        assert_eq!(filter.find("good word"), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(WordFilter::validate(RuleKind::Term, "word").is_ok());
+        assert!(matches!(
+            WordFilter::validate(RuleKind::Term, "!!!"),
+            Err(FilterError::Empty)
+        ));
+        assert!(matches!(
+            WordFilter::validate(RuleKind::Wildcard, "**"),
+            Err(FilterError::Empty)
+        ));
+        assert!(matches!(
+            WordFilter::validate(RuleKind::Regex, "(unclosed"),
+            Err(FilterError::Regex(_))
+        ));
+        assert!(matches!(
+            WordFilter::validate(RuleKind::Regex, r"\w{1000}{1000}"),
+            Err(FilterError::Regex(_))
+        ));
+    }
+
+    #[test]
+    fn test_empty() {
+        let filter = WordFilter::new([]).unwrap();
+
+        assert_eq!(filter.find("anything"), None);
+    }
+}
@@ -0,0 +1,181 @@
+//! Message language detection.
+//!
+//! The language of a message is detected by counting the occurrences of
+//! common words (articles, pronouns, prepositions) of each supported language.
+//! This is much cheaper than statistical models and good enough to select the
+//! language-specific filters that should run on a message.
+//!
+//! Short messages often contain too few common words to be detected. The
+//! caller should fall back to the guild language in this case.
+
+use std::fmt::{self, Display};
+
+/// Minimum number of common words for a language to be detected.
+const MIN_MATCHES: usize = 2;
+
+/// Language supported by the language-specific filters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Language {
+    English,
+    French,
+    German,
+    Spanish,
+}
+
+impl Language {
+    /// All supported languages.
+    pub const ALL: [Language; 4] = [
+        Language::English,
+        Language::French,
+        Language::German,
+        Language::Spanish,
+    ];
+
+    /// Get a language from its ISO 639-1 code.
+    ///
+    /// Region subtags are ignored (`en-US` is the same as `en`).
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = match code.split_once('-') {
+            Some((code, _)) => code,
+            None => code,
+        };
+
+        match code {
+            "en" => Some(Language::English),
+            "fr" => Some(Language::French),
+            "de" => Some(Language::German),
+            "es" => Some(Language::Spanish),
+            _ => None,
+        }
+    }
+
+    /// ISO 639-1 code of the language.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::French => "fr",
+            Language::German => "de",
+            Language::Spanish => "es",
+        }
+    }
+
+    /// Common words of the language, sorted alphabetically.
+    fn common_words(&self) -> &'static [&'static str] {
+        match self {
+            Language::English => &[
+                "a", "and", "are", "for", "have", "i", "in", "is", "it", "my", "of", "on", "that",
+                "the", "this", "to", "was", "what", "with", "you", "your",
+            ],
+            Language::French => &[
+                "au", "avec", "ce", "des", "du", "est", "et", "je", "la", "le", "les", "mon",
+                "pas", "pour", "que", "qui", "sur", "un", "une", "vous",
+            ],
+            Language::German => &[
+                "auf", "das", "dem", "den", "der", "die", "du", "ein", "eine", "ich", "ist", "mit",
+                "nicht", "sie", "und", "von", "zu",
+            ],
+            Language::Spanish => &[
+                "con", "del", "el", "en", "es", "las", "los", "mi", "para", "por", "que", "se",
+                "su", "tu", "un", "una", "y",
+            ],
+        }
+    }
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Detect the language of a message content.
+///
+/// Returns [`None`] if no language has enough common words, or if several
+/// languages are equally likely.
+pub fn detect(content: &str) -> Option<Language> {
+    let mut scores = [0usize; Language::ALL.len()];
+
+    let words = content
+        .split(|char: char| !char.is_alphabetic() && char != '\'')
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase);
+
+    for word in words {
+        // Elided articles and pronouns (l'argent, j'ai) are counted as words.
+        let word = match word.split_once('\'') {
+            Some((prefix, _)) => prefix,
+            None => &word,
+        };
+
+        for (score, language) in scores.iter_mut().zip(Language::ALL) {
+            if language.common_words().binary_search(&word).is_ok() {
+                *score += 1;
+            }
+        }
+    }
+
+    let (best, best_score) = scores
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, score)| **score)
+        .expect("at least one language");
+
+    let ambiguous = scores
+        .iter()
+        .enumerate()
+        .any(|(index, score)| index != best && score == best_score);
+
+    if *best_score < MIN_MATCHES || ambiguous {
+        return None;
+    }
+
+    Some(Language::ALL[best])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_common_words_sorted() {
+        for language in Language::ALL {
+            let words = language.common_words();
+
+            assert!(words.windows(2).all(|pair| pair[0] < pair[1]), "{language}");
+        }
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            detect("Hey, what is the best way to configure this bot?"),
+            Some(Language::English)
+        );
+        assert_eq!(
+            detect("Je ne trouve pas le salon de vérification, vous pouvez m'aider ?"),
+            Some(Language::French)
+        );
+        assert_eq!(
+            detect("Ich habe das Problem nicht verstanden, kannst du mir helfen?"),
+            Some(Language::German)
+        );
+        assert_eq!(
+            detect("Hola, ¿alguien sabe cómo configurar el bot para mi servidor?"),
+            Some(Language::Spanish)
+        );
+    }
+
+    #[test]
+    fn test_detect_undetermined() {
+        assert_eq!(detect("lol"), None);
+        assert_eq!(detect("discord.gg/abcdef"), None);
+        assert_eq!(detect("Привет, как дела?"), None);
+    }
+
+    #[test]
+    fn test_from_code() {
+        assert_eq!(Language::from_code("en-US"), Some(Language::English));
+        assert_eq!(Language::from_code("fr"), Some(Language::French));
+        assert_eq!(Language::from_code("ja"), None);
+    }
+}
@@ -0,0 +1,41 @@
+//! # Anti-spam
+//!
+//! This library contains the spam detection logic used by RaidProtect. The
+//! detectors only keep track of the messages they receive and return a
+//! [`SpamVerdict`] for each of them. Applying the punishment is up to the
+//! caller. A [`SpamReport`] with the verdicts of all the detectors can be used
+//! to explain a decision.
+//!
+//! The detectors are independent of Discord and can be used with any source
+//! of messages, such as archived message logs. The [`engine::AntiSpam`] type
+//! runs all the detectors on a message. Detectors implement the
+//! [`detector::Detector`] trait, so new heuristics can be added as
+//! independent modules.
+//!
+//! Most detectors work on the messages of a single member. Raids made of many
+//! accounts posting the same message across channels are detected at the
+//! guild level by the [`raid`] module, as well as join raids.
+//!
+//! Links to phishing websites are matched against the lists of the
+//! [`domains`] module, and words forbidden by the moderators are matched by
+//! the [`filter`] module. Scam messages linking to unknown websites are
+//! detected with the heuristic of the [`phishing`] module.
+//!
+//! A backtest tool is available with the `cli` feature to replay archived
+//! messages with candidate configurations.
+
+pub mod detector;
+pub mod domains;
+pub mod engine;
+pub mod filter;
+pub mod language;
+pub mod message;
+pub mod phishing;
+pub mod phrases;
+pub mod raid;
+pub mod rate;
+
+mod verdict;
+mod window;
+
+pub use verdict::{SpamReport, SpamVerdict};
@@ -0,0 +1,271 @@
+//! Duplicate message detection.
+//!
+//! The [`MessageCache`] keeps a fingerprint of the recent messages of each
+//! guild member. Fingerprints are computed with [`simhash`] from the
+//! normalized message content, so messages with minor variations (casing,
+//! punctuation, repeated characters) have close fingerprints. A message is
+//! considered as a duplicate if the [Hamming distance] between its fingerprint
+//! and the fingerprint of a recent message is below a threshold.
+//!
+//! [Hamming distance]: https://en.wikipedia.org/wiki/Hamming_distance
+
+use std::{sync::Mutex, time::Duration};
+
+use twilight_model::{
+    id::{
+        marker::{GuildMarker, UserMarker},
+        Id,
+    },
+    util::Timestamp,
+};
+
+use crate::{window::MemberWindows, SpamVerdict};
+
+/// Minimum length of the normalized content for a message to be checked.
+///
+/// Short messages such as "ok" or "lol" are often repeated and are not
+/// considered as duplicates.
+pub const MIN_CONTENT_LEN: usize = 10;
+
+/// Maximum number of fingerprints kept for each member.
+pub const HISTORY_LEN: usize = 10;
+
+/// Size of the shingles used to compute the fingerprints.
+const SHINGLE_LEN: usize = 3;
+
+/// Duplicate messages limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DuplicateLimit {
+    /// Maximum number of similar messages allowed during the interval.
+    pub max_duplicates: usize,
+    /// Interval in which the messages are compared.
+    pub interval: Duration,
+    /// Maximum distance between two fingerprints to consider the messages
+    /// as similar.
+    pub max_distance: u32,
+}
+
+/// Recent message fingerprints of each member.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug, Default)]
+pub struct MessageCache {
+    windows: Mutex<MemberWindows<u64>>,
+}
+
+impl MessageCache {
+    /// Initialize a new [`MessageCache`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a new message and check whether it duplicates recent messages of
+    /// its author.
+    ///
+    /// When the limit is exceeded, the similar messages are removed from the
+    /// cache so the verdict is only returned once.
+    pub fn record(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        timestamp: Timestamp,
+        content: &str,
+        limit: DuplicateLimit,
+    ) -> SpamVerdict {
+        let normalized = normalize(content);
+        if normalized.len() < MIN_CONTENT_LEN {
+            return SpamVerdict::Clean;
+        }
+
+        let fingerprint = simhash(&normalized);
+        let timestamp = timestamp.as_micros();
+
+        let mut windows = self.windows.lock().expect("poisoned lock");
+        let window = windows.window((guild_id, user_id), timestamp, limit.interval);
+
+        if window.len() >= HISTORY_LEN {
+            window.pop_front();
+        }
+        window.push_back((timestamp, fingerprint));
+
+        let is_similar = |other: &u64| (fingerprint ^ other).count_ones() <= limit.max_distance;
+        let count = window.iter().filter(|(_, other)| is_similar(other)).count();
+
+        if count > limit.max_duplicates {
+            window.retain(|(_, other)| !is_similar(other));
+
+            return SpamVerdict::Duplicate { count };
+        }
+
+        SpamVerdict::Clean
+    }
+}
+
+/// Normalize a message content before computing its fingerprint.
+///
+/// The content is converted to lowercase, only alphanumeric characters are
+/// kept and repeated characters are collapsed.
+pub fn normalize(content: &str) -> String {
+    let mut normalized = String::with_capacity(content.len());
+    let mut last = None;
+
+    for char in content.chars().flat_map(char::to_lowercase) {
+        if !char.is_alphanumeric() || last == Some(char) {
+            continue;
+        }
+
+        normalized.push(char);
+        last = Some(char);
+    }
+
+    normalized
+}
+
+/// Compute the [SimHash] fingerprint of a normalized content.
+///
+/// The content is split into overlapping shingles of a few characters, and
+/// each bit of the fingerprint is set if it is set in the hash of the majority
+/// of the shingles.
+///
+/// [SimHash]: https://en.wikipedia.org/wiki/SimHash
+pub fn simhash(content: &str) -> u64 {
+    let chars = content.chars().collect::<Vec<_>>();
+    let mut weights = [0i32; 64];
+
+    for shingle in chars.windows(SHINGLE_LEN.min(chars.len()).max(1)) {
+        let hash = fnv1a(shingle);
+
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            if hash & (1 << bit) != 0 {
+                *weight += 1;
+            } else {
+                *weight -= 1;
+            }
+        }
+    }
+
+    weights
+        .iter()
+        .enumerate()
+        .filter(|(_, weight)| **weight > 0)
+        .fold(0, |fingerprint, (bit, _)| fingerprint | (1 << bit))
+}
+
+/// Compute the FNV-1a hash of a shingle.
+fn fnv1a(shingle: &[char]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+
+    for char in shingle {
+        for byte in (*char as u32).to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMIT: DuplicateLimit = DuplicateLimit {
+        max_duplicates: 2,
+        interval: Duration::from_secs(60),
+        max_distance: 10,
+    };
+
+    fn timestamp(secs: i64) -> Timestamp {
+        Timestamp::from_secs(1_600_000_000 + secs).unwrap()
+    }
+
+    fn distance(a: &str, b: &str) -> u32 {
+        (simhash(&normalize(a)) ^ simhash(&normalize(b))).count_ones()
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("Hello, World!!"), "heloworld");
+        assert_eq!(normalize("FREE   NITRO"), "frenitro");
+        assert_eq!(normalize("Été"), "été");
+    }
+
+    #[test]
+    fn test_similar_content() {
+        let original = "Join my server for free nitro discord.gg/abcdef";
+
+        assert_eq!(distance(original, original), 0);
+        assert_eq!(
+            distance(
+                original,
+                "JOIN MY SERVER FOR FREE NITRO discord.gg/abcdef !!"
+            ),
+            0
+        );
+        assert!(distance(original, "Join my server for free nitro discord.gg/abcdeg") <= 10);
+        assert!(distance(original, "What time is the meeting planned tomorrow?") > 10);
+    }
+
+    #[test]
+    fn test_duplicate() {
+        let cache = MessageCache::new();
+        let messages = [
+            "Join my server for free nitro discord.gg/abcdef",
+            "join my server for FREE nitro discord.gg/abcdef",
+        ];
+
+        for (secs, content) in messages.iter().enumerate() {
+            let verdict = cache.record(
+                Id::new(1),
+                Id::new(2),
+                timestamp(secs as i64),
+                content,
+                LIMIT,
+            );
+            assert_eq!(verdict, SpamVerdict::Clean);
+        }
+
+        assert_eq!(
+            cache.record(
+                Id::new(1),
+                Id::new(2),
+                timestamp(2),
+                "Join my server for free nitro!! discord.gg/abcdef",
+                LIMIT
+            ),
+            SpamVerdict::Duplicate { count: 3 }
+        );
+    }
+
+    #[test]
+    fn test_different_messages() {
+        let cache = MessageCache::new();
+        let messages = [
+            "Hello everyone, how are you today?",
+            "Did anyone watch the game yesterday?",
+            "I think the new update is really great",
+            "Let's meet in the voice channel tonight",
+        ];
+
+        for (secs, content) in messages.iter().enumerate() {
+            let verdict = cache.record(
+                Id::new(1),
+                Id::new(2),
+                timestamp(secs as i64),
+                content,
+                LIMIT,
+            );
+            assert_eq!(verdict, SpamVerdict::Clean);
+        }
+    }
+
+    #[test]
+    fn test_short_messages() {
+        let cache = MessageCache::new();
+
+        for secs in 0..5 {
+            let verdict = cache.record(Id::new(1), Id::new(2), timestamp(secs), "lol", LIMIT);
+            assert_eq!(verdict, SpamVerdict::Clean);
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Phishing scam detection.
+//!
+//! Fake Nitro and Steam giveaways are rarely caught by a single rule: scam
+//! phrases change often, and the phishing domains are registered faster than
+//! they can be listed. The [`phishing_score`] heuristic combines several
+//! signals commonly found in these messages into a score from 0 to 100:
+//!
+//! - the message contains a link
+//! - the message contains scam keywords, either a known [scam phrase] in any
+//!   language or several lure words such as "nitro" or "gift"
+//! - the message mentions everyone
+//! - the author account has been created recently
+//!
+//! Each signal has a fixed weight (see [`PhishingSignal::weight`]). A message
+//! with a link and scam keywords is enough to reach the default threshold.
+//!
+//! [scam phrase]: crate::phrases
+
+use twilight_model::{
+    id::{marker::UserMarker, Id},
+    util::Timestamp,
+};
+
+use crate::{
+    engine::SpamConfig,
+    language::Language,
+    phrases::{contains_words, find_scam_phrase, normalize},
+    raid::created_at,
+};
+
+/// Default score above which a message is considered as phishing.
+pub const DEFAULT_THRESHOLD: u8 = 70;
+
+/// Minimum number of lure words to trigger the [`PhishingSignal::ScamKeywords`]
+/// signal without a known scam phrase.
+const MIN_LURE_WORDS: usize = 2;
+
+/// Words commonly used to lure users in phishing messages.
+const LURE_WORDS: [&str; 10] = [
+    "airdrop", "claim", "csgo", "free", "gift", "giveaway", "nitro", "skins", "steam", "trade",
+];
+
+/// Message checked by the phishing heuristic.
+#[derive(Debug, Clone, Copy)]
+pub struct PhishingMessage<'a> {
+    /// Author of the message.
+    pub author_id: Id<UserMarker>,
+    /// Timestamp of when the message was created.
+    pub timestamp: Timestamp,
+    /// Message content.
+    pub content: &'a str,
+    /// Whether the message mentions everyone (@everyone or @here mentions).
+    pub mention_everyone: bool,
+}
+
+/// Signal contributing to the phishing score of a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhishingSignal {
+    /// The message contains a link.
+    Link,
+    /// The message contains scam keywords.
+    ScamKeywords,
+    /// The message mentions everyone.
+    EveryoneMention,
+    /// The author account has been created recently (see
+    /// [`SpamConfig::NEW_ACCOUNT_AGE`]).
+    NewAccount,
+}
+
+impl PhishingSignal {
+    /// Weight of the signal in the phishing score.
+    ///
+    /// The weights of all the signals sum to 100.
+    pub fn weight(&self) -> u8 {
+        match self {
+            PhishingSignal::Link => 30,
+            PhishingSignal::ScamKeywords => 40,
+            PhishingSignal::EveryoneMention => 15,
+            PhishingSignal::NewAccount => 15,
+        }
+    }
+
+    /// Name of the signal.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PhishingSignal::Link => "link",
+            PhishingSignal::ScamKeywords => "keywords",
+            PhishingSignal::EveryoneMention => "everyone",
+            PhishingSignal::NewAccount => "new-account",
+        }
+    }
+}
+
+/// Phishing score of a message.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PhishingScore {
+    /// Signals found in the message.
+    pub signals: Vec<PhishingSignal>,
+}
+
+impl PhishingScore {
+    /// Score of the message, from 0 to 100.
+    pub fn score(&self) -> u8 {
+        self.signals.iter().map(PhishingSignal::weight).sum()
+    }
+
+    /// Whether the score reaches the given threshold.
+    ///
+    /// A message without any signal is never considered as phishing, even
+    /// with a threshold of 0.
+    pub fn exceeds(&self, threshold: u8) -> bool {
+        !self.signals.is_empty() && self.score() >= threshold
+    }
+}
+
+/// Compute the phishing score of a message.
+///
+/// See the [module documentation](self) for more information.
+pub fn phishing_score(message: PhishingMessage) -> PhishingScore {
+    let mut signals = Vec::new();
+
+    if contains_link(message.content) {
+        signals.push(PhishingSignal::Link);
+    }
+
+    if contains_scam_keywords(message.content) {
+        signals.push(PhishingSignal::ScamKeywords);
+    }
+
+    if message.mention_everyone {
+        signals.push(PhishingSignal::EveryoneMention);
+    }
+
+    let account_age = message
+        .timestamp
+        .as_micros()
+        .saturating_sub(created_at(message.author_id));
+    if account_age < SpamConfig::NEW_ACCOUNT_AGE.as_micros() as i64 {
+        signals.push(PhishingSignal::NewAccount);
+    }
+
+    PhishingScore { signals }
+}
+
+/// Whether a message content contains a link.
+fn contains_link(content: &str) -> bool {
+    content
+        .split_whitespace()
+        .any(|word| word.contains("://") || word.starts_with("www."))
+}
+
+/// Whether a message content contains scam keywords.
+fn contains_scam_keywords(content: &str) -> bool {
+    if Language::ALL
+        .into_iter()
+        .any(|language| find_scam_phrase(content, language).is_some())
+    {
+        return true;
+    }
+
+    let normalized = normalize(content);
+    let lure_words = LURE_WORDS
+        .iter()
+        .filter(|word| contains_words(&normalized, word))
+        .count();
+
+    lure_words >= MIN_LURE_WORDS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Discord epoch (first second of 2015) in microseconds.
+    const DISCORD_EPOCH: i64 = 1_420_070_400_000_000;
+
+    const NOW: i64 = 1_600_000_000;
+
+    /// Id of an account created the given number of days before [`NOW`].
+    fn account(days: i64) -> Id<UserMarker> {
+        let millis = ((NOW - days * 24 * 60 * 60) * 1_000_000 - DISCORD_EPOCH) / 1000;
+
+        Id::new(((millis as u64) << 22) + 1)
+    }
+
+    fn message(content: &str, mention_everyone: bool, days: i64) -> PhishingMessage<'_> {
+        PhishingMessage {
+            author_id: account(days),
+            timestamp: Timestamp::from_secs(NOW).unwrap(),
+            content,
+            mention_everyone,
+        }
+    }
+
+    #[test]
+    fn test_phishing_message() {
+        let score = phishing_score(message(
+            "@everyone Free nitro for 3 months: https://dlscord.gift/abc",
+            true,
+            1,
+        ));
+
+        assert_eq!(score.score(), 100);
+        assert!(score.exceeds(DEFAULT_THRESHOLD));
+    }
+
+    #[test]
+    fn test_lure_words() {
+        let score = phishing_score(message(
+            "Steam is giving a gift to everyone https://stearncommunity.ru/gift",
+            false,
+            365,
+        ));
+
+        assert_eq!(
+            score.signals,
+            [PhishingSignal::Link, PhishingSignal::ScamKeywords]
+        );
+        assert!(score.exceeds(DEFAULT_THRESHOLD));
+    }
+
+    #[test]
+    fn test_legitimate_message() {
+        let score = phishing_score(message(
+            "@everyone The new update is out: https://example.com/changelog",
+            true,
+            365,
+        ));
+
+        assert_eq!(
+            score.signals,
+            [PhishingSignal::Link, PhishingSignal::EveryoneMention]
+        );
+        assert!(!score.exceeds(DEFAULT_THRESHOLD));
+    }
+
+    #[test]
+    fn test_empty_score() {
+        let score = phishing_score(message("hello", false, 365));
+
+        assert_eq!(score.score(), 0);
+        assert!(!score.exceeds(0));
+    }
+}
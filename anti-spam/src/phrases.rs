@@ -0,0 +1,130 @@
+//! Scam phrase detection.
+//!
+//! Scam messages (fake Nitro giveaways, fake Steam gifts) are often sent from
+//! compromised accounts and reuse the same phrases. Each [`Language`] has its
+//! own list of phrases, and only the lists of the languages relevant for a
+//! message are checked.
+
+use crate::language::Language;
+
+/// Find a scam phrase of the given language in a message content.
+///
+/// The content is normalized before the search: it is converted to lowercase
+/// and words are separated by a single space, so punctuation and formatting do
+/// not prevent a match. Phrases only match whole words.
+pub fn find_scam_phrase(content: &str, language: Language) -> Option<&'static str> {
+    let normalized = normalize(content);
+
+    scam_phrases(language)
+        .iter()
+        .find(|phrase| contains_words(&normalized, phrase))
+        .copied()
+}
+
+/// Scam phrases of a language.
+///
+/// Phrases must be lowercase words separated by a single space.
+fn scam_phrases(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::English => &[
+            "free discord nitro",
+            "free nitro",
+            "free steam gift",
+            "gift nitro for free",
+            "i leave from cs go",
+            "nitro for free",
+            "steam gave away",
+            "who is first take it",
+        ],
+        Language::French => &[
+            "cadeau steam gratuit",
+            "discord nitro gratuit",
+            "je quitte cs go",
+            "nitro gratuit",
+            "nitro offert",
+            "premier arrivé premier servi",
+        ],
+        Language::German => &[
+            "gratis nitro",
+            "kostenloses discord nitro",
+            "kostenloses nitro",
+            "steam geschenk",
+        ],
+        Language::Spanish => &["discord nitro gratis", "nitro gratis", "regalo de steam"],
+    }
+}
+
+/// Normalize a message content.
+///
+/// Non-alphanumeric characters are replaced with spaces, and the content is
+/// surrounded with spaces so that phrases can be matched on word boundaries.
+pub(crate) fn normalize(content: &str) -> String {
+    let mut normalized = String::with_capacity(content.len() + 2);
+    normalized.push(' ');
+
+    for char in content.chars().flat_map(char::to_lowercase) {
+        if char.is_alphanumeric() {
+            normalized.push(char);
+        } else if !normalized.ends_with(' ') {
+            normalized.push(' ');
+        }
+    }
+
+    if !normalized.ends_with(' ') {
+        normalized.push(' ');
+    }
+
+    normalized
+}
+
+/// Whether a normalized content contains a phrase on word boundaries.
+pub(crate) fn contains_words(normalized: &str, phrase: &str) -> bool {
+    normalized.match_indices(phrase).any(|(index, _)| {
+        normalized[..index].ends_with(' ') && normalized[index + phrase.len()..].starts_with(' ')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("FREE **Nitro**!!"), " free nitro ");
+        assert_eq!(normalize("  "), " ");
+    }
+
+    #[test]
+    fn test_find_scam_phrase() {
+        assert_eq!(
+            find_scam_phrase(
+                "@everyone Free **Nitro** for 3 months: https://dlscord.gift/abc",
+                Language::English
+            ),
+            Some("free nitro")
+        );
+        assert_eq!(
+            find_scam_phrase(
+                "Nitro gratuit ici -> https://dlscord.gift/abc",
+                Language::French
+            ),
+            Some("nitro gratuit")
+        );
+    }
+
+    #[test]
+    fn test_other_language() {
+        assert_eq!(
+            find_scam_phrase("Nitro gratuit ici", Language::English),
+            None
+        );
+    }
+
+    #[test]
+    fn test_word_boundaries() {
+        assert_eq!(
+            find_scam_phrase("it was a carefree nitrogen experiment", Language::English),
+            None
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! Join raid detection.
+//!
+//! Join raids are made of many accounts joining a guild in a short interval,
+//! usually before sending spam messages. The [`JoinRaidDetector`] keeps the
+//! recent joins of each guild and returns a [`RaidAlert`] when:
+//!
+//! - the number of joins exceeds the limit
+//! - too many recently created accounts joined the guild
+//! - enough accounts with similar usernames joined the guild
+//!
+//! Usernames are considered as similar when they share the same stem, which
+//! is the lowercase username without digits and symbols. Raid accounts often
+//! have generated names like `raider_0142` and `Raider 77`.
+
+use std::{sync::Mutex, time::Duration};
+
+use twilight_model::{
+    id::{
+        marker::{GuildMarker, UserMarker},
+        Id,
+    },
+    util::Timestamp,
+};
+
+use super::{RaidAlert, RaidKind};
+use crate::window::GuildWindows;
+
+/// Discord epoch (first second of 2015) in microseconds.
+const DISCORD_EPOCH: i64 = 1_420_070_400_000_000;
+
+/// Minimum length of a username stem to be compared.
+///
+/// Shorter stems are too common to detect similar usernames.
+pub const MIN_STEM_LEN: usize = 3;
+
+/// Join raid detection limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JoinRaidLimit {
+    /// Maximum number of joins during `interval`.
+    pub max_joins: usize,
+    /// Interval in which the joins are counted.
+    pub interval: Duration,
+    /// Maximum age of an account to be considered as recently created.
+    pub new_account_age: Duration,
+    /// Maximum number of recently created accounts joining during `interval`.
+    pub max_new_accounts: usize,
+    /// Minimum number of accounts with similar usernames to trigger an alert.
+    pub min_similar_names: usize,
+}
+
+/// Recent join of a guild.
+#[derive(Debug, Clone)]
+struct GuildJoin {
+    user_id: Id<UserMarker>,
+    new_account: bool,
+    stem: String,
+}
+
+/// Recent joins of each guild.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug, Default)]
+pub struct JoinRaidDetector {
+    windows: Mutex<GuildWindows<GuildJoin>>,
+}
+
+impl JoinRaidDetector {
+    /// Initialize a new [`JoinRaidDetector`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a new member join and check whether the guild is being raided.
+    ///
+    /// The account creation date is computed from the user id. When an alert
+    /// is returned, the reported accounts are removed from the history.
+    pub fn record(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        name: &str,
+        joined_at: Timestamp,
+        limit: JoinRaidLimit,
+    ) -> Option<RaidAlert> {
+        let joined_at = joined_at.as_micros();
+        let account_age = joined_at.saturating_sub(created_at(user_id));
+        let stem = name_stem(name);
+
+        let mut windows = self.windows.lock().expect("poisoned lock");
+        let window = windows.window(guild_id, joined_at, limit.interval);
+
+        window.push_back((
+            joined_at,
+            GuildJoin {
+                user_id,
+                new_account: account_age < limit.new_account_age.as_micros() as i64,
+                stem: stem.clone(),
+            },
+        ));
+
+        let is_similar = |join: &GuildJoin| stem.len() >= MIN_STEM_LEN && join.stem == stem;
+
+        let joins = window.len();
+        let new_accounts = window.iter().filter(|(_, join)| join.new_account).count();
+        let similar_names = window.iter().filter(|(_, join)| is_similar(join)).count();
+
+        let kind = RaidKind::Joins {
+            joins,
+            new_accounts,
+            similar_names,
+        };
+
+        let accounts = if joins > limit.max_joins {
+            window.drain(..).map(|(_, join)| join.user_id).collect()
+        } else if new_accounts > limit.max_new_accounts {
+            let accounts = window
+                .iter()
+                .filter(|(_, join)| join.new_account)
+                .map(|(_, join)| join.user_id)
+                .collect();
+
+            window.retain(|(_, join)| !join.new_account);
+            accounts
+        } else if similar_names >= limit.min_similar_names {
+            let accounts = window
+                .iter()
+                .filter(|(_, join)| is_similar(join))
+                .map(|(_, join)| join.user_id)
+                .collect();
+
+            window.retain(|(_, join)| !is_similar(join));
+            accounts
+        } else {
+            return None;
+        };
+
+        Some(RaidAlert { accounts, kind })
+    }
+}
+
+/// Get the creation date of an account (in microseconds) from its id.
+pub(crate) fn created_at(user_id: Id<UserMarker>) -> i64 {
+    DISCORD_EPOCH + (user_id.get() >> 22) as i64 * 1000
+}
+
+/// Get the stem of a username.
+///
+/// The stem is the lowercase username with only its alphabetic characters.
+fn name_stem(name: &str) -> String {
+    name.chars()
+        .filter(|char| char.is_alphabetic())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMIT: JoinRaidLimit = JoinRaidLimit {
+        max_joins: 5,
+        interval: Duration::from_secs(60),
+        new_account_age: Duration::from_secs(7 * 24 * 60 * 60),
+        max_new_accounts: 3,
+        min_similar_names: 3,
+    };
+
+    /// Id of an account created at the given unix timestamp.
+    fn account(secs: i64) -> Id<UserMarker> {
+        let millis = (secs * 1_000_000 - DISCORD_EPOCH) / 1000;
+
+        Id::new(((millis as u64) << 22) + 1)
+    }
+
+    /// Id of an account created long before the joins.
+    fn old_account(index: i64) -> Id<UserMarker> {
+        account(1_500_000_000 + index)
+    }
+
+    fn record(detector: &JoinRaidDetector, user: Id<UserMarker>, name: &str) -> Option<RaidAlert> {
+        detector.record(
+            Id::new(1),
+            user,
+            name,
+            Timestamp::from_secs(1_600_000_000).unwrap(),
+            LIMIT,
+        )
+    }
+
+    #[test]
+    fn test_created_at() {
+        assert_eq!(created_at(account(1_500_000_000)), 1_500_000_000_000_000);
+    }
+
+    #[test]
+    fn test_name_stem() {
+        assert_eq!(name_stem("Raider_0142"), "raider");
+        assert_eq!(name_stem("raider 77"), "raider");
+        assert_eq!(name_stem("1234"), "");
+    }
+
+    #[test]
+    fn test_join_rate() {
+        let detector = JoinRaidDetector::new();
+        let names = ["alice", "bob", "carol", "dave", "erin", "frank"];
+
+        for (index, name) in names.iter().enumerate().take(5) {
+            assert_eq!(record(&detector, old_account(index as i64), name), None);
+        }
+
+        let alert = record(&detector, old_account(5), names[5]).unwrap();
+        assert_eq!(alert.accounts.len(), 6);
+        assert_eq!(
+            alert.kind,
+            RaidKind::Joins {
+                joins: 6,
+                new_accounts: 0,
+                similar_names: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_new_accounts() {
+        let detector = JoinRaidDetector::new();
+        let new_account = |index| account(1_600_000_000 - 60 * 60 + index);
+
+        assert_eq!(record(&detector, old_account(0), "alice"), None);
+        for (index, name) in ["bob", "carol", "dave"].iter().enumerate() {
+            assert_eq!(record(&detector, new_account(index as i64), name), None);
+        }
+
+        let alert = record(&detector, new_account(3), "erin").unwrap();
+        assert_eq!(alert.accounts, (0..4).map(new_account).collect::<Vec<_>>());
+
+        // Only the recently created accounts are removed from the history.
+        assert_eq!(record(&detector, old_account(1), "frank"), None);
+    }
+
+    #[test]
+    fn test_similar_names() {
+        let detector = JoinRaidDetector::new();
+
+        assert_eq!(record(&detector, old_account(0), "Raider_01"), None);
+        assert_eq!(record(&detector, old_account(1), "alice"), None);
+        assert_eq!(record(&detector, old_account(2), "raider 02"), None);
+
+        let alert = record(&detector, old_account(3), "RAIDER03").unwrap();
+        assert_eq!(
+            alert.accounts,
+            vec![old_account(0), old_account(2), old_account(3)]
+        );
+        assert_eq!(
+            alert.kind,
+            RaidKind::Joins {
+                joins: 4,
+                new_accounts: 0,
+                similar_names: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn test_short_names() {
+        let detector = JoinRaidDetector::new();
+
+        for (index, name) in ["a1", "a2", "a3", "a4"].iter().enumerate() {
+            assert_eq!(record(&detector, old_account(index as i64), name), None);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Cross-account spam correlation.
+//!
+//! Raids are often made of many accounts posting the same message in several
+//! channels. Each account stays below the per-member limits, so the raid is
+//! only visible at the guild level. The [`MessageRaidDetector`] keeps the
+//! fingerprint of the recent messages of each guild, and returns a
+//! [`RaidAlert`] when enough different accounts sent similar messages.
+//!
+//! Messages are compared with the same fingerprints as the [duplicate
+//! detection](crate::message).
+
+use std::{sync::Mutex, time::Duration};
+
+use twilight_model::{
+    id::{
+        marker::{ChannelMarker, GuildMarker, UserMarker},
+        Id,
+    },
+    util::Timestamp,
+};
+
+use super::{RaidAlert, RaidKind};
+use crate::{
+    message::{normalize, simhash, MIN_CONTENT_LEN},
+    window::GuildWindows,
+};
+
+/// Maximum number of fingerprints kept for each guild.
+pub const GUILD_HISTORY_LEN: usize = 256;
+
+/// Message raid detection limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageRaidLimit {
+    /// Minimum number of accounts sending similar messages to trigger an
+    /// alert.
+    pub min_accounts: usize,
+    /// Interval in which the messages are compared.
+    pub interval: Duration,
+    /// Maximum distance between two fingerprints to consider the messages
+    /// as similar.
+    pub max_distance: u32,
+}
+
+/// Recent message of a guild.
+#[derive(Debug, Clone, Copy)]
+struct GuildMessage {
+    author_id: Id<UserMarker>,
+    channel_id: Id<ChannelMarker>,
+    fingerprint: u64,
+}
+
+/// Recent message fingerprints of each guild.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug, Default)]
+pub struct MessageRaidDetector {
+    windows: Mutex<GuildWindows<GuildMessage>>,
+}
+
+impl MessageRaidDetector {
+    /// Initialize a new [`MessageRaidDetector`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a new message and check whether similar messages were sent by
+    /// enough different accounts.
+    ///
+    /// When an alert is returned, the similar messages are removed from the
+    /// history. Accounts that continue the raid afterwards are reported in a
+    /// new alert.
+    pub fn record(
+        &self,
+        guild_id: Id<GuildMarker>,
+        author_id: Id<UserMarker>,
+        channel_id: Id<ChannelMarker>,
+        timestamp: Timestamp,
+        content: &str,
+        limit: MessageRaidLimit,
+    ) -> Option<RaidAlert> {
+        let normalized = normalize(content);
+        if normalized.len() < MIN_CONTENT_LEN {
+            return None;
+        }
+
+        let fingerprint = simhash(&normalized);
+        let timestamp = timestamp.as_micros();
+
+        let mut windows = self.windows.lock().expect("poisoned lock");
+        let window = windows.window(guild_id, timestamp, limit.interval);
+
+        if window.len() >= GUILD_HISTORY_LEN {
+            window.pop_front();
+        }
+        window.push_back((
+            timestamp,
+            GuildMessage {
+                author_id,
+                channel_id,
+                fingerprint,
+            },
+        ));
+
+        let is_similar = |message: &GuildMessage| {
+            (fingerprint ^ message.fingerprint).count_ones() <= limit.max_distance
+        };
+
+        let mut accounts = Vec::new();
+        let mut channels = Vec::new();
+        let mut messages = 0;
+
+        for (_, message) in window.iter().filter(|(_, message)| is_similar(message)) {
+            if !accounts.contains(&message.author_id) {
+                accounts.push(message.author_id);
+            }
+            if !channels.contains(&message.channel_id) {
+                channels.push(message.channel_id);
+            }
+            messages += 1;
+        }
+
+        if accounts.len() < limit.min_accounts {
+            return None;
+        }
+
+        window.retain(|(_, message)| !is_similar(message));
+
+        Some(RaidAlert {
+            accounts,
+            kind: RaidKind::Messages { channels, messages },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMIT: MessageRaidLimit = MessageRaidLimit {
+        min_accounts: 3,
+        interval: Duration::from_secs(30),
+        max_distance: 10,
+    };
+
+    const RAID_MESSAGE: &str = "Join the best server ever discord.gg/abcdef";
+
+    fn record(
+        detector: &MessageRaidDetector,
+        user: u64,
+        channel: u64,
+        secs: i64,
+    ) -> Option<RaidAlert> {
+        detector.record(
+            Id::new(1),
+            Id::new(user),
+            Id::new(channel),
+            Timestamp::from_secs(1_600_000_000 + secs).unwrap(),
+            RAID_MESSAGE,
+            LIMIT,
+        )
+    }
+
+    #[test]
+    fn test_raid() {
+        let detector = MessageRaidDetector::new();
+
+        assert_eq!(record(&detector, 1, 10, 0), None);
+        assert_eq!(record(&detector, 2, 11, 1), None);
+        assert_eq!(
+            record(&detector, 3, 10, 2),
+            Some(RaidAlert {
+                accounts: vec![Id::new(1), Id::new(2), Id::new(3)],
+                kind: RaidKind::Messages {
+                    channels: vec![Id::new(10), Id::new(11)],
+                    messages: 3,
+                },
+            })
+        );
+
+        // The history is cleared after an alert.
+        assert_eq!(record(&detector, 4, 10, 3), None);
+    }
+
+    #[test]
+    fn test_single_account() {
+        let detector = MessageRaidDetector::new();
+
+        for secs in 0..5 {
+            assert_eq!(record(&detector, 1, 10 + secs as u64, secs), None);
+        }
+    }
+
+    #[test]
+    fn test_interval() {
+        let detector = MessageRaidDetector::new();
+
+        assert_eq!(record(&detector, 1, 10, 0), None);
+        assert_eq!(record(&detector, 2, 10, 20), None);
+        assert_eq!(record(&detector, 3, 10, 40), None);
+    }
+}
@@ -0,0 +1,59 @@
+//! Guild-level raid detection.
+//!
+//! Raids are made of many accounts acting together, each of them often
+//! staying below the per-member limits. This module detects two kinds of
+//! raids:
+//!
+//! - [message raids](MessageRaidDetector), where many accounts post similar
+//!   messages in a short interval
+//! - [join raids](JoinRaidDetector), where many accounts join the guild in a
+//!   short interval, most of them being recently created or having similar
+//!   usernames
+//!
+//! Both detectors return a [`RaidAlert`] with the accounts involved in the
+//! raid. The alert is returned once, and the accounts are removed from the
+//! history of the detector.
+
+mod joins;
+mod messages;
+
+use twilight_model::id::{
+    marker::{ChannelMarker, UserMarker},
+    Id,
+};
+
+pub(crate) use self::joins::created_at;
+pub use self::{
+    joins::{JoinRaidDetector, JoinRaidLimit},
+    messages::{MessageRaidDetector, MessageRaidLimit, GUILD_HISTORY_LEN},
+};
+
+/// Alert returned when a raid is detected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaidAlert {
+    /// Accounts that participated in the raid.
+    pub accounts: Vec<Id<UserMarker>>,
+    /// Kind of raid.
+    pub kind: RaidKind,
+}
+
+/// Kind of a detected raid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RaidKind {
+    /// Many accounts sent similar messages.
+    Messages {
+        /// Channels in which the similar messages were sent.
+        channels: Vec<Id<ChannelMarker>>,
+        /// Number of similar messages.
+        messages: usize,
+    },
+    /// Many accounts joined the guild.
+    Joins {
+        /// Number of accounts that recently joined the guild.
+        joins: usize,
+        /// Number of recently created accounts among them.
+        new_accounts: usize,
+        /// Size of the largest group of accounts with similar usernames.
+        similar_names: usize,
+    },
+}
@@ -0,0 +1,164 @@
+//! Message rate tracking.
+//!
+//! The [`MessageRateTracker`] counts the messages sent by each member of a
+//! guild in a sliding window. The timestamps of the recent messages are stored
+//! in a ring buffer for each member, and the oldest timestamps are removed when
+//! they no longer are in the window.
+//!
+//! Intervals longer than [`MAX_INTERVAL`] are truncated.
+
+use std::{sync::Mutex, time::Duration};
+
+use twilight_model::{
+    id::{
+        marker::{GuildMarker, UserMarker},
+        Id,
+    },
+    util::Timestamp,
+};
+
+pub use crate::window::MAX_INTERVAL;
+use crate::{window::MemberWindows, SpamVerdict};
+
+/// Message rate limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Maximum number of messages allowed during the interval.
+    pub max_messages: usize,
+    /// Interval in which the messages are counted.
+    pub interval: Duration,
+}
+
+/// Per-member sliding window message counter.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug, Default)]
+pub struct MessageRateTracker {
+    windows: Mutex<MemberWindows<()>>,
+}
+
+impl MessageRateTracker {
+    /// Initialize a new [`MessageRateTracker`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a new message and check whether the author exceeded the limit.
+    ///
+    /// When the limit is exceeded, the window of the member is cleared so the
+    /// verdict is only returned once for a burst of messages.
+    pub fn record(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        timestamp: Timestamp,
+        limit: RateLimit,
+    ) -> SpamVerdict {
+        let timestamp = timestamp.as_micros();
+
+        let mut windows = self.windows.lock().expect("poisoned lock");
+        let window = windows.window((guild_id, user_id), timestamp, limit.interval);
+
+        window.push_back((timestamp, ()));
+
+        if window.len() > limit.max_messages {
+            let count = window.len();
+            window.clear();
+
+            return SpamVerdict::MessageRate {
+                count,
+                interval: limit.interval,
+            };
+        }
+
+        SpamVerdict::Clean
+    }
+
+    /// Number of members currently tracked.
+    pub fn len(&self) -> usize {
+        self.windows.lock().expect("poisoned lock").len()
+    }
+
+    /// Whether no member is currently tracked.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::PRUNE_EVERY;
+
+    const LIMIT: RateLimit = RateLimit {
+        max_messages: 3,
+        interval: Duration::from_secs(5),
+    };
+
+    fn timestamp(secs: i64) -> Timestamp {
+        Timestamp::from_secs(1_600_000_000 + secs).unwrap()
+    }
+
+    #[test]
+    fn test_below_limit() {
+        let tracker = MessageRateTracker::new();
+
+        for secs in [0, 2, 4, 6, 8, 10] {
+            let verdict = tracker.record(Id::new(1), Id::new(2), timestamp(secs), LIMIT);
+
+            assert_eq!(verdict, SpamVerdict::Clean);
+        }
+    }
+
+    #[test]
+    fn test_exceed_limit() {
+        let tracker = MessageRateTracker::new();
+
+        for secs in 0..3 {
+            let verdict = tracker.record(Id::new(1), Id::new(2), timestamp(secs), LIMIT);
+            assert_eq!(verdict, SpamVerdict::Clean);
+        }
+
+        assert_eq!(
+            tracker.record(Id::new(1), Id::new(2), timestamp(3), LIMIT),
+            SpamVerdict::MessageRate {
+                count: 4,
+                interval: LIMIT.interval
+            }
+        );
+
+        // The window is cleared after a verdict.
+        assert_eq!(
+            tracker.record(Id::new(1), Id::new(2), timestamp(3), LIMIT),
+            SpamVerdict::Clean
+        );
+    }
+
+    #[test]
+    fn test_separate_members() {
+        let tracker = MessageRateTracker::new();
+
+        for user in 1..=4 {
+            let verdict = tracker.record(Id::new(1), Id::new(user), timestamp(0), LIMIT);
+            assert_eq!(verdict, SpamVerdict::Clean);
+        }
+
+        for guild in 1..=4 {
+            let verdict = tracker.record(Id::new(guild), Id::new(5), timestamp(0), LIMIT);
+            assert_eq!(verdict, SpamVerdict::Clean);
+        }
+    }
+
+    #[test]
+    fn test_prune() {
+        let tracker = MessageRateTracker::new();
+
+        tracker.record(Id::new(1), Id::new(1), timestamp(0), LIMIT);
+
+        for index in 0..PRUNE_EVERY as u64 {
+            tracker.record(Id::new(1), Id::new(index + 2), timestamp(600), LIMIT);
+        }
+
+        assert_eq!(tracker.len(), PRUNE_EVERY);
+    }
+}
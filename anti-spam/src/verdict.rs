@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use crate::language::Language;
+
+/// Result of the spam detection for a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpamVerdict {
+    /// The message is not considered as spam.
+    Clean,
+    /// The author sent too many messages in a short interval.
+    MessageRate {
+        /// Number of messages sent during the interval.
+        count: usize,
+        /// Interval in which the messages were counted.
+        interval: Duration,
+    },
+    /// The author sent the same message several times.
+    Duplicate {
+        /// Number of similar messages.
+        count: usize,
+    },
+    /// The message contains too many mentions.
+    Mentions {
+        /// Number of mentions in the message.
+        count: usize,
+    },
+    /// The message contains a known scam phrase.
+    ScamPhrase {
+        /// Language of the phrase.
+        language: Language,
+        /// Phrase found in the message.
+        phrase: &'static str,
+    },
+    /// Verdict of a detector without a dedicated variant.
+    Score {
+        /// Identifier of the detector.
+        rule: &'static str,
+        /// Score computed by the detector.
+        score: u8,
+    },
+}
+
+impl SpamVerdict {
+    /// Whether the message is considered as spam.
+    pub fn is_spam(&self) -> bool {
+        !matches!(self, SpamVerdict::Clean)
+    }
+
+    /// Identifier of the rule that returned the verdict.
+    ///
+    /// Returns [`None`] if the message is not considered as spam.
+    pub fn rule(&self) -> Option<&'static str> {
+        match self {
+            SpamVerdict::Clean => None,
+            SpamVerdict::MessageRate { .. } => Some("antispam:rate"),
+            SpamVerdict::Duplicate { .. } => Some("antispam:duplicates"),
+            SpamVerdict::Mentions { .. } => Some("antispam:mentions"),
+            SpamVerdict::ScamPhrase { .. } => Some("antispam:phrases"),
+            SpamVerdict::Score { rule, .. } => Some(rule),
+        }
+    }
+}
+
+/// Result of all the spam detectors for a message.
+///
+/// Unlike [`SpamVerdict`], which only holds the first detector considered as
+/// spam, the report contains the verdicts of every detector that fired. It is
+/// used to explain the anti-spam decisions to the moderators.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpamReport {
+    /// Verdicts of the detectors that fired, in the order they were run.
+    pub verdicts: Vec<SpamVerdict>,
+}
+
+impl SpamReport {
+    /// Whether the message is considered as spam.
+    pub fn is_spam(&self) -> bool {
+        !self.verdicts.is_empty()
+    }
+
+    /// Verdict of the first detector that fired.
+    ///
+    /// Returns [`SpamVerdict::Clean`] if no detector fired.
+    pub fn verdict(&self) -> SpamVerdict {
+        self.verdicts.first().copied().unwrap_or(SpamVerdict::Clean)
+    }
+
+    /// Add the verdict of a detector to the report.
+    ///
+    /// Verdicts that are not considered as spam are ignored.
+    pub(crate) fn push(&mut self, verdict: SpamVerdict) {
+        if verdict.is_spam() {
+            self.verdicts.push(verdict);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Sliding windows of recent entries.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    time::Duration,
+};
+
+use twilight_model::id::{
+    marker::{GuildMarker, UserMarker},
+    Id,
+};
+
+/// Maximum interval supported by the windows.
+///
+/// Windows that haven't received any entry since this interval are removed
+/// when the windows are pruned.
+pub const MAX_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Number of recorded entries between each pruning.
+pub(crate) const PRUNE_EVERY: usize = 1024;
+
+/// Key identifying a guild member.
+type MemberKey = (Id<GuildMarker>, Id<UserMarker>);
+
+/// Recent entries of each guild member.
+pub(crate) type MemberWindows<T> = Windows<MemberKey, T>;
+
+/// Recent entries of each guild.
+pub(crate) type GuildWindows<T> = Windows<Id<GuildMarker>, T>;
+
+/// Recent entries of each key, with their timestamp (in microseconds).
+#[derive(Debug)]
+pub(crate) struct Windows<K, T> {
+    windows: HashMap<K, VecDeque<(i64, T)>>,
+    /// Number of entries recorded since the last pruning.
+    recorded: usize,
+}
+
+impl<K: Eq + Hash, T> Windows<K, T> {
+    /// Get the window of a key, without the entries older than `interval`.
+    ///
+    /// The windows are periodically pruned when calling this method.
+    pub(crate) fn window(
+        &mut self,
+        key: K,
+        now: i64,
+        interval: Duration,
+    ) -> &mut VecDeque<(i64, T)> {
+        self.recorded += 1;
+        if self.recorded >= PRUNE_EVERY {
+            self.prune(now);
+        }
+
+        let interval = interval.min(MAX_INTERVAL).as_micros() as i64;
+        let window = self.windows.entry(key).or_default();
+
+        while matches!(window.front(), Some((first, _)) if now - first >= interval) {
+            window.pop_front();
+        }
+
+        window
+    }
+
+    /// Number of keys currently tracked.
+    pub(crate) fn len(&self) -> usize {
+        self.windows.len()
+    }
+
+    /// Remove the windows without any entry since [`MAX_INTERVAL`].
+    fn prune(&mut self, now: i64) {
+        let max_interval = MAX_INTERVAL.as_micros() as i64;
+
+        self.windows.retain(
+            |_, window| matches!(window.back(), Some((last, _)) if now - last < max_interval),
+        );
+        self.recorded = 0;
+    }
+}
+
+impl<K, T> Default for Windows<K, T> {
+    fn default() -> Self {
+        Self {
+            windows: HashMap::new(),
+            recorded: 0,
+        }
+    }
+}
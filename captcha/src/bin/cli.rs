@@ -11,6 +11,7 @@ use raidprotect_captcha::{
     code::{random_code, random_human_code},
     generate_captcha,
 };
+use rand::{rngs::StdRng, SeedableRng};
 
 /// Generate a captcha.
 #[derive(FromArgs, Debug)]
@@ -27,19 +28,27 @@ pub struct CaptchaArgs {
     /// whether the generated code should be easy to read for a human
     #[argh(switch, short = 'h')]
     human: bool,
+    /// seed of the random number generator (random if missing)
+    #[argh(option, short = 's')]
+    seed: Option<u64>,
 }
 
 fn main() {
     let args: CaptchaArgs = argh::from_env();
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
     let code = args.code.unwrap_or_else(|| {
         if args.human {
-            random_human_code(args.length)
+            random_human_code(args.length, &mut rng)
         } else {
-            random_code(args.length)
+            random_code(args.length, &mut rng)
         }
     });
 
-    let image = generate_captcha(&code);
+    let image = generate_captcha(&code, &mut rng);
     let (width, height) = image.dimensions();
 
     if let Some(output) = args.output {
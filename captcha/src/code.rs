@@ -3,20 +3,22 @@
 //! - [`random_code`] generates a random code using alphabetic ascii characters.
 //! - [`random_human_code`] generates a random human-readable code using
 //!   alphabetic ascii character.
+//!
+//! Both functions take the random number generator to use as argument. The
+//! [`validate_code`] function is used to check a code entered by a user.
 
-use rand::{rngs::ThreadRng, Rng};
+use rand::Rng;
 
 /// Generates a random code.
 ///
 /// The generated code is a [`String`] of `len` random a-z ascii characters.
-pub fn random_code(len: usize) -> String {
+pub fn random_code(len: usize, rng: &mut impl Rng) -> String {
     const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
 
-    let mut rng = rand::thread_rng();
     let mut code = String::with_capacity(len);
 
     for _ in 0..len {
-        code.push(random_char(&mut rng, CHARSET));
+        code.push(random_char(rng, CHARSET));
     }
 
     code
@@ -27,25 +29,44 @@ pub fn random_code(len: usize) -> String {
 /// The generated code alternates between consonants and vowels.
 ///
 /// Adapted from [Proquints](https://arxiv.org/html/0901.4016).
-pub fn random_human_code(len: usize) -> String {
+pub fn random_human_code(len: usize, rng: &mut impl Rng) -> String {
     const CONSONANTS: &[u8] = b"bdfghjklmnprstvz";
     const VOWELS: &[u8] = b"aiou";
 
-    let mut rng = rand::thread_rng();
     let mut code = String::with_capacity(5);
 
     for idx in 0..len {
         if idx % 2 == 0 {
-            code.push(random_char(&mut rng, CONSONANTS));
+            code.push(random_char(rng, CONSONANTS));
         } else {
-            code.push(random_char(&mut rng, VOWELS));
+            code.push(random_char(rng, VOWELS));
         }
     }
 
     code
 }
 
-fn random_char(rng: &mut ThreadRng, charset: &[u8]) -> char {
+/// Validate a code entered by a user, accepting at most one error.
+///
+/// The comparison is case-insensitive since the captcha image only displays
+/// uppercase letters.
+pub fn validate_code(input: &str, code: &str) -> bool {
+    if input.chars().count() != code.chars().count() {
+        return false;
+    }
+
+    let mut errors: u8 = 0;
+
+    for (a, b) in input.chars().zip(code.chars()) {
+        if !a.eq_ignore_ascii_case(&b) {
+            errors += 1;
+        }
+    }
+
+    errors <= 1
+}
+
+fn random_char(rng: &mut impl Rng, charset: &[u8]) -> char {
     let index = rng.gen_range(0..charset.len());
 
     charset[index] as char
@@ -53,12 +74,15 @@ fn random_char(rng: &mut ThreadRng, charset: &[u8]) -> char {
 
 #[cfg(test)]
 mod tests {
-    use super::{random_code, random_human_code};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{random_code, random_human_code, validate_code};
 
     #[test]
     fn test_random_code() {
-        let code_1 = random_code(6);
-        let code_2 = random_code(6);
+        let mut rng = rand::thread_rng();
+        let code_1 = random_code(6, &mut rng);
+        let code_2 = random_code(6, &mut rng);
 
         assert_eq!(code_1.len(), 6);
         assert_ne!(code_1, code_2);
@@ -66,10 +90,28 @@ mod tests {
 
     #[test]
     fn test_random_human_code() {
-        let code_1 = random_human_code(6);
-        let code_2 = random_human_code(6);
+        let mut rng = rand::thread_rng();
+        let code_1 = random_human_code(6, &mut rng);
+        let code_2 = random_human_code(6, &mut rng);
 
         assert_eq!(code_1.len(), 6);
         assert_ne!(code_1, code_2);
     }
+
+    #[test]
+    fn test_code_seeded() {
+        let code_1 = random_human_code(6, &mut StdRng::seed_from_u64(0));
+        let code_2 = random_human_code(6, &mut StdRng::seed_from_u64(0));
+
+        assert_eq!(code_1, code_2);
+    }
+
+    #[test]
+    fn test_validate_code() {
+        assert!(validate_code("abc", "abc")); // no errors
+        assert!(validate_code("abc", "abd")); // one error
+        assert!(!validate_code("abc", "ade")); // two errors (fail)
+        assert!(validate_code("ABC", "abc")); // case-insensitive
+        assert!(!validate_code("ab", "abc")); // invalid length
+    }
 }
@@ -3,6 +3,10 @@
 //! This library contains the captcha image generator used by RaidProtect. The
 //! generated [`GrayImage`] can be converted to any relevant image format. A
 //! [`generate_captcha_png`] function is provided for convenience.
+//!
+//! All the generation functions take the random number generator to use as
+//! argument, which allows to generate reproducible images using a seeded
+//! generator. Use [`rand::thread_rng`] if you don't need reproducible images.
 
 pub mod code;
 
@@ -17,7 +21,7 @@ use imageproc::{
     geometric_transformations::{self, Interpolation, Projection},
 };
 use once_cell::sync::Lazy;
-use rand::{rngs::ThreadRng, seq::SliceRandom, Rng};
+use rand::{seq::SliceRandom, Rng};
 use rusttype::{Font, Scale};
 
 /// Font used for the captcha generation.
@@ -27,32 +31,38 @@ use rusttype::{Font, Scale};
 static FONT: Lazy<Font<'static>> =
     Lazy::new(|| Font::try_from_bytes(include_bytes!("../include/FreeMonoBold.ttf")).unwrap());
 
-const IMAGE_HEIGHT: u32 = 150;
+/// Height of the generated images.
+pub const IMAGE_HEIGHT: u32 = 150;
+/// Width of each letter in the generated images.
+pub const LETTER_WIDTH: u32 = 80;
+/// Horizontal margin on each side of the generated images.
+pub const IMAGE_MARGIN: u32 = 20;
+
 const LETTER_HEIGHT: u32 = 100;
-const LETTER_WIDTH: u32 = 80;
 
 /// Generate a new captcha image with the provided code.
-pub fn generate_captcha(code: &str) -> GrayImage {
-    let image_width = (code.len() as u32 * LETTER_WIDTH) + 40;
+///
+/// Using the same seeded generator will always produce the same image.
+pub fn generate_captcha(code: &str, rng: &mut impl Rng) -> GrayImage {
+    let image_width = (code.len() as u32 * LETTER_WIDTH) + IMAGE_MARGIN * 2;
     let mut image = GrayAlphaImage::from_pixel(image_width, IMAGE_HEIGHT, LumaA([255, 255]));
-    let mut rng = rand::thread_rng();
 
     for (index, letter) in code.char_indices() {
-        let x = (index as u32 * LETTER_WIDTH) + 20;
+        let x = (index as u32 * LETTER_WIDTH) + IMAGE_MARGIN;
         let y = rng.gen_range(0..70);
 
-        let letter_image = generate_letter(letter, &mut rng);
+        let letter_image = generate_letter(letter, rng);
         overlay(&mut image, &letter_image, x as i64, y);
     }
 
-    image_noise(&mut image, &mut rng);
+    image_noise(&mut image, rng);
 
     DynamicImage::ImageLumaA8(image).to_luma8()
 }
 
 /// Generate a new captcha with the provided code and encode it as png.
-pub fn generate_captcha_png(code: &str) -> Result<Vec<u8>, ImageError> {
-    let image = generate_captcha(code);
+pub fn generate_captcha_png(code: &str, rng: &mut impl Rng) -> Result<Vec<u8>, ImageError> {
+    let image = generate_captcha(code, rng);
     let mut buffer = Cursor::new(Vec::new());
 
     image.write_to(&mut buffer, ImageOutputFormat::Png)?;
@@ -61,7 +71,7 @@ pub fn generate_captcha_png(code: &str) -> Result<Vec<u8>, ImageError> {
 }
 
 /// Generate a captcha letter.
-fn generate_letter(letter: char, rng: &mut ThreadRng) -> GrayAlphaImage {
+fn generate_letter(letter: char, rng: &mut impl Rng) -> GrayAlphaImage {
     let mut image = GrayAlphaImage::new(LETTER_WIDTH, LETTER_HEIGHT);
 
     drawing::draw_text_mut(
@@ -81,7 +91,7 @@ fn generate_letter(letter: char, rng: &mut ThreadRng) -> GrayAlphaImage {
 ///
 /// A projection is calculated with a randomization of the found image corners
 /// coordinates.
-fn letter_transform(image: GrayAlphaImage, rng: &mut ThreadRng) -> GrayAlphaImage {
+fn letter_transform(image: GrayAlphaImage, rng: &mut impl Rng) -> GrayAlphaImage {
     let (width, height) = (image.dimensions().0 as f32, image.dimensions().1 as f32);
 
     // Choose which corners to transform.
@@ -142,10 +152,40 @@ fn letter_transform(image: GrayAlphaImage, rng: &mut ThreadRng) -> GrayAlphaImag
 }
 
 /// Add noise to the image.
-fn image_noise(image: &mut GrayAlphaImage, rng: &mut ThreadRng) {
+fn image_noise(image: &mut GrayAlphaImage, rng: &mut impl Rng) {
     for pixel in image.pixels_mut() {
         let noise = rng.gen_range(0..255);
 
         pixel.blend(&LumaA([noise, 160]));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    /// Minimum number of opaque pixels in a generated letter.
+    ///
+    /// Letters can be heavily squeezed when both top or bottom corners are
+    /// transformed, so this value is kept low.
+    const MIN_LETTER_PIXELS: usize = 100;
+
+    #[test]
+    fn test_letters_visible_after_transform() {
+        for seed in 0..5 {
+            let mut rng = StdRng::seed_from_u64(seed);
+
+            for letter in 'a'..='z' {
+                let image = generate_letter(letter, &mut rng);
+                let opaque = image.pixels().filter(|pixel| pixel.0[1] > 128).count();
+
+                assert!(
+                    opaque >= MIN_LETTER_PIXELS,
+                    "letter {letter} has only {opaque} visible pixels (seed {seed})"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use image::GrayImage;
+use raidprotect_captcha::{
+    code::random_code, generate_captcha, IMAGE_HEIGHT, IMAGE_MARGIN, LETTER_WIDTH,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// Compute a FNV-1a hash of the image pixels.
+///
+/// This is used to compare generated images with known snapshots without
+/// storing the images in the repository.
+fn image_hash(image: &GrayImage) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+
+    for byte in image.as_raw() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    hash
+}
+
+#[test]
+fn test_dimensions_scale_with_length() {
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+    for len in 1..=10 {
+        let code = random_code(len, &mut rng);
+        let image = generate_captcha(&code, &mut rng);
+
+        assert_eq!(
+            image.dimensions(),
+            (len as u32 * LETTER_WIDTH + IMAGE_MARGIN * 2, IMAGE_HEIGHT),
+            "invalid dimensions for code {code}"
+        );
+    }
+}
+
+#[test]
+fn test_deterministic_with_seed() {
+    for seed in 0..5 {
+        let first = generate_captcha("abcdef", &mut ChaCha8Rng::seed_from_u64(seed));
+        let second = generate_captcha("abcdef", &mut ChaCha8Rng::seed_from_u64(seed));
+
+        assert_eq!(first, second, "images differ with seed {seed}");
+    }
+}
+
+#[test]
+fn test_different_seeds() {
+    let first = generate_captcha("abcdef", &mut ChaCha8Rng::seed_from_u64(1));
+    let second = generate_captcha("abcdef", &mut ChaCha8Rng::seed_from_u64(2));
+
+    assert_ne!(first, second);
+}
+
+/// Ensure the generated image does not change unexpectedly.
+///
+/// If the generation algorithm is intentionally modified, this hash must be
+/// updated.
+#[test]
+fn test_snapshot() {
+    let image = generate_captcha("raidpro", &mut ChaCha8Rng::seed_from_u64(42));
+
+    assert_eq!(image_hash(&image), 8099269341780066639);
+}
@@ -0,0 +1,257 @@
+//! Cache backends.
+//!
+//! The [`CacheClient`] stores its values in a [`CacheBackend`], which provides
+//! the key-value operations used by the high-level methods of the client. The
+//! [`RedisBackend`] is used by [`CacheClient::connect`], and the in-memory
+//! backend of the `memory` module replaces it in tests (with the `test-util`
+//! feature).
+//!
+//! Raw connections, channel subscriptions, streams and cache reports are only
+//! available with the [`RedisBackend`] (see [`CacheBackend::redis`]).
+//!
+//! [`CacheClient`]: super::CacheClient
+//! [`CacheClient::connect`]: super::CacheClient::connect
+
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use bb8::Pool;
+use bb8_redis::RedisConnectionManager;
+use redis::AsyncCommands;
+
+use super::RedisConnection;
+
+/// Key-value store used by the [`CacheClient`].
+///
+/// See the [module documentation](self) for more information.
+///
+/// [`CacheClient`]: super::CacheClient
+#[async_trait]
+pub trait CacheBackend: Debug + Send + Sync {
+    /// Get the value of a key.
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, anyhow::Error>;
+
+    /// Get the values of multiple keys, in the same order.
+    async fn get_many(&self, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>, anyhow::Error>;
+
+    /// Set the value of a key, with an optional expiration (in seconds).
+    async fn set(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        expires_after: Option<usize>,
+    ) -> Result<(), anyhow::Error>;
+
+    /// Delete a key.
+    ///
+    /// Returns whether the key existed.
+    async fn delete(&self, key: &str) -> Result<bool, anyhow::Error>;
+
+    /// Get the values of the keys matching a pattern.
+    async fn scan(&self, pattern: &str) -> Result<Vec<Vec<u8>>, anyhow::Error>;
+
+    /// Increment a counter, created with an expiration (in seconds).
+    ///
+    /// Returns the incremented value and the remaining time before the
+    /// counter expires (in seconds).
+    async fn increment(&self, key: &str, expires_after: usize)
+        -> Result<(u64, u64), anyhow::Error>;
+
+    /// Set the value of a key with an expiration if it does not exist.
+    ///
+    /// Returns whether the value has been set.
+    async fn set_nx(
+        &self,
+        key: &str,
+        value: &[u8],
+        expires_after: usize,
+    ) -> Result<bool, anyhow::Error>;
+
+    /// Set the expiration of a key if its value is `value`.
+    ///
+    /// Returns whether the expiration has been set.
+    async fn expire_if(
+        &self,
+        key: &str,
+        value: &[u8],
+        expires_after: usize,
+    ) -> Result<bool, anyhow::Error>;
+
+    /// Delete a key if its value is `value`.
+    async fn delete_if(&self, key: &str, value: &[u8]) -> Result<(), anyhow::Error>;
+
+    /// Publish a message on a channel.
+    async fn publish(&self, channel: &str, message: Vec<u8>) -> Result<(), anyhow::Error>;
+
+    /// Returns the underlying [`RedisBackend`], if any.
+    ///
+    /// This is used by the Redis-specific methods of the client.
+    fn redis(&self) -> Option<&RedisBackend> {
+        None
+    }
+}
+
+/// Cache backend using a Redis connection pool.
+#[derive(Debug, Clone)]
+pub struct RedisBackend {
+    /// Internal connection pool.
+    pool: Pool<RedisConnectionManager>,
+    /// Redis client used to open dedicated connections.
+    client: redis::Client,
+}
+
+impl RedisBackend {
+    /// Initialize a new [`RedisBackend`].
+    pub fn new(pool: Pool<RedisConnectionManager>, client: redis::Client) -> Self {
+        Self { pool, client }
+    }
+
+    /// Returns a new [`RedisConnection`] from the pool.
+    pub async fn conn(&self) -> Result<RedisConnection<'_>, anyhow::Error> {
+        Ok(self.pool.get().await?)
+    }
+
+    /// Open a dedicated connection, outside of the pool.
+    pub async fn dedicated_conn(&self) -> Result<redis::aio::Connection, anyhow::Error> {
+        Ok(self.client.get_async_connection().await?)
+    }
+}
+
+#[async_trait]
+impl CacheBackend for RedisBackend {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        Ok(self.conn().await?.get(key).await?)
+    }
+
+    async fn get_many(&self, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>, anyhow::Error> {
+        Ok(redis::cmd("MGET")
+            .arg(keys)
+            .query_async(&mut *self.conn().await?)
+            .await?)
+    }
+
+    async fn set(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        expires_after: Option<usize>,
+    ) -> Result<(), anyhow::Error> {
+        let mut conn = self.conn().await?;
+
+        match expires_after {
+            Some(expires_after) => conn.set_ex(key, value, expires_after).await?,
+            None => conn.set(key, value).await?,
+        }
+
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, anyhow::Error> {
+        let deleted: u64 = self.conn().await?.del(key).await?;
+
+        Ok(deleted > 0)
+    }
+
+    async fn scan(&self, pattern: &str) -> Result<Vec<Vec<u8>>, anyhow::Error> {
+        let mut conn = self.conn().await?;
+
+        let mut keys = Vec::new();
+        let mut iter = conn.scan_match::<_, String>(pattern).await?;
+        while let Some(key) = iter.next_item().await {
+            keys.push(key);
+        }
+        drop(iter);
+
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            let value: Option<_> = conn.get(&key).await?;
+            values.extend(value);
+        }
+
+        Ok(values)
+    }
+
+    async fn increment(
+        &self,
+        key: &str,
+        expires_after: usize,
+    ) -> Result<(u64, u64), anyhow::Error> {
+        let counter: (u64, u64) = redis::pipe()
+            .atomic()
+            .cmd("SET")
+            .arg(key)
+            .arg(0)
+            .arg("EX")
+            .arg(expires_after)
+            .arg("NX")
+            .ignore()
+            .incr(key, 1)
+            .ttl(key)
+            .query_async(&mut *self.conn().await?)
+            .await?;
+
+        Ok(counter)
+    }
+
+    async fn set_nx(
+        &self,
+        key: &str,
+        value: &[u8],
+        expires_after: usize,
+    ) -> Result<bool, anyhow::Error> {
+        let reply: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(value)
+            .arg("EX")
+            .arg(expires_after)
+            .arg("NX")
+            .query_async(&mut *self.conn().await?)
+            .await?;
+
+        Ok(reply.is_some())
+    }
+
+    async fn expire_if(
+        &self,
+        key: &str,
+        value: &[u8],
+        expires_after: usize,
+    ) -> Result<bool, anyhow::Error> {
+        const SCRIPT: &str = "if redis.call('GET', KEYS[1]) == ARGV[1] then \
+            return redis.call('EXPIRE', KEYS[1], ARGV[2]) else return 0 end";
+
+        let renewed: u64 = redis::cmd("EVAL")
+            .arg(SCRIPT)
+            .arg(1)
+            .arg(key)
+            .arg(value)
+            .arg(expires_after)
+            .query_async(&mut *self.conn().await?)
+            .await?;
+
+        Ok(renewed == 1)
+    }
+
+    async fn delete_if(&self, key: &str, value: &[u8]) -> Result<(), anyhow::Error> {
+        const SCRIPT: &str = "if redis.call('GET', KEYS[1]) == ARGV[1] then \
+            return redis.call('DEL', KEYS[1]) else return 0 end";
+
+        redis::cmd("EVAL")
+            .arg(SCRIPT)
+            .arg(1)
+            .arg(key)
+            .arg(value)
+            .query_async::<_, ()>(&mut *self.conn().await?)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn publish(&self, channel: &str, message: Vec<u8>) -> Result<(), anyhow::Error> {
+        Ok(self.conn().await?.publish(channel, message).await?)
+    }
+
+    fn redis(&self) -> Option<&RedisBackend> {
+        Some(self)
+    }
+}
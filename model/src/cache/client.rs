@@ -3,35 +3,57 @@
 //! This module expose the [`CacheClient`] type used to access the cache stored
 //! in Redis.
 
-use std::{fmt::Debug, time::Duration};
+use std::{
+    any::type_name, borrow::Borrow, cmp::Reverse, collections::HashMap, fmt::Debug, sync::Arc,
+    time::Duration,
+};
 
 use anyhow::Context;
 use bb8::{Pool, PooledConnection};
 use bb8_redis::RedisConnectionManager;
+use futures_util::{Stream, StreamExt};
 use redis::AsyncCommands;
 use serde::{de::DeserializeOwned, Serialize};
 use tracing::{instrument, trace};
 
+use super::{
+    backend::{CacheBackend, RedisBackend},
+    stats::{key_group, CacheReport, CacheStats, KeyGroupStats},
+    CacheTtl,
+};
+
+/// Maximum number of keys sampled per group to estimate memory usage.
+const MEMORY_SAMPLES: usize = 16;
+
 /// Alias for Redis connection type.
 pub type RedisConnection<'a> = PooledConnection<'a, RedisConnectionManager>;
 
-/// Wrapper around a Redis connection pool.
+/// Client of the cache.
 ///
-/// This type wraps an underlying Redis connection pool and exposes high-level
-/// methods to access data stored in the cache.
+/// This type wraps an underlying [`CacheBackend`] (usually Redis) and exposes
+/// high-level methods to access data stored in the cache.
 ///
-/// It can be cheaply cloned because the underlying [`Pool`] uses [`Arc`].
+/// It can be cheaply cloned because the backend is stored in an [`Arc`].
 ///
-/// [`Arc`]: std::sync::Arc
+/// Reads and writes of each model are counted in [`CacheStats`].
 #[derive(Debug, Clone)]
 pub struct CacheClient {
-    /// Internal connection pool.
-    pool: Pool<RedisConnectionManager>,
+    /// Storage backend of the cache.
+    backend: Arc<dyn CacheBackend>,
+    /// Expiration delays of cached models.
+    ttl: Arc<CacheTtl>,
+    /// Cache usage counters.
+    stats: Arc<CacheStats>,
 }
 
 impl CacheClient {
     /// Connects to Redis and returns the client.
-    pub async fn connect(uri: &str) -> Result<Self, anyhow::Error> {
+    ///
+    /// The expiration delays in `ttl` are applied to every value written in
+    /// the cache. Cached data of previous versions is removed (see
+    /// [`migrate`](super::migrate)).
+    pub async fn connect(uri: &str, ttl: CacheTtl) -> Result<Self, anyhow::Error> {
+        let client = redis::Client::open(uri).context("failed to initialize redis client")?;
         let manager =
             RedisConnectionManager::new(uri).context("failed to initialize connection manager")?;
 
@@ -41,17 +63,57 @@ impl CacheClient {
             .await
             .context("failed to initialize connection pool")?;
 
-        Ok(Self { pool })
+        let mut conn = pool.get().await.context("failed to connect to redis")?;
+        super::migrate::migrate(&mut conn)
+            .await
+            .context("failed to migrate cache")?;
+        drop(conn);
+
+        Ok(Self::with_backend(RedisBackend::new(pool, client), ttl))
+    }
+
+    /// Create a client using a custom [`CacheBackend`].
+    ///
+    /// This is used in tests with the in-memory backend of the `memory`
+    /// module (with the `test-util` feature).
+    pub fn with_backend(backend: impl CacheBackend + 'static, ttl: CacheTtl) -> Self {
+        Self {
+            backend: Arc::new(backend),
+            ttl: Arc::new(ttl),
+            stats: Arc::new(CacheStats::default()),
+        }
+    }
+
+    /// Returns the Redis backend, or an error if another backend is used.
+    fn redis(&self) -> Result<&RedisBackend, anyhow::Error> {
+        self.backend
+            .redis()
+            .context("operation only supported by the redis cache backend")
+    }
+
+    /// Returns the expiration delays of cached models.
+    pub fn ttl(&self) -> &CacheTtl {
+        &self.ttl
+    }
+
+    /// Returns the cache usage counters.
+    pub fn stats(&self) -> &CacheStats {
+        &self.stats
     }
 
     /// Returns a new [`RedisConnection`] from the pool.
     pub async fn conn(&self) -> Result<RedisConnection<'_>, anyhow::Error> {
-        Ok(self.pool.get().await?)
+        self.redis()?.conn().await
     }
 
     /// Run a `PING` command to check if the cache is connected.
     pub async fn ping(&self) -> Result<(), anyhow::Error> {
-        let mut conn = self.conn().await?;
+        let redis = match self.backend.redis() {
+            Some(redis) => redis,
+            None => return Ok(()),
+        };
+
+        let mut conn = redis.conn().await?;
         redis::cmd("PING").query_async(&mut *conn).await?;
 
         Ok(())
@@ -60,28 +122,76 @@ impl CacheClient {
     /// Get a value from the cache.
     #[instrument(skip(self))]
     pub async fn get<T: RedisModel>(&self, id: &T::Id) -> Result<Option<T>, anyhow::Error> {
-        let mut conn = self.conn().await?;
         let key = T::key_from(id);
 
         trace!("getting value for key {}", key);
-        let value: Option<_> = conn.get(&key).await?;
+        let value = self.backend.get(&key).await?;
+
+        match value {
+            Some(_) => self.stats.record_reads::<T>(1, 0),
+            None => self.stats.record_reads::<T>(0, 1),
+        }
 
         value.map(RedisModel::deserialize_model).transpose()
     }
 
+    /// Get multiple values from the cache.
+    ///
+    /// The values are queried with a single `MGET` command. The returned
+    /// [`Vec`] has the same order as `ids`, with [`None`] for missing values.
+    #[instrument(skip(self))]
+    pub async fn get_many<T: RedisModel>(
+        &self,
+        ids: &[impl Borrow<T::Id> + Debug],
+    ) -> Result<Vec<Option<T>>, anyhow::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::new()); // MGET requires at least one key.
+        }
+
+        let keys: Vec<_> = ids.iter().map(|id| T::key_from(id.borrow())).collect();
+
+        trace!("getting values for {} keys", keys.len());
+        let values = self.backend.get_many(&keys).await?;
+
+        let hits = values.iter().filter(|value| value.is_some()).count() as u64;
+        self.stats
+            .record_reads::<T>(hits, values.len() as u64 - hits);
+
+        values
+            .into_iter()
+            .map(|value| value.map(RedisModel::deserialize_model).transpose())
+            .collect()
+    }
+
     /// Set a value in the cache.
     #[instrument(skip(self))]
     pub async fn set<T: RedisModel>(&self, value: &T) -> Result<(), anyhow::Error> {
-        let mut conn = self.conn().await?;
         let key = value.key();
 
         trace!(value = ?value, "setting value for key {}", key);
-        if let Some(expires_after) = T::EXPIRES_AFTER {
-            conn.set_ex(value.key(), value.serialize_model()?, expires_after)
-                .await?;
-        } else {
-            conn.set(value.key(), value.serialize_model()?).await?;
-        }
+        self.backend
+            .set(
+                &key,
+                value.serialize_model()?,
+                self.ttl.expires_after::<T>(),
+            )
+            .await?;
+        self.stats.record_insert::<T>();
+
+        Ok(())
+    }
+
+    /// Add a command to set a value to a pipeline.
+    ///
+    /// This is the equivalent of [`set`](Self::set) for values written with a
+    /// [`Pipeline`](redis::Pipeline).
+    pub fn pipe_set<T: RedisModel>(
+        &self,
+        pipe: &mut redis::Pipeline,
+        value: &T,
+    ) -> Result<(), anyhow::Error> {
+        self.ttl.pipe_set(pipe, value)?;
+        self.stats.record_insert::<T>();
 
         Ok(())
     }
@@ -89,14 +199,307 @@ impl CacheClient {
     /// Delete a value from the cache.
     #[instrument(skip(self))]
     pub async fn delete<T: RedisModel>(&self, value: &T) -> Result<(), anyhow::Error> {
-        let mut conn = self.conn().await?;
         let key = value.key();
 
         trace!("deleting value for key {}", key);
-        conn.del(key).await?;
+        self.backend.delete(&key).await?;
+        self.stats.record_eviction::<T>();
 
         Ok(())
     }
+
+    /// Delete a value from the cache with its id.
+    ///
+    /// This is the equivalent of [`delete`](Self::delete) when the value is
+    /// not known. Returns whether the value existed, which can be used to
+    /// consume a value only once.
+    #[instrument(skip(self))]
+    pub async fn delete_from<T: RedisModel>(&self, id: &T::Id) -> Result<bool, anyhow::Error> {
+        let key = T::key_from(id);
+
+        trace!("deleting value for key {}", key);
+        let deleted = self.backend.delete(&key).await?;
+        self.stats.record_eviction::<T>();
+
+        Ok(deleted)
+    }
+
+    /// Get all the values with a key matching a pattern.
+    ///
+    /// The keys are listed with the `SCAN` command, which iterates over all the
+    /// keys of the database. This should only be used by background tasks.
+    #[instrument(skip(self))]
+    pub async fn scan<T: RedisModel>(&self, pattern: &str) -> Result<Vec<T>, anyhow::Error> {
+        let values = self.backend.scan(pattern).await?;
+        trace!("scanned {} keys for pattern {}", values.len(), pattern);
+
+        values.into_iter().map(T::deserialize_model).collect()
+    }
+
+    /// Generate a report of the cache state.
+    ///
+    /// The keys of the database are listed with the `SCAN` command, and the
+    /// memory usage of each key group is estimated from a sample of keys. This
+    /// should only be used for debugging.
+    #[instrument(skip(self))]
+    pub async fn report(&self) -> Result<CacheReport, anyhow::Error> {
+        let redis = match self.backend.redis() {
+            Some(redis) => redis,
+            None => {
+                return Ok(CacheReport {
+                    models: self.stats.models(),
+                    keys: Vec::new(),
+                    used_memory: None,
+                })
+            }
+        };
+
+        let mut conn = redis.conn().await?;
+
+        let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+        let mut iter = conn.scan::<String>().await?;
+        while let Some(key) = iter.next_item().await {
+            groups.entry(key_group(&key)).or_default().push(key);
+        }
+        drop(iter);
+
+        let mut keys = Vec::with_capacity(groups.len());
+        for (group, group_keys) in groups {
+            let samples = &group_keys[..group_keys.len().min(MEMORY_SAMPLES)];
+
+            let mut sampled = 0;
+            for key in samples {
+                let usage: Option<u64> = redis::cmd("MEMORY")
+                    .arg("USAGE")
+                    .arg(key)
+                    .query_async(&mut *conn)
+                    .await?;
+                sampled += usage.unwrap_or_default();
+            }
+
+            let count = group_keys.len() as u64;
+            keys.push(KeyGroupStats {
+                group,
+                count,
+                memory: sampled * count / samples.len() as u64,
+            });
+        }
+        keys.sort_by_key(|group| Reverse(group.memory));
+
+        let info: String = redis::cmd("INFO")
+            .arg("memory")
+            .query_async(&mut *conn)
+            .await?;
+        let used_memory = info
+            .lines()
+            .find_map(|line| line.strip_prefix("used_memory:"))
+            .and_then(|value| value.trim().parse().ok());
+
+        Ok(CacheReport {
+            models: self.stats.models(),
+            keys,
+            used_memory,
+        })
+    }
+
+    /// Increment a counter.
+    ///
+    /// The counter is created with an expiration of `expires_after` seconds,
+    /// which is not extended by further increments. Returns the incremented
+    /// value and the remaining time before the counter expires (in seconds).
+    #[instrument(skip(self))]
+    pub async fn increment(
+        &self,
+        key: &str,
+        expires_after: usize,
+    ) -> Result<(u64, u64), anyhow::Error> {
+        self.backend.increment(key, expires_after).await
+    }
+
+    /// Acquire a lease.
+    ///
+    /// The lease is stored at `key` with the id of its `owner`, and expires
+    /// after `expires_after` seconds unless renewed with [`renew_lease`].
+    /// Returns `false` if the lease is held by another owner.
+    ///
+    /// [`renew_lease`]: Self::renew_lease
+    #[instrument(skip(self))]
+    pub async fn acquire_lease(
+        &self,
+        key: &str,
+        owner: &str,
+        expires_after: usize,
+    ) -> Result<bool, anyhow::Error> {
+        self.backend
+            .set_nx(key, owner.as_bytes(), expires_after)
+            .await
+    }
+
+    /// Renew a lease acquired with [`acquire_lease`].
+    ///
+    /// Returns `false` if the lease expired or is held by another owner.
+    ///
+    /// [`acquire_lease`]: Self::acquire_lease
+    #[instrument(skip(self))]
+    pub async fn renew_lease(
+        &self,
+        key: &str,
+        owner: &str,
+        expires_after: usize,
+    ) -> Result<bool, anyhow::Error> {
+        self.backend
+            .expire_if(key, owner.as_bytes(), expires_after)
+            .await
+    }
+
+    /// Release a lease acquired with [`acquire_lease`].
+    ///
+    /// Nothing is done if the lease is held by another owner.
+    ///
+    /// [`acquire_lease`]: Self::acquire_lease
+    #[instrument(skip(self))]
+    pub async fn release_lease(&self, key: &str, owner: &str) -> Result<(), anyhow::Error> {
+        self.backend.delete_if(key, owner.as_bytes()).await
+    }
+
+    /// Publish a message on a Redis channel.
+    ///
+    /// The message is serialized in MessagePack using [`rmp_serde`].
+    #[instrument(skip(self))]
+    pub async fn publish<T: Serialize + Debug>(
+        &self,
+        channel: &str,
+        message: &T,
+    ) -> Result<(), anyhow::Error> {
+        trace!(message = ?message, "publishing message on channel {}", channel);
+        self.backend
+            .publish(channel, rmp_serde::to_vec_named(message)?)
+            .await
+    }
+
+    /// Subscribe to a Redis channel.
+    ///
+    /// The returned stream yields the messages published with [`publish`]. A
+    /// dedicated connection is opened since Redis connections cannot be used
+    /// for other commands while subscribed to a channel.
+    ///
+    /// [`publish`]: Self::publish
+    pub async fn subscribe<T: DeserializeOwned>(
+        &self,
+        channel: &str,
+    ) -> Result<impl Stream<Item = Result<T, anyhow::Error>>, anyhow::Error> {
+        let mut pubsub = self.redis()?.dedicated_conn().await?.into_pubsub();
+        pubsub.subscribe(channel).await?;
+
+        let stream = pubsub.into_on_message().map(|message| {
+            let payload: Vec<u8> = message.get_payload()?;
+
+            Ok(rmp_serde::from_slice(&payload)?)
+        });
+
+        Ok(stream)
+    }
+
+    /// Append entries to a Redis stream.
+    ///
+    /// Each entry is a list of fields. The stream is trimmed to approximately
+    /// `max_len` entries, and expires `expires_after` seconds after the last
+    /// appended entry.
+    #[instrument(skip(self, entries))]
+    pub async fn append_stream(
+        &self,
+        key: &str,
+        entries: &[Vec<(&str, Vec<u8>)>],
+        max_len: usize,
+        expires_after: usize,
+    ) -> Result<(), anyhow::Error> {
+        let mut conn = self.conn().await?;
+        let mut pipe = redis::pipe();
+
+        for fields in entries {
+            pipe.cmd("XADD")
+                .arg(key)
+                .arg("MAXLEN")
+                .arg("~")
+                .arg(max_len)
+                .arg("*");
+
+            for (field, value) in fields {
+                pipe.arg(*field).arg(&value[..]);
+            }
+            pipe.ignore();
+        }
+        pipe.expire(key, expires_after).ignore();
+
+        trace!("appending {} entries to stream {}", entries.len(), key);
+        pipe.query_async::<_, ()>(&mut *conn).await?;
+
+        Ok(())
+    }
+
+    /// Read the entries appended to a Redis stream.
+    ///
+    /// The returned [`StreamReader`] reads the entries appended after its
+    /// creation. Like with [`subscribe`], a dedicated connection is opened
+    /// since reads block until new entries are appended.
+    ///
+    /// [`subscribe`]: Self::subscribe
+    pub async fn stream_reader(&self, key: &str) -> Result<StreamReader, anyhow::Error> {
+        let conn = self.redis()?.dedicated_conn().await?;
+
+        Ok(StreamReader {
+            conn,
+            key: key.to_owned(),
+            last_id: "$".to_owned(),
+        })
+    }
+}
+
+/// Reader of a Redis stream.
+///
+/// Created with [`CacheClient::stream_reader`].
+pub struct StreamReader {
+    conn: redis::aio::Connection,
+    key: String,
+    /// Id of the last read entry.
+    last_id: String,
+}
+
+impl StreamReader {
+    /// Wait for new entries, for at most `timeout`.
+    ///
+    /// Returns the id and the value of `field` of each new entry. Entries
+    /// without this field are skipped. The connection must not be used again
+    /// if the returned future is dropped before completion.
+    pub async fn next_entries(
+        &mut self,
+        field: &str,
+        timeout: Duration,
+    ) -> Result<Vec<(String, Vec<u8>)>, anyhow::Error> {
+        type Reply = Option<Vec<(String, Vec<(String, HashMap<String, Vec<u8>>)>)>>;
+
+        let reply: Reply = redis::cmd("XREAD")
+            .arg("BLOCK")
+            .arg(timeout.as_millis() as u64)
+            .arg("STREAMS")
+            .arg(&self.key)
+            .arg(&self.last_id)
+            .query_async(&mut self.conn)
+            .await?;
+
+        let mut values = Vec::new();
+        for (_, entries) in reply.into_iter().flatten() {
+            for (id, mut fields) in entries {
+                self.last_id = id.clone();
+
+                if let Some(value) = fields.remove(field) {
+                    values.push((id, value));
+                }
+            }
+        }
+
+        Ok(values)
+    }
 }
 
 /// Type representing a model stored in the cache.
@@ -109,9 +512,20 @@ pub trait RedisModel: Debug + Serialize + DeserializeOwned {
 
     /// Default key expiration delay.
     ///
-    /// If set to `None`, the key never expires.
+    /// If set to `None`, the key never expires. This delay can be overridden
+    /// with the configuration, see [`CacheTtl`].
     const EXPIRES_AFTER: Option<usize> = None;
 
+    /// Name of the model used in the configuration.
+    ///
+    /// The default implementation returns the name of the type without its
+    /// module path.
+    fn name() -> &'static str {
+        let name = type_name::<Self>();
+
+        name.rsplit("::").next().unwrap_or(name)
+    }
+
     /// Get the current value key.
     fn key(&self) -> String;
 
@@ -1,13 +1,21 @@
 //! Methods to query Discord objects in the cache for [`CacheClient`].
 
+use time::OffsetDateTime;
 use tracing::{instrument, trace};
 use twilight_http::Client as HttpClient;
-use twilight_model::id::{marker::GuildMarker, Id};
+use twilight_model::{
+    guild::Permissions,
+    id::{
+        marker::{ApplicationMarker, GuildMarker},
+        Id,
+    },
+};
 
 use super::{
-    http::CacheHttp, permission::GuildPermissions, CachedChannel, CachedGuild, CachedRole,
+    http::CacheHttp, permission::GuildPermissions, process::resource, CachedChannel, CachedGuild,
+    CachedInvite, CachedInvites, CachedRole,
 };
-use crate::cache::{CacheClient, RedisModel};
+use crate::cache::CacheClient;
 
 impl CacheClient {
     /// Get a [`GuildPermissions`] for a given guild.
@@ -26,6 +34,83 @@ impl CacheClient {
         CacheHttp::new(self, http, guild_id)
     }
 
+    /// Fetch a guild from the Discord API and insert it into the cache.
+    ///
+    /// This is used when a guild is missing from the cache, for example when an
+    /// interaction is received after a restart before the guild has been sent
+    /// by the gateway. The guild, its channels, its roles and the bot member
+    /// are fetched and cached like with a `GuildCreate` event.
+    #[instrument(skip(self, http))]
+    pub async fn hydrate_guild(
+        &self,
+        http: &HttpClient,
+        guild_id: Id<GuildMarker>,
+        current_user: Id<ApplicationMarker>,
+    ) -> Result<(), anyhow::Error> {
+        let mut guild = http.guild(guild_id).exec().await?.model().await?;
+        guild.channels = http.guild_channels(guild_id).exec().await?.models().await?;
+
+        let current_member = http
+            .guild_member(guild_id, current_user.cast())
+            .exec()
+            .await?
+            .model()
+            .await?;
+        guild.members = vec![current_member];
+
+        trace!(
+            channels = guild.channels.len(),
+            roles = guild.roles.len(),
+            "hydrating guild {}",
+            guild_id
+        );
+
+        let mut pipe = redis::pipe();
+        resource::cache_guild(&mut pipe, self, current_user, &guild)?;
+
+        let mut conn = self.conn().await?;
+        pipe.query_async(&mut *conn).await?;
+
+        Ok(())
+    }
+
+    /// Fetch the invites of a guild from the Discord API and update the cache.
+    ///
+    /// If the invites were already cached, the invite used by a member that
+    /// joined since the last update is returned (see
+    /// [`CachedInvites::find_used`]). Invites cannot be fetched without the
+    /// `MANAGE_GUILD` permission, in which case the cache is not updated.
+    #[instrument(skip(self, http))]
+    pub async fn update_invites(
+        &self,
+        http: &HttpClient,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Option<CachedInvite>, anyhow::Error> {
+        let permissions = self.permissions(guild_id).await?.current_member().await?;
+
+        if !permissions.guild().contains(Permissions::MANAGE_GUILD) {
+            trace!("missing permission to fetch invites of guild {}", guild_id);
+            return Ok(None);
+        }
+
+        let invites = http.guild_invites(guild_id).exec().await?.models().await?;
+        let invites: Vec<_> = invites.iter().map(CachedInvite::from).collect();
+
+        let used = self
+            .get::<CachedInvites>(&guild_id)
+            .await?
+            .and_then(|cached| cached.find_used(&invites));
+
+        let cached = CachedInvites {
+            guild_id,
+            invites,
+            updated_at: OffsetDateTime::now_utc(),
+        };
+        self.set(&cached).await?;
+
+        Ok(used)
+    }
+
     /// Get all the [`CachedChannel`] of a guild.
     ///
     /// If the guild is not cached, an empty [`Vec`] is returned.
@@ -34,30 +119,16 @@ impl CacheClient {
         &self,
         id: Id<GuildMarker>,
     ) -> Result<Vec<CachedChannel>, anyhow::Error> {
-        let guild = self.get::<CachedGuild>(&id).await?;
-
-        if let Some(guild) = guild {
-            let mut conn = self.conn().await?;
-            let mut pipe = redis::pipe();
-
-            trace!(
-                channels = ?guild.channels,
-                "querying channels for guild {}",
-                id
-            );
-            for channel in &guild.channels {
-                pipe.get(CachedChannel::key_from(channel));
-            }
-
-            let value: Vec<_> = pipe.query_async(&mut *conn).await?;
-
-            value
-                .into_iter()
-                .map(RedisModel::deserialize_model)
-                .collect()
-        } else {
-            Ok(Vec::new())
-        }
+        let guild = match self.get::<CachedGuild>(&id).await? {
+            Some(guild) => guild,
+            None => return Ok(Vec::new()),
+        };
+
+        trace!(channels = ?guild.channels, "querying channels for guild {}", id);
+        let channels: Vec<_> = guild.channels.into_iter().collect();
+        let channels = self.get_many::<CachedChannel>(&channels).await?;
+
+        Ok(channels.into_iter().flatten().collect())
     }
 
     /// Get all the [`CachedRole`] of a guild.
@@ -65,25 +136,15 @@ impl CacheClient {
     /// If the guild is not cached, an empty [`Vec`] is returned.
     #[instrument(skip(self))]
     pub async fn guild_roles(&self, id: Id<GuildMarker>) -> Result<Vec<CachedRole>, anyhow::Error> {
-        let guild = self.get::<CachedGuild>(&id).await?;
+        let guild = match self.get::<CachedGuild>(&id).await? {
+            Some(guild) => guild,
+            None => return Ok(Vec::new()),
+        };
 
-        if let Some(guild) = guild {
-            let mut conn = self.conn().await?;
-            let mut pipe = redis::pipe();
+        trace!(roles = ?guild.roles, "querying roles for guild {}", id);
+        let roles: Vec<_> = guild.roles.into_iter().collect();
+        let roles = self.get_many::<CachedRole>(&roles).await?;
 
-            trace!(roles = ?guild.roles, "querying roles for guild {}", id);
-            for role in &guild.roles {
-                pipe.get(CachedRole::key_from(role));
-            }
-
-            let value: Vec<_> = pipe.query_async(&mut *conn).await?;
-
-            value
-                .into_iter()
-                .map(RedisModel::deserialize_model)
-                .collect()
-        } else {
-            Ok(Vec::new())
-        }
+        Ok(roles.into_iter().flatten().collect())
     }
 }
@@ -2,13 +2,26 @@
 //!
 //! This module exports wrapper around twilight's HTTP client that use the cache
 //! data to check permissions before making requests.
+//!
+//! It also provides getters for cached models. When a model is missing from the
+//! cache, for example if it has not been sent by the gateway since a restart,
+//! it is fetched from the Discord API and inserted into the cache. This can be
+//! disabled with [`CacheHttp::cache_only`].
 
 use anyhow::anyhow;
+use tracing::trace;
 use twilight_http::{
+    error::{Error as HttpError, ErrorType},
     request::{
-        channel::{message::CreateMessage, UpdateChannelPermission},
+        channel::{
+            message::{CreateMessage, DeleteMessage},
+            webhook::DeleteWebhook,
+            UpdateChannel, UpdateChannelPermission,
+        },
         guild::{
-            member::{AddRoleToMember, RemoveMember},
+            ban::{CreateBan, DeleteBan},
+            member::{AddRoleToMember, RemoveMember, UpdateGuildMember},
+            role::UpdateRole,
             CreateGuildChannel,
         },
     },
@@ -18,12 +31,17 @@ use twilight_model::{
     guild::Permissions,
     http::permission_overwrite::PermissionOverwrite,
     id::{
-        marker::{ChannelMarker, GuildMarker, RoleMarker, UserMarker},
+        marker::{
+            ChannelMarker, GuildMarker, MessageMarker, RoleMarker, UserMarker, WebhookMarker,
+        },
         Id,
     },
 };
 
-use super::{permission::RoleOrdering, CachedRole};
+use super::{
+    permission::RoleOrdering, process::resource, CachedChannel, CachedGuild, CachedMember,
+    CachedRole,
+};
 use crate::cache::CacheClient;
 
 /// HTTP client with permission checks.
@@ -32,6 +50,7 @@ pub struct CacheHttp<'a> {
     cache: &'a CacheClient,
     http: &'a HttpClient,
     guild_id: Id<GuildMarker>,
+    fetch: bool,
 }
 
 impl<'a> CacheHttp<'a> {
@@ -45,7 +64,138 @@ impl<'a> CacheHttp<'a> {
             cache,
             http,
             guild_id,
+            fetch: true,
+        }
+    }
+
+    /// Only query the cache, without fetching missing models from the API.
+    pub fn cache_only(mut self) -> Self {
+        self.fetch = false;
+        self
+    }
+
+    /// Get a member of the guild.
+    ///
+    /// If the member is not in the cache, it is fetched from the API. [`None`]
+    /// is returned if the user is not a member of the guild.
+    pub async fn member(
+        &self,
+        user_id: Id<UserMarker>,
+    ) -> Result<Option<CachedMember>, anyhow::Error> {
+        if let Some(member) = self
+            .cache
+            .get::<CachedMember>(&(self.guild_id, user_id))
+            .await?
+        {
+            return Ok(Some(member));
+        }
+
+        if !self.fetch {
+            return Ok(None);
+        }
+
+        trace!("fetching member {} of guild {}", user_id, self.guild_id);
+        let member = match self.http.guild_member(self.guild_id, user_id).exec().await {
+            Ok(response) => response.model().await?,
+            Err(error) if is_not_found(&error) => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut pipe = redis::pipe();
+        let cached = resource::cache_member(&mut pipe, self.cache, &member)?;
+        let mut conn = self.cache.conn().await?;
+        pipe.query_async(&mut *conn).await?;
+
+        Ok(Some(cached))
+    }
+
+    /// Get a channel of the guild.
+    ///
+    /// If the channel is not in the cache, it is fetched from the API. [`None`]
+    /// is returned if the channel does not exist, belongs to another guild or
+    /// is not of a cached type.
+    pub async fn channel(
+        &self,
+        channel_id: Id<ChannelMarker>,
+    ) -> Result<Option<CachedChannel>, anyhow::Error> {
+        if let Some(channel) = self.cache.get::<CachedChannel>(&channel_id).await? {
+            return Ok(Some(channel));
+        }
+
+        if !self.fetch {
+            return Ok(None);
         }
+
+        trace!("fetching channel {} of guild {}", channel_id, self.guild_id);
+        let channel = match self.http.channel(channel_id).exec().await {
+            Ok(response) => response.model().await?,
+            Err(error) if is_not_found(&error) => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+
+        if channel.guild_id != Some(self.guild_id) {
+            return Ok(None);
+        }
+
+        let mut pipe = redis::pipe();
+        let cached = match resource::cache_guild_channel(&mut pipe, self.cache, &channel)? {
+            Some(cached) => cached,
+            None => return Ok(None),
+        };
+
+        if let Some(mut guild) = self.cache.get::<CachedGuild>(&self.guild_id).await? {
+            guild.channels.insert(channel_id);
+            self.cache.pipe_set(&mut pipe, &guild)?;
+        }
+
+        let mut conn = self.cache.conn().await?;
+        pipe.query_async(&mut *conn).await?;
+
+        Ok(Some(cached))
+    }
+
+    /// Get a role of the guild.
+    ///
+    /// If the role is not in the cache, all the roles of the guild are fetched
+    /// from the API. [`None`] is returned if the role does not exist.
+    pub async fn role(&self, role_id: Id<RoleMarker>) -> Result<Option<CachedRole>, anyhow::Error> {
+        if let Some(role) = self.cache.get::<CachedRole>(&role_id).await? {
+            return Ok(Some(role));
+        }
+
+        if !self.fetch {
+            return Ok(None);
+        }
+
+        trace!("fetching roles of guild {}", self.guild_id);
+        let roles = self
+            .http
+            .roles(self.guild_id)
+            .exec()
+            .await?
+            .models()
+            .await?;
+
+        let mut pipe = redis::pipe();
+        let mut found = None;
+
+        for role in &roles {
+            let cached = resource::cache_role(&mut pipe, self.cache, role, self.guild_id)?;
+
+            if cached.id == role_id {
+                found = Some(cached);
+            }
+        }
+
+        if let Some(mut guild) = self.cache.get::<CachedGuild>(&self.guild_id).await? {
+            guild.roles = roles.iter().map(|role| role.id).collect();
+            self.cache.pipe_set(&mut pipe, &guild)?;
+        }
+
+        let mut conn = self.cache.conn().await?;
+        pipe.query_async(&mut *conn).await?;
+
+        Ok(found)
     }
 
     /// Send a message to a channel.
@@ -66,9 +216,9 @@ impl<'a> CacheHttp<'a> {
         let (permissions, kind) = permissions.current_member().await?.channel(channel).await?;
 
         let send_messages = if kind.is_thread() {
-            Permissions::SEND_MESSAGES
-        } else {
             Permissions::SEND_MESSAGES_IN_THREADS
+        } else {
+            Permissions::SEND_MESSAGES
         };
 
         if !permissions
@@ -104,6 +254,32 @@ impl<'a> CacheHttp<'a> {
         Ok(self.http.create_guild_channel(self.guild_id, name)?)
     }
 
+    /// Update a channel, for example to change its slowmode.
+    ///
+    /// This method ensures that the bot has the [`MANAGE_CHANNELS`] permission
+    /// in the channel.
+    ///
+    /// [`MANAGE_CHANNELS`]: Permissions::MANAGE_CHANNELS
+    pub async fn update_channel(
+        &'a self,
+        channel_id: Id<ChannelMarker>,
+    ) -> Result<UpdateChannel<'a>, anyhow::Error> {
+        let (permissions, _) = self
+            .cache
+            .permissions(self.guild_id)
+            .await?
+            .current_member()
+            .await?
+            .channel(channel_id)
+            .await?;
+
+        if !permissions.contains(Permissions::MANAGE_CHANNELS) {
+            return Err(anyhow!("missing permissions to update channel"));
+        }
+
+        Ok(self.http.update_channel(channel_id))
+    }
+
     /// Update a channel's permission overwrite.
     ///
     /// This method ensures that the bot has the [`MANAGE_ROLES`] and
@@ -171,6 +347,39 @@ impl<'a> CacheHttp<'a> {
             .add_guild_member_role(self.guild_id, user_id, role_id))
     }
 
+    /// Update a role of the guild.
+    ///
+    /// This method ensures that the bot has the [`MANAGE_ROLES`] permission and
+    /// the role to update is lower than the bot's highest role.
+    ///
+    /// [`MANAGE_ROLES`]: Permissions::MANAGE_ROLES
+    pub async fn update_role(
+        &'a self,
+        role_id: Id<RoleMarker>,
+    ) -> Result<UpdateRole<'a>, anyhow::Error> {
+        let permissions = self
+            .cache
+            .permissions(self.guild_id)
+            .await?
+            .current_member()
+            .await?;
+
+        if !permissions.guild().contains(Permissions::MANAGE_ROLES) {
+            return Err(anyhow!("missing permissions to update role"));
+        }
+
+        let role = match self.cache.get::<CachedRole>(&role_id).await? {
+            Some(role) => role,
+            None => return Err(anyhow!("role to update not found")),
+        };
+
+        if RoleOrdering::from(&role) >= permissions.highest_role() {
+            return Err(anyhow!("role to update is higher than bot's highest role"));
+        }
+
+        Ok(self.http.update_role(self.guild_id, role_id))
+    }
+
     /// Kick a user from a guild.
     ///
     /// This method ensures that the bot has the [`KICK_MEMBERS`] permission. It
@@ -194,4 +403,134 @@ impl<'a> CacheHttp<'a> {
 
         Ok(self.http.remove_guild_member(self.guild_id, user_id))
     }
+
+    /// Delete a message.
+    ///
+    /// This method ensures that the bot has the [`MANAGE_MESSAGES`] permission
+    /// in the channel.
+    ///
+    /// [`MANAGE_MESSAGES`]: Permissions::MANAGE_MESSAGES
+    pub async fn delete_message(
+        &'a self,
+        channel_id: Id<ChannelMarker>,
+        message_id: Id<MessageMarker>,
+    ) -> Result<DeleteMessage<'a>, anyhow::Error> {
+        let (permissions, _) = self
+            .cache
+            .permissions(self.guild_id)
+            .await?
+            .current_member()
+            .await?
+            .channel(channel_id)
+            .await?;
+
+        if !permissions.contains(Permissions::MANAGE_MESSAGES) {
+            return Err(anyhow!("missing permissions to delete message"));
+        }
+
+        Ok(self.http.delete_message(channel_id, message_id))
+    }
+
+    /// Update a guild member, for example to timeout it.
+    ///
+    /// This method ensures that the bot has the [`MODERATE_MEMBERS`]
+    /// permission. It does not check for the role hierarchy.
+    ///
+    /// [`MODERATE_MEMBERS`]: Permissions::MODERATE_MEMBERS
+    pub async fn update_guild_member(
+        &'a self,
+        user_id: Id<UserMarker>,
+    ) -> Result<UpdateGuildMember<'a>, anyhow::Error> {
+        let permissions = self
+            .cache
+            .permissions(self.guild_id)
+            .await?
+            .current_member()
+            .await?;
+
+        if !permissions.guild().contains(Permissions::MODERATE_MEMBERS) {
+            return Err(anyhow!("missing permissions to update member"));
+        }
+
+        Ok(self.http.update_guild_member(self.guild_id, user_id))
+    }
+
+    /// Ban a user from a guild.
+    ///
+    /// This method ensures that the bot has the [`BAN_MEMBERS`] permission. It
+    /// does not check for the role hierarchy.
+    ///
+    /// [`BAN_MEMBERS`]: Permissions::BAN_MEMBERS
+    pub async fn create_ban(
+        &'a self,
+        user_id: Id<UserMarker>,
+    ) -> Result<CreateBan<'a>, anyhow::Error> {
+        let permissions = self
+            .cache
+            .permissions(self.guild_id)
+            .await?
+            .current_member()
+            .await?;
+
+        if !permissions.guild().contains(Permissions::BAN_MEMBERS) {
+            return Err(anyhow!("missing permissions to ban member"));
+        }
+
+        Ok(self.http.create_ban(self.guild_id, user_id))
+    }
+
+    /// Unban a user from a guild.
+    ///
+    /// This method ensures that the bot has the [`BAN_MEMBERS`] permission.
+    ///
+    /// [`BAN_MEMBERS`]: Permissions::BAN_MEMBERS
+    pub async fn delete_ban(
+        &'a self,
+        user_id: Id<UserMarker>,
+    ) -> Result<DeleteBan<'a>, anyhow::Error> {
+        let permissions = self
+            .cache
+            .permissions(self.guild_id)
+            .await?
+            .current_member()
+            .await?;
+
+        if !permissions.guild().contains(Permissions::BAN_MEMBERS) {
+            return Err(anyhow!("missing permissions to unban member"));
+        }
+
+        Ok(self.http.delete_ban(self.guild_id, user_id))
+    }
+
+    /// Delete a webhook.
+    ///
+    /// This method ensures that the bot has the [`MANAGE_WEBHOOKS`] permission
+    /// in the channel of the webhook.
+    ///
+    /// [`MANAGE_WEBHOOKS`]: Permissions::MANAGE_WEBHOOKS
+    pub async fn delete_webhook(
+        &'a self,
+        channel_id: Id<ChannelMarker>,
+        webhook_id: Id<WebhookMarker>,
+    ) -> Result<DeleteWebhook<'a>, anyhow::Error> {
+        let (permissions, _) = self
+            .cache
+            .permissions(self.guild_id)
+            .await?
+            .current_member()
+            .await?
+            .channel(channel_id)
+            .await?;
+
+        if !permissions.contains(Permissions::MANAGE_WEBHOOKS) {
+            return Err(anyhow!("missing permissions to delete webhook"));
+        }
+
+        Ok(self.http.delete_webhook(webhook_id))
+    }
+}
+
+/// Whether an HTTP error is a `404 Not Found` response.
+pub fn is_not_found(error: &HttpError) -> bool {
+    matches!(error.kind(), ErrorType::Response { status, .. } if status.get() == 404)
 }
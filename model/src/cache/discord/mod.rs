@@ -16,7 +16,9 @@
 //! | Guilds                | `GuildCreate`, `GuildUpdate`, `GuildDelete`, `UnavailableGuild`   |
 //! | Channels (guild-only) | `ChannelCreate`, `ChannelUpdate`, `ChannelUpdate` (+ thread ones) |
 //! | Roles                 | `RoleCreate`, `RoleUpdate`, `RoleDelete`                          |
-//! | Current user member   | `MemberAdd`, `MemberUpdate`                                       |
+//! | Members               | `MemberAdd`, `MemberUpdate`, `MemberRemove`, `MemberChunk`        |
+//! | Voice states          | `VoiceStateUpdate`                                                |
+//! | Invites               | `InviteCreate`, `InviteDelete` (+ periodic fetches)               |
 //!
 //! [`Serialize`]: serde::Serialize
 //! [`Deserialize`]: serde::Deserialize
@@ -31,5 +33,8 @@ pub mod permission;
 pub use model::{
     channel::CachedChannel,
     guild::{CachedGuild, CachedRole, CurrentMember},
+    invite::{CachedInvite, CachedInvites},
+    member::CachedMember,
+    voice::CachedVoiceState,
 };
 pub use process::event::UpdateCache;
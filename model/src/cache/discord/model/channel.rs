@@ -8,7 +8,10 @@ use twilight_model::{
     },
 };
 
-use crate::{cache::RedisModel, serde::IdAsU64};
+use crate::{
+    cache::{migrate::KEY_PREFIX, RedisModel},
+    serde::IdAsU64,
+};
 
 /// Cached model of a [`Channel`].
 ///
@@ -81,6 +84,6 @@ impl RedisModel for CachedChannel {
     }
 
     fn key_from(id: &Self::Id) -> String {
-        format!("c:channel:{id}")
+        format!("{KEY_PREFIX}:channel:{id}")
     }
 }
@@ -12,7 +12,7 @@ use twilight_model::{
 };
 
 use crate::{
-    cache::RedisModel,
+    cache::{migrate::KEY_PREFIX, RedisModel},
     serde::{IdAsU64, TimestampAsI64},
 };
 
@@ -78,7 +78,7 @@ impl RedisModel for CachedGuild {
     }
 
     fn key_from(id: &Self::Id) -> String {
-        format!("c:guild:{id}")
+        format!("{KEY_PREFIX}:guild:{id}")
     }
 }
 
@@ -119,6 +119,8 @@ pub struct CachedRole {
     ///
     /// Managed roles include bot, integration or boost roles.
     pub managed: bool,
+    /// Whether the role is displayed separately in the member list.
+    pub hoist: bool,
 }
 
 impl RedisModel for CachedRole {
@@ -129,6 +131,6 @@ impl RedisModel for CachedRole {
     }
 
     fn key_from(id: &Self::Id) -> String {
-        format!("c:role:{id}")
+        format!("{KEY_PREFIX}:role:{id}")
     }
 }
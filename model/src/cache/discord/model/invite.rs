@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use time::OffsetDateTime;
+use twilight_model::{
+    id::{
+        marker::{ChannelMarker, GuildMarker, UserMarker},
+        Id,
+    },
+    invite::Invite,
+};
+
+use crate::{
+    cache::{migrate::KEY_PREFIX, RedisModel},
+    serde::{DateTimeAsI64, IdAsU64},
+};
+
+/// Cached invites of a guild.
+///
+/// Invites are cached with their use counts, which are compared with the
+/// current use counts when a member joins to find the invite that has been
+/// used (see [`CachedInvites::find_used`]).
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CachedInvites {
+    /// Id of the guild.
+    #[serde_as(as = "IdAsU64")]
+    pub guild_id: Id<GuildMarker>,
+    /// Invites of the guild.
+    pub invites: Vec<CachedInvite>,
+    /// Time at which the invites were last fetched from the Discord API.
+    #[serde_as(as = "DateTimeAsI64")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl CachedInvites {
+    /// Pattern matching the keys of all the cached guild invites.
+    pub fn key_pattern() -> String {
+        format!("{KEY_PREFIX}:invites:*")
+    }
+
+    /// Find the invite used by a new member.
+    ///
+    /// `current` are the invites of the guild fetched after the member joined.
+    /// The used invite is either an invite whose use count increased, or a
+    /// cached invite that reached its maximum uses and has been removed.
+    ///
+    /// If several invites may have been used (e.g. when multiple members join
+    /// at the same time), [`None`] is returned.
+    pub fn find_used(&self, current: &[CachedInvite]) -> Option<CachedInvite> {
+        let mut candidates = Vec::new();
+
+        for invite in current {
+            let previous_uses = self
+                .invites
+                .iter()
+                .find(|cached| cached.code == invite.code)
+                .map_or(0, |cached| cached.uses);
+
+            if invite.uses > previous_uses {
+                candidates.push(invite);
+            }
+        }
+
+        for cached in &self.invites {
+            let exhausted = cached.max_uses != 0 && cached.uses + 1 >= cached.max_uses;
+
+            if exhausted && !current.iter().any(|invite| invite.code == cached.code) {
+                candidates.push(cached);
+            }
+        }
+
+        match candidates.as_slice() {
+            [invite] => Some((*invite).clone()),
+            _ => None,
+        }
+    }
+}
+
+impl RedisModel for CachedInvites {
+    type Id = Id<GuildMarker>;
+
+    fn key(&self) -> String {
+        Self::key_from(&self.guild_id)
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("{KEY_PREFIX}:invites:{id}")
+    }
+}
+
+/// Cached model of an [`Invite`].
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CachedInvite {
+    /// Code of the invite.
+    pub code: String,
+    /// Id of the channel the invite points to.
+    #[serde_as(as = "Option<IdAsU64>")]
+    pub channel_id: Option<Id<ChannelMarker>>,
+    /// Id of the user that created the invite.
+    #[serde_as(as = "Option<IdAsU64>")]
+    pub inviter_id: Option<Id<UserMarker>>,
+    /// Number of times the invite has been used.
+    pub uses: u64,
+    /// Maximum number of uses of the invite, `0` if unlimited.
+    pub max_uses: u64,
+}
+
+impl From<&Invite> for CachedInvite {
+    fn from(invite: &Invite) -> Self {
+        Self {
+            code: invite.code.clone(),
+            channel_id: invite.channel.as_ref().map(|channel| channel.id),
+            inviter_id: invite.inviter.as_ref().map(|user| user.id),
+            uses: invite.uses.unwrap_or_default(),
+            max_uses: invite.max_uses.unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invite(code: &str, uses: u64, max_uses: u64) -> CachedInvite {
+        CachedInvite {
+            code: code.to_owned(),
+            channel_id: None,
+            inviter_id: None,
+            uses,
+            max_uses,
+        }
+    }
+
+    fn cached(invites: Vec<CachedInvite>) -> CachedInvites {
+        CachedInvites {
+            guild_id: Id::new(1),
+            invites,
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn test_find_used_increased() {
+        let cached = cached(vec![invite("abc", 3, 0), invite("def", 1, 0)]);
+        let current = [invite("abc", 3, 0), invite("def", 2, 0)];
+
+        assert_eq!(cached.find_used(&current), Some(invite("def", 2, 0)));
+    }
+
+    #[test]
+    fn test_find_used_new_invite() {
+        let cached = cached(vec![invite("abc", 3, 0)]);
+        let current = [invite("abc", 3, 0), invite("def", 1, 0)];
+
+        assert_eq!(cached.find_used(&current), Some(invite("def", 1, 0)));
+    }
+
+    #[test]
+    fn test_find_used_exhausted() {
+        let cached = cached(vec![invite("abc", 3, 0), invite("def", 0, 1)]);
+        let current = [invite("abc", 3, 0)];
+
+        assert_eq!(cached.find_used(&current), Some(invite("def", 0, 1)));
+    }
+
+    #[test]
+    fn test_find_used_ambiguous() {
+        let cached = cached(vec![invite("abc", 3, 0), invite("def", 1, 0)]);
+        let current = [invite("abc", 4, 0), invite("def", 2, 0)];
+
+        assert_eq!(cached.find_used(&current), None);
+        assert_eq!(cached.find_used(&cached.invites), None);
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use twilight_model::{
+    id::{
+        marker::{GuildMarker, RoleMarker, UserMarker},
+        Id,
+    },
+    util::Timestamp,
+};
+
+use crate::{
+    cache::{migrate::KEY_PREFIX, RedisModel},
+    serde::{IdAsU64, TimestampAsI64},
+};
+
+/// Cached model of a [`Member`].
+///
+/// Members are cached per-guild, the same user has a distinct entry for each
+/// guild they are a member of.
+///
+/// [`Member`]: twilight_model::guild::member::Member
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CachedMember {
+    /// Id of the guild.
+    #[serde_as(as = "IdAsU64")]
+    pub guild_id: Id<GuildMarker>,
+    /// Id of the member user.
+    #[serde_as(as = "IdAsU64")]
+    pub user_id: Id<UserMarker>,
+    /// Roles of the member.
+    #[serde_as(as = "Vec<IdAsU64>")]
+    pub roles: Vec<Id<RoleMarker>>,
+    /// Nickname of the member.
+    pub nick: Option<String>,
+    /// When the member joined the guild.
+    #[serde_as(as = "TimestampAsI64")]
+    pub joined_at: Timestamp,
+    /// Whether the member has not yet passed the membership screening.
+    pub pending: bool,
+    /// When the timeout of the member expires.
+    #[serde_as(as = "Option<TimestampAsI64>")]
+    pub communication_disabled_until: Option<Timestamp>,
+}
+
+impl RedisModel for CachedMember {
+    type Id = (Id<GuildMarker>, Id<UserMarker>);
+
+    fn key(&self) -> String {
+        Self::key_from(&(self.guild_id, self.user_id))
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!(
+            "{KEY_PREFIX}:member:{guild}:{user}",
+            guild = id.0,
+            user = id.1
+        )
+    }
+}
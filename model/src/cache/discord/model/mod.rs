@@ -11,3 +11,6 @@
 
 pub mod channel;
 pub mod guild;
+pub mod invite;
+pub mod member;
+pub mod voice;
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use twilight_model::id::{
+    marker::{ChannelMarker, GuildMarker, UserMarker},
+    Id,
+};
+
+use crate::{
+    cache::{migrate::KEY_PREFIX, RedisModel},
+    serde::IdAsU64,
+};
+
+/// Cached model of a [`VoiceState`].
+///
+/// Only the voice states of members connected to a voice channel are cached,
+/// the cached state is removed when the member disconnects.
+///
+/// [`VoiceState`]: twilight_model::voice::VoiceState
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CachedVoiceState {
+    /// Id of the guild.
+    #[serde_as(as = "IdAsU64")]
+    pub guild_id: Id<GuildMarker>,
+    /// Id of the member user.
+    #[serde_as(as = "IdAsU64")]
+    pub user_id: Id<UserMarker>,
+    /// Id of the voice channel the member is connected to.
+    #[serde_as(as = "IdAsU64")]
+    pub channel_id: Id<ChannelMarker>,
+    /// Whether the member is deafened by the guild.
+    pub deaf: bool,
+    /// Whether the member is muted by the guild.
+    pub mute: bool,
+    /// Whether the member is locally deafened.
+    pub self_deaf: bool,
+    /// Whether the member is locally muted.
+    pub self_mute: bool,
+    /// Whether the member is streaming using "Go Live".
+    pub self_stream: bool,
+    /// Whether the member camera is enabled.
+    pub self_video: bool,
+}
+
+impl RedisModel for CachedVoiceState {
+    type Id = (Id<GuildMarker>, Id<UserMarker>);
+
+    fn key(&self) -> String {
+        Self::key_from(&(self.guild_id, self.user_id))
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!(
+            "{KEY_PREFIX}:voice:{guild}:{user}",
+            guild = id.0,
+            user = id.1
+        )
+    }
+}
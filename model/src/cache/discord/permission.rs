@@ -2,6 +2,19 @@
 //!
 //! This module allows to compute in-channel or guild permissions for a given
 //! member using [`twilight_util::permission_calculator`].
+//!
+//! Threads do not have their own permission overwrites: the permissions in a
+//! thread are the permissions in its parent channel, where the
+//! [`SEND_MESSAGES_IN_THREADS`] permission replaces [`SEND_MESSAGES`] (see
+//! [`CachePermissions::permissions_in_thread`]).
+//!
+//! Moderation actions are also restricted by the role hierarchy: a member can
+//! only target members whose highest role is lower than their own. This is
+//! checked with [`CachePermissions::can_target`], or with the
+//! [`GuildPermissions`] helpers when only the member ids are known.
+//!
+//! [`SEND_MESSAGES`]: Permissions::SEND_MESSAGES
+//! [`SEND_MESSAGES_IN_THREADS`]: Permissions::SEND_MESSAGES_IN_THREADS
 
 use std::cmp::Ordering;
 
@@ -17,8 +30,8 @@ use twilight_model::{
 };
 use twilight_util::permission_calculator::PermissionCalculator;
 
-use super::{CachedChannel, CachedGuild, CachedRole};
-use crate::cache::{CacheClient, RedisModel};
+use super::{CachedChannel, CachedGuild, CachedMember, CachedRole};
+use crate::cache::CacheClient;
 
 /// Calculate the permissions for a given guild.
 pub struct GuildPermissions<'a> {
@@ -56,6 +69,56 @@ impl<'a> GuildPermissions<'a> {
     pub async fn current_member(&self) -> Result<CachePermissions<'a>, anyhow::Error> {
         CachePermissions::current_member(self).await
     }
+
+    /// Compute permissions for a guild member with roles from the cache.
+    ///
+    /// An error is returned if the member is not cached.
+    #[instrument(skip(self))]
+    pub async fn cached_member(
+        &self,
+        member_id: Id<UserMarker>,
+    ) -> Result<CachePermissions<'a>, anyhow::Error> {
+        let member = self
+            .client
+            .get::<CachedMember>(&(self.guild.id, member_id))
+            .await?
+            .context("member not found in cache")?;
+
+        CachePermissions::new(self, member_id, &member.roles).await
+    }
+
+    /// Returns the highest role of a cached member.
+    pub async fn highest_role(
+        &self,
+        member_id: Id<UserMarker>,
+    ) -> Result<RoleOrdering, anyhow::Error> {
+        Ok(self.cached_member(member_id).await?.highest_role())
+    }
+
+    /// Whether a cached member can target another cached member with a
+    /// moderation action.
+    ///
+    /// See [`CachePermissions::can_target`] for more information.
+    pub async fn can_target(
+        &self,
+        actor_id: Id<UserMarker>,
+        target_id: Id<UserMarker>,
+    ) -> Result<bool, anyhow::Error> {
+        let actor = self.cached_member(actor_id).await?;
+        let target = self.cached_member(target_id).await?;
+
+        Ok(actor.can_target(&target))
+    }
+
+    /// Whether the bot can target a cached member with a moderation action.
+    ///
+    /// See [`CachePermissions::can_target`] for more information.
+    pub async fn bot_can_target(&self, target_id: Id<UserMarker>) -> Result<bool, anyhow::Error> {
+        let bot = self.current_member().await?;
+        let target = self.cached_member(target_id).await?;
+
+        Ok(bot.can_target(&target))
+    }
 }
 
 /// Calculate the permissions of a member with information from the cache.
@@ -138,6 +201,18 @@ impl<'a> CachePermissions<'a> {
         }
     }
 
+    /// Whether the user can target another member with a moderation action.
+    ///
+    /// The guild owner cannot be targeted and can target any other member.
+    /// Otherwise, the highest role of the user must be higher than the highest
+    /// role of the target.
+    pub fn can_target(&self, target: &CachePermissions<'_>) -> bool {
+        outranks(
+            (self.is_owner, self.highest_role()),
+            (target.is_owner, target.highest_role()),
+        )
+    }
+
     /// Calculate the permissions of the user in the guild.
     pub fn guild(&self) -> Permissions {
         // Owners have all permissions
@@ -145,63 +220,122 @@ impl<'a> CachePermissions<'a> {
             return Permissions::all();
         }
 
-        // TODO: extract this into a function
-        let everyone_role = self.member_roles.everyone.permissions;
-        let member_roles = self
-            .member_roles
-            .roles
-            .iter()
-            .map(|role| (role.id, role.permissions))
-            .collect::<Vec<_>>();
-
-        let calculator =
-            PermissionCalculator::new(self.guild_id, self.member_id, everyone_role, &member_roles);
+        let member_roles = self.member_roles.permissions();
 
-        calculator.root()
+        self.calculator(&member_roles).root()
     }
 
     /// Calculate the permissions of the user in a given channel.
     ///
     /// This method also return the [`ChannelType`] of the requested channel
-    /// to handle the case where the channel is a thread.
+    /// to handle the case where the channel is a thread. The permissions in
+    /// threads are calculated like with
+    /// [`permissions_in_thread`](Self::permissions_in_thread).
     pub async fn channel(
         &self,
         channel: Id<ChannelMarker>,
     ) -> Result<(Permissions, ChannelType), anyhow::Error> {
-        let mut channel = self
+        let channel = self
             .client
             .get::<CachedChannel>(&channel)
             .await?
             .context("channel not found in cache")?;
 
-        // If the channel is a thread, get the parent channel.
-        if channel.is_thread() {
-            if let Some(parent_id) = channel.parent_id {
-                channel = self
-                    .client
-                    .get::<CachedChannel>(&parent_id)
-                    .await?
-                    .context("parent channel not found in cache")?;
-            }
+        let permissions = if channel.is_thread() {
+            self.in_thread(&channel).await?
+        } else {
+            self.in_channel(&channel)
+        };
+
+        Ok((permissions, channel.kind))
+    }
+
+    /// Calculate the permissions of the user in a given thread.
+    ///
+    /// Threads inherit the permission overwrites of their parent channel.
+    /// [`SEND_MESSAGES`] is only granted if the user has the
+    /// [`SEND_MESSAGES_IN_THREADS`] permission in the parent channel.
+    ///
+    /// Membership of private threads is not checked: users that can view the
+    /// parent channel are considered as able to view private threads.
+    ///
+    /// [`SEND_MESSAGES`]: Permissions::SEND_MESSAGES
+    /// [`SEND_MESSAGES_IN_THREADS`]: Permissions::SEND_MESSAGES_IN_THREADS
+    pub async fn permissions_in_thread(
+        &self,
+        thread: Id<ChannelMarker>,
+    ) -> Result<Permissions, anyhow::Error> {
+        let thread = self
+            .client
+            .get::<CachedChannel>(&thread)
+            .await?
+            .context("thread not found in cache")?;
+
+        if !thread.is_thread() {
+            return Err(anyhow!("channel {} is not a thread", thread.id));
         }
 
-        // TODO: extract this into a function
-        let everyone_role = self.member_roles.everyone.permissions;
-        let member_roles = self
-            .member_roles
-            .roles
-            .iter()
-            .map(|role| (role.id, role.permissions))
-            .collect::<Vec<_>>();
+        self.in_thread(&thread).await
+    }
+
+    /// Calculate the permissions of the user in a cached thread.
+    async fn in_thread(&self, thread: &CachedChannel) -> Result<Permissions, anyhow::Error> {
+        let parent_id = thread.parent_id.context("missing thread parent channel")?;
+        let parent = self
+            .client
+            .get::<CachedChannel>(&parent_id)
+            .await?
+            .context("parent channel not found in cache")?;
 
-        let calculator =
-            PermissionCalculator::new(self.guild_id, self.member_id, everyone_role, &member_roles);
+        Ok(thread_permissions(self.in_channel(&parent)))
+    }
 
-        let kind = channel.kind;
-        let permissions =
-            calculator.in_channel(kind, &channel.permission_overwrites.unwrap_or_default());
+    /// Calculate the permissions of the user in a cached channel.
+    fn in_channel(&self, channel: &CachedChannel) -> Permissions {
+        let member_roles = self.member_roles.permissions();
+        let overwrites = channel.permission_overwrites.as_deref().unwrap_or_default();
 
-        Ok((permissions, kind))
+        self.calculator(&member_roles)
+            .in_channel(channel.kind, overwrites)
+    }
+
+    /// Initialize a [`PermissionCalculator`] for the user.
+    fn calculator<'b>(
+        &self,
+        member_roles: &'b [(Id<RoleMarker>, Permissions)],
+    ) -> PermissionCalculator<'b> {
+        PermissionCalculator::new(
+            self.guild_id,
+            self.member_id,
+            self.member_roles.everyone.permissions,
+            member_roles,
+        )
+    }
+}
+
+/// Convert the permissions in the parent channel of a thread into the
+/// permissions in the thread.
+///
+/// See [`CachePermissions::permissions_in_thread`] for more information.
+fn thread_permissions(parent: Permissions) -> Permissions {
+    let mut permissions = parent;
+    permissions.set(
+        Permissions::SEND_MESSAGES,
+        parent.contains(Permissions::SEND_MESSAGES_IN_THREADS),
+    );
+
+    permissions
+}
+
+/// Whether an actor outranks a target in the role hierarchy.
+///
+/// Members are represented by whether they are the guild owner and their
+/// highest role.
+fn outranks(actor: (bool, RoleOrdering), target: (bool, RoleOrdering)) -> bool {
+    match (actor.0, target.0) {
+        (_, true) => false,
+        (true, false) => true,
+        (false, false) => actor.1 > target.1,
     }
 }
 
@@ -214,6 +348,14 @@ struct MemberRoles {
 }
 
 impl MemberRoles {
+    /// Permissions of the member roles, excluding the everyone role.
+    fn permissions(&self) -> Vec<(Id<RoleMarker>, Permissions)> {
+        self.roles
+            .iter()
+            .map(|role| (role.id, role.permissions))
+            .collect()
+    }
+
     /// Query roles of a member in the cache.
     async fn query(
         client: &CacheClient,
@@ -223,24 +365,18 @@ impl MemberRoles {
         let everyone_id = guild_id.cast();
 
         // Get user roles
-        let mut pipe = redis::pipe();
-        for role in member_roles.copied().chain([everyone_id]) {
-            pipe.get(CachedRole::key_from(&role));
-        }
-
-        let mut conn = client.conn().await?;
-        let result: Vec<_> = pipe
-            .query_async(&mut *conn)
+        let ids: Vec<_> = member_roles.copied().chain([everyone_id]).collect();
+        let result = client
+            .get_many::<CachedRole>(&ids)
             .await
             .context("failed to query user roles")?;
 
-        // Filter everyone role and other roles
+        // Filter everyone role and other roles. Roles missing from the cache
+        // have been deleted and are ignored.
         let mut everyone_role = None;
         let mut roles = Vec::new();
 
-        for value in result {
-            let role = CachedRole::deserialize_model(value)?;
-
+        for role in result.into_iter().flatten() {
             if role.id == everyone_id {
                 everyone_role = Some(role);
             } else {
@@ -351,4 +487,40 @@ mod tests {
         assert_eq!(Ordering::Equal, role_a.cmp(&role_a));
         assert_eq!(Ordering::Equal, role_b.cmp(&role_b));
     }
+
+    #[test]
+    fn thread_send_messages() {
+        let parent = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES;
+        assert_eq!(thread_permissions(parent), Permissions::VIEW_CHANNEL);
+
+        let parent = Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES_IN_THREADS;
+        assert_eq!(
+            thread_permissions(parent),
+            parent | Permissions::SEND_MESSAGES
+        );
+
+        assert_eq!(
+            thread_permissions(Permissions::empty()),
+            Permissions::empty()
+        );
+    }
+
+    #[test]
+    fn outranks_hierarchy() {
+        let low = RoleOrdering {
+            id: Id::new(1),
+            position: 1,
+        };
+        let high = RoleOrdering {
+            id: Id::new(2),
+            position: 5,
+        };
+
+        assert!(outranks((false, high), (false, low)));
+        assert!(!outranks((false, low), (false, high)));
+        assert!(!outranks((false, high), (false, high)));
+        assert!(outranks((true, low), (false, high)));
+        assert!(!outranks((false, high), (true, low)));
+        assert!(!outranks((true, high), (true, high)));
+    }
 }
@@ -5,14 +5,22 @@ use tracing::error;
 use twilight_model::{
     gateway::payload::incoming::{
         ChannelCreate, ChannelDelete, ChannelUpdate, GuildCreate, GuildDelete, GuildUpdate,
-        MemberAdd, MemberUpdate, RoleCreate, RoleDelete, RoleUpdate, ThreadCreate, ThreadDelete,
-        ThreadUpdate, UnavailableGuild,
+        InviteCreate, InviteDelete, MemberAdd, MemberChunk, MemberRemove, MemberUpdate, RoleCreate,
+        RoleDelete, RoleUpdate, ThreadCreate, ThreadDelete, ThreadUpdate, UnavailableGuild,
+        VoiceStateUpdate,
+    },
+    id::{
+        marker::{ApplicationMarker, GuildMarker},
+        Id,
     },
-    id::{marker::ApplicationMarker, Id},
 };
 
 use crate::cache::{
-    discord::{CachedChannel, CachedGuild, CachedRole, CurrentMember},
+    discord::{
+        CachedChannel, CachedGuild, CachedInvite, CachedInvites, CachedMember, CachedRole,
+        CachedVoiceState, CurrentMember,
+    },
+    migrate::KEY_PREFIX,
     CacheClient, RedisModel,
 };
 
@@ -48,7 +56,7 @@ impl UpdateCache for GuildCreate {
         current_user: Id<ApplicationMarker>,
     ) -> Result<(), anyhow::Error> {
         let mut pipe = redis::pipe();
-        super::resource::cache_guild(&mut pipe, current_user, &self.0)?;
+        super::resource::cache_guild(&mut pipe, redis, current_user, &self.0)?;
 
         let mut conn = redis.conn().await?;
         pipe.query_async(&mut *conn).await?;
@@ -67,7 +75,8 @@ impl UpdateCache for GuildDelete {
         _current_user: Id<ApplicationMarker>,
     ) -> Result<(), anyhow::Error> {
         if let Some(guild) = redis.get::<CachedGuild>(&self.id).await? {
-            // Remove all channels and roles from the cache.
+            // Remove all channels, roles, members, voice states and invites from
+            // the cache.
             let mut conn = redis.conn().await?;
             let mut pipe = redis::pipe();
             pipe.del(CachedGuild::key_from(&self.id));
@@ -78,6 +87,9 @@ impl UpdateCache for GuildDelete {
             for role in &guild.roles {
                 pipe.del(CachedRole::key_from(role));
             }
+            delete_guild_entries::<CachedMember>(&mut pipe, redis, "member", self.id).await?;
+            delete_guild_entries::<CachedVoiceState>(&mut pipe, redis, "voice", self.id).await?;
+            pipe.del(CachedInvites::key_from(&self.id));
 
             pipe.query_async(&mut *conn).await?;
         }
@@ -98,10 +110,11 @@ impl UpdateCache for UnavailableGuild {
         if let Some(mut guild) = redis.get::<CachedGuild>(&self.id).await? {
             guild.unavailable = true;
 
-            // Remove all channels and roles from the cache.
+            // Remove all channels, roles, members, voice states and invites from
+            // the cache.
             let mut conn = redis.conn().await?;
             let mut pipe = redis::pipe();
-            pipe.set(guild.key(), guild.serialize_model()?);
+            redis.pipe_set(&mut pipe, &guild)?;
 
             for channel in &guild.channels {
                 pipe.del(CachedChannel::key_from(channel));
@@ -109,6 +122,9 @@ impl UpdateCache for UnavailableGuild {
             for role in &guild.roles {
                 pipe.del(CachedRole::key_from(role));
             }
+            delete_guild_entries::<CachedMember>(&mut pipe, redis, "member", self.id).await?;
+            delete_guild_entries::<CachedVoiceState>(&mut pipe, redis, "voice", self.id).await?;
+            pipe.del(CachedInvites::key_from(&self.id));
 
             pipe.query_async(&mut *conn).await?;
         }
@@ -152,10 +168,10 @@ impl UpdateCache for ChannelCreate {
                 let mut conn = redis.conn().await?;
 
                 if CachedChannel::is_cached(self.kind) {
-                    match super::resource::cache_guild_channel(&mut pipe, self) {
+                    match super::resource::cache_guild_channel(&mut pipe, redis, self) {
                         Ok(_) => {
                             guild.channels.insert(self.id);
-                            pipe.set(guild.key(), guild.serialize_model()?);
+                            redis.pipe_set(&mut pipe, &guild)?;
                         }
                         Err(error) => {
                             error!(error = ?error, "failed to cache guild channel");
@@ -186,7 +202,7 @@ impl UpdateCache for ChannelDelete {
         if let Some(guild_id) = self.guild_id {
             if let Some(mut guild) = redis.get::<CachedGuild>(&guild_id).await? {
                 guild.channels.remove(&self.id);
-                pipe.set(guild.key(), guild.serialize_model()?);
+                redis.pipe_set(&mut pipe, &guild)?;
             }
         }
 
@@ -216,7 +232,7 @@ impl UpdateCache for ChannelUpdate {
         let mut pipe = redis::pipe();
         let mut conn = redis.conn().await?;
 
-        match super::resource::cache_guild_channel(&mut pipe, self) {
+        match super::resource::cache_guild_channel(&mut pipe, redis, self) {
             Ok(_) => pipe.query_async(&mut *conn).await?,
             Err(error) => {
                 error!(error = ?error, "failed to cache guild channel");
@@ -241,10 +257,10 @@ impl UpdateCache for ThreadCreate {
                 let mut pipe = redis::pipe();
                 let mut conn = redis.conn().await?;
 
-                match super::resource::cache_guild_channel(&mut pipe, self) {
+                match super::resource::cache_guild_channel(&mut pipe, redis, self) {
                     Ok(_) => {
                         guild.channels.insert(self.id);
-                        pipe.set(guild.key(), guild.serialize_model()?);
+                        redis.pipe_set(&mut pipe, &guild)?;
                     }
                     Err(error) => {
                         error!(error = ?error, "failed to cache guild channel");
@@ -273,7 +289,7 @@ impl UpdateCache for ThreadDelete {
         // Remove the channel from the guild.
         if let Some(mut guild) = redis.get::<CachedGuild>(&self.guild_id).await? {
             guild.channels.remove(&self.id);
-            pipe.set(guild.key(), guild.serialize_model()?);
+            redis.pipe_set(&mut pipe, &guild)?;
         }
 
         // Remove the channel from the cache.
@@ -302,7 +318,7 @@ impl UpdateCache for ThreadUpdate {
         let mut pipe = redis::pipe();
         let mut conn = redis.conn().await?;
 
-        match super::resource::cache_guild_channel(&mut pipe, self) {
+        match super::resource::cache_guild_channel(&mut pipe, redis, self) {
             Ok(_) => pipe.query_async(&mut *conn).await?,
             Err(error) => {
                 error!(error = ?error, "failed to cache guild channel");
@@ -324,11 +340,11 @@ impl UpdateCache for RoleCreate {
     ) -> Result<(), anyhow::Error> {
         let mut pipe = redis::pipe();
 
-        super::resource::cache_role(&mut pipe, &self.role, self.guild_id)?;
+        super::resource::cache_role(&mut pipe, redis, &self.role, self.guild_id)?;
 
         if let Some(mut guild) = redis.get::<CachedGuild>(&self.guild_id).await? {
             guild.roles.insert(self.role.id);
-            pipe.set(guild.key(), guild.serialize_model()?);
+            redis.pipe_set(&mut pipe, &guild)?;
         }
 
         let mut conn = redis.conn().await?;
@@ -351,7 +367,7 @@ impl UpdateCache for RoleDelete {
 
         if let Some(mut guild) = redis.get::<CachedGuild>(&self.guild_id).await? {
             guild.roles.remove(&self.role_id);
-            pipe.set(guild.key(), guild.serialize_model()?);
+            redis.pipe_set(&mut pipe, &guild)?;
         }
 
         pipe.del(CachedRole::key_from(&self.role_id));
@@ -375,7 +391,7 @@ impl UpdateCache for RoleUpdate {
         let mut pipe = redis::pipe();
         let mut conn = redis.conn().await?;
 
-        super::resource::cache_role(&mut pipe, &self.role, self.guild_id)?;
+        super::resource::cache_role(&mut pipe, redis, &self.role, self.guild_id)?;
         pipe.query_async(&mut *conn).await?;
 
         Ok(())
@@ -391,8 +407,13 @@ impl UpdateCache for MemberAdd {
         redis: &CacheClient,
         current_user: Id<ApplicationMarker>,
     ) -> Result<(), anyhow::Error> {
+        let mut pipe = redis::pipe();
+        super::resource::cache_member(&mut pipe, redis, &self.0)?;
+
+        let mut conn = redis.conn().await?;
+        pipe.query_async(&mut *conn).await?;
+
         if self.user.id != current_user.cast() {
-            // Only cache bot user
             return Ok(());
         }
 
@@ -420,8 +441,18 @@ impl UpdateCache for MemberUpdate {
         redis: &CacheClient,
         current_user: Id<ApplicationMarker>,
     ) -> Result<(), anyhow::Error> {
+        let cached = CachedMember {
+            guild_id: self.guild_id,
+            user_id: self.user.id,
+            roles: self.roles.clone(),
+            nick: self.nick.clone(),
+            joined_at: self.joined_at,
+            pending: self.pending,
+            communication_disabled_until: self.communication_disabled_until,
+        };
+        redis.set(&cached).await?;
+
         if self.user.id != current_user.cast() {
-            // Only cache bot user
             return Ok(());
         }
 
@@ -435,6 +466,147 @@ impl UpdateCache for MemberUpdate {
             guild.current_member = Some(cached);
             redis.set(&guild).await?;
         }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UpdateCache for MemberRemove {
+    const NAME: &'static str = "MemberRemove";
+
+    async fn update(
+        &self,
+        redis: &CacheClient,
+        _current_user: Id<ApplicationMarker>,
+    ) -> Result<(), anyhow::Error> {
+        let mut conn = redis.conn().await?;
+        let mut pipe = redis::pipe();
+
+        pipe.del(CachedMember::key_from(&(self.guild_id, self.user.id)));
+        pipe.query_async(&mut *conn).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UpdateCache for MemberChunk {
+    const NAME: &'static str = "MemberChunk";
+
+    async fn update(
+        &self,
+        redis: &CacheClient,
+        _current_user: Id<ApplicationMarker>,
+    ) -> Result<(), anyhow::Error> {
+        let mut pipe = redis::pipe();
+
+        for member in &self.members {
+            super::resource::cache_member(&mut pipe, redis, member)?;
+        }
+
+        let mut conn = redis.conn().await?;
+        pipe.query_async(&mut *conn).await?;
+
         Ok(())
     }
 }
+
+#[async_trait]
+impl UpdateCache for VoiceStateUpdate {
+    const NAME: &'static str = "VoiceStateUpdate";
+
+    async fn update(
+        &self,
+        redis: &CacheClient,
+        _current_user: Id<ApplicationMarker>,
+    ) -> Result<(), anyhow::Error> {
+        let guild_id = match self.guild_id {
+            Some(guild_id) => guild_id,
+            None => return Ok(()), // Ensure the voice state is in a guild.
+        };
+
+        let mut pipe = redis::pipe();
+        super::resource::cache_voice_state(&mut pipe, redis, guild_id, self)?;
+
+        let mut conn = redis.conn().await?;
+        pipe.query_async(&mut *conn).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UpdateCache for InviteCreate {
+    const NAME: &'static str = "InviteCreate";
+
+    async fn update(
+        &self,
+        redis: &CacheClient,
+        _current_user: Id<ApplicationMarker>,
+    ) -> Result<(), anyhow::Error> {
+        // Invites are only added once they have been fetched for the guild,
+        // otherwise the other invites use counts are unknown.
+        if let Some(mut cached) = redis.get::<CachedInvites>(&self.guild_id).await? {
+            cached.invites.retain(|invite| invite.code != self.code);
+            cached.invites.push(CachedInvite {
+                code: self.code.clone(),
+                channel_id: Some(self.channel_id),
+                inviter_id: self.inviter.as_ref().map(|user| user.id),
+                uses: self.uses.into(),
+                max_uses: self.max_uses,
+            });
+
+            redis.set(&cached).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UpdateCache for InviteDelete {
+    const NAME: &'static str = "InviteDelete";
+
+    async fn update(
+        &self,
+        redis: &CacheClient,
+        _current_user: Id<ApplicationMarker>,
+    ) -> Result<(), anyhow::Error> {
+        if let Some(mut cached) = redis.get::<CachedInvites>(&self.guild_id).await? {
+            // Invites that reached their maximum uses are deleted, possibly
+            // before the `MemberAdd` event of the member that used it is
+            // processed. They are kept until the next fetch to be attributed.
+            cached.invites.retain(|invite| {
+                invite.code != self.code
+                    || (invite.max_uses != 0 && invite.uses + 1 >= invite.max_uses)
+            });
+
+            redis.set(&cached).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Add the deletion of all the cached entries of a guild to a pipeline.
+///
+/// Members and voice states are not referenced by the [`CachedGuild`], so
+/// their keys (prefixed with `{kind}:{guild_id}:`) are listed with the
+/// [`scan`](CacheClient::scan) method.
+async fn delete_guild_entries<T: RedisModel>(
+    pipe: &mut redis::Pipeline,
+    redis: &CacheClient,
+    kind: &str,
+    guild_id: Id<GuildMarker>,
+) -> Result<(), anyhow::Error> {
+    let entries = redis
+        .scan::<T>(&format!("{KEY_PREFIX}:{kind}:{guild_id}:*"))
+        .await?;
+
+    for entry in &entries {
+        pipe.del(entry.key());
+    }
+
+    Ok(())
+}
@@ -10,20 +10,24 @@ use redis::Pipeline;
 use tracing::error;
 use twilight_model::{
     channel::Channel,
-    guild::{Guild, Role},
+    guild::{Guild, Member, Role},
     id::{
         marker::{ApplicationMarker, GuildMarker},
         Id,
     },
+    voice::VoiceState,
 };
 
 use crate::cache::{
-    discord::{CachedChannel, CachedGuild, CachedRole, CurrentMember},
-    RedisModel,
+    discord::{
+        CachedChannel, CachedGuild, CachedMember, CachedRole, CachedVoiceState, CurrentMember,
+    },
+    CacheClient, RedisModel,
 };
 
 pub fn cache_guild(
     pipe: &mut Pipeline,
+    redis: &CacheClient,
     current_user: Id<ApplicationMarker>,
     guild: &Guild,
 ) -> Result<(), anyhow::Error> {
@@ -33,7 +37,7 @@ pub fn cache_guild(
 
     for channel in &guild.channels {
         if CachedChannel::is_cached(channel.kind) {
-            match cache_guild_channel(pipe, channel) {
+            match cache_guild_channel(pipe, redis, channel) {
                 Ok(_) => {
                     channels.insert(channel.id);
                 }
@@ -45,11 +49,19 @@ pub fn cache_guild(
     }
 
     for role in &guild.roles {
-        cache_role(pipe, role, guild.id)?;
+        cache_role(pipe, redis, role, guild.id)?;
 
         roles.insert(role.id);
     }
 
+    for member in &guild.members {
+        cache_member(pipe, redis, member)?;
+    }
+
+    for voice_state in &guild.voice_states {
+        cache_voice_state(pipe, redis, guild.id, voice_state)?;
+    }
+
     // Find the bot current member.
     let current_member = guild
         .members
@@ -73,16 +85,17 @@ pub fn cache_guild(
         channels,
     };
 
-    pipe.set(cached.key(), cached.serialize_model()?);
+    redis.pipe_set(pipe, &cached)?;
 
     Ok(())
 }
 
 pub fn cache_role(
     pipe: &mut Pipeline,
+    redis: &CacheClient,
     role: &Role,
     guild_id: Id<GuildMarker>,
-) -> Result<(), anyhow::Error> {
+) -> Result<CachedRole, anyhow::Error> {
     let cached = CachedRole {
         id: role.id,
         guild_id,
@@ -93,14 +106,19 @@ pub fn cache_role(
         position: role.position,
         permissions: role.permissions,
         managed: role.managed,
+        hoist: role.hoist,
     };
 
-    pipe.set(cached.key(), cached.serialize_model()?);
+    redis.pipe_set(pipe, &cached)?;
 
-    Ok(())
+    Ok(cached)
 }
 
-pub fn cache_guild_channel(pipe: &mut Pipeline, channel: &Channel) -> Result<(), anyhow::Error> {
+pub fn cache_guild_channel(
+    pipe: &mut Pipeline,
+    redis: &CacheClient,
+    channel: &Channel,
+) -> Result<Option<CachedChannel>, anyhow::Error> {
     if CachedChannel::is_cached(channel.kind) {
         let cached = CachedChannel {
             id: channel.id,
@@ -113,8 +131,62 @@ pub fn cache_guild_channel(pipe: &mut Pipeline, channel: &Channel) -> Result<(),
             rate_limit_per_user: channel.rate_limit_per_user,
         };
 
-        pipe.set(cached.key(), cached.serialize_model()?);
+        redis.pipe_set(pipe, &cached)?;
+
+        return Ok(Some(cached));
     }
 
+    Ok(None)
+}
+
+pub fn cache_member(
+    pipe: &mut Pipeline,
+    redis: &CacheClient,
+    member: &Member,
+) -> Result<CachedMember, anyhow::Error> {
+    let cached = CachedMember {
+        guild_id: member.guild_id,
+        user_id: member.user.id,
+        roles: member.roles.clone(),
+        nick: member.nick.clone(),
+        joined_at: member.joined_at,
+        pending: member.pending,
+        communication_disabled_until: member.communication_disabled_until,
+    };
+
+    redis.pipe_set(pipe, &cached)?;
+
+    Ok(cached)
+}
+
+pub fn cache_voice_state(
+    pipe: &mut Pipeline,
+    redis: &CacheClient,
+    guild_id: Id<GuildMarker>,
+    voice_state: &VoiceState,
+) -> Result<(), anyhow::Error> {
+    let channel_id = match voice_state.channel_id {
+        Some(channel_id) => channel_id,
+        None => {
+            // The member disconnected from the voice channel.
+            pipe.del(CachedVoiceState::key_from(&(guild_id, voice_state.user_id)));
+            return Ok(());
+        }
+    };
+
+    let cached = CachedVoiceState {
+        guild_id,
+        user_id: voice_state.user_id,
+        channel_id,
+        deaf: voice_state.deaf,
+        mute: voice_state.mute,
+        self_deaf: voice_state.self_deaf,
+        self_mute: voice_state.self_mute,
+        self_stream: voice_state.self_stream,
+        self_video: voice_state.self_video,
+    };
+
+    redis.pipe_set(pipe, &cached)?;
+
     Ok(())
 }
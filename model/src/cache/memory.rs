@@ -0,0 +1,287 @@
+//! In-memory cache backend.
+//!
+//! The [`MemoryCache`] type is a [`CacheBackend`] replacing Redis in a
+//! [`CacheClient`] created with [`CacheClient::with_backend`], so that code
+//! using the cache can be tested without a Redis server. Only the high-level
+//! methods of the client are supported: raw connections returned by
+//! [`CacheClient::conn`] (used by pipelines and the Discord cache processing),
+//! channel subscriptions and streams return an error. Published messages are
+//! dropped.
+//!
+//! [`CacheClient`]: super::CacheClient
+//! [`CacheClient::with_backend`]: super::CacheClient::with_backend
+//! [`CacheClient::conn`]: super::CacheClient::conn
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+
+use super::CacheBackend;
+
+/// In-memory key-value store with expiration.
+#[derive(Debug, Default)]
+pub struct MemoryCache {
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    value: Vec<u8>,
+    expires_at: Option<Instant>,
+}
+
+impl Entry {
+    fn new(value: Vec<u8>, expires_after: Option<usize>) -> Self {
+        Self {
+            value,
+            expires_at: expires_after
+                .map(|seconds| Instant::now() + Duration::from_secs(seconds as u64)),
+        }
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+impl MemoryCache {
+    /// Run a function with the entries, after removing the expired ones.
+    fn with_entries<R>(&self, f: impl FnOnce(&mut HashMap<String, Entry>) -> R) -> R {
+        let mut entries = self
+            .entries
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        let now = Instant::now();
+
+        entries.retain(|_, entry| !entry.is_expired(now));
+        f(&mut entries)
+    }
+
+    /// Get the value of a key.
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        self.with_entries(|entries| entries.get(key).map(|entry| entry.value.clone()))
+    }
+
+    /// Set the value of a key, with an optional expiration (in seconds).
+    pub fn set(&self, key: &str, value: Vec<u8>, expires_after: Option<usize>) {
+        self.with_entries(|entries| {
+            entries.insert(key.to_owned(), Entry::new(value, expires_after));
+        });
+    }
+
+    /// Set the value of a key if it does not exist.
+    ///
+    /// Returns whether the value has been set.
+    pub fn set_nx(&self, key: &str, value: Vec<u8>, expires_after: Option<usize>) -> bool {
+        self.with_entries(|entries| {
+            if entries.contains_key(key) {
+                return false;
+            }
+
+            entries.insert(key.to_owned(), Entry::new(value, expires_after));
+            true
+        })
+    }
+
+    /// Delete a key.
+    ///
+    /// Returns whether the key existed.
+    pub fn delete(&self, key: &str) -> bool {
+        self.with_entries(|entries| entries.remove(key).is_some())
+    }
+
+    /// Delete a key if its value is `value`.
+    ///
+    /// Returns whether the key has been deleted.
+    pub fn delete_if(&self, key: &str, value: &[u8]) -> bool {
+        self.with_entries(|entries| match entries.get(key) {
+            Some(entry) if entry.value == value => entries.remove(key).is_some(),
+            _ => false,
+        })
+    }
+
+    /// Set the expiration of a key if its value is `value`.
+    ///
+    /// Returns whether the expiration has been set.
+    pub fn expire_if(&self, key: &str, value: &[u8], expires_after: usize) -> bool {
+        self.with_entries(|entries| match entries.get_mut(key) {
+            Some(entry) if entry.value == value => {
+                *entry = Entry::new(entry.value.clone(), Some(expires_after));
+                true
+            }
+            _ => false,
+        })
+    }
+
+    /// Increment a counter, created with an expiration (in seconds).
+    ///
+    /// Returns the incremented value and the remaining time before the
+    /// counter expires (in seconds).
+    pub fn increment(&self, key: &str, expires_after: usize) -> (u64, u64) {
+        self.with_entries(|entries| {
+            let entry = entries
+                .entry(key.to_owned())
+                .or_insert_with(|| Entry::new(b"0".to_vec(), Some(expires_after)));
+
+            let value = String::from_utf8_lossy(&entry.value)
+                .parse::<u64>()
+                .unwrap_or_default()
+                + 1;
+            entry.value = value.to_string().into_bytes();
+
+            let ttl = entry
+                .expires_at
+                .map(|expires_at| expires_at.saturating_duration_since(Instant::now()))
+                .unwrap_or_default();
+
+            (value, ttl.as_secs())
+        })
+    }
+
+    /// Get the keys matching a pattern.
+    ///
+    /// Like the Redis `SCAN` command, `*` matches any sequence of characters
+    /// and `?` any single character. Character classes are not supported.
+    pub fn keys(&self, pattern: &str) -> Vec<String> {
+        let pattern = pattern.chars().collect::<Vec<_>>();
+
+        self.with_entries(|entries| {
+            entries
+                .keys()
+                .filter(|key| glob_match(&pattern, &key.chars().collect::<Vec<_>>()))
+                .cloned()
+                .collect()
+        })
+    }
+}
+
+#[async_trait]
+impl CacheBackend for MemoryCache {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        Ok(MemoryCache::get(self, key))
+    }
+
+    async fn get_many(&self, keys: &[String]) -> Result<Vec<Option<Vec<u8>>>, anyhow::Error> {
+        Ok(keys.iter().map(|key| MemoryCache::get(self, key)).collect())
+    }
+
+    async fn set(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        expires_after: Option<usize>,
+    ) -> Result<(), anyhow::Error> {
+        MemoryCache::set(self, key, value, expires_after);
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, anyhow::Error> {
+        Ok(MemoryCache::delete(self, key))
+    }
+
+    async fn scan(&self, pattern: &str) -> Result<Vec<Vec<u8>>, anyhow::Error> {
+        Ok(self
+            .keys(pattern)
+            .into_iter()
+            .filter_map(|key| MemoryCache::get(self, &key))
+            .collect())
+    }
+
+    async fn increment(
+        &self,
+        key: &str,
+        expires_after: usize,
+    ) -> Result<(u64, u64), anyhow::Error> {
+        Ok(MemoryCache::increment(self, key, expires_after))
+    }
+
+    async fn set_nx(
+        &self,
+        key: &str,
+        value: &[u8],
+        expires_after: usize,
+    ) -> Result<bool, anyhow::Error> {
+        Ok(MemoryCache::set_nx(
+            self,
+            key,
+            value.to_vec(),
+            Some(expires_after),
+        ))
+    }
+
+    async fn expire_if(
+        &self,
+        key: &str,
+        value: &[u8],
+        expires_after: usize,
+    ) -> Result<bool, anyhow::Error> {
+        Ok(MemoryCache::expire_if(self, key, value, expires_after))
+    }
+
+    async fn delete_if(&self, key: &str, value: &[u8]) -> Result<(), anyhow::Error> {
+        MemoryCache::delete_if(self, key, value);
+        Ok(())
+    }
+
+    async fn publish(&self, _channel: &str, _message: Vec<u8>) -> Result<(), anyhow::Error> {
+        Ok(()) // There are no subscribers to the in-memory cache.
+    }
+}
+
+/// Match a string against a glob pattern.
+fn glob_match(pattern: &[char], value: &[char]) -> bool {
+    match (pattern.first(), value.first()) {
+        (None, None) => true,
+        (Some('*'), _) => {
+            glob_match(&pattern[1..], value)
+                || (!value.is_empty() && glob_match(pattern, &value[1..]))
+        }
+        (Some('?'), Some(_)) => glob_match(&pattern[1..], &value[1..]),
+        (Some(expected), Some(actual)) if expected == actual => {
+            glob_match(&pattern[1..], &value[1..])
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys() {
+        let cache = MemoryCache::default();
+        cache.set("quarantine:1:42", Vec::new(), None);
+        cache.set("quarantine:2:42", Vec::new(), None);
+        cache.set("quarantine:1:43", Vec::new(), None);
+
+        let mut keys = cache.keys("quarantine:*:42");
+        keys.sort();
+
+        assert_eq!(keys, ["quarantine:1:42", "quarantine:2:42"]);
+        assert_eq!(cache.keys("quarantine:?:43"), ["quarantine:1:43"]);
+    }
+
+    #[test]
+    fn test_increment() {
+        let cache = MemoryCache::default();
+
+        assert_eq!(cache.increment("counter", 60).0, 1);
+        assert_eq!(cache.increment("counter", 60).0, 2);
+        assert!(cache.increment("counter", 60).1 <= 60);
+    }
+
+    #[test]
+    fn test_set_nx() {
+        let cache = MemoryCache::default();
+
+        assert!(cache.set_nx("lease", b"a".to_vec(), Some(60)));
+        assert!(!cache.set_nx("lease", b"b".to_vec(), Some(60)));
+        assert!(!cache.delete_if("lease", b"b"));
+        assert!(cache.delete_if("lease", b"a"));
+    }
+}
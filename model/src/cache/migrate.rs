@@ -0,0 +1,124 @@
+//! Cache keys versioning.
+//!
+//! Cached data (keys starting with `c:`) is prefixed with a schema version
+//! ([`KEY_PREFIX`]). When the layout of a cached model changes, the version
+//! must be incremented so that entries with the old layout are no longer read,
+//! which would otherwise fail to deserialize.
+//!
+//! Entries of previous versions are removed by [`migrate`] when connecting to
+//! the cache. This is safe since the cached data is rebuilt from gateway
+//! events and Discord API requests. Other models (such as pending interactions
+//! or quarantined members) are not versioned as they cannot be rebuilt, and
+//! their layout should be changed in a backward-compatible way.
+//!
+//! The version of the cache is stored in the [`VERSION_KEY`] key, so that old
+//! entries are only searched once after the version changed.
+
+use redis::AsyncCommands;
+use tracing::{info, trace};
+
+use super::RedisConnection;
+
+/// Prefix of versioned cache keys.
+///
+/// The version must be incremented when the layout of a cached model changes.
+pub const KEY_PREFIX: &str = "c:v2";
+
+/// Key storing the [`KEY_PREFIX`] of the cached data.
+pub const VERSION_KEY: &str = "cache-version";
+
+/// Pattern matching the keys of all cached data, including previous versions.
+const CACHE_PATTERN: &str = "c:*";
+
+/// Number of keys deleted per command.
+const DELETE_BATCH_SIZE: usize = 1_000;
+
+/// Remove the cached data of previous versions.
+///
+/// Nothing is done if the cache already uses the current version.
+pub async fn migrate(conn: &mut RedisConnection<'_>) -> Result<(), anyhow::Error> {
+    let version: Option<String> = conn.get(VERSION_KEY).await?;
+
+    if version.as_deref() == Some(KEY_PREFIX) {
+        trace!("cache is up-to-date with version {KEY_PREFIX}");
+        return Ok(());
+    }
+
+    let mut stale = Vec::new();
+    let mut iter = conn.scan_match::<_, String>(CACHE_PATTERN).await?;
+    while let Some(key) = iter.next_item().await {
+        if is_stale(&key) {
+            stale.push(key);
+        }
+    }
+    drop(iter);
+
+    for keys in stale.chunks(DELETE_BATCH_SIZE) {
+        conn.del(keys).await?;
+    }
+
+    conn.set(VERSION_KEY, KEY_PREFIX).await?;
+
+    info!(
+        previous = ?version,
+        current = KEY_PREFIX,
+        "migrated cache, {} stale entries removed",
+        stale.len()
+    );
+
+    Ok(())
+}
+
+/// Whether a cache key belongs to a previous version.
+fn is_stale(key: &str) -> bool {
+    key.starts_with("c:")
+        && !key
+            .strip_prefix(KEY_PREFIX)
+            .is_some_and(|key| key.starts_with(':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::id::Id;
+
+    use super::*;
+    use crate::{
+        cache::{
+            discord::{CachedChannel, CachedGuild, CachedInvites, CachedMember, CachedRole},
+            model::message::CachedMessage,
+            RedisModel,
+        },
+        database::model::GuildConfig,
+    };
+
+    #[test]
+    fn test_is_stale() {
+        assert!(is_stale("c:guild:1"));
+        assert!(is_stale("c:v0:guild:1"));
+        assert!(is_stale(&format!("{KEY_PREFIX}0:guild:1")));
+        assert!(!is_stale(&format!("{KEY_PREFIX}:guild:1")));
+        assert!(!is_stale("pending:captcha:1:2"));
+        assert!(!is_stale(VERSION_KEY));
+    }
+
+    #[test]
+    fn test_cache_keys_versioned() {
+        let keys = [
+            CachedGuild::key_from(&Id::new(1)),
+            CachedRole::key_from(&Id::new(1)),
+            CachedChannel::key_from(&Id::new(1)),
+            CachedMember::key_from(&(Id::new(1), Id::new(2))),
+            CachedInvites::key_from(&Id::new(1)),
+            CachedMessage::key_from(&Id::new(1)),
+            GuildConfig::key_from(&Id::new(1)),
+        ];
+
+        for key in keys {
+            assert!(key.starts_with(&format!("{KEY_PREFIX}:")), "{key}");
+            assert!(!is_stale(&key), "{key}");
+        }
+
+        assert!(!is_stale(&CachedInvites::key_pattern()));
+        assert!(!is_stale(&GuildConfig::key_pattern()));
+    }
+}
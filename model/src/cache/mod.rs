@@ -13,10 +13,45 @@
 //! Models of cached data can be found in the [`model`] module and the [`discord`]
 //! module (for cached Discord data). All models implements the [`RedisModel`]
 //! trait to be serializable in the cache.
+//!
+//! ## Expiration
+//! Each model has a default expiration delay, which can be overridden with
+//! the configuration. See the [`ttl`] module for more information.
+//!
+//! ## Versioning
+//! Cached data keys are prefixed with a schema version, and entries of previous
+//! versions are removed when connecting to the cache. See the [`migrate`]
+//! module for more information.
+//!
+//! ## Statistics
+//! The [`CacheClient`] counts reads and writes of each model, which are
+//! reported along with the size of the cache by [`CacheClient::report`]. See
+//! the [`stats`] module for more information.
+//!
+//! ## Testing
+//! The cache is stored in a [`CacheBackend`], which is Redis by default. With
+//! the `test-util` feature, the `memory` module provides a backend that stores
+//! the cache in memory, used with [`CacheClient::with_backend`].
+//!
+//! ## Snapshots
+//! The content of the cache can be saved to a file and loaded back for local
+//! development. See the [`snapshot`] module for more information.
 
 pub mod discord;
+pub mod migrate;
 pub mod model;
+pub mod search;
+pub mod snapshot;
+pub mod stats;
+pub mod ttl;
 
+mod backend;
 mod client;
+#[cfg(feature = "test-util")]
+pub mod memory;
 
-pub use self::client::{CacheClient, RedisConnection, RedisModel};
+pub use self::{
+    backend::{CacheBackend, RedisBackend},
+    client::{CacheClient, RedisConnection, RedisModel, StreamReader},
+    ttl::CacheTtl,
+};
@@ -0,0 +1,52 @@
+//! Channels state models.
+
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use time::OffsetDateTime;
+use twilight_model::id::{
+    marker::{ChannelMarker, GuildMarker},
+    Id,
+};
+
+use crate::{
+    cache::RedisModel,
+    serde::{DateTimeAsI64, IdAsU64},
+};
+
+/// Emergency slowmode enabled in a channel.
+///
+/// The slowmode is enabled by the anti-spam during spam waves, and the
+/// previous slowmode of the channel is restored after `restore_at`. This model
+/// does not expire, so that the slowmode is restored even if the bot restarts.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EmergencySlowmode {
+    /// Id of the channel.
+    #[serde_as(as = "IdAsU64")]
+    pub channel_id: Id<ChannelMarker>,
+    /// Id of the guild.
+    #[serde_as(as = "IdAsU64")]
+    pub guild_id: Id<GuildMarker>,
+    /// Slowmode of the channel before the emergency slowmode (in seconds).
+    pub previous: u16,
+    /// Time at which the previous slowmode is restored.
+    #[serde_as(as = "DateTimeAsI64")]
+    pub restore_at: OffsetDateTime,
+}
+
+impl EmergencySlowmode {
+    /// Pattern matching the keys of all the emergency slowmodes.
+    pub const KEY_PATTERN: &'static str = "slowmode:*";
+}
+
+impl RedisModel for EmergencySlowmode {
+    type Id = Id<ChannelMarker>;
+
+    fn key(&self) -> String {
+        Self::key_from(&self.channel_id)
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("slowmode:{id}")
+    }
+}
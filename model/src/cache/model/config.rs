@@ -0,0 +1,35 @@
+//! Cached guild configuration.
+//!
+//! The [`GuildConfig`] of guilds is read for most events and interactions, so
+//! it is cached to avoid querying the database each time. Updates are written
+//! to both the database and the cache. Cached configurations expire after a
+//! short delay, in case the database is updated without updating the cache.
+
+use twilight_model::id::{marker::GuildMarker, Id};
+
+use crate::{
+    cache::{migrate::KEY_PREFIX, RedisModel},
+    database::model::GuildConfig,
+};
+
+impl GuildConfig {
+    /// Pattern matching the keys of all the cached guild configurations.
+    pub fn key_pattern() -> String {
+        format!("{KEY_PREFIX}:guild-config:*")
+    }
+}
+
+impl RedisModel for GuildConfig {
+    type Id = Id<GuildMarker>;
+
+    // Cached configurations expire after 5 minutes.
+    const EXPIRES_AFTER: Option<usize> = Some(5 * 60);
+
+    fn key(&self) -> String {
+        Self::key_from(&self.id)
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("{KEY_PREFIX}:guild-config:{id}")
+    }
+}
@@ -0,0 +1,244 @@
+//! Gateway sessions models.
+
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use time::OffsetDateTime;
+use twilight_model::id::{
+    marker::{GuildMarker, UserMarker},
+    Id,
+};
+
+use crate::{
+    cache::RedisModel,
+    serde::{DateTimeAsI64, IdAsU64},
+};
+
+/// Gateway session of a shard.
+///
+/// Sessions are saved when the bot shuts down, so that the shards can resume
+/// their session when restarted instead of identifying again. This allows
+/// restarting the processes of a deployment one after the other without
+/// replaying the whole `GuildCreate` flood. Discord only allows resuming a
+/// session for a short time, so this model expires after 2 minutes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ShardSession {
+    /// Id of the shard.
+    pub shard_id: u64,
+    /// Total number of shards when the session was saved.
+    ///
+    /// Sessions cannot be resumed if the number of shards changed.
+    pub total_shards: u64,
+    /// Id of the gateway session.
+    pub session_id: String,
+    /// Last received event sequence number.
+    pub sequence: u64,
+    /// Url used to resume the session.
+    pub resume_url: Option<String>,
+}
+
+impl RedisModel for ShardSession {
+    type Id = u64;
+
+    const EXPIRES_AFTER: Option<usize> = Some(2 * 60);
+
+    fn key(&self) -> String {
+        Self::key_from(&self.shard_id)
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("shard-session:{id}")
+    }
+}
+
+/// Status of a shard.
+///
+/// Statuses are published periodically by the bot processes, and read by the
+/// `/debug shards` command and the health endpoint of the web service. This
+/// model expires after 90 seconds, so that the status of a stopped process is
+/// no longer reported.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ShardStatus {
+    /// Id of the shard.
+    pub shard_id: u64,
+    /// Total number of shards.
+    pub total_shards: u64,
+    /// Name of the connection stage of the shard.
+    pub stage: String,
+    /// Whether the shard is connected to the gateway.
+    pub connected: bool,
+    /// Latency of the last heartbeat (in milliseconds).
+    pub latency: Option<u64>,
+    /// Number of events received per minute.
+    pub events_per_minute: u64,
+    /// Number of events waiting to be processed by the process running the
+    /// shard.
+    pub queue_depth: u64,
+    /// Size of the payloads received per minute, after decompression (in
+    /// bytes).
+    ///
+    /// This is only measured if the gateway payload metrics are enabled.
+    pub payload_bytes_per_minute: Option<u64>,
+    /// Time at which the status was published.
+    #[serde_as(as = "DateTimeAsI64")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl ShardStatus {
+    /// Pattern matching the keys of all the shard statuses.
+    pub const KEY_PATTERN: &'static str = "shard-status:*";
+}
+
+impl RedisModel for ShardStatus {
+    type Id = u64;
+
+    const EXPIRES_AFTER: Option<usize> = Some(90);
+
+    fn key(&self) -> String {
+        Self::key_from(&self.shard_id)
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("shard-status:{id}")
+    }
+}
+
+/// Bot process running a range of shards in coordinator mode.
+///
+/// In coordinator mode, the shards are split in ranges, and each process
+/// claims a free range with a lease stored at [`ShardProcess::lease_key`].
+/// The process renews the lease and this model periodically: if it stops,
+/// the lease expires after [`ShardProcess::LEASE_DURATION`] seconds and the
+/// range can be claimed by another process.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ShardProcess {
+    /// Random id of the process.
+    pub process_id: String,
+    /// Index of the claimed range.
+    pub group: u64,
+    /// Id of the first shard of the range.
+    pub from: u64,
+    /// Id of the last shard of the range (inclusive).
+    pub to: u64,
+    /// Total number of shards.
+    pub total_shards: u64,
+    /// Time of the last heartbeat of the process.
+    #[serde_as(as = "DateTimeAsI64")]
+    pub heartbeat_at: OffsetDateTime,
+}
+
+impl ShardProcess {
+    /// Pattern matching the keys of all the shard processes.
+    pub const KEY_PATTERN: &'static str = "shard-process:*";
+
+    /// Duration of the range leases (in seconds).
+    pub const LEASE_DURATION: usize = 30;
+
+    /// Key of the lease of a range.
+    ///
+    /// The key includes the total number of shards, so that processes
+    /// started with a different number of shards do not share leases.
+    pub fn lease_key(total_shards: u64, group: u64) -> String {
+        format!("shard-lease:{total_shards}:{group}")
+    }
+}
+
+impl RedisModel for ShardProcess {
+    type Id = str;
+
+    const EXPIRES_AFTER: Option<usize> = Some(Self::LEASE_DURATION);
+
+    fn key(&self) -> String {
+        Self::key_from(&self.process_id)
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("shard-process:{id}")
+    }
+}
+
+/// Raw gateway events tap.
+///
+/// When this model is set, the bot processes record the raw payloads of the
+/// gateway events matching the filters to the [`EventTap::STREAM_KEY`] Redis
+/// stream, until the tap expires. This is used to debug cache desync issues
+/// in production. Taps cannot last more than one hour.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EventTap {
+    /// Only record the events of this guild.
+    #[serde_as(as = "Option<IdAsU64>")]
+    pub guild_id: Option<Id<GuildMarker>>,
+    /// Only record the events of these types (such as `MESSAGE_CREATE`).
+    ///
+    /// All the event types are recorded if empty.
+    pub event_types: Vec<String>,
+    /// Owner who started the tap.
+    #[serde_as(as = "IdAsU64")]
+    pub started_by: Id<UserMarker>,
+    /// Time at which the tap stops.
+    #[serde_as(as = "DateTimeAsI64")]
+    pub expires_at: OffsetDateTime,
+}
+
+impl EventTap {
+    /// Key of the Redis stream the payloads are recorded to.
+    pub const STREAM_KEY: &'static str = "event-tap:stream";
+
+    /// Whether the tap has expired.
+    pub fn expired(&self) -> bool {
+        self.expires_at <= OffsetDateTime::now_utc()
+    }
+
+    /// Whether an event matches the filters of the tap.
+    pub fn matches(&self, event_type: &str, guild_id: Option<Id<GuildMarker>>) -> bool {
+        if self.guild_id.is_some() && self.guild_id != guild_id {
+            return false;
+        }
+
+        self.event_types.is_empty() || self.event_types.iter().any(|kind| kind == event_type)
+    }
+}
+
+impl RedisModel for EventTap {
+    type Id = ();
+
+    const EXPIRES_AFTER: Option<usize> = Some(60 * 60);
+
+    fn key(&self) -> String {
+        Self::key_from(&())
+    }
+
+    fn key_from(_: &Self::Id) -> String {
+        "event-tap".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_event_tap_matches() {
+        let mut tap = EventTap {
+            guild_id: None,
+            event_types: Vec::new(),
+            started_by: Id::new(1),
+            expires_at: OffsetDateTime::now_utc() + Duration::minutes(5),
+        };
+
+        assert!(!tap.expired());
+        assert!(tap.matches("MESSAGE_CREATE", None));
+
+        tap.guild_id = Some(Id::new(2));
+        tap.event_types = vec!["MESSAGE_CREATE".to_owned()];
+
+        assert!(tap.matches("MESSAGE_CREATE", Some(Id::new(2))));
+        assert!(!tap.matches("MESSAGE_CREATE", Some(Id::new(3))));
+        assert!(!tap.matches("MESSAGE_CREATE", None));
+        assert!(!tap.matches("MESSAGE_UPDATE", Some(Id::new(2))));
+    }
+}
@@ -1,12 +1,12 @@
 //! State for interactions (buttons, select menus, modals).
 
 use serde::{Deserialize, Serialize};
-use serde_with::serde_as;
+use serde_with::{serde_as, Bytes};
 use time::OffsetDateTime;
 use twilight_model::{
     http::interaction::InteractionResponseData,
     id::{
-        marker::{GuildMarker, InteractionMarker, UserMarker},
+        marker::{ChannelMarker, GuildMarker, InteractionMarker, MessageMarker, UserMarker},
         Id,
     },
     user::User,
@@ -14,7 +14,7 @@ use twilight_model::{
 
 use crate::{
     cache::RedisModel,
-    database::model::ModlogType,
+    database::model::{AntiSpamProfile, AntiSpamPunishment, ModlogType},
     serde::{DateTimeAsI64, IdAsU64},
 };
 
@@ -64,9 +64,21 @@ pub struct PendingCaptcha {
     /// verification" button (new image with the same code) or the "regenerate"
     /// button (new image with a new code).
     pub regenerate_count: u8,
+    /// Whether the captcha code has been solved.
+    ///
+    /// This is used when the guild has a join-gate questionnaire, since the
+    /// pending captcha is kept until the questionnaire is answered.
+    #[serde(default)]
+    pub solved: bool,
+    /// Whether the captcha was created while the strict mode was enabled.
+    #[serde(default)]
+    pub strict: bool,
     /// Time at which the captcha expires.
     #[serde_as(as = "DateTimeAsI64")]
     pub expires_at: OffsetDateTime,
+    /// Token of the current web verification link, if any (see [`WebCaptcha`]).
+    #[serde(default)]
+    pub web_token: Option<String>,
 }
 
 impl RedisModel for PendingCaptcha {
@@ -89,6 +101,52 @@ impl RedisModel for PendingCaptcha {
     }
 }
 
+/// State for a captcha solved on the web verification page.
+///
+/// The web verification page is a fallback for users that cannot use Discord
+/// modals. The token is generated by the bot and included in the link to the
+/// page. The captcha image is rendered once by the bot and stored with the
+/// token, so that the page cannot be used to get several renderings of the
+/// same code.
+///
+/// When the code is solved, the web service schedules a
+/// [`JobAction::WebCaptchaCompleted`] job that updates the member roles and
+/// deletes the token.
+///
+/// [`JobAction::WebCaptchaCompleted`]: crate::database::model::JobAction::WebCaptchaCompleted
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebCaptcha {
+    /// Token used in the verification link.
+    pub token: String,
+    /// Id of the guild.
+    #[serde_as(as = "IdAsU64")]
+    pub guild_id: Id<GuildMarker>,
+    /// Id of the member that needs to solve the captcha.
+    #[serde_as(as = "IdAsU64")]
+    pub member_id: Id<UserMarker>,
+    /// Code of the captcha.
+    pub code: String,
+    /// Captcha image, encoded in PNG.
+    #[serde_as(as = "Bytes")]
+    pub image: Vec<u8>,
+}
+
+impl RedisModel for WebCaptcha {
+    type Id = str;
+
+    // Same expiration as the pending captcha.
+    const EXPIRES_AFTER: Option<usize> = Some(10 * 60);
+
+    fn key(&self) -> String {
+        Self::key_from(&self.token)
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("pending:web-captcha:{id}")
+    }
+}
+
 /// State for a pending sanction modal.
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -116,3 +174,209 @@ impl RedisModel for PendingSanction {
         format!("pending:sanction:{id}")
     }
 }
+
+/// State of a pending `/setup` wizard.
+///
+/// The choices are stored until the last step of the wizard, where they are
+/// written to the guild configuration.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingSetup {
+    /// Id of the guild.
+    #[serde_as(as = "IdAsU64")]
+    pub guild_id: Id<GuildMarker>,
+    /// Id of the member running the wizard.
+    #[serde_as(as = "IdAsU64")]
+    pub author_id: Id<UserMarker>,
+    /// Selected logs channel.
+    #[serde_as(as = "Option<IdAsU64>")]
+    pub logs_chan: Option<Id<ChannelMarker>>,
+    /// Whether the captcha should be enabled.
+    pub captcha: bool,
+    /// Selected anti-spam profile.
+    pub antispam: Option<AntiSpamProfile>,
+}
+
+impl RedisModel for PendingSetup {
+    type Id = (Id<GuildMarker>, Id<UserMarker>);
+
+    // Setup wizards expire after 15 minutes, which is also the lifetime of
+    // the interaction token used to update the message.
+    const EXPIRES_AFTER: Option<usize> = Some(15 * 60);
+
+    fn key(&self) -> String {
+        Self::key_from(&(self.guild_id, self.author_id))
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!(
+            "pending:setup:{guild}:{author}",
+            guild = id.0.get(),
+            author = id.1.get()
+        )
+    }
+}
+
+/// Report of an error that occurred while processing an interaction.
+///
+/// The report is used by the "Get help" button of the error message to open a
+/// thread in the support server.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorReport {
+    /// Error reference (ID of the failed interaction).
+    #[serde_as(as = "IdAsU64")]
+    pub reference: Id<InteractionMarker>,
+    /// Guild where the error occurred.
+    #[serde_as(as = "Option<IdAsU64>")]
+    pub guild_id: Option<Id<GuildMarker>>,
+    /// User that triggered the interaction.
+    #[serde_as(as = "Option<IdAsU64>")]
+    pub user_id: Option<Id<UserMarker>>,
+    /// Name of the command or component that failed.
+    pub name: Option<String>,
+    /// Code of the error shown to the user.
+    ///
+    /// The error itself is only logged, with the reference.
+    #[serde(default)]
+    pub code: String,
+    /// Link to the help thread created for this error, if any.
+    pub thread_url: Option<String>,
+}
+
+impl RedisModel for ErrorReport {
+    type Id = str;
+
+    // Error reports expires after 1 hour
+    const EXPIRES_AFTER: Option<usize> = Some(60 * 60);
+
+    fn key(&self) -> String {
+        Self::key_from(&self.reference.to_string())
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("pending:error-report:{id}")
+    }
+}
+
+/// State for the "ban accounts" button of a raid alert.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingRaidAlert {
+    /// Id of the message that triggered the alert.
+    #[serde_as(as = "IdAsU64")]
+    pub message_id: Id<MessageMarker>,
+    /// Id of the guild.
+    #[serde_as(as = "IdAsU64")]
+    pub guild_id: Id<GuildMarker>,
+    /// Accounts that participated in the raid.
+    #[serde_as(as = "Vec<IdAsU64>")]
+    pub accounts: Vec<Id<UserMarker>>,
+}
+
+impl RedisModel for PendingRaidAlert {
+    type Id = str;
+
+    // Raid alerts expires after 1 day
+    const EXPIRES_AFTER: Option<usize> = Some(24 * 60 * 60);
+
+    fn key(&self) -> String {
+        Self::key_from(&self.message_id.to_string())
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("pending:raid-alert:{id}")
+    }
+}
+
+/// State for the "false positive" button of anti-spam decision logs.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingSpamDecision {
+    /// Id of the message detected as spam.
+    #[serde_as(as = "IdAsU64")]
+    pub message_id: Id<MessageMarker>,
+    /// Id of the guild.
+    #[serde_as(as = "IdAsU64")]
+    pub guild_id: Id<GuildMarker>,
+    /// Member punished by the anti-spam.
+    #[serde_as(as = "IdAsU64")]
+    pub user_id: Id<UserMarker>,
+    /// Identifiers of the detectors that fired.
+    pub rules: Vec<String>,
+    /// Punishment applied to the member.
+    pub punishment: AntiSpamPunishment,
+}
+
+impl RedisModel for PendingSpamDecision {
+    type Id = str;
+
+    // Decisions can be reported for 7 days
+    const EXPIRES_AFTER: Option<usize> = Some(7 * 24 * 60 * 60);
+
+    fn key(&self) -> String {
+        Self::key_from(&self.message_id.to_string())
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("pending:spam-decision:{id}")
+    }
+}
+
+/// State of a running captcha migration.
+///
+/// This is used to prevent the migration from being started twice in the same
+/// guild.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingCaptchaMigration {
+    /// Id of the guild.
+    #[serde_as(as = "IdAsU64")]
+    pub guild_id: Id<GuildMarker>,
+}
+
+impl RedisModel for PendingCaptchaMigration {
+    type Id = Id<GuildMarker>;
+
+    // Migrations are expected to be completed in less than 1 hour. The state
+    // expires in case the migration is interrupted by a restart.
+    const EXPIRES_AFTER: Option<usize> = Some(60 * 60);
+
+    fn key(&self) -> String {
+        Self::key_from(&self.guild_id)
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("pending:captcha-migration:{id}")
+    }
+}
+
+/// Hash of the commands registered to the Discord API.
+///
+/// The bot only registers its commands when their hash changed since the last
+/// registration. The `scope` is either `global` or the id of the guild the
+/// commands are registered in. This model does not expire.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RegisteredCommands {
+    /// Scope of the commands.
+    pub scope: String,
+    /// Hash of the registered commands.
+    pub hash: String,
+}
+
+impl RegisteredCommands {
+    /// Pattern matching the keys of all the registered commands.
+    pub const KEY_PATTERN: &'static str = "registered-commands:*";
+}
+
+impl RedisModel for RegisteredCommands {
+    type Id = str;
+
+    fn key(&self) -> String {
+        Self::key_from(&self.scope)
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("registered-commands:{id}")
+    }
+}
@@ -0,0 +1,48 @@
+//! Join sources of members.
+
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use twilight_model::id::{
+    marker::{GuildMarker, UserMarker},
+    Id,
+};
+
+use crate::{
+    cache::{discord::CachedInvite, RedisModel},
+    serde::IdAsU64,
+};
+
+/// Invite used by a member to join a guild.
+///
+/// The join source is only kept for a short time, to find the invites used
+/// during join raids.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MemberJoinSource {
+    /// Id of the guild.
+    #[serde_as(as = "IdAsU64")]
+    pub guild_id: Id<GuildMarker>,
+    /// Id of the member.
+    #[serde_as(as = "IdAsU64")]
+    pub user_id: Id<UserMarker>,
+    /// Invite used by the member.
+    pub invite: CachedInvite,
+}
+
+impl RedisModel for MemberJoinSource {
+    type Id = (Id<GuildMarker>, Id<UserMarker>);
+
+    const EXPIRES_AFTER: Option<usize> = Some(60 * 60);
+
+    fn key(&self) -> String {
+        Self::key_from(&(self.guild_id, self.user_id))
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!(
+            "join-source:{guild}:{user}",
+            guild = id.0.get(),
+            user = id.1.get()
+        )
+    }
+}
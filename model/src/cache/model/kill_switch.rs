@@ -0,0 +1,49 @@
+//! Kill switches.
+
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use time::OffsetDateTime;
+use twilight_model::id::{marker::UserMarker, Id};
+
+use crate::{
+    cache::RedisModel,
+    serde::{DateTimeAsI64, IdAsU64},
+};
+
+/// Kill switch disabling a command or an automation globally.
+///
+/// Kill switches are enabled by the bot owners when a bug is discovered in
+/// production. The `target` is prefixed by the kind of disabled feature, such
+/// as `command:kick` or `automation:antispam`. This model does not expire, the
+/// kill switch must be disabled manually.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KillSwitch {
+    /// Disabled command or automation.
+    pub target: String,
+    /// Reason shown in the maintenance notice.
+    pub reason: Option<String>,
+    /// Owner who enabled the kill switch.
+    #[serde_as(as = "IdAsU64")]
+    pub enabled_by: Id<UserMarker>,
+    /// Time at which the kill switch was enabled.
+    #[serde_as(as = "DateTimeAsI64")]
+    pub enabled_at: OffsetDateTime,
+}
+
+impl KillSwitch {
+    /// Pattern matching the keys of all the kill switches.
+    pub const KEY_PATTERN: &'static str = "kill-switch:*";
+}
+
+impl RedisModel for KillSwitch {
+    type Id = str;
+
+    fn key(&self) -> String {
+        Self::key_from(&self.target)
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("kill-switch:{id}")
+    }
+}
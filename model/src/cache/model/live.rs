@@ -0,0 +1,103 @@
+//! Live events of the dashboard.
+//!
+//! The bot appends [`LiveEvent`]s to a Redis stream per guild (see
+//! [`LiveEvent::stream_key`]), which is read by the web service to send the
+//! events to the dashboard over WebSocket. Streams are trimmed to
+//! [`LiveEvent::MAX_LEN`] entries and expire [`LiveEvent::EXPIRES_AFTER`]
+//! seconds after the last event.
+//!
+//! Unlike other cached models, ids are serialized as strings since events are
+//! forwarded as JSON to the dashboard.
+
+use serde::{Deserialize, Serialize};
+use twilight_model::id::{
+    marker::{GuildMarker, UserMarker},
+    Id,
+};
+
+use crate::database::model::ModlogType;
+
+/// Event streamed to the dashboard.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveEvent {
+    /// A sanction has been recorded in the moderation logs.
+    Sanction {
+        kind: ModlogType,
+        user_id: Id<UserMarker>,
+        moderator_id: Id<UserMarker>,
+        reason: Option<String>,
+    },
+    /// A raid has been detected.
+    RaidAlert {
+        kind: RaidAlertKind,
+        /// Number of accounts that participated in the raid.
+        accounts: usize,
+    },
+    /// A member completed or failed the captcha.
+    Captcha {
+        user_id: Id<UserMarker>,
+        outcome: CaptchaOutcome,
+    },
+}
+
+/// Kind of detected raid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RaidAlertKind {
+    /// Similar messages sent by many accounts.
+    Messages,
+    /// Many accounts joining the guild.
+    Joins,
+}
+
+/// Outcome of a captcha verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptchaOutcome {
+    /// The member completed the captcha and has been verified.
+    Passed,
+    /// The member entered an invalid code.
+    Failed,
+}
+
+impl LiveEvent {
+    /// Name of the stream entries field containing the event.
+    pub const FIELD: &'static str = "event";
+
+    /// Approximate maximum number of events kept in a stream.
+    pub const MAX_LEN: usize = 100;
+
+    /// Expiration of a stream after the last event (in seconds).
+    pub const EXPIRES_AFTER: usize = 60 * 60;
+
+    /// Key of the stream of a guild.
+    pub fn stream_key(guild_id: Id<GuildMarker>) -> String {
+        format!("live:{guild_id}")
+    }
+
+    /// Serialize the event in MessagePack.
+    pub fn encode(&self) -> Result<Vec<u8>, anyhow::Error> {
+        Ok(rmp_serde::to_vec_named(self)?)
+    }
+
+    /// Deserialize an event serialized with [`encode`](Self::encode).
+    pub fn decode(value: &[u8]) -> Result<Self, anyhow::Error> {
+        Ok(rmp_serde::from_slice(value)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        let event = LiveEvent::Captcha {
+            user_id: Id::new(1),
+            outcome: CaptchaOutcome::Passed,
+        };
+
+        assert_eq!(LiveEvent::decode(&event.encode().unwrap()).unwrap(), event);
+    }
+}
@@ -5,7 +5,7 @@ use serde_with::serde_as;
 use twilight_model::{
     channel::Attachment,
     id::{
-        marker::{ChannelMarker, MessageMarker, RoleMarker, UserMarker},
+        marker::{ChannelMarker, GuildMarker, MessageMarker, RoleMarker, UserMarker},
         Id,
     },
     util::Timestamp,
@@ -13,13 +13,18 @@ use twilight_model::{
 use url::Url;
 
 use crate::{
-    cache::RedisModel,
+    cache::{migrate::KEY_PREFIX, RedisModel},
     serde::{IdAsU64, TimestampAsI64},
 };
 
 /// Cached model of a [`Message`].
 ///
+/// Guild messages are cached with [`CacheClient::set_message`] to be indexed
+/// by author (see the [`search`] module).
+///
 /// [`Message`]: twilight_model::channel::message::Message
+/// [`CacheClient::set_message`]: crate::cache::CacheClient::set_message
+/// [`search`]: crate::cache::search
 #[serde_as]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct CachedMessage {
@@ -67,7 +72,100 @@ impl RedisModel for CachedMessage {
     }
 
     fn key_from(id: &Self::Id) -> String {
-        format!("c:msg:{id}")
+        format!("{KEY_PREFIX}:msg:{id}")
+    }
+}
+
+/// Number of recent mention abuses of a member.
+///
+/// Mention abuses are ghost pings (messages with mentions deleted shortly
+/// after being sent) and mass pings of hoisted roles. The count is reset after
+/// 24 hours without any abuse.
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MentionOffenses {
+    /// Id of the guild.
+    #[serde_as(as = "IdAsU64")]
+    pub guild_id: Id<GuildMarker>,
+    /// Id of the member.
+    #[serde_as(as = "IdAsU64")]
+    pub user_id: Id<UserMarker>,
+    /// Number of mention abuses.
+    pub count: u8,
+}
+
+impl RedisModel for MentionOffenses {
+    type Id = (Id<GuildMarker>, Id<UserMarker>);
+
+    const EXPIRES_AFTER: Option<usize> = Some(24 * 60 * 60);
+
+    fn key(&self) -> String {
+        Self::key_from(&(self.guild_id, self.user_id))
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("mention-offenses:{}:{}", id.0, id.1)
+    }
+}
+
+/// Number of recent invite filter warnings of a member.
+///
+/// The count is reset after 24 hours without any warning.
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct InviteWarnings {
+    /// Id of the guild.
+    #[serde_as(as = "IdAsU64")]
+    pub guild_id: Id<GuildMarker>,
+    /// Id of the member.
+    #[serde_as(as = "IdAsU64")]
+    pub user_id: Id<UserMarker>,
+    /// Number of warnings.
+    pub count: u8,
+}
+
+impl RedisModel for InviteWarnings {
+    type Id = (Id<GuildMarker>, Id<UserMarker>);
+
+    const EXPIRES_AFTER: Option<usize> = Some(24 * 60 * 60);
+
+    fn key(&self) -> String {
+        Self::key_from(&(self.guild_id, self.user_id))
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("invite-warnings:{}:{}", id.0, id.1)
+    }
+}
+
+/// Invite resolved using the Discord API.
+///
+/// Resolved invites are cached to avoid requesting the same invite each time
+/// it is sent.
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedInvite {
+    /// Code of the invite.
+    pub code: String,
+    /// Id of the guild the invite points to.
+    ///
+    /// This is [`None`] if the invite is invalid or expired.
+    #[serde_as(as = "Option<IdAsU64>")]
+    pub guild_id: Option<Id<GuildMarker>>,
+}
+
+impl RedisModel for ResolvedInvite {
+    type Id = str;
+
+    // Resolved invites expires after 1 hour
+    const EXPIRES_AFTER: Option<usize> = Some(60 * 60);
+
+    fn key(&self) -> String {
+        Self::key_from(&self.code)
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("{KEY_PREFIX}:invite:{id}")
     }
 }
 
@@ -0,0 +1,43 @@
+//! Metrics snapshots.
+
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use time::OffsetDateTime;
+
+use crate::{cache::RedisModel, metrics::MetricFamily, serde::DateTimeAsI64};
+
+/// Snapshot of the metrics of a bot process.
+///
+/// Snapshots are published periodically by the bot processes, and exposed by
+/// the web service on the `/metrics` route. This model expires after 90
+/// seconds, so that the metrics of a stopped process are no longer exposed.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProcessMetrics {
+    /// Name of the process, added as a label to the metrics.
+    pub process: String,
+    /// Metrics of the process.
+    pub metrics: Vec<MetricFamily>,
+    /// Time at which the snapshot was published.
+    #[serde_as(as = "DateTimeAsI64")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl ProcessMetrics {
+    /// Pattern matching the keys of all the snapshots.
+    pub const KEY_PATTERN: &'static str = "metrics:*";
+}
+
+impl RedisModel for ProcessMetrics {
+    type Id = str;
+
+    const EXPIRES_AFTER: Option<usize> = Some(90);
+
+    fn key(&self) -> String {
+        Self::key_from(&self.process)
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("metrics:{id}")
+    }
+}
@@ -5,5 +5,15 @@
 //!
 //! [`discord`]: super::discord
 
+pub mod channel;
+pub mod config;
+pub mod gateway;
 pub mod interaction;
+pub mod join;
+pub mod kill_switch;
+pub mod live;
 pub mod message;
+pub mod metrics;
+pub mod quarantine;
+pub mod rpc;
+pub mod session;
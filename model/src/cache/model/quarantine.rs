@@ -0,0 +1,57 @@
+//! Quarantined members.
+
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use time::OffsetDateTime;
+use twilight_model::id::{
+    marker::{GuildMarker, RoleMarker, UserMarker},
+    Id,
+};
+
+use crate::{
+    cache::RedisModel,
+    serde::{DateTimeAsI64, IdAsU64},
+};
+
+/// Member placed in quarantine.
+///
+/// The roles removed from the member when quarantined are saved to be
+/// restored when the member is released.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QuarantinedMember {
+    /// Id of the guild.
+    #[serde_as(as = "IdAsU64")]
+    pub guild_id: Id<GuildMarker>,
+    /// Id of the quarantined member.
+    #[serde_as(as = "IdAsU64")]
+    pub user_id: Id<UserMarker>,
+    /// Roles removed from the member.
+    #[serde_as(as = "Vec<IdAsU64>")]
+    pub roles: Vec<Id<RoleMarker>>,
+    /// Reason of the quarantine.
+    pub reason: String,
+    /// Time at which the member was quarantined.
+    #[serde_as(as = "DateTimeAsI64")]
+    pub quarantined_at: OffsetDateTime,
+}
+
+impl RedisModel for QuarantinedMember {
+    type Id = (Id<GuildMarker>, Id<UserMarker>);
+
+    // Quarantined members are expected to be reviewed by a moderator. The
+    // saved roles expire after 30 days to avoid keeping them forever.
+    const EXPIRES_AFTER: Option<usize> = Some(30 * 24 * 60 * 60);
+
+    fn key(&self) -> String {
+        Self::key_from(&(self.guild_id, self.user_id))
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!(
+            "quarantine:{guild}:{user}",
+            guild = id.0.get(),
+            user = id.1.get()
+        )
+    }
+}
@@ -0,0 +1,108 @@
+//! Remote actions requested by other services.
+//!
+//! Services that are not connected to the gateway (such as the web service)
+//! ask the bot to perform actions by publishing a [`RpcRequest`] on the
+//! [`RpcRequest::CHANNEL`] Redis channel. The request is handled by the bot
+//! process running the shard of the guild, which publishes a [`RpcResponse`]
+//! on the reply channel of the request (see [`RpcRequest::reply_channel`]).
+//!
+//! Callers are authorized by the service before sending the request, and the
+//! bot checks the permissions of the user that requested the action.
+
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use twilight_model::id::{
+    marker::{GuildMarker, UserMarker},
+    Id,
+};
+
+use crate::serde::IdAsU64;
+
+/// Action requested to the bot.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpcRequest {
+    /// Unique id of the request, used for the reply channel.
+    pub id: String,
+    /// Guild where the action is performed.
+    #[serde_as(as = "IdAsU64")]
+    pub guild_id: Id<GuildMarker>,
+    /// Caller that requested the action.
+    pub caller: RpcCaller,
+    /// Action to perform.
+    pub action: RpcAction,
+}
+
+impl RpcRequest {
+    /// Name of the Redis channel.
+    pub const CHANNEL: &'static str = "rpc:requests";
+
+    /// Name of the Redis channel the response is published on.
+    pub fn reply_channel(&self) -> String {
+        format!("rpc:reply:{}", self.id)
+    }
+}
+
+/// Caller of a [`RpcRequest`].
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RpcCaller {
+    /// User logged in on the dashboard.
+    User(#[serde_as(as = "IdAsU64")] Id<UserMarker>),
+    /// Third-party integration using an API key.
+    ApiKey {
+        /// Name of the API key.
+        name: String,
+        /// User that created the API key.
+        #[serde_as(as = "IdAsU64")]
+        created_by: Id<UserMarker>,
+    },
+}
+
+impl RpcCaller {
+    /// Id of the user responsible for the action.
+    pub fn user_id(&self) -> Id<UserMarker> {
+        match self {
+            RpcCaller::User(user_id) => *user_id,
+            RpcCaller::ApiKey { created_by, .. } => *created_by,
+        }
+    }
+}
+
+/// Action of a [`RpcRequest`].
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RpcAction {
+    /// Ban a user from the guild.
+    Ban {
+        #[serde_as(as = "IdAsU64")]
+        user_id: Id<UserMarker>,
+        reason: Option<String>,
+    },
+    /// Drop the values computed from the guild configuration, so they are
+    /// computed again with the latest configuration.
+    InvalidateCache,
+}
+
+/// Response to a [`RpcRequest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpcResponse {
+    /// Id of the request.
+    pub id: String,
+    /// Result of the action.
+    pub result: Result<(), RpcError>,
+}
+
+/// Error returned when a [`RpcRequest`] failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcError {
+    /// The caller is missing permissions to perform the action.
+    Forbidden,
+    /// The bot is missing permissions to perform the action.
+    BotMissingPermissions,
+    /// The target of the action is invalid (e.g. the guild owner).
+    InvalidTarget,
+    /// An error occurred while performing the action.
+    Internal,
+}
@@ -0,0 +1,63 @@
+//! Sessions of the web dashboard.
+//!
+//! Dashboard users log in with Discord OAuth2. The state of the authorization
+//! request is stored in [`OAuthState`] until the user is redirected back to
+//! the dashboard, then a [`WebSession`] is created and its token is sent in a
+//! cookie.
+
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use twilight_model::id::{marker::UserMarker, Id};
+
+use crate::{cache::RedisModel, serde::IdAsU64};
+
+/// Session of a user logged in the web dashboard.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebSession {
+    /// Token stored in the session cookie.
+    pub token: String,
+    /// Id of the logged in user.
+    #[serde_as(as = "IdAsU64")]
+    pub user_id: Id<UserMarker>,
+}
+
+impl RedisModel for WebSession {
+    type Id = str;
+
+    // Sessions expires after 7 days.
+    const EXPIRES_AFTER: Option<usize> = Some(7 * 24 * 60 * 60);
+
+    fn key(&self) -> String {
+        Self::key_from(&self.token)
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("session:web:{id}")
+    }
+}
+
+/// State of a pending OAuth2 authorization request.
+///
+/// The state is sent to Discord with the authorization request and checked
+/// when the user is redirected back, to prevent CSRF attacks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthState {
+    /// Random state sent with the authorization request.
+    pub state: String,
+}
+
+impl RedisModel for OAuthState {
+    type Id = str;
+
+    // Pending authorizations expires after 10 minutes.
+    const EXPIRES_AFTER: Option<usize> = Some(10 * 60);
+
+    fn key(&self) -> String {
+        Self::key_from(&self.state)
+    }
+
+    fn key_from(id: &Self::Id) -> String {
+        format!("pending:oauth-state:{id}")
+    }
+}
@@ -0,0 +1,252 @@
+//! Search of cached messages.
+//!
+//! Cached messages (see [`CachedMessage`]) are indexed by guild and author,
+//! so that the recent messages of a member can be attached as evidence to
+//! moderation logs. The index of each member is a Redis sorted set of message
+//! ids scored by their timestamp, which is limited to the last
+//! [`MAX_INDEXED_MESSAGES`] messages and expires with them.
+//!
+//! Messages must be cached with [`CacheClient::set_message`] to be indexed,
+//! and are queried with [`CacheClient::search_messages`].
+//!
+//! Deleted messages can be kept longer than the cache expiration with
+//! [`CacheClient::retain_messages`], so that their content can still be
+//! recovered after a raid cleanup.
+
+use redis::AsyncCommands;
+use tracing::{instrument, trace};
+use twilight_model::{
+    id::{
+        marker::{ChannelMarker, GuildMarker, MessageMarker, UserMarker},
+        Id,
+    },
+    util::Timestamp,
+};
+
+use super::{migrate::KEY_PREFIX, model::message::CachedMessage, CacheClient, RedisModel};
+
+/// Maximum number of indexed messages per member.
+pub const MAX_INDEXED_MESSAGES: isize = 50;
+
+/// Default maximum number of messages returned by a [`MessageQuery`].
+const DEFAULT_LIMIT: usize = 10;
+
+/// Query of cached messages.
+///
+/// Messages are always searched by guild and author, and can be filtered by
+/// channel and by time range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageQuery {
+    /// Id of the guild.
+    pub guild_id: Id<GuildMarker>,
+    /// Id of the message author.
+    pub author_id: Id<UserMarker>,
+    /// Only return messages sent in this channel.
+    pub channel_id: Option<Id<ChannelMarker>>,
+    /// Only return messages sent at or after this time.
+    pub after: Option<Timestamp>,
+    /// Only return messages sent at or before this time.
+    pub before: Option<Timestamp>,
+    /// Maximum number of returned messages.
+    pub limit: usize,
+}
+
+impl MessageQuery {
+    /// Query the messages of an author in a guild.
+    pub fn author(guild_id: Id<GuildMarker>, author_id: Id<UserMarker>) -> Self {
+        Self {
+            guild_id,
+            author_id,
+            channel_id: None,
+            after: None,
+            before: None,
+            limit: DEFAULT_LIMIT,
+        }
+    }
+
+    /// Only return messages sent in a channel.
+    pub fn channel(mut self, channel_id: Id<ChannelMarker>) -> Self {
+        self.channel_id = Some(channel_id);
+        self
+    }
+
+    /// Only return messages sent at or after a time.
+    pub fn after(mut self, after: Timestamp) -> Self {
+        self.after = Some(after);
+        self
+    }
+
+    /// Only return messages sent at or before a time.
+    pub fn before(mut self, before: Timestamp) -> Self {
+        self.before = Some(before);
+        self
+    }
+
+    /// Set the maximum number of returned messages.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Whether a message matches the query filters.
+    ///
+    /// The guild is not checked since [`CachedMessage`] does not store it.
+    pub fn matches(&self, message: &CachedMessage) -> bool {
+        let timestamp = message.timestamp.as_micros();
+
+        message.author_id == self.author_id
+            && self.channel_id.is_none_or(|id| message.channel_id == id)
+            && self
+                .after
+                .is_none_or(|after| timestamp >= after.as_micros())
+            && self
+                .before
+                .is_none_or(|before| timestamp <= before.as_micros())
+    }
+}
+
+/// Key of the messages index of a member.
+fn index_key(guild_id: Id<GuildMarker>, author_id: Id<UserMarker>) -> String {
+    format!("{KEY_PREFIX}:msg-index:{guild_id}:{author_id}")
+}
+
+impl CacheClient {
+    /// Cache a message sent in a guild.
+    ///
+    /// The message is added to the index of its author to be returned by
+    /// [`search_messages`](Self::search_messages).
+    #[instrument(skip(self))]
+    pub async fn set_message(
+        &self,
+        guild_id: Id<GuildMarker>,
+        message: &CachedMessage,
+    ) -> Result<(), anyhow::Error> {
+        let index = index_key(guild_id, message.author_id);
+        let mut pipe = redis::pipe();
+
+        self.pipe_set(&mut pipe, message)?;
+        pipe.zadd(&index, message.id.get(), message.timestamp.as_micros())
+            .ignore()
+            .zremrangebyrank(&index, 0, -(MAX_INDEXED_MESSAGES + 1))
+            .ignore();
+
+        // The index expires with the most recent message. Expired messages
+        // still in the index are skipped when searching.
+        if let Some(expires_after) = self.ttl().expires_after::<CachedMessage>() {
+            pipe.expire(&index, expires_after).ignore();
+        }
+
+        let mut conn = self.conn().await?;
+        pipe.query_async(&mut *conn).await?;
+
+        Ok(())
+    }
+
+    /// Search cached messages.
+    ///
+    /// Messages are returned from the most recent to the oldest.
+    #[instrument(skip(self))]
+    pub async fn search_messages(
+        &self,
+        query: &MessageQuery,
+    ) -> Result<Vec<CachedMessage>, anyhow::Error> {
+        let index = index_key(query.guild_id, query.author_id);
+        let ids: Vec<u64> = self.conn().await?.zrevrange(&index, 0, -1).await?;
+
+        let ids: Vec<Id<MessageMarker>> = ids.into_iter().filter_map(Id::new_checked).collect();
+        trace!("found {} indexed messages", ids.len());
+
+        let messages = self.get_many::<CachedMessage>(&ids).await?;
+
+        Ok(messages
+            .into_iter()
+            .flatten()
+            .filter(|message| query.matches(message))
+            .take(query.limit)
+            .collect())
+    }
+
+    /// Keep cached messages for `expires_after` seconds.
+    ///
+    /// This is used to recover the content of deleted messages after the
+    /// default expiration of cached messages. The messages still in the cache
+    /// are returned.
+    #[instrument(skip(self))]
+    pub async fn retain_messages(
+        &self,
+        ids: &[Id<MessageMarker>],
+        expires_after: usize,
+    ) -> Result<Vec<CachedMessage>, anyhow::Error> {
+        let messages: Vec<_> = self
+            .get_many::<CachedMessage>(ids)
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        if !messages.is_empty() {
+            let mut pipe = redis::pipe();
+            for message in &messages {
+                pipe.expire(message.key(), expires_after).ignore();
+            }
+
+            let mut conn = self.conn().await?;
+            pipe.query_async(&mut *conn).await?;
+        }
+
+        Ok(messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(channel_id: u64, secs: i64) -> CachedMessage {
+        CachedMessage {
+            id: Id::new(1),
+            author_id: Id::new(2),
+            channel_id: Id::new(channel_id),
+            content: "hello".to_owned(),
+            timestamp: timestamp(secs),
+            words: Vec::new(),
+            attachments: Vec::new(),
+            links: Vec::new(),
+            mention_everyone: false,
+            mention_users: Vec::new(),
+            mention_roles: Vec::new(),
+        }
+    }
+
+    fn timestamp(secs: i64) -> Timestamp {
+        Timestamp::from_secs(1_600_000_000 + secs).unwrap()
+    }
+
+    #[test]
+    fn test_matches_author() {
+        let query = MessageQuery::author(Id::new(1), Id::new(2));
+
+        assert!(query.matches(&message(3, 0)));
+        assert!(!MessageQuery::author(Id::new(1), Id::new(3)).matches(&message(3, 0)));
+    }
+
+    #[test]
+    fn test_matches_channel() {
+        let query = MessageQuery::author(Id::new(1), Id::new(2)).channel(Id::new(3));
+
+        assert!(query.matches(&message(3, 0)));
+        assert!(!query.matches(&message(4, 0)));
+    }
+
+    #[test]
+    fn test_matches_time_range() {
+        let query = MessageQuery::author(Id::new(1), Id::new(2))
+            .after(timestamp(10))
+            .before(timestamp(20));
+
+        assert!(!query.matches(&message(3, 9)));
+        assert!(query.matches(&message(3, 10)));
+        assert!(query.matches(&message(3, 20)));
+        assert!(!query.matches(&message(3, 21)));
+    }
+}
@@ -0,0 +1,172 @@
+//! Snapshot of the cache.
+//!
+//! This module allows saving the content of the cache to a file with
+//! [`export`] and loading it back with [`import`]. It is intended to be used
+//! during development, to replay a production-like cache state locally or in
+//! integration tests.
+//!
+//! All the keys of the Redis database are included in the snapshot, which
+//! assumes the database is dedicated to RaidProtect. Values are serialized with
+//! the Redis `DUMP` command, so a snapshot can only be imported in a Redis
+//! server with a compatible version. The remaining time to live of each key is
+//! preserved.
+//!
+//! Snapshots are bound to the cache version (see [`migrate`](super::migrate)),
+//! and cannot be imported after the version changed.
+
+use std::path::Path;
+
+use anyhow::bail;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, Bytes};
+use tracing::{info, instrument};
+
+use super::{migrate::KEY_PREFIX, CacheClient};
+
+/// Number of keys exported or imported per pipeline.
+const BATCH_SIZE: usize = 1_000;
+
+/// Snapshot of the cache.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Version of the cache when the snapshot was taken.
+    pub version: String,
+    /// Entries of the snapshot.
+    pub entries: Vec<SnapshotEntry>,
+}
+
+/// Single key of a [`Snapshot`].
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    /// Key of the entry.
+    pub key: String,
+    /// Remaining time to live of the key, in milliseconds.
+    pub ttl: Option<u64>,
+    /// Value serialized with the `DUMP` command.
+    #[serde_as(as = "Bytes")]
+    pub value: Vec<u8>,
+}
+
+impl Snapshot {
+    /// Serialize the snapshot using [`rmp_serde`].
+    pub fn encode(&self) -> Result<Vec<u8>, anyhow::Error> {
+        Ok(rmp_serde::to_vec_named(self)?)
+    }
+
+    /// Deserialize a snapshot using [`rmp_serde`].
+    pub fn decode(value: &[u8]) -> Result<Self, anyhow::Error> {
+        Ok(rmp_serde::from_slice(value)?)
+    }
+}
+
+/// Save the content of the cache to a file.
+///
+/// Returns the number of exported keys.
+#[instrument(skip(cache, path))]
+pub async fn export(cache: &CacheClient, path: impl AsRef<Path>) -> Result<usize, anyhow::Error> {
+    let mut conn = cache.conn().await?;
+
+    let mut keys = Vec::new();
+    let mut iter = conn.scan::<String>().await?;
+    while let Some(key) = iter.next_item().await {
+        keys.push(key);
+    }
+    drop(iter);
+
+    let mut entries = Vec::with_capacity(keys.len());
+    for keys in keys.chunks(BATCH_SIZE) {
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.cmd("DUMP").arg(key).cmd("PTTL").arg(key);
+        }
+
+        let values: Vec<(Option<Vec<u8>>, i64)> = pipe.query_async(&mut *conn).await?;
+
+        for (key, (value, ttl)) in keys.iter().zip(values) {
+            // The key may have expired since it was scanned.
+            if let Some(value) = value {
+                entries.push(SnapshotEntry {
+                    key: key.clone(),
+                    ttl: u64::try_from(ttl).ok(),
+                    value,
+                });
+            }
+        }
+    }
+
+    let snapshot = Snapshot {
+        version: KEY_PREFIX.to_owned(),
+        entries,
+    };
+    tokio::fs::write(path, snapshot.encode()?).await?;
+
+    info!("exported {} keys from the cache", snapshot.entries.len());
+
+    Ok(snapshot.entries.len())
+}
+
+/// Load the content of the cache from a file.
+///
+/// Keys already in the cache are replaced. Returns the number of imported keys.
+#[instrument(skip(cache, path))]
+pub async fn import(cache: &CacheClient, path: impl AsRef<Path>) -> Result<usize, anyhow::Error> {
+    let snapshot = Snapshot::decode(&tokio::fs::read(path).await?)?;
+
+    if snapshot.version != KEY_PREFIX {
+        bail!(
+            "snapshot version {} does not match cache version {}",
+            snapshot.version,
+            KEY_PREFIX
+        );
+    }
+
+    let mut conn = cache.conn().await?;
+
+    for entries in snapshot.entries.chunks(BATCH_SIZE) {
+        let mut pipe = redis::pipe();
+        for entry in entries {
+            pipe.cmd("RESTORE")
+                .arg(&entry.key)
+                .arg(entry.ttl.unwrap_or(0))
+                .arg(&entry.value[..])
+                .arg("REPLACE")
+                .ignore();
+        }
+
+        pipe.query_async::<_, ()>(&mut *conn).await?;
+    }
+
+    info!("imported {} keys into the cache", snapshot.entries.len());
+
+    Ok(snapshot.entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let snapshot = Snapshot {
+            version: KEY_PREFIX.to_owned(),
+            entries: vec![
+                SnapshotEntry {
+                    key: format!("{KEY_PREFIX}:guild:1"),
+                    ttl: None,
+                    value: vec![0, 1, 2, 255],
+                },
+                SnapshotEntry {
+                    key: "slowmode:2".to_owned(),
+                    ttl: Some(60_000),
+                    value: Vec::new(),
+                },
+            ],
+        };
+
+        let encoded = snapshot.encode().unwrap();
+
+        assert_eq!(Snapshot::decode(&encoded).unwrap(), snapshot);
+    }
+}
@@ -0,0 +1,202 @@
+//! Cache statistics.
+//!
+//! The [`CacheClient`] counts cache hits, misses, inserts and evictions for
+//! each model (see [`CacheStats`]). These counters are local to the process
+//! and reset when it restarts.
+//!
+//! A [`CacheReport`] can be generated with [`CacheClient::report`] for
+//! operational debugging. It includes the counters and the number of keys and
+//! estimated memory usage of each key group, computed by scanning the whole
+//! database.
+//!
+//! Cache hits and misses are also recorded in the global metrics registry (see
+//! [`metrics`]).
+//!
+//! [`CacheClient`]: super::CacheClient
+//! [`CacheClient::report`]: super::CacheClient::report
+
+use std::{collections::HashMap, sync::Mutex};
+
+use super::RedisModel;
+use crate::metrics;
+
+/// Name of the cache hits metric.
+const CACHE_HITS: &str = "raidprotect_cache_hits_total";
+
+/// Name of the cache misses metric.
+const CACHE_MISSES: &str = "raidprotect_cache_misses_total";
+
+/// Counters of a cached model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModelStats {
+    /// Number of values found in the cache.
+    pub hits: u64,
+    /// Number of values missing from the cache.
+    pub misses: u64,
+    /// Number of values written in the cache.
+    pub inserts: u64,
+    /// Number of values deleted with [`CacheClient::delete`].
+    ///
+    /// Values removed by Redis after their expiration are not counted.
+    ///
+    /// [`CacheClient::delete`]: super::CacheClient::delete
+    pub evictions: u64,
+}
+
+impl ModelStats {
+    /// Proportion of cache reads that found a value.
+    ///
+    /// Returns [`None`] if the model has never been read.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let reads = self.hits + self.misses;
+
+        (reads > 0).then(|| self.hits as f64 / reads as f64)
+    }
+}
+
+/// Counters of all the cached models.
+#[derive(Debug, Default)]
+pub struct CacheStats {
+    models: Mutex<HashMap<&'static str, ModelStats>>,
+}
+
+impl CacheStats {
+    /// Record reads of a model.
+    pub(crate) fn record_reads<T: RedisModel>(&self, hits: u64, misses: u64) {
+        self.record::<T>(|stats| {
+            stats.hits += hits;
+            stats.misses += misses;
+        });
+
+        let labels = [("model", T::name())];
+        let metrics = metrics::registry();
+        metrics.add_counter(
+            CACHE_HITS,
+            "Number of values found in the cache",
+            &labels,
+            hits,
+        );
+        metrics.add_counter(
+            CACHE_MISSES,
+            "Number of values missing from the cache",
+            &labels,
+            misses,
+        );
+    }
+
+    /// Record a write of a model.
+    pub(crate) fn record_insert<T: RedisModel>(&self) {
+        self.record::<T>(|stats| stats.inserts += 1);
+    }
+
+    /// Record a deletion of a model.
+    pub(crate) fn record_eviction<T: RedisModel>(&self) {
+        self.record::<T>(|stats| stats.evictions += 1);
+    }
+
+    fn record<T: RedisModel>(&self, update: impl FnOnce(&mut ModelStats)) {
+        let mut models = self.models.lock().unwrap();
+
+        update(models.entry(T::name()).or_default());
+    }
+
+    /// Get the counters of all models, sorted by model name.
+    pub fn models(&self) -> Vec<(&'static str, ModelStats)> {
+        let models = self.models.lock().unwrap();
+
+        let mut models: Vec<_> = models.iter().map(|(name, stats)| (*name, *stats)).collect();
+        models.sort_by_key(|(name, _)| *name);
+
+        models
+    }
+}
+
+/// Size of a group of keys stored in the cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyGroupStats {
+    /// Common prefix of the keys (see [`key_group`]).
+    pub group: String,
+    /// Number of keys in the group.
+    pub count: u64,
+    /// Estimated memory usage of the group (in bytes).
+    ///
+    /// The estimation is based on the memory usage of a sample of keys.
+    pub memory: u64,
+}
+
+/// Report of the cache state.
+#[derive(Debug, Clone)]
+pub struct CacheReport {
+    /// Counters of the cached models.
+    pub models: Vec<(&'static str, ModelStats)>,
+    /// Size of each key group, sorted by descending memory usage.
+    pub keys: Vec<KeyGroupStats>,
+    /// Total memory used by Redis (in bytes).
+    pub used_memory: Option<u64>,
+}
+
+/// Get the group of a key.
+///
+/// The group is the key without its identifiers: segments are kept until the
+/// first numeric one, with at most three segments (e.g. `c:v1:guild:1234`
+/// belongs to the `c:v1:guild` group).
+pub fn key_group(key: &str) -> String {
+    key.split(':')
+        .take_while(|segment| !segment.bytes().all(|c| c.is_ascii_digit()))
+        .take(3)
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::discord::{CachedGuild, CachedRole};
+
+    #[test]
+    fn test_key_group() {
+        assert_eq!(key_group("c:v1:guild:1234"), "c:v1:guild");
+        assert_eq!(key_group("c:v1:member:1234:5678"), "c:v1:member");
+        assert_eq!(key_group("pending:captcha:1234:5678"), "pending:captcha");
+        assert_eq!(
+            key_group("kill-switch:command:kick"),
+            "kill-switch:command:kick"
+        );
+        assert_eq!(key_group("cache-version"), "cache-version");
+    }
+
+    #[test]
+    fn test_hit_rate() {
+        let mut stats = ModelStats::default();
+        assert_eq!(stats.hit_rate(), None);
+
+        stats.hits = 3;
+        stats.misses = 1;
+        assert_eq!(stats.hit_rate(), Some(0.75));
+    }
+
+    #[test]
+    fn test_record() {
+        let stats = CacheStats::default();
+
+        stats.record_reads::<CachedGuild>(2, 1);
+        stats.record_insert::<CachedGuild>();
+        stats.record_eviction::<CachedRole>();
+
+        let guild = ModelStats {
+            hits: 2,
+            misses: 1,
+            inserts: 1,
+            evictions: 0,
+        };
+        let role = ModelStats {
+            evictions: 1,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            stats.models(),
+            vec![("CachedGuild", guild), ("CachedRole", role)]
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Expiration of cached values.
+//!
+//! Each [`RedisModel`] has a default expiration delay defined with
+//! [`RedisModel::EXPIRES_AFTER`]: Discord objects never expire as they are
+//! kept up-to-date with gateway events, messages are short-lived and
+//! interaction states expire after a few minutes or days.
+//!
+//! These delays can be overridden per-model with the `cache_ttl` configuration
+//! (see [`DatabaseConfig`]), a comma-separated list of `Model=seconds` pairs
+//! where the model is identified by its type name (see
+//! [`RedisModel::name`]). A delay of `0` disables the expiration.
+//!
+//! ```text
+//! RAIDPROTECT_CACHE_TTL=CachedMessage=300,CachedMember=604800
+//! ```
+//!
+//! The expiration is applied every time a value is written, either with
+//! [`CacheClient::set`] or with [`CacheClient::pipe_set`] in pipelines.
+//!
+//! [`DatabaseConfig`]: crate::config::shared::DatabaseConfig
+//! [`CacheClient::set`]: super::CacheClient::set
+//! [`CacheClient::pipe_set`]: super::CacheClient::pipe_set
+
+use std::collections::HashMap;
+
+use redis::Pipeline;
+use serde::{de, Deserialize};
+
+use super::RedisModel;
+
+/// Expiration delays of the cached models.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheTtl {
+    /// Delays overridden by the configuration (in seconds).
+    overrides: HashMap<String, usize>,
+}
+
+impl CacheTtl {
+    /// Parse the expiration delays from the configuration format.
+    pub fn parse(value: &str) -> Result<Self, anyhow::Error> {
+        let mut overrides = HashMap::new();
+
+        for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (model, delay) = entry
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("invalid cache ttl entry `{entry}`"))?;
+            let delay = delay
+                .trim()
+                .parse()
+                .map_err(|_| anyhow::anyhow!("invalid cache ttl delay `{delay}`"))?;
+
+            overrides.insert(model.trim().to_owned(), delay);
+        }
+
+        Ok(Self { overrides })
+    }
+
+    /// Expiration delay of a model (in seconds).
+    ///
+    /// Returns [`None`] if the model never expires.
+    pub fn expires_after<T: RedisModel>(&self) -> Option<usize> {
+        match self.overrides.get(T::name()) {
+            Some(0) => None,
+            Some(delay) => Some(*delay),
+            None => T::EXPIRES_AFTER,
+        }
+    }
+
+    /// Add a command to set a value to a pipeline.
+    ///
+    /// The expiration delay of the model is applied.
+    pub fn pipe_set<T: RedisModel>(
+        &self,
+        pipe: &mut Pipeline,
+        value: &T,
+    ) -> Result<(), anyhow::Error> {
+        match self.expires_after::<T>() {
+            Some(delay) => pipe.set_ex(value.key(), value.serialize_model()?, delay),
+            None => pipe.set(value.key(), value.serialize_model()?),
+        };
+
+        Ok(())
+    }
+}
+
+impl<'de> Deserialize<'de> for CacheTtl {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+
+        Self::parse(&value).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, serde::Deserialize)]
+    struct Message;
+
+    impl RedisModel for Message {
+        type Id = ();
+
+        const EXPIRES_AFTER: Option<usize> = Some(120);
+
+        fn key(&self) -> String {
+            Self::key_from(&())
+        }
+
+        fn key_from(_: &()) -> String {
+            "message".to_owned()
+        }
+    }
+
+    #[derive(Debug, Serialize, serde::Deserialize)]
+    struct Guild;
+
+    impl RedisModel for Guild {
+        type Id = ();
+
+        fn key(&self) -> String {
+            Self::key_from(&())
+        }
+
+        fn key_from(_: &()) -> String {
+            "guild".to_owned()
+        }
+    }
+
+    /// Packed commands of a pipeline setting a value.
+    fn packed<T: RedisModel>(ttl: &CacheTtl, value: &T) -> String {
+        let mut pipe = redis::pipe();
+        ttl.pipe_set(&mut pipe, value).unwrap();
+
+        String::from_utf8_lossy(&pipe.get_packed_pipeline()).into_owned()
+    }
+
+    #[test]
+    fn test_default_ttl() {
+        let ttl = CacheTtl::default();
+
+        assert_eq!(ttl.expires_after::<Message>(), Some(120));
+        assert_eq!(ttl.expires_after::<Guild>(), None);
+    }
+
+    #[test]
+    fn test_override_ttl() {
+        let ttl = CacheTtl::parse("Message=0, Guild=3600").unwrap();
+
+        assert_eq!(ttl.expires_after::<Message>(), None);
+        assert_eq!(ttl.expires_after::<Guild>(), Some(3600));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(CacheTtl::parse("Message").is_err());
+        assert!(CacheTtl::parse("Message=soon").is_err());
+        assert_eq!(CacheTtl::parse("").unwrap(), CacheTtl::default());
+    }
+
+    #[test]
+    fn test_pipe_set_expiration() {
+        let ttl = CacheTtl::parse("Guild=3600").unwrap();
+
+        let message = packed(&ttl, &Message);
+        assert!(message.contains("SETEX") && message.contains("120"));
+
+        let guild = packed(&ttl, &Guild);
+        assert!(guild.contains("SETEX") && guild.contains("3600"));
+
+        let guild = packed(&CacheTtl::default(), &Guild);
+        assert!(guild.contains("SET") && !guild.contains("SETEX"));
+    }
+}
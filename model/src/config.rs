@@ -8,6 +8,13 @@
 use std::net::SocketAddr;
 
 use serde::{de, Deserialize};
+use twilight_model::{
+    gateway::Intents,
+    id::{
+        marker::{ApplicationMarker, ChannelMarker, GuildMarker, UserMarker},
+        Id,
+    },
+};
 
 /// Parse configuration from environment variables.
 ///
@@ -26,6 +33,113 @@ where
 pub struct BotConfig {
     /// Discord bot token.
     pub token: String,
+    /// Public URL of the web service (without trailing slash).
+    ///
+    /// If set, the captcha verification message includes a link to the web
+    /// verification page.
+    pub web_url: Option<String>,
+    /// Channel of the support server where help threads are created.
+    ///
+    /// If set, error messages include a "Get help" button that opens a thread
+    /// in this channel with the error reference.
+    pub support_channel: Option<Id<ChannelMarker>>,
+    /// Ids of the bot owners (comma-separated).
+    ///
+    /// Owners can use the administration commands, such as the kill switches.
+    #[serde(default)]
+    pub owners: Vec<Id<UserMarker>>,
+    /// Guild where the administration commands are registered.
+    ///
+    /// If not set, the administration commands are not available.
+    pub admin_guild: Option<Id<GuildMarker>>,
+    /// Guilds where the beta commands are registered (comma-separated).
+    #[serde(default)]
+    pub beta_guilds: Vec<Id<GuildMarker>>,
+    /// Names of the beta commands (comma-separated).
+    ///
+    /// Beta commands are only registered in the [`beta_guilds`] instead of
+    /// globally.
+    ///
+    /// [`beta_guilds`]: Self::beta_guilds
+    #[serde(default)]
+    pub beta_commands: Vec<String>,
+    /// Number of days the moderation logs are kept after the bot is removed
+    /// from a guild.
+    ///
+    /// Defaults to 90 days.
+    #[serde(default = "default_modlog_retention")]
+    pub modlog_retention_days: u32,
+    /// Number of years after which the moderation logs are moved to the
+    /// archive collection.
+    ///
+    /// Archived moderation logs are only included in exports. Defaults to 2
+    /// years.
+    #[serde(default = "default_modlog_archive")]
+    pub modlog_archive_years: u32,
+    /// Path of a file with additional phishing domains.
+    ///
+    /// The file contains one domain per line and is merged with the built-in
+    /// list. It is reloaded periodically, so it can be updated without
+    /// restarting the bot.
+    pub phishing_domains_path: Option<String>,
+    /// Range of shards started by this process, in the `from-to/total` format
+    /// (for example `0-7/16`).
+    ///
+    /// This is used to split the shards between multiple processes. If not
+    /// set, all the shards recommended by Discord are started.
+    pub shards: Option<ShardRange>,
+    /// Number of shard ranges in coordinator mode.
+    ///
+    /// If set, the shards recommended by Discord are split in this number of
+    /// ranges, and the process claims a free range in the cache instead of
+    /// using `shards`. Processes without a free range wait for one to be
+    /// released, so standby processes can be started to take over the range
+    /// of a stopped process.
+    pub shard_groups: Option<u64>,
+    /// Gateway intents of the shards, as an integer.
+    ///
+    /// The intents can be reduced to lower the number of received events,
+    /// which disables the features depending on them. Defaults to the intents
+    /// required by all the features.
+    pub intents: Option<Intents>,
+    /// Websocket url of a gateway proxy.
+    ///
+    /// If set, the shards connect to this url instead of the Discord gateway.
+    /// The proxy keeps the sessions open when the bot restarts.
+    pub gateway_url: Option<String>,
+    /// Whether the gateway proxy handles the identify rate limit.
+    ///
+    /// If enabled, the shards identify without waiting, which would get the
+    /// bot rate-limited without proxy. Defaults to `false`.
+    #[serde(default)]
+    pub gateway_proxy_identify: bool,
+    /// Measure the size of the gateway payloads.
+    ///
+    /// Payloads are received twice by the cluster when enabled, so this
+    /// should only be enabled temporarily. Defaults to `false`.
+    #[serde(default)]
+    pub gateway_payload_metrics: bool,
+    /// Allow recording raw gateway payloads with the `/debug tap` command.
+    ///
+    /// Like the payload metrics, payloads are received twice by the cluster
+    /// when enabled, even if no tap is running. Defaults to `false`.
+    #[serde(default)]
+    pub event_tap: bool,
+    /// Record the daily usage of the commands in the database.
+    ///
+    /// Usage metrics are always exposed with the other metrics, this only
+    /// keeps a history for capacity planning. Defaults to `false`.
+    #[serde(default)]
+    pub command_usage_stats: bool,
+    /// Number of workers processing the gateway events.
+    ///
+    /// Events of a guild are always processed by the same worker. Defaults to
+    /// 16 workers.
+    #[serde(default = "default_event_workers")]
+    pub event_workers: usize,
+    /// Artifact storage configuration.
+    #[serde(flatten, default)]
+    pub storage: StorageConfig,
     /// Databases configuration.
     #[serde(flatten, default)]
     pub database: shared::DatabaseConfig,
@@ -34,12 +148,82 @@ pub struct BotConfig {
     pub log: shared::LogConfig,
 }
 
+/// Artifact storage configuration model.
+///
+/// Generated artifacts (such as transcripts, exports or backups) are stored
+/// using the configured [`StorageKind`]. See the [`storage`] module for more
+/// information.
+///
+/// [`storage`]: crate::storage
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Backend used to store the artifacts.
+    pub storage_kind: StorageKind,
+    /// Folder used to store artifacts with [`StorageKind::Local`].
+    ///
+    /// Defaults to `artifacts`.
+    pub storage_path: String,
+    /// Public URL where the stored artifacts are served (without trailing
+    /// slash).
+    ///
+    /// If not set, artifacts are sent as attachments.
+    pub storage_url: Option<String>,
+    /// Endpoint of the S3-compatible service (e.g. `https://s3.amazonaws.com`).
+    pub s3_endpoint: Option<String>,
+    /// Name of the S3 bucket.
+    pub s3_bucket: Option<String>,
+    /// Region of the S3 bucket.
+    ///
+    /// Defaults to `us-east-1`.
+    pub s3_region: String,
+    /// S3 access key id.
+    pub s3_access_key: Option<String>,
+    /// S3 secret access key.
+    pub s3_secret_key: Option<String>,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            storage_kind: StorageKind::None,
+            storage_path: "artifacts".to_owned(),
+            storage_url: None,
+            s3_endpoint: None,
+            s3_bucket: None,
+            s3_region: "us-east-1".to_owned(),
+            s3_access_key: None,
+            s3_secret_key: None,
+        }
+    }
+}
+
+/// Backend used to store artifacts.
+#[derive(Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageKind {
+    /// Local disk storage.
+    ///
+    /// Artifacts are written in the folder configured in [`StorageConfig`].
+    Local,
+    /// S3-compatible object storage.
+    S3,
+    /// Artifacts are not stored and are always sent as attachments.
+    None,
+}
+
 /// Base web api configuration model.
 #[derive(Debug, Deserialize, Clone)]
 pub struct WebConfig {
     /// Server listening address.
     #[serde(default = "default_address")]
     pub address: SocketAddr,
+    /// Discord OAuth2 configuration of the dashboard.
+    #[serde(flatten, default)]
+    pub oauth: OAuthConfig,
+    /// Rate limiting configuration.
+    #[serde(flatten, default)]
+    pub ratelimit: RateLimitConfig,
     /// Databases configuration.
     #[serde(flatten, default)]
     pub database: shared::DatabaseConfig,
@@ -48,6 +232,140 @@ pub struct WebConfig {
     pub log: shared::LogConfig,
 }
 
+/// Discord OAuth2 configuration model.
+///
+/// Dashboard users log in with their Discord account. If the client id or
+/// secret is not set, the dashboard routes are disabled.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct OAuthConfig {
+    /// Client id of the Discord application.
+    pub oauth_client_id: Option<Id<ApplicationMarker>>,
+    /// Client secret of the Discord application.
+    pub oauth_client_secret: Option<String>,
+    /// URL users are redirected to after the authorization (must end with
+    /// `/auth/callback` and be registered in the Discord application).
+    pub oauth_redirect_url: Option<String>,
+    /// Whether the session cookie is only sent over HTTPS.
+    ///
+    /// Should only be disabled in development. Defaults to `true`.
+    pub oauth_secure_cookie: bool,
+}
+
+impl Default for OAuthConfig {
+    fn default() -> Self {
+        Self {
+            oauth_client_id: None,
+            oauth_client_secret: None,
+            oauth_redirect_url: None,
+            oauth_secure_cookie: true,
+        }
+    }
+}
+
+/// Rate limiting configuration model of the web service.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// Maximum number of requests per IP address in each window.
+    ///
+    /// Defaults to `120`.
+    pub ratelimit_ip: u64,
+    /// Maximum number of requests per API key in each window.
+    ///
+    /// Defaults to `60`.
+    pub ratelimit_api_key: u64,
+    /// Duration of the rate limit window (in seconds).
+    ///
+    /// Defaults to `60`.
+    pub ratelimit_window: usize,
+    /// Whether the client IP address is read from the `X-Forwarded-For`
+    /// header.
+    ///
+    /// Must only be enabled behind a trusted reverse proxy. Defaults to
+    /// `false`.
+    pub ratelimit_forwarded_for: bool,
+    /// Maximum size of the request bodies (in bytes).
+    ///
+    /// Defaults to 64 KiB.
+    pub max_body_size: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            ratelimit_ip: 120,
+            ratelimit_api_key: 60,
+            ratelimit_window: 60,
+            ratelimit_forwarded_for: false,
+            max_body_size: 64 * 1024,
+        }
+    }
+}
+
+/// Range of shards started by a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShardRange {
+    /// Id of the first shard of the range.
+    pub from: u64,
+    /// Id of the last shard of the range (inclusive).
+    pub to: u64,
+    /// Total number of shards across all processes.
+    pub total: u64,
+}
+
+impl ShardRange {
+    /// Parse a shard range from the `from-to/total` format.
+    pub fn parse(value: &str) -> Result<Self, anyhow::Error> {
+        let (range, total) = value
+            .split_once('/')
+            .ok_or_else(|| anyhow::anyhow!("missing total shards in `{value}`"))?;
+        let (from, to) = range
+            .split_once('-')
+            .ok_or_else(|| anyhow::anyhow!("invalid shard range `{range}`"))?;
+
+        let parse = |value: &str| {
+            value
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| anyhow::anyhow!("invalid shard id `{value}`"))
+        };
+        let (from, to, total) = (parse(from)?, parse(to)?, parse(total)?);
+
+        if from > to || to >= total {
+            anyhow::bail!("shard range `{value}` is out of bounds");
+        }
+
+        Ok(Self { from, to, total })
+    }
+}
+
+impl<'de> Deserialize<'de> for ShardRange {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+
+        Self::parse(&value).map_err(de::Error::custom)
+    }
+}
+
+/// Default number of event workers.
+fn default_event_workers() -> usize {
+    16
+}
+
+/// Default moderation logs retention period (in days).
+fn default_modlog_retention() -> u32 {
+    90
+}
+
+/// Default delay before archiving moderation logs (in years).
+fn default_modlog_archive() -> u32 {
+    2
+}
+
 /// Default server address.
 fn default_address() -> SocketAddr {
     "127.0.0.1:3000".parse().unwrap()
@@ -59,6 +377,8 @@ pub mod shared {
     use tracing::Level;
     use tracing_appender::non_blocking::WorkerGuard;
 
+    use crate::cache::CacheTtl;
+
     /// Databases configuration model.
     ///
     /// This model holds configuration values for Redis and MongoDB database.
@@ -81,6 +401,12 @@ pub mod shared {
         ///
         /// Defaults to `raidprotect`.
         pub mongodb_database: String,
+        /// Expiration delays of cached models.
+        ///
+        /// Comma-separated list of `Model=seconds` pairs overriding the
+        /// default expiration of cached models, `0` disables the expiration
+        /// (see [`CacheTtl`]). Defaults to the models expiration delays.
+        pub cache_ttl: CacheTtl,
     }
 
     impl Default for DatabaseConfig {
@@ -89,6 +415,7 @@ pub mod shared {
                 redis_uri: "redis://localhost:6379".to_owned(),
                 mongodb_uri: "mongodb://localhost:27017".to_owned(),
                 mongodb_database: "raidprotect".to_owned(),
+                cache_ttl: CacheTtl::default(),
             }
         }
     }
@@ -194,3 +521,29 @@ pub mod shared {
             .map_err(de::Error::custom)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_shard_range() {
+        assert_eq!(
+            ShardRange::parse("0-7/16").unwrap(),
+            ShardRange {
+                from: 0,
+                to: 7,
+                total: 16
+            }
+        );
+        assert_eq!(ShardRange::parse("3-3/4").unwrap().from, 3);
+    }
+
+    #[test]
+    fn test_parse_shard_range_invalid() {
+        assert!(ShardRange::parse("0-7").is_err());
+        assert!(ShardRange::parse("7-0/16").is_err());
+        assert!(ShardRange::parse("0-16/16").is_err());
+        assert!(ShardRange::parse("a-1/2").is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! Models for the `api_keys` collection.
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use futures_util::TryStreamExt;
+use mongodb::bson::{doc, oid::ObjectId, Bson};
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
+use twilight_model::id::{
+    marker::{GuildMarker, UserMarker},
+    Id,
+};
+
+use super::{ApiKeyStore, DbClient};
+use crate::serde::{DateTimeAsBson, IdAsI64};
+
+/// API key used by third-party integrations.
+///
+/// API keys are created by the guild owner and give access to the web API
+/// routes of a single guild, restricted by their [`ApiScope`]s. Only the
+/// SHA-256 hash of the token is stored: the token itself is shown once when
+/// the key is created.
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ApiKey {
+    /// Unique ID of the key.
+    #[serde(rename = "_id")]
+    pub id: Option<ObjectId>,
+    /// Guild the key gives access to.
+    #[serde_as(as = "IdAsI64")]
+    pub guild_id: Id<GuildMarker>,
+    /// Name of the key, unique in the guild.
+    pub name: String,
+    /// Hex-encoded SHA-256 hash of the token.
+    pub hash: String,
+    /// Routes the key gives access to.
+    pub scopes: Vec<ApiScope>,
+    /// User that created the key.
+    #[serde_as(as = "IdAsI64")]
+    pub created_by: Id<UserMarker>,
+    /// Date of creation of the key.
+    #[serde_as(as = "DateTimeAsBson")]
+    pub created_at: OffsetDateTime,
+}
+
+impl ApiKey {
+    /// Name of the MongoDB collection.
+    pub const COLLECTION: &'static str = "api_keys";
+
+    /// Maximum number of keys per guild.
+    pub const MAX_LEN: usize = 10;
+
+    /// Maximum length of the name of a key.
+    pub const MAX_NAME_LEN: usize = 32;
+
+    /// Prefix of the tokens, to make them easily recognizable.
+    pub const TOKEN_PREFIX: &'static str = "rp_";
+
+    /// Hash a token.
+    pub fn hash_token(token: &str) -> String {
+        hex::encode(Sha256::digest(token.as_bytes()))
+    }
+}
+
+/// Scope of an [`ApiKey`].
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiScope {
+    /// Read the guild information.
+    Guild,
+    /// Receive the live events of the guild.
+    Events,
+    /// Ask the bot to perform actions in the guild (such as bans).
+    Actions,
+}
+
+impl ApiScope {
+    /// All the available scopes.
+    pub const ALL: [ApiScope; 3] = [ApiScope::Guild, ApiScope::Events, ApiScope::Actions];
+}
+
+// Implementation of methods to query the database.
+#[async_trait]
+impl ApiKeyStore for DbClient {
+    async fn create_api_key(&self, key: &ApiKey) -> Result<ObjectId, anyhow::Error> {
+        let result = self
+            .db()
+            .collection::<ApiKey>(ApiKey::COLLECTION)
+            .insert_one(key, None)
+            .await?;
+
+        match result.inserted_id {
+            Bson::ObjectId(id) => Ok(id),
+            other => Err(anyhow!("expected object id, got {:?}", other)),
+        }
+    }
+
+    async fn get_api_key_by_hash(&self, hash: &str) -> Result<Option<ApiKey>, anyhow::Error> {
+        let key = self
+            .db()
+            .collection::<ApiKey>(ApiKey::COLLECTION)
+            .find_one(doc! { "hash": hash }, None)
+            .await?;
+
+        Ok(key)
+    }
+
+    async fn get_guild_api_keys(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Vec<ApiKey>, anyhow::Error> {
+        let keys = self
+            .db()
+            .collection::<ApiKey>(ApiKey::COLLECTION)
+            .find(doc! { "guild_id": guild_id.get() as i64 }, None)
+            .await?
+            .try_collect()
+            .await?;
+
+        Ok(keys)
+    }
+
+    async fn get_user_api_keys(
+        &self,
+        user_id: Id<UserMarker>,
+    ) -> Result<Vec<ApiKey>, anyhow::Error> {
+        let keys = self
+            .db()
+            .collection::<ApiKey>(ApiKey::COLLECTION)
+            .find(doc! { "created_by": user_id.get() as i64 }, None)
+            .await?
+            .try_collect()
+            .await?;
+
+        Ok(keys)
+    }
+
+    async fn delete_api_key(
+        &self,
+        guild_id: Id<GuildMarker>,
+        name: &str,
+    ) -> Result<bool, anyhow::Error> {
+        let result = self
+            .db()
+            .collection::<ApiKey>(ApiKey::COLLECTION)
+            .delete_one(
+                doc! { "guild_id": guild_id.get() as i64, "name": name },
+                None,
+            )
+            .await?;
+
+        Ok(result.deleted_count > 0)
+    }
+
+    async fn delete_guild_api_keys(&self, guild_id: Id<GuildMarker>) -> Result<u64, anyhow::Error> {
+        let result = self
+            .db()
+            .collection::<ApiKey>(ApiKey::COLLECTION)
+            .delete_many(doc! { "guild_id": guild_id.get() as i64 }, None)
+            .await?;
+
+        Ok(result.deleted_count)
+    }
+
+    async fn delete_user_api_keys(&self, user_id: Id<UserMarker>) -> Result<u64, anyhow::Error> {
+        let result = self
+            .db()
+            .collection::<ApiKey>(ApiKey::COLLECTION)
+            .delete_many(doc! { "created_by": user_id.get() as i64 }, None)
+            .await?;
+
+        Ok(result.deleted_count)
+    }
+}
@@ -1,6 +1,18 @@
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
-use mongodb::{bson::doc, options, Client, Database};
+use mongodb::{
+    bson::doc,
+    event::command::{CommandEventHandler, CommandFailedEvent, CommandSucceededEvent},
+    options, Client, Database,
+};
+
+use crate::metrics;
+
+/// Name of the database query latency metric.
+const QUERY_DURATION: &str = "raidprotect_database_query_duration_seconds";
+
+/// Help of the database query latency metric.
+const QUERY_DURATION_HELP: &str = "Duration of the database commands";
 
 /// Wrapper around a MongoDB [`Client`].
 ///
@@ -18,6 +30,9 @@ pub struct DbClient {
 
 impl DbClient {
     /// Connects to a MongoDB database and returns the client.
+    ///
+    /// The missing indexes of the collections are created, so the connection
+    /// fails if the database is unreachable.
     pub async fn connect(uri: &str, database: String) -> Result<Self, anyhow::Error> {
         let mut config = options::ClientOptions::parse(uri).await?;
 
@@ -27,9 +42,13 @@ impl DbClient {
         config.server_selection_timeout = Some(Duration::from_secs(2));
         config.compressors = Some(vec![options::Compressor::Zlib { level: None }]);
         config.default_database = Some(database.clone());
+        config.command_event_handler = Some(Arc::new(QueryMetrics));
 
         let client = Client::with_options(config)?;
-        Ok(Self { client, database })
+        let client = Self { client, database };
+        client.create_indexes().await?;
+
+        Ok(client)
     }
 
     /// Return a clone of the underlying client.
@@ -49,3 +68,29 @@ impl DbClient {
         Ok(())
     }
 }
+
+/// Record the latency of database commands in the metrics registry.
+///
+/// See the [`metrics`] module for more information.
+#[derive(Debug)]
+struct QueryMetrics;
+
+impl CommandEventHandler for QueryMetrics {
+    fn handle_command_succeeded_event(&self, event: CommandSucceededEvent) {
+        metrics::registry().observe(
+            QUERY_DURATION,
+            QUERY_DURATION_HELP,
+            &[("command", &event.command_name), ("status", "success")],
+            event.duration.as_secs_f64(),
+        );
+    }
+
+    fn handle_command_failed_event(&self, event: CommandFailedEvent) {
+        metrics::registry().observe(
+            QUERY_DURATION,
+            QUERY_DURATION_HELP,
+            &[("command", &event.command_name), ("status", "error")],
+            event.duration.as_secs_f64(),
+        );
+    }
+}
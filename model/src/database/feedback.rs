@@ -0,0 +1,119 @@
+//! Models for the `antispam_feedback` collection.
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use futures_util::TryStreamExt;
+use mongodb::bson::{doc, oid::ObjectId, Bson, Document};
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use time::OffsetDateTime;
+use twilight_model::id::{
+    marker::{GuildMarker, UserMarker},
+    Id,
+};
+
+use super::{guild::AntiSpamPunishment, DbClient, FeedbackStore};
+use crate::{
+    privacy::ANONYMOUS_USER_ID,
+    serde::{DateTimeAsBson, IdAsI64},
+};
+
+/// Anti-spam decision reported as a false positive.
+///
+/// Moderators can report anti-spam actions as false positives from the
+/// decision logs. Reports are stored to tune the default thresholds of the
+/// detectors.
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AntiSpamFeedback {
+    /// Unique ID of the report.
+    #[serde(rename = "_id")]
+    pub id: Option<ObjectId>,
+    /// Guild where the action was taken.
+    #[serde_as(as = "IdAsI64")]
+    pub guild_id: Id<GuildMarker>,
+    /// Member targeted by the action.
+    #[serde_as(as = "IdAsI64")]
+    pub user_id: Id<UserMarker>,
+    /// Identifiers of the detectors that fired (e.g. `antispam:rate`).
+    pub rules: Vec<String>,
+    /// Punishment that was applied.
+    pub punishment: AntiSpamPunishment,
+    /// Moderator that reported the false positive.
+    #[serde_as(as = "IdAsI64")]
+    pub reported_by: Id<UserMarker>,
+    /// Date of the report.
+    #[serde_as(as = "DateTimeAsBson")]
+    pub date: OffsetDateTime,
+}
+
+impl AntiSpamFeedback {
+    /// Name of the MongoDB collection.
+    pub const COLLECTION: &'static str = "antispam_feedback";
+}
+
+// Implementation of methods to query the database.
+#[async_trait]
+impl FeedbackStore for DbClient {
+    async fn create_antispam_feedback(
+        &self,
+        feedback: &AntiSpamFeedback,
+    ) -> Result<ObjectId, anyhow::Error> {
+        let result = self
+            .db()
+            .collection::<AntiSpamFeedback>(AntiSpamFeedback::COLLECTION)
+            .insert_one(feedback, None)
+            .await?;
+
+        match result.inserted_id {
+            Bson::ObjectId(id) => Ok(id),
+            other => Err(anyhow!("expected object id, got {:?}", other)),
+        }
+    }
+
+    async fn find_user_antispam_feedback(
+        &self,
+        user_id: Id<UserMarker>,
+    ) -> Result<Vec<AntiSpamFeedback>, anyhow::Error> {
+        let id = user_id.get() as i64;
+
+        let feedback = self
+            .db()
+            .collection::<AntiSpamFeedback>(AntiSpamFeedback::COLLECTION)
+            .find(
+                doc! { "$or": [{ "user_id": id }, { "reported_by": id }] },
+                None,
+            )
+            .await?
+            .try_collect()
+            .await?;
+
+        Ok(feedback)
+    }
+
+    async fn anonymize_user_antispam_feedback(
+        &self,
+        user_id: Id<UserMarker>,
+    ) -> Result<u64, anyhow::Error> {
+        let id = user_id.get() as i64;
+        let anonymous_id = ANONYMOUS_USER_ID.get() as i64;
+        let mut count = 0;
+
+        for field in ["user_id", "reported_by"] {
+            let result = self
+                .db()
+                .collection::<Document>(AntiSpamFeedback::COLLECTION)
+                .update_many(
+                    doc! { field: id },
+                    doc! { "$set": { field: anonymous_id } },
+                    None,
+                )
+                .await?;
+
+            count += result.modified_count;
+        }
+
+        Ok(count)
+    }
+}
@@ -1,19 +1,27 @@
 //! Models for the `guilds` collection.
 
 use anyhow::Context;
+use async_trait::async_trait;
+use futures_util::{StreamExt, TryStreamExt};
 use mongodb::{
-    bson::{doc, to_document},
+    bson::{doc, to_document, Document},
+    change_stream::{
+        event::{ChangeStreamEvent, ResumeToken},
+        ChangeStream,
+    },
+    error::ErrorKind,
     options,
 };
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, skip_serializing_none};
+use time::OffsetDateTime;
 use twilight_model::id::{
-    marker::{ChannelMarker, GuildMarker, MessageMarker, RoleMarker},
+    marker::{ChannelMarker, GuildMarker, MessageMarker, RoleMarker, UserMarker},
     Id,
 };
 
-use super::DbClient;
-use crate::serde::IdAsI64;
+use super::{offboarding::GuildOffboarding, DbClient, GuildConfigStore};
+use crate::serde::{DateTimeAsBson, IdAsI64};
 
 /// Guild configuration.
 ///
@@ -37,12 +45,51 @@ pub struct GuildConfig {
     /// Lang used for the global guild messages.
     #[serde(default = "default_lang")]
     pub lang: String,
+    /// The logged events configuration.
+    #[serde(default)]
+    pub logs: LogsConfig,
     /// The moderation module configuration.
     #[serde(default)]
     pub moderation: ModerationConfig,
     /// The captcha module configuration.
     #[serde(default)]
     pub captcha: CaptchaConfig,
+    /// The anti-spam module configuration.
+    #[serde(default)]
+    pub antispam: AntiSpamConfig,
+    /// The invite filter configuration.
+    #[serde(default)]
+    pub invites: InviteFilterConfig,
+    /// The domain filter configuration.
+    #[serde(default)]
+    pub domains: DomainFilterConfig,
+    /// The word filter configuration.
+    #[serde(default)]
+    pub filter: WordFilterConfig,
+    /// The attachment policy configuration.
+    #[serde(default)]
+    pub attachments: AttachmentPolicyConfig,
+    /// The phishing detection configuration.
+    #[serde(default)]
+    pub phishing: PhishingConfig,
+    /// The quarantine configuration.
+    #[serde(default)]
+    pub quarantine: QuarantineConfig,
+    /// The webhook messages checks configuration.
+    #[serde(default)]
+    pub webhooks: WebhookConfig,
+    /// The anti-nuke configuration.
+    #[serde(default)]
+    pub antinuke: AntiNukeConfig,
+    /// The public statistics configuration.
+    #[serde(default)]
+    pub stats: StatsConfig,
+    /// The outgoing webhook notifications configuration.
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    /// The premium entitlements of the guild.
+    #[serde(default)]
+    pub entitlements: Entitlements,
 }
 
 fn default_lang() -> String {
@@ -59,8 +106,156 @@ impl GuildConfig {
             id,
             logs_chan: None,
             lang: default_lang(),
+            logs: LogsConfig::default(),
             moderation: ModerationConfig::default(),
             captcha: CaptchaConfig::default(),
+            antispam: AntiSpamConfig::default(),
+            invites: InviteFilterConfig::default(),
+            domains: DomainFilterConfig::default(),
+            filter: WordFilterConfig::default(),
+            attachments: AttachmentPolicyConfig::default(),
+            phishing: PhishingConfig::default(),
+            quarantine: QuarantineConfig::default(),
+            webhooks: WebhookConfig::default(),
+            antinuke: AntiNukeConfig::default(),
+            stats: StatsConfig::default(),
+            notifications: NotificationsConfig::default(),
+            entitlements: Entitlements::default(),
+        }
+    }
+}
+
+/// Configuration for the logged events.
+///
+/// Each boolean field enables the logging of an event type in the logs
+/// channel.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct LogsConfig {
+    /// Channels the logs of each category are sent to.
+    pub channels: LogChannels,
+    /// Whether deleted messages are logged.
+    pub message_delete: bool,
+    /// Whether members joining the guild are logged.
+    pub member_join: bool,
+    /// Whether members leaving the guild are logged.
+    pub member_leave: bool,
+    /// Whether role and nickname changes of members are logged.
+    pub member_update: bool,
+    /// Whether alerts are sent when a bot is added or a role gains dangerous
+    /// permissions.
+    pub security_alerts: bool,
+    /// Whether voice channel activity of members is logged.
+    pub voice: bool,
+}
+
+/// Category of logs.
+///
+/// The logs of each category can be sent to a different channel (see
+/// [`LogChannels`]).
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum LogCategory {
+    /// Moderation actions and automatic sanctions.
+    Moderation,
+    /// Deleted messages and message filters.
+    Messages,
+    /// Members joining and leaving the guild.
+    Members,
+    /// Captcha verification.
+    Captcha,
+    /// Anti-spam decisions.
+    AntiSpam,
+    /// Voice channel activity.
+    Voice,
+}
+
+impl LogCategory {
+    /// All the categories of logs.
+    pub const ALL: [LogCategory; 6] = [
+        LogCategory::Moderation,
+        LogCategory::Messages,
+        LogCategory::Members,
+        LogCategory::Captcha,
+        LogCategory::AntiSpam,
+        LogCategory::Voice,
+    ];
+
+    /// Name of the category, used in the name of created channels.
+    pub fn name(self) -> &'static str {
+        match self {
+            LogCategory::Moderation => "moderation",
+            LogCategory::Messages => "messages",
+            LogCategory::Members => "members",
+            LogCategory::Captcha => "captcha",
+            LogCategory::AntiSpam => "antispam",
+            LogCategory::Voice => "voice",
+        }
+    }
+}
+
+/// Logs channel of each [`LogCategory`].
+///
+/// Logs of categories without channel are sent to the default logs channel
+/// ([`GuildConfig::logs_chan`]).
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct LogChannels {
+    /// Channel of the moderation logs.
+    #[serde_as(as = "Option<IdAsI64>")]
+    pub moderation: Option<Id<ChannelMarker>>,
+    /// Channel of the messages logs.
+    #[serde_as(as = "Option<IdAsI64>")]
+    pub messages: Option<Id<ChannelMarker>>,
+    /// Channel of the members logs.
+    #[serde_as(as = "Option<IdAsI64>")]
+    pub members: Option<Id<ChannelMarker>>,
+    /// Channel of the captcha logs.
+    #[serde_as(as = "Option<IdAsI64>")]
+    pub captcha: Option<Id<ChannelMarker>>,
+    /// Channel of the anti-spam logs.
+    #[serde_as(as = "Option<IdAsI64>")]
+    pub antispam: Option<Id<ChannelMarker>>,
+    /// Channel of the voice logs.
+    #[serde_as(as = "Option<IdAsI64>")]
+    pub voice: Option<Id<ChannelMarker>>,
+}
+
+impl LogChannels {
+    /// Get the channel of a category.
+    pub fn get(&self, category: LogCategory) -> Option<Id<ChannelMarker>> {
+        *self.route(category)
+    }
+
+    /// Set the channel of a category.
+    ///
+    /// If `channel` is [`None`], the logs of the category are sent to the
+    /// default logs channel.
+    pub fn set(&mut self, category: LogCategory, channel: Option<Id<ChannelMarker>>) {
+        *self.route_mut(category) = channel;
+    }
+
+    fn route(&self, category: LogCategory) -> &Option<Id<ChannelMarker>> {
+        match category {
+            LogCategory::Moderation => &self.moderation,
+            LogCategory::Messages => &self.messages,
+            LogCategory::Members => &self.members,
+            LogCategory::Captcha => &self.captcha,
+            LogCategory::AntiSpam => &self.antispam,
+            LogCategory::Voice => &self.voice,
+        }
+    }
+
+    fn route_mut(&mut self, category: LogCategory) -> &mut Option<Id<ChannelMarker>> {
+        match category {
+            LogCategory::Moderation => &mut self.moderation,
+            LogCategory::Messages => &mut self.messages,
+            LogCategory::Members => &mut self.members,
+            LogCategory::Captcha => &mut self.captcha,
+            LogCategory::AntiSpam => &mut self.antispam,
+            LogCategory::Voice => &mut self.voice,
         }
     }
 }
@@ -124,17 +319,571 @@ pub struct CaptchaConfig {
     /// If set, the captcha will send detailed logs to this channel.
     #[serde_as(as = "Option<IdAsI64>")]
     pub logs: Option<Id<ChannelMarker>>,
+    /// The join-gate questionnaire configuration.
+    ///
+    /// If set, new members must answer a few questions and be approved by a
+    /// moderator before being verified.
+    pub questionnaire: Option<QuestionnaireConfig>,
+    /// End of the strict mode.
+    ///
+    /// The strict mode is automatically enabled when a join raid is detected.
+    /// While it is enabled, new members must solve a harder captcha.
+    #[serde_as(as = "Option<DateTimeAsBson>")]
+    pub strict_until: Option<OffsetDateTime>,
 }
 
 impl CaptchaConfig {
     /// Max length of the `verified_roles` field.
     pub const MAX_VERIFIED_ROLES_LEN: usize = 5;
+
+    /// Whether the strict mode is currently enabled.
+    pub fn is_strict(&self) -> bool {
+        matches!(self.strict_until, Some(until) if until > OffsetDateTime::now_utc())
+    }
+}
+
+/// Configuration for the captcha join-gate questionnaire.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct QuestionnaireConfig {
+    /// Questions asked to new members.
+    pub questions: Vec<String>,
+    /// Channel where the answers are sent for review.
+    #[serde_as(as = "IdAsI64")]
+    pub review_channel: Id<ChannelMarker>,
+    /// Whether the questionnaire replaces the captcha image.
+    ///
+    /// If `false`, the questionnaire is presented after the captcha has been
+    /// completed.
+    #[serde(default)]
+    pub replace_captcha: bool,
+}
+
+impl QuestionnaireConfig {
+    /// Max number of questions.
+    pub const MAX_QUESTIONS_LEN: usize = 3;
+
+    /// Max length of a question.
+    ///
+    /// This corresponds to the max length of a modal text input label.
+    pub const MAX_QUESTION_LEN: usize = 45;
+}
+
+/// Configuration for the anti-spam module.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct AntiSpamConfig {
+    /// Whether the anti-spam is enabled.
+    pub enabled: bool,
+    /// Max number of messages a member can send during `message_interval`.
+    pub max_messages: u8,
+    /// Interval used to count the messages sent by a member (in seconds).
+    pub message_interval: u8,
+    /// Max number of mentions allowed in a single message.
+    pub max_mentions: u8,
+    /// Max number of similar messages a member can send in a short interval.
+    pub max_duplicates: u8,
+    /// Punishment applied to members that exceed the limits.
+    pub punishment: AntiSpamPunishment,
+    /// Duration of the punishment (in seconds).
+    ///
+    /// This is only used by the [`AntiSpamPunishment::Timeout`] punishment.
+    pub punishment_duration: u32,
+    /// Whether members repeatedly abusing mentions are punished.
+    ///
+    /// Mention abuses are ghost pings and mass pings of hoisted roles. They
+    /// are always logged when the anti-spam is enabled.
+    pub mention_sanction: bool,
+    /// Slowmode applied to channels during spam waves (in seconds).
+    ///
+    /// The emergency slowmode is disabled if set to `0`.
+    pub emergency_slowmode: u16,
+    /// Roles ignored by the anti-spam.
+    #[serde_as(as = "Vec<IdAsI64>")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exempt_roles: Vec<Id<RoleMarker>>,
+    /// Channels ignored by the anti-spam.
+    #[serde_as(as = "Vec<IdAsI64>")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exempt_channels: Vec<Id<ChannelMarker>>,
+    /// Identifiers of the disabled spam detectors (e.g. `antispam:rate`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub disabled_detectors: Vec<String>,
+}
+
+impl AntiSpamConfig {
+    /// Max length of the `exempt_roles` and `exempt_channels` fields.
+    pub const MAX_EXEMPT_LEN: usize = 10;
+
+    /// Max duration of the punishment (in seconds).
+    ///
+    /// This corresponds to the max duration of a Discord timeout (28 days).
+    pub const MAX_PUNISHMENT_DURATION: u32 = 28 * 24 * 60 * 60;
+
+    /// Max duration of the emergency slowmode (in seconds).
+    ///
+    /// This corresponds to the max slowmode of a Discord channel (6 hours).
+    pub const MAX_EMERGENCY_SLOWMODE: u16 = 6 * 60 * 60;
+}
+
+impl Default for AntiSpamConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_messages: 5,
+            message_interval: 5,
+            max_mentions: 5,
+            max_duplicates: 3,
+            punishment: AntiSpamPunishment::default(),
+            punishment_duration: 10 * 60,
+            mention_sanction: false,
+            emergency_slowmode: 0,
+            exempt_roles: Vec::new(),
+            exempt_channels: Vec::new(),
+            disabled_detectors: Vec::new(),
+        }
+    }
+}
+
+/// Punishment applied by the anti-spam.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AntiSpamPunishment {
+    /// Only delete the messages.
+    Delete,
+    /// Delete the messages and timeout the member.
+    #[default]
+    Timeout,
+    /// Delete the messages and kick the member.
+    Kick,
+    /// Delete the messages and ban the member.
+    Ban,
+}
+
+/// Preset of anti-spam limits.
+///
+/// Profiles are used by the setup wizard to configure the anti-spam without
+/// having to choose each limit.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AntiSpamProfile {
+    /// The anti-spam is disabled.
+    Disabled,
+    /// High limits, messages are only deleted.
+    Relaxed,
+    /// Default limits.
+    Standard,
+    /// Low limits, members abusing mentions are punished.
+    Strict,
+}
+
+impl AntiSpamProfile {
+    /// Apply the profile to an anti-spam configuration.
+    ///
+    /// Exempt roles and channels and disabled detectors are kept.
+    pub fn apply(self, config: &mut AntiSpamConfig) {
+        let defaults = AntiSpamConfig::default();
+
+        let (max_messages, max_mentions, max_duplicates, punishment, punishment_duration) =
+            match self {
+                AntiSpamProfile::Disabled => {
+                    config.enabled = false;
+                    return;
+                }
+                AntiSpamProfile::Relaxed => (8, 8, 5, AntiSpamPunishment::Delete, 0),
+                AntiSpamProfile::Standard => (
+                    defaults.max_messages,
+                    defaults.max_mentions,
+                    defaults.max_duplicates,
+                    defaults.punishment,
+                    defaults.punishment_duration,
+                ),
+                AntiSpamProfile::Strict => (4, 3, 2, AntiSpamPunishment::Timeout, 60 * 60),
+            };
+
+        config.enabled = true;
+        config.max_messages = max_messages;
+        config.message_interval = defaults.message_interval;
+        config.max_mentions = max_mentions;
+        config.max_duplicates = max_duplicates;
+        config.punishment = punishment;
+        config.punishment_duration = punishment_duration;
+        config.mention_sanction = self == AntiSpamProfile::Strict;
+    }
+}
+
+/// Configuration for the invite filter.
+///
+/// The invite filter deletes messages containing invites to other servers.
+/// Roles and channels exempted from the anti-spam are also exempted from the
+/// invite filter.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct InviteFilterConfig {
+    /// Whether the invite filter is enabled.
+    pub enabled: bool,
+    /// Whether invites are resolved using the Discord API.
+    ///
+    /// If enabled, invites to the current server and to the `allowed_guilds`
+    /// are not deleted. Otherwise, all invites are deleted.
+    pub resolve: bool,
+    /// Servers whose invites are allowed.
+    #[serde_as(as = "Vec<IdAsI64>")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allowed_guilds: Vec<Id<GuildMarker>>,
+    /// Number of warnings before a member is timed out.
+    pub max_warnings: u8,
+    /// Duration of the timeout (in seconds).
+    pub timeout_duration: u32,
+}
+
+impl InviteFilterConfig {
+    /// Max length of the `allowed_guilds` field.
+    pub const MAX_ALLOWED_GUILDS_LEN: usize = 10;
+}
+
+impl Default for InviteFilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            resolve: true,
+            allowed_guilds: Vec::new(),
+            max_warnings: 3,
+            timeout_duration: 10 * 60,
+        }
+    }
+}
+
+/// Configuration for the domain filter.
+///
+/// The domain filter deletes messages containing links to blocked domains.
+/// Roles and channels exempted from the anti-spam are also exempted from the
+/// domain filter.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct DomainFilterConfig {
+    /// Whether the domain filter is enabled.
+    pub enabled: bool,
+    /// Whether the built-in list of phishing domains is used.
+    pub block_phishing: bool,
+    /// Domains blocked in the guild.
+    ///
+    /// Subdomains of these domains are also blocked.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub blocked: Vec<String>,
+    /// Domains allowed in the guild.
+    ///
+    /// Allowed domains take precedence over the blocked domains and the
+    /// built-in list.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allowed: Vec<String>,
+}
+
+impl DomainFilterConfig {
+    /// Max length of the `blocked` and `allowed` fields.
+    pub const MAX_DOMAINS_LEN: usize = 50;
+}
+
+impl Default for DomainFilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            block_phishing: true,
+            blocked: Vec::new(),
+            allowed: Vec::new(),
+        }
+    }
+}
+
+/// Configuration for the word filter.
+///
+/// The word filter deletes messages matching one of the configured rules. It
+/// is enabled as long as at least one rule is configured. Roles and channels
+/// exempted from the anti-spam are also exempted from the word filter.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct WordFilterConfig {
+    /// Rules of the word filter.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<WordFilterRule>,
+}
+
+impl WordFilterConfig {
+    /// Max length of the `rules` field.
+    pub const MAX_RULES_LEN: usize = 50;
+
+    /// Max length of a rule pattern.
+    pub const MAX_PATTERN_LEN: usize = 100;
+}
+
+/// Word filter rule.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct WordFilterRule {
+    /// Kind of rule.
+    pub kind: WordFilterRuleKind,
+    /// Pattern of the rule.
+    pub pattern: String,
+}
+
+/// Kind of word filter rule.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WordFilterRuleKind {
+    /// Word or phrase matched on word boundaries.
+    Term,
+    /// Term where `*` matches any part of a word.
+    Wildcard,
+    /// Regular expression.
+    Regex,
+}
+
+/// Configuration for the attachment policy.
+///
+/// Messages with attachments that do not comply with the policy are deleted.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct AttachmentPolicyConfig {
+    /// Whether the attachment policy is enabled.
+    pub enabled: bool,
+    /// Whether executable files are blocked.
+    ///
+    /// Executables are detected using their extension and MIME type.
+    pub block_executables: bool,
+    /// Max number of attachments per message.
+    ///
+    /// The number of attachments is not limited if set to `0`.
+    pub max_attachments: u8,
+    /// Channels where only images are allowed.
+    ///
+    /// Messages without images are deleted in these channels.
+    #[serde_as(as = "Vec<IdAsI64>")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub image_only_channels: Vec<Id<ChannelMarker>>,
+    /// Roles ignored by the attachment policy.
+    #[serde_as(as = "Vec<IdAsI64>")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exempt_roles: Vec<Id<RoleMarker>>,
+}
+
+impl AttachmentPolicyConfig {
+    /// Max length of the `image_only_channels` and `exempt_roles` fields.
+    pub const MAX_LIST_LEN: usize = 10;
+
+    /// Max value of the `max_attachments` field.
+    ///
+    /// This corresponds to the max number of attachments of a Discord message.
+    pub const MAX_ATTACHMENTS: u8 = 10;
+}
+
+impl Default for AttachmentPolicyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            block_executables: true,
+            max_attachments: 0,
+            image_only_channels: Vec::new(),
+            exempt_roles: Vec::new(),
+        }
+    }
+}
+
+/// Configuration for the phishing detection.
+///
+/// Messages are given a phishing score from several signals (links, scam
+/// keywords, mentions of everyone and recently created accounts). Messages
+/// reaching the threshold are deleted and their author is quarantined.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct PhishingConfig {
+    /// Whether the phishing detection is enabled.
+    pub enabled: bool,
+    /// Score above which a message is considered as phishing (0 to 100).
+    pub threshold: u8,
+}
+
+impl PhishingConfig {
+    /// Max value of the `threshold` field.
+    pub const MAX_THRESHOLD: u8 = 100;
+}
+
+impl Default for PhishingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: 70,
+        }
+    }
+}
+
+/// Configuration for the quarantine.
+///
+/// Members flagged by the automations are quarantined: their roles are
+/// replaced by the quarantine role until a moderator releases or bans them.
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct QuarantineConfig {
+    /// Role given to quarantined members.
+    ///
+    /// The quarantine is disabled if no role is configured.
+    #[serde_as(as = "Option<IdAsI64>")]
+    pub role: Option<Id<RoleMarker>>,
+}
+
+/// Configuration for the webhook messages checks.
+///
+/// Messages sent by webhooks are checked for spam and phishing links. Since
+/// webhooks cannot be sanctioned like members, a separate action is applied.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// Whether webhook messages are checked.
+    pub enabled: bool,
+    /// Action applied to abusive webhook messages.
+    pub action: WebhookAction,
+}
+
+/// Action applied to abusive webhook messages.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookAction {
+    /// Only delete the messages.
+    #[default]
+    Delete,
+    /// Delete the messages and the webhook.
+    Disable,
+}
+
+/// Configuration for the anti-nuke protection.
+///
+/// Members performing too many destructive actions (deleting channels or
+/// roles, banning members or creating webhooks) in a short interval have their
+/// roles removed.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct AntiNukeConfig {
+    /// Whether the anti-nuke protection is enabled.
+    pub enabled: bool,
+    /// Max number of destructive actions a member can perform during
+    /// `interval`.
+    pub max_actions: u8,
+    /// Interval used to count the destructive actions (in seconds).
+    pub interval: u16,
+    /// Members allowed to perform destructive actions without limits.
+    #[serde_as(as = "Vec<IdAsI64>")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub trusted_users: Vec<Id<UserMarker>>,
+}
+
+impl AntiNukeConfig {
+    /// Max length of the `trusted_users` field.
+    pub const MAX_TRUSTED_LEN: usize = 10;
+
+    /// Max interval used to count the destructive actions (in seconds).
+    pub const MAX_INTERVAL: u16 = 60 * 60;
+}
+
+impl Default for AntiNukeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_actions: 5,
+            interval: 60,
+            trusted_users: Vec::new(),
+        }
+    }
+}
+
+/// Configuration for the public statistics.
+///
+/// If enabled, aggregate statistics of the guild are available on the web
+/// service so that they can be embedded on other websites.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct StatsConfig {
+    /// Whether the statistics are public.
+    pub public: bool,
+}
+
+/// Configuration for the outgoing webhook notifications.
+///
+/// Sanctions, raid alerts and captcha failures are sent as JSON payloads to
+/// the configured URL, signed with the secret.
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// HTTPS URL of the webhook.
+    pub url: Option<String>,
+    /// Secret used to sign the payloads with HMAC-SHA256.
+    pub secret: Option<String>,
+}
+
+/// Premium entitlements of a guild.
+///
+/// Entitlements are granted by the bot owners and give access to features
+/// that are expensive to run. The premium tier gives access to all the
+/// [`PremiumFeature`]s, and features can also be granted individually.
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct Entitlements {
+    /// Premium tier of the guild.
+    pub tier: PremiumTier,
+    /// Features granted in addition to the tier.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<PremiumFeature>,
+    /// Date the entitlements expire.
+    ///
+    /// The entitlements never expire if not set.
+    #[serde_as(as = "Option<DateTimeAsBson>")]
+    pub expires_at: Option<OffsetDateTime>,
+}
+
+impl Entitlements {
+    /// Whether the entitlements have not expired.
+    pub fn is_active(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => expires_at > OffsetDateTime::now_utc(),
+            None => true,
+        }
+    }
+
+    /// Whether the guild has access to a feature.
+    pub fn has_feature(&self, feature: PremiumFeature) -> bool {
+        self.is_active() && (self.tier == PremiumTier::Premium || self.features.contains(&feature))
+    }
+}
+
+/// Premium tier of a guild.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PremiumTier {
+    /// No premium tier.
+    #[default]
+    Free,
+    /// Access to all the premium features.
+    Premium,
+}
+
+/// Feature restricted to guilds with [`Entitlements`].
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PremiumFeature {
+    /// Regular expression rules in the word filter.
+    RegexFilter,
 }
 
 // Implementation of methods to query the database.
-impl DbClient {
-    /// Get the [`GuildConfig`] for a given guild_id, if it exists.
-    pub async fn get_guild(
+#[async_trait]
+impl GuildConfigStore for DbClient {
+    async fn get_guild(
         &self,
         guild_id: Id<GuildMarker>,
     ) -> Result<Option<GuildConfig>, anyhow::Error> {
@@ -149,8 +898,7 @@ impl DbClient {
         Ok(guild)
     }
 
-    /// Get the [`GuildConfig`] for a given guild_id, or create it with default configuration.
-    pub async fn get_guild_or_create(
+    async fn get_guild_or_create(
         &self,
         guild_id: Id<GuildMarker>,
     ) -> Result<GuildConfig, anyhow::Error> {
@@ -174,8 +922,7 @@ impl DbClient {
         guild.context("no guild sent by the database")
     }
 
-    /// Update or insert a [`GuildConfig`] in the database.
-    pub async fn update_guild(&self, guild: &GuildConfig) -> Result<(), anyhow::Error> {
+    async fn update_guild(&self, guild: &GuildConfig) -> Result<(), anyhow::Error> {
         let query = GuildQuery { id: guild.id };
         let options = options::ReplaceOptions::builder().upsert(true).build();
 
@@ -186,6 +933,126 @@ impl DbClient {
 
         Ok(())
     }
+
+    async fn find_trusting_guilds(
+        &self,
+        user_id: Id<UserMarker>,
+    ) -> Result<Vec<Id<GuildMarker>>, anyhow::Error> {
+        let guilds = self
+            .db()
+            .collection::<GuildConfig>(GuildConfig::COLLECTION)
+            .find(
+                doc! { "antinuke.trusted_users": user_id.get() as i64 },
+                None,
+            )
+            .await?
+            .map_ok(|config| config.id)
+            .try_collect()
+            .await?;
+
+        Ok(guilds)
+    }
+
+    async fn remove_trusted_user(&self, user_id: Id<UserMarker>) -> Result<u64, anyhow::Error> {
+        let id = user_id.get() as i64;
+        let mut count = 0;
+
+        for collection in [
+            GuildConfig::COLLECTION,
+            GuildOffboarding::ARCHIVE_COLLECTION,
+        ] {
+            let result = self
+                .db()
+                .collection::<Document>(collection)
+                .update_many(
+                    doc! { "antinuke.trusted_users": id },
+                    doc! { "$pull": { "antinuke.trusted_users": id } },
+                    None,
+                )
+                .await?;
+
+            count += result.modified_count;
+        }
+
+        Ok(count)
+    }
+}
+
+impl DbClient {
+    /// Watch the changes made to the guild configurations.
+    ///
+    /// The stream starts after the change identified by `resume_after` if set,
+    /// see [`GuildChanges::resume_token`]. Returns [`None`] if the database does
+    /// not support change streams, which are only available on replica sets.
+    pub async fn watch_guilds(
+        &self,
+        resume_after: Option<ResumeToken>,
+    ) -> Result<Option<GuildChanges>, anyhow::Error> {
+        let pipeline = [doc! {
+            "$match": { "operationType": { "$in": ["insert", "update", "replace", "delete"] } }
+        }];
+
+        let options = options::ChangeStreamOptions::builder()
+            .resume_after(resume_after)
+            .build();
+
+        let result = self
+            .db()
+            .collection::<Document>(GuildConfig::COLLECTION)
+            .watch(pipeline, options)
+            .await;
+
+        match result {
+            Ok(stream) => Ok(Some(GuildChanges { stream })),
+            Err(error) => match &*error.kind {
+                ErrorKind::Command(error) if error.code == CHANGE_STREAM_UNSUPPORTED => Ok(None),
+                _ => Err(error.into()),
+            },
+        }
+    }
+}
+
+/// Error code returned when opening a change stream on a standalone server.
+const CHANGE_STREAM_UNSUPPORTED: i32 = 40573;
+
+/// Stream of the guilds whose configuration changed.
+///
+/// See [`DbClient::watch_guilds`]. The driver automatically resumes the stream
+/// after transient errors.
+pub struct GuildChanges {
+    stream: ChangeStream<ChangeStreamEvent<Document>>,
+}
+
+impl GuildChanges {
+    /// Token used to resume the stream after the last received change.
+    ///
+    /// Returns [`None`] if no change has been received yet.
+    pub fn resume_token(&self) -> Option<ResumeToken> {
+        self.stream.resume_token()
+    }
+
+    /// Wait for the next guild whose configuration changed.
+    ///
+    /// Returns [`None`] if the stream has been closed.
+    pub async fn next(&mut self) -> Option<Result<Id<GuildMarker>, anyhow::Error>> {
+        while let Some(event) = self.stream.next().await {
+            let event = match event {
+                Ok(event) => event,
+                Err(error) => return Some(Err(error.into())),
+            };
+
+            let guild_id = event
+                .document_key
+                .and_then(|key| key.get_i64("_id").ok())
+                .and_then(|id| Id::new_checked(id as u64));
+
+            if let Some(guild_id) = guild_id {
+                return Some(Ok(guild_id));
+            }
+        }
+
+        None
+    }
 }
 
 /// Query a guild with its guild_id
@@ -0,0 +1,163 @@
+//! Indexes of the MongoDB collections.
+//!
+//! Indexes are created when connecting to the database (see
+//! [`DbClient::connect`]). Existing indexes with the same name but a different
+//! definition are dropped and created again.
+//!
+//! Collections whose documents are identified by the guild id (`guilds`,
+//! `offboardings` and `guild_stats`) use it as `_id`, which is already unique
+//! and indexed.
+
+use futures_util::TryStreamExt;
+use mongodb::{
+    bson::{doc, Document},
+    error::{Error, ErrorKind},
+    options::IndexOptions,
+    IndexModel,
+};
+use tracing::info;
+
+use super::{api_key::ApiKey, job::ScheduledJob, modlog::Modlog, DbClient};
+
+/// Error code returned when listing the indexes of a missing collection.
+const NAMESPACE_NOT_FOUND: i32 = 26;
+
+/// Index of a collection.
+struct Index {
+    collection: &'static str,
+    name: &'static str,
+    keys: Document,
+    unique: bool,
+}
+
+impl Index {
+    fn new(collection: &'static str, name: &'static str, keys: Document) -> Self {
+        Self {
+            collection,
+            name,
+            keys,
+            unique: false,
+        }
+    }
+
+    fn unique(mut self) -> Self {
+        self.unique = true;
+        self
+    }
+
+    fn model(&self) -> IndexModel {
+        let options = IndexOptions::builder()
+            .name(self.name.to_owned())
+            .unique(self.unique.then_some(true))
+            .build();
+
+        IndexModel::builder()
+            .keys(self.keys.clone())
+            .options(options)
+            .build()
+    }
+
+    /// Whether an existing index has the same definition.
+    fn matches(&self, index: &IndexModel) -> bool {
+        let unique = index
+            .options
+            .as_ref()
+            .and_then(|options| options.unique)
+            .unwrap_or(false);
+
+        index.keys == self.keys && unique == self.unique
+    }
+}
+
+/// Indexes created at startup.
+fn indexes() -> Vec<Index> {
+    vec![
+        Index::new(
+            Modlog::COLLECTION,
+            "guild_id_user_id",
+            doc! { "guild_id": 1_i32, "user.id": 1_i32 },
+        ),
+        Index::new(
+            Modlog::COLLECTION,
+            "guild_id_date",
+            doc! { "guild_id": 1_i32, "date": -1_i32 },
+        ),
+        Index::new(Modlog::COLLECTION, "date", doc! { "date": 1_i32 }),
+        Index::new(
+            Modlog::ARCHIVE_COLLECTION,
+            "guild_id_date",
+            doc! { "guild_id": 1_i32, "date": -1_i32 },
+        ),
+        Index::new(ApiKey::COLLECTION, "hash", doc! { "hash": 1_i32 }).unique(),
+        Index::new(
+            ApiKey::COLLECTION,
+            "guild_id_name",
+            doc! { "guild_id": 1_i32, "name": 1_i32 },
+        )
+        .unique(),
+        Index::new(ScheduledJob::COLLECTION, "run_at", doc! { "run_at": 1_i32 }),
+    ]
+}
+
+impl DbClient {
+    /// Create the missing indexes of the collections.
+    pub(super) async fn create_indexes(&self) -> Result<(), anyhow::Error> {
+        for index in indexes() {
+            let collection = self.db().collection::<Document>(index.collection);
+            let existing = match collection.list_indexes(None).await {
+                Ok(cursor) => cursor.try_collect::<Vec<_>>().await?,
+                Err(error) if is_namespace_not_found(&error) => Vec::new(),
+                Err(error) => return Err(error.into()),
+            };
+
+            let current = existing.iter().find(|existing| {
+                existing
+                    .options
+                    .as_ref()
+                    .and_then(|options| options.name.as_deref())
+                    == Some(index.name)
+            });
+
+            match current {
+                Some(current) if index.matches(current) => continue,
+                Some(_) => {
+                    collection.drop_index(index.name, None).await?;
+                    collection.create_index(index.model(), None).await?;
+
+                    info!(
+                        collection = index.collection,
+                        "changed index {}", index.name
+                    );
+                }
+                None => {
+                    collection.create_index(index.model(), None).await?;
+
+                    info!(
+                        collection = index.collection,
+                        "created index {}", index.name
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn is_namespace_not_found(error: &Error) -> bool {
+    matches!(&*error.kind, ErrorKind::Command(error) if error.code == NAMESPACE_NOT_FOUND)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_matches() {
+        let index = Index::new("test", "name", doc! { "name": 1_i32 }).unique();
+
+        assert!(index.matches(&index.model()));
+        assert!(!index.matches(&Index::new("test", "name", doc! { "name": 1_i32 }).model()));
+        assert!(!index.matches(&Index::new("test", "name", doc! { "name": -1_i32 }).model()));
+    }
+}
@@ -0,0 +1,259 @@
+//! Models for the `scheduled_jobs` collection.
+
+use async_trait::async_trait;
+use mongodb::{bson::doc, options};
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use time::{Duration, OffsetDateTime};
+use twilight_model::id::{
+    marker::{ChannelMarker, GuildMarker, UserMarker},
+    Id,
+};
+
+use super::{DbClient, ScheduleStore};
+use crate::serde::{DateTimeAsBson, IdAsI64};
+
+/// Delayed job run by the scheduler.
+///
+/// Jobs are identified by a key derived from their action (see
+/// [`ScheduledJob::key`]), so that scheduling the same action twice replaces
+/// the previous job instead of running it twice. Jobs are claimed by a bot
+/// process before being run, and are retried if the process fails to run them
+/// or stops while running them.
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ScheduledJob {
+    /// Unique key of the job.
+    #[serde(rename = "_id")]
+    pub key: String,
+    /// Guild in which the job is run.
+    #[serde_as(as = "IdAsI64")]
+    pub guild_id: Id<GuildMarker>,
+    /// Action of the job.
+    pub action: JobAction,
+    /// Date after which the job is run.
+    #[serde_as(as = "DateTimeAsBson")]
+    pub run_at: OffsetDateTime,
+    /// Number of times the job has been claimed.
+    #[serde(default)]
+    pub attempts: u32,
+    /// Date until which the job is claimed by a bot process.
+    #[serde_as(as = "Option<DateTimeAsBson>")]
+    #[serde(default)]
+    pub locked_until: Option<OffsetDateTime>,
+    /// Error of the last failed attempt.
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+/// Action of a [`ScheduledJob`].
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JobAction {
+    /// Remove the ban of a user.
+    Unban {
+        #[serde_as(as = "IdAsI64")]
+        user_id: Id<UserMarker>,
+    },
+    /// Remove the timeout of a member.
+    Unmute {
+        #[serde_as(as = "IdAsI64")]
+        user_id: Id<UserMarker>,
+    },
+    /// Allow `@everyone` to send messages in a channel again.
+    UnlockChannel {
+        #[serde_as(as = "IdAsI64")]
+        channel_id: Id<ChannelMarker>,
+    },
+    /// Kick a member that failed or did not complete the captcha verification.
+    CaptchaDeadline {
+        #[serde_as(as = "IdAsI64")]
+        user_id: Id<UserMarker>,
+    },
+    /// Verify a member that solved the captcha on the web verification page.
+    WebCaptchaCompleted {
+        #[serde_as(as = "IdAsI64")]
+        user_id: Id<UserMarker>,
+    },
+}
+
+impl ScheduledJob {
+    /// Name of the MongoDB collection.
+    pub const COLLECTION: &'static str = "scheduled_jobs";
+
+    /// Maximum number of attempts before a job is abandoned.
+    pub const MAX_ATTEMPTS: u32 = 5;
+
+    /// Duration during which a claimed job cannot be claimed again.
+    pub const LOCK_DURATION: Duration = Duration::minutes(5);
+
+    /// Initialize a new [`ScheduledJob`].
+    pub fn new(guild_id: Id<GuildMarker>, action: JobAction, run_at: OffsetDateTime) -> Self {
+        Self {
+            key: Self::key(guild_id, action),
+            guild_id,
+            action,
+            run_at,
+            attempts: 0,
+            locked_until: None,
+            last_error: None,
+        }
+    }
+
+    /// Get the unique key of a job.
+    pub fn key(guild_id: Id<GuildMarker>, action: JobAction) -> String {
+        match action {
+            JobAction::Unban { user_id } => format!("unban:{guild_id}:{user_id}"),
+            JobAction::Unmute { user_id } => format!("unmute:{guild_id}:{user_id}"),
+            JobAction::UnlockChannel { channel_id } => {
+                format!("unlock-channel:{guild_id}:{channel_id}")
+            }
+            JobAction::CaptchaDeadline { user_id } => {
+                format!("captcha-deadline:{guild_id}:{user_id}")
+            }
+            JobAction::WebCaptchaCompleted { user_id } => {
+                format!("web-captcha:{guild_id}:{user_id}")
+            }
+        }
+    }
+
+    /// Delay before the next attempt of a failed job.
+    ///
+    /// The delay doubles after each attempt, starting at 30 seconds.
+    pub fn retry_delay(&self) -> Duration {
+        Duration::seconds(30) * 2_i32.pow(self.attempts.saturating_sub(1).min(8))
+    }
+}
+
+// Implementation of methods to query the database.
+#[async_trait]
+impl ScheduleStore for DbClient {
+    async fn schedule_job(&self, job: &ScheduledJob) -> Result<(), anyhow::Error> {
+        let options = options::ReplaceOptions::builder().upsert(true).build();
+
+        self.db()
+            .collection::<ScheduledJob>(ScheduledJob::COLLECTION)
+            .replace_one(doc! { "_id": &job.key }, job, options)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn cancel_job(&self, key: &str) -> Result<bool, anyhow::Error> {
+        let result = self
+            .db()
+            .collection::<ScheduledJob>(ScheduledJob::COLLECTION)
+            .delete_one(doc! { "_id": key }, None)
+            .await?;
+
+        Ok(result.deleted_count > 0)
+    }
+
+    async fn claim_job(&self) -> Result<Option<ScheduledJob>, anyhow::Error> {
+        let now = OffsetDateTime::now_utc();
+        let locked_until = now + ScheduledJob::LOCK_DURATION;
+        let options = options::FindOneAndUpdateOptions::builder()
+            .sort(doc! { "run_at": 1_i32 })
+            .return_document(options::ReturnDocument::After)
+            .build();
+
+        let job = self
+            .db()
+            .collection::<ScheduledJob>(ScheduledJob::COLLECTION)
+            .find_one_and_update(
+                doc! {
+                    "run_at": { "$lte": bson_date(now) },
+                    "$or": [
+                        { "locked_until": null },
+                        { "locked_until": { "$lte": bson_date(now) } },
+                    ],
+                },
+                doc! {
+                    "$set": { "locked_until": bson_date(locked_until) },
+                    "$inc": { "attempts": 1_i32 },
+                },
+                options,
+            )
+            .await?;
+
+        Ok(job)
+    }
+
+    async fn complete_job(&self, job: &ScheduledJob) -> Result<(), anyhow::Error> {
+        self.db()
+            .collection::<ScheduledJob>(ScheduledJob::COLLECTION)
+            .delete_one(
+                doc! { "_id": &job.key, "attempts": job.attempts as i64 },
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn retry_job(&self, job: &ScheduledJob, error: String) -> Result<(), anyhow::Error> {
+        let mut job = job.clone();
+        job.run_at = OffsetDateTime::now_utc() + job.retry_delay();
+        job.locked_until = None;
+        job.last_error = Some(error);
+
+        self.db()
+            .collection::<ScheduledJob>(ScheduledJob::COLLECTION)
+            .replace_one(
+                doc! { "_id": &job.key, "attempts": job.attempts as i64 },
+                &job,
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Convert a date to a BSON date, the same way as [`DateTimeAsBson`].
+pub(super) fn bson_date(date: OffsetDateTime) -> mongodb::bson::DateTime {
+    mongodb::bson::DateTime::from_millis(date.unix_timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_key() {
+        let guild_id = Id::new(1);
+        let user_id = Id::new(2);
+
+        assert_eq!(
+            ScheduledJob::key(guild_id, JobAction::Unban { user_id }),
+            "unban:1:2"
+        );
+        assert_eq!(
+            ScheduledJob::key(
+                guild_id,
+                JobAction::UnlockChannel {
+                    channel_id: Id::new(3)
+                }
+            ),
+            "unlock-channel:1:3"
+        );
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let mut job = ScheduledJob::new(
+            Id::new(1),
+            JobAction::Unmute {
+                user_id: Id::new(2),
+            },
+            OffsetDateTime::now_utc(),
+        );
+
+        job.attempts = 1;
+        assert_eq!(job.retry_delay(), Duration::seconds(30));
+        job.attempts = 3;
+        assert_eq!(job.retry_delay(), Duration::minutes(2));
+    }
+}
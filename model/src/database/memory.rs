@@ -0,0 +1,549 @@
+//! In-memory storage backend.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use mongodb::bson::oid::ObjectId;
+use time::OffsetDateTime;
+use twilight_model::id::{
+    marker::{GuildMarker, UserMarker},
+    Id,
+};
+
+use super::{
+    model::{
+        AntiSpamFeedback, ApiKey, CommandUsage, GuildConfig, GuildOffboarding, GuildStat,
+        GuildStats, Modlog, ModlogUser, ScheduledJob,
+    },
+    ApiKeyStore, FeedbackStore, GuildConfigStore, ModlogStore, OffboardingStore, ScheduleStore,
+    StatsStore,
+};
+use crate::privacy::ANONYMOUS_USER_ID;
+
+/// In-memory implementation of the storage traits.
+///
+/// This type is intended to be used in tests, as a replacement for
+/// [`DbClient`]. It behaves like the MongoDB implementation, except that the
+/// data is lost when it is dropped.
+///
+/// [`DbClient`]: super::DbClient
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    guilds: Mutex<HashMap<Id<GuildMarker>, GuildConfig>>,
+    archived_guilds: Mutex<HashMap<Id<GuildMarker>, GuildConfig>>,
+    modlogs: Mutex<Vec<Modlog>>,
+    archived_modlogs: Mutex<Vec<Modlog>>,
+    jobs: Mutex<HashMap<String, ScheduledJob>>,
+    api_keys: Mutex<Vec<ApiKey>>,
+    feedback: Mutex<Vec<AntiSpamFeedback>>,
+    stats: Mutex<HashMap<Id<GuildMarker>, GuildStats>>,
+    command_usage: Mutex<HashMap<String, CommandUsage>>,
+    offboardings: Mutex<HashMap<Id<GuildMarker>, GuildOffboarding>>,
+}
+
+impl MemoryStore {
+    /// Initialize a new empty [`MemoryStore`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl GuildConfigStore for MemoryStore {
+    async fn get_guild(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Option<GuildConfig>, anyhow::Error> {
+        Ok(lock(&self.guilds).get(&guild_id).cloned())
+    }
+
+    async fn get_guild_or_create(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<GuildConfig, anyhow::Error> {
+        let guild = lock(&self.guilds)
+            .entry(guild_id)
+            .or_insert_with(|| GuildConfig::new(guild_id))
+            .clone();
+
+        Ok(guild)
+    }
+
+    async fn update_guild(&self, guild: &GuildConfig) -> Result<(), anyhow::Error> {
+        lock(&self.guilds).insert(guild.id, guild.clone());
+
+        Ok(())
+    }
+
+    async fn find_trusting_guilds(
+        &self,
+        user_id: Id<UserMarker>,
+    ) -> Result<Vec<Id<GuildMarker>>, anyhow::Error> {
+        let guilds = lock(&self.guilds)
+            .values()
+            .filter(|guild| guild.antinuke.trusted_users.contains(&user_id))
+            .map(|guild| guild.id)
+            .collect();
+
+        Ok(guilds)
+    }
+
+    async fn remove_trusted_user(&self, user_id: Id<UserMarker>) -> Result<u64, anyhow::Error> {
+        let mut count = 0;
+
+        for guilds in [&self.guilds, &self.archived_guilds] {
+            for guild in lock(guilds).values_mut() {
+                let trusted = &mut guild.antinuke.trusted_users;
+                let len = trusted.len();
+                trusted.retain(|id| *id != user_id);
+
+                if trusted.len() != len {
+                    count += 1;
+                }
+            }
+        }
+
+        Ok(count)
+    }
+}
+
+#[async_trait]
+impl ModlogStore for MemoryStore {
+    async fn create_modlog(&self, modlog: &Modlog) -> Result<ObjectId, anyhow::Error> {
+        let id = modlog.id.unwrap_or_default();
+        let mut modlogs = lock(&self.modlogs);
+
+        if modlogs.iter().any(|modlog| modlog.id == Some(id)) {
+            anyhow::bail!("duplicate modlog id {id}");
+        }
+
+        modlogs.push(Modlog {
+            id: Some(id),
+            ..modlog.clone()
+        });
+
+        Ok(id)
+    }
+
+    async fn get_modlog(&self, id: ObjectId) -> Result<Option<Modlog>, anyhow::Error> {
+        let modlog = lock(&self.modlogs)
+            .iter()
+            .find(|modlog| modlog.id == Some(id))
+            .cloned();
+
+        Ok(modlog)
+    }
+
+    async fn find_modlogs(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Option<Id<UserMarker>>,
+        automated: Option<bool>,
+    ) -> Result<Vec<Modlog>, anyhow::Error> {
+        let modlogs = lock(&self.modlogs)
+            .iter()
+            .filter(|modlog| modlog.guild_id == guild_id)
+            .filter(|modlog| user_id.is_none_or(|user_id| modlog.user.id == user_id))
+            .filter(|modlog| automated.is_none_or(|automated| modlog.is_automated() == automated))
+            .cloned()
+            .collect();
+
+        Ok(modlogs)
+    }
+
+    async fn find_guild_modlogs(
+        &self,
+        guild_id: Id<GuildMarker>,
+        archived: bool,
+    ) -> Result<BoxStream<'static, Result<Modlog, anyhow::Error>>, anyhow::Error> {
+        let modlogs = match archived {
+            true => &self.archived_modlogs,
+            false => &self.modlogs,
+        };
+
+        let mut modlogs = lock(modlogs)
+            .iter()
+            .filter(|modlog| modlog.guild_id == guild_id)
+            .cloned()
+            .collect::<Vec<_>>();
+        modlogs.sort_by_key(|modlog| modlog.date);
+
+        Ok(stream::iter(modlogs.into_iter().map(Ok)).boxed())
+    }
+
+    async fn find_user_modlogs(
+        &self,
+        user_id: Id<UserMarker>,
+    ) -> Result<Vec<Modlog>, anyhow::Error> {
+        let mut modlogs = Vec::new();
+
+        for stored in [&self.modlogs, &self.archived_modlogs] {
+            modlogs.extend(
+                lock(stored)
+                    .iter()
+                    .filter(|modlog| modlog.user.id == user_id || modlog.moderator.id == user_id)
+                    .cloned(),
+            );
+        }
+
+        Ok(modlogs)
+    }
+
+    async fn archive_modlogs(
+        &self,
+        before: OffsetDateTime,
+        limit: i64,
+    ) -> Result<u64, anyhow::Error> {
+        let mut modlogs = lock(&self.modlogs);
+        modlogs.sort_by_key(|modlog| modlog.date);
+
+        let count = modlogs
+            .iter()
+            .take_while(|modlog| modlog.date < before)
+            .count()
+            .min(limit.try_into().unwrap_or(0));
+
+        lock(&self.archived_modlogs).extend(modlogs.drain(..count));
+
+        Ok(count as u64)
+    }
+
+    async fn delete_guild_modlogs(&self, guild_id: Id<GuildMarker>) -> Result<u64, anyhow::Error> {
+        let count = [&self.modlogs, &self.archived_modlogs]
+            .into_iter()
+            .map(|modlogs| retain(modlogs, |modlog| modlog.guild_id != guild_id))
+            .sum();
+
+        Ok(count)
+    }
+
+    async fn anonymize_user_modlogs(&self, user_id: Id<UserMarker>) -> Result<u64, anyhow::Error> {
+        let mut count = 0;
+
+        for stored in [&self.modlogs, &self.archived_modlogs] {
+            for modlog in lock(stored).iter_mut() {
+                for user in [&mut modlog.user, &mut modlog.moderator] {
+                    if user.id == user_id {
+                        *user = ModlogUser::anonymous();
+                        count += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(count)
+    }
+}
+
+#[async_trait]
+impl ScheduleStore for MemoryStore {
+    async fn schedule_job(&self, job: &ScheduledJob) -> Result<(), anyhow::Error> {
+        lock(&self.jobs).insert(job.key.clone(), job.clone());
+
+        Ok(())
+    }
+
+    async fn cancel_job(&self, key: &str) -> Result<bool, anyhow::Error> {
+        Ok(lock(&self.jobs).remove(key).is_some())
+    }
+
+    async fn claim_job(&self) -> Result<Option<ScheduledJob>, anyhow::Error> {
+        let now = OffsetDateTime::now_utc();
+        let mut jobs = lock(&self.jobs);
+
+        let job = jobs
+            .values_mut()
+            .filter(|job| job.run_at <= now)
+            .filter(|job| job.locked_until.is_none_or(|locked| locked <= now))
+            .min_by_key(|job| job.run_at);
+
+        Ok(job.map(|job| {
+            job.locked_until = Some(now + ScheduledJob::LOCK_DURATION);
+            job.attempts += 1;
+
+            job.clone()
+        }))
+    }
+
+    async fn complete_job(&self, job: &ScheduledJob) -> Result<(), anyhow::Error> {
+        let mut jobs = lock(&self.jobs);
+
+        if jobs.get(&job.key).map(|stored| stored.attempts) == Some(job.attempts) {
+            jobs.remove(&job.key);
+        }
+
+        Ok(())
+    }
+
+    async fn retry_job(&self, job: &ScheduledJob, error: String) -> Result<(), anyhow::Error> {
+        let mut jobs = lock(&self.jobs);
+
+        if let Some(stored) = jobs.get_mut(&job.key) {
+            if stored.attempts == job.attempts {
+                *stored = ScheduledJob {
+                    run_at: OffsetDateTime::now_utc() + job.retry_delay(),
+                    locked_until: None,
+                    last_error: Some(error),
+                    ..job.clone()
+                };
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ApiKeyStore for MemoryStore {
+    async fn create_api_key(&self, key: &ApiKey) -> Result<ObjectId, anyhow::Error> {
+        let id = key.id.unwrap_or_default();
+        lock(&self.api_keys).push(ApiKey {
+            id: Some(id),
+            ..key.clone()
+        });
+
+        Ok(id)
+    }
+
+    async fn get_api_key_by_hash(&self, hash: &str) -> Result<Option<ApiKey>, anyhow::Error> {
+        let keys = lock(&self.api_keys);
+
+        Ok(keys.iter().find(|key| key.hash == hash).cloned())
+    }
+
+    async fn get_guild_api_keys(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Vec<ApiKey>, anyhow::Error> {
+        let keys = lock(&self.api_keys)
+            .iter()
+            .filter(|key| key.guild_id == guild_id)
+            .cloned()
+            .collect();
+
+        Ok(keys)
+    }
+
+    async fn get_user_api_keys(
+        &self,
+        user_id: Id<UserMarker>,
+    ) -> Result<Vec<ApiKey>, anyhow::Error> {
+        let keys = lock(&self.api_keys)
+            .iter()
+            .filter(|key| key.created_by == user_id)
+            .cloned()
+            .collect();
+
+        Ok(keys)
+    }
+
+    async fn delete_api_key(
+        &self,
+        guild_id: Id<GuildMarker>,
+        name: &str,
+    ) -> Result<bool, anyhow::Error> {
+        let mut keys = lock(&self.api_keys);
+        let position = keys
+            .iter()
+            .position(|key| key.guild_id == guild_id && key.name == name);
+
+        Ok(position.map(|position| keys.remove(position)).is_some())
+    }
+
+    async fn delete_guild_api_keys(&self, guild_id: Id<GuildMarker>) -> Result<u64, anyhow::Error> {
+        Ok(retain(&self.api_keys, |key| key.guild_id != guild_id))
+    }
+
+    async fn delete_user_api_keys(&self, user_id: Id<UserMarker>) -> Result<u64, anyhow::Error> {
+        Ok(retain(&self.api_keys, |key| key.created_by != user_id))
+    }
+}
+
+#[async_trait]
+impl FeedbackStore for MemoryStore {
+    async fn create_antispam_feedback(
+        &self,
+        feedback: &AntiSpamFeedback,
+    ) -> Result<ObjectId, anyhow::Error> {
+        let id = feedback.id.unwrap_or_default();
+        lock(&self.feedback).push(AntiSpamFeedback {
+            id: Some(id),
+            ..feedback.clone()
+        });
+
+        Ok(id)
+    }
+
+    async fn find_user_antispam_feedback(
+        &self,
+        user_id: Id<UserMarker>,
+    ) -> Result<Vec<AntiSpamFeedback>, anyhow::Error> {
+        let feedback = lock(&self.feedback)
+            .iter()
+            .filter(|feedback| feedback.user_id == user_id || feedback.reported_by == user_id)
+            .cloned()
+            .collect();
+
+        Ok(feedback)
+    }
+
+    async fn anonymize_user_antispam_feedback(
+        &self,
+        user_id: Id<UserMarker>,
+    ) -> Result<u64, anyhow::Error> {
+        let mut count = 0;
+
+        for feedback in lock(&self.feedback).iter_mut() {
+            for id in [&mut feedback.user_id, &mut feedback.reported_by] {
+                if *id == user_id {
+                    *id = ANONYMOUS_USER_ID;
+                    count += 1;
+                }
+            }
+        }
+
+        Ok(count)
+    }
+}
+
+#[async_trait]
+impl StatsStore for MemoryStore {
+    async fn increment_guild_stat(
+        &self,
+        guild_id: Id<GuildMarker>,
+        stat: GuildStat,
+    ) -> Result<(), anyhow::Error> {
+        let mut stats = lock(&self.stats);
+        let stats = stats
+            .entry(guild_id)
+            .or_insert_with(|| GuildStats::new(guild_id));
+
+        match stat {
+            GuildStat::MembersVerified => stats.members_verified += 1,
+            GuildStat::SpamBlocked => stats.spam_blocked += 1,
+        }
+
+        Ok(())
+    }
+
+    async fn delete_guild_stats(&self, guild_id: Id<GuildMarker>) -> Result<(), anyhow::Error> {
+        lock(&self.stats).remove(&guild_id);
+
+        Ok(())
+    }
+
+    async fn record_command_usage(
+        &self,
+        name: &str,
+        duration: Duration,
+        failed: bool,
+    ) -> Result<(), anyhow::Error> {
+        let day = CommandUsage::today();
+        let id = format!("{name}:{day}");
+
+        let mut usage = lock(&self.command_usage);
+        let usage = usage.entry(id.clone()).or_insert_with(|| CommandUsage {
+            id,
+            name: name.to_string(),
+            day,
+            invocations: 0,
+            errors: 0,
+            duration_total: 0.0,
+        });
+
+        usage.invocations += 1;
+        usage.errors += i64::from(failed);
+        usage.duration_total += duration.as_secs_f64();
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OffboardingStore for MemoryStore {
+    async fn create_offboarding(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<GuildOffboarding, anyhow::Error> {
+        let offboarding = lock(&self.offboardings)
+            .entry(guild_id)
+            .or_insert_with(|| GuildOffboarding::new(guild_id))
+            .clone();
+
+        Ok(offboarding)
+    }
+
+    async fn update_offboarding(
+        &self,
+        offboarding: &GuildOffboarding,
+    ) -> Result<bool, anyhow::Error> {
+        let mut offboardings = lock(&self.offboardings);
+
+        match offboardings.get_mut(&offboarding.guild_id) {
+            Some(stored) => {
+                *stored = offboarding.clone();
+
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn delete_offboarding(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Option<GuildOffboarding>, anyhow::Error> {
+        Ok(lock(&self.offboardings).remove(&guild_id))
+    }
+
+    async fn pending_offboardings(&self) -> Result<Vec<GuildOffboarding>, anyhow::Error> {
+        Ok(lock(&self.offboardings).values().cloned().collect())
+    }
+
+    async fn archive_guild(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Option<GuildConfig>, anyhow::Error> {
+        let config = lock(&self.guilds).remove(&guild_id);
+
+        if let Some(config) = &config {
+            lock(&self.archived_guilds).insert(guild_id, config.clone());
+        }
+
+        Ok(config)
+    }
+
+    async fn restore_guild(&self, guild_id: Id<GuildMarker>) -> Result<bool, anyhow::Error> {
+        let config = lock(&self.archived_guilds).remove(&guild_id);
+
+        match config {
+            Some(config) => {
+                lock(&self.guilds).insert(guild_id, config);
+
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+/// Keep the items of a vector matching a predicate.
+///
+/// Returns the number of removed items.
+fn retain<T>(mutex: &Mutex<Vec<T>>, predicate: impl FnMut(&T) -> bool) -> u64 {
+    let mut items = lock(mutex);
+    let len = items.len();
+    items.retain(predicate);
+
+    (len - items.len()) as u64
+}
+
+/// Lock a mutex, ignoring poisoning.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|error| error.into_inner())
+}
@@ -7,17 +7,59 @@
 //! The following collections are used:
 //! - `guilds` ([GuildConfig]): configuration for guilds that uses the bot
 //! - `modlogs` ([Modlog]): moderation logs
+//! - `modlogs_archive` ([Modlog]): moderation logs older than the configured
+//!   archival delay
+//! - `antispam_feedback` ([AntiSpamFeedback]): anti-spam decisions reported
+//!   as false positives
+//! - `offboardings` ([GuildOffboarding]): guilds that removed the bot
+//! - `guilds_archive` ([GuildConfig]): archived configuration of guilds that
+//!   removed the bot
+//! - `scheduled_jobs` ([ScheduledJob]): delayed jobs run by the scheduler
+//! - `guild_stats` ([GuildStats]): aggregate statistics of guilds
+//! - `command_usage` ([CommandUsage]): daily usage of the commands
+//! - `api_keys` ([ApiKey]): API keys used by third-party integrations
 //!
-//! Each collection name is exported as an associated constant.
+//! Each collection name is exported as an associated constant. Indexes are
+//! created when connecting to the database.
+//!
+//! ## Storage traits
+//! The storage of the models is abstracted by the traits of the [`Store`]
+//! trait (such as [`GuildConfigStore`] or [`ModlogStore`]), implemented by
+//! [`DbClient`] and by the in-memory `MemoryStore` used in tests (with the
+//! `test-util` feature). Only the MongoDB-specific features, such as the
+//! change streams of [`DbClient::watch_guilds`], are not abstracted.
 //!
 //! [GuildConfig]: guild::GuildConfig
 //! [Modlog]: modlog::Modlog
+//! [AntiSpamFeedback]: feedback::AntiSpamFeedback
+//! [GuildOffboarding]: offboarding::GuildOffboarding
+//! [ScheduledJob]: job::ScheduledJob
+//! [GuildStats]: stats::GuildStats
+//! [CommandUsage]: stats::CommandUsage
+//! [ApiKey]: api_key::ApiKey
 
+mod api_key;
 mod client;
+mod feedback;
 mod guild;
+mod index;
+mod job;
+#[cfg(feature = "test-util")]
+mod memory;
 mod modlog;
+mod offboarding;
+mod stats;
+mod store;
 
 pub use client::DbClient;
+pub use guild::GuildChanges;
+#[cfg(feature = "test-util")]
+pub use memory::MemoryStore;
+pub use mongodb::change_stream::event::ResumeToken;
+pub use store::{
+    ApiKeyStore, FeedbackStore, GuildConfigStore, ModlogStore, OffboardingStore, ScheduleStore,
+    StatsStore, Store,
+};
 
 pub mod model {
     //! Models used to represent data in the MongoDB database.
@@ -25,7 +67,19 @@ pub mod model {
     //! See the [module documentation](crate::database) for more information.
 
     pub use super::{
-        guild::{CaptchaConfig, GuildConfig, ModerationConfig},
-        modlog::{Modlog, ModlogType, ModlogUser},
+        api_key::{ApiKey, ApiScope},
+        feedback::AntiSpamFeedback,
+        guild::{
+            AntiNukeConfig, AntiSpamConfig, AntiSpamProfile, AntiSpamPunishment,
+            AttachmentPolicyConfig, CaptchaConfig, DomainFilterConfig, Entitlements, GuildConfig,
+            InviteFilterConfig, LogCategory, LogChannels, LogsConfig, ModerationConfig,
+            NotificationsConfig, PhishingConfig, PremiumFeature, PremiumTier, QuarantineConfig,
+            QuestionnaireConfig, StatsConfig, WebhookAction, WebhookConfig, WordFilterConfig,
+            WordFilterRule, WordFilterRuleKind,
+        },
+        job::{JobAction, ScheduledJob},
+        modlog::{Modlog, ModlogAutomation, ModlogType, ModlogUser},
+        offboarding::GuildOffboarding,
+        stats::{CommandUsage, GuildStat, GuildStats},
     };
 }
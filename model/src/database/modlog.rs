@@ -1,9 +1,12 @@
 //! Models for the `modlogs` collection.
 
 use anyhow::anyhow;
+use async_trait::async_trait;
+use futures_util::{stream::BoxStream, StreamExt, TryStreamExt};
 use mongodb::{
-    bson::{doc, oid::ObjectId, to_document, Bson},
-    Cursor,
+    bson::{doc, oid::ObjectId, to_bson, to_document, Bson, Document},
+    error::{Error, ErrorKind},
+    options::{FindOptions, InsertManyOptions},
 };
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, skip_serializing_none, DisplayFromStr};
@@ -13,11 +16,12 @@ use twilight_model::{
         marker::{GuildMarker, UserMarker},
         Id,
     },
-    util::ImageHash,
+    user::User,
+    util::{ImageHash, Timestamp},
 };
 
-use super::DbClient;
-use crate::serde::{DateTimeAsBson, IdAsI64};
+use super::{job::bson_date, DbClient, ModlogStore};
+use crate::serde::{to_extjson, DateTimeAsBson, IdAsI64};
 
 /// Moderation log entry.
 ///
@@ -46,11 +50,92 @@ pub struct Modlog {
     pub reason: Option<String>,
     /// Optional notes attached to the moderation log.
     pub notes: Option<String>,
+    /// Metadata of the rule that triggered the action.
+    ///
+    /// This is only set for actions automatically performed by the bot (such
+    /// as anti-spam or anti-raid), to distinguish them from the actions of
+    /// human moderators.
+    pub automation: Option<ModlogAutomation>,
 }
 
 impl Modlog {
     /// Name of the MongoDB collection.
     pub const COLLECTION: &'static str = "modlogs";
+
+    /// Name of the MongoDB collection of archived moderation logs.
+    pub const ARCHIVE_COLLECTION: &'static str = "modlogs_archive";
+
+    /// Header of the CSV export (see [`to_csv_record`](Self::to_csv_record)).
+    pub const CSV_HEADER: &'static str = "id,date,kind,user_id,user_name,moderator_id,\
+        moderator_name,reason,notes,automation_rule,automation_details";
+
+    /// Whether the action has been automatically performed by the bot.
+    pub fn is_automated(&self) -> bool {
+        self.automation.is_some()
+    }
+
+    /// Serialize the moderation log into MongoDB relaxed extended JSON.
+    pub fn to_json(&self) -> Result<String, anyhow::Error> {
+        to_extjson(self)
+    }
+
+    /// Format the moderation log as a CSV record, without line terminator.
+    ///
+    /// Fields are quoted when needed. Text fields starting with a formula
+    /// character are prefixed with a quote to not be evaluated by spreadsheet
+    /// applications.
+    pub fn to_csv_record(&self) -> String {
+        let date = Timestamp::from_secs(self.date.unix_timestamp())
+            .map(|date| date.iso_8601().to_string())
+            .unwrap_or_else(|_| self.date.unix_timestamp().to_string());
+
+        let kind = match self.kind {
+            ModlogType::Kick => "kick",
+            ModlogType::Timeout => "timeout",
+            ModlogType::Ban => "ban",
+            ModlogType::Quarantine => "quarantine",
+        };
+
+        let automation = self.automation.as_ref();
+        let fields = [
+            self.id.map(|id| id.to_hex()).unwrap_or_default(),
+            date,
+            kind.to_owned(),
+            self.user.id.to_string(),
+            self.user.name.clone(),
+            self.moderator.id.to_string(),
+            self.moderator.name.clone(),
+            self.reason.clone().unwrap_or_default(),
+            self.notes.clone().unwrap_or_default(),
+            automation
+                .map(|automation| automation.rule.clone())
+                .unwrap_or_default(),
+            automation
+                .and_then(|automation| automation.details.clone())
+                .unwrap_or_default(),
+        ];
+
+        fields
+            .iter()
+            .map(|field| csv_field(field))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Escape a CSV field.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{value}")
+    } else {
+        value.to_owned()
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
 }
 
 /// Type of modlog entry.
@@ -58,6 +143,9 @@ impl Modlog {
 #[serde(rename_all = "lowercase")]
 pub enum ModlogType {
     Kick,
+    Timeout,
+    Ban,
+    Quarantine,
 }
 
 /// User model stored with modlog information.
@@ -76,10 +164,33 @@ pub struct ModlogUser {
     pub avatar: Option<ImageHash>,
 }
 
+impl From<&User> for ModlogUser {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id,
+            name: user.name.clone(),
+            discriminator: user.discriminator,
+            avatar: user.avatar,
+        }
+    }
+}
+
+/// Metadata of an automated moderation action.
+///
+/// See [`Modlog::automation`].
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ModlogAutomation {
+    /// Identifier of the rule that triggered the action (e.g. `antispam:mentions`).
+    pub rule: String,
+    /// Optional details about what triggered the rule.
+    pub details: Option<String>,
+}
+
 // Implementation of methods to query the database.
-impl DbClient {
-    /// Insert a new [`Modlog`] in the database.
-    pub async fn create_modlog(&self, modlog: &Modlog) -> Result<ObjectId, anyhow::Error> {
+#[async_trait]
+impl ModlogStore for DbClient {
+    async fn create_modlog(&self, modlog: &Modlog) -> Result<ObjectId, anyhow::Error> {
         let result = self
             .db()
             .collection::<Modlog>(Modlog::COLLECTION)
@@ -92,8 +203,7 @@ impl DbClient {
         }
     }
 
-    /// Get a [`Modlog`] from the database with its id.
-    pub async fn get_modlog(&self, id: ObjectId) -> Result<Option<Modlog>, anyhow::Error> {
+    async fn get_modlog(&self, id: ObjectId) -> Result<Option<Modlog>, anyhow::Error> {
         let query = doc! { "_id": id };
 
         let modlog = self
@@ -105,22 +215,183 @@ impl DbClient {
         Ok(modlog)
     }
 
-    /// Find multiple [`Modlog`]s from the database that match a given guild id
-    /// and optional user id.
-    pub async fn find_modlogs(
+    async fn find_modlogs(
         &self,
         guild_id: Id<GuildMarker>,
         user_id: Option<Id<UserMarker>>,
-    ) -> Result<Cursor<Modlog>, anyhow::Error> {
+        automated: Option<bool>,
+    ) -> Result<Vec<Modlog>, anyhow::Error> {
         let query = ModlogQuery { guild_id, user_id };
+        let mut query = to_document(&query)?;
 
-        let cursor = self
+        if let Some(automated) = automated {
+            query.insert("automation", doc! { "$exists": automated });
+        }
+
+        let modlogs = self
             .db()
             .collection::<Modlog>(Modlog::COLLECTION)
-            .find(to_document(&query)?, None)
+            .find(query, None)
+            .await?
+            .try_collect()
+            .await?;
+
+        Ok(modlogs)
+    }
+
+    async fn find_guild_modlogs(
+        &self,
+        guild_id: Id<GuildMarker>,
+        archived: bool,
+    ) -> Result<BoxStream<'static, Result<Modlog, anyhow::Error>>, anyhow::Error> {
+        let collection = match archived {
+            true => Modlog::ARCHIVE_COLLECTION,
+            false => Modlog::COLLECTION,
+        };
+        let options = FindOptions::builder().sort(doc! { "date": 1_i32 }).build();
+
+        let cursor = self
+            .db()
+            .collection::<Modlog>(collection)
+            .find(doc! { "guild_id": guild_id.get() as i64 }, options)
             .await?;
 
-        Ok(cursor)
+        Ok(cursor.map_err(anyhow::Error::from).boxed())
+    }
+
+    async fn find_user_modlogs(
+        &self,
+        user_id: Id<UserMarker>,
+    ) -> Result<Vec<Modlog>, anyhow::Error> {
+        let id = user_id.get() as i64;
+        let mut modlogs = Vec::new();
+
+        for collection in [Modlog::COLLECTION, Modlog::ARCHIVE_COLLECTION] {
+            let mut cursor = self
+                .db()
+                .collection::<Modlog>(collection)
+                .find(
+                    doc! { "$or": [{ "user.id": id }, { "moderator.id": id }] },
+                    None,
+                )
+                .await?;
+
+            while let Some(modlog) = cursor.try_next().await? {
+                modlogs.push(modlog);
+            }
+        }
+
+        Ok(modlogs)
+    }
+
+    async fn archive_modlogs(
+        &self,
+        before: OffsetDateTime,
+        limit: i64,
+    ) -> Result<u64, anyhow::Error> {
+        let options = FindOptions::builder()
+            .sort(doc! { "date": 1_i32 })
+            .limit(limit)
+            .build();
+
+        let modlogs = self
+            .db()
+            .collection::<Document>(Modlog::COLLECTION)
+            .find(doc! { "date": { "$lt": bson_date(before) } }, options)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        if modlogs.is_empty() {
+            return Ok(0);
+        }
+
+        // Moderation logs are inserted in the archive before being deleted, so
+        // that they are not lost if the operation is interrupted. Moderation
+        // logs already in the archive are ignored.
+        let options = InsertManyOptions::builder().ordered(false).build();
+        let result = self
+            .db()
+            .collection::<Document>(Modlog::ARCHIVE_COLLECTION)
+            .insert_many(&modlogs, options)
+            .await;
+
+        match result {
+            Ok(_) => {}
+            Err(error) if is_duplicate_key(&error) => {}
+            Err(error) => return Err(error.into()),
+        }
+
+        let ids = modlogs
+            .iter()
+            .filter_map(|modlog| modlog.get("_id").cloned())
+            .collect::<Vec<_>>();
+
+        let result = self
+            .db()
+            .collection::<Document>(Modlog::COLLECTION)
+            .delete_many(doc! { "_id": { "$in": ids } }, None)
+            .await?;
+
+        Ok(result.deleted_count)
+    }
+
+    async fn delete_guild_modlogs(&self, guild_id: Id<GuildMarker>) -> Result<u64, anyhow::Error> {
+        let mut count = 0;
+
+        for collection in [Modlog::COLLECTION, Modlog::ARCHIVE_COLLECTION] {
+            let result = self
+                .db()
+                .collection::<Modlog>(collection)
+                .delete_many(doc! { "guild_id": guild_id.get() as i64 }, None)
+                .await?;
+
+            count += result.deleted_count;
+        }
+
+        Ok(count)
+    }
+
+    async fn anonymize_user_modlogs(&self, user_id: Id<UserMarker>) -> Result<u64, anyhow::Error> {
+        let id = user_id.get() as i64;
+        let anonymous = to_bson(&ModlogUser::anonymous())?;
+        let mut count = 0;
+
+        for collection in [Modlog::COLLECTION, Modlog::ARCHIVE_COLLECTION] {
+            for field in ["user", "moderator"] {
+                let result = self
+                    .db()
+                    .collection::<Document>(collection)
+                    .update_many(
+                        doc! { format!("{field}.id"): id },
+                        doc! { "$set": { field: anonymous.clone() } },
+                        None,
+                    )
+                    .await?;
+
+                count += result.modified_count;
+            }
+        }
+
+        Ok(count)
+    }
+}
+
+/// Error code returned when inserting a document with an existing key.
+const DUPLICATE_KEY: i32 = 11000;
+
+/// Whether all the errors of a bulk insert are duplicate key errors.
+fn is_duplicate_key(error: &Error) -> bool {
+    match &*error.kind {
+        ErrorKind::BulkWrite(failure) => {
+            failure.write_concern_error.is_none()
+                && failure
+                    .write_errors
+                    .iter()
+                    .flatten()
+                    .all(|error| error.code == DUPLICATE_KEY)
+        }
+        _ => false,
     }
 }
 
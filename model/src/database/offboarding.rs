@@ -0,0 +1,193 @@
+//! Models for the `offboardings` collection.
+
+use async_trait::async_trait;
+use futures_util::TryStreamExt;
+use mongodb::{
+    bson::{doc, to_document},
+    options,
+};
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, skip_serializing_none};
+use time::OffsetDateTime;
+use twilight_model::id::{marker::GuildMarker, Id};
+
+use super::{guild::GuildConfig, DbClient, GuildConfigStore, OffboardingStore};
+use crate::serde::{DateTimeAsBson, IdAsI64};
+
+/// Offboarding of a guild that removed the bot.
+///
+/// When the bot is removed from a guild, its data is not deleted immediately
+/// but in several stages, in case the bot is added back:
+///
+/// - the guild configuration is moved to the `guilds_archive` collection
+///   after [`CONFIG_ARCHIVE_DELAY`](Self::CONFIG_ARCHIVE_DELAY)
+/// - the moderation logs are deleted after the configured retention period
+///
+/// The offboarding is cancelled if the bot rejoins the guild.
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct GuildOffboarding {
+    /// Id of the guild.
+    #[serde_as(as = "IdAsI64")]
+    #[serde(rename = "_id")]
+    pub guild_id: Id<GuildMarker>,
+    /// Date the bot was removed from the guild.
+    #[serde_as(as = "DateTimeAsBson")]
+    pub removed_at: OffsetDateTime,
+    /// Date the guild configuration was archived.
+    #[serde_as(as = "Option<DateTimeAsBson>")]
+    #[serde(default)]
+    pub config_archived_at: Option<OffsetDateTime>,
+    /// Date the moderation logs were deleted.
+    #[serde_as(as = "Option<DateTimeAsBson>")]
+    #[serde(default)]
+    pub modlogs_deleted_at: Option<OffsetDateTime>,
+}
+
+impl GuildOffboarding {
+    /// Name of the MongoDB collection.
+    pub const COLLECTION: &'static str = "offboardings";
+
+    /// Name of the MongoDB collection of archived guild configurations.
+    pub const ARCHIVE_COLLECTION: &'static str = "guilds_archive";
+
+    /// Delay before the guild configuration is archived.
+    pub const CONFIG_ARCHIVE_DELAY: time::Duration = time::Duration::days(30);
+
+    /// Initialize a new [`GuildOffboarding`] for a guild removed now.
+    pub fn new(guild_id: Id<GuildMarker>) -> Self {
+        Self {
+            guild_id,
+            removed_at: OffsetDateTime::now_utc(),
+            config_archived_at: None,
+            modlogs_deleted_at: None,
+        }
+    }
+
+    /// Whether all the stages of the offboarding are completed.
+    pub fn is_completed(&self) -> bool {
+        self.config_archived_at.is_some() && self.modlogs_deleted_at.is_some()
+    }
+}
+
+// Implementation of methods to query the database.
+#[async_trait]
+impl OffboardingStore for DbClient {
+    async fn create_offboarding(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<GuildOffboarding, anyhow::Error> {
+        let offboarding = GuildOffboarding::new(guild_id);
+        let options = options::FindOneAndUpdateOptions::builder()
+            .upsert(true)
+            .return_document(options::ReturnDocument::After)
+            .build();
+
+        let offboarding = self
+            .db()
+            .collection::<GuildOffboarding>(GuildOffboarding::COLLECTION)
+            .find_one_and_update(
+                doc! { "_id": guild_id.get() as i64 },
+                doc! { "$setOnInsert": to_document(&offboarding)? },
+                options,
+            )
+            .await?;
+
+        Ok(offboarding.unwrap_or_else(|| GuildOffboarding::new(guild_id)))
+    }
+
+    async fn update_offboarding(
+        &self,
+        offboarding: &GuildOffboarding,
+    ) -> Result<bool, anyhow::Error> {
+        let result = self
+            .db()
+            .collection::<GuildOffboarding>(GuildOffboarding::COLLECTION)
+            .replace_one(
+                doc! { "_id": offboarding.guild_id.get() as i64 },
+                offboarding,
+                None,
+            )
+            .await?;
+
+        Ok(result.matched_count > 0)
+    }
+
+    async fn delete_offboarding(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Option<GuildOffboarding>, anyhow::Error> {
+        let offboarding = self
+            .db()
+            .collection::<GuildOffboarding>(GuildOffboarding::COLLECTION)
+            .find_one_and_delete(doc! { "_id": guild_id.get() as i64 }, None)
+            .await?;
+
+        Ok(offboarding)
+    }
+
+    async fn pending_offboardings(&self) -> Result<Vec<GuildOffboarding>, anyhow::Error> {
+        let offboardings = self
+            .db()
+            .collection::<GuildOffboarding>(GuildOffboarding::COLLECTION)
+            .find(None, None)
+            .await?
+            .try_collect()
+            .await?;
+
+        Ok(offboardings)
+    }
+
+    // The configuration is copied to the archive before being deleted, without
+    // a transaction (transactions require a replica set). If the process stops
+    // between both writes, the configuration is in both collections, which is
+    // tolerated by `restore_guild` and by archiving the guild again.
+    async fn archive_guild(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Option<GuildConfig>, anyhow::Error> {
+        let query = doc! { "_id": guild_id.get() as i64 };
+
+        let config = self
+            .db()
+            .collection::<GuildConfig>(GuildConfig::COLLECTION)
+            .find_one(query.clone(), None)
+            .await?;
+
+        let config = match config {
+            Some(config) => config,
+            None => return Ok(None),
+        };
+
+        let options = options::ReplaceOptions::builder().upsert(true).build();
+        self.db()
+            .collection::<GuildConfig>(GuildOffboarding::ARCHIVE_COLLECTION)
+            .replace_one(query.clone(), &config, options)
+            .await?;
+
+        self.db()
+            .collection::<GuildConfig>(GuildConfig::COLLECTION)
+            .delete_one(query, None)
+            .await?;
+
+        Ok(Some(config))
+    }
+
+    async fn restore_guild(&self, guild_id: Id<GuildMarker>) -> Result<bool, anyhow::Error> {
+        let config = self
+            .db()
+            .collection::<GuildConfig>(GuildOffboarding::ARCHIVE_COLLECTION)
+            .find_one_and_delete(doc! { "_id": guild_id.get() as i64 }, None)
+            .await?;
+
+        match config {
+            Some(config) => {
+                self.update_guild(&config).await?;
+
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
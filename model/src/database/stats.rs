@@ -0,0 +1,200 @@
+//! Models for the `guild_stats` and `command_usage` collections.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use mongodb::{bson::doc, options};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use time::OffsetDateTime;
+use twilight_model::id::{marker::GuildMarker, Id};
+
+use super::{job::bson_date, modlog::Modlog, DbClient, StatsStore};
+use crate::serde::IdAsI64;
+
+/// Aggregate statistics of a guild.
+///
+/// Counters are incremented by the bot with
+/// [`StatsStore::increment_guild_stat`] and never reset. The guild can make them public with the
+/// [`StatsConfig`](super::guild::StatsConfig).
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct GuildStats {
+    /// Id of the guild.
+    #[serde_as(as = "IdAsI64")]
+    #[serde(rename = "_id")]
+    pub guild_id: Id<GuildMarker>,
+    /// Number of members that completed the captcha.
+    #[serde(default)]
+    pub members_verified: i64,
+    /// Number of spam messages deleted by the anti-spam.
+    #[serde(default)]
+    pub spam_blocked: i64,
+}
+
+impl GuildStats {
+    /// Name of the MongoDB collection.
+    pub const COLLECTION: &'static str = "guild_stats";
+
+    /// Initialize empty [`GuildStats`] for a guild.
+    pub fn new(guild_id: Id<GuildMarker>) -> Self {
+        Self {
+            guild_id,
+            members_verified: 0,
+            spam_blocked: 0,
+        }
+    }
+}
+
+/// Counter of the [`GuildStats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuildStat {
+    MembersVerified,
+    SpamBlocked,
+}
+
+impl GuildStat {
+    /// Name of the field of the counter.
+    fn field(self) -> &'static str {
+        match self {
+            GuildStat::MembersVerified => "members_verified",
+            GuildStat::SpamBlocked => "spam_blocked",
+        }
+    }
+}
+
+/// Daily usage of a command.
+///
+/// Usage is recorded by the bot when [`BotConfig::command_usage_stats`] is
+/// enabled, with one document per command and day (UTC).
+///
+/// [`BotConfig::command_usage_stats`]: crate::config::BotConfig::command_usage_stats
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CommandUsage {
+    /// Id of the document (`{name}:{day}`).
+    #[serde(rename = "_id")]
+    pub id: String,
+    /// Name of the command.
+    pub name: String,
+    /// Day of the usage (`YYYY-MM-DD`).
+    pub day: String,
+    /// Number of invocations.
+    pub invocations: i64,
+    /// Number of invocations that failed.
+    pub errors: i64,
+    /// Total processing time of the invocations (in seconds).
+    pub duration_total: f64,
+}
+
+impl CommandUsage {
+    /// Name of the MongoDB collection.
+    pub const COLLECTION: &'static str = "command_usage";
+
+    /// Current day (UTC), in the format of the `day` field.
+    pub(super) fn today() -> String {
+        let today = OffsetDateTime::now_utc().date();
+
+        format!(
+            "{:04}-{:02}-{:02}",
+            today.year(),
+            u8::from(today.month()),
+            today.day()
+        )
+    }
+}
+
+// Implementation of methods to query the database.
+impl DbClient {
+    /// Get the [`GuildStats`] of a guild.
+    ///
+    /// Empty statistics are returned if no counter has been incremented yet.
+    pub async fn get_guild_stats(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<GuildStats, anyhow::Error> {
+        let stats = self
+            .db()
+            .collection::<GuildStats>(GuildStats::COLLECTION)
+            .find_one(doc! { "_id": guild_id.get() as i64 }, None)
+            .await?;
+
+        Ok(stats.unwrap_or_else(|| GuildStats::new(guild_id)))
+    }
+
+    /// Count the moderation logs of a guild created after a given date.
+    pub async fn count_modlogs_since(
+        &self,
+        guild_id: Id<GuildMarker>,
+        since: OffsetDateTime,
+    ) -> Result<u64, anyhow::Error> {
+        let count = self
+            .db()
+            .collection::<Modlog>(Modlog::COLLECTION)
+            .count_documents(
+                doc! { "guild_id": guild_id.get() as i64, "date": { "$gte": bson_date(since) } },
+                None,
+            )
+            .await?;
+
+        Ok(count)
+    }
+}
+
+#[async_trait]
+impl StatsStore for DbClient {
+    async fn increment_guild_stat(
+        &self,
+        guild_id: Id<GuildMarker>,
+        stat: GuildStat,
+    ) -> Result<(), anyhow::Error> {
+        let options = options::UpdateOptions::builder().upsert(true).build();
+
+        self.db()
+            .collection::<GuildStats>(GuildStats::COLLECTION)
+            .update_one(
+                doc! { "_id": guild_id.get() as i64 },
+                doc! { "$inc": { stat.field(): 1_i64 } },
+                options,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_guild_stats(&self, guild_id: Id<GuildMarker>) -> Result<(), anyhow::Error> {
+        self.db()
+            .collection::<GuildStats>(GuildStats::COLLECTION)
+            .delete_one(doc! { "_id": guild_id.get() as i64 }, None)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn record_command_usage(
+        &self,
+        name: &str,
+        duration: Duration,
+        failed: bool,
+    ) -> Result<(), anyhow::Error> {
+        let day = CommandUsage::today();
+        let options = options::UpdateOptions::builder().upsert(true).build();
+
+        self.db()
+            .collection::<CommandUsage>(CommandUsage::COLLECTION)
+            .update_one(
+                doc! { "_id": format!("{name}:{day}") },
+                doc! {
+                    "$setOnInsert": { "name": name, "day": &day },
+                    "$inc": {
+                        "invocations": 1_i64,
+                        "errors": i64::from(failed),
+                        "duration_total": duration.as_secs_f64(),
+                    },
+                },
+                options,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
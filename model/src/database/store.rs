@@ -0,0 +1,314 @@
+//! Storage traits.
+//!
+//! These traits abstract the storage of the models, so that the code using
+//! them does not depend on a specific backend. They are implemented by
+//! [`DbClient`] for MongoDB and by `MemoryStore` for tests (with the
+//! `test-util` feature).
+//!
+//! [`DbClient`]: super::DbClient
+
+use std::{fmt::Debug, time::Duration};
+
+use async_trait::async_trait;
+use futures_util::stream::BoxStream;
+use mongodb::bson::oid::ObjectId;
+use time::OffsetDateTime;
+use twilight_model::id::{
+    marker::{GuildMarker, UserMarker},
+    Id,
+};
+
+use super::model::{
+    AntiSpamFeedback, ApiKey, GuildConfig, GuildOffboarding, GuildStat, Modlog, ScheduledJob,
+};
+
+/// Storage of all the models abstracted by the storage traits.
+///
+/// This trait is implemented for all types that implement the other storage
+/// traits, and is used as a trait object.
+pub trait Store:
+    GuildConfigStore
+    + ModlogStore
+    + ScheduleStore
+    + ApiKeyStore
+    + FeedbackStore
+    + StatsStore
+    + OffboardingStore
+    + Debug
+{
+}
+
+impl<T> Store for T where
+    T: GuildConfigStore
+        + ModlogStore
+        + ScheduleStore
+        + ApiKeyStore
+        + FeedbackStore
+        + StatsStore
+        + OffboardingStore
+        + Debug
+{
+}
+
+/// Storage of the [`GuildConfig`]s.
+#[async_trait]
+pub trait GuildConfigStore: Send + Sync {
+    /// Get the [`GuildConfig`] for a given guild_id, if it exists.
+    async fn get_guild(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Option<GuildConfig>, anyhow::Error>;
+
+    /// Get the [`GuildConfig`] for a given guild_id, or create it with default configuration.
+    async fn get_guild_or_create(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<GuildConfig, anyhow::Error>;
+
+    /// Update or insert a [`GuildConfig`].
+    async fn update_guild(&self, guild: &GuildConfig) -> Result<(), anyhow::Error>;
+
+    /// Get the guilds where a user is trusted by the anti-nuke protection.
+    async fn find_trusting_guilds(
+        &self,
+        user_id: Id<UserMarker>,
+    ) -> Result<Vec<Id<GuildMarker>>, anyhow::Error>;
+
+    /// Remove a user from the trusted users of all the guilds, including the
+    /// archived configurations.
+    ///
+    /// Returns the number of modified configurations.
+    async fn remove_trusted_user(&self, user_id: Id<UserMarker>) -> Result<u64, anyhow::Error>;
+}
+
+/// Storage of the [`Modlog`]s.
+#[async_trait]
+pub trait ModlogStore: Send + Sync {
+    /// Insert a new [`Modlog`].
+    async fn create_modlog(&self, modlog: &Modlog) -> Result<ObjectId, anyhow::Error>;
+
+    /// Get a [`Modlog`] with its id.
+    async fn get_modlog(&self, id: ObjectId) -> Result<Option<Modlog>, anyhow::Error>;
+
+    /// Find the [`Modlog`]s that match a given guild id and optional user id.
+    ///
+    /// If `automated` is set, only automated (`true`) or human (`false`)
+    /// moderation logs are returned.
+    async fn find_modlogs(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Option<Id<UserMarker>>,
+        automated: Option<bool>,
+    ) -> Result<Vec<Modlog>, anyhow::Error>;
+
+    /// Find all the [`Modlog`]s of a guild, from the oldest to the newest.
+    ///
+    /// If `archived` is set, the archived moderation logs are returned.
+    async fn find_guild_modlogs(
+        &self,
+        guild_id: Id<GuildMarker>,
+        archived: bool,
+    ) -> Result<BoxStream<'static, Result<Modlog, anyhow::Error>>, anyhow::Error>;
+
+    /// Find the [`Modlog`]s where a user is the target or the moderator,
+    /// including the archived ones.
+    async fn find_user_modlogs(
+        &self,
+        user_id: Id<UserMarker>,
+    ) -> Result<Vec<Modlog>, anyhow::Error>;
+
+    /// Move up to `limit` [`Modlog`]s older than `before` to the archive.
+    ///
+    /// Returns the number of archived moderation logs.
+    async fn archive_modlogs(
+        &self,
+        before: OffsetDateTime,
+        limit: i64,
+    ) -> Result<u64, anyhow::Error>;
+
+    /// Delete all the [`Modlog`]s of a guild, including the archived ones.
+    ///
+    /// Returns the number of deleted moderation logs.
+    async fn delete_guild_modlogs(&self, guild_id: Id<GuildMarker>) -> Result<u64, anyhow::Error>;
+
+    /// Replace a user by [`ModlogUser::anonymous`] in all the [`Modlog`]s,
+    /// including the archived ones.
+    ///
+    /// Returns the number of modified moderation logs.
+    ///
+    /// [`ModlogUser::anonymous`]: super::model::ModlogUser::anonymous
+    async fn anonymize_user_modlogs(&self, user_id: Id<UserMarker>) -> Result<u64, anyhow::Error>;
+}
+
+/// Storage of the [`ScheduledJob`]s.
+#[async_trait]
+pub trait ScheduleStore: Send + Sync {
+    /// Schedule a job.
+    ///
+    /// An existing job with the same key is replaced.
+    async fn schedule_job(&self, job: &ScheduledJob) -> Result<(), anyhow::Error>;
+
+    /// Cancel a scheduled job.
+    ///
+    /// Returns whether a job has been cancelled.
+    async fn cancel_job(&self, key: &str) -> Result<bool, anyhow::Error>;
+
+    /// Claim a job that should be run.
+    ///
+    /// The job is locked for [`ScheduledJob::LOCK_DURATION`] so that other
+    /// processes do not run it at the same time, and its number of attempts is
+    /// incremented. Returns [`None`] if no job should be run.
+    async fn claim_job(&self) -> Result<Option<ScheduledJob>, anyhow::Error>;
+
+    /// Mark a job as completed, removing it from the store.
+    ///
+    /// The job is not removed if it has been rescheduled or claimed again
+    /// since it was claimed.
+    async fn complete_job(&self, job: &ScheduledJob) -> Result<(), anyhow::Error>;
+
+    /// Schedule the next attempt of a failed job.
+    async fn retry_job(&self, job: &ScheduledJob, error: String) -> Result<(), anyhow::Error>;
+}
+
+/// Storage of the [`ApiKey`]s.
+#[async_trait]
+pub trait ApiKeyStore: Send + Sync {
+    /// Insert a new [`ApiKey`].
+    async fn create_api_key(&self, key: &ApiKey) -> Result<ObjectId, anyhow::Error>;
+
+    /// Get an [`ApiKey`] with the hash of its token.
+    async fn get_api_key_by_hash(&self, hash: &str) -> Result<Option<ApiKey>, anyhow::Error>;
+
+    /// Get all the [`ApiKey`]s of a guild.
+    async fn get_guild_api_keys(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Vec<ApiKey>, anyhow::Error>;
+
+    /// Get all the [`ApiKey`]s created by a user.
+    async fn get_user_api_keys(
+        &self,
+        user_id: Id<UserMarker>,
+    ) -> Result<Vec<ApiKey>, anyhow::Error>;
+
+    /// Delete an [`ApiKey`] of a guild with its name.
+    ///
+    /// Returns `false` if no key with this name exists.
+    async fn delete_api_key(
+        &self,
+        guild_id: Id<GuildMarker>,
+        name: &str,
+    ) -> Result<bool, anyhow::Error>;
+
+    /// Delete all the [`ApiKey`]s of a guild.
+    ///
+    /// Returns the number of deleted keys.
+    async fn delete_guild_api_keys(&self, guild_id: Id<GuildMarker>) -> Result<u64, anyhow::Error>;
+
+    /// Delete all the [`ApiKey`]s created by a user.
+    ///
+    /// Returns the number of deleted keys.
+    async fn delete_user_api_keys(&self, user_id: Id<UserMarker>) -> Result<u64, anyhow::Error>;
+}
+
+/// Storage of the [`AntiSpamFeedback`]s.
+#[async_trait]
+pub trait FeedbackStore: Send + Sync {
+    /// Insert a new [`AntiSpamFeedback`].
+    async fn create_antispam_feedback(
+        &self,
+        feedback: &AntiSpamFeedback,
+    ) -> Result<ObjectId, anyhow::Error>;
+
+    /// Find the [`AntiSpamFeedback`]s about or reported by a user.
+    async fn find_user_antispam_feedback(
+        &self,
+        user_id: Id<UserMarker>,
+    ) -> Result<Vec<AntiSpamFeedback>, anyhow::Error>;
+
+    /// Replace a user by [`ANONYMOUS_USER_ID`] in all the
+    /// [`AntiSpamFeedback`]s.
+    ///
+    /// Returns the number of modified reports.
+    ///
+    /// [`ANONYMOUS_USER_ID`]: crate::privacy::ANONYMOUS_USER_ID
+    async fn anonymize_user_antispam_feedback(
+        &self,
+        user_id: Id<UserMarker>,
+    ) -> Result<u64, anyhow::Error>;
+}
+
+/// Storage of the guild statistics and command usage.
+#[async_trait]
+pub trait StatsStore: Send + Sync {
+    /// Increment a counter of the [`GuildStats`] of a guild.
+    ///
+    /// [`GuildStats`]: super::model::GuildStats
+    async fn increment_guild_stat(
+        &self,
+        guild_id: Id<GuildMarker>,
+        stat: GuildStat,
+    ) -> Result<(), anyhow::Error>;
+
+    /// Delete the [`GuildStats`] of a guild.
+    ///
+    /// [`GuildStats`]: super::model::GuildStats
+    async fn delete_guild_stats(&self, guild_id: Id<GuildMarker>) -> Result<(), anyhow::Error>;
+
+    /// Record an invocation of a command in the [`CommandUsage`] of the day.
+    ///
+    /// [`CommandUsage`]: super::model::CommandUsage
+    async fn record_command_usage(
+        &self,
+        name: &str,
+        duration: Duration,
+        failed: bool,
+    ) -> Result<(), anyhow::Error>;
+}
+
+/// Storage of the [`GuildOffboarding`]s and archived guild configurations.
+#[async_trait]
+pub trait OffboardingStore: Send + Sync {
+    /// Start the offboarding of a guild.
+    ///
+    /// If the guild is already being offboarded, the existing offboarding is
+    /// kept.
+    async fn create_offboarding(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<GuildOffboarding, anyhow::Error>;
+
+    /// Update a [`GuildOffboarding`].
+    ///
+    /// Returns `false` if the offboarding no longer exists (it has been
+    /// cancelled in the meantime).
+    async fn update_offboarding(
+        &self,
+        offboarding: &GuildOffboarding,
+    ) -> Result<bool, anyhow::Error>;
+
+    /// Remove the offboarding of a guild.
+    ///
+    /// The removed offboarding is returned if it existed.
+    async fn delete_offboarding(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Option<GuildOffboarding>, anyhow::Error>;
+
+    /// Get all pending offboardings.
+    async fn pending_offboardings(&self) -> Result<Vec<GuildOffboarding>, anyhow::Error>;
+
+    /// Move the configuration of a guild to the archive.
+    ///
+    /// Returns the archived configuration, if any.
+    async fn archive_guild(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<Option<GuildConfig>, anyhow::Error>;
+
+    /// Restore the archived configuration of a guild.
+    ///
+    /// The archived configuration overwrites the current one, if any. Returns
+    /// whether a configuration has been restored.
+    async fn restore_guild(&self, guild_id: Id<GuildMarker>) -> Result<bool, anyhow::Error>;
+}
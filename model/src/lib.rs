@@ -17,3 +17,6 @@ mod serde;
 pub mod cache;
 pub mod config;
 pub mod database;
+pub mod metrics;
+pub mod privacy;
+pub mod storage;
@@ -0,0 +1,349 @@
+//! Prometheus metrics.
+//!
+//! This module exposes a minimal metrics [`Registry`] with counters and
+//! histograms, and renders them in the Prometheus text format with [`render`].
+//! A global registry is available with [`registry`], and is used to instrument
+//! the shared clients (cache hits and misses, database query latency) as well
+//! as the bot itself.
+//!
+//! Each bot process periodically publishes a snapshot of its registry in the
+//! cache (see [`ProcessMetrics`]). The web service merges the snapshots of all
+//! the processes with its own metrics, adding a `process` label to each series,
+//! and exposes them on the `/metrics` route.
+//!
+//! [`ProcessMetrics`]: crate::cache::model::metrics::ProcessMetrics
+
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+/// Upper bounds of the histogram buckets (in seconds).
+pub const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Global metrics registry.
+static REGISTRY: Lazy<Registry> = Lazy::new(Registry::default);
+
+/// Get the global metrics registry.
+pub fn registry() -> &'static Registry {
+    &REGISTRY
+}
+
+/// Kind of a metric.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetricKind {
+    Counter,
+    Histogram,
+}
+
+impl MetricKind {
+    /// Name of the kind in the Prometheus format.
+    fn name(self) -> &'static str {
+        match self {
+            MetricKind::Counter => "counter",
+            MetricKind::Histogram => "histogram",
+        }
+    }
+}
+
+/// Labels of a series, sorted by name.
+pub type Labels = Vec<(String, String)>;
+
+/// Value of a series.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SeriesValue {
+    Counter(u64),
+    Histogram {
+        /// Number of observations in each bucket of [`BUCKETS`] (not
+        /// cumulative).
+        buckets: Vec<u64>,
+        /// Sum of the observed values.
+        sum: f64,
+        /// Number of observations.
+        count: u64,
+    },
+}
+
+/// Snapshot of a metric and all its series.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricFamily {
+    pub name: String,
+    pub help: String,
+    pub kind: MetricKind,
+    pub series: Vec<(Labels, SeriesValue)>,
+}
+
+impl MetricFamily {
+    /// Add a label to all the series of the metric.
+    pub fn with_label(mut self, name: &str, value: &str) -> Self {
+        for (labels, _) in &mut self.series {
+            labels.push((name.to_owned(), value.to_owned()));
+            labels.sort();
+        }
+
+        self
+    }
+}
+
+/// Metric stored in a [`Registry`].
+#[derive(Debug)]
+struct Metric {
+    help: &'static str,
+    kind: MetricKind,
+    series: BTreeMap<Labels, SeriesValue>,
+}
+
+/// Registry of metrics.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug, Default)]
+pub struct Registry {
+    metrics: Mutex<BTreeMap<&'static str, Metric>>,
+}
+
+impl Registry {
+    /// Increment a counter.
+    pub fn inc_counter(&self, name: &'static str, help: &'static str, labels: &[(&str, &str)]) {
+        self.add_counter(name, help, labels, 1);
+    }
+
+    /// Add a value to a counter.
+    pub fn add_counter(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&str, &str)],
+        value: u64,
+    ) {
+        self.update(name, help, MetricKind::Counter, labels, |series| {
+            if let SeriesValue::Counter(count) = series {
+                *count += value;
+            }
+        });
+    }
+
+    /// Record an observation in a histogram (in seconds).
+    pub fn observe(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&str, &str)],
+        value: f64,
+    ) {
+        self.update(name, help, MetricKind::Histogram, labels, |series| {
+            if let SeriesValue::Histogram {
+                buckets,
+                sum,
+                count,
+            } = series
+            {
+                if let Some(index) = BUCKETS.iter().position(|bound| value <= *bound) {
+                    buckets[index] += 1;
+                }
+
+                *sum += value;
+                *count += 1;
+            }
+        });
+    }
+
+    /// Take a snapshot of all the metrics, sorted by name.
+    pub fn snapshot(&self) -> Vec<MetricFamily> {
+        let metrics = self.metrics.lock().expect("metrics registry poisoned");
+
+        metrics
+            .iter()
+            .map(|(name, metric)| MetricFamily {
+                name: (*name).to_owned(),
+                help: metric.help.to_owned(),
+                kind: metric.kind,
+                series: metric
+                    .series
+                    .iter()
+                    .map(|(labels, value)| (labels.clone(), value.clone()))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    fn update(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        kind: MetricKind,
+        labels: &[(&str, &str)],
+        update: impl FnOnce(&mut SeriesValue),
+    ) {
+        let mut labels: Labels = labels
+            .iter()
+            .map(|(name, value)| ((*name).to_owned(), (*value).to_owned()))
+            .collect();
+        labels.sort();
+
+        let mut metrics = self.metrics.lock().expect("metrics registry poisoned");
+        let metric = metrics.entry(name).or_insert_with(|| Metric {
+            help,
+            kind,
+            series: BTreeMap::new(),
+        });
+
+        let value = metric.series.entry(labels).or_insert_with(|| match kind {
+            MetricKind::Counter => SeriesValue::Counter(0),
+            MetricKind::Histogram => SeriesValue::Histogram {
+                buckets: vec![0; BUCKETS.len()],
+                sum: 0.0,
+                count: 0,
+            },
+        });
+
+        update(value);
+    }
+}
+
+/// Render metrics in the Prometheus text format.
+///
+/// Metrics with the same name are merged, so that the snapshots of multiple
+/// processes can be rendered together.
+pub fn render(families: impl IntoIterator<Item = MetricFamily>) -> String {
+    let mut merged: BTreeMap<String, MetricFamily> = BTreeMap::new();
+    for family in families {
+        match merged.get_mut(&family.name) {
+            Some(existing) => existing.series.extend(family.series),
+            None => {
+                merged.insert(family.name.clone(), family);
+            }
+        }
+    }
+
+    let mut output = String::new();
+    for family in merged.values() {
+        writeln!(output, "# HELP {} {}", family.name, family.help).ok();
+        writeln!(output, "# TYPE {} {}", family.name, family.kind.name()).ok();
+
+        for (labels, value) in &family.series {
+            match value {
+                SeriesValue::Counter(count) => {
+                    writeln!(
+                        output,
+                        "{}{} {count}",
+                        family.name,
+                        format_labels(labels, None)
+                    )
+                    .ok();
+                }
+                SeriesValue::Histogram {
+                    buckets,
+                    sum,
+                    count,
+                } => {
+                    let mut cumulative = 0;
+                    for (bound, bucket) in BUCKETS.iter().zip(buckets) {
+                        cumulative += bucket;
+                        let labels = format_labels(labels, Some(&bound.to_string()));
+                        writeln!(output, "{}_bucket{labels} {cumulative}", family.name).ok();
+                    }
+
+                    let inf = format_labels(labels, Some("+Inf"));
+                    let labels = format_labels(labels, None);
+                    writeln!(output, "{}_bucket{inf} {count}", family.name).ok();
+                    writeln!(output, "{}_sum{labels} {sum}", family.name).ok();
+                    writeln!(output, "{}_count{labels} {count}", family.name).ok();
+                }
+            }
+        }
+    }
+
+    output
+}
+
+/// Format the labels of a series, with an optional `le` label for histogram
+/// buckets.
+fn format_labels(labels: &Labels, le: Option<&str>) -> String {
+    let mut formatted = labels
+        .iter()
+        .map(|(name, value)| format!("{name}=\"{}\"", escape_label(value)))
+        .collect::<Vec<_>>();
+
+    if let Some(le) = le {
+        formatted.push(format!("le=\"{le}\""));
+    }
+
+    if formatted.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", formatted.join(","))
+    }
+}
+
+/// Escape a label value.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_render_counter() {
+        let registry = Registry::default();
+        registry.inc_counter("test_total", "Test counter", &[("kind", "a")]);
+        registry.inc_counter("test_total", "Test counter", &[("kind", "a")]);
+        registry.inc_counter("test_total", "Test counter", &[("kind", "b\"")]);
+
+        let expected = "# HELP test_total Test counter\n\
+            # TYPE test_total counter\n\
+            test_total{kind=\"a\"} 2\n\
+            test_total{kind=\"b\\\"\"} 1\n";
+
+        assert_eq!(render(registry.snapshot()), expected);
+    }
+
+    #[test]
+    fn test_render_histogram() {
+        let registry = Registry::default();
+        registry.observe("test_seconds", "Test histogram", &[], 0.02);
+        registry.observe("test_seconds", "Test histogram", &[], 20.0);
+
+        let output = render(registry.snapshot());
+
+        assert!(output.contains("# TYPE test_seconds histogram\n"));
+        assert!(output.contains("test_seconds_bucket{le=\"0.01\"} 0\n"));
+        assert!(output.contains("test_seconds_bucket{le=\"0.025\"} 1\n"));
+        assert!(output.contains("test_seconds_bucket{le=\"10\"} 1\n"));
+        assert!(output.contains("test_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(output.contains("test_seconds_sum 20.02\n"));
+        assert!(output.contains("test_seconds_count 2\n"));
+    }
+
+    #[test]
+    fn test_render_merge() {
+        let registry = Registry::default();
+        registry.inc_counter("test_total", "Test counter", &[]);
+
+        let families = registry
+            .snapshot()
+            .into_iter()
+            .map(|family| family.with_label("process", "bot"))
+            .chain(
+                registry
+                    .snapshot()
+                    .into_iter()
+                    .map(|family| family.with_label("process", "web")),
+            );
+
+        let expected = "# HELP test_total Test counter\n\
+            # TYPE test_total counter\n\
+            test_total{process=\"bot\"} 1\n\
+            test_total{process=\"web\"} 1\n";
+
+        assert_eq!(render(families), expected);
+    }
+}
@@ -0,0 +1,293 @@
+//! Export and erasure of the data stored about a user.
+//!
+//! These functions are used to process data-protection requests. The
+//! [`export_user_data`] function collects the data that references a user in
+//! the database and the cache, and [`erase_user_data`] irreversibly removes
+//! the user from it.
+//!
+//! Moderation logs and anti-spam feedback are kept since they belong to the
+//! guilds, but the user is replaced by an anonymous placeholder
+//! ([`ANONYMOUS_USER_ID`]). Scheduled jobs are not modified since they undo
+//! sanctions (such as temporary bans) and are deleted once run. Cached
+//! messages are not collected either: they expire after a few minutes.
+
+use mongodb::bson::{to_document, Bson, Document};
+use serde::Serialize;
+use serde_with::serde_as;
+use twilight_model::id::{
+    marker::{GuildMarker, UserMarker},
+    Id,
+};
+
+use crate::{
+    cache::{
+        discord::{CachedMember, CachedVoiceState},
+        model::{
+            interaction::PendingCaptcha,
+            join::MemberJoinSource,
+            message::{InviteWarnings, MentionOffenses},
+            quarantine::QuarantinedMember,
+            session::WebSession,
+        },
+        CacheClient, RedisModel,
+    },
+    database::{
+        model::{AntiSpamFeedback, ApiKey, GuildConfig, Modlog, ModlogUser},
+        Store,
+    },
+    serde::{to_extjson, IdAsI64},
+};
+
+/// Id of the placeholder user that replaces erased users.
+///
+/// This id is not a valid Discord snowflake and cannot be used by a real user.
+pub const ANONYMOUS_USER_ID: Id<UserMarker> = Id::new(1);
+
+/// Name of the placeholder user that replaces erased users.
+pub const ANONYMOUS_USER_NAME: &str = "Deleted User";
+
+impl ModlogUser {
+    /// Placeholder user that replaces erased users in moderation logs.
+    pub fn anonymous() -> Self {
+        Self {
+            id: ANONYMOUS_USER_ID,
+            name: ANONYMOUS_USER_NAME.to_owned(),
+            discriminator: 0,
+            avatar: None,
+        }
+    }
+}
+
+/// Data stored about a user.
+///
+/// Web sessions are not included since they only contain the session token.
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+pub struct UserData {
+    /// Id of the user.
+    #[serde_as(as = "IdAsI64")]
+    pub user_id: Id<UserMarker>,
+    /// Moderation logs where the user is the target or the moderator,
+    /// including the archived ones.
+    pub modlogs: Vec<Modlog>,
+    /// Anti-spam feedback about or reported by the user.
+    pub antispam_feedback: Vec<AntiSpamFeedback>,
+    /// API keys created by the user.
+    pub api_keys: Vec<ApiKey>,
+    /// Guilds where the user is trusted by the anti-nuke protection.
+    #[serde_as(as = "Vec<IdAsI64>")]
+    pub trusted_in: Vec<Id<GuildMarker>>,
+    /// Cached guild members of the user.
+    pub members: Vec<CachedMember>,
+    /// Cached voice states of the user.
+    pub voice_states: Vec<CachedVoiceState>,
+    /// Quarantines of the user.
+    pub quarantines: Vec<QuarantinedMember>,
+    /// Invites recently used by the user to join guilds.
+    pub join_sources: Vec<MemberJoinSource>,
+    /// Pending captcha verifications of the user.
+    pub captchas: Vec<PendingCaptcha>,
+    /// Recent mention abuses of the user.
+    pub mention_offenses: Vec<MentionOffenses>,
+    /// Recent invite filter warnings of the user.
+    pub invite_warnings: Vec<InviteWarnings>,
+}
+
+impl UserData {
+    /// Serialize the data into MongoDB relaxed extended JSON.
+    ///
+    /// This format is used since the models use serializers targeted to the
+    /// database format (see the [crate documentation](crate)).
+    pub fn to_json(&self) -> Result<String, anyhow::Error> {
+        to_extjson(self)
+    }
+
+    /// Serialize the data exported to the user itself.
+    ///
+    /// The moderator and the notes of the moderation logs, and the reporter of
+    /// the anti-spam feedback, are only visible to the guild moderators. They
+    /// are removed from the entries where the user is only the target.
+    pub fn to_self_service_json(&self) -> Result<String, anyhow::Error> {
+        let id = self.user_id.get() as i64;
+        let mut data = to_document(self)?;
+
+        for modlog in documents_mut(&mut data, "modlogs") {
+            if modlog.get_document("moderator")?.get_i64("id")? != id {
+                modlog.remove("moderator");
+                modlog.remove("notes");
+            }
+        }
+
+        for feedback in documents_mut(&mut data, "antispam_feedback") {
+            if feedback.get_i64("reported_by")? != id {
+                feedback.remove("reported_by");
+            }
+        }
+
+        to_extjson(&data)
+    }
+}
+
+/// Get the documents of an array field.
+fn documents_mut<'a>(
+    document: &'a mut Document,
+    key: &str,
+) -> impl Iterator<Item = &'a mut Document> {
+    document
+        .get_array_mut(key)
+        .into_iter()
+        .flatten()
+        .filter_map(Bson::as_document_mut)
+}
+
+/// Number of entries modified when erasing the data of a user.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ErasureSummary {
+    /// Anonymized moderation logs.
+    pub modlogs: u64,
+    /// Anonymized anti-spam feedback.
+    pub antispam_feedback: u64,
+    /// Deleted API keys.
+    pub api_keys: u64,
+    /// Guild configurations the user was removed from.
+    pub guilds: u64,
+    /// Deleted cache entries.
+    pub cache: u64,
+}
+
+/// Export the data stored about a user.
+pub async fn export_user_data(
+    user_id: Id<UserMarker>,
+    store: &dyn Store,
+    cache: &CacheClient,
+) -> Result<UserData, anyhow::Error> {
+    let modlogs = store.find_user_modlogs(user_id).await?;
+    let antispam_feedback = store.find_user_antispam_feedback(user_id).await?;
+    let api_keys = store.get_user_api_keys(user_id).await?;
+    let trusted_in = store.find_trusting_guilds(user_id).await?;
+
+    Ok(UserData {
+        user_id,
+        modlogs,
+        antispam_feedback,
+        api_keys,
+        trusted_in,
+        members: cache.scan(&member_pattern::<CachedMember>(user_id)).await?,
+        voice_states: cache
+            .scan(&member_pattern::<CachedVoiceState>(user_id))
+            .await?,
+        quarantines: cache
+            .scan(&member_pattern::<QuarantinedMember>(user_id))
+            .await?,
+        join_sources: cache
+            .scan(&member_pattern::<MemberJoinSource>(user_id))
+            .await?,
+        captchas: cache
+            .scan(&member_pattern::<PendingCaptcha>(user_id))
+            .await?,
+        mention_offenses: cache
+            .scan(&member_pattern::<MentionOffenses>(user_id))
+            .await?,
+        invite_warnings: cache
+            .scan(&member_pattern::<InviteWarnings>(user_id))
+            .await?,
+    })
+}
+
+/// Irreversibly erase the data stored about a user.
+///
+/// The user is replaced by [`ModlogUser::anonymous`] in moderation logs and by
+/// [`ANONYMOUS_USER_ID`] in anti-spam feedback. API keys created by the user
+/// are deleted, since they act with the permissions of their creator. The
+/// cache entries of the user and its web sessions are deleted.
+///
+/// Cached members and voice states are added back by the gateway events if
+/// the user is still in a guild with the bot.
+pub async fn erase_user_data(
+    user_id: Id<UserMarker>,
+    store: &dyn Store,
+    cache: &CacheClient,
+) -> Result<ErasureSummary, anyhow::Error> {
+    let mut summary = ErasureSummary {
+        modlogs: store.anonymize_user_modlogs(user_id).await?,
+        antispam_feedback: store.anonymize_user_antispam_feedback(user_id).await?,
+        api_keys: store.delete_user_api_keys(user_id).await?,
+        ..Default::default()
+    };
+
+    // Cached configurations are deleted to not restore the trusted user when
+    // the configuration is updated.
+    let trusted_in = store.find_trusting_guilds(user_id).await?;
+    summary.guilds = store.remove_trusted_user(user_id).await?;
+
+    for guild_id in trusted_in {
+        cache.delete_from::<GuildConfig>(&guild_id).await?;
+    }
+
+    summary.cache += erase_cache::<CachedMember>(user_id, cache).await?;
+    summary.cache += erase_cache::<CachedVoiceState>(user_id, cache).await?;
+    summary.cache += erase_cache::<QuarantinedMember>(user_id, cache).await?;
+    summary.cache += erase_cache::<MemberJoinSource>(user_id, cache).await?;
+    summary.cache += erase_cache::<PendingCaptcha>(user_id, cache).await?;
+    summary.cache += erase_cache::<MentionOffenses>(user_id, cache).await?;
+    summary.cache += erase_cache::<InviteWarnings>(user_id, cache).await?;
+
+    let sessions = cache.scan::<WebSession>(&WebSession::key_from("*")).await?;
+    for session in sessions.iter().filter(|session| session.user_id == user_id) {
+        cache.delete(session).await?;
+        summary.cache += 1;
+    }
+
+    Ok(summary)
+}
+
+/// Delete the cached values of a user in all guilds.
+async fn erase_cache<T>(user_id: Id<UserMarker>, cache: &CacheClient) -> Result<u64, anyhow::Error>
+where
+    T: RedisModel<Id = (Id<GuildMarker>, Id<UserMarker>)>,
+{
+    let values = cache.scan::<T>(&member_pattern::<T>(user_id)).await?;
+
+    for value in &values {
+        cache.delete(value).await?;
+    }
+
+    Ok(values.len() as u64)
+}
+
+/// Pattern matching the keys of a per-guild model for a user.
+///
+/// The guild id is replaced by a placeholder in the key, then by a wildcard.
+fn member_pattern<T>(user_id: Id<UserMarker>) -> String
+where
+    T: RedisModel<Id = (Id<GuildMarker>, Id<UserMarker>)>,
+{
+    let placeholder = Id::new(u64::MAX);
+    let key = T::key_from(&(placeholder, user_id));
+
+    key.replace(&placeholder.to_string(), "*")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::migrate::KEY_PREFIX;
+
+    #[test]
+    fn test_member_pattern() {
+        let user_id = Id::new(42);
+
+        assert_eq!(
+            member_pattern::<CachedMember>(user_id),
+            format!("{KEY_PREFIX}:member:*:42")
+        );
+        assert_eq!(
+            member_pattern::<QuarantinedMember>(user_id),
+            "quarantine:*:42"
+        );
+        assert_eq!(
+            member_pattern::<MentionOffenses>(user_id),
+            "mention-offenses:*:42"
+        );
+    }
+}
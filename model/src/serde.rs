@@ -153,6 +153,36 @@ impl SerializeAs<OffsetDateTime> for DateTimeAsBson {
     }
 }
 
+/// Serialize a value into MongoDB relaxed extended JSON.
+///
+/// This is used to export models whose fields use serializers targeted to the
+/// database format. Dates serialized with [`DateTimeAsBson`] store the UNIX
+/// timestamp in place of the milliseconds, so they are converted back to
+/// actual dates.
+pub(crate) fn to_extjson<T: Serialize>(value: &T) -> Result<String, anyhow::Error> {
+    fn convert_dates(value: bson::Bson) -> bson::Bson {
+        match value {
+            bson::Bson::DateTime(date) => bson::Bson::DateTime(bson::DateTime::from_millis(
+                date.timestamp_millis().saturating_mul(1000),
+            )),
+            bson::Bson::Document(document) => bson::Bson::Document(
+                document
+                    .into_iter()
+                    .map(|(key, value)| (key, convert_dates(value)))
+                    .collect(),
+            ),
+            bson::Bson::Array(array) => {
+                bson::Bson::Array(array.into_iter().map(convert_dates).collect())
+            }
+            value => value,
+        }
+    }
+
+    let value = convert_dates(bson::to_bson(value)?);
+
+    Ok(value.into_relaxed_extjson().to_string())
+}
+
 /// Serialize [`OffsetDateTime`] as a UNIX timestamp ([`i64`]).
 ///
 /// This type implement [`SerializeAs`] and [`DeserializeAs`] and should be
@@ -0,0 +1,36 @@
+//! Local disk storage.
+
+use std::{path::PathBuf, sync::Arc};
+
+use tokio::fs;
+
+/// Local disk storage backend.
+#[derive(Debug, Clone)]
+pub(super) struct LocalStorage {
+    /// Folder where the artifacts are stored.
+    root: Arc<PathBuf>,
+}
+
+impl LocalStorage {
+    /// Initialize a new [`LocalStorage`] in the given folder.
+    pub(super) fn new(root: &str) -> Self {
+        Self {
+            root: Arc::new(PathBuf::from(root)),
+        }
+    }
+
+    /// Write an artifact on the disk.
+    ///
+    /// Missing parent folders are created.
+    pub(super) async fn put(&self, key: &str, data: &[u8]) -> Result<(), anyhow::Error> {
+        let path = self.root.join(key);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        fs::write(path, data).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+//! Storage of generated artifacts.
+//!
+//! Some features generate files that are sent to the users, such as ticket
+//! transcripts, moderation logs exports or configuration backups. These
+//! artifacts are stored using the backend configured in [`StorageConfig`]:
+//!
+//! - **local**: the artifacts are written on the local disk.
+//! - **s3**: the artifacts are uploaded to an S3-compatible object storage.
+//! - **none**: the artifacts are not stored.
+//!
+//! If a public URL is configured, the stored artifacts are returned as an
+//! [`Artifact::Url`]. Otherwise, they are returned as an
+//! [`Artifact::Attachment`] that should be sent with the response message.
+
+mod local;
+mod s3;
+
+use std::sync::Arc;
+
+use anyhow::{bail, Context};
+use twilight_model::id::{marker::GuildMarker, Id};
+
+use self::{local::LocalStorage, s3::S3Storage};
+use crate::config::{StorageConfig, StorageKind};
+
+/// Kind of generated artifact.
+///
+/// Each kind of artifact is stored in a separate folder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactKind {
+    /// Transcript of a channel.
+    Transcript,
+    /// Export of moderation logs.
+    ModlogExport,
+    /// Backup of a guild configuration.
+    Backup,
+}
+
+impl ArtifactKind {
+    /// Folder used to store the artifacts of this kind.
+    pub fn folder(&self) -> &'static str {
+        match self {
+            ArtifactKind::Transcript => "transcripts",
+            ArtifactKind::ModlogExport => "exports",
+            ArtifactKind::Backup => "backups",
+        }
+    }
+}
+
+/// Stored artifact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Artifact {
+    /// Public URL of the artifact.
+    Url(String),
+    /// Artifact content, to be sent as an attachment.
+    Attachment { filename: String, data: Vec<u8> },
+}
+
+/// Artifact storage.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug, Clone)]
+pub struct ArtifactStorage {
+    backend: Backend,
+    public_url: Option<Arc<str>>,
+}
+
+#[derive(Debug, Clone)]
+enum Backend {
+    Local(LocalStorage),
+    S3(S3Storage),
+    None,
+}
+
+impl ArtifactStorage {
+    /// Initialize a new [`ArtifactStorage`] from its configuration.
+    pub fn new(config: &StorageConfig) -> Result<Self, anyhow::Error> {
+        let backend = match config.storage_kind {
+            StorageKind::Local => Backend::Local(LocalStorage::new(&config.storage_path)),
+            StorageKind::S3 => Backend::S3(S3Storage::new(config).context("invalid s3 config")?),
+            StorageKind::None => Backend::None,
+        };
+
+        let public_url = match backend {
+            Backend::None => None,
+            _ => config.storage_url.as_deref().map(Arc::from),
+        };
+
+        Ok(Self {
+            backend,
+            public_url,
+        })
+    }
+
+    /// Store an artifact generated for a guild.
+    ///
+    /// The `filename` must not contain path separators. If an artifact with
+    /// the same name already exists, it is replaced.
+    pub async fn store(
+        &self,
+        kind: ArtifactKind,
+        guild_id: Id<GuildMarker>,
+        filename: &str,
+        data: Vec<u8>,
+    ) -> Result<Artifact, anyhow::Error> {
+        if filename.is_empty() || filename.contains(['/', '\\']) || filename.starts_with('.') {
+            bail!("invalid artifact filename: {filename}");
+        }
+
+        let key = format!("{}/{}/{}", kind.folder(), guild_id, filename);
+
+        match &self.backend {
+            Backend::Local(local) => local.put(&key, &data).await?,
+            Backend::S3(s3) => s3.put(&key, data.clone()).await?,
+            Backend::None => {}
+        }
+
+        match &self.public_url {
+            Some(url) => Ok(Artifact::Url(format!("{url}/{key}"))),
+            None => Ok(Artifact::Attachment {
+                filename: filename.to_owned(),
+                data,
+            }),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use mongodb::bson::{self, DateTime};
+use pretty_assertions::assert_eq;
+use raidprotect_model::database::model::{ApiKey, ApiScope};
+use time::OffsetDateTime;
+use twilight_model::id::Id;
+
+#[test]
+fn test_api_key_bson() {
+    let key = ApiKey {
+        id: None,
+        guild_id: Id::new(1),
+        name: "integration".to_owned(),
+        hash: ApiKey::hash_token("rp_token"),
+        scopes: vec![ApiScope::Guild, ApiScope::Events],
+        created_by: Id::new(2),
+        created_at: OffsetDateTime::from_unix_timestamp(1_628_594_197).unwrap(),
+    };
+
+    let expected = bson::doc! {
+        "guild_id": 1_i64,
+        "name": "integration",
+        "hash": ApiKey::hash_token("rp_token"),
+        "scopes": ["guild", "events"],
+        "created_by": 2_i64,
+        "created_at": DateTime::from_millis(1_628_594_197),
+    };
+
+    assert_eq!(bson::to_document(&key).unwrap(), expected);
+    assert_eq!(bson::from_document::<ApiKey>(expected).unwrap(), key);
+}
+
+#[test]
+fn test_hash_token() {
+    let hash = ApiKey::hash_token("rp_token");
+
+    assert_eq!(hash.len(), 64);
+    assert_eq!(hash, ApiKey::hash_token("rp_token"));
+    assert_ne!(hash, ApiKey::hash_token("rp_other"));
+}
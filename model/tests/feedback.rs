@@ -0,0 +1,33 @@
+use mongodb::bson::{self, DateTime};
+use pretty_assertions::assert_eq;
+use raidprotect_model::database::model::{AntiSpamFeedback, AntiSpamPunishment};
+use time::OffsetDateTime;
+use twilight_model::id::Id;
+
+#[test]
+fn test_feedback_bson() {
+    let feedback = AntiSpamFeedback {
+        id: None,
+        guild_id: Id::new(1),
+        user_id: Id::new(2),
+        rules: vec!["antispam:rate".to_owned(), "antispam:duplicates".to_owned()],
+        punishment: AntiSpamPunishment::Timeout,
+        reported_by: Id::new(3),
+        date: OffsetDateTime::from_unix_timestamp(1_628_594_197).unwrap(),
+    };
+
+    let expected = bson::doc! {
+        "guild_id": 1_i64,
+        "user_id": 2_i64,
+        "rules": ["antispam:rate", "antispam:duplicates"],
+        "punishment": "timeout",
+        "reported_by": 3_i64,
+        "date": DateTime::from_millis(1_628_594_197),
+    };
+
+    assert_eq!(bson::to_document(&feedback).unwrap(), expected);
+    assert_eq!(
+        bson::from_document::<AntiSpamFeedback>(expected).unwrap(),
+        feedback
+    );
+}
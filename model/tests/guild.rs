@@ -1,7 +1,17 @@
 use mongodb::bson;
 use pretty_assertions::assert_eq;
-use raidprotect_model::database::model::{CaptchaConfig, GuildConfig, ModerationConfig};
+use raidprotect_model::{
+    cache::RedisModel,
+    database::model::{
+        AntiNukeConfig, AntiSpamConfig, AntiSpamPunishment, AttachmentPolicyConfig, CaptchaConfig,
+        DomainFilterConfig, Entitlements, GuildConfig, InviteFilterConfig, LogCategory,
+        LogChannels, LogsConfig, ModerationConfig, NotificationsConfig, PhishingConfig,
+        PremiumFeature, PremiumTier, QuarantineConfig, QuestionnaireConfig, StatsConfig,
+        WebhookAction, WebhookConfig, WordFilterConfig, WordFilterRule, WordFilterRuleKind,
+    },
+};
 use serde_test::{assert_tokens, Token};
+use time::OffsetDateTime;
 use twilight_model::id::Id;
 
 #[test]
@@ -13,7 +23,7 @@ fn test_guild_default() {
         &[
             Token::Struct {
                 name: "GuildConfig",
-                len: 5,
+                len: 18,
             },
             Token::Str("_id"),
             Token::I64(1),
@@ -21,6 +31,30 @@ fn test_guild_default() {
             Token::None,
             Token::Str("lang"),
             Token::Str("fr"),
+            Token::Str("logs"),
+            Token::Struct {
+                name: "LogsConfig",
+                len: 7,
+            },
+            Token::Str("channels"),
+            Token::Struct {
+                name: "LogChannels",
+                len: 0,
+            },
+            Token::StructEnd,
+            Token::Str("message_delete"),
+            Token::Bool(false),
+            Token::Str("member_join"),
+            Token::Bool(false),
+            Token::Str("member_leave"),
+            Token::Bool(false),
+            Token::Str("member_update"),
+            Token::Bool(false),
+            Token::Str("security_alerts"),
+            Token::Bool(false),
+            Token::Str("voice"),
+            Token::Bool(false),
+            Token::StructEnd,
             Token::Str("moderation"),
             Token::Struct {
                 name: "ModerationConfig",
@@ -39,6 +73,141 @@ fn test_guild_default() {
             Token::Str("enabled"),
             Token::Bool(false),
             Token::StructEnd,
+            Token::Str("antispam"),
+            Token::Struct {
+                name: "AntiSpamConfig",
+                len: 9,
+            },
+            Token::Str("enabled"),
+            Token::Bool(false),
+            Token::Str("max_messages"),
+            Token::U8(5),
+            Token::Str("message_interval"),
+            Token::U8(5),
+            Token::Str("max_mentions"),
+            Token::U8(5),
+            Token::Str("max_duplicates"),
+            Token::U8(3),
+            Token::Str("punishment"),
+            Token::UnitVariant {
+                name: "AntiSpamPunishment",
+                variant: "timeout",
+            },
+            Token::Str("punishment_duration"),
+            Token::U32(600),
+            Token::Str("mention_sanction"),
+            Token::Bool(false),
+            Token::Str("emergency_slowmode"),
+            Token::U16(0),
+            Token::StructEnd,
+            Token::Str("invites"),
+            Token::Struct {
+                name: "InviteFilterConfig",
+                len: 4,
+            },
+            Token::Str("enabled"),
+            Token::Bool(false),
+            Token::Str("resolve"),
+            Token::Bool(true),
+            Token::Str("max_warnings"),
+            Token::U8(3),
+            Token::Str("timeout_duration"),
+            Token::U32(600),
+            Token::StructEnd,
+            Token::Str("domains"),
+            Token::Struct {
+                name: "DomainFilterConfig",
+                len: 2,
+            },
+            Token::Str("enabled"),
+            Token::Bool(false),
+            Token::Str("block_phishing"),
+            Token::Bool(true),
+            Token::StructEnd,
+            Token::Str("filter"),
+            Token::Struct {
+                name: "WordFilterConfig",
+                len: 0,
+            },
+            Token::StructEnd,
+            Token::Str("attachments"),
+            Token::Struct {
+                name: "AttachmentPolicyConfig",
+                len: 3,
+            },
+            Token::Str("enabled"),
+            Token::Bool(false),
+            Token::Str("block_executables"),
+            Token::Bool(true),
+            Token::Str("max_attachments"),
+            Token::U8(0),
+            Token::StructEnd,
+            Token::Str("phishing"),
+            Token::Struct {
+                name: "PhishingConfig",
+                len: 2,
+            },
+            Token::Str("enabled"),
+            Token::Bool(false),
+            Token::Str("threshold"),
+            Token::U8(70),
+            Token::StructEnd,
+            Token::Str("quarantine"),
+            Token::Struct {
+                name: "QuarantineConfig",
+                len: 0,
+            },
+            Token::StructEnd,
+            Token::Str("webhooks"),
+            Token::Struct {
+                name: "WebhookConfig",
+                len: 2,
+            },
+            Token::Str("enabled"),
+            Token::Bool(false),
+            Token::Str("action"),
+            Token::UnitVariant {
+                name: "WebhookAction",
+                variant: "delete",
+            },
+            Token::StructEnd,
+            Token::Str("antinuke"),
+            Token::Struct {
+                name: "AntiNukeConfig",
+                len: 3,
+            },
+            Token::Str("enabled"),
+            Token::Bool(false),
+            Token::Str("max_actions"),
+            Token::U8(5),
+            Token::Str("interval"),
+            Token::U16(60),
+            Token::StructEnd,
+            Token::Str("stats"),
+            Token::Struct {
+                name: "StatsConfig",
+                len: 1,
+            },
+            Token::Str("public"),
+            Token::Bool(false),
+            Token::StructEnd,
+            Token::Str("notifications"),
+            Token::Struct {
+                name: "NotificationsConfig",
+                len: 0,
+            },
+            Token::StructEnd,
+            Token::Str("entitlements"),
+            Token::Struct {
+                name: "Entitlements",
+                len: 1,
+            },
+            Token::Str("tier"),
+            Token::UnitVariant {
+                name: "PremiumTier",
+                variant: "free",
+            },
+            Token::StructEnd,
             Token::StructEnd,
         ],
     );
@@ -50,6 +219,18 @@ fn test_guild_full() {
         id: Id::new(1),
         logs_chan: Some(Id::new(2)),
         lang: "en".to_owned(),
+        logs: LogsConfig {
+            channels: LogChannels {
+                members: Some(Id::new(18)),
+                ..Default::default()
+            },
+            message_delete: true,
+            member_join: true,
+            member_leave: false,
+            member_update: true,
+            security_alerts: true,
+            voice: true,
+        },
         moderation: ModerationConfig {
             roles: vec![Id::new(3), Id::new(4)],
             enforce_reason: true,
@@ -62,6 +243,79 @@ fn test_guild_full() {
             role: Some(Id::new(7)),
             verified_roles: vec![Id::new(8), Id::new(9)],
             logs: Some(Id::new(10)),
+            questionnaire: Some(QuestionnaireConfig {
+                questions: vec!["Why do you join?".to_owned()],
+                review_channel: Id::new(11),
+                replace_captcha: true,
+            }),
+            strict_until: None,
+        },
+        antispam: AntiSpamConfig {
+            enabled: true,
+            max_messages: 8,
+            message_interval: 10,
+            max_mentions: 3,
+            max_duplicates: 4,
+            punishment: AntiSpamPunishment::Kick,
+            punishment_duration: 3600,
+            mention_sanction: true,
+            emergency_slowmode: 30,
+            exempt_roles: vec![Id::new(12)],
+            exempt_channels: vec![Id::new(13)],
+            disabled_detectors: vec!["antispam:rate".to_owned()],
+        },
+        invites: InviteFilterConfig {
+            enabled: true,
+            resolve: false,
+            allowed_guilds: vec![Id::new(14)],
+            max_warnings: 2,
+            timeout_duration: 1800,
+        },
+        domains: DomainFilterConfig {
+            enabled: true,
+            block_phishing: false,
+            blocked: vec!["evil.com".to_owned()],
+            allowed: vec!["raidprotect.org".to_owned()],
+        },
+        filter: WordFilterConfig {
+            rules: vec![WordFilterRule {
+                kind: WordFilterRuleKind::Wildcard,
+                pattern: "spam*".to_owned(),
+            }],
+        },
+        attachments: AttachmentPolicyConfig {
+            enabled: true,
+            block_executables: false,
+            max_attachments: 4,
+            image_only_channels: vec![Id::new(15)],
+            exempt_roles: vec![Id::new(16)],
+        },
+        phishing: PhishingConfig {
+            enabled: true,
+            threshold: 55,
+        },
+        quarantine: QuarantineConfig {
+            role: Some(Id::new(17)),
+        },
+        webhooks: WebhookConfig {
+            enabled: true,
+            action: WebhookAction::Disable,
+        },
+        antinuke: AntiNukeConfig {
+            enabled: true,
+            max_actions: 3,
+            interval: 30,
+            trusted_users: vec![Id::new(19)],
+        },
+        stats: StatsConfig { public: true },
+        notifications: NotificationsConfig {
+            url: Some("https://example.com/webhook".to_owned()),
+            secret: Some("secret".to_owned()),
+        },
+        entitlements: Entitlements {
+            tier: PremiumTier::Free,
+            features: vec![PremiumFeature::RegexFilter],
+            expires_at: None,
         },
     };
 
@@ -70,7 +324,7 @@ fn test_guild_full() {
         &[
             Token::Struct {
                 name: "GuildConfig",
-                len: 5,
+                len: 18,
             },
             Token::Str("_id"),
             Token::I64(1),
@@ -79,6 +333,34 @@ fn test_guild_full() {
             Token::I64(2),
             Token::Str("lang"),
             Token::Str("en"),
+            // logs
+            Token::Str("logs"),
+            Token::Struct {
+                name: "LogsConfig",
+                len: 7,
+            },
+            Token::Str("channels"),
+            Token::Struct {
+                name: "LogChannels",
+                len: 1,
+            },
+            Token::Str("members"),
+            Token::Some,
+            Token::I64(18),
+            Token::StructEnd,
+            Token::Str("message_delete"),
+            Token::Bool(true),
+            Token::Str("member_join"),
+            Token::Bool(true),
+            Token::Str("member_leave"),
+            Token::Bool(false),
+            Token::Str("member_update"),
+            Token::Bool(true),
+            Token::Str("security_alerts"),
+            Token::Bool(true),
+            Token::Str("voice"),
+            Token::Bool(true),
+            Token::StructEnd,
             // moderation
             Token::Str("moderation"),
             Token::Struct {
@@ -99,7 +381,7 @@ fn test_guild_full() {
             Token::Str("captcha"),
             Token::Struct {
                 name: "CaptchaConfig",
-                len: 6,
+                len: 7,
             },
             Token::Str("enabled"),
             Token::Bool(true),
@@ -120,6 +402,235 @@ fn test_guild_full() {
             Token::Str("logs"),
             Token::Some,
             Token::I64(10),
+            Token::Str("questionnaire"),
+            Token::Some,
+            Token::Struct {
+                name: "QuestionnaireConfig",
+                len: 3,
+            },
+            Token::Str("questions"),
+            Token::Seq { len: Some(1) },
+            Token::Str("Why do you join?"),
+            Token::SeqEnd,
+            Token::Str("review_channel"),
+            Token::I64(11),
+            Token::Str("replace_captcha"),
+            Token::Bool(true),
+            Token::StructEnd,
+            Token::StructEnd,
+            // antispam
+            Token::Str("antispam"),
+            Token::Struct {
+                name: "AntiSpamConfig",
+                len: 12,
+            },
+            Token::Str("enabled"),
+            Token::Bool(true),
+            Token::Str("max_messages"),
+            Token::U8(8),
+            Token::Str("message_interval"),
+            Token::U8(10),
+            Token::Str("max_mentions"),
+            Token::U8(3),
+            Token::Str("max_duplicates"),
+            Token::U8(4),
+            Token::Str("punishment"),
+            Token::UnitVariant {
+                name: "AntiSpamPunishment",
+                variant: "kick",
+            },
+            Token::Str("punishment_duration"),
+            Token::U32(3600),
+            Token::Str("mention_sanction"),
+            Token::Bool(true),
+            Token::Str("emergency_slowmode"),
+            Token::U16(30),
+            Token::Str("exempt_roles"),
+            Token::Seq { len: Some(1) },
+            Token::I64(12),
+            Token::SeqEnd,
+            Token::Str("exempt_channels"),
+            Token::Seq { len: Some(1) },
+            Token::I64(13),
+            Token::SeqEnd,
+            Token::Str("disabled_detectors"),
+            Token::Seq { len: Some(1) },
+            Token::Str("antispam:rate"),
+            Token::SeqEnd,
+            Token::StructEnd,
+            // invites
+            Token::Str("invites"),
+            Token::Struct {
+                name: "InviteFilterConfig",
+                len: 5,
+            },
+            Token::Str("enabled"),
+            Token::Bool(true),
+            Token::Str("resolve"),
+            Token::Bool(false),
+            Token::Str("allowed_guilds"),
+            Token::Seq { len: Some(1) },
+            Token::I64(14),
+            Token::SeqEnd,
+            Token::Str("max_warnings"),
+            Token::U8(2),
+            Token::Str("timeout_duration"),
+            Token::U32(1800),
+            Token::StructEnd,
+            // domains
+            Token::Str("domains"),
+            Token::Struct {
+                name: "DomainFilterConfig",
+                len: 4,
+            },
+            Token::Str("enabled"),
+            Token::Bool(true),
+            Token::Str("block_phishing"),
+            Token::Bool(false),
+            Token::Str("blocked"),
+            Token::Seq { len: Some(1) },
+            Token::Str("evil.com"),
+            Token::SeqEnd,
+            Token::Str("allowed"),
+            Token::Seq { len: Some(1) },
+            Token::Str("raidprotect.org"),
+            Token::SeqEnd,
+            Token::StructEnd,
+            // filter
+            Token::Str("filter"),
+            Token::Struct {
+                name: "WordFilterConfig",
+                len: 1,
+            },
+            Token::Str("rules"),
+            Token::Seq { len: Some(1) },
+            Token::Struct {
+                name: "WordFilterRule",
+                len: 2,
+            },
+            Token::Str("kind"),
+            Token::UnitVariant {
+                name: "WordFilterRuleKind",
+                variant: "wildcard",
+            },
+            Token::Str("pattern"),
+            Token::Str("spam*"),
+            Token::StructEnd,
+            Token::SeqEnd,
+            Token::StructEnd,
+            // attachments
+            Token::Str("attachments"),
+            Token::Struct {
+                name: "AttachmentPolicyConfig",
+                len: 5,
+            },
+            Token::Str("enabled"),
+            Token::Bool(true),
+            Token::Str("block_executables"),
+            Token::Bool(false),
+            Token::Str("max_attachments"),
+            Token::U8(4),
+            Token::Str("image_only_channels"),
+            Token::Seq { len: Some(1) },
+            Token::I64(15),
+            Token::SeqEnd,
+            Token::Str("exempt_roles"),
+            Token::Seq { len: Some(1) },
+            Token::I64(16),
+            Token::SeqEnd,
+            Token::StructEnd,
+            // phishing
+            Token::Str("phishing"),
+            Token::Struct {
+                name: "PhishingConfig",
+                len: 2,
+            },
+            Token::Str("enabled"),
+            Token::Bool(true),
+            Token::Str("threshold"),
+            Token::U8(55),
+            Token::StructEnd,
+            // quarantine
+            Token::Str("quarantine"),
+            Token::Struct {
+                name: "QuarantineConfig",
+                len: 1,
+            },
+            Token::Str("role"),
+            Token::Some,
+            Token::I64(17),
+            Token::StructEnd,
+            // webhooks
+            Token::Str("webhooks"),
+            Token::Struct {
+                name: "WebhookConfig",
+                len: 2,
+            },
+            Token::Str("enabled"),
+            Token::Bool(true),
+            Token::Str("action"),
+            Token::UnitVariant {
+                name: "WebhookAction",
+                variant: "disable",
+            },
+            Token::StructEnd,
+            // antinuke
+            Token::Str("antinuke"),
+            Token::Struct {
+                name: "AntiNukeConfig",
+                len: 4,
+            },
+            Token::Str("enabled"),
+            Token::Bool(true),
+            Token::Str("max_actions"),
+            Token::U8(3),
+            Token::Str("interval"),
+            Token::U16(30),
+            Token::Str("trusted_users"),
+            Token::Seq { len: Some(1) },
+            Token::I64(19),
+            Token::SeqEnd,
+            Token::StructEnd,
+            // stats
+            Token::Str("stats"),
+            Token::Struct {
+                name: "StatsConfig",
+                len: 1,
+            },
+            Token::Str("public"),
+            Token::Bool(true),
+            Token::StructEnd,
+            // notifications
+            Token::Str("notifications"),
+            Token::Struct {
+                name: "NotificationsConfig",
+                len: 2,
+            },
+            Token::Str("url"),
+            Token::Some,
+            Token::Str("https://example.com/webhook"),
+            Token::Str("secret"),
+            Token::Some,
+            Token::Str("secret"),
+            Token::StructEnd,
+            // entitlements
+            Token::Str("entitlements"),
+            Token::Struct {
+                name: "Entitlements",
+                len: 2,
+            },
+            Token::Str("tier"),
+            Token::UnitVariant {
+                name: "PremiumTier",
+                variant: "free",
+            },
+            Token::Str("features"),
+            Token::Seq { len: Some(1) },
+            Token::UnitVariant {
+                name: "PremiumFeature",
+                variant: "regex_filter",
+            },
+            Token::SeqEnd,
             Token::StructEnd,
             Token::StructEnd,
         ],
@@ -132,6 +643,18 @@ fn test_guild_bson() {
         id: Id::new(1),
         logs_chan: Some(Id::new(2)),
         lang: "en".to_owned(),
+        logs: LogsConfig {
+            channels: LogChannels {
+                members: Some(Id::new(18)),
+                ..Default::default()
+            },
+            message_delete: true,
+            member_join: true,
+            member_leave: false,
+            member_update: true,
+            security_alerts: true,
+            voice: true,
+        },
         moderation: ModerationConfig {
             roles: vec![Id::new(3), Id::new(4)],
             enforce_reason: true,
@@ -144,6 +667,79 @@ fn test_guild_bson() {
             role: Some(Id::new(7)),
             verified_roles: vec![Id::new(8), Id::new(9)],
             logs: Some(Id::new(10)),
+            questionnaire: Some(QuestionnaireConfig {
+                questions: vec!["Why do you join?".to_owned()],
+                review_channel: Id::new(11),
+                replace_captcha: true,
+            }),
+            strict_until: Some(OffsetDateTime::from_unix_timestamp(1_628_594_197).unwrap()),
+        },
+        antispam: AntiSpamConfig {
+            enabled: true,
+            max_messages: 8,
+            message_interval: 10,
+            max_mentions: 3,
+            max_duplicates: 4,
+            punishment: AntiSpamPunishment::Kick,
+            punishment_duration: 3600,
+            mention_sanction: true,
+            emergency_slowmode: 30,
+            exempt_roles: vec![Id::new(12)],
+            exempt_channels: vec![Id::new(13)],
+            disabled_detectors: vec!["antispam:rate".to_owned()],
+        },
+        invites: InviteFilterConfig {
+            enabled: true,
+            resolve: false,
+            allowed_guilds: vec![Id::new(14)],
+            max_warnings: 2,
+            timeout_duration: 1800,
+        },
+        domains: DomainFilterConfig {
+            enabled: true,
+            block_phishing: false,
+            blocked: vec!["evil.com".to_owned()],
+            allowed: vec!["raidprotect.org".to_owned()],
+        },
+        filter: WordFilterConfig {
+            rules: vec![WordFilterRule {
+                kind: WordFilterRuleKind::Wildcard,
+                pattern: "spam*".to_owned(),
+            }],
+        },
+        attachments: AttachmentPolicyConfig {
+            enabled: true,
+            block_executables: false,
+            max_attachments: 4,
+            image_only_channels: vec![Id::new(15)],
+            exempt_roles: vec![Id::new(16)],
+        },
+        phishing: PhishingConfig {
+            enabled: true,
+            threshold: 55,
+        },
+        quarantine: QuarantineConfig {
+            role: Some(Id::new(17)),
+        },
+        webhooks: WebhookConfig {
+            enabled: true,
+            action: WebhookAction::Disable,
+        },
+        antinuke: AntiNukeConfig {
+            enabled: true,
+            max_actions: 3,
+            interval: 30,
+            trusted_users: vec![Id::new(19)],
+        },
+        stats: StatsConfig { public: true },
+        notifications: NotificationsConfig {
+            url: Some("https://example.com/webhook".to_owned()),
+            secret: Some("secret".to_owned()),
+        },
+        entitlements: Entitlements {
+            tier: PremiumTier::Premium,
+            features: Vec::new(),
+            expires_at: Some(OffsetDateTime::from_unix_timestamp(1_628_594_197).unwrap()),
         },
     };
 
@@ -151,6 +747,17 @@ fn test_guild_bson() {
         "_id": 1_i64,
         "logs_chan": 2_i64,
         "lang": "en".to_owned(),
+        "logs": {
+            "channels": {
+                "members": 18_i64,
+            },
+            "message_delete": true,
+            "member_join": true,
+            "member_leave": false,
+            "member_update": true,
+            "security_alerts": true,
+            "voice": true,
+        },
         "moderation": {
             "roles": [3_i64, 4_i64],
             "enforce_reason": true,
@@ -163,9 +770,126 @@ fn test_guild_bson() {
             "role": 7_i64,
             "verified_roles": [8_i64, 9_i64],
             "logs": 10_i64,
+            "questionnaire": {
+                "questions": ["Why do you join?"],
+                "review_channel": 11_i64,
+                "replace_captcha": true,
+            },
+            "strict_until": bson::DateTime::from_millis(1_628_594_197),
+        },
+        "antispam": {
+            "enabled": true,
+            "max_messages": 8_i32,
+            "message_interval": 10_i32,
+            "max_mentions": 3_i32,
+            "max_duplicates": 4_i32,
+            "punishment": "kick",
+            "punishment_duration": 3600_i64,
+            "mention_sanction": true,
+            "emergency_slowmode": 30_i32,
+            "exempt_roles": [12_i64],
+            "exempt_channels": [13_i64],
+            "disabled_detectors": ["antispam:rate"],
+        },
+        "invites": {
+            "enabled": true,
+            "resolve": false,
+            "allowed_guilds": [14_i64],
+            "max_warnings": 2_i32,
+            "timeout_duration": 1800_i64,
+        },
+        "domains": {
+            "enabled": true,
+            "block_phishing": false,
+            "blocked": ["evil.com"],
+            "allowed": ["raidprotect.org"],
+        },
+        "filter": {
+            "rules": [{ "kind": "wildcard", "pattern": "spam*" }],
+        },
+        "attachments": {
+            "enabled": true,
+            "block_executables": false,
+            "max_attachments": 4_i32,
+            "image_only_channels": [15_i64],
+            "exempt_roles": [16_i64],
+        },
+        "phishing": {
+            "enabled": true,
+            "threshold": 55_i32,
+        },
+        "quarantine": {
+            "role": 17_i64,
+        },
+        "webhooks": {
+            "enabled": true,
+            "action": "disable",
+        },
+        "antinuke": {
+            "enabled": true,
+            "max_actions": 3_i32,
+            "interval": 30_i32,
+            "trusted_users": [19_i64],
+        },
+        "stats": {
+            "public": true,
+        },
+        "notifications": {
+            "url": "https://example.com/webhook",
+            "secret": "secret",
+        },
+        "entitlements": {
+            "tier": "premium",
+            "expires_at": bson::DateTime::from_millis(1_628_594_197),
         },
     };
 
     assert_eq!(bson::to_document(&guild).unwrap(), expected);
     assert_eq!(bson::from_document::<GuildConfig>(expected).unwrap(), guild);
 }
+
+#[test]
+fn test_guild_msgpack() {
+    let mut guild = GuildConfig::new(Id::new(1));
+    guild.captcha.strict_until = Some(OffsetDateTime::from_unix_timestamp(1_628_594_197).unwrap());
+    guild.antispam.exempt_roles = vec![Id::new(2)];
+
+    let serialized = guild.serialize_model().unwrap();
+
+    assert_eq!(GuildConfig::deserialize_model(serialized).unwrap(), guild);
+}
+
+#[test]
+fn test_entitlements() {
+    let mut entitlements = Entitlements::default();
+    assert!(!entitlements.has_feature(PremiumFeature::RegexFilter));
+
+    entitlements.features.push(PremiumFeature::RegexFilter);
+    assert!(entitlements.has_feature(PremiumFeature::RegexFilter));
+
+    let premium = Entitlements {
+        tier: PremiumTier::Premium,
+        ..Default::default()
+    };
+    assert!(premium.has_feature(PremiumFeature::RegexFilter));
+
+    let expired = Entitlements {
+        expires_at: Some(OffsetDateTime::now_utc() - time::Duration::days(1)),
+        ..premium
+    };
+    assert!(!expired.is_active());
+    assert!(!expired.has_feature(PremiumFeature::RegexFilter));
+}
+
+#[test]
+fn test_log_channels() {
+    let mut channels = LogChannels::default();
+    channels.set(LogCategory::Captcha, Some(Id::new(1)));
+
+    assert_eq!(channels.get(LogCategory::Captcha), Some(Id::new(1)));
+    assert_eq!(channels.get(LogCategory::Members), None);
+    assert_eq!(channels.get(LogCategory::Voice), None);
+
+    channels.set(LogCategory::Captcha, None);
+    assert_eq!(channels, LogChannels::default());
+}
@@ -1,6 +1,6 @@
 use mongodb::bson::{self, oid::ObjectId, DateTime};
 use pretty_assertions::assert_eq;
-use raidprotect_model::database::model::{Modlog, ModlogType, ModlogUser};
+use raidprotect_model::database::model::{Modlog, ModlogAutomation, ModlogType, ModlogUser};
 use serde_test::{assert_tokens, Configure, Token};
 use time::OffsetDateTime;
 use twilight_model::{id::Id, util::ImageHash};
@@ -28,6 +28,10 @@ fn test_modlog_full() {
         date: OffsetDateTime::from_unix_timestamp(1_628_594_197_123).unwrap(),
         reason: Some("reason".to_owned()),
         notes: Some("notes".to_owned()),
+        automation: Some(ModlogAutomation {
+            rule: "antispam:mentions".to_owned(),
+            details: Some("10 mentions".to_owned()),
+        }),
     };
 
     assert_tokens(
@@ -35,7 +39,7 @@ fn test_modlog_full() {
         &[
             Token::Struct {
                 name: "Modlog",
-                len: 8,
+                len: 9,
             },
             // id
             Token::Str("_id"),
@@ -118,6 +122,19 @@ fn test_modlog_full() {
             Token::Str("notes"),
             Token::Some,
             Token::String("notes"),
+            // automation
+            Token::Str("automation"),
+            Token::Some,
+            Token::Struct {
+                name: "ModlogAutomation",
+                len: 2,
+            },
+            Token::Str("rule"),
+            Token::String("antispam:mentions"),
+            Token::Str("details"),
+            Token::Some,
+            Token::String("10 mentions"),
+            Token::StructEnd,
             Token::StructEnd,
         ],
     )
@@ -146,6 +163,10 @@ fn test_modlog_bson() {
         date: OffsetDateTime::from_unix_timestamp(1_628_594_197_123).unwrap(),
         reason: Some("reason".to_owned()),
         notes: Some("notes".to_owned()),
+        automation: Some(ModlogAutomation {
+            rule: "antispam:mentions".to_owned(),
+            details: Some("10 mentions".to_owned()),
+        }),
     };
 
     let expected = bson::doc! {
@@ -167,8 +188,51 @@ fn test_modlog_bson() {
         "date": DateTime::from_millis(1_628_594_197_123),
         "reason": "reason",
         "notes": "notes",
+        "automation": {
+            "rule": "antispam:mentions",
+            "details": "10 mentions",
+        },
     };
 
     assert_eq!(bson::to_document(&modlog).unwrap(), expected);
     assert_eq!(bson::from_document::<Modlog>(expected).unwrap(), modlog);
 }
+
+#[test]
+fn test_modlog_csv() {
+    let modlog = Modlog {
+        id: Some(ObjectId::parse_str("62aca55a551e9a0102351bda").unwrap()),
+        kind: ModlogType::Kick,
+        guild_id: Id::new(1),
+        user: ModlogUser {
+            id: Id::new(2),
+            name: "user, name".to_owned(),
+            discriminator: 1234,
+            avatar: None,
+        },
+        moderator: ModlogUser {
+            id: Id::new(3),
+            name: "moderator".to_owned(),
+            discriminator: 4567,
+            avatar: None,
+        },
+        date: OffsetDateTime::from_unix_timestamp(1_628_594_197).unwrap(),
+        reason: Some("said \"hello\"".to_owned()),
+        notes: Some("=HYPERLINK()".to_owned()),
+        automation: Some(ModlogAutomation {
+            rule: "antispam:mentions".to_owned(),
+            details: None,
+        }),
+    };
+
+    assert_eq!(
+        Modlog::CSV_HEADER,
+        "id,date,kind,user_id,user_name,moderator_id,moderator_name,reason,notes,\
+         automation_rule,automation_details"
+    );
+    assert_eq!(
+        modlog.to_csv_record(),
+        "62aca55a551e9a0102351bda,2021-08-10T11:16:37.000000+00:00,kick,2,\"user, name\",3,\
+         moderator,\"said \"\"hello\"\"\",'=HYPERLINK(),antispam:mentions,"
+    );
+}
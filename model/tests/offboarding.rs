@@ -0,0 +1,28 @@
+use mongodb::bson::{self, DateTime};
+use pretty_assertions::assert_eq;
+use raidprotect_model::database::model::GuildOffboarding;
+use time::OffsetDateTime;
+use twilight_model::id::Id;
+
+#[test]
+fn test_offboarding_bson() {
+    let offboarding = GuildOffboarding {
+        guild_id: Id::new(1),
+        removed_at: OffsetDateTime::from_unix_timestamp(1_628_594_197).unwrap(),
+        config_archived_at: Some(OffsetDateTime::from_unix_timestamp(1_631_186_197).unwrap()),
+        modlogs_deleted_at: None,
+    };
+
+    let expected = bson::doc! {
+        "_id": 1_i64,
+        "removed_at": DateTime::from_millis(1_628_594_197),
+        "config_archived_at": DateTime::from_millis(1_631_186_197),
+    };
+
+    assert_eq!(bson::to_document(&offboarding).unwrap(), expected);
+    assert_eq!(
+        bson::from_document::<GuildOffboarding>(expected).unwrap(),
+        offboarding
+    );
+    assert!(!offboarding.is_completed());
+}
@@ -0,0 +1,114 @@
+use pretty_assertions::assert_eq;
+use raidprotect_model::{
+    cache::model::message::MentionOffenses,
+    database::model::{AntiSpamFeedback, AntiSpamPunishment, Modlog, ModlogType, ModlogUser},
+    privacy::{UserData, ANONYMOUS_USER_ID},
+};
+use time::OffsetDateTime;
+use twilight_model::id::{marker::UserMarker, Id};
+
+#[test]
+fn test_anonymous_user() {
+    let user = ModlogUser::anonymous();
+
+    assert_eq!(user.id, ANONYMOUS_USER_ID);
+    assert_eq!(user.avatar, None);
+}
+
+fn user(id: u64) -> ModlogUser {
+    ModlogUser {
+        id: Id::new(id),
+        name: format!("user {id}"),
+        discriminator: 0,
+        avatar: None,
+    }
+}
+
+fn modlog(user: ModlogUser, moderator: ModlogUser) -> Modlog {
+    Modlog {
+        id: None,
+        kind: ModlogType::Ban,
+        guild_id: Id::new(1),
+        user,
+        moderator,
+        date: OffsetDateTime::from_unix_timestamp(1_628_594_197).unwrap(),
+        reason: None,
+        notes: Some("private notes".to_owned()),
+        automation: None,
+    }
+}
+
+fn feedback(user_id: Id<UserMarker>, reported_by: Id<UserMarker>) -> AntiSpamFeedback {
+    AntiSpamFeedback {
+        id: None,
+        guild_id: Id::new(1),
+        user_id,
+        rules: vec!["antispam:rate".to_owned()],
+        punishment: AntiSpamPunishment::Timeout,
+        reported_by,
+        date: OffsetDateTime::from_unix_timestamp(1_628_594_197).unwrap(),
+    }
+}
+
+#[test]
+fn test_user_data_json() {
+    let data = UserData {
+        user_id: Id::new(42),
+        modlogs: vec![modlog(ModlogUser::anonymous(), ModlogUser::anonymous())],
+        antispam_feedback: Vec::new(),
+        api_keys: Vec::new(),
+        trusted_in: vec![Id::new(1)],
+        members: Vec::new(),
+        voice_states: Vec::new(),
+        quarantines: Vec::new(),
+        join_sources: Vec::new(),
+        captchas: Vec::new(),
+        mention_offenses: vec![MentionOffenses {
+            guild_id: Id::new(1),
+            user_id: Id::new(42),
+            count: 2,
+        }],
+        invite_warnings: Vec::new(),
+    };
+
+    let json = data.to_json().unwrap();
+
+    assert!(json.starts_with(r#"{"user_id":42,"#));
+    assert!(json.contains(r#""trusted_in":[1]"#));
+    assert!(json.contains(r#""date":{"$date":"2021-08-10T11:16:37Z"}"#));
+    assert!(json.contains(r#""mention_offenses":[{"guild_id":1,"user_id":42,"count":2}]"#));
+}
+
+#[test]
+fn test_user_data_self_service_json() {
+    let data = UserData {
+        user_id: Id::new(42),
+        modlogs: vec![modlog(user(42), user(7)), modlog(user(8), user(42))],
+        antispam_feedback: vec![
+            feedback(Id::new(42), Id::new(7)),
+            feedback(Id::new(8), Id::new(42)),
+        ],
+        api_keys: Vec::new(),
+        trusted_in: Vec::new(),
+        members: Vec::new(),
+        voice_states: Vec::new(),
+        quarantines: Vec::new(),
+        join_sources: Vec::new(),
+        captchas: Vec::new(),
+        mention_offenses: Vec::new(),
+        invite_warnings: Vec::new(),
+    };
+
+    let full = data.to_json().unwrap();
+    assert!(full.contains(r#""moderator":{"id":7,"name":"user 7""#));
+    assert!(full.contains(r#""reported_by":7"#));
+
+    // Moderators and reporters are hidden from the targeted user, but kept in
+    // the entries where the user is the moderator.
+    let json = data.to_self_service_json().unwrap();
+    assert!(!json.contains("user 7"));
+    assert!(!json.contains(r#""reported_by":7"#));
+    assert!(json.contains(r#""moderator":{"id":42,"name":"user 42""#));
+    assert!(json.contains(r#""reported_by":42"#));
+    assert_eq!(json.matches("private notes").count(), 1);
+}
@@ -0,0 +1,46 @@
+use pretty_assertions::assert_eq;
+use raidprotect_model::cache::model::rpc::{
+    RpcAction, RpcCaller, RpcError, RpcRequest, RpcResponse,
+};
+use twilight_model::id::Id;
+
+#[test]
+fn test_rpc_request_msgpack() {
+    let request = RpcRequest {
+        id: "abc".to_owned(),
+        guild_id: Id::new(1),
+        caller: RpcCaller::ApiKey {
+            name: "integration".to_owned(),
+            created_by: Id::new(2),
+        },
+        action: RpcAction::Ban {
+            user_id: Id::new(3),
+            reason: Some("spam".to_owned()),
+        },
+    };
+
+    let bytes = rmp_serde::to_vec_named(&request).unwrap();
+
+    assert_eq!(
+        rmp_serde::from_slice::<RpcRequest>(&bytes).unwrap(),
+        request
+    );
+    assert_eq!(request.reply_channel(), "rpc:reply:abc");
+}
+
+#[test]
+fn test_rpc_response_msgpack() {
+    for result in [Ok(()), Err(RpcError::InvalidTarget)] {
+        let response = RpcResponse {
+            id: "abc".to_owned(),
+            result,
+        };
+
+        let bytes = rmp_serde::to_vec_named(&response).unwrap();
+
+        assert_eq!(
+            rmp_serde::from_slice::<RpcResponse>(&bytes).unwrap(),
+            response
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use futures_util::StreamExt;
+use pretty_assertions::assert_eq;
+use raidprotect_model::database::{
+    model::{JobAction, Modlog, ModlogAutomation, ModlogType, ModlogUser, ScheduledJob},
+    GuildConfigStore, MemoryStore, ModlogStore, OffboardingStore, ScheduleStore,
+};
+use time::{Duration, OffsetDateTime};
+use twilight_model::id::Id;
+
+fn modlog(user_id: u64, automated: bool) -> Modlog {
+    let user = ModlogUser {
+        id: Id::new(user_id),
+        name: "user".to_owned(),
+        discriminator: 1,
+        avatar: None,
+    };
+
+    Modlog {
+        id: None,
+        kind: ModlogType::Ban,
+        guild_id: Id::new(1),
+        user: user.clone(),
+        moderator: user,
+        date: OffsetDateTime::now_utc(),
+        reason: None,
+        notes: None,
+        automation: automated.then(|| ModlogAutomation {
+            rule: "antispam".to_owned(),
+            details: None,
+        }),
+    }
+}
+
+#[tokio::test]
+async fn test_memory_guilds() {
+    let store = MemoryStore::new();
+
+    assert_eq!(store.get_guild(Id::new(1)).await.unwrap(), None);
+
+    let mut config = store.get_guild_or_create(Id::new(1)).await.unwrap();
+    config.lang = "en".to_owned();
+    store.update_guild(&config).await.unwrap();
+
+    assert_eq!(store.get_guild(Id::new(1)).await.unwrap(), Some(config));
+}
+
+#[tokio::test]
+async fn test_memory_modlogs() {
+    let store = MemoryStore::new();
+
+    let id = store.create_modlog(&modlog(2, false)).await.unwrap();
+    store.create_modlog(&modlog(3, true)).await.unwrap();
+
+    let stored = store.get_modlog(id).await.unwrap().unwrap();
+    assert_eq!(stored.id, Some(id));
+
+    let find = |user_id, automated| store.find_modlogs(Id::new(1), user_id, automated);
+    assert_eq!(find(None, None).await.unwrap().len(), 2);
+    assert_eq!(find(Some(Id::new(2)), None).await.unwrap(), vec![stored]);
+    assert_eq!(find(None, Some(true)).await.unwrap().len(), 1);
+    assert_eq!(
+        store.find_modlogs(Id::new(2), None, None).await.unwrap(),
+        vec![]
+    );
+}
+
+#[tokio::test]
+async fn test_memory_modlogs_archive() {
+    let store = MemoryStore::new();
+
+    let mut old = modlog(2, false);
+    old.date -= Duration::days(10);
+    store.create_modlog(&old).await.unwrap();
+    store.create_modlog(&modlog(3, false)).await.unwrap();
+
+    let before = OffsetDateTime::now_utc() - Duration::days(1);
+    assert_eq!(store.archive_modlogs(before, 10).await.unwrap(), 1);
+    assert_eq!(store.archive_modlogs(before, 10).await.unwrap(), 0);
+
+    let archived = store.find_guild_modlogs(Id::new(1), true).await.unwrap();
+    assert_eq!(archived.count().await, 1);
+    assert_eq!(store.find_user_modlogs(Id::new(2)).await.unwrap().len(), 1);
+
+    assert_eq!(store.delete_guild_modlogs(Id::new(1)).await.unwrap(), 2);
+    assert_eq!(store.find_user_modlogs(Id::new(2)).await.unwrap(), vec![]);
+}
+
+#[tokio::test]
+async fn test_memory_offboarding() {
+    let store = MemoryStore::new();
+    let config = store.get_guild_or_create(Id::new(1)).await.unwrap();
+
+    let offboarding = store.create_offboarding(Id::new(1)).await.unwrap();
+    assert_eq!(
+        store.pending_offboardings().await.unwrap(),
+        vec![offboarding.clone()]
+    );
+
+    assert_eq!(
+        store.archive_guild(Id::new(1)).await.unwrap(),
+        Some(config.clone())
+    );
+    assert_eq!(store.get_guild(Id::new(1)).await.unwrap(), None);
+
+    assert_eq!(
+        store.delete_offboarding(Id::new(1)).await.unwrap(),
+        Some(offboarding.clone())
+    );
+    assert!(!store.update_offboarding(&offboarding).await.unwrap());
+
+    assert!(store.restore_guild(Id::new(1)).await.unwrap());
+    assert_eq!(store.get_guild(Id::new(1)).await.unwrap(), Some(config));
+}
+
+#[tokio::test]
+async fn test_memory_jobs() {
+    let store = MemoryStore::new();
+    let action = JobAction::Unban {
+        user_id: Id::new(2),
+    };
+    let run_at = OffsetDateTime::now_utc() - Duration::seconds(1);
+
+    store
+        .schedule_job(&ScheduledJob::new(Id::new(1), action, run_at))
+        .await
+        .unwrap();
+
+    let job = store.claim_job().await.unwrap().unwrap();
+    assert_eq!(job.attempts, 1);
+    assert!(store.claim_job().await.unwrap().is_none());
+
+    // Failed jobs are run again after a delay.
+    store.retry_job(&job, "error".to_owned()).await.unwrap();
+    assert!(store.claim_job().await.unwrap().is_none());
+
+    store.complete_job(&job).await.unwrap();
+    assert!(!store.cancel_job(&job.key).await.unwrap());
+}
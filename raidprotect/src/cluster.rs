@@ -1,16 +1,88 @@
 //! Shards cluster implementation.
+//!
+//! ## Sharding
+//! The number of shards recommended by Discord is fetched at startup. Shards
+//! can be split between multiple processes by configuring the range of shards
+//! started by each process (see [`BotConfig::shards`]).
+//!
+//! When shutting down, the gateway sessions of the shards are saved in the
+//! cache (see [`ShardSession`]) and resumed by the next process starting these
+//! shards. This allows restarting the processes one after the other without
+//! dropping the events of the whole cluster. The recommended number of shards
+//! is periodically checked, and a warning is logged when the running cluster
+//! should be restarted with a different number of shards.
+//!
+//! ## Gateway proxy
+//! The shards can connect to the gateway through a proxy (see
+//! [`BotConfig::gateway_url`]), which keeps the sessions with Discord open
+//! when the bot restarts. If the proxy handles the identify rate limit, the
+//! queue of the cluster can be disabled with
+//! [`BotConfig::gateway_proxy_identify`] so that restarts are not delayed.
+//!
+//! ## Events
+//! The gateway intents can be configured (see [`BotConfig::intents`]). Only
+//! the event types processed by the bot are requested to the gateway (see
+//! [`processed_events`]), other events are dropped before being deserialized.
+//!
+//! Events are processed by a pool of workers, see [`EventQueue`].
+//!
+//! ## Status
+//! The connection stage, heartbeat latency and event throughput of each shard
+//! are periodically published in the cache (see [`ShardStatus`]). They are
+//! shown by the `/debug shards` command and the health endpoint of the web
+//! service.
+//!
+//! ## Metrics
+//! The number of received events of each type is recorded in the metrics
+//! registry, and a snapshot of the registry is periodically published in the
+//! cache (see [`ProcessMetrics`]) to be exposed by the web service.
+//!
+//! ## Compression
+//! Gateway payloads are compressed with `zlib-stream` if the
+//! `gateway-compression` feature is enabled (the default). twilight-gateway
+//! only allows to select the compression at build time, so it cannot be
+//! disabled from the [`BotConfig`]. The size of the payloads before and after
+//! decompression is recorded in the metrics registry (see
+//! [`gateway_metrics`](crate::util::gateway_metrics)). Without compression,
+//! the size of the payloads can be measured with
+//! [`BotConfig::gateway_payload_metrics`].
+//!
+//! ## Event tap
+//! Raw payloads can also be recorded with the `/debug tap` command if
+//! [`BotConfig::event_tap`] is enabled, see [`EventTapRecorder`].
 
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use anyhow::Context;
 use futures_util::StreamExt;
+use raidprotect_anti_spam::engine::AntiSpam;
 use raidprotect_model::{
-    cache::{discord::http::CacheHttp, CacheClient},
-    config::BotConfig,
-    database::DbClient,
+    cache::{
+        discord::http::CacheHttp,
+        model::{
+            gateway::{ShardSession, ShardStatus},
+            metrics::ProcessMetrics,
+        },
+        stats::CacheReport,
+        CacheClient,
+    },
+    config::{BotConfig, ShardRange},
+    database::{model::GuildConfig, DbClient, Store},
+    metrics,
+};
+use time::OffsetDateTime;
+use tokio::task::JoinHandle;
+use tracing::{error, info, instrument, warn};
+use twilight_gateway::{
+    cluster::{Events, ShardScheme},
+    queue::{LargeBotQueue, NoOpQueue},
+    shard::{ResumeSession, Stage},
+    Cluster, Event, EventTypeFlags, Intents,
 };
-use tracing::{info, info_span, instrument, trace};
-use twilight_gateway::{cluster::Events, Cluster, Intents};
 use twilight_http::Client as HttpClient;
 use twilight_model::{
     gateway::{
@@ -18,15 +90,57 @@ use twilight_model::{
         presence::{ActivityType, MinimalActivity, Status},
     },
     id::{
-        marker::{ApplicationMarker, GuildMarker},
+        marker::{ApplicationMarker, ChannelMarker, GuildMarker, UserMarker},
         Id,
     },
 };
 
 use crate::{
-    event::ProcessEvent, interaction::register_commands, util::shutdown::ShutdownSubscriber,
+    coordinator::{self, ShardLease},
+    event::{processed_events, EventQueue, EventTapRecorder},
+    feature::{
+        captcha::CaptchaRenderer,
+        config_sync::config_sync_listener,
+        domains::{phishing_task, PhishingDomains},
+        filter::FilterCache,
+        join_source::invites_task,
+        modlogs::modlog_archive_task,
+        offboarding::offboarding_task,
+        rpc::rpc_listener,
+        scheduler::scheduler_task,
+        slowmode::slowmode_task,
+        webhooks::{webhooks_task, WebhookDeliveries, WebhookQueue},
+    },
+    interaction::register_commands,
+    util::{
+        ratelimit::RateLimiter,
+        shutdown::{Shutdown, ShutdownStage, ShutdownSubscriber},
+        MemberRequests, RequestedMembers,
+    },
 };
 
+/// Gateway intents required by all the features.
+const DEFAULT_INTENTS: Intents = Intents::GUILDS
+    .union(Intents::GUILD_BANS)
+    .union(Intents::GUILD_INVITES)
+    .union(Intents::GUILD_MEMBERS)
+    .union(Intents::GUILD_MESSAGES)
+    .union(Intents::GUILD_VOICE_STATES)
+    .union(Intents::GUILD_WEBHOOKS)
+    .union(Intents::MESSAGE_CONTENT);
+
+/// Interval between checks of the recommended number of shards.
+const RESHARD_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Interval between updates of the shards status.
+const STATUS_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Interval between publications of the metrics snapshot.
+const METRICS_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Name of the received events metric.
+const GATEWAY_EVENTS: &str = "raidprotect_gateway_events_total";
+
 /// Discord shards cluster.
 ///
 /// This type is a wrapper around twilight [`Cluster`] and manages incoming
@@ -39,6 +153,24 @@ pub struct ShardCluster {
     events: Events,
     /// Shared cluster state
     state: ClusterState,
+    /// MongoDB client, used for the change streams of the database
+    database: DbClient,
+    /// Queue of events waiting to be processed
+    queue: Arc<EventQueue>,
+    /// Number of events received by each shard
+    event_counters: Arc<EventCounters>,
+    /// Whether the size of the gateway payloads is measured
+    payload_metrics: bool,
+    /// Recorder of the raw gateway payloads, if enabled
+    event_tap: Option<EventTapRecorder>,
+    /// Moderation logs retention period after the bot is removed from a guild
+    modlog_retention: time::Duration,
+    /// Age of the moderation logs moved to the archive collection
+    modlog_archive: time::Duration,
+    /// Deliveries of the outgoing webhooks, taken when the cluster starts
+    webhook_deliveries: Option<WebhookDeliveries>,
+    /// Range of shards claimed in coordinator mode
+    lease: Option<ShardLease>,
 }
 
 impl ShardCluster {
@@ -59,12 +191,16 @@ impl ShardCluster {
 
         info!("logged as {} with ID {}", application.name, current_user);
 
-        let redis = CacheClient::connect(&config.database.redis_uri).await?;
+        let redis = CacheClient::connect(
+            &config.database.redis_uri,
+            config.database.cache_ttl.clone(),
+        )
+        .await?;
         redis.ping().await.context("failed to connect to redis")?;
 
         let mongodb = DbClient::connect(
             &config.database.mongodb_uri,
-            config.database.mongodb_database,
+            config.database.mongodb_database.clone(),
         )
         .await?;
         mongodb
@@ -72,61 +208,503 @@ impl ShardCluster {
             .await
             .context("failed to connect to mongodb")?;
 
-        let intents = Intents::GUILDS
-            | Intents::GUILD_MEMBERS
-            | Intents::GUILD_MESSAGES
-            | Intents::MESSAGE_CONTENT;
+        let intents = config.intents.unwrap_or(DEFAULT_INTENTS);
+        if intents != DEFAULT_INTENTS {
+            warn!(
+                missing = ?DEFAULT_INTENTS - intents,
+                "using custom gateway intents {:?}", intents
+            );
+        }
+
+        // Get the recommended number of shards and the maximum number of shards
+        // allowed to identify concurrently.
+        let gateway = http.gateway().authed().exec().await?.model().await?;
+        let max_concurrency = gateway.session_start_limit.max_concurrency;
+        let lease = match config.shard_groups {
+            Some(groups) => Some(coordinator::claim(&redis, groups, gateway.shards.max(1)).await?),
+            None => None,
+        };
+        let scheme = match &lease {
+            Some(lease) => ShardScheme::Range {
+                from: lease.from(),
+                to: lease.to(),
+                total: gateway.shards.max(1),
+            },
+            None => shard_scheme(config.shards, gateway.shards),
+        };
+
+        info!(
+            recommended = gateway.shards,
+            max_concurrency, "using shard scheme {:?}", scheme
+        );
 
-        let (cluster, events) = Cluster::builder(config.token, intents)
+        let resume_sessions = resume_sessions(&redis, &scheme).await?;
+        let mut builder = Cluster::builder(config.token.clone(), intents)
             .http_client(http.clone())
             .presence(presence())
-            .build()
-            .await?;
+            .shard_scheme(scheme)
+            .resume_sessions(resume_sessions);
+
+        let mut event_types = processed_events();
+        if config.gateway_payload_metrics || config.event_tap {
+            event_types |= EventTypeFlags::SHARD_PAYLOAD;
+        }
+        builder = builder.event_types(event_types);
+
+        if let Some(gateway_url) = &config.gateway_url {
+            info!("connecting to the gateway through {}", gateway_url);
+            builder = builder.gateway_url(gateway_url.clone());
+        }
+
+        if config.gateway_proxy_identify {
+            if config.gateway_url.is_none() {
+                warn!("identify rate limit disabled without gateway proxy");
+            }
+
+            builder = builder.queue(Arc::new(NoOpQueue));
+        } else if max_concurrency > 1 {
+            let queue = LargeBotQueue::new(max_concurrency as usize, http.clone()).await;
+            builder = builder.queue(Arc::new(queue));
+        }
+
+        #[cfg(feature = "gateway-compression")]
+        crate::util::gateway_metrics::install();
+
+        let (cluster, events) = builder.build().await?;
 
         info!("started cluster with {} shards", cluster.shards().len());
 
-        let state = ClusterState::new(redis, mongodb, http, current_user);
+        let cluster = Arc::new(cluster);
+        let (webhooks, webhook_deliveries) = WebhookQueue::new();
+        let state = ClusterState::new(
+            redis,
+            Arc::new(mongodb.clone()),
+            http,
+            cluster.clone(),
+            current_user,
+            webhooks,
+            &config,
+        );
 
-        register_commands(&state, application.id).await;
+        register_commands(&state, application.id, &config).await;
+        let queue = EventQueue::new(config.event_workers, state.clone());
+        let event_tap = config
+            .event_tap
+            .then(|| EventTapRecorder::new(state.cache.clone()));
 
         Ok(Self {
-            cluster: Arc::new(cluster),
+            cluster,
             events,
             state,
+            database: mongodb,
+            queue: Arc::new(queue),
+            event_counters: Arc::new(EventCounters::default()),
+            payload_metrics: config.gateway_payload_metrics,
+            event_tap,
+            modlog_retention: time::Duration::days(config.modlog_retention_days.into()),
+            modlog_archive: time::Duration::days(365 * i64::from(config.modlog_archive_years)),
+            webhook_deliveries: Some(webhook_deliveries),
+            lease,
         })
     }
 
     /// Start the cluster and handle incoming events.
     ///
-    /// A [`ShutdownSubscriber`] must be provided to gracefully stop the cluster.
-    #[instrument(name = "start_cluster", skip_all)]
-    pub async fn start(mut self, mut shutdown: ShutdownSubscriber) {
+    /// The event loop and the background tasks are stopped in the stages of
+    /// the provided [`Shutdown`].
+    pub fn start(mut self, shutdown: &Shutdown) -> JoinHandle<()> {
         // Start the cluster
         let cluster = self.cluster.clone();
         tokio::spawn(async move {
             cluster.up().await;
         });
 
-        // Handle incoming events
+        // Handle actions requested by other services
+        tokio::spawn(rpc_listener(self.state.clone()));
+
+        // Apply the guild configs edited by other services
+        tokio::spawn(config_sync_listener(
+            self.state.clone(),
+            self.database.clone(),
+        ));
+
+        // Run the offboarding of guilds that removed the bot
+        tokio::spawn(offboarding_task(
+            self.state.clone(),
+            self.modlog_retention,
+            shutdown.subscriber(ShutdownStage::Database),
+        ));
+
+        // Archive the old moderation logs
+        tokio::spawn(modlog_archive_task(
+            self.state.clone(),
+            self.modlog_archive,
+            shutdown.subscriber(ShutdownStage::Database),
+        ));
+
+        // Run the scheduled jobs
+        tokio::spawn(scheduler_task(
+            self.state.clone(),
+            shutdown.subscriber(ShutdownStage::Database),
+        ));
+
+        // Deliver the outgoing webhook notifications
+        if let Some(deliveries) = self.webhook_deliveries.take() {
+            tokio::spawn(webhooks_task(
+                deliveries,
+                shutdown.subscriber(ShutdownStage::Database),
+            ));
+        }
+
+        // Restore the channels slowmode after spam waves
+        tokio::spawn(slowmode_task(
+            self.state.clone(),
+            shutdown.subscriber(ShutdownStage::AntiSpam),
+        ));
+
+        // Reload the phishing domains file
+        tokio::spawn(phishing_task(self.state.clone()));
+
+        // Refresh the cached guild invites
+        tokio::spawn(invites_task(
+            self.state.clone(),
+            shutdown.subscriber(ShutdownStage::Cache),
+        ));
+
+        // Check the recommended number of shards
+        tokio::spawn(reshard_task(self.state.clone()));
+
+        // Publish the status of the shards
+        tokio::spawn(status_task(
+            self.state.clone(),
+            self.queue.clone(),
+            self.event_counters.clone(),
+            self.payload_metrics,
+        ));
+
+        // Publish the metrics snapshot
+        tokio::spawn(metrics_task(self.state.clone()));
+
+        tokio::spawn(self.run(shutdown.subscriber(ShutdownStage::Events)))
+    }
+
+    /// Handle incoming events until a shutdown signal is received.
+    #[instrument(name = "run_cluster", skip_all)]
+    async fn run(mut self, mut shutdown: ShutdownSubscriber) {
+        let mut lease = self.lease.take();
+        let cache = self.state.cache.clone();
+
         tokio::select! {
             _ = self.handle_events() => {},
             _ = shutdown.wait_shutdown() => {},
+            _ = coordinator::heartbeat(lease.as_mut(), &cache) => {},
         };
 
-        self.cluster.down();
+        // Save the gateway sessions to resume them after a restart
+        let total_shards = self.cluster.config().shard_scheme().total();
+        let sessions = self.cluster.down_resumable();
+
+        if let Err(error) = save_sessions(&self.state.cache, total_shards, sessions).await {
+            error!(error = ?error, "failed to save gateway sessions");
+        }
+
+        // Release the range once the shards are stopped
+        if let Some(lease) = lease {
+            lease.release(&cache).await;
+        }
+
+        // Process the events received before the shutdown
+        self.queue.close().await;
     }
 
     /// Handle incoming events
     async fn handle_events(&mut self) {
-        while let Some((_shard_id, event)) = self.events.next().await {
-            let span = info_span!("handle_event");
+        while let Some((shard_id, event)) = self.events.next().await {
+            // Raw payloads are only received to measure their size and for
+            // the event tap.
+            if let Event::ShardPayload(payload) = event {
+                if self.payload_metrics {
+                    self.event_counters
+                        .record_payload(shard_id, payload.bytes.len());
+                }
+
+                if let Some(event_tap) = &self.event_tap {
+                    event_tap.record(shard_id, payload.bytes);
+                }
+
+                continue;
+            }
+
+            self.event_counters.record_event(shard_id);
+            metrics::registry().inc_counter(
+                GATEWAY_EVENTS,
+                "Number of gateway events received",
+                &[("event", event.kind().name().unwrap_or("UNKNOWN"))],
+            );
+            self.queue.push(shard_id, event).await;
+        }
+    }
+}
+
+/// Get the shard scheme of the cluster.
+///
+/// If no range is configured, all the shards recommended by Discord are
+/// started.
+fn shard_scheme(range: Option<ShardRange>, recommended: u64) -> ShardScheme {
+    match range {
+        Some(range) => {
+            if range.total != recommended {
+                warn!(
+                    configured = range.total,
+                    recommended, "configured number of shards differs from recommended"
+                );
+            }
+
+            ShardScheme::Range {
+                from: range.from,
+                to: range.to,
+                total: range.total,
+            }
+        }
+        None => ShardScheme::Range {
+            from: 0,
+            to: recommended.saturating_sub(1),
+            total: recommended.max(1),
+        },
+    }
+}
+
+/// Load the saved gateway sessions of the shards of a scheme.
+///
+/// Sessions saved with a different number of shards are ignored. Loaded
+/// sessions are removed from the cache since they can only be resumed once.
+async fn resume_sessions(
+    cache: &CacheClient,
+    scheme: &ShardScheme,
+) -> Result<HashMap<u64, ResumeSession>, anyhow::Error> {
+    let mut sessions = HashMap::new();
+
+    for shard_id in scheme.iter() {
+        let session = match cache.get::<ShardSession>(&shard_id).await? {
+            Some(session) => session,
+            None => continue,
+        };
+        cache.delete(&session).await?;
+
+        if session.total_shards == scheme.total() {
+            sessions.insert(
+                shard_id,
+                ResumeSession {
+                    resume_url: session.resume_url,
+                    session_id: session.session_id,
+                    sequence: session.sequence,
+                },
+            );
+        }
+    }
+
+    info!("resuming {} gateway sessions", sessions.len());
+
+    Ok(sessions)
+}
+
+/// Save the gateway sessions of the shards.
+async fn save_sessions(
+    cache: &CacheClient,
+    total_shards: u64,
+    sessions: HashMap<u64, ResumeSession>,
+) -> Result<(), anyhow::Error> {
+    for (shard_id, session) in sessions {
+        let session = ShardSession {
+            shard_id,
+            total_shards,
+            session_id: session.session_id,
+            sequence: session.sequence,
+            resume_url: session.resume_url,
+        };
+
+        cache.set(&session).await?;
+    }
+
+    Ok(())
+}
+
+/// Periodically check the number of shards recommended by Discord.
+///
+/// The cluster cannot be resharded while running, so a warning is logged when
+/// the recommended number of shards changed. The processes must then be
+/// restarted with an updated shard range.
+async fn reshard_task(state: ClusterState) {
+    let mut interval = tokio::time::interval(RESHARD_CHECK_INTERVAL);
+    interval.tick().await; // The first tick completes immediately.
+
+    loop {
+        interval.tick().await;
+
+        let running = state.cluster.config().shard_scheme().total();
+        let recommended = match state.http.gateway().authed().exec().await {
+            Ok(response) => match response.model().await {
+                Ok(gateway) => gateway.shards,
+                Err(error) => {
+                    error!(error = ?error, "failed to deserialize gateway information");
+                    continue;
+                }
+            },
+            Err(error) => {
+                error!(error = ?error, "failed to fetch gateway information");
+                continue;
+            }
+        };
+
+        if recommended != running {
+            warn!(
+                running,
+                recommended, "recommended number of shards changed, a restart is required"
+            );
+        }
+    }
+}
 
-            span.in_scope(|| {
-                trace!(event = ?event, "received event");
+/// Number of events received by each shard.
+#[derive(Debug, Default)]
+struct EventCounters(Mutex<HashMap<u64, ShardCounters>>);
 
-                let state = self.state.clone();
-                tokio::spawn(event.process(state));
-            });
+/// Counters of a shard.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct ShardCounters {
+    /// Number of received events.
+    events: u64,
+    /// Size of the received payloads after decompression (in bytes).
+    payload_bytes: u64,
+}
+
+impl EventCounters {
+    /// Record an event received by a shard.
+    fn record_event(&self, shard_id: u64) {
+        let mut counters = self.0.lock().expect("event counters poisoned");
+
+        counters.entry(shard_id).or_default().events += 1;
+    }
+
+    /// Record the size of a payload received by a shard.
+    fn record_payload(&self, shard_id: u64, size: usize) {
+        let mut counters = self.0.lock().expect("event counters poisoned");
+
+        counters.entry(shard_id).or_default().payload_bytes += size as u64;
+    }
+
+    /// Get the counters of each shard and reset them.
+    fn take(&self) -> HashMap<u64, ShardCounters> {
+        std::mem::take(&mut *self.0.lock().expect("event counters poisoned"))
+    }
+}
+
+/// Get the status of the shards of the cluster.
+///
+/// The events throughput is computed from the counters since `elapsed`. The
+/// payloads throughput is only included if `payload_metrics` is enabled.
+/// `queue_depth` is the number of events waiting in the [`EventQueue`].
+fn shard_status(
+    cluster: &Cluster,
+    counters: &HashMap<u64, ShardCounters>,
+    elapsed: Duration,
+    queue_depth: usize,
+    payload_metrics: bool,
+) -> Vec<ShardStatus> {
+    let updated_at = OffsetDateTime::now_utc();
+
+    cluster
+        .shards()
+        .map(|shard| {
+            let [shard_id, total_shards] = shard.config().shard();
+            let (stage, latency) = match shard.info() {
+                Ok(info) => (
+                    info.stage(),
+                    info.latency()
+                        .recent()
+                        .back()
+                        .map(|latency| latency.as_millis() as u64),
+                ),
+                Err(_) => (Stage::Disconnected, None),
+            };
+
+            let counters = counters.get(&shard_id).copied().unwrap_or_default();
+
+            ShardStatus {
+                shard_id,
+                total_shards,
+                stage: stage.to_string(),
+                connected: stage == Stage::Connected,
+                latency,
+                events_per_minute: per_minute(counters.events, elapsed),
+                queue_depth: queue_depth as u64,
+                payload_bytes_per_minute: payload_metrics
+                    .then(|| per_minute(counters.payload_bytes, elapsed)),
+                updated_at,
+            }
+        })
+        .collect()
+}
+
+/// Compute a rate per minute from a value counted during `elapsed`.
+fn per_minute(value: u64, elapsed: Duration) -> u64 {
+    let elapsed = elapsed.as_millis().max(1);
+
+    (u128::from(value) * 60_000 / elapsed) as u64
+}
+
+/// Periodically publish the status of the shards in the cache.
+async fn status_task(
+    state: ClusterState,
+    queue: Arc<EventQueue>,
+    event_counters: Arc<EventCounters>,
+    payload_metrics: bool,
+) {
+    let mut interval = tokio::time::interval(STATUS_INTERVAL);
+    let mut last_update = Instant::now();
+
+    loop {
+        interval.tick().await;
+
+        let counters = event_counters.take();
+        let status = shard_status(
+            &state.cluster,
+            &counters,
+            last_update.elapsed(),
+            queue.depth(),
+            payload_metrics,
+        );
+        last_update = Instant::now();
+
+        for status in status {
+            if let Err(error) = state.cache.set(&status).await {
+                error!(error = ?error, shard = status.shard_id, "failed to publish shard status");
+            }
+        }
+    }
+}
+
+/// Periodically publish a snapshot of the metrics registry in the cache.
+///
+/// The snapshot is named after the range of shards run by the process.
+async fn metrics_task(state: ClusterState) {
+    let mut interval = tokio::time::interval(METRICS_INTERVAL);
+    let process = match state.cluster.config().shard_scheme() {
+        ShardScheme::Range { from, to, .. } => format!("shards-{from}-{to}"),
+        _ => "bot".to_owned(),
+    };
+
+    loop {
+        interval.tick().await;
+
+        let snapshot = ProcessMetrics {
+            process: process.clone(),
+            metrics: metrics::registry().snapshot(),
+            updated_at: OffsetDateTime::now_utc(),
+        };
+
+        if let Err(error) = state.cache.set(&snapshot).await {
+            error!(error = ?error, "failed to publish metrics");
         }
     }
 }
@@ -154,29 +732,242 @@ fn presence() -> UpdatePresencePayload {
 #[derive(Debug, Clone)]
 pub struct ClusterState {
     pub cache: CacheClient,
-    pub database: DbClient,
+    pub store: Arc<dyn Store>,
     pub http: Arc<HttpClient>,
+    pub cluster: Arc<Cluster>,
     pub current_user: Id<ApplicationMarker>,
+    pub web_url: Option<Arc<str>>,
+    pub support_channel: Option<Id<ChannelMarker>>,
+    pub owners: Arc<[Id<UserMarker>]>,
+    pub captcha_renderer: CaptchaRenderer,
+    pub antispam: Arc<AntiSpam>,
+    pub phishing_domains: Arc<PhishingDomains>,
+    pub filters: Arc<FilterCache>,
+    pub member_requests: MemberRequests,
+    pub ratelimit: Arc<RateLimiter>,
+    pub webhooks: WebhookQueue,
+    pub command_usage_stats: bool,
 }
 
 impl ClusterState {
     /// Initialize a new [`ClusterState`].
+    ///
+    /// Optional settings such as the web URL are read from the [`BotConfig`].
     pub fn new(
         cache: CacheClient,
-        mongodb: DbClient,
+        store: Arc<dyn Store>,
         http: Arc<HttpClient>,
+        cluster: Arc<Cluster>,
         current_user: Id<ApplicationMarker>,
+        webhooks: WebhookQueue,
+        config: &BotConfig,
     ) -> Self {
         Self {
             cache,
-            database: mongodb,
+            store,
             http,
+            cluster,
             current_user,
+            web_url: config.web_url.as_deref().map(Arc::from),
+            support_channel: config.support_channel,
+            owners: Arc::from(config.owners.as_slice()),
+            captcha_renderer: CaptchaRenderer::new(),
+            antispam: Arc::new(AntiSpam::new()),
+            phishing_domains: Arc::new(PhishingDomains::new(config.phishing_domains_path.clone())),
+            filters: Arc::new(FilterCache::new()),
+            member_requests: MemberRequests::default(),
+            ratelimit: Arc::new(RateLimiter::new()),
+            webhooks,
+            command_usage_stats: config.command_usage_stats,
+        }
+    }
+
+    /// Initialize a [`ClusterState`] for tests.
+    ///
+    /// The state uses the in-memory cache and storage, so interaction handlers
+    /// can be tested without external services. The http client and the
+    /// cluster are not connected to Discord.
+    #[cfg(any(test, feature = "test-util"))]
+    #[cfg_attr(not(test), allow(unused))]
+    pub async fn for_tests() -> Self {
+        use raidprotect_model::{
+            cache::{memory::MemoryCache, CacheTtl},
+            database::MemoryStore,
+        };
+
+        let http = Arc::new(HttpClient::new(String::new()));
+        let scheme = ShardScheme::Range {
+            from: 0,
+            to: 0,
+            total: 1,
+        };
+        let (cluster, _) = Cluster::builder(String::new(), Intents::empty())
+            .http_client(http.clone())
+            .shard_scheme(scheme)
+            .build()
+            .await
+            .expect("failed to build cluster");
+        let (webhooks, _) = WebhookQueue::new();
+
+        Self {
+            cache: CacheClient::with_backend(MemoryCache::default(), CacheTtl::default()),
+            store: Arc::new(MemoryStore::new()),
+            http,
+            cluster: Arc::new(cluster),
+            current_user: Id::new(1),
+            web_url: None,
+            support_channel: None,
+            owners: Arc::from([]),
+            captcha_renderer: CaptchaRenderer::new(),
+            antispam: Arc::new(AntiSpam::new()),
+            phishing_domains: Arc::new(PhishingDomains::new(None)),
+            filters: Arc::new(FilterCache::new()),
+            member_requests: MemberRequests::default(),
+            ratelimit: Arc::new(RateLimiter::new()),
+            webhooks,
+            command_usage_stats: false,
         }
     }
 
+    /// Generate a report of the cache size and usage.
+    ///
+    /// See [`CacheClient::report`] for more information.
+    pub async fn cache_report(&self) -> Result<CacheReport, anyhow::Error> {
+        self.cache.report().await
+    }
+
+    /// Get the status of the shards, sorted by shard id.
+    ///
+    /// The statuses are read from the cache, so the shards of all the bot
+    /// processes are returned.
+    pub async fn shard_status(&self) -> Result<Vec<ShardStatus>, anyhow::Error> {
+        let mut status = self
+            .cache
+            .scan::<ShardStatus>(ShardStatus::KEY_PATTERN)
+            .await?;
+        status.sort_by_key(|status| status.shard_id);
+
+        Ok(status)
+    }
+
+    /// Request all the members of a guild from the gateway.
+    ///
+    /// The members are inserted in the cache before the returned future
+    /// resolves. See [`MemberRequests`] for more information.
+    pub async fn request_members(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<RequestedMembers, anyhow::Error> {
+        self.member_requests.all(&self.cluster, guild_id).await
+    }
+
+    /// Request members of a guild by id from the gateway.
+    ///
+    /// Up to 100 users can be requested at once. Users that are not members of
+    /// the guild are returned in [`RequestedMembers::not_found`].
+    pub async fn request_members_by_id(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_ids: Vec<Id<UserMarker>>,
+    ) -> Result<RequestedMembers, anyhow::Error> {
+        self.member_requests
+            .by_id(&self.cluster, guild_id, user_ids)
+            .await
+    }
+
+    /// Get the [`GuildConfig`] of a guild, or create it with the default
+    /// configuration.
+    ///
+    /// The configuration is read from the cache if available, and cached
+    /// after being read from the database.
+    pub async fn guild_config(
+        &self,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<GuildConfig, anyhow::Error> {
+        if let Some(config) = self.cache.get::<GuildConfig>(&guild_id).await? {
+            return Ok(config);
+        }
+
+        let config = self.store.get_guild_or_create(guild_id).await?;
+        self.cache.set(&config).await?;
+
+        Ok(config)
+    }
+
+    /// Update the [`GuildConfig`] of a guild in the database and the cache.
+    pub async fn update_guild_config(&self, config: &GuildConfig) -> Result<(), anyhow::Error> {
+        self.store.update_guild(config).await?;
+        self.cache.set(config).await?;
+
+        Ok(())
+    }
+
+    /// Check whether the shard of a guild runs in this process.
+    pub fn runs_guild(&self, guild_id: Id<GuildMarker>) -> bool {
+        let scheme = self.cluster.config().shard_scheme();
+        let shard = (guild_id.get() >> 22) % scheme.total();
+
+        (scheme.from()..=scheme.to()).contains(&shard)
+    }
+
     /// Get the [`CacheHttp`] client associated with the cache client.
     pub fn cache_http(&self, guild_id: Id<GuildMarker>) -> CacheHttp {
         self.cache.http(&self.http, guild_id)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_guild_config() {
+        let state = ClusterState::for_tests().await;
+        let guild_id = Id::new(1);
+
+        let mut config = state.guild_config(guild_id).await.unwrap();
+        config.lang = "en".to_owned();
+        state.update_guild_config(&config).await.unwrap();
+
+        let cached = state.cache.get::<GuildConfig>(&guild_id).await.unwrap();
+        assert_eq!(cached, Some(config.clone()));
+        assert_eq!(state.store.get_guild(guild_id).await.unwrap(), Some(config));
+        assert!(state.runs_guild(guild_id));
+    }
+
+    #[test]
+    fn test_per_minute() {
+        assert_eq!(per_minute(50, Duration::from_secs(30)), 100);
+        assert_eq!(per_minute(0, Duration::from_secs(30)), 0);
+        assert_eq!(per_minute(3, Duration::ZERO), 180_000);
+    }
+
+    #[test]
+    fn test_event_counters() {
+        let counters = EventCounters::default();
+        counters.record_event(0);
+        counters.record_event(0);
+        counters.record_payload(0, 512);
+        counters.record_event(1);
+
+        let expected = HashMap::from([
+            (
+                0,
+                ShardCounters {
+                    events: 2,
+                    payload_bytes: 512,
+                },
+            ),
+            (
+                1,
+                ShardCounters {
+                    events: 1,
+                    payload_bytes: 0,
+                },
+            ),
+        ]);
+
+        assert_eq!(counters.take(), expected);
+        assert!(counters.take().is_empty());
+    }
+}
@@ -0,0 +1,192 @@
+//! Shard coordination between bot processes.
+//!
+//! In coordinator mode (enabled with the `shard_groups` configuration), the
+//! shards are split in contiguous ranges and each process claims a free range
+//! with a lease stored in the cache. The process is registered in the cache
+//! as a [`ShardProcess`], along with its range and last heartbeat.
+//!
+//! The lease is renewed periodically while the cluster runs (see
+//! [`heartbeat`]) and released on shutdown. If a process stops without
+//! releasing its lease, the lease expires after
+//! [`ShardProcess::LEASE_DURATION`] and the range is claimed by a process
+//! waiting for a free range. A process that fails to renew its lease stops
+//! its cluster, so that a range is never run by two processes.
+
+use std::time::Duration;
+
+use raidprotect_model::cache::{model::gateway::ShardProcess, CacheClient};
+use rand::{distributions::Alphanumeric, Rng};
+use time::OffsetDateTime;
+use tracing::{error, info, warn};
+
+/// Interval between each renewal of the lease.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Interval between each attempt to claim a range.
+const CLAIM_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Range of shards claimed by the process.
+#[derive(Debug, Clone)]
+pub struct ShardLease {
+    process: ShardProcess,
+}
+
+impl ShardLease {
+    /// Id of the first shard of the range.
+    pub fn from(&self) -> u64 {
+        self.process.from
+    }
+
+    /// Id of the last shard of the range (inclusive).
+    pub fn to(&self) -> u64 {
+        self.process.to
+    }
+
+    /// Key of the lease in the cache.
+    fn key(&self) -> String {
+        ShardProcess::lease_key(self.process.total_shards, self.process.group)
+    }
+
+    /// Renew the lease and the process registration.
+    ///
+    /// Returns `false` if the lease has been lost.
+    async fn renew(&mut self, cache: &CacheClient) -> Result<bool, anyhow::Error> {
+        let renewed = cache
+            .renew_lease(
+                &self.key(),
+                &self.process.process_id,
+                ShardProcess::LEASE_DURATION,
+            )
+            .await?;
+
+        if renewed {
+            self.process.heartbeat_at = OffsetDateTime::now_utc();
+            cache.set(&self.process).await?;
+        }
+
+        Ok(renewed)
+    }
+
+    /// Release the lease, so that the range can be claimed by another process.
+    pub async fn release(&self, cache: &CacheClient) {
+        if let Err(error) = cache
+            .release_lease(&self.key(), &self.process.process_id)
+            .await
+        {
+            error!(error = ?error, "failed to release shard range lease");
+        }
+
+        if let Err(error) = cache.delete(&self.process).await {
+            error!(error = ?error, "failed to delete shard process registration");
+        }
+    }
+}
+
+/// Claim a free range of shards.
+///
+/// The `total` shards are split in `groups` ranges. If all the ranges are
+/// claimed, this waits until one of them is released.
+pub async fn claim(
+    cache: &CacheClient,
+    groups: u64,
+    total: u64,
+) -> Result<ShardLease, anyhow::Error> {
+    let process_id: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(16)
+        .map(char::from)
+        .collect();
+
+    info!(process = %process_id, groups, total, "claiming a shard range");
+
+    loop {
+        for group in 0..groups {
+            let (from, to) = match group_range(group, groups, total) {
+                Some(range) => range,
+                None => continue,
+            };
+
+            let key = ShardProcess::lease_key(total, group);
+            if !cache
+                .acquire_lease(&key, &process_id, ShardProcess::LEASE_DURATION)
+                .await?
+            {
+                continue;
+            }
+
+            let process = ShardProcess {
+                process_id,
+                group,
+                from,
+                to,
+                total_shards: total,
+                heartbeat_at: OffsetDateTime::now_utc(),
+            };
+            cache.set(&process).await?;
+
+            info!(group, from, to, "claimed shard range");
+
+            return Ok(ShardLease { process });
+        }
+
+        info!("no free shard range, waiting for a range to be released");
+        tokio::time::sleep(CLAIM_INTERVAL).await;
+    }
+}
+
+/// Renew the lease periodically.
+///
+/// Returns if the lease is lost. Never returns if `lease` is [`None`].
+pub async fn heartbeat(lease: Option<&mut ShardLease>, cache: &CacheClient) {
+    let lease = match lease {
+        Some(lease) => lease,
+        None => return std::future::pending().await,
+    };
+
+    // The first renewal is immediate, since the cluster may have taken some
+    // time to start after the range was claimed.
+    let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        match lease.renew(cache).await {
+            Ok(true) => {}
+            Ok(false) => {
+                warn!("shard range lease lost, stopping the cluster");
+                return;
+            }
+            // The lease is kept until it expires, a later renewal may succeed.
+            Err(error) => error!(error = ?error, "failed to renew shard range lease"),
+        }
+    }
+}
+
+/// Get the range of shards of a group.
+///
+/// Returns [`None`] if the group is empty, which happens if there are more
+/// groups than shards.
+fn group_range(group: u64, groups: u64, total: u64) -> Option<(u64, u64)> {
+    let size = (total + groups - 1) / groups.max(1);
+    let from = group * size;
+
+    if from >= total {
+        return None;
+    }
+
+    Some((from, (from + size).min(total) - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::group_range;
+
+    #[test]
+    fn test_group_range() {
+        assert_eq!(group_range(0, 2, 16), Some((0, 7)));
+        assert_eq!(group_range(1, 2, 16), Some((8, 15)));
+        assert_eq!(group_range(2, 3, 10), Some((8, 9)));
+        assert_eq!(group_range(0, 1, 1), Some((0, 0)));
+        assert_eq!(group_range(3, 4, 3), None);
+    }
+}
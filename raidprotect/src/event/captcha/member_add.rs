@@ -1,12 +1,16 @@
 //! Handle `MemberAdd` event.
 
-use raidprotect_model::cache::model::interaction::PendingCaptcha;
+use raidprotect_model::{cache::model::interaction::PendingCaptcha, database::model::JobAction};
 use time::{Duration, OffsetDateTime};
-use tracing::{debug, error, instrument};
+use tracing::{debug, error};
 use twilight_http::request::AuditLogReason;
 use twilight_model::guild::Member;
 
-use crate::{cluster::ClusterState, feature::captcha, translations::Lang};
+use crate::{
+    cluster::ClusterState,
+    feature::{captcha, scheduler},
+    translations::Lang,
+};
 
 /// Handle `MemberAdd` event.
 pub async fn member_add(member: &Member, state: &ClusterState) {
@@ -26,7 +30,7 @@ async fn member_add_inner(member: &Member, state: &ClusterState) -> Result<(), a
     }
 
     // Get the guild configuration.
-    let config = state.database.get_guild_or_create(member.guild_id).await?;
+    let config = state.guild_config(member.guild_id).await?;
     let lang = Lang::from(&*config.lang);
 
     if !config.captcha.enabled {
@@ -62,56 +66,19 @@ async fn member_add_inner(member: &Member, state: &ClusterState) -> Result<(), a
         member_id: member.user.id,
         code: String::new(), // Code generated on button click.
         regenerate_count: 0,
+        solved: false,
+        strict: config.captcha.is_strict(),
         expires_at: OffsetDateTime::now_utc() + captcha::DEFAULT_DURATION,
+        web_token: None,
     };
 
-    let state_clone = state.clone();
     state.cache.set(&pending_captcha).await?;
-    tokio::spawn(captcha_expire(state_clone, pending_captcha, lang));
 
-    Ok(())
-}
-
-/// Kick the user if the captcha has not been validated in time.
-#[instrument(skip(state))]
-async fn captcha_expire(state: ClusterState, captcha: PendingCaptcha, lang: Lang) {
-    // Sleep until the captcha expiration.
-    let duration = captcha.expires_at - OffsetDateTime::now_utc();
-
-    if let Ok(duration) = duration.try_into() {
-        tokio::time::sleep(duration).await;
-    }
-
-    // If the captcha still present in the cache, kick the user.
-    let key = (captcha.guild_id, captcha.member_id);
-    let pending_captcha = state.cache.get::<PendingCaptcha>(&key).await;
-
-    if pending_captcha
-        .as_ref()
-        .map(Option::is_some)
-        .unwrap_or(false)
-    {
-        debug!("captcha not expired");
-        return;
-    }
-
-    if let Err(error) = kick_user_expired(&state, captcha, lang).await {
-        error!(error = ?error, "error while kicking user on captcha expiration");
-    }
-}
-
-async fn kick_user_expired(
-    state: &ClusterState,
-    captcha: PendingCaptcha,
-    lang: Lang,
-) -> Result<(), anyhow::Error> {
-    state
-        .cache_http(captcha.guild_id)
-        .remove_guild_member(captcha.member_id)
-        .await?
-        .reason(lang.captcha_expired_reason())?
-        .exec()
-        .await?;
+    // Kick the member if the captcha is not completed in time.
+    let action = JobAction::CaptchaDeadline {
+        user_id: member.user.id,
+    };
+    scheduler::schedule(state, member.guild_id, action, captcha::DEFAULT_DURATION).await?;
 
     Ok(())
 }
@@ -1,42 +1,201 @@
 use anyhow::Context;
-use raidprotect_model::{cache::discord::CachedGuild, database::model::GuildConfig};
-use tracing::{error, info};
-use twilight_model::{channel::Message, gateway::payload::incoming::MessageDelete};
+use raidprotect_model::{
+    cache::{discord::CachedGuild, model::message::CachedMessage},
+    database::model::GuildConfig,
+};
+use tracing::{debug, error, trace};
+use twilight_model::{
+    channel::Message,
+    gateway::payload::incoming::{MessageDelete, MessageDeleteBulk, MessageUpdate},
+    id::{marker::WebhookMarker, Id},
+};
 
 use super::{
-    old_command::{is_old_command, warn_old_command},
-    parser::parse_message,
+    old_command::{find_old_command, warn_old_command},
+    parser::{parse_message, update_message},
 };
 use crate::{
     cluster::ClusterState,
+    feature::{
+        antispam, attachments, domains, filter, invite,
+        kill_switch::{automation_disabled, disabled_automations, Automation},
+        logs, mention, phishing, webhook,
+    },
     interaction::{component::captcha::verification_message, util::GuildConfigExt},
+    translations::Lang,
 };
 
 /// Handle incoming [`Message`].
 ///
 /// This method will forward message to the cache and various auto-moderation
 /// modules.
-pub async fn handle_message_create(message: Message, state: &ClusterState) {
+pub async fn handle_message_create(mut message: Message, state: &ClusterState) {
+    // Check messages from webhooks. Interaction responses are also sent as
+    // webhooks, but have an application id.
+    if let (Some(webhook_id), None) = (message.webhook_id, message.application_id) {
+        handle_webhook_message(message, webhook_id, state).await;
+        return;
+    }
+
     // Ignore messages from bots.
     if message.author.bot {
         return;
     }
 
-    let parsed = parse_message(&message);
-    state.cache.set(&parsed).await.ok();
+    // The message is consumed by the parser, only keep the fields needed
+    // afterwards.
+    let locale = message.author.locale.take();
+    let roles = message
+        .member
+        .take()
+        .map(|member| member.roles)
+        .unwrap_or_default();
+    let guild_id = match message.guild_id {
+        Some(guild_id) => guild_id,
+        None => return,
+    };
+    let parsed = parse_message(message);
 
     // Warn the user if they're using an old command.
-    if is_old_command(&message.content) {
-        let (message, state) = (message.clone(), state.clone());
+    if let Some(command) = find_old_command(&parsed.content) {
+        let lang = locale
+            .map(|lang| Lang::from(&*lang))
+            .unwrap_or(Lang::DEFAULT);
+        let (channel_id, message_id, state) = (parsed.channel_id, parsed.id, state.clone());
 
         tokio::spawn(async move {
-            if let Err(error) = warn_old_command(message, &state).await {
+            if let Err(error) =
+                warn_old_command(command, channel_id, message_id, lang, &state).await
+            {
                 error!(error = ?error, "failed to warn user about old command");
             }
         });
     }
 
-    info!("received message: {}", message.content) // Debug util real implementation
+    state.cache.set_message(guild_id, &parsed).await.ok();
+    trace!(message = ?parsed.id, "message cached");
+
+    // Each check returns whether it deleted the message, in which case the
+    // following checks are skipped so that a message is only handled once.
+    let disabled = disabled_automations(state).await;
+
+    if !disabled.contains(Automation::AntiSpam) {
+        match antispam::check_message(&parsed, guild_id, &roles, state).await {
+            Ok(true) => return,
+            Ok(false) => {}
+            Err(error) => error!(error = ?error, "error while checking message for spam"),
+        }
+    }
+
+    if !disabled.contains(Automation::Invites) {
+        match invite::check_message(&parsed, guild_id, &roles, state).await {
+            Ok(true) => return,
+            Ok(false) => {}
+            Err(error) => error!(error = ?error, "error while checking message for invites"),
+        }
+    }
+
+    if !disabled.contains(Automation::WordFilter) {
+        match filter::check_message(&parsed, guild_id, &roles, state).await {
+            Ok(true) => return,
+            Ok(false) => {}
+            Err(error) => {
+                error!(error = ?error, "error while checking message with the word filter")
+            }
+        }
+    }
+
+    if !disabled.contains(Automation::Attachments) {
+        match attachments::check_message(&parsed, guild_id, &roles, state).await {
+            Ok(true) => return,
+            Ok(false) => {}
+            Err(error) => error!(error = ?error, "error while checking message attachments"),
+        }
+    }
+
+    if !disabled.contains(Automation::Phishing) {
+        match phishing::check_message(&parsed, guild_id, &roles, state).await {
+            Ok(true) => return,
+            Ok(false) => {}
+            Err(error) => error!(error = ?error, "error while checking message for phishing"),
+        }
+    }
+
+    if !disabled.contains(Automation::Domains) {
+        if let Err(error) = domains::check_message(&parsed, guild_id, &roles, state).await {
+            error!(error = ?error, "error while checking message for blocked domains");
+        }
+    }
+}
+
+/// Handle incoming [`Message`] sent by a webhook.
+async fn handle_webhook_message(
+    message: Message,
+    webhook_id: Id<WebhookMarker>,
+    state: &ClusterState,
+) {
+    let guild_id = match message.guild_id {
+        Some(guild_id) => guild_id,
+        None => return,
+    };
+    let name = message.author.name.clone();
+    let parsed = parse_message(message);
+
+    state.cache.set_message(guild_id, &parsed).await.ok();
+
+    if !automation_disabled(Automation::Webhooks, state).await {
+        if let Err(error) =
+            webhook::check_message(&parsed, webhook_id, &name, guild_id, state).await
+        {
+            error!(error = ?error, "error while checking webhook message");
+        }
+    }
+}
+
+/// Delay during which bulk-deleted messages are kept in the cache (1 hour).
+const BULK_DELETED_EXPIRES_AFTER: usize = 60 * 60;
+
+/// Handle updated [`Message`].
+///
+/// The cached message is updated to reflect the edit. Messages that are not
+/// cached (or expired) are ignored.
+pub async fn handle_message_update(event: MessageUpdate, state: &ClusterState) {
+    let mut message = match state.cache.get::<CachedMessage>(&event.id).await {
+        Ok(Some(message)) => message,
+        Ok(None) => return,
+        Err(error) => {
+            error!(error = ?error, "failed to get updated message from cache");
+            return;
+        }
+    };
+
+    update_message(&mut message, event);
+
+    if let Err(error) = state.cache.set(&message).await {
+        error!(error = ?error, "failed to update cached message");
+    }
+    trace!(message = ?message.id, "cached message updated");
+}
+
+/// Handle bulk-deleted [`Message`]s.
+///
+/// Bulk deletions are mostly used by moderators to clean up a channel after
+/// a raid. The deleted messages are kept in the cache for an hour so that
+/// their content can be recovered.
+pub async fn handle_message_delete_bulk(event: MessageDeleteBulk, state: &ClusterState) {
+    match state
+        .cache
+        .retain_messages(&event.ids, BULK_DELETED_EXPIRES_AFTER)
+        .await
+    {
+        Ok(messages) => debug!(
+            channel = ?event.channel_id,
+            "{} messages bulk-deleted, {} recovered from cache",
+            event.ids.len(),
+            messages.len()
+        ),
+        Err(error) => error!(error = ?error, "failed to retain bulk-deleted messages"),
+    }
 }
 
 /// Handle deleted [`Message`].
@@ -56,11 +215,14 @@ async fn handle_message_delete_inner(
         .context("missing guild_id in message delete event")?;
 
     let mut config = state
-        .database
-        .get_guild_or_create(guild_id)
+        .guild_config(guild_id)
         .await
         .context("failed to get guild configuration")?;
 
+    if let Err(error) = logs::log_message_delete(event.id, guild_id, &config, state).await {
+        error!(error = ?error, "failed to log deleted message");
+    }
+
     // Resend the captcha message if deleted.
     if config.captcha.enabled && Some(event.id) == config.captcha.message {
         if let Err(error) = resend_captcha_message(&mut config, state).await {
@@ -68,6 +230,11 @@ async fn handle_message_delete_inner(
         }
     }
 
+    // Check for ghost pings.
+    if config.antispam.enabled && !automation_disabled(Automation::Mentions, state).await {
+        mention::check_deleted(event.id, guild_id, &config, state).await?;
+    }
+
     Ok(())
 }
 
@@ -88,7 +255,7 @@ async fn resend_captcha_message(
 
     // Update guild configuration.
     config.captcha.message = Some(message.id);
-    state.database.update_guild(config).await?;
+    state.update_guild_config(config).await?;
 
     Ok(())
 }
@@ -8,7 +8,9 @@ mod old_command;
 
 pub mod parser;
 
-pub use handle::{handle_message_create, handle_message_delete};
+pub use handle::{
+    handle_message_create, handle_message_delete, handle_message_delete_bulk, handle_message_update,
+};
 
 /// Messages types processed by the bot.
 pub const ALLOWED_MESSAGES_TYPES: [twilight_model::channel::message::MessageType; 3] = [
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 
-use anyhow::bail;
 use once_cell::sync::Lazy;
-use twilight_model::channel::Message;
+use twilight_model::id::{
+    marker::{ChannelMarker, MessageMarker},
+    Id,
+};
 use twilight_util::builder::embed::EmbedBuilder;
 
 use crate::{cluster::ClusterState, interaction::embed::COLOR_TRANSPARENT, translations::Lang};
@@ -26,43 +28,59 @@ static OLD_COMMANDS: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
     ])
 });
 
-/// Check whether a message contains an old command.
-pub fn is_old_command(content: &str) -> bool {
-    if let Some((command, _)) = content.split_once(' ') {
-        return OLD_COMMANDS.contains_key(command);
-    }
+/// Old command used in a message.
+#[derive(Debug, Clone, Copy)]
+pub struct OldCommand {
+    /// Name of the old command.
+    pub old: &'static str,
+    /// Name of the new command.
+    pub new: &'static str,
+}
+
+/// Find the old command used in a message, if any.
+pub fn find_old_command(content: &str) -> Option<OldCommand> {
+    let (command, _) = content.split_once(' ')?;
+    let (old, new) = OLD_COMMANDS.get_key_value(command)?;
 
-    false
+    Some(OldCommand { old, new })
 }
 
 /// Send a warning message to the user that they used an old command.
-pub async fn warn_old_command(message: Message, state: &ClusterState) -> Result<(), anyhow::Error> {
-    let lang = message
-        .author
-        .locale
-        .map(|lang| Lang::from(&*lang))
-        .unwrap_or(Lang::DEFAULT);
+pub async fn warn_old_command(
+    command: OldCommand,
+    channel_id: Id<ChannelMarker>,
+    message_id: Id<MessageMarker>,
+    lang: Lang,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let embed = EmbedBuilder::new()
+        .title(lang.warning_deprecated_command_title())
+        .description(lang.warning_deprecated_command_description(command.new, command.old))
+        .color(COLOR_TRANSPARENT)
+        .build();
 
-    if let Some((command, _)) = message.content.split_once(' ') {
-        let new = match OLD_COMMANDS.get(command) {
-            Some(new) => new,
-            None => bail!("no command matching {} found", command),
-        };
+    state
+        .http
+        .create_message(channel_id)
+        .reply(message_id)
+        .embeds(&[embed])?
+        .exec()
+        .await?;
 
-        let embed = EmbedBuilder::new()
-            .title(lang.warning_deprecated_command_title())
-            .description(lang.warning_deprecated_command_description(new, command))
-            .color(COLOR_TRANSPARENT)
-            .build();
+    Ok(())
+}
 
-        state
-            .http
-            .create_message(message.channel_id)
-            .reply(message.id)
-            .embeds(&[embed])?
-            .exec()
-            .await?;
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Ok(())
+    #[test]
+    fn test_find_old_command() {
+        let command = find_old_command("?kick @user spam").unwrap();
+
+        assert_eq!(command.old, "?kick");
+        assert_eq!(command.new, "/kick");
+        assert!(find_old_command("?kick").is_none());
+        assert!(find_old_command("hello world").is_none());
+    }
 }
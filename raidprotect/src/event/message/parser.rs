@@ -1,12 +1,12 @@
 //! Message parser.
 //!
 //! This module is used to convert an incoming [`Message`] into a parsed
-//! [`CachedMessage`].
+//! [`CachedMessage`], and to apply edits to a cached message.
 
 use any_ascii::any_ascii;
 use linkify::{LinkFinder, LinkKind};
 use raidprotect_model::cache::model::message::{CachedMessage, MessageLink};
-use twilight_model::channel::Message;
+use twilight_model::{channel::Message, gateway::payload::incoming::MessageUpdate};
 use unicode_segmentation::UnicodeSegmentation;
 use url::Url;
 
@@ -21,7 +21,10 @@ const MEDIA_EXT: [&str; 9] = [
 ];
 
 /// Parse incoming [`Message`] into a [`CachedMessage`].
-pub fn parse_message(message: &Message) -> CachedMessage {
+///
+/// The message is consumed to move its content and attachments into the
+/// parsed message instead of cloning them.
+pub fn parse_message(message: Message) -> CachedMessage {
     // Only these message types are processed.
     // This must be enforced in the gateway crate.
     debug_assert!(
@@ -33,26 +36,74 @@ pub fn parse_message(message: &Message) -> CachedMessage {
         "unsupported message type"
     );
 
-    let words = message.content.unicode_words().map(any_ascii).collect();
     let mention_users = message.mentions.iter().map(|mention| mention.id).collect();
-    let links = LinkFinder::new()
-        .kinds(&[LinkKind::Url])
-        .links(&message.content)
-        .filter_map(|link| parse_link(link.as_str()))
-        .collect();
 
     CachedMessage {
         id: message.id,
         author_id: message.author.id,
         channel_id: message.channel_id,
-        content: message.content.clone(),
+        words: parse_words(&message.content),
+        links: parse_links(&message.content),
+        content: message.content,
         timestamp: message.timestamp,
-        words,
-        attachments: message.attachments.clone(),
-        links,
+        attachments: message.attachments,
         mention_everyone: message.mention_everyone,
         mention_users,
-        mention_roles: message.mention_roles.clone(),
+        mention_roles: message.mention_roles,
+    }
+}
+
+/// Apply a [`MessageUpdate`] to a [`CachedMessage`].
+///
+/// Only the fields present in the update are changed. The words and links are
+/// parsed again if the content changed.
+pub fn update_message(message: &mut CachedMessage, update: MessageUpdate) {
+    if let Some(content) = update.content {
+        message.words = parse_words(&content);
+        message.links = parse_links(&content);
+        message.content = content;
+    }
+
+    if let Some(attachments) = update.attachments {
+        message.attachments = attachments;
+    }
+
+    if let Some(mention_everyone) = update.mention_everyone {
+        message.mention_everyone = mention_everyone;
+    }
+
+    if let Some(mentions) = update.mentions {
+        message.mention_users = mentions.iter().map(|mention| mention.id).collect();
+    }
+
+    if let Some(mention_roles) = update.mention_roles {
+        message.mention_roles = mention_roles;
+    }
+}
+
+/// Split a message content into ASCII words.
+fn parse_words(content: &str) -> Vec<String> {
+    content.unicode_words().map(parse_word).collect()
+}
+
+/// Find the links included in a message content.
+fn parse_links(content: &str) -> Vec<MessageLink> {
+    LinkFinder::new()
+        .kinds(&[LinkKind::Url])
+        .links(content)
+        .filter_map(|link| parse_link(link.as_str()))
+        .collect()
+}
+
+/// Convert a word into ASCII.
+///
+/// Most words are already ASCII and are directly copied, without going through
+/// the [`any_ascii`] conversion.
+fn parse_word(word: &str) -> String {
+    if word.is_ascii() {
+        word.to_owned()
+    } else {
+        any_ascii(word)
     }
 }
 
@@ -75,8 +126,17 @@ fn parse_link(link: &str) -> Option<MessageLink> {
 
 #[cfg(test)]
 mod tests {
+    use twilight_model::{id::Id, util::Timestamp};
+
     use super::*;
 
+    #[test]
+    fn test_parse_word() {
+        assert_eq!(parse_word("raidprotect"), "raidprotect");
+        assert_eq!(parse_word("été"), "ete");
+        assert_eq!(parse_word("ｒａｉｄ"), "raid");
+    }
+
     #[test]
     fn test_link_invite() {
         assert_eq!(
@@ -123,4 +183,50 @@ mod tests {
             ))
         );
     }
+
+    #[test]
+    fn test_update_message() {
+        let mut message = CachedMessage {
+            id: Id::new(1),
+            author_id: Id::new(2),
+            channel_id: Id::new(3),
+            content: "hello".into(),
+            timestamp: Timestamp::from_secs(1_600_000_000).unwrap(),
+            words: vec!["hello".to_owned()],
+            attachments: Vec::new(),
+            links: Vec::new(),
+            mention_everyone: true,
+            mention_users: Vec::new(),
+            mention_roles: Vec::new(),
+        };
+
+        let update = MessageUpdate {
+            attachments: None,
+            author: None,
+            channel_id: Id::new(3),
+            content: Some("join https://discord.gg/raidprotect".to_owned()),
+            edited_timestamp: None,
+            embeds: None,
+            guild_id: Some(Id::new(4)),
+            id: Id::new(1),
+            kind: None,
+            mention_everyone: Some(false),
+            mention_roles: None,
+            mentions: None,
+            pinned: None,
+            timestamp: None,
+            tts: None,
+        };
+        update_message(&mut message, update);
+
+        assert_eq!(&*message.content, "join https://discord.gg/raidprotect");
+        assert_eq!(message.words[0], "join");
+        assert_eq!(
+            message.links,
+            vec![MessageLink::Invite(
+                Url::parse("https://discord.gg/raidprotect").unwrap()
+            )]
+        );
+        assert!(!message.mention_everyone);
+    }
 }
@@ -8,5 +8,9 @@
 mod captcha;
 mod message;
 mod process;
+mod queue;
+mod tap;
 
-pub use process::ProcessEvent;
+pub use process::{processed_events, ProcessEvent};
+pub use queue::EventQueue;
+pub use tap::EventTapRecorder;
@@ -1,12 +1,24 @@
 use std::fmt::Debug;
 
 use async_trait::async_trait;
-use raidprotect_model::cache::discord::UpdateCache;
+use raidprotect_model::cache::discord::{CachedMember, CachedRole, CachedVoiceState, UpdateCache};
 use tracing::{debug, error, trace};
-use twilight_model::gateway::{event::Event as GatewayEvent, payload::incoming};
+use twilight_gateway::EventTypeFlags;
+use twilight_model::gateway::{
+    event::{Event as GatewayEvent, EventType},
+    payload::incoming,
+};
 
 use super::message::ALLOWED_MESSAGES_TYPES;
-use crate::cluster::ClusterState;
+use crate::{
+    cluster::ClusterState,
+    feature::{
+        antinuke, antispam, audit_log, join_source,
+        kill_switch::{automation_disabled, Automation},
+        logs, offboarding, security_alert,
+    },
+    util::warm_up_guild,
+};
 
 /// Process incoming events.
 #[async_trait]
@@ -15,13 +27,28 @@ pub trait ProcessEvent: Sized {
     async fn process(self, state: ClusterState);
 }
 
+/// Implement [`ProcessEvent`] for [`GatewayEvent`] and generate the
+/// [`processed_events`] function from the list of processed events.
 macro_rules! process_events {
-    ($self:ident, $state:ident => $( $event:path ),+ ) => {
-        match $self {
-            $(
-                $event(event) => event.process($state).await,
-            )+
-            event => trace!(kind = event.kind().name(), "unprocessed event type"),
+    ( $( $event:ident ),+ ) => {
+        #[async_trait]
+        impl ProcessEvent for GatewayEvent {
+            async fn process(self, state: ClusterState) {
+                match self {
+                    $(
+                        GatewayEvent::$event(event) => event.process(state).await,
+                    )+
+                    event => trace!(kind = event.kind().name(), "unprocessed event type"),
+                }
+            }
+        }
+
+        /// Types of the events processed by the bot.
+        ///
+        /// The cluster only receives these events: other events are dropped by
+        /// the gateway before being deserialized.
+        pub fn processed_events() -> EventTypeFlags {
+            EventTypeFlags::empty() $( | EventTypeFlags::from(EventType::$event) )+
         }
     };
 }
@@ -46,49 +73,137 @@ macro_rules! process_cache_events {
     };
 }
 
-#[async_trait]
-impl ProcessEvent for GatewayEvent {
-    async fn process(self, state: ClusterState) {
-        use GatewayEvent::*;
-
-        // `self` is renamed `__self` in async_trait macro expansion
-        process_events! { __self, state =>
-            GuildCreate,
-            GuildDelete,
-            UnavailableGuild,
-            GuildUpdate,
-            ChannelCreate,
-            ChannelDelete,
-            ChannelUpdate,
-            InteractionCreate,
-            ThreadCreate,
-            ThreadDelete,
-            ThreadUpdate,
-            RoleCreate,
-            RoleDelete,
-            MemberAdd,
-            MemberUpdate,
-            MessageCreate,
-            MessageDelete
-        }
-    }
-}
-
-// Implementation of events only processed in cache
-process_cache_events! {
+process_events! {
     GuildCreate,
     GuildDelete,
     UnavailableGuild,
     GuildUpdate,
+    BanAdd,
     ChannelCreate,
     ChannelDelete,
     ChannelUpdate,
+    InteractionCreate,
+    InviteCreate,
+    InviteDelete,
     ThreadCreate,
     ThreadDelete,
     ThreadUpdate,
     RoleCreate,
     RoleDelete,
-    MemberUpdate
+    RoleUpdate,
+    MemberAdd,
+    MemberUpdate,
+    MemberRemove,
+    MemberChunk,
+    MessageCreate,
+    MessageDelete,
+    MessageDeleteBulk,
+    MessageUpdate,
+    VoiceStateUpdate,
+    WebhooksUpdate
+}
+
+// Implementation of events only processed in cache
+process_cache_events! {
+    UnavailableGuild,
+    GuildUpdate,
+    ChannelCreate,
+    ChannelUpdate,
+    InviteCreate,
+    InviteDelete,
+    ThreadCreate,
+    ThreadDelete,
+    ThreadUpdate,
+    RoleCreate
+}
+
+#[async_trait]
+impl ProcessEvent for incoming::GuildCreate {
+    async fn process(self, state: ClusterState) {
+        let (guild_id, large) = (self.id, self.large);
+
+        process_cache_event(self, &state).await;
+
+        // Members of large guilds are requested in the background since
+        // gateway commands are rate-limited.
+        if large {
+            let state = state.clone();
+            tokio::spawn(async move { warm_up_guild(&state, guild_id).await });
+        }
+
+        offboarding::guild_joined(guild_id, &state).await;
+        join_source::guild_joined(guild_id, &state).await;
+    }
+}
+
+#[async_trait]
+impl ProcessEvent for incoming::MemberChunk {
+    async fn process(self, state: ClusterState) {
+        process_cache_event(self.clone(), &state).await;
+
+        // Pending requests are resolved once the members are cached.
+        state.member_requests.receive(&self);
+    }
+}
+
+#[async_trait]
+impl ProcessEvent for incoming::GuildDelete {
+    async fn process(self, state: ClusterState) {
+        process_cache_event(self.clone(), &state).await;
+
+        // The guild is only unavailable because of an outage.
+        if !self.unavailable {
+            offboarding::guild_removed(self.id, &state).await;
+        }
+    }
+}
+
+#[async_trait]
+impl ProcessEvent for incoming::ChannelDelete {
+    async fn process(self, state: ClusterState) {
+        process_cache_event(self.clone(), &state).await;
+
+        if let Some(guild_id) = self.guild_id {
+            if !automation_disabled(Automation::AntiNuke, &state).await {
+                antinuke::channel_deleted(self.id, guild_id, &state).await;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ProcessEvent for incoming::RoleDelete {
+    async fn process(self, state: ClusterState) {
+        process_cache_event(self.clone(), &state).await;
+
+        if !automation_disabled(Automation::AntiNuke, &state).await {
+            antinuke::role_deleted(self.role_id, self.guild_id, &state).await;
+        }
+    }
+}
+
+#[async_trait]
+impl ProcessEvent for incoming::RoleUpdate {
+    async fn process(self, state: ClusterState) {
+        // The role is retrieved before being updated in the cache.
+        let role = state
+            .cache
+            .get::<CachedRole>(&self.role.id)
+            .await
+            .unwrap_or_default();
+
+        process_cache_event(self.clone(), &state).await;
+        security_alert::role_updated(&self, role, &state).await;
+    }
+}
+
+#[async_trait]
+impl ProcessEvent for incoming::WebhooksUpdate {
+    async fn process(self, state: ClusterState) {
+        if !automation_disabled(Automation::AntiNuke, &state).await {
+            antinuke::webhooks_updated(self.guild_id, &state).await;
+        }
+    }
 }
 
 #[async_trait]
@@ -102,7 +217,97 @@ impl ProcessEvent for incoming::InteractionCreate {
 impl ProcessEvent for incoming::MemberAdd {
     async fn process(self, state: ClusterState) {
         process_cache_event(self.clone(), &state).await;
-        super::captcha::member_add(&self.0, &state).await;
+        join_source::member_joined(&self.0, &state).await;
+
+        // Join raids are checked first to enable the captcha strict mode
+        // before the member receives the captcha.
+        if !automation_disabled(Automation::JoinRaid, &state).await {
+            antispam::check_join(&self.0, &state).await;
+        }
+
+        if !automation_disabled(Automation::Captcha, &state).await {
+            super::captcha::member_add(&self.0, &state).await;
+        }
+
+        if let Err(error) = logs::log_member_join(&self.0, &state).await {
+            error!(error = ?error, "failed to log member join");
+        }
+
+        security_alert::bot_added(&self.0, &state).await;
+    }
+}
+
+#[async_trait]
+impl ProcessEvent for incoming::MemberUpdate {
+    async fn process(self, state: ClusterState) {
+        // The member is retrieved before being updated in the cache.
+        let member = state
+            .cache
+            .get::<CachedMember>(&(self.guild_id, self.user.id))
+            .await
+            .unwrap_or_default();
+
+        process_cache_event(self.clone(), &state).await;
+        audit_log::member_updated(&self, member.as_ref(), &state).await;
+
+        if let Err(error) = logs::log_member_update(&self, member.as_ref(), &state).await {
+            error!(error = ?error, "failed to log member update");
+        }
+    }
+}
+
+#[async_trait]
+impl ProcessEvent for incoming::MemberRemove {
+    async fn process(self, state: ClusterState) {
+        // The member is retrieved before being removed from the cache.
+        let member = state
+            .cache
+            .get::<CachedMember>(&(self.guild_id, self.user.id))
+            .await
+            .unwrap_or_default();
+
+        process_cache_event(self.clone(), &state).await;
+
+        if let Err(error) = logs::log_member_leave(&self.user, self.guild_id, member, &state).await
+        {
+            error!(error = ?error, "failed to log member leave");
+        }
+
+        audit_log::member_removed(&self.user, self.guild_id, &state).await;
+    }
+}
+
+#[async_trait]
+impl ProcessEvent for incoming::VoiceStateUpdate {
+    async fn process(self, state: ClusterState) {
+        let guild_id = match self.guild_id {
+            Some(guild_id) => guild_id,
+            None => return,
+        };
+
+        // The voice state is retrieved before being updated in the cache.
+        let voice_state = state
+            .cache
+            .get::<CachedVoiceState>(&(guild_id, self.user_id))
+            .await
+            .unwrap_or_default();
+
+        process_cache_event(self.clone(), &state).await;
+
+        if let Err(error) = logs::log_voice_state(&self.0, guild_id, voice_state, &state).await {
+            error!(error = ?error, "failed to log voice state update");
+        }
+    }
+}
+
+#[async_trait]
+impl ProcessEvent for incoming::BanAdd {
+    async fn process(self, state: ClusterState) {
+        audit_log::ban_added(&self.user, self.guild_id, &state).await;
+
+        if !automation_disabled(Automation::AntiNuke, &state).await {
+            antinuke::ban_added(self.user.id, self.guild_id, &state).await;
+        }
     }
 }
 
@@ -123,3 +328,34 @@ impl ProcessEvent for incoming::MessageDelete {
         }
     }
 }
+
+#[async_trait]
+impl ProcessEvent for incoming::MessageDeleteBulk {
+    async fn process(self, state: ClusterState) {
+        if self.guild_id.is_some() {
+            super::message::handle_message_delete_bulk(self, &state).await;
+        }
+    }
+}
+
+#[async_trait]
+impl ProcessEvent for incoming::MessageUpdate {
+    async fn process(self, state: ClusterState) {
+        if self.guild_id.is_some() {
+            super::message::handle_message_update(self, &state).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_processed_events() {
+        let events = processed_events();
+
+        assert!(events.contains(EventTypeFlags::GUILD_CREATE | EventTypeFlags::MESSAGE_CREATE));
+        assert!(!events.intersects(EventTypeFlags::TYPING_START | EventTypeFlags::PRESENCE_UPDATE));
+    }
+}
@@ -0,0 +1,202 @@
+//! Event processing queue.
+//!
+//! Incoming events are processed by a fixed number of workers instead of
+//! spawning a task per event, to keep the memory usage stable during event
+//! storms (such as the `GuildCreate` flood when the bot starts).
+//!
+//! Events of a guild are always sent to the same worker and processed in the
+//! order they were received, so that the cache updates are applied in order.
+//! The queue of each worker is bounded: when full, the cluster stops reading
+//! new events until the workers catch up.
+//!
+//! Interactions are not queued since they do not update the cache and must be
+//! answered within a few seconds.
+//!
+//! When shutting down, the queue is closed and the queued events and running
+//! interactions are processed before the next shutdown stage (see
+//! [`EventQueue::close`]).
+
+use std::{
+    mem,
+    sync::{Mutex, RwLock},
+};
+
+use tokio::{sync::mpsc, task::JoinHandle};
+use tracing::{error, info_span, trace, Instrument};
+use twilight_model::{
+    gateway::event::Event as GatewayEvent,
+    id::{marker::GuildMarker, Id},
+};
+
+use super::ProcessEvent;
+use crate::cluster::ClusterState;
+
+/// Maximum number of events waiting in the queue of each worker.
+const WORKER_CAPACITY: usize = 256;
+
+/// Bounded queue of events processed by a pool of workers.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug)]
+pub struct EventQueue {
+    /// Senders of the queue, removed when the queue is closed.
+    senders: RwLock<Option<QueueSenders>>,
+    /// Handles of the worker tasks.
+    handles: Mutex<Vec<JoinHandle<()>>>,
+    /// Receiver notified when all the interaction tasks have completed.
+    interactions: Mutex<Option<mpsc::Receiver<()>>>,
+    state: ClusterState,
+}
+
+/// Senders of an [`EventQueue`].
+#[derive(Debug)]
+struct QueueSenders {
+    /// Senders of the worker queues.
+    workers: Vec<mpsc::Sender<GatewayEvent>>,
+    /// Sender held by each interaction task, used to track their completion.
+    interactions: mpsc::Sender<()>,
+}
+
+impl EventQueue {
+    /// Initialize a new [`EventQueue`] and spawn its workers.
+    pub fn new(workers: usize, state: ClusterState) -> Self {
+        let (workers, handles) = (0..workers.max(1))
+            .map(|id| {
+                let (tx, rx) = mpsc::channel(WORKER_CAPACITY);
+                let handle = tokio::spawn(worker(id, rx, state.clone()));
+
+                (tx, handle)
+            })
+            .unzip();
+        let (interactions, interactions_rx) = mpsc::channel(1);
+
+        Self {
+            senders: RwLock::new(Some(QueueSenders {
+                workers,
+                interactions,
+            })),
+            handles: Mutex::new(handles),
+            interactions: Mutex::new(Some(interactions_rx)),
+            state,
+        }
+    }
+
+    /// Push an event to the queue.
+    ///
+    /// This waits until the queue of the worker processing the event has free
+    /// capacity.
+    pub async fn push(&self, shard_id: u64, event: GatewayEvent) {
+        let (index, worker) = {
+            let senders = self.senders.read().expect("event queue poisoned");
+            let senders = match &*senders {
+                Some(senders) => senders,
+                None => {
+                    error!("event queue closed, event dropped");
+                    return;
+                }
+            };
+
+            if let GatewayEvent::InteractionCreate(_) = event {
+                let guard = senders.interactions.clone();
+                let state = self.state.clone();
+
+                tokio::spawn(async move {
+                    event.process(state).await;
+                    drop(guard);
+                });
+
+                return;
+            }
+
+            let index = worker_index(event.guild_id(), shard_id, senders.workers.len());
+            (index, senders.workers[index].clone())
+        };
+
+        if worker.send(event).await.is_err() {
+            error!(worker = index, "event worker stopped, event dropped");
+        }
+    }
+
+    /// Number of events waiting to be processed.
+    pub fn depth(&self) -> usize {
+        match &*self.senders.read().expect("event queue poisoned") {
+            Some(senders) => senders
+                .workers
+                .iter()
+                .map(|worker| WORKER_CAPACITY - worker.capacity())
+                .sum(),
+            None => 0,
+        }
+    }
+
+    /// Close the queue and wait until the queued events and the running
+    /// interactions are processed.
+    ///
+    /// Events pushed after the queue is closed are dropped.
+    pub async fn close(&self) {
+        self.senders.write().expect("event queue poisoned").take();
+
+        let handles = mem::take(&mut *self.handles.lock().expect("event queue poisoned"));
+        for handle in handles {
+            handle.await.ok();
+        }
+
+        let interactions = self
+            .interactions
+            .lock()
+            .expect("event queue poisoned")
+            .take();
+        if let Some(mut interactions) = interactions {
+            interactions.recv().await;
+        }
+    }
+}
+
+/// Process the events of a worker queue.
+///
+/// Each event is processed in its own task so that a panic does not stop the
+/// worker, but the worker waits for it to complete before processing the next
+/// event.
+async fn worker(id: usize, mut rx: mpsc::Receiver<GatewayEvent>, state: ClusterState) {
+    while let Some(event) = rx.recv().await {
+        let span = info_span!("handle_event", worker = id);
+        trace!(parent: &span, event = ?event, "received event");
+
+        let task = tokio::spawn(event.process(state.clone()).instrument(span));
+
+        if let Err(error) = task.await {
+            error!(error = ?error, worker = id, "event processing panicked");
+        }
+    }
+}
+
+/// Get the index of the worker processing an event.
+///
+/// Events of a guild are always processed by the same worker. Events without
+/// guild are distributed by shard.
+fn worker_index(guild_id: Option<Id<GuildMarker>>, shard_id: u64, workers: usize) -> usize {
+    let key = match guild_id {
+        Some(guild_id) => guild_id.get() >> 22,
+        None => shard_id,
+    };
+
+    (key % workers as u64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_worker_index() {
+        let guild = Id::new(197038439483310086);
+
+        assert_eq!(
+            worker_index(Some(guild), 0, 8),
+            worker_index(Some(guild), 3, 8)
+        );
+        assert_eq!(worker_index(None, 3, 8), 3);
+        assert_eq!(worker_index(None, 9, 8), 1);
+        assert_eq!(worker_index(Some(guild), 0, 1), 0);
+    }
+}
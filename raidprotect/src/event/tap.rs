@@ -0,0 +1,245 @@
+//! Raw gateway events tap.
+//!
+//! The bot owners can record the raw payloads received from the gateway with
+//! the `/debug tap` command, to debug cache desync issues in production. The
+//! tap settings are stored in the cache (see [`EventTap`]) so that all the bot
+//! processes record the events of their shards.
+//!
+//! Payloads are written to a Redis stream, which can be read with the `XRANGE`
+//! command and used to replay the events locally. The stream is trimmed to the
+//! last [`STREAM_MAX_LEN`] entries and expires one day after the last recorded
+//! event.
+//!
+//! Receiving the raw payloads has a cost even when no tap is running, so the
+//! tap must be enabled in the configuration (see [`BotConfig::event_tap`]).
+//!
+//! [`BotConfig::event_tap`]: raidprotect_model::config::BotConfig::event_tap
+
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use raidprotect_model::cache::{model::gateway::EventTap, CacheClient};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tracing::{error, info, trace};
+use twilight_model::id::{marker::GuildMarker, Id};
+
+/// Maximum number of entries kept in the stream.
+pub const STREAM_MAX_LEN: usize = 10_000;
+
+/// Delay after which the stream expires (in seconds).
+const STREAM_EXPIRES_AFTER: usize = 24 * 60 * 60;
+
+/// Interval between reloads of the tap settings.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Maximum number of payloads waiting to be written.
+///
+/// Payloads are dropped when the queue is full, so that a slow Redis server
+/// does not delay the processing of events.
+const QUEUE_CAPACITY: usize = 1_024;
+
+/// Payload recorded by the tap.
+#[derive(Debug)]
+struct TapEntry {
+    shard_id: u64,
+    payload: Vec<u8>,
+}
+
+/// Recorder of the raw gateway payloads.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug)]
+pub struct EventTapRecorder {
+    tap: Arc<RwLock<Option<EventTap>>>,
+    sender: mpsc::Sender<TapEntry>,
+}
+
+impl EventTapRecorder {
+    /// Initialize a new [`EventTapRecorder`] and spawn its background tasks.
+    pub fn new(cache: CacheClient) -> Self {
+        let tap = Arc::new(RwLock::new(None));
+        let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+
+        tokio::spawn(refresh_task(cache.clone(), tap.clone()));
+        tokio::spawn(writer_task(cache, receiver));
+
+        Self { tap, sender }
+    }
+
+    /// Record a raw payload if it matches the running tap.
+    pub fn record(&self, shard_id: u64, payload: Vec<u8>) {
+        {
+            let tap = self.tap.read().expect("event tap poisoned");
+            let tap = match &*tap {
+                Some(tap) if !tap.expired() => tap,
+                _ => return,
+            };
+
+            let matches = match serde_json::from_slice::<RawPayload>(&payload) {
+                Ok(raw) => raw.matches(tap),
+                Err(_) => false,
+            };
+
+            if !matches {
+                return;
+            }
+        }
+
+        if self
+            .sender
+            .try_send(TapEntry { shard_id, payload })
+            .is_err()
+        {
+            trace!(shard = shard_id, "event tap queue full, payload dropped");
+        }
+    }
+}
+
+/// Periodically reload the tap settings from the cache.
+async fn refresh_task(cache: CacheClient, tap: Arc<RwLock<Option<EventTap>>>) {
+    let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let current = match cache.get::<EventTap>(&()).await {
+            Ok(current) => current.filter(|current| !current.expired()),
+            Err(error) => {
+                error!(error = ?error, "failed to load event tap");
+                continue;
+            }
+        };
+
+        let mut tap = tap.write().expect("event tap poisoned");
+        if *tap != current {
+            match &current {
+                Some(current) => info!(
+                    guild = ?current.guild_id,
+                    event_types = ?current.event_types,
+                    "started recording gateway events"
+                ),
+                None => info!("stopped recording gateway events"),
+            }
+
+            *tap = current;
+        }
+    }
+}
+
+/// Write the recorded payloads to the stream.
+async fn writer_task(cache: CacheClient, mut receiver: mpsc::Receiver<TapEntry>) {
+    while let Some(entry) = receiver.recv().await {
+        let mut entries = vec![entry];
+        while let Ok(entry) = receiver.try_recv() {
+            entries.push(entry);
+        }
+
+        let entries = entries
+            .into_iter()
+            .map(|entry| {
+                vec![
+                    ("shard", entry.shard_id.to_string().into_bytes()),
+                    ("payload", entry.payload),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        if let Err(error) = cache
+            .append_stream(
+                EventTap::STREAM_KEY,
+                &entries,
+                STREAM_MAX_LEN,
+                STREAM_EXPIRES_AFTER,
+            )
+            .await
+        {
+            error!(error = ?error, "failed to write tapped events");
+        }
+    }
+}
+
+/// Raw gateway payload.
+///
+/// Only the fields used to filter the events are deserialized.
+#[derive(Debug, Deserialize)]
+struct RawPayload<'a> {
+    /// Opcode of the payload.
+    op: u8,
+    /// Type of the event, for dispatch payloads.
+    #[serde(borrow)]
+    t: Option<&'a str>,
+    /// Data of the event.
+    d: Option<RawData>,
+}
+
+/// Data of a raw gateway payload.
+#[derive(Debug, Deserialize)]
+struct RawData {
+    guild_id: Option<Id<GuildMarker>>,
+    id: Option<Id<GuildMarker>>,
+}
+
+impl RawPayload<'_> {
+    /// Whether the payload matches the filters of a tap.
+    ///
+    /// Only dispatch payloads are recorded.
+    fn matches(&self, tap: &EventTap) -> bool {
+        let event_type = match (self.op, self.t) {
+            (0, Some(event_type)) => event_type,
+            _ => return false,
+        };
+
+        tap.matches(event_type, self.guild_id())
+    }
+
+    /// Id of the guild of the event.
+    ///
+    /// Guild events contain the guild id in the `id` field.
+    fn guild_id(&self) -> Option<Id<GuildMarker>> {
+        let data = self.d.as_ref()?;
+
+        match self.t {
+            Some("GUILD_CREATE" | "GUILD_UPDATE" | "GUILD_DELETE") => data.id,
+            _ => data.guild_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Duration, OffsetDateTime};
+
+    use super::*;
+
+    fn tap(guild_id: Option<u64>, event_types: &[&str]) -> EventTap {
+        EventTap {
+            guild_id: guild_id.map(Id::new),
+            event_types: event_types.iter().map(|kind| kind.to_string()).collect(),
+            started_by: Id::new(1),
+            expires_at: OffsetDateTime::now_utc() + Duration::minutes(5),
+        }
+    }
+
+    fn matches(payload: &str, tap: &EventTap) -> bool {
+        serde_json::from_str::<RawPayload>(payload)
+            .unwrap()
+            .matches(tap)
+    }
+
+    #[test]
+    fn test_raw_payload_matches() {
+        let message = r#"{"op":0,"s":42,"t":"MESSAGE_CREATE","d":{"id":"10","guild_id":"2","content":"hello"}}"#;
+        let guild = r#"{"op":0,"s":1,"t":"GUILD_CREATE","d":{"id":"2","name":"guild"}}"#;
+        let heartbeat = r#"{"op":11,"d":null}"#;
+
+        assert!(matches(message, &tap(None, &[])));
+        assert!(matches(message, &tap(Some(2), &["MESSAGE_CREATE"])));
+        assert!(!matches(message, &tap(Some(10), &[])));
+        assert!(!matches(message, &tap(None, &["GUILD_CREATE"])));
+        assert!(matches(guild, &tap(Some(2), &[])));
+        assert!(!matches(heartbeat, &tap(None, &[])));
+    }
+}
@@ -0,0 +1,444 @@
+//! Anti-nuke protection.
+//!
+//! Compromised moderator accounts (or rogue moderators) can destroy a server
+//! in a few seconds by deleting its channels and roles or banning its members.
+//! If enabled with `/config antinuke`, destructive actions are counted per
+//! member, using the guild audit log to find who performed them:
+//!
+//! - channel and role deletions
+//! - member bans
+//! - webhook creations
+//!
+//! Members that perform more than the configured number of actions in the
+//! configured interval have their roles removed, and an alert is sent in the
+//! logs channel and to the guild owner. Actions of the bot, of the guild owner
+//! and of trusted members are not counted.
+//!
+//! Roles that cannot be removed by the bot (managed roles and roles higher
+//! than the bot's highest role) are kept, the alert mentions it.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context};
+use once_cell::sync::Lazy;
+use raidprotect_model::{
+    cache::discord::{permission::RoleOrdering, CachedGuild},
+    database::model::{GuildConfig, LogCategory},
+};
+use time::OffsetDateTime;
+use tracing::{error, info};
+use twilight_http::request::AuditLogReason;
+use twilight_mention::Mention;
+use twilight_model::{
+    channel::embed::Embed,
+    guild::{audit_log::AuditLogEventType, Permissions},
+    id::{
+        marker::{
+            AuditLogEntryMarker, ChannelMarker, GenericMarker, GuildMarker, RoleMarker, UserMarker,
+        },
+        Id,
+    },
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+use super::audit_log::{is_recent, recent_entries};
+use crate::{
+    cluster::ClusterState,
+    interaction::{embed::COLOR_RED, util::GuildConfigExt},
+    translations::Lang,
+    util::{format_duration, guild_logs_channel},
+};
+
+/// Number of tracked members after which inactive members are removed.
+const MAX_TRACKED_MEMBERS: usize = 256;
+
+/// Destructive actions tracked by the anti-nuke.
+static TRACKER: Lazy<ActionTracker> = Lazy::new(ActionTracker::default);
+
+/// Destructive action counted by the anti-nuke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NukeAction {
+    ChannelDelete,
+    RoleDelete,
+    Ban,
+    WebhookCreate,
+}
+
+impl NukeAction {
+    /// Type of the audit log entries of the action.
+    fn audit_log_type(self) -> AuditLogEventType {
+        match self {
+            NukeAction::ChannelDelete => AuditLogEventType::ChannelDelete,
+            NukeAction::RoleDelete => AuditLogEventType::RoleDelete,
+            NukeAction::Ban => AuditLogEventType::MemberBanAdd,
+            NukeAction::WebhookCreate => AuditLogEventType::WebhookCreate,
+        }
+    }
+
+    /// Name of the action.
+    fn name(self, lang: Lang) -> &'static str {
+        match self {
+            NukeAction::ChannelDelete => lang.antinuke_action_channel_delete(),
+            NukeAction::RoleDelete => lang.antinuke_action_role_delete(),
+            NukeAction::Ban => lang.antinuke_action_ban(),
+            NukeAction::WebhookCreate => lang.antinuke_action_webhook_create(),
+        }
+    }
+}
+
+/// Count a deleted channel.
+pub async fn channel_deleted(
+    channel_id: Id<ChannelMarker>,
+    guild_id: Id<GuildMarker>,
+    state: &ClusterState,
+) {
+    check(
+        guild_id,
+        NukeAction::ChannelDelete,
+        Some(channel_id.cast()),
+        state,
+    )
+    .await;
+}
+
+/// Count a deleted role.
+pub async fn role_deleted(
+    role_id: Id<RoleMarker>,
+    guild_id: Id<GuildMarker>,
+    state: &ClusterState,
+) {
+    check(
+        guild_id,
+        NukeAction::RoleDelete,
+        Some(role_id.cast()),
+        state,
+    )
+    .await;
+}
+
+/// Count a banned member.
+pub async fn ban_added(user_id: Id<UserMarker>, guild_id: Id<GuildMarker>, state: &ClusterState) {
+    check(guild_id, NukeAction::Ban, Some(user_id.cast()), state).await;
+}
+
+/// Count the webhooks created in a guild.
+///
+/// The gateway event does not include the updated webhook, so all the recent
+/// webhook creations are counted. Entries already counted are ignored.
+pub async fn webhooks_updated(guild_id: Id<GuildMarker>, state: &ClusterState) {
+    check(guild_id, NukeAction::WebhookCreate, None, state).await;
+}
+
+async fn check(
+    guild_id: Id<GuildMarker>,
+    action: NukeAction,
+    target: Option<Id<GenericMarker>>,
+    state: &ClusterState,
+) {
+    if let Err(error) = check_inner(guild_id, action, target, state).await {
+        error!(error = ?error, guild = ?guild_id, action = ?action, "error while checking destructive action");
+    }
+}
+
+async fn check_inner(
+    guild_id: Id<GuildMarker>,
+    action: NukeAction,
+    target: Option<Id<GenericMarker>>,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let config = state.guild_config(guild_id).await?;
+    if !config.antinuke.enabled {
+        return Ok(());
+    }
+
+    let audit_log = match recent_entries(guild_id, action.audit_log_type(), state).await? {
+        Some(audit_log) => audit_log,
+        None => return Ok(()),
+    };
+
+    let owner_id = state
+        .cache
+        .get::<CachedGuild>(&guild_id)
+        .await?
+        .map(|guild| guild.owner_id);
+
+    let now = OffsetDateTime::now_utc();
+    let interval = Duration::from_secs(config.antinuke.interval.into());
+    let entries = audit_log
+        .entries
+        .iter()
+        .filter(|entry| is_recent(entry, now) && (target.is_none() || entry.target_id == target));
+
+    for entry in entries {
+        let actor = match entry.user_id {
+            Some(actor) => actor,
+            None => continue,
+        };
+
+        if actor == state.current_user.cast()
+            || Some(actor) == owner_id
+            || config.antinuke.trusted_users.contains(&actor)
+        {
+            continue;
+        }
+
+        let max_actions = config.antinuke.max_actions.into();
+        if let Some(count) = TRACKER.record(
+            guild_id,
+            actor,
+            entry.id,
+            Instant::now(),
+            interval,
+            max_actions,
+        ) {
+            info!(guild = ?guild_id, user = ?actor, count = count, "nuke attempt detected");
+
+            neutralize(guild_id, actor, action, count, &config, state).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove the roles of a member and send an alert.
+async fn neutralize(
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    action: NukeAction,
+    count: usize,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let lang = config.lang();
+
+    let result = match strip_roles(guild_id, user_id, lang, state).await {
+        Ok(true) => lang.antinuke_roles_removed(),
+        Ok(false) => lang.antinuke_roles_kept(),
+        Err(error) => {
+            error!(error = ?error, guild = ?guild_id, user = ?user_id, "failed to remove roles");
+            lang.antinuke_roles_failed()
+        }
+    };
+
+    let embed = EmbedBuilder::new()
+        .title(lang.antinuke_alert_title())
+        .color(COLOR_RED)
+        .description(lang.antinuke_alert_description(
+            action.name(lang),
+            count,
+            format_duration(
+                time::Duration::seconds(config.antinuke.interval.into()),
+                lang,
+            ),
+            user_id.mention(),
+        ))
+        .field(EmbedFieldBuilder::new(lang.antinuke_result(), result))
+        .build();
+
+    let owner_alert = embed.clone();
+    let channel = guild_logs_channel(state, config, LogCategory::Moderation).await?;
+    state
+        .cache_http(guild_id)
+        .create_message(channel)
+        .await?
+        .embeds(&[embed])?
+        .exec()
+        .await?;
+
+    if let Err(error) = alert_owner(guild_id, owner_alert, state).await {
+        error!(error = ?error, guild = ?guild_id, "failed to alert guild owner");
+    }
+
+    Ok(())
+}
+
+/// Remove the roles of a member.
+///
+/// Returns `false` if some roles cannot be removed by the bot.
+async fn strip_roles(
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    lang: Lang,
+    state: &ClusterState,
+) -> Result<bool, anyhow::Error> {
+    let permissions = state
+        .cache
+        .permissions(guild_id)
+        .await?
+        .current_member()
+        .await?;
+
+    if !permissions.guild().contains(Permissions::MANAGE_ROLES) {
+        bail!("missing permission to manage roles");
+    }
+
+    let member = state
+        .cache_http(guild_id)
+        .member(user_id)
+        .await?
+        .context("member not found")?;
+
+    let highest_role = permissions.highest_role();
+    let guild_roles = state.cache.guild_roles(guild_id).await?;
+
+    let kept: Vec<_> = member
+        .roles
+        .into_iter()
+        .filter(|role_id| {
+            !guild_roles.iter().any(|role| {
+                role.id == *role_id && !role.managed && RoleOrdering::from(role) < highest_role
+            })
+        })
+        .collect();
+
+    state
+        .http
+        .update_guild_member(guild_id, user_id)
+        .roles(&kept)
+        .reason(lang.antinuke_reason())?
+        .exec()
+        .await?;
+
+    Ok(kept.is_empty())
+}
+
+/// Send an alert to the guild owner in direct messages.
+async fn alert_owner(
+    guild_id: Id<GuildMarker>,
+    embed: Embed,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let guild = state
+        .cache
+        .get::<CachedGuild>(&guild_id)
+        .await?
+        .context("guild not found in cache")?;
+
+    let channel = state
+        .http
+        .create_private_channel(guild.owner_id)
+        .exec()
+        .await?
+        .model()
+        .await?;
+
+    state
+        .http
+        .create_message(channel.id)
+        .embeds(&[embed])?
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+/// Action recorded by the [`ActionTracker`].
+#[derive(Debug, Clone, Copy)]
+struct TrackedAction {
+    /// Id of the audit log entry of the action.
+    entry_id: Id<AuditLogEntryMarker>,
+    /// Time the action was recorded.
+    at: Instant,
+    /// Whether the member has already been neutralized for this action.
+    neutralized: bool,
+}
+
+/// Actions performed by each member.
+type ActionsMap = HashMap<(Id<GuildMarker>, Id<UserMarker>), VecDeque<TrackedAction>>;
+
+/// Destructive actions performed by members.
+///
+/// Actions are identified by the id of their audit log entry, so that an
+/// action is only counted once. Once a member is neutralized, its actions are
+/// kept until they expire but are no longer counted, so that the audit log
+/// entries still returned by the next checks do not trigger it again.
+#[derive(Debug, Default)]
+struct ActionTracker {
+    actions: Mutex<ActionsMap>,
+}
+
+impl ActionTracker {
+    /// Record an action of a member.
+    ///
+    /// If the member performed more than `max_actions` actions in the
+    /// interval, the actions are marked as neutralized and their number is
+    /// returned.
+    fn record(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        entry_id: Id<AuditLogEntryMarker>,
+        now: Instant,
+        interval: Duration,
+        max_actions: usize,
+    ) -> Option<usize> {
+        let mut actions = self.actions.lock().expect("action tracker poisoned");
+        let expired = |action: &TrackedAction| now.saturating_duration_since(action.at) > interval;
+
+        if actions.len() > MAX_TRACKED_MEMBERS {
+            actions.retain(|_, member| member.back().is_some_and(|action| !expired(action)));
+        }
+
+        let member = actions.entry((guild_id, user_id)).or_default();
+        while member.front().is_some_and(expired) {
+            member.pop_front();
+        }
+
+        if !member.iter().any(|action| action.entry_id == entry_id) {
+            member.push_back(TrackedAction {
+                entry_id,
+                at: now,
+                neutralized: false,
+            });
+        }
+
+        let count = member.iter().filter(|action| !action.neutralized).count();
+        if count <= max_actions {
+            return None;
+        }
+
+        for action in member.iter_mut() {
+            action.neutralized = true;
+        }
+
+        Some(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_tracker() {
+        let tracker = ActionTracker::default();
+        let (guild, user) = (Id::new(1), Id::new(2));
+        let now = Instant::now();
+        let interval = Duration::from_secs(60);
+
+        let record = |user, entry, at| tracker.record(guild, user, Id::new(entry), at, interval, 2);
+
+        assert_eq!(record(user, 10, now), None);
+        assert_eq!(record(user, 11, now), None);
+        // The same entry is only counted once.
+        assert_eq!(record(user, 11, now), None);
+        // Other members are counted separately.
+        assert_eq!(record(Id::new(3), 12, now), None);
+        assert_eq!(record(user, 12, now), Some(3));
+
+        // Neutralized actions are not counted again.
+        assert_eq!(record(user, 12, now), None);
+        assert_eq!(record(user, 11, now), None);
+        assert_eq!(record(user, 13, now), None);
+
+        // Expired actions are not counted.
+        let later = now + Duration::from_secs(61);
+        assert_eq!(record(user, 14, later), None);
+        assert_eq!(record(user, 15, later), None);
+        assert_eq!(record(user, 16, later), Some(3));
+    }
+}
@@ -0,0 +1,720 @@
+//! Anti-spam feature.
+//!
+//! The spam detection itself is implemented in the `raidprotect-anti-spam`
+//! crate. This module checks incoming messages against the guild
+//! configuration and applies the configured punishment.
+//!
+//! Messages with too many mentions are always punished with at least a
+//! timeout, as mass mentions are disruptive even when sent once. Actions
+//! taken against members are recorded in the moderation logs.
+//!
+//! When similar messages are sent by many accounts, a raid alert is sent in
+//! the logs channel with a button to ban all the accounts, and the emergency
+//! slowmode is enabled in the affected channels.
+//!
+//! Each action is explained in the logs channel with the detectors that fired
+//! and the applied punishment, along with the recent cached messages of the
+//! member as evidence. Moderators can report the decision as a false
+//! positive, which reverts the punishment and records the report to tune the
+//! detector thresholds.
+//!
+//! Ghost pings and mass role pings are detected by the [`mention`] module.
+//!
+//! Member joins are also checked to detect join raids. When a join raid is
+//! detected, the captcha strict mode is enabled and the moderators are
+//! notified in the logs channel, with the invites used by the raid accounts
+//! (see [`join_source`]).
+
+use std::time::Duration as StdDuration;
+
+use anyhow::Context;
+use raidprotect_anti_spam::{
+    engine::{SpamConfig, SpamMember, SpamMessage},
+    language::Language,
+    raid::{RaidAlert, RaidKind},
+    SpamReport, SpamVerdict,
+};
+use raidprotect_model::{
+    cache::{
+        discord::http::CacheHttp,
+        model::{
+            interaction::{PendingRaidAlert, PendingSpamDecision},
+            live::{LiveEvent, RaidAlertKind},
+            message::CachedMessage,
+        },
+        search::MessageQuery,
+    },
+    database::model::{
+        AntiSpamConfig, AntiSpamPunishment, GuildConfig, GuildStat, LogCategory, Modlog,
+        ModlogAutomation, ModlogType, ModlogUser,
+    },
+};
+use time::OffsetDateTime;
+use tracing::{error, info};
+use twilight_http::request::AuditLogReason;
+use twilight_mention::Mention;
+use twilight_model::{
+    application::component::{button::ButtonStyle, ActionRow, Button, Component},
+    guild::Member,
+    id::{
+        marker::{ChannelMarker, GuildMarker, RoleMarker, UserMarker},
+        Id,
+    },
+    util::Timestamp,
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+use crate::{
+    cluster::ClusterState,
+    feature::{
+        captcha, join_source,
+        kill_switch::{automation_disabled, Automation},
+        live, mention, slowmode,
+    },
+    interaction::{
+        embed::COLOR_RED,
+        util::{CustomId, GuildConfigExt},
+    },
+    translations::Lang,
+    util::{guild_logs_channel, TextProcessExt},
+};
+
+/// Maximum number of recent messages attached to anti-spam decision logs.
+const EVIDENCE_MESSAGES: usize = 5;
+
+/// Check an incoming message for spam.
+///
+/// If the message is considered as spam, the configured punishment is applied
+/// to its author. `roles` are the roles of the message author.
+///
+/// Returns whether the message has been deleted.
+pub async fn check_message(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    roles: &[Id<RoleMarker>],
+    state: &ClusterState,
+) -> Result<bool, anyhow::Error> {
+    let config = state.guild_config(guild_id).await?;
+    if !config.antispam.enabled {
+        return Ok(false);
+    }
+    let antispam = &config.antispam;
+
+    if is_exempt(antispam, message.channel_id, roles) {
+        return Ok(false);
+    }
+
+    if !automation_disabled(Automation::Mentions, state).await {
+        if let Err(error) = mention::check_message(message, guild_id, &config, state).await {
+            error!(error = ?error, "failed to check message mentions");
+        }
+    }
+
+    let spam_config = spam_config(antispam);
+    let spam_message = SpamMessage {
+        guild_id,
+        author_id: message.author_id,
+        channel_id: message.channel_id,
+        timestamp: message.timestamp,
+        content: &message.content,
+        mentions: message.mention_users.len()
+            + message.mention_roles.len()
+            + usize::from(message.mention_everyone),
+        default_language: Language::from_code(&config.lang),
+    };
+
+    if let Some(RaidAlert {
+        accounts,
+        kind: RaidKind::Messages { channels, messages },
+    }) = state.antispam.correlate(spam_message, &spam_config)
+    {
+        info!(guild = ?guild_id, accounts = accounts.len(), "raid detected");
+
+        let event = LiveEvent::RaidAlert {
+            kind: RaidAlertKind::Messages,
+            accounts: accounts.len(),
+        };
+        live::publish(guild_id, event, state).await;
+
+        slowmode::enable(guild_id, &channels, &config, state).await;
+
+        if let Err(error) = raid_alert(
+            message, guild_id, accounts, &channels, messages, &config, state,
+        )
+        .await
+        {
+            error!(error = ?error, "failed to send raid alert");
+        }
+    }
+
+    let report = state.antispam.analyze(spam_message, &spam_config);
+    if !report.is_spam() {
+        return Ok(false);
+    }
+
+    info!(guild = ?guild_id, user = ?message.author_id, verdicts = ?report.verdicts, "spam detected");
+
+    let punishment = apply_punishment(
+        message,
+        guild_id,
+        &report.verdict(),
+        antispam,
+        config.lang(),
+        state,
+    )
+    .await;
+
+    // The message has been deleted by `apply_punishment` even if the
+    // punishment failed, so errors are only logged.
+    match punishment {
+        Ok(punishment) => {
+            if let Err(error) =
+                decision_log(message, guild_id, &report, punishment, &config, state).await
+            {
+                error!(error = ?error, "failed to send anti-spam decision log");
+            }
+        }
+        Err(error) => error!(error = ?error, "failed to apply anti-spam punishment"),
+    }
+
+    Ok(true)
+}
+
+/// Explain an anti-spam decision in the logs channel.
+///
+/// The log has a button to report the decision as a false positive (see
+/// [`FalsePositiveButton`]).
+///
+/// [`FalsePositiveButton`]: crate::interaction::component::FalsePositiveButton
+async fn decision_log(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    report: &SpamReport,
+    punishment: AntiSpamPunishment,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let lang = config.lang();
+    let channel = guild_logs_channel(state, config, LogCategory::AntiSpam).await?;
+
+    let detectors = report
+        .verdicts
+        .iter()
+        .map(|verdict| {
+            format!(
+                "`{}` {}",
+                verdict.rule().unwrap_or_default(),
+                verdict_description(verdict, &config.antispam, lang)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let action = match punishment {
+        AntiSpamPunishment::Delete => lang.antispam_decision_delete().to_owned(),
+        AntiSpamPunishment::Timeout => {
+            let until = OffsetDateTime::now_utc().unix_timestamp()
+                + i64::from(config.antispam.punishment_duration);
+            lang.antispam_decision_timeout(format!("<t:{until}:R>"))
+        }
+        AntiSpamPunishment::Kick => lang.antispam_decision_kick().to_owned(),
+        AntiSpamPunishment::Ban => lang.antispam_decision_ban().to_owned(),
+    };
+
+    let mut embed = EmbedBuilder::new()
+        .title(lang.antispam_decision_title())
+        .color(COLOR_RED)
+        .description(lang.antispam_decision_description(
+            message.channel_id.mention(),
+            message.author_id.mention(),
+        ))
+        .field(EmbedFieldBuilder::new(
+            lang.antispam_decision_detectors(),
+            detectors.max_len(1024),
+        ))
+        .field(EmbedFieldBuilder::new(
+            lang.antispam_decision_action(),
+            action,
+        ));
+
+    if !message.content.is_empty() {
+        embed = embed.field(EmbedFieldBuilder::new(
+            lang.antispam_decision_content(),
+            message.content.max_len(1024),
+        ));
+    }
+
+    let query = MessageQuery::author(guild_id, message.author_id).limit(EVIDENCE_MESSAGES + 1);
+    match state.cache.search_messages(&query).await {
+        Ok(recent) => {
+            let recent: Vec<_> = recent
+                .into_iter()
+                .filter(|recent| recent.id != message.id && !recent.content.is_empty())
+                .take(EVIDENCE_MESSAGES)
+                .collect();
+
+            if !recent.is_empty() {
+                embed = embed.field(EmbedFieldBuilder::new(
+                    lang.antispam_decision_recent_messages(),
+                    evidence_messages(&recent),
+                ));
+            }
+        }
+        Err(error) => error!(error = ?error, "failed to search recent messages"),
+    }
+
+    let pending = PendingSpamDecision {
+        message_id: message.id,
+        guild_id,
+        user_id: message.author_id,
+        rules: report
+            .verdicts
+            .iter()
+            .filter_map(SpamVerdict::rule)
+            .map(ToOwned::to_owned)
+            .collect(),
+        punishment,
+    };
+    state.cache.set(&pending).await?;
+
+    let components = Component::ActionRow(ActionRow {
+        components: vec![Component::Button(Button {
+            custom_id: Some(
+                CustomId::new("antispam-false-positive", message.id.to_string()).to_string(),
+            ),
+            disabled: false,
+            emoji: None,
+            label: Some(lang.antispam_false_positive_button().to_owned()),
+            style: ButtonStyle::Secondary,
+            url: None,
+        })],
+    });
+
+    state
+        .cache_http(guild_id)
+        .create_message(channel)
+        .await?
+        .embeds(&[embed.build()])?
+        .components(&[components])?
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+/// Format the recent messages of a member attached as evidence.
+fn evidence_messages(messages: &[CachedMessage]) -> String {
+    messages
+        .iter()
+        .map(|message| {
+            format!(
+                "<t:{}:T> {} {}",
+                message.timestamp.as_secs(),
+                message.channel_id.mention(),
+                message.content.replace('\n', " ").max_len(100)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .max_len(1024)
+}
+
+/// Description of a detector verdict, with the configured limit.
+fn verdict_description(verdict: &SpamVerdict, config: &AntiSpamConfig, lang: Lang) -> String {
+    match verdict {
+        SpamVerdict::Clean => String::new(),
+        SpamVerdict::MessageRate { count, interval } => {
+            lang.antispam_decision_rate(count, interval.as_secs(), config.max_messages)
+        }
+        SpamVerdict::Duplicate { count } => {
+            lang.antispam_decision_duplicates(count, config.max_duplicates)
+        }
+        SpamVerdict::Mentions { count } => {
+            lang.antispam_decision_mentions(count, config.max_mentions)
+        }
+        SpamVerdict::ScamPhrase { language, phrase } => {
+            lang.antispam_decision_phrase(language, phrase)
+        }
+        SpamVerdict::Score { score, .. } => lang.antispam_decision_score(score),
+    }
+}
+
+/// Send a raid alert in the logs channel.
+///
+/// The alert suggests to ban all the accounts that participated in the raid.
+async fn raid_alert(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    accounts: Vec<Id<UserMarker>>,
+    channels: &[Id<ChannelMarker>],
+    messages: usize,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let lang = config.lang();
+    let channel = guild_logs_channel(state, config, LogCategory::AntiSpam).await?;
+
+    let channels = channels
+        .iter()
+        .map(|channel| channel.mention().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let embed = EmbedBuilder::new()
+        .title(lang.antispam_raid_title())
+        .color(COLOR_RED)
+        .description(lang.antispam_raid_description(channels, accounts.len(), messages))
+        .field(EmbedFieldBuilder::new(
+            lang.antispam_raid_accounts(),
+            mention_accounts(&accounts),
+        ))
+        .field(EmbedFieldBuilder::new(
+            lang.antispam_raid_suggestion(),
+            lang.antispam_raid_suggestion_description(),
+        ))
+        .build();
+
+    let pending = PendingRaidAlert {
+        message_id: message.id,
+        guild_id,
+        accounts,
+    };
+    state.cache.set(&pending).await?;
+
+    let components = Component::ActionRow(ActionRow {
+        components: vec![Component::Button(Button {
+            custom_id: Some(CustomId::new("raid-ban", message.id.to_string()).to_string()),
+            disabled: false,
+            emoji: None,
+            label: Some(lang.antispam_raid_ban_button(pending.accounts.len())),
+            style: ButtonStyle::Danger,
+            url: None,
+        })],
+    });
+
+    state
+        .cache_http(guild_id)
+        .create_message(channel)
+        .await?
+        .embeds(&[embed])?
+        .components(&[components])?
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+/// Check a new member for join raids.
+///
+/// When a join raid is detected, the captcha strict mode is enabled for
+/// [`captcha::STRICT_MODE_DURATION`] if the captcha is enabled, and an alert
+/// is sent in the logs channel.
+pub async fn check_join(member: &Member, state: &ClusterState) {
+    if let Err(error) = check_join_inner(member, state).await {
+        error!(error = ?error, member = ?member, "error while checking member join");
+    }
+}
+
+async fn check_join_inner(member: &Member, state: &ClusterState) -> Result<(), anyhow::Error> {
+    if member.user.bot {
+        return Ok(());
+    }
+
+    let mut config = state.guild_config(member.guild_id).await?;
+    if !config.antispam.enabled {
+        return Ok(());
+    }
+
+    let spam_member = SpamMember {
+        guild_id: member.guild_id,
+        user_id: member.user.id,
+        name: &member.user.name,
+        joined_at: member.joined_at,
+    };
+
+    let alert = match state
+        .antispam
+        .record_join(spam_member, &spam_config(&config.antispam))
+    {
+        Some(alert) => alert,
+        None => return Ok(()),
+    };
+
+    info!(guild = ?member.guild_id, accounts = alert.accounts.len(), "join raid detected");
+
+    let event = LiveEvent::RaidAlert {
+        kind: RaidAlertKind::Joins,
+        accounts: alert.accounts.len(),
+    };
+    live::publish(member.guild_id, event, state).await;
+
+    // Enable the captcha strict mode.
+    if config.captcha.enabled && !config.captcha.is_strict() {
+        config.captcha.strict_until =
+            Some(OffsetDateTime::now_utc() + captcha::STRICT_MODE_DURATION);
+        state.update_guild_config(&config).await?;
+    }
+
+    join_raid_alert(member.guild_id, alert, &config, state).await
+}
+
+/// Send a join raid alert in the logs channel.
+async fn join_raid_alert(
+    guild_id: Id<GuildMarker>,
+    alert: RaidAlert,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let (joins, new_accounts, similar_names) = match alert.kind {
+        RaidKind::Joins {
+            joins,
+            new_accounts,
+            similar_names,
+        } => (joins, new_accounts, similar_names),
+        RaidKind::Messages { .. } => return Ok(()),
+    };
+
+    let lang = config.lang();
+    let channel = guild_logs_channel(state, config, LogCategory::AntiSpam).await?;
+
+    let mut embed = EmbedBuilder::new()
+        .title(lang.antispam_join_raid_title())
+        .color(COLOR_RED)
+        .description(lang.antispam_join_raid_description(joins, new_accounts, similar_names))
+        .field(EmbedFieldBuilder::new(
+            lang.antispam_raid_accounts(),
+            mention_accounts(&alert.accounts),
+        ));
+
+    let invites = join_source::raid_invites(guild_id, &alert.accounts, state).await?;
+    if !invites.is_empty() {
+        let invites = invites
+            .into_iter()
+            .map(|(code, count)| lang.antispam_join_raid_invite_uses(code, count))
+            .collect::<Vec<_>>()
+            .join("\n")
+            .max_len(1024);
+
+        embed = embed.field(EmbedFieldBuilder::new(
+            lang.antispam_join_raid_invites(),
+            invites,
+        ));
+    }
+
+    embed = match config.captcha.strict_until {
+        Some(until) if config.captcha.enabled => embed.field(EmbedFieldBuilder::new(
+            lang.antispam_join_raid_strict(),
+            lang.antispam_join_raid_strict_description(format!("<t:{}:t>", until.unix_timestamp())),
+        )),
+        _ => embed.field(EmbedFieldBuilder::new(
+            lang.antispam_raid_suggestion(),
+            lang.antispam_join_raid_suggestion_description(),
+        )),
+    };
+
+    state
+        .cache_http(guild_id)
+        .create_message(channel)
+        .await?
+        .embeds(&[embed.build()])?
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+/// Initialize the [`SpamConfig`] of a guild.
+pub(super) fn spam_config(antispam: &AntiSpamConfig) -> SpamConfig {
+    let mut config = SpamConfig::new(
+        antispam.max_messages.into(),
+        StdDuration::from_secs(antispam.message_interval.into()),
+        antispam.max_duplicates.into(),
+        antispam.max_mentions.into(),
+    );
+    config.disabled = antispam.disabled_detectors.clone();
+
+    config
+}
+
+/// Mention the accounts of a raid alert.
+fn mention_accounts(accounts: &[Id<UserMarker>]) -> String {
+    accounts
+        .iter()
+        .map(|account| account.mention().to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .max_len(1024)
+}
+
+/// Delete the message and apply the configured punishment to its author.
+///
+/// Returns the applied punishment. Mention spam is punished with a timeout if the configured punishment only
+/// deletes the message.
+async fn apply_punishment(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    verdict: &SpamVerdict,
+    config: &AntiSpamConfig,
+    lang: Lang,
+    state: &ClusterState,
+) -> Result<AntiSpamPunishment, anyhow::Error> {
+    let http = state.cache_http(guild_id);
+    let reason = lang.antispam_reason();
+
+    match delete_message(message, &http, state).await {
+        Ok(()) => {
+            if let Err(error) = state
+                .store
+                .increment_guild_stat(guild_id, GuildStat::SpamBlocked)
+                .await
+            {
+                error!(error = ?error, "failed to increment guild stats");
+            }
+        }
+        Err(error) => error!(error = ?error, "failed to delete spam message"),
+    }
+
+    let punishment = match (config.punishment, verdict) {
+        (AntiSpamPunishment::Delete, SpamVerdict::Mentions { .. }) => AntiSpamPunishment::Timeout,
+        (punishment, _) => punishment,
+    };
+
+    let kind = match punishment {
+        AntiSpamPunishment::Delete => return Ok(punishment),
+        AntiSpamPunishment::Timeout => {
+            let until =
+                OffsetDateTime::now_utc().unix_timestamp() + i64::from(config.punishment_duration);
+            let until = Timestamp::from_secs(until).context("invalid timeout timestamp")?;
+
+            http.update_guild_member(message.author_id)
+                .await?
+                .communication_disabled_until(Some(until))?
+                .reason(reason)?
+                .exec()
+                .await?;
+
+            ModlogType::Timeout
+        }
+        AntiSpamPunishment::Kick => {
+            http.remove_guild_member(message.author_id)
+                .await?
+                .reason(reason)?
+                .exec()
+                .await?;
+
+            ModlogType::Kick
+        }
+        AntiSpamPunishment::Ban => {
+            http.create_ban(message.author_id)
+                .await?
+                .reason(reason)?
+                .exec()
+                .await?;
+
+            ModlogType::Ban
+        }
+    };
+
+    create_modlog(message, guild_id, kind, verdict, reason, state).await?;
+
+    Ok(punishment)
+}
+
+/// Record an anti-spam action in the moderation logs.
+async fn create_modlog(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    kind: ModlogType,
+    verdict: &SpamVerdict,
+    reason: &str,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let details = match verdict {
+        SpamVerdict::Clean => None,
+        SpamVerdict::MessageRate { count, interval } => {
+            Some(format!("{count} messages in {}s", interval.as_secs()))
+        }
+        SpamVerdict::Duplicate { count } => Some(format!("{count} similar messages")),
+        SpamVerdict::Mentions { count } => Some(format!("{count} mentions")),
+        SpamVerdict::ScamPhrase { language, phrase } => {
+            Some(format!("scam phrase \"{phrase}\" ({language})"))
+        }
+        SpamVerdict::Score { score, .. } => Some(format!("score {score}")),
+    };
+
+    let automation = verdict.rule().map(|rule| ModlogAutomation {
+        rule: rule.to_owned(),
+        details,
+    });
+
+    record_modlog(guild_id, message.author_id, kind, reason, automation, state).await
+}
+
+/// Record an automated action in the moderation logs.
+///
+/// The bot is recorded as the moderator.
+pub(super) async fn record_modlog(
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    kind: ModlogType,
+    reason: &str,
+    automation: Option<ModlogAutomation>,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let user = state.http.user(user_id).exec().await?.model().await?;
+    let moderator = state.http.current_user().exec().await?.model().await?;
+
+    let modlog = Modlog {
+        id: None,
+        kind,
+        guild_id,
+        user: ModlogUser::from(&user),
+        moderator: ModlogUser {
+            id: moderator.id,
+            name: moderator.name,
+            discriminator: moderator.discriminator,
+            avatar: moderator.avatar,
+        },
+        date: OffsetDateTime::now_utc(),
+        reason: Some(reason.to_owned()),
+        notes: None,
+        automation,
+    };
+
+    state.store.create_modlog(&modlog).await?;
+    live::sanction(&modlog, state).await;
+
+    Ok(())
+}
+
+/// Whether a message is exempted from the anti-spam and the other
+/// auto-moderation modules.
+///
+/// `roles` are the roles of the message author.
+pub(super) fn is_exempt(
+    config: &AntiSpamConfig,
+    channel_id: Id<ChannelMarker>,
+    roles: &[Id<RoleMarker>],
+) -> bool {
+    config.exempt_channels.contains(&channel_id)
+        || roles.iter().any(|role| config.exempt_roles.contains(role))
+}
+
+/// Delete a message detected by an auto-moderation module.
+///
+/// The message is removed from the cache first, so that its deletion is not
+/// considered as a ghost ping.
+pub(super) async fn delete_message(
+    message: &CachedMessage,
+    http: &CacheHttp<'_>,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    state.cache.delete(message).await?;
+
+    http.delete_message(message.channel_id, message.id)
+        .await?
+        .exec()
+        .await?;
+
+    Ok(())
+}
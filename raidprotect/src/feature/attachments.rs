@@ -0,0 +1,281 @@
+//! Attachment policy.
+//!
+//! The attachment policy deletes messages with attachments that are not
+//! allowed in the guild:
+//!
+//! - **executables**: files detected as executables using their extension or
+//!   their MIME type, if `block_executables` is enabled.
+//! - **too many attachments**: messages with more than `max_attachments`.
+//! - **image-only channels**: messages without images, or with attachments
+//!   that are not images, sent in one of the `image_only_channels`.
+//!
+//! Deleted messages are logged in the logs channel with the violated rule.
+//! Members with one of the `exempt_roles` are ignored.
+
+use raidprotect_model::{
+    cache::model::message::{CachedMessage, MessageLink},
+    database::model::{AttachmentPolicyConfig, GuildConfig, LogCategory},
+};
+use tracing::{error, info};
+use twilight_mention::Mention;
+use twilight_model::{
+    channel::Attachment,
+    id::{
+        marker::{ChannelMarker, GuildMarker, RoleMarker},
+        Id,
+    },
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+use super::antispam::delete_message;
+use crate::{
+    cluster::ClusterState,
+    interaction::{embed::COLOR_RED, util::GuildConfigExt},
+    translations::Lang,
+    util::{guild_logs_channel, TextProcessExt},
+};
+
+/// Extensions of executable files.
+const EXECUTABLE_EXT: [&str; 17] = [
+    "apk", "app", "bat", "cmd", "com", "cpl", "dll", "exe", "hta", "jar", "lnk", "msi", "pif",
+    "ps1", "reg", "scr", "vbs",
+];
+
+/// MIME types of executable files.
+const EXECUTABLE_MIME: [&str; 7] = [
+    "application/java-archive",
+    "application/vnd.android.package-archive",
+    "application/vnd.microsoft.portable-executable",
+    "application/x-dosexec",
+    "application/x-ms-installer",
+    "application/x-msdos-program",
+    "application/x-msdownload",
+];
+
+/// Extensions of image files.
+const IMAGE_EXT: [&str; 6] = ["png", "jpg", "jpeg", "gif", "webp", "avif"];
+
+/// Rule of the attachment policy violated by a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Violation {
+    /// Message with an executable file.
+    Executable { filename: String },
+    /// Message with too many attachments.
+    TooManyAttachments { count: usize },
+    /// Message without images in an image-only channel.
+    NotImage,
+}
+
+impl Violation {
+    /// Description of the violated rule.
+    fn description(&self, config: &AttachmentPolicyConfig, lang: Lang) -> String {
+        match self {
+            Violation::Executable { filename } => {
+                lang.attachments_violation_executable(filename.max_len(100))
+            }
+            Violation::TooManyAttachments { count } => {
+                lang.attachments_violation_too_many(count, config.max_attachments)
+            }
+            Violation::NotImage => lang.attachments_violation_not_image().to_owned(),
+        }
+    }
+}
+
+/// Check an incoming message against the attachment policy.
+///
+/// `roles` are the roles of the message author.
+///
+/// Returns whether the message has been deleted.
+pub async fn check_message(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    roles: &[Id<RoleMarker>],
+    state: &ClusterState,
+) -> Result<bool, anyhow::Error> {
+    let config = state.guild_config(guild_id).await?;
+    if !config.attachments.enabled {
+        return Ok(false);
+    }
+
+    if roles
+        .iter()
+        .any(|role| config.attachments.exempt_roles.contains(role))
+    {
+        return Ok(false);
+    }
+
+    let violation = match check_policy(message, &config.attachments) {
+        Some(violation) => violation,
+        None => return Ok(false),
+    };
+
+    info!(guild = ?guild_id, user = ?message.author_id, violation = ?violation, "attachment policy violated");
+
+    if let Err(error) = delete_message(message, &state.cache_http(guild_id), state).await {
+        error!(error = ?error, "failed to delete attachment message");
+    }
+
+    if let Err(error) = log_message(message, guild_id, &violation, &config, state).await {
+        error!(error = ?error, "failed to log attachment message");
+    }
+
+    Ok(true)
+}
+
+/// Find the rule of the policy violated by a message, if any.
+fn check_policy(message: &CachedMessage, config: &AttachmentPolicyConfig) -> Option<Violation> {
+    let attachments = &message.attachments;
+
+    if config.block_executables {
+        if let Some(attachment) = attachments.iter().find(|a| is_executable(a)) {
+            return Some(Violation::Executable {
+                filename: attachment.filename.clone(),
+            });
+        }
+    }
+
+    if config.max_attachments > 0 && attachments.len() > config.max_attachments.into() {
+        return Some(Violation::TooManyAttachments {
+            count: attachments.len(),
+        });
+    }
+
+    if is_image_only(message.channel_id, config) {
+        let has_image = if attachments.is_empty() {
+            message
+                .links
+                .iter()
+                .any(|link| matches!(link, MessageLink::Media(_)))
+        } else {
+            attachments.iter().all(is_image)
+        };
+
+        if !has_image {
+            return Some(Violation::NotImage);
+        }
+    }
+
+    None
+}
+
+/// Whether a channel only allows images.
+fn is_image_only(channel_id: Id<ChannelMarker>, config: &AttachmentPolicyConfig) -> bool {
+    config.image_only_channels.contains(&channel_id)
+}
+
+/// Whether an attachment is an executable file.
+fn is_executable(attachment: &Attachment) -> bool {
+    let mime = attachment.content_type.as_deref().unwrap_or_default();
+
+    EXECUTABLE_MIME.iter().any(|m| mime.starts_with(m))
+        || has_extension(&attachment.filename, &EXECUTABLE_EXT)
+}
+
+/// Whether an attachment is an image.
+fn is_image(attachment: &Attachment) -> bool {
+    match attachment.content_type.as_deref() {
+        Some(mime) => mime.starts_with("image/"),
+        None => has_extension(&attachment.filename, &IMAGE_EXT),
+    }
+}
+
+/// Whether a filename has one of the given extensions (case-insensitive).
+fn has_extension(filename: &str, extensions: &[&str]) -> bool {
+    match filename.rsplit_once('.') {
+        Some((_, ext)) => extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)),
+        None => false,
+    }
+}
+
+/// Log a deleted message in the logs channel.
+async fn log_message(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    violation: &Violation,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let lang = config.lang();
+    let filenames = message
+        .attachments
+        .iter()
+        .map(|attachment| format!("`{}`", attachment.filename))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut embed = EmbedBuilder::new()
+        .title(lang.attachments_deleted_title())
+        .color(COLOR_RED)
+        .description(lang.attachments_deleted_description(
+            message.channel_id.mention(),
+            message.author_id.mention(),
+        ))
+        .field(EmbedFieldBuilder::new(
+            lang.attachments_deleted_reason(),
+            violation.description(&config.attachments, lang),
+        ));
+
+    if !filenames.is_empty() {
+        embed = embed.field(EmbedFieldBuilder::new(
+            lang.attachments_deleted_files(),
+            filenames.max_len(1024),
+        ));
+    }
+
+    if !message.content.is_empty() {
+        embed = embed.field(EmbedFieldBuilder::new(
+            lang.attachments_deleted_content(),
+            message.content.max_len(1024),
+        ));
+    }
+
+    let channel = guild_logs_channel(state, config, LogCategory::Messages).await?;
+    state
+        .cache_http(guild_id)
+        .create_message(channel)
+        .await?
+        .embeds(&[embed.build()])?
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attachment(filename: &str, content_type: Option<&str>) -> Attachment {
+        Attachment {
+            content_type: content_type.map(ToOwned::to_owned),
+            ephemeral: false,
+            filename: filename.to_owned(),
+            description: None,
+            height: None,
+            id: Id::new(1),
+            proxy_url: String::new(),
+            size: 0,
+            url: String::new(),
+            width: None,
+        }
+    }
+
+    #[test]
+    fn test_is_executable() {
+        assert!(is_executable(&attachment("setup.EXE", None)));
+        assert!(is_executable(&attachment(
+            "file",
+            Some("application/x-msdownload")
+        )));
+        assert!(!is_executable(&attachment("image.png", Some("image/png"))));
+        assert!(!is_executable(&attachment("exe", None)));
+    }
+
+    #[test]
+    fn test_is_image() {
+        assert!(is_image(&attachment("image.png", Some("image/png"))));
+        assert!(is_image(&attachment("image.JPG", None)));
+        assert!(!is_image(&attachment("video.mp4", Some("video/mp4"))));
+        assert!(!is_image(&attachment("image.png", Some("text/plain"))));
+    }
+}
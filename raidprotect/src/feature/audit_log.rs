@@ -0,0 +1,265 @@
+//! Moderation actions performed outside the bot.
+//!
+//! Bans, kicks and timeouts performed by moderators with the Discord client
+//! (or with another bot) are detected from gateway events, and recorded in the
+//! moderation logs so that the history of a user is complete. The guild audit
+//! log is used to find the moderator who performed the action and its reason.
+//!
+//! Kicks cannot be distinguished from members leaving the guild, and updated
+//! members are not always timed out, so the audit log is queried for an entry
+//! matching the event. Entries older than [`MAX_ENTRY_AGE`] are ignored, and
+//! actions performed by the bot are already recorded when they are performed.
+
+use std::time::Duration;
+
+use raidprotect_model::{
+    cache::discord::CachedMember,
+    database::model::{Modlog, ModlogType, ModlogUser},
+};
+use time::OffsetDateTime;
+use tracing::{debug, error};
+use twilight_model::{
+    gateway::payload::incoming::MemberUpdate,
+    guild::{
+        audit_log::{AuditLog, AuditLogChange, AuditLogEntry, AuditLogEventType},
+        Permissions,
+    },
+    id::{marker::GuildMarker, Id},
+    user::User,
+};
+use twilight_util::snowflake::Snowflake;
+
+use crate::cluster::ClusterState;
+
+/// Maximum age of an audit log entry matching an event.
+const MAX_ENTRY_AGE: Duration = Duration::from_secs(30);
+
+/// Number of audit log entries searched for a matching entry.
+const ENTRIES_LIMIT: u16 = 10;
+
+/// Record a ban performed outside the bot.
+pub async fn ban_added(user: &User, guild_id: Id<GuildMarker>, state: &ClusterState) {
+    if let Err(error) = record(user, guild_id, ModlogType::Ban, state).await {
+        error!(error = ?error, guild = ?guild_id, "failed to record external ban");
+    }
+}
+
+/// Record a kick performed outside the bot.
+///
+/// Nothing is recorded if the member left the guild by themselves.
+pub async fn member_removed(user: &User, guild_id: Id<GuildMarker>, state: &ClusterState) {
+    if let Err(error) = record(user, guild_id, ModlogType::Kick, state).await {
+        error!(error = ?error, guild = ?guild_id, "failed to record external kick");
+    }
+}
+
+/// Record a timeout performed outside the bot.
+///
+/// The previous cached member is used to only query the audit log when the
+/// timeout of the member has changed.
+pub async fn member_updated(
+    event: &MemberUpdate,
+    previous: Option<&CachedMember>,
+    state: &ClusterState,
+) {
+    let timeout = match event.communication_disabled_until {
+        Some(timeout) if timeout.as_secs() > OffsetDateTime::now_utc().unix_timestamp() => timeout,
+        _ => return,
+    };
+
+    if previous.is_some_and(|member| member.communication_disabled_until == Some(timeout)) {
+        return;
+    }
+
+    if let Err(error) = record(&event.user, event.guild_id, ModlogType::Timeout, state).await {
+        error!(error = ?error, guild = ?event.guild_id, "failed to record external timeout");
+    }
+}
+
+/// Find the audit log entry of an action and record it in the moderation logs.
+async fn record(
+    user: &User,
+    guild_id: Id<GuildMarker>,
+    kind: ModlogType,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let action_type = match kind {
+        ModlogType::Ban => AuditLogEventType::MemberBanAdd,
+        ModlogType::Kick => AuditLogEventType::MemberKick,
+        ModlogType::Timeout => AuditLogEventType::MemberUpdate,
+        ModlogType::Quarantine => return Ok(()),
+    };
+
+    let audit_log = match recent_entries(guild_id, action_type, state).await? {
+        Some(audit_log) => audit_log,
+        None => return Ok(()),
+    };
+
+    let entry = match find_entry(&audit_log, user, kind, OffsetDateTime::now_utc()) {
+        Some(entry) => entry,
+        None => return Ok(()),
+    };
+
+    // Actions of the bot are recorded when they are performed.
+    if entry.user_id == Some(state.current_user.cast()) {
+        return Ok(());
+    }
+
+    let moderator = match audit_log
+        .users
+        .iter()
+        .find(|moderator| Some(moderator.id) == entry.user_id)
+    {
+        Some(moderator) => moderator,
+        None => {
+            debug!(guild = ?guild_id, entry = ?entry.id, "audit log entry without moderator");
+            return Ok(());
+        }
+    };
+
+    let modlog = Modlog {
+        id: None,
+        kind,
+        guild_id,
+        user: ModlogUser::from(user),
+        moderator: ModlogUser::from(moderator),
+        date: OffsetDateTime::now_utc(),
+        reason: entry.reason.clone(),
+        notes: None,
+        automation: None,
+    };
+
+    state.store.create_modlog(&modlog).await?;
+    super::live::sanction(&modlog, state).await;
+
+    Ok(())
+}
+
+/// Get the latest entries of the guild audit log for an action type.
+///
+/// Returns [`None`] if the bot is not allowed to view the audit log.
+pub(super) async fn recent_entries(
+    guild_id: Id<GuildMarker>,
+    action_type: AuditLogEventType,
+    state: &ClusterState,
+) -> Result<Option<AuditLog>, anyhow::Error> {
+    let permissions = state
+        .cache
+        .permissions(guild_id)
+        .await?
+        .current_member()
+        .await?;
+
+    if !permissions.guild().contains(Permissions::VIEW_AUDIT_LOG) {
+        return Ok(None);
+    }
+
+    let audit_log = state
+        .http
+        .audit_log(guild_id)
+        .action_type(action_type)
+        .limit(ENTRIES_LIMIT)?
+        .exec()
+        .await?
+        .model()
+        .await?;
+
+    Ok(Some(audit_log))
+}
+
+/// Whether an audit log entry is recent enough to match an event.
+pub(super) fn is_recent(entry: &AuditLogEntry, now: OffsetDateTime) -> bool {
+    entry.id.timestamp() >= (now - MAX_ENTRY_AGE).unix_timestamp() * 1000
+}
+
+/// Find the audit log entry of an action targeting a user.
+///
+/// Timeout entries are member updates that changed the timeout of the member.
+fn find_entry<'a>(
+    audit_log: &'a AuditLog,
+    user: &User,
+    kind: ModlogType,
+    now: OffsetDateTime,
+) -> Option<&'a AuditLogEntry> {
+    audit_log.entries.iter().find(|entry| {
+        entry.target_id == Some(user.id.cast())
+            && is_recent(entry, now)
+            && (kind != ModlogType::Timeout
+                || entry.changes.iter().any(|change| {
+                    matches!(
+                        change,
+                        AuditLogChange::CommunicationDisabledUntil { new: Some(_), .. }
+                    )
+                }))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// Build an audit log entry id created at a given date.
+    fn entry_id(date: OffsetDateTime) -> String {
+        let millis = (date.unix_timestamp() * 1000 - 1_420_070_400_000) as u64;
+
+        (millis << 22).to_string()
+    }
+
+    fn user(id: u64) -> User {
+        serde_json::from_value(json!({
+            "id": id.to_string(),
+            "username": "user",
+            "discriminator": "0001",
+            "avatar": null,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_find_entry() {
+        let now = OffsetDateTime::now_utc();
+        let audit_log: AuditLog = serde_json::from_value(json!({
+            "application_commands": [],
+            "audit_log_entries": [
+                {
+                    "action_type": 24,
+                    "id": entry_id(now),
+                    "target_id": "1",
+                    "user_id": "10",
+                    "changes": [{ "key": "nick", "new_value": "nick" }],
+                },
+                {
+                    "action_type": 24,
+                    "id": entry_id(now),
+                    "target_id": "2",
+                    "user_id": "10",
+                    "changes": [{
+                        "key": "communication_disabled_until",
+                        "new_value": "2030-01-01T00:00:00.000000+00:00",
+                    }],
+                },
+                {
+                    "action_type": 20,
+                    "id": entry_id(now - Duration::from_secs(60)),
+                    "target_id": "3",
+                    "user_id": "10",
+                },
+            ],
+            "auto_moderation_rules": [],
+            "guild_scheduled_events": [],
+            "integrations": [],
+            "threads": [],
+            "users": [],
+            "webhooks": [],
+        }))
+        .unwrap();
+
+        // Member updates that do not change the timeout are ignored.
+        assert!(find_entry(&audit_log, &user(1), ModlogType::Timeout, now).is_none());
+        assert!(find_entry(&audit_log, &user(2), ModlogType::Timeout, now).is_some());
+        // Old entries are ignored.
+        assert!(find_entry(&audit_log, &user(3), ModlogType::Kick, now).is_none());
+    }
+}
@@ -0,0 +1,181 @@
+//! Captcha migration.
+//!
+//! When the captcha is enabled on an existing server, the current members do
+//! not have the verified roles. Instead of asking them to complete the
+//! verification, the migration gives the verified roles to all the current
+//! members that do not have the unverified role.
+//!
+//! The progress is displayed in the logs channel and updated periodically.
+
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use raidprotect_model::{
+    cache::model::interaction::PendingCaptchaMigration,
+    database::model::{GuildConfig, LogCategory},
+};
+use tokio::sync::watch;
+use tracing::{error, info};
+use twilight_mention::Mention;
+use twilight_model::{
+    channel::embed::Embed,
+    guild::Permissions,
+    id::{marker::UserMarker, Id},
+};
+use twilight_util::builder::embed::EmbedBuilder;
+
+use super::check_role_permission;
+use crate::{
+    cluster::ClusterState,
+    interaction::{
+        embed::{COLOR_SUCCESS, COLOR_TRANSPARENT},
+        util::GuildConfigExt,
+    },
+    translations::Lang,
+    util::{guild_logs_channel, BatchProgress, RoleBatch},
+};
+
+/// Interval between each update of the progress message.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Give the verified roles to all the current members of a guild.
+///
+/// The [`PendingCaptchaMigration`] state of the guild is removed once the
+/// migration is completed, even if it failed.
+pub async fn migrate_members(config: GuildConfig, moderator: Id<UserMarker>, state: ClusterState) {
+    let guild_id = config.id;
+
+    if let Err(error) = run_migration(&config, moderator, &state).await {
+        error!(error = ?error, guild = ?guild_id, "failed to run captcha migration");
+    }
+
+    let pending = PendingCaptchaMigration { guild_id };
+    if let Err(error) = state.cache.delete(&pending).await {
+        error!(error = ?error, guild = ?guild_id, "failed to remove pending captcha migration");
+    }
+}
+
+async fn run_migration(
+    config: &GuildConfig,
+    moderator: Id<UserMarker>,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let lang = config.lang();
+    let unverified = config
+        .captcha
+        .role
+        .context("missing captcha role in config")?;
+
+    // Ensure the bot has required permissions.
+    let permissions = state
+        .cache
+        .permissions(config.id)
+        .await?
+        .current_member()
+        .await?;
+
+    if !permissions.guild().contains(Permissions::MANAGE_ROLES) {
+        bail!("missing permission to manage roles");
+    }
+
+    let mut roles = Vec::with_capacity(config.captcha.verified_roles.len());
+    for role in &config.captcha.verified_roles {
+        if check_role_permission(&permissions, *role, state).await {
+            roles.push(*role);
+        } else {
+            info!(role = ?role, "missing permission to manage verified role");
+        }
+    }
+
+    if roles.is_empty() {
+        bail!("missing permission to manage verified roles");
+    }
+
+    // Send the progress message.
+    let channel = guild_logs_channel(state, config, LogCategory::Captcha).await?;
+    let message = state
+        .cache_http(config.id)
+        .create_message(channel)
+        .await?
+        .embeds(&[progress_embed(BatchProgress::default(), moderator, lang)])?
+        .exec()
+        .await?
+        .model()
+        .await?;
+
+    info!(guild = ?config.id, "captcha migration started");
+
+    // Run the migration and update the progress periodically.
+    let batch = RoleBatch {
+        guild_id: config.id,
+        roles,
+        exclude: vec![unverified],
+        reason: lang.captcha_migrate_reason().to_owned(),
+    };
+
+    let (sender, mut receiver) = watch::channel(BatchProgress::default());
+    let run = batch.run(state, &sender);
+    tokio::pin!(run);
+
+    let mut interval = tokio::time::interval(PROGRESS_INTERVAL);
+    let result = loop {
+        tokio::select! {
+            result = &mut run => break result,
+            _ = interval.tick() => {
+                if !receiver.has_changed()? {
+                    continue;
+                }
+
+                let progress = *receiver.borrow_and_update();
+                let embed = progress_embed(progress, moderator, lang);
+
+                if let Err(error) = state
+                    .http
+                    .update_message(channel, message.id)
+                    .embeds(Some(&[embed]))?
+                    .exec()
+                    .await
+                {
+                    error!(error = ?error, "failed to update captcha migration progress");
+                }
+            }
+        }
+    };
+
+    let progress = result?;
+    let embed = EmbedBuilder::new()
+        .color(COLOR_SUCCESS)
+        .title(lang.captcha_migrate_completed_title())
+        .description(lang.captcha_migrate_progress_description(
+            progress.failed,
+            moderator.mention(),
+            progress.processed,
+            progress.updated,
+        ))
+        .build();
+
+    state
+        .http
+        .update_message(channel, message.id)
+        .embeds(Some(&[embed]))?
+        .exec()
+        .await?;
+
+    info!(guild = ?config.id, progress = ?progress, "captcha migration completed");
+
+    Ok(())
+}
+
+/// Embed displaying the progress of a running migration.
+fn progress_embed(progress: BatchProgress, moderator: Id<UserMarker>, lang: Lang) -> Embed {
+    EmbedBuilder::new()
+        .color(COLOR_TRANSPARENT)
+        .title(lang.captcha_migrate_progress_title())
+        .description(lang.captcha_migrate_progress_description(
+            progress.failed,
+            moderator.mention(),
+            progress.processed,
+            progress.updated,
+        ))
+        .build()
+}
@@ -0,0 +1,150 @@
+//! Captcha feature.
+
+mod migration;
+mod renderer;
+
+use anyhow::{bail, Context};
+use raidprotect_model::{
+    cache::{
+        discord::{
+            permission::{CachePermissions, RoleOrdering},
+            CachedRole,
+        },
+        model::live::{CaptchaOutcome, LiveEvent},
+    },
+    database::model::{GuildConfig, GuildStat},
+};
+use time::Duration;
+use tracing::{error, info};
+use twilight_model::{
+    guild::Permissions,
+    id::{
+        marker::{RoleMarker, UserMarker},
+        Id,
+    },
+};
+
+pub use self::{migration::migrate_members, renderer::CaptchaRenderer};
+use super::live;
+use crate::cluster::ClusterState;
+
+/// Default length of the generated captcha code.
+pub const DEFAULT_LENGTH: usize = 5;
+
+/// Default duration before the captcha expires.
+pub const DEFAULT_DURATION: Duration = Duration::minutes(5);
+
+/// Duration before the member is kicked for not completing the verification.
+pub const KICK_AFTER: Duration = Duration::seconds(10);
+
+/// Maximum number of regenerations of the captcha code.
+pub const MAX_RETRY: u8 = 2;
+
+/// Length of the captcha code when the strict mode is enabled.
+pub const STRICT_LENGTH: usize = 7;
+
+/// Maximum number of regenerations when the strict mode is enabled.
+///
+/// The first generation of the code is counted, so the code cannot be
+/// regenerated.
+pub const STRICT_MAX_RETRY: u8 = 1;
+
+/// Duration of the strict mode enabled when a join raid is detected.
+pub const STRICT_MODE_DURATION: Duration = Duration::hours(1);
+
+/// Update the roles of a member that completed the verification.
+///
+/// The unverified role is removed and the configured verified roles are added.
+pub async fn update_roles(
+    user_id: Id<UserMarker>,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    // Get the current member roles.
+    let member = state
+        .cache_http(config.id)
+        .member(user_id)
+        .await?
+        .context("member not found")?;
+    let mut roles = member.roles;
+
+    // Ensure the bot has required permissions.
+    let permissions = state
+        .cache
+        .permissions(config.id)
+        .await?
+        .current_member()
+        .await?;
+
+    if !permissions.guild().contains(Permissions::MANAGE_ROLES) {
+        bail!("missing permission to manage roles");
+    }
+
+    // Remove the captcha role.
+    let role = config
+        .captcha
+        .role
+        .context("missing captcha role in config")?;
+
+    if !check_role_permission(&permissions, role, state).await {
+        bail!("missing permission to manage captcha role");
+    }
+
+    if let Some(index) = roles.iter().position(|r| r == &role) {
+        roles.remove(index);
+    }
+
+    // Add the verified roles.
+    for role in &config.captcha.verified_roles {
+        if !check_role_permission(&permissions, *role, state).await {
+            info!("missing permission to manage verified role");
+            continue;
+        }
+
+        if !roles.contains(role) {
+            roles.push(*role);
+        }
+    }
+
+    // Update the member roles.
+    state
+        .http
+        .update_guild_member(config.id, user_id)
+        .roles(&roles)
+        .exec()
+        .await?;
+
+    if let Err(error) = state
+        .store
+        .increment_guild_stat(config.id, GuildStat::MembersVerified)
+        .await
+    {
+        error!(error = ?error, "failed to increment guild stats");
+    }
+
+    let event = LiveEvent::Captcha {
+        user_id,
+        outcome: CaptchaOutcome::Passed,
+    };
+    live::publish(config.id, event, state).await;
+
+    Ok(())
+}
+
+/// Ensure the bot has the required permission to update a role.
+async fn check_role_permission(
+    permissions: &CachePermissions<'_>,
+    role_id: Id<RoleMarker>,
+    state: &ClusterState,
+) -> bool {
+    let role = match state.cache.get::<CachedRole>(&role_id).await {
+        Ok(Some(role)) => role,
+        _ => return false,
+    };
+
+    if RoleOrdering::from(&role) >= permissions.highest_role() {
+        return false;
+    }
+
+    true
+}
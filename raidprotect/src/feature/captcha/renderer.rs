@@ -0,0 +1,204 @@
+//! Captcha image rendering pool.
+//!
+//! Generating a captcha image is CPU-intensive. During a raid, hundreds of
+//! members may start the verification at the same time, which could block the
+//! async runtime if the images were generated without any limit.
+//!
+//! The [`CaptchaRenderer`] limits the number of images rendered at the same
+//! time on the blocking thread pool, and bounds the number of pending
+//! requests. Each guild can only use a few workers at the same time, so a raid
+//! on a single guild does not delay the verifications of other guilds.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use raidprotect_captcha::generate_captcha_png;
+use tokio::sync::Semaphore;
+use tracing::warn;
+use twilight_model::id::{marker::GuildMarker, Id};
+
+/// Maximum number of pending render requests.
+pub const QUEUE_CAPACITY: usize = 256;
+
+/// Maximum number of pending render requests for a single guild.
+pub const GUILD_QUEUE_CAPACITY: usize = 64;
+
+/// Maximum number of images rendered at the same time for a single guild.
+pub const GUILD_WORKERS: usize = 2;
+
+/// Captcha image rendering pool.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug, Clone)]
+pub struct CaptchaRenderer {
+    inner: Arc<RendererInner>,
+}
+
+#[derive(Debug)]
+struct RendererInner {
+    /// Available rendering workers.
+    workers: Semaphore,
+    /// Available slots in the queue.
+    queue: Semaphore,
+    /// Pending requests of each guild.
+    guilds: Mutex<HashMap<Id<GuildMarker>, GuildQueue>>,
+}
+
+/// Pending requests of a guild.
+#[derive(Debug)]
+struct GuildQueue {
+    /// Number of pending requests.
+    pending: usize,
+    /// Available rendering workers for this guild.
+    workers: Arc<Semaphore>,
+}
+
+impl CaptchaRenderer {
+    /// Initialize a new [`CaptchaRenderer`].
+    ///
+    /// The number of workers is the available parallelism of the system.
+    pub fn new() -> Self {
+        let workers = thread::available_parallelism().map_or(1, |n| n.get());
+
+        Self::with_workers(workers)
+    }
+
+    /// Initialize a new [`CaptchaRenderer`] with a given number of workers.
+    pub fn with_workers(workers: usize) -> Self {
+        Self {
+            inner: Arc::new(RendererInner {
+                workers: Semaphore::new(workers),
+                queue: Semaphore::new(QUEUE_CAPACITY),
+                guilds: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// Render a captcha image with the given code.
+    ///
+    /// The image is returned encoded in PNG. If the queue is full, either
+    /// globally or for the guild, [`None`] is returned.
+    pub async fn render(
+        &self,
+        guild_id: Id<GuildMarker>,
+        code: String,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        let _queue_permit = match self.inner.queue.try_acquire() {
+            Ok(permit) => permit,
+            Err(_) => {
+                warn!("captcha render queue is full");
+                return Ok(None);
+            }
+        };
+
+        let guild = match self.enqueue(guild_id) {
+            Some(guild) => guild,
+            None => {
+                warn!(guild = ?guild_id, "captcha render queue is full for guild");
+                return Ok(None);
+            }
+        };
+
+        let _guild_permit = guild.workers.acquire().await?;
+        let _worker_permit = self.inner.workers.acquire().await?;
+
+        let image = tokio::task::spawn_blocking(move || {
+            generate_captcha_png(&code, &mut rand::thread_rng())
+        })
+        .await??;
+
+        Ok(Some(image))
+    }
+
+    /// Add a pending request to the guild queue.
+    fn enqueue(&self, guild_id: Id<GuildMarker>) -> Option<GuildGuard> {
+        let mut guilds = self.inner.guilds.lock().expect("poisoned lock");
+        let queue = guilds.entry(guild_id).or_insert_with(|| GuildQueue {
+            pending: 0,
+            workers: Arc::new(Semaphore::new(GUILD_WORKERS)),
+        });
+
+        if queue.pending >= GUILD_QUEUE_CAPACITY {
+            return None;
+        }
+
+        queue.pending += 1;
+
+        Some(GuildGuard {
+            renderer: self.clone(),
+            guild_id,
+            workers: queue.workers.clone(),
+        })
+    }
+
+    /// Number of pending requests for a guild.
+    #[cfg(test)]
+    fn pending(&self, guild_id: Id<GuildMarker>) -> usize {
+        let guilds = self.inner.guilds.lock().expect("poisoned lock");
+
+        guilds.get(&guild_id).map_or(0, |queue| queue.pending)
+    }
+}
+
+impl Default for CaptchaRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Guard removing a pending request from the guild queue when dropped.
+///
+/// This ensures the queue is updated even if the render future is cancelled.
+struct GuildGuard {
+    renderer: CaptchaRenderer,
+    guild_id: Id<GuildMarker>,
+    workers: Arc<Semaphore>,
+}
+
+impl Drop for GuildGuard {
+    fn drop(&mut self) {
+        let mut guilds = self.renderer.inner.guilds.lock().expect("poisoned lock");
+
+        if let Some(queue) = guilds.get_mut(&self.guild_id) {
+            queue.pending -= 1;
+
+            if queue.pending == 0 {
+                guilds.remove(&self.guild_id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_render() {
+        let renderer = CaptchaRenderer::with_workers(1);
+        let guild_id = Id::new(1);
+
+        let image = renderer.render(guild_id, "abcde".to_owned()).await.unwrap();
+
+        assert!(image.is_some());
+        assert_eq!(renderer.pending(guild_id), 0);
+    }
+
+    #[test]
+    fn test_guild_queue_full() {
+        let renderer = CaptchaRenderer::with_workers(1);
+        let guards = (0..GUILD_QUEUE_CAPACITY)
+            .map(|_| renderer.enqueue(Id::new(1)).unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(renderer.enqueue(Id::new(1)).is_none());
+        assert!(renderer.enqueue(Id::new(2)).is_some());
+
+        drop(guards);
+        assert_eq!(renderer.pending(Id::new(1)), 0);
+        assert!(renderer.enqueue(Id::new(1)).is_some());
+    }
+}
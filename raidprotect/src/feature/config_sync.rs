@@ -0,0 +1,99 @@
+//! Synchronization of guild configurations edited by other services.
+//!
+//! Guild configurations are cached in Redis (see [`ClusterState::guild_config`]).
+//! When a configuration is edited outside of the bot, such as from the web
+//! dashboard, the cached configuration and the compiled word filter of the
+//! guild are dropped so that the change is applied immediately.
+//!
+//! Changes are received with a MongoDB change stream, which requires the
+//! database to run as a replica set. Otherwise, edited configurations are only
+//! applied once the cached configuration expires. Changes made by the bot
+//! itself are also received, which only causes the configuration to be read
+//! again from the database.
+//!
+//! When the stream is opened again, it is resumed after the last received
+//! change so that no change is missed. If it cannot be resumed, all the cached
+//! configurations are dropped instead.
+
+use std::time::Duration;
+
+use raidprotect_model::database::{model::GuildConfig, DbClient, ResumeToken};
+use tracing::{debug, error, warn};
+use twilight_model::id::{marker::GuildMarker, Id};
+
+use crate::cluster::ClusterState;
+
+/// Listen for changes of the guild configurations.
+///
+/// If the change stream is closed, it is opened again after a short delay.
+pub async fn config_sync_listener(state: ClusterState, database: DbClient) {
+    let mut resume_token: Option<ResumeToken> = None;
+
+    loop {
+        match database.watch_guilds(resume_token.clone()).await {
+            Ok(Some(mut changes)) => {
+                while let Some(guild_id) = changes.next().await {
+                    let result = match guild_id {
+                        Ok(guild_id) => invalidate(guild_id, &state).await,
+                        Err(error) => Err(error),
+                    };
+
+                    if let Err(error) = result {
+                        error!(error = ?error, "error while processing guild config change");
+                        break;
+                    }
+                }
+
+                if let Some(token) = changes.resume_token() {
+                    resume_token = Some(token);
+                }
+            }
+            Ok(None) => {
+                warn!(
+                    "change streams are not supported by the database, guild config sync disabled"
+                );
+                return;
+            }
+            Err(error) if resume_token.is_some() => {
+                warn!(error = ?error, "failed to resume guild config changes, dropping cached configs");
+
+                // Changes made since the last received change are lost.
+                match invalidate_all(&state).await {
+                    Ok(()) => resume_token = None,
+                    Err(error) => error!(error = ?error, "failed to drop cached guild configs"),
+                }
+            }
+            Err(error) => error!(error = ?error, "failed to watch guild configs"),
+        }
+
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
+
+/// Drop the cached values computed from the configuration of a guild.
+async fn invalidate(guild_id: Id<GuildMarker>, state: &ClusterState) -> Result<(), anyhow::Error> {
+    if !state.runs_guild(guild_id) {
+        return Ok(());
+    }
+
+    debug!(guild = ?guild_id, "guild config changed");
+
+    state.cache.delete_from::<GuildConfig>(&guild_id).await?;
+    state.filters.remove(guild_id);
+
+    Ok(())
+}
+
+/// Drop the cached values computed from the configuration of all the guilds.
+async fn invalidate_all(state: &ClusterState) -> Result<(), anyhow::Error> {
+    let configs = state
+        .cache
+        .scan::<GuildConfig>(&GuildConfig::key_pattern())
+        .await?;
+
+    for config in configs {
+        invalidate(config.id, state).await?;
+    }
+
+    Ok(())
+}
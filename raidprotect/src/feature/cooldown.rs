@@ -0,0 +1,99 @@
+//! Command cooldowns.
+//!
+//! Expensive commands can only be used once per cooldown period by each user
+//! of a guild. Cooldowns are stored in Redis with a counter that expires at
+//! the end of the period (`cooldown:{guild}:{user}:{command}`), so they are
+//! shared by all the bot processes.
+//!
+//! Cooldowns are checked in the interaction dispatch layer (see the
+//! [`Cooldown`] middleware), which responds with the remaining time instead of
+//! executing the command.
+//!
+//! [`Cooldown`]: crate::interaction::middleware::Cooldown
+
+use twilight_model::id::{
+    marker::{GuildMarker, UserMarker},
+    Id,
+};
+
+use crate::cluster::ClusterState;
+
+/// Cooldown of the commands, in seconds.
+///
+/// Commands that are not listed have no cooldown.
+const COMMAND_COOLDOWNS: &[(&str, usize)] = &[("kick", 3), ("modlogs", 60)];
+
+/// Get the cooldown of a command, in seconds.
+pub fn command_cooldown(name: &str) -> Option<usize> {
+    COMMAND_COOLDOWNS
+        .iter()
+        .find(|(command, _)| *command == name)
+        .map(|(_, cooldown)| *cooldown)
+}
+
+/// Start the cooldown of a command for a user.
+///
+/// Returns the remaining time before the command can be used again (in
+/// seconds) if the cooldown has already started.
+pub async fn start_cooldown(
+    guild_id: Option<Id<GuildMarker>>,
+    user_id: Id<UserMarker>,
+    command: &str,
+    cooldown: usize,
+    state: &ClusterState,
+) -> Result<Option<u64>, anyhow::Error> {
+    let key = cooldown_key(guild_id, user_id, command);
+    let (uses, remaining) = state.cache.increment(&key, cooldown).await?;
+
+    match uses {
+        1 => Ok(None),
+        _ => Ok(Some(remaining.max(1))),
+    }
+}
+
+/// Key of the cooldown of a command.
+///
+/// Commands used in direct messages share the `dm` guild.
+fn cooldown_key(
+    guild_id: Option<Id<GuildMarker>>,
+    user_id: Id<UserMarker>,
+    command: &str,
+) -> String {
+    match guild_id {
+        Some(guild_id) => format!("cooldown:{guild_id}:{user_id}:{command}"),
+        None => format!("cooldown:dm:{user_id}:{command}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_cooldown() {
+        assert_eq!(command_cooldown("modlogs"), Some(60));
+        assert_eq!(command_cooldown("help"), None);
+    }
+
+    #[test]
+    fn test_cooldown_key() {
+        assert_eq!(
+            cooldown_key(Some(Id::new(1)), Id::new(2), "kick"),
+            "cooldown:1:2:kick"
+        );
+        assert_eq!(cooldown_key(None, Id::new(2), "kick"), "cooldown:dm:2:kick");
+    }
+
+    #[tokio::test]
+    async fn test_start_cooldown() {
+        let state = ClusterState::for_tests().await;
+
+        let first = start_cooldown(Some(Id::new(1)), Id::new(2), "kick", 3, &state).await;
+        let second = start_cooldown(Some(Id::new(1)), Id::new(2), "kick", 3, &state).await;
+        let other = start_cooldown(Some(Id::new(1)), Id::new(3), "kick", 3, &state).await;
+
+        assert_eq!(first.unwrap(), None);
+        assert!(matches!(second.unwrap(), Some(1..=3)));
+        assert_eq!(other.unwrap(), None);
+    }
+}
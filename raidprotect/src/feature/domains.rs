@@ -0,0 +1,246 @@
+//! Domain filter.
+//!
+//! Messages containing links to blocked domains are deleted and logged in the
+//! logs channel with the matched domain. Two lists are checked:
+//!
+//! - the domains blocked by the guild, configured with `/config domains`.
+//! - the built-in list of phishing domains, if the `block_phishing` option is
+//!   enabled.
+//!
+//! Domains allowed by the guild are never blocked. Roles and channels exempted
+//! from the anti-spam are also exempted from the domain filter.
+//!
+//! The phishing list can be extended with a file set in the
+//! `phishing_domains_path` option, which is reloaded every
+//! [`RELOAD_INTERVAL`].
+
+use std::{
+    path::{Path, PathBuf},
+    sync::RwLock,
+    time::Duration,
+};
+
+use raidprotect_anti_spam::domains::{find_domain, DomainList};
+use raidprotect_model::{
+    cache::model::message::{CachedMessage, MessageLink},
+    database::model::{GuildConfig, LogCategory},
+};
+use tracing::{debug, error, info};
+use twilight_mention::Mention;
+use twilight_model::id::{
+    marker::{GuildMarker, RoleMarker},
+    Id,
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+use super::antispam::{delete_message, is_exempt};
+use crate::{
+    cluster::ClusterState,
+    interaction::{embed::COLOR_RED, util::GuildConfigExt},
+    util::{guild_logs_channel, TextProcessExt},
+};
+
+/// Interval between each reload of the phishing domains file.
+pub const RELOAD_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// List of phishing domains.
+///
+/// This type holds the built-in list of phishing domains, extended with the
+/// domains of the configured file.
+#[derive(Debug)]
+pub struct PhishingDomains {
+    list: RwLock<DomainList>,
+    path: Option<PathBuf>,
+}
+
+impl PhishingDomains {
+    /// Initialize a new [`PhishingDomains`] with the built-in list.
+    ///
+    /// The domains of the file are loaded by [`phishing_task`].
+    pub fn new(path: Option<String>) -> Self {
+        Self {
+            list: RwLock::new(DomainList::builtin()),
+            path: path.map(PathBuf::from),
+        }
+    }
+
+    /// Find the phishing domain matching a host, if any.
+    pub fn find(&self, host: &str) -> Option<String> {
+        self.list
+            .read()
+            .expect("poisoned lock")
+            .find(host)
+            .map(ToOwned::to_owned)
+    }
+
+    /// Reload the domains of the file.
+    ///
+    /// The list is only replaced if the file has been read successfully.
+    async fn reload(&self, path: &Path) -> Result<usize, anyhow::Error> {
+        let content = tokio::fs::read_to_string(path).await?;
+
+        let mut list = DomainList::builtin();
+        list.extend(DomainList::parse(&content));
+        let len = list.len();
+
+        *self.list.write().expect("poisoned lock") = list;
+
+        Ok(len)
+    }
+}
+
+/// Reload the phishing domains file periodically.
+pub async fn phishing_task(state: ClusterState) {
+    let path = match &state.phishing_domains.path {
+        Some(path) => path.clone(),
+        None => return,
+    };
+
+    let mut interval = tokio::time::interval(RELOAD_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        match state.phishing_domains.reload(&path).await {
+            Ok(len) => debug!(len, "phishing domains reloaded"),
+            Err(error) => error!(error = ?error, "failed to reload phishing domains"),
+        }
+    }
+}
+
+/// List matching a blocked domain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum BlockedList {
+    /// Domains blocked by the guild.
+    Guild,
+    /// Built-in phishing domains.
+    Phishing,
+}
+
+/// Check an incoming message for links to blocked domains.
+///
+/// `roles` are the roles of the message author.
+///
+/// Returns whether the message has been deleted.
+pub async fn check_message(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    roles: &[Id<RoleMarker>],
+    state: &ClusterState,
+) -> Result<bool, anyhow::Error> {
+    let hosts = message
+        .links
+        .iter()
+        .filter_map(|link| match link {
+            MessageLink::Media(url) | MessageLink::Other(url) => url.host_str(),
+            MessageLink::Invite(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+    if hosts.is_empty() {
+        return Ok(false);
+    }
+
+    let config = state.guild_config(guild_id).await?;
+    if !config.domains.enabled {
+        return Ok(false);
+    }
+
+    if is_exempt(&config.antispam, message.channel_id, roles) {
+        return Ok(false);
+    }
+
+    let blocked = hosts
+        .into_iter()
+        .find_map(|host| blocked_domain(host, &config, state));
+
+    let (domain, list) = match blocked {
+        Some(blocked) => blocked,
+        None => return Ok(false),
+    };
+
+    info!(guild = ?guild_id, user = ?message.author_id, domain, list = ?list, "blocked domain detected");
+
+    if let Err(error) = delete_message(message, &state.cache_http(guild_id), state).await {
+        error!(error = ?error, "failed to delete blocked domain message");
+    }
+
+    if let Err(error) = log_message(message, guild_id, &domain, list, &config, state).await {
+        error!(error = ?error, "failed to log blocked domain message");
+    }
+
+    Ok(true)
+}
+
+/// Find the blocked domain matching a host, if any.
+pub(super) fn blocked_domain(
+    host: &str,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Option<(String, BlockedList)> {
+    let domains = &config.domains;
+
+    if find_domain(host, domains.allowed.iter().map(String::as_str)).is_some() {
+        return None;
+    }
+
+    if let Some(domain) = find_domain(host, domains.blocked.iter().map(String::as_str)) {
+        return Some((domain.to_owned(), BlockedList::Guild));
+    }
+
+    if domains.block_phishing {
+        return state
+            .phishing_domains
+            .find(host)
+            .map(|domain| (domain, BlockedList::Phishing));
+    }
+
+    None
+}
+
+/// Log a deleted message in the logs channel.
+async fn log_message(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    domain: &str,
+    list: BlockedList,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let lang = config.lang();
+
+    let list = match list {
+        BlockedList::Guild => lang.domains_list_guild(),
+        BlockedList::Phishing => lang.domains_list_phishing(),
+    };
+
+    let embed = EmbedBuilder::new()
+        .title(lang.domains_blocked_title())
+        .color(COLOR_RED)
+        .description(
+            lang.domains_blocked_description(
+                message.channel_id.mention(),
+                message.author_id.mention(),
+            ),
+        )
+        .field(
+            EmbedFieldBuilder::new(lang.domains_blocked_domain(), format!("`{domain}`")).inline(),
+        )
+        .field(EmbedFieldBuilder::new(lang.domains_blocked_list(), list).inline())
+        .field(EmbedFieldBuilder::new(
+            lang.domains_blocked_content(),
+            message.content.max_len(1024),
+        ))
+        .build();
+
+    let channel = guild_logs_channel(state, config, LogCategory::Messages).await?;
+    state
+        .cache_http(guild_id)
+        .create_message(channel)
+        .await?
+        .embeds(&[embed])?
+        .exec()
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,190 @@
+//! Word filter.
+//!
+//! Messages matching one of the rules configured with `/config filter` are
+//! deleted and logged in the logs channel with the matched rule. See the
+//! [`filter`] module of the anti-spam for more information about the rules.
+//!
+//! Compiling the rules is expensive, so the compiled filters are kept in a
+//! [`FilterCache`] and only compiled again when the rules of the guild change.
+//!
+//! [`filter`]: raidprotect_anti_spam::filter
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use raidprotect_anti_spam::filter::{RuleKind, WordFilter};
+use raidprotect_model::{
+    cache::model::message::CachedMessage,
+    database::model::{GuildConfig, LogCategory, WordFilterRule, WordFilterRuleKind},
+};
+use tracing::{error, info};
+use twilight_mention::Mention;
+use twilight_model::id::{
+    marker::{GuildMarker, RoleMarker},
+    Id,
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+use super::antispam::{delete_message, is_exempt};
+use crate::{
+    cluster::ClusterState,
+    interaction::{embed::COLOR_RED, util::GuildConfigExt},
+    util::{guild_logs_channel, TextProcessExt},
+};
+
+/// Cache of the compiled word filters of each guild.
+#[derive(Debug, Default)]
+pub struct FilterCache {
+    filters: Mutex<HashMap<Id<GuildMarker>, CachedFilter>>,
+}
+
+/// Compiled word filter, with the rules it was compiled from.
+#[derive(Debug)]
+struct CachedFilter {
+    rules: Vec<WordFilterRule>,
+    filter: Arc<WordFilter>,
+}
+
+impl FilterCache {
+    /// Initialize a new empty [`FilterCache`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the compiled filter of a guild.
+    ///
+    /// The filter is compiled if missing from the cache, or if its rules are
+    /// outdated.
+    pub fn get(
+        &self,
+        guild_id: Id<GuildMarker>,
+        rules: &[WordFilterRule],
+    ) -> Result<Arc<WordFilter>, anyhow::Error> {
+        let mut filters = self.filters.lock().expect("poisoned lock");
+
+        if let Some(cached) = filters.get(&guild_id) {
+            if cached.rules == rules {
+                return Ok(cached.filter.clone());
+            }
+        }
+
+        let filter = Arc::new(WordFilter::new(
+            rules
+                .iter()
+                .map(|rule| (rule_kind(rule.kind), &*rule.pattern)),
+        )?);
+
+        filters.insert(
+            guild_id,
+            CachedFilter {
+                rules: rules.to_vec(),
+                filter: filter.clone(),
+            },
+        );
+
+        Ok(filter)
+    }
+
+    /// Remove the compiled filter of a guild.
+    pub fn remove(&self, guild_id: Id<GuildMarker>) {
+        self.filters
+            .lock()
+            .expect("poisoned lock")
+            .remove(&guild_id);
+    }
+}
+
+/// Convert a configured rule kind to a [`RuleKind`].
+pub fn rule_kind(kind: WordFilterRuleKind) -> RuleKind {
+    match kind {
+        WordFilterRuleKind::Term => RuleKind::Term,
+        WordFilterRuleKind::Wildcard => RuleKind::Wildcard,
+        WordFilterRuleKind::Regex => RuleKind::Regex,
+    }
+}
+
+/// Check an incoming message with the word filter.
+///
+/// `roles` are the roles of the message author.
+///
+/// Returns whether the message has been deleted.
+pub async fn check_message(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    roles: &[Id<RoleMarker>],
+    state: &ClusterState,
+) -> Result<bool, anyhow::Error> {
+    if message.content.is_empty() {
+        return Ok(false);
+    }
+
+    let config = state.guild_config(guild_id).await?;
+    if config.filter.rules.is_empty() {
+        state.filters.remove(guild_id);
+        return Ok(false);
+    }
+
+    if is_exempt(&config.antispam, message.channel_id, roles) {
+        return Ok(false);
+    }
+
+    let filter = state.filters.get(guild_id, &config.filter.rules)?;
+    let rule = match filter.find(&message.content) {
+        Some(rule) => rule,
+        None => return Ok(false),
+    };
+
+    info!(guild = ?guild_id, user = ?message.author_id, rule, "filtered word detected");
+
+    if let Err(error) = delete_message(message, &state.cache_http(guild_id), state).await {
+        error!(error = ?error, "failed to delete filtered message");
+    }
+
+    if let Err(error) = log_message(message, guild_id, rule, &config, state).await {
+        error!(error = ?error, "failed to log filtered message");
+    }
+
+    Ok(true)
+}
+
+/// Log a deleted message in the logs channel.
+async fn log_message(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    rule: &str,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let lang = config.lang();
+
+    let embed =
+        EmbedBuilder::new()
+            .title(lang.filter_deleted_title())
+            .color(COLOR_RED)
+            .description(lang.filter_deleted_description(
+                message.channel_id.mention(),
+                message.author_id.mention(),
+            ))
+            .field(EmbedFieldBuilder::new(
+                lang.filter_deleted_rule(),
+                format!("`{}`", rule.max_len(1000)),
+            ))
+            .field(EmbedFieldBuilder::new(
+                lang.filter_deleted_content(),
+                message.content.max_len(1024),
+            ))
+            .build();
+
+    let channel = guild_logs_channel(state, config, LogCategory::Messages).await?;
+    state
+        .cache_http(guild_id)
+        .create_message(channel)
+        .await?
+        .embeds(&[embed])?
+        .exec()
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,255 @@
+//! Invite filter.
+//!
+//! Messages containing invites to other servers are deleted and their author
+//! is warned. After `max_warnings` warnings in 24 hours, the member is timed
+//! out.
+//!
+//! If the `resolve` option is enabled, invites are resolved using the Discord
+//! API to find the server they point to. Invites to the current server and to
+//! the allowed servers are not deleted. Invites that cannot be resolved are
+//! considered as foreign invites.
+
+use anyhow::Context;
+use raidprotect_model::{
+    cache::model::message::{CachedMessage, InviteWarnings, MessageLink, ResolvedInvite},
+    database::model::{GuildConfig, ModlogAutomation, ModlogType},
+};
+use time::{Duration, OffsetDateTime};
+use tracing::{error, info, warn};
+use twilight_http::request::AuditLogReason;
+use twilight_mention::Mention;
+use twilight_model::{
+    id::{
+        marker::{GuildMarker, RoleMarker},
+        Id,
+    },
+    util::Timestamp,
+};
+use twilight_util::builder::embed::EmbedBuilder;
+use url::Url;
+
+use super::antispam::{delete_message, is_exempt, record_modlog};
+use crate::{
+    cluster::ClusterState,
+    interaction::{embed::COLOR_RED, util::GuildConfigExt},
+    util::format_duration,
+};
+
+/// Check an incoming message for foreign invites.
+///
+/// `roles` are the roles of the message author.
+///
+/// Returns whether the message has been deleted.
+pub async fn check_message(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    roles: &[Id<RoleMarker>],
+    state: &ClusterState,
+) -> Result<bool, anyhow::Error> {
+    let codes = message
+        .links
+        .iter()
+        .filter_map(|link| match link {
+            MessageLink::Invite(url) => invite_code(url),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if codes.is_empty() {
+        return Ok(false);
+    }
+
+    let config = state.guild_config(guild_id).await?;
+    if !config.invites.enabled {
+        return Ok(false);
+    }
+
+    if is_exempt(&config.antispam, message.channel_id, roles) {
+        return Ok(false);
+    }
+
+    let mut foreign = None;
+    for code in codes {
+        if !is_allowed(code, &config, state).await? {
+            foreign = Some(code);
+            break;
+        }
+    }
+
+    let code = match foreign {
+        Some(code) => code,
+        None => return Ok(false),
+    };
+
+    info!(guild = ?guild_id, user = ?message.author_id, code, "foreign invite detected");
+
+    if let Err(error) = delete_message(message, &state.cache_http(guild_id), state).await {
+        error!(error = ?error, "failed to delete invite message");
+    }
+
+    if let Err(error) = warn_member(message, guild_id, code, &config, state).await {
+        error!(error = ?error, "failed to warn member about invite");
+    }
+
+    Ok(true)
+}
+
+/// Whether an invite is allowed in the guild.
+async fn is_allowed(
+    code: &str,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<bool, anyhow::Error> {
+    if !config.invites.resolve {
+        return Ok(false);
+    }
+
+    let resolved = match state.cache.get::<ResolvedInvite>(code).await? {
+        Some(resolved) => resolved,
+        None => {
+            let guild_id = match state.http.invite(code).exec().await {
+                Ok(response) => response.model().await?.guild.map(|guild| guild.id),
+                Err(error) => {
+                    warn!(error = ?error, code, "failed to resolve invite");
+                    None
+                }
+            };
+
+            let resolved = ResolvedInvite {
+                code: code.to_owned(),
+                guild_id,
+            };
+            state.cache.set(&resolved).await?;
+
+            resolved
+        }
+    };
+
+    Ok(match resolved.guild_id {
+        Some(guild_id) => {
+            guild_id == config.id || config.invites.allowed_guilds.contains(&guild_id)
+        }
+        None => false,
+    })
+}
+
+/// Warn the author of a foreign invite, and time them out after too many
+/// warnings.
+async fn warn_member(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    code: &str,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let lang = config.lang();
+    let key = (guild_id, message.author_id);
+    let mut warnings = state
+        .cache
+        .get::<InviteWarnings>(&key)
+        .await?
+        .unwrap_or(InviteWarnings {
+            guild_id,
+            user_id: message.author_id,
+            count: 0,
+        });
+    warnings.count = warnings.count.saturating_add(1);
+
+    let description = if warnings.count >= config.invites.max_warnings {
+        let duration = Duration::seconds(config.invites.timeout_duration.into());
+        let until = OffsetDateTime::now_utc() + duration;
+        let until = Timestamp::from_secs(until.unix_timestamp()).context("invalid timeout")?;
+        let reason = lang.invite_reason();
+
+        state
+            .cache_http(guild_id)
+            .update_guild_member(message.author_id)
+            .await?
+            .communication_disabled_until(Some(until))?
+            .reason(reason)?
+            .exec()
+            .await?;
+
+        let automation = ModlogAutomation {
+            rule: "invites:foreign-invite".to_owned(),
+            details: Some(format!("invite {code}, {} warnings", warnings.count)),
+        };
+        record_modlog(
+            guild_id,
+            message.author_id,
+            ModlogType::Timeout,
+            reason,
+            Some(automation),
+            state,
+        )
+        .await?;
+
+        state.cache.delete(&warnings).await?;
+
+        lang.invite_timeout(format_duration(duration, lang), message.author_id.mention())
+    } else {
+        state.cache.set(&warnings).await?;
+
+        lang.invite_warning(
+            warnings.count,
+            config.invites.max_warnings,
+            message.author_id.mention(),
+        )
+    };
+
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(description)
+        .build();
+
+    state
+        .cache_http(guild_id)
+        .create_message(message.channel_id)
+        .await?
+        .embeds(&[embed])?
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+/// Extract the code of an invite link.
+///
+/// Links to `discord.com` and `discordapp.com` that are not invites (such as
+/// message links) return [`None`].
+fn invite_code(url: &Url) -> Option<&str> {
+    let mut segments = url.path_segments()?;
+
+    if url.domain()? != "discord.gg" && segments.next()? != "invite" {
+        return None;
+    }
+
+    segments.next().filter(|code| !code.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(link: &str) -> Option<String> {
+        invite_code(&Url::parse(link).unwrap()).map(ToOwned::to_owned)
+    }
+
+    #[test]
+    fn test_invite_code() {
+        assert_eq!(
+            code("https://discord.gg/raidprotect").unwrap(),
+            "raidprotect"
+        );
+        assert_eq!(
+            code("https://discord.com/invite/raidprotect").unwrap(),
+            "raidprotect"
+        );
+        assert_eq!(
+            code("https://discordapp.com/invite/raidprotect").unwrap(),
+            "raidprotect"
+        );
+        assert!(code("https://discord.com/channels/1/2/3").is_none());
+        assert!(code("https://discord.gg/").is_none());
+    }
+}
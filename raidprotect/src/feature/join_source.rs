@@ -0,0 +1,143 @@
+//! Join source tracking.
+//!
+//! The invites of each guild are cached with their use counts (see
+//! [`CachedInvites`]). When a member joins, the invites are fetched again and
+//! compared with the cached ones to find the invite that has been used, which
+//! is saved as the [`MemberJoinSource`] of the member. Join raid alerts use it
+//! to show the invites used by the raid accounts.
+//!
+//! Invites are fetched when the bot joins a guild and refreshed by a
+//! background task, so that the use counts stay accurate even if some events
+//! are missed.
+
+use std::{cmp::Reverse, time::Duration as StdDuration};
+
+use raidprotect_model::cache::{discord::CachedInvites, model::join::MemberJoinSource};
+use time::{Duration, OffsetDateTime};
+use tracing::{debug, error};
+use twilight_model::{
+    guild::Member,
+    id::{
+        marker::{GuildMarker, UserMarker},
+        Id,
+    },
+};
+
+use crate::{cluster::ClusterState, util::shutdown::ShutdownSubscriber};
+
+/// Interval between two runs of the invites refresh task.
+const REFRESH_INTERVAL: StdDuration = StdDuration::from_secs(5 * 60);
+
+/// Age of cached invites after which they are refreshed.
+const MAX_INVITES_AGE: Duration = Duration::minutes(30);
+
+/// Find the invite used by a new member.
+pub async fn member_joined(member: &Member, state: &ClusterState) {
+    if member.user.bot {
+        return;
+    }
+
+    let invite = match state
+        .cache
+        .update_invites(&state.http, member.guild_id)
+        .await
+    {
+        Ok(Some(invite)) => invite,
+        Ok(None) => return,
+        Err(error) => {
+            error!(error = ?error, guild = ?member.guild_id, "failed to update guild invites");
+            return;
+        }
+    };
+
+    debug!(guild = ?member.guild_id, user = ?member.user.id, invite = %invite.code, "member joined with invite");
+
+    let source = MemberJoinSource {
+        guild_id: member.guild_id,
+        user_id: member.user.id,
+        invite,
+    };
+
+    if let Err(error) = state.cache.set(&source).await {
+        error!(error = ?error, "failed to save member join source");
+    }
+}
+
+/// Fetch the invites of a guild the bot joined.
+pub async fn guild_joined(guild_id: Id<GuildMarker>, state: &ClusterState) {
+    if let Err(error) = state.cache.update_invites(&state.http, guild_id).await {
+        error!(error = ?error, guild = ?guild_id, "failed to fetch guild invites");
+    }
+}
+
+/// Refresh the cached invites periodically.
+pub async fn invites_task(state: ClusterState, mut shutdown: ShutdownSubscriber) {
+    let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {},
+            _ = shutdown.wait_shutdown() => break,
+        }
+
+        if let Err(error) = refresh_invites(&state).await {
+            error!(error = ?error, "failed to refresh guild invites");
+        }
+    }
+}
+
+async fn refresh_invites(state: &ClusterState) -> Result<(), anyhow::Error> {
+    let outdated = OffsetDateTime::now_utc() - MAX_INVITES_AGE;
+    let cached = state
+        .cache
+        .scan::<CachedInvites>(&CachedInvites::key_pattern())
+        .await?;
+
+    for invites in cached {
+        if invites.updated_at > outdated {
+            continue;
+        }
+
+        if let Err(error) = state
+            .cache
+            .update_invites(&state.http, invites.guild_id)
+            .await
+        {
+            error!(error = ?error, guild = ?invites.guild_id, "failed to refresh guild invites");
+        }
+    }
+
+    Ok(())
+}
+
+/// Count the invites used by the accounts of a join raid.
+///
+/// Invites are sorted by descending number of uses. Accounts without a known
+/// join source are ignored.
+pub async fn raid_invites(
+    guild_id: Id<GuildMarker>,
+    accounts: &[Id<UserMarker>],
+    state: &ClusterState,
+) -> Result<Vec<(String, usize)>, anyhow::Error> {
+    let mut invites: Vec<(String, usize)> = Vec::new();
+
+    let ids: Vec<_> = accounts
+        .iter()
+        .map(|user_id| (guild_id, *user_id))
+        .collect();
+    let sources = state.cache.get_many::<MemberJoinSource>(&ids).await?;
+
+    for source in sources.into_iter().flatten() {
+        match invites
+            .iter_mut()
+            .find(|(code, _)| *code == source.invite.code)
+        {
+            Some((_, count)) => *count += 1,
+            None => invites.push((source.invite.code, 1)),
+        }
+    }
+
+    invites.sort_by_key(|(_, count)| Reverse(*count));
+
+    Ok(invites)
+}
@@ -0,0 +1,218 @@
+//! Kill switches.
+//!
+//! Kill switches allow the bot owners to instantly disable a command or an
+//! automation globally when a bug is discovered in production. They are
+//! stored in Redis (see [`KillSwitch`]) and checked before executing the
+//! disabled feature:
+//!
+//! - **commands**: checked in the interaction dispatch layer, using the name
+//!   of the command or the custom id name of components and modals. A
+//!   maintenance notice is sent instead of executing the interaction.
+//! - **automations**: checked in the event handlers before running the
+//!   automation (see [`Automation`]). The handlers running several
+//!   automations load all the kill switches at once with
+//!   [`disabled_automations`].
+//!
+//! Kill switches fail open: if they cannot be read, the commands and the
+//! automations are considered as enabled, so that an unavailable Redis does
+//! not disable the whole bot.
+//!
+//! Kill switches are managed with the `/killswitch` command, only registered
+//! in the administration guild.
+
+use raidprotect_model::cache::model::kill_switch::KillSwitch;
+use tracing::error;
+
+use crate::cluster::ClusterState;
+
+/// Automation that can be disabled with a kill switch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Automation {
+    /// Anti-spam checks on new messages.
+    AntiSpam,
+    /// Join raids detection.
+    JoinRaid,
+    /// Mass role pings and ghost pings detection.
+    Mentions,
+    /// Captcha sent to new members.
+    Captcha,
+    /// Invite filter.
+    Invites,
+    /// Domain filter.
+    Domains,
+    /// Word filter.
+    WordFilter,
+    /// Attachment policy.
+    Attachments,
+    /// Phishing detection.
+    Phishing,
+    /// Webhook messages checks.
+    Webhooks,
+    /// Anti-nuke protection.
+    AntiNuke,
+}
+
+impl Automation {
+    /// List of all the automations.
+    pub const ALL: [Automation; 11] = [
+        Automation::AntiSpam,
+        Automation::JoinRaid,
+        Automation::Mentions,
+        Automation::Captcha,
+        Automation::Invites,
+        Automation::Domains,
+        Automation::WordFilter,
+        Automation::Attachments,
+        Automation::Phishing,
+        Automation::Webhooks,
+        Automation::AntiNuke,
+    ];
+
+    /// Name of the automation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Automation::AntiSpam => "antispam",
+            Automation::JoinRaid => "join-raid",
+            Automation::Mentions => "mentions",
+            Automation::Captcha => "captcha",
+            Automation::Invites => "invites",
+            Automation::Domains => "domains",
+            Automation::WordFilter => "filter",
+            Automation::Attachments => "attachments",
+            Automation::Phishing => "phishing",
+            Automation::Webhooks => "webhooks",
+            Automation::AntiNuke => "antinuke",
+        }
+    }
+
+    /// Find an automation from its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|automation| automation.name() == name)
+    }
+}
+
+/// Get the kill switch target of a command.
+pub fn command_target(name: &str) -> String {
+    format!("command:{name}")
+}
+
+/// Get the kill switch target of an automation.
+pub fn automation_target(automation: Automation) -> String {
+    format!("automation:{}", automation.name())
+}
+
+/// Get the kill switch of a command, if enabled.
+///
+/// `name` is the name of the command, or the custom id name of a component
+/// or a modal. Errors are logged and the command is considered as enabled.
+pub async fn command_disabled(name: &str, state: &ClusterState) -> Option<KillSwitch> {
+    match state.cache.get::<KillSwitch>(&command_target(name)).await {
+        Ok(switch) => switch,
+        Err(error) => {
+            error!(error = ?error, command = name, "failed to check kill switch");
+
+            None
+        }
+    }
+}
+
+/// Whether an automation is disabled by a kill switch.
+///
+/// Errors are logged and the automation is considered as enabled.
+pub async fn automation_disabled(automation: Automation, state: &ClusterState) -> bool {
+    match state
+        .cache
+        .get::<KillSwitch>(&automation_target(automation))
+        .await
+    {
+        Ok(switch) => switch.is_some(),
+        Err(error) => {
+            error!(error = ?error, automation = ?automation, "failed to check kill switch");
+
+            false
+        }
+    }
+}
+
+/// Automations disabled by a kill switch.
+///
+/// See [`disabled_automations`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DisabledAutomations(Vec<Automation>);
+
+impl DisabledAutomations {
+    /// Whether an automation is disabled.
+    pub fn contains(&self, automation: Automation) -> bool {
+        self.0.contains(&automation)
+    }
+}
+
+/// Get the automations disabled by a kill switch.
+///
+/// The kill switches of all the automations are read with a single query.
+/// Errors are logged and all the automations are considered as enabled.
+pub async fn disabled_automations(state: &ClusterState) -> DisabledAutomations {
+    let targets = Automation::ALL.map(automation_target);
+
+    match state.cache.get_many::<KillSwitch>(&targets).await {
+        Ok(switches) => DisabledAutomations(
+            Automation::ALL
+                .into_iter()
+                .zip(switches)
+                .filter_map(|(automation, switch)| switch.map(|_| automation))
+                .collect(),
+        ),
+        Err(error) => {
+            error!(error = ?error, "failed to check kill switches");
+
+            DisabledAutomations::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+    use twilight_model::id::Id;
+
+    use super::*;
+
+    #[test]
+    fn test_automation_name() {
+        for automation in Automation::ALL {
+            assert_eq!(Automation::from_name(automation.name()), Some(automation));
+        }
+
+        assert_eq!(Automation::from_name("unknown"), None);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_automations() {
+        let state = ClusterState::for_tests().await;
+        assert_eq!(
+            disabled_automations(&state).await,
+            DisabledAutomations::default()
+        );
+
+        for target in [
+            automation_target(Automation::Phishing),
+            command_target("kick"),
+        ] {
+            let kill_switch = KillSwitch {
+                target,
+                reason: None,
+                enabled_by: Id::new(1),
+                enabled_at: OffsetDateTime::now_utc(),
+            };
+            state.cache.set(&kill_switch).await.unwrap();
+        }
+
+        let disabled = disabled_automations(&state).await;
+        assert!(disabled.contains(Automation::Phishing));
+        assert!(!disabled.contains(Automation::AntiSpam));
+        assert!(command_disabled("kick", &state).await.is_some());
+        assert!(command_disabled("ban", &state).await.is_none());
+    }
+}
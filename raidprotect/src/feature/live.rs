@@ -0,0 +1,52 @@
+//! Live events of the dashboard.
+//!
+//! Sanctions, raid alerts and captcha outcomes are appended to the live events
+//! stream of the guild (see [`LiveEvent`]), which is streamed to the dashboard
+//! by the web service. Events are published even if no dashboard is
+//! connected, the stream is trimmed and expires after a while.
+//!
+//! Events are also sent to the outgoing webhook of the guild, if configured
+//! (see [`webhooks`]).
+
+use raidprotect_model::{cache::model::live::LiveEvent, database::model::Modlog};
+use tracing::error;
+use twilight_model::id::{marker::GuildMarker, Id};
+
+use crate::{cluster::ClusterState, feature::webhooks};
+
+/// Publish a live event of a guild.
+pub async fn publish(guild_id: Id<GuildMarker>, event: LiveEvent, state: &ClusterState) {
+    if let Err(error) = publish_inner(guild_id, &event, state).await {
+        error!(error = ?error, guild = ?guild_id, event = ?event, "failed to publish live event");
+    }
+
+    webhooks::notify(guild_id, &event, state).await;
+}
+
+/// Publish a recorded sanction.
+pub async fn sanction(modlog: &Modlog, state: &ClusterState) {
+    let event = LiveEvent::Sanction {
+        kind: modlog.kind,
+        user_id: modlog.user.id,
+        moderator_id: modlog.moderator.id,
+        reason: modlog.reason.clone(),
+    };
+
+    publish(modlog.guild_id, event, state).await;
+}
+
+async fn publish_inner(
+    guild_id: Id<GuildMarker>,
+    event: &LiveEvent,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    state
+        .cache
+        .append_stream(
+            &LiveEvent::stream_key(guild_id),
+            &[vec![(LiveEvent::FIELD, event.encode()?)]],
+            LiveEvent::MAX_LEN,
+            LiveEvent::EXPIRES_AFTER,
+        )
+        .await
+}
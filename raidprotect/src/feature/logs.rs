@@ -0,0 +1,499 @@
+//! Events logging.
+//!
+//! Guild events can be logged in the logs channel. Each event type is enabled
+//! separately in the guild [`LogsConfig`].
+//!
+//! Deleted messages are retrieved from the messages cache, so only the
+//! messages deleted shortly after being sent can be logged. Member joins are
+//! logged with the invite used by the member (see the [`join_source`] module).
+//! Role and nickname changes are computed from the cached member, and voice
+//! channel activity from the cached voice state.
+//!
+//! [`LogsConfig`]: raidprotect_model::database::model::LogsConfig
+//! [`join_source`]: super::join_source
+
+use std::time::Duration;
+
+use raidprotect_model::{
+    cache::{
+        discord::{CachedMember, CachedVoiceState},
+        model::{interaction::PendingCaptcha, join::MemberJoinSource, message::CachedMessage},
+    },
+    database::model::{GuildConfig, LogCategory},
+};
+use twilight_mention::{
+    timestamp::{Timestamp, TimestampStyle},
+    Mention,
+};
+use twilight_model::{
+    channel::{embed::Embed, Attachment},
+    gateway::payload::incoming::MemberUpdate,
+    guild::Member,
+    id::{
+        marker::{ChannelMarker, GuildMarker, MessageMarker, RoleMarker, UserMarker},
+        Id,
+    },
+    user::User,
+    voice::VoiceState,
+};
+use twilight_util::{
+    builder::embed::{EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder},
+    snowflake::Snowflake,
+};
+
+use crate::{
+    cluster::ClusterState,
+    interaction::{
+        embed::{COLOR_RED, COLOR_SUCCESS, COLOR_TRANSPARENT},
+        util::GuildConfigExt,
+    },
+    util::{guild_logs_channel, TextProcessExt},
+};
+
+/// Log a deleted message in the logs channel.
+///
+/// Messages missing from the cache are not logged.
+pub async fn log_message_delete(
+    message_id: Id<MessageMarker>,
+    guild_id: Id<GuildMarker>,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    if !config.logs.message_delete {
+        return Ok(());
+    }
+
+    let message = match state.cache.get::<CachedMessage>(&message_id).await? {
+        Some(message) => message,
+        None => return Ok(()),
+    };
+
+    let lang = config.lang();
+    let mut embed = EmbedBuilder::new()
+        .title(lang.logs_message_delete_title())
+        .color(COLOR_RED)
+        .description(lang.logs_message_delete_description(
+            message.channel_id.mention(),
+            message.author_id.mention(),
+        ));
+
+    if !message.content.is_empty() {
+        embed = embed.field(EmbedFieldBuilder::new(
+            lang.logs_message_delete_content(),
+            message.content.max_len(1024),
+        ));
+    }
+
+    if !message.attachments.is_empty() {
+        embed = embed.field(EmbedFieldBuilder::new(
+            lang.logs_message_delete_attachments(),
+            attachment_list(&message.attachments).max_len(1024),
+        ));
+    }
+
+    send_log(
+        embed.build(),
+        guild_id,
+        LogCategory::Messages,
+        config,
+        state,
+    )
+    .await
+}
+
+/// Log a member joining the guild in the logs channel.
+///
+/// This must be called after the join source of the member has been saved
+/// and its captcha has been created.
+pub async fn log_member_join(member: &Member, state: &ClusterState) -> Result<(), anyhow::Error> {
+    let config = state.guild_config(member.guild_id).await?;
+    if !config.logs.member_join {
+        return Ok(());
+    }
+
+    let lang = config.lang();
+    let key = (member.guild_id, member.user.id);
+
+    let invite = match state.cache.get::<MemberJoinSource>(&key).await? {
+        Some(source) => match source.invite.inviter_id {
+            Some(inviter) => {
+                lang.logs_member_join_invite_by(&source.invite.code, inviter.mention())
+            }
+            None => format!("`{}`", source.invite.code),
+        },
+        None => lang.logs_member_join_invite_unknown().to_owned(),
+    };
+
+    let captcha = if state.cache.get::<PendingCaptcha>(&key).await?.is_some() {
+        lang.logs_member_join_captcha_pending()
+    } else {
+        lang.logs_member_join_captcha_none()
+    };
+
+    let embed = EmbedBuilder::new()
+        .title(lang.logs_member_join_title())
+        .color(COLOR_SUCCESS)
+        .description(
+            lang.logs_member_join_description(user_tag(&member.user), member.user.id.mention()),
+        )
+        .field(EmbedFieldBuilder::new(
+            lang.logs_member_created_at(),
+            format_date(created_at(member.user.id)),
+        ))
+        .field(EmbedFieldBuilder::new(lang.logs_member_join_invite(), invite).inline())
+        .field(EmbedFieldBuilder::new(lang.logs_member_join_captcha(), captcha).inline())
+        .footer(EmbedFooterBuilder::new(format!("ID: {}", member.user.id)))
+        .build();
+
+    send_log(embed, member.guild_id, LogCategory::Members, &config, state).await
+}
+
+/// Log a member leaving the guild in the logs channel.
+///
+/// The cached member is used to show when the member joined, and must be
+/// retrieved before the member is removed from the cache.
+pub async fn log_member_leave(
+    user: &User,
+    guild_id: Id<GuildMarker>,
+    member: Option<CachedMember>,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let config = state.guild_config(guild_id).await?;
+    if !config.logs.member_leave {
+        return Ok(());
+    }
+
+    let lang = config.lang();
+    let mut embed = EmbedBuilder::new()
+        .title(lang.logs_member_leave_title())
+        .color(COLOR_RED)
+        .description(lang.logs_member_leave_description(user_tag(user), user.id.mention()))
+        .field(EmbedFieldBuilder::new(
+            lang.logs_member_created_at(),
+            format_date(created_at(user.id)),
+        ));
+
+    if let Some(member) = member {
+        embed = embed.field(EmbedFieldBuilder::new(
+            lang.logs_member_leave_joined_at(),
+            format_date(member.joined_at.as_secs() as u64),
+        ));
+    }
+
+    let embed = embed
+        .footer(EmbedFooterBuilder::new(format!("ID: {}", user.id)))
+        .build();
+
+    send_log(embed, guild_id, LogCategory::Members, &config, state).await
+}
+
+/// Log the role and nickname changes of a member in the logs channel.
+///
+/// Changes are computed from the cached member, which must be retrieved
+/// before the member is updated in the cache. Nothing is logged if the member
+/// was not cached.
+pub async fn log_member_update(
+    event: &MemberUpdate,
+    previous: Option<&CachedMember>,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let previous = match previous {
+        Some(previous) => previous,
+        None => return Ok(()),
+    };
+
+    let added = role_diff(&event.roles, &previous.roles);
+    let removed = role_diff(&previous.roles, &event.roles);
+    let nick_changed = event.nick != previous.nick;
+
+    if added.is_empty() && removed.is_empty() && !nick_changed {
+        return Ok(());
+    }
+
+    let config = state.guild_config(event.guild_id).await?;
+    if !config.logs.member_update {
+        return Ok(());
+    }
+
+    let lang = config.lang();
+    let mut embed = EmbedBuilder::new()
+        .title(lang.logs_member_update_title())
+        .color(COLOR_TRANSPARENT)
+        .description(
+            lang.logs_member_update_description(user_tag(&event.user), event.user.id.mention()),
+        );
+
+    if nick_changed {
+        let format_nick = |nick: &Option<String>| match nick {
+            Some(nick) => format!("`{}`", nick.remove_markdown()),
+            None => lang.logs_member_update_no_nick().to_owned(),
+        };
+
+        embed = embed.field(EmbedFieldBuilder::new(
+            lang.logs_member_update_nick(),
+            format!(
+                "{} → {}",
+                format_nick(&previous.nick),
+                format_nick(&event.nick)
+            ),
+        ));
+    }
+
+    if !added.is_empty() {
+        embed = embed.field(EmbedFieldBuilder::new(
+            lang.logs_member_update_roles_added(),
+            role_list(&added).max_len(1024),
+        ));
+    }
+
+    if !removed.is_empty() {
+        embed = embed.field(EmbedFieldBuilder::new(
+            lang.logs_member_update_roles_removed(),
+            role_list(&removed).max_len(1024),
+        ));
+    }
+
+    let embed = embed
+        .footer(EmbedFooterBuilder::new(format!("ID: {}", event.user.id)))
+        .build();
+
+    send_log(embed, event.guild_id, LogCategory::Members, &config, state).await
+}
+
+/// Log the voice channel activity of a member in the logs channel.
+///
+/// Changes are computed from the cached voice state, which must be retrieved
+/// before the voice state is updated in the cache.
+pub async fn log_voice_state(
+    voice_state: &VoiceState,
+    guild_id: Id<GuildMarker>,
+    previous: Option<CachedVoiceState>,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let changes = voice_changes(previous.as_ref(), voice_state);
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let config = state.guild_config(guild_id).await?;
+    if !config.logs.voice {
+        return Ok(());
+    }
+
+    let lang = config.lang();
+    let user = voice_state.user_id.mention();
+    let color = match changes[0] {
+        VoiceChange::Join(_) => COLOR_SUCCESS,
+        VoiceChange::Leave(_) => COLOR_RED,
+        _ => COLOR_TRANSPARENT,
+    };
+
+    let description = changes
+        .into_iter()
+        .map(|change| match change {
+            VoiceChange::Join(channel) => lang.logs_voice_join(channel.mention(), user),
+            VoiceChange::Leave(channel) => lang.logs_voice_leave(channel.mention(), user),
+            VoiceChange::Move { from, to } => {
+                lang.logs_voice_move(from.mention(), to.mention(), user)
+            }
+            VoiceChange::Mute(true) => lang.logs_voice_mute(user),
+            VoiceChange::Mute(false) => lang.logs_voice_unmute(user),
+            VoiceChange::Deaf(true) => lang.logs_voice_deaf(user),
+            VoiceChange::Deaf(false) => lang.logs_voice_undeaf(user),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let embed = EmbedBuilder::new()
+        .title(lang.logs_voice_title())
+        .color(color)
+        .description(description)
+        .footer(EmbedFooterBuilder::new(format!(
+            "ID: {}",
+            voice_state.user_id
+        )))
+        .build();
+
+    send_log(embed, guild_id, LogCategory::Voice, &config, state).await
+}
+
+/// Change of the voice state of a member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VoiceChange {
+    /// The member joined a voice channel.
+    Join(Id<ChannelMarker>),
+    /// The member left a voice channel.
+    Leave(Id<ChannelMarker>),
+    /// The member moved to another voice channel.
+    Move {
+        from: Id<ChannelMarker>,
+        to: Id<ChannelMarker>,
+    },
+    /// The member has been muted or unmuted by the guild.
+    Mute(bool),
+    /// The member has been deafened or undeafened by the guild.
+    Deaf(bool),
+}
+
+/// Compute the changes between the cached voice state and the new one.
+///
+/// Only the server mute and deafen are compared, the changes made by the
+/// member themselves are ignored.
+fn voice_changes(previous: Option<&CachedVoiceState>, current: &VoiceState) -> Vec<VoiceChange> {
+    let previous = match (previous, current.channel_id) {
+        (None, Some(channel)) => return vec![VoiceChange::Join(channel)],
+        (Some(previous), None) => return vec![VoiceChange::Leave(previous.channel_id)],
+        (None, None) => return Vec::new(),
+        (Some(previous), Some(_)) => previous,
+    };
+
+    let mut changes = Vec::new();
+
+    match current.channel_id {
+        Some(channel) if channel != previous.channel_id => changes.push(VoiceChange::Move {
+            from: previous.channel_id,
+            to: channel,
+        }),
+        _ => {}
+    }
+
+    if current.mute != previous.mute {
+        changes.push(VoiceChange::Mute(current.mute));
+    }
+
+    if current.deaf != previous.deaf {
+        changes.push(VoiceChange::Deaf(current.deaf));
+    }
+
+    changes
+}
+
+/// Send a log embed in the logs channel.
+async fn send_log(
+    embed: Embed,
+    guild_id: Id<GuildMarker>,
+    category: LogCategory,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let channel = guild_logs_channel(state, config, category).await?;
+    state
+        .cache_http(guild_id)
+        .create_message(channel)
+        .await?
+        .embeds(&[embed])?
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+/// Get the creation date of a user account (in seconds).
+fn created_at(user_id: Id<UserMarker>) -> u64 {
+    Duration::from_millis(user_id.timestamp() as u64).as_secs()
+}
+
+/// Format a date as a long date followed by the relative time.
+fn format_date(timestamp: u64) -> String {
+    let long = Timestamp::new(timestamp, Some(TimestampStyle::LongDate)).mention();
+    let relative = Timestamp::new(timestamp, Some(TimestampStyle::RelativeTime)).mention();
+
+    format!("{long} ({relative})")
+}
+
+/// Format the name and discriminator of a user.
+fn user_tag(user: &User) -> String {
+    format!("{}#{}", user.name, user.discriminator())
+}
+
+/// Get the roles of `roles` missing from `other`.
+fn role_diff(roles: &[Id<RoleMarker>], other: &[Id<RoleMarker>]) -> Vec<Id<RoleMarker>> {
+    roles
+        .iter()
+        .filter(|role| !other.contains(role))
+        .copied()
+        .collect()
+}
+
+/// Format a list of roles as mentions.
+fn role_list(roles: &[Id<RoleMarker>]) -> String {
+    roles
+        .iter()
+        .map(|role| role.mention().to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Format the attachments of a message as a list of links.
+fn attachment_list(attachments: &[Attachment]) -> String {
+    attachments
+        .iter()
+        .map(|attachment| format!("[{}]({})", attachment.filename, attachment.url))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn voice_state(channel: Option<u64>, mute: bool) -> VoiceState {
+        serde_json::from_value(json!({
+            "channel_id": channel.map(|id| id.to_string()),
+            "deaf": false,
+            "mute": mute,
+            "self_deaf": false,
+            "self_mute": false,
+            "self_video": false,
+            "session_id": "session",
+            "suppress": false,
+            "user_id": "1",
+            "request_to_speak_timestamp": null,
+        }))
+        .unwrap()
+    }
+
+    fn cached(channel: u64) -> CachedVoiceState {
+        CachedVoiceState {
+            guild_id: Id::new(1),
+            user_id: Id::new(1),
+            channel_id: Id::new(channel),
+            deaf: false,
+            mute: false,
+            self_deaf: false,
+            self_mute: false,
+            self_stream: false,
+            self_video: false,
+        }
+    }
+
+    #[test]
+    fn test_voice_changes() {
+        let (first, second) = (Id::new(10), Id::new(11));
+
+        assert_eq!(
+            voice_changes(None, &voice_state(Some(10), false)),
+            vec![VoiceChange::Join(first)]
+        );
+        assert_eq!(
+            voice_changes(Some(&cached(10)), &voice_state(None, false)),
+            vec![VoiceChange::Leave(first)]
+        );
+        assert_eq!(
+            voice_changes(Some(&cached(10)), &voice_state(Some(11), true)),
+            vec![
+                VoiceChange::Move {
+                    from: first,
+                    to: second
+                },
+                VoiceChange::Mute(true)
+            ]
+        );
+        // Changes made by the member are ignored.
+        let mut self_muted = voice_state(Some(10), false);
+        self_muted.self_mute = true;
+        assert!(voice_changes(Some(&cached(10)), &self_muted).is_empty());
+    }
+}
@@ -0,0 +1,341 @@
+//! Mention protection.
+//!
+//! Members can abuse mentions in ways that are not caught by the anti-spam
+//! limits:
+//!
+//! - **mass role pings**: mentioning several hoisted roles in a single
+//!   message. Hoisted roles are displayed separately in the member list, and
+//!   are usually staff or large community roles.
+//! - **ghost pings**: sending a message with mentions and deleting it shortly
+//!   after, so that the mentioned members get a notification without being
+//!   able to see the message.
+//!
+//! Mention abuses are logged in the logs channel with the original message
+//! content, which is retrieved from the message cache. The number of abuses
+//! of each member is kept for 24 hours, and repeat offenders are timed out if
+//! the `mention_sanction` option of the anti-spam is enabled.
+
+use anyhow::Context;
+use raidprotect_model::{
+    cache::{
+        discord::{CachedMember, CachedRole},
+        model::message::{CachedMessage, MentionOffenses},
+    },
+    database::model::{GuildConfig, LogCategory, ModlogAutomation, ModlogType},
+};
+use time::{Duration, OffsetDateTime};
+use tracing::{debug, info};
+use twilight_http::request::AuditLogReason;
+use twilight_mention::Mention;
+use twilight_model::{
+    guild::{audit_log::AuditLogEventType, Permissions},
+    id::{
+        marker::{GuildMarker, MessageMarker},
+        Id,
+    },
+    util::Timestamp,
+};
+use twilight_util::{
+    builder::embed::{EmbedBuilder, EmbedFieldBuilder},
+    snowflake::Snowflake,
+};
+
+use super::antispam::{is_exempt, record_modlog};
+use crate::{
+    cluster::ClusterState,
+    interaction::{embed::COLOR_RED, util::GuildConfigExt},
+    util::{format_duration, guild_logs_channel, TextProcessExt},
+};
+
+/// Minimum number of hoisted roles mentioned in a message to be considered as
+/// a mass role ping.
+pub const MASS_ROLE_PINGS: usize = 2;
+
+/// Maximum delay between the creation and the deletion of a message to be
+/// considered as a ghost ping.
+pub const GHOST_PING_DELAY: Duration = Duration::minutes(1);
+
+/// Number of mention abuses after which a member is timed out.
+pub const MAX_OFFENSES: u8 = 3;
+
+/// Number of audit log entries checked to find who deleted a message.
+const AUDIT_LOG_LIMIT: u16 = 10;
+
+/// Kind of mention abuse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MentionAbuse {
+    /// Message mentioning several hoisted roles.
+    RolePing { roles: usize },
+    /// Message with mentions deleted shortly after being sent.
+    GhostPing { delay: Duration },
+}
+
+impl MentionAbuse {
+    /// Name of the rule recorded in the moderation logs.
+    fn rule(&self) -> &'static str {
+        match self {
+            MentionAbuse::RolePing { .. } => "mentions:role-ping",
+            MentionAbuse::GhostPing { .. } => "mentions:ghost-ping",
+        }
+    }
+}
+
+/// Check a new message for mass role pings.
+///
+/// The message author must not be exempted from the anti-spam.
+pub async fn check_message(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    if message.mention_roles.len() < MASS_ROLE_PINGS {
+        return Ok(());
+    }
+
+    let roles = state
+        .cache
+        .get_many::<CachedRole>(&message.mention_roles)
+        .await?
+        .into_iter()
+        .flatten()
+        .filter(|role| role.hoist)
+        .count();
+
+    if roles < MASS_ROLE_PINGS {
+        return Ok(());
+    }
+
+    report_abuse(
+        message,
+        guild_id,
+        MentionAbuse::RolePing { roles },
+        config,
+        state,
+    )
+    .await
+}
+
+/// Check a deleted message for ghost pings.
+///
+/// Only messages still in the cache are checked. Messages of exempted members
+/// or channels and messages deleted by another member than their author (found
+/// in the audit log) are ignored.
+pub async fn check_deleted(
+    message_id: Id<MessageMarker>,
+    guild_id: Id<GuildMarker>,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let message = match state.cache.get::<CachedMessage>(&message_id).await? {
+        Some(message) => message,
+        None => return Ok(()),
+    };
+
+    let has_mentions = message.mention_everyone
+        || !message.mention_users.is_empty()
+        || !message.mention_roles.is_empty();
+
+    if !has_mentions {
+        return Ok(());
+    }
+
+    let delay = OffsetDateTime::now_utc()
+        - OffsetDateTime::from_unix_timestamp(message.timestamp.as_secs())?;
+    if delay > GHOST_PING_DELAY {
+        return Ok(());
+    }
+
+    let roles = state
+        .cache
+        .get::<CachedMember>(&(guild_id, message.author_id))
+        .await?
+        .map(|member| member.roles)
+        .unwrap_or_default();
+
+    if is_exempt(&config.antispam, message.channel_id, &roles) {
+        return Ok(());
+    }
+
+    if deleted_by_moderator(&message, guild_id, state).await? {
+        return Ok(());
+    }
+
+    report_abuse(
+        &message,
+        guild_id,
+        MentionAbuse::GhostPing { delay },
+        config,
+        state,
+    )
+    .await
+}
+
+/// Check in the audit log whether a message was deleted by a moderator.
+///
+/// Deletions by the message author are not recorded in the audit log. Only
+/// the entries created after the message was sent are considered, so that a
+/// previous deletion in the same channel is not mistaken for this one. If the
+/// bot cannot read the audit log, the message is considered as deleted by a
+/// moderator to avoid false reports.
+async fn deleted_by_moderator(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    state: &ClusterState,
+) -> Result<bool, anyhow::Error> {
+    let permissions = state
+        .cache
+        .permissions(guild_id)
+        .await?
+        .current_member()
+        .await?;
+
+    if !permissions.guild().contains(Permissions::VIEW_AUDIT_LOG) {
+        debug!(guild = ?guild_id, "missing permission to check ghost pings");
+
+        return Ok(true);
+    }
+
+    let audit_log = state
+        .http
+        .audit_log(guild_id)
+        .action_type(AuditLogEventType::MessageDelete)
+        .limit(AUDIT_LOG_LIMIT)?
+        .exec()
+        .await?
+        .model()
+        .await?;
+
+    let sent_at = message.timestamp.as_micros() / 1000;
+    let deleted = audit_log.entries.iter().any(|entry| {
+        entry.id.timestamp() >= sent_at
+            && entry.target_id == Some(message.author_id.cast())
+            && entry
+                .options
+                .as_ref()
+                .and_then(|options| options.channel_id)
+                == Some(message.channel_id)
+    });
+
+    Ok(deleted)
+}
+
+/// Log a mention abuse and punish repeat offenders.
+async fn report_abuse(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    abuse: MentionAbuse,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    info!(guild = ?guild_id, user = ?message.author_id, abuse = ?abuse, "mention abuse detected");
+
+    let lang = config.lang();
+    let key = (guild_id, message.author_id);
+    let mut offenses = state
+        .cache
+        .get::<MentionOffenses>(&key)
+        .await?
+        .unwrap_or(MentionOffenses {
+            guild_id,
+            user_id: message.author_id,
+            count: 0,
+        });
+    offenses.count = offenses.count.saturating_add(1);
+
+    let (title, description) = match abuse {
+        MentionAbuse::RolePing { roles } => (
+            lang.mention_role_ping_title(),
+            lang.mention_role_ping_description(
+                message.channel_id.mention(),
+                roles,
+                message.author_id.mention(),
+            ),
+        ),
+        MentionAbuse::GhostPing { delay } => (
+            lang.mention_ghost_ping_title(),
+            lang.mention_ghost_ping_description(
+                message.channel_id.mention(),
+                format_duration(delay, lang),
+                message.author_id.mention(),
+            ),
+        ),
+    };
+
+    let mentioned = message
+        .mention_users
+        .iter()
+        .map(|user| user.mention().to_string())
+        .chain(
+            message
+                .mention_roles
+                .iter()
+                .map(|role| role.mention().to_string()),
+        )
+        .chain(message.mention_everyone.then(|| "@everyone".to_owned()))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut embed = EmbedBuilder::new()
+        .title(title)
+        .color(COLOR_RED)
+        .description(description)
+        .field(EmbedFieldBuilder::new(
+            lang.mention_content(),
+            message.content.max_len(1024),
+        ))
+        .field(EmbedFieldBuilder::new(lang.mention_mentioned(), mentioned.max_len(1024)).inline())
+        .field(
+            EmbedFieldBuilder::new(lang.mention_offenses(), offenses.count.to_string()).inline(),
+        );
+
+    // Punish repeat offenders.
+    if config.antispam.mention_sanction && offenses.count >= MAX_OFFENSES {
+        let duration = Duration::seconds(config.antispam.punishment_duration.into());
+        let until = OffsetDateTime::now_utc() + duration;
+        let until = Timestamp::from_secs(until.unix_timestamp()).context("invalid timeout")?;
+        let reason = lang.mention_sanction_reason();
+
+        state
+            .cache_http(guild_id)
+            .update_guild_member(message.author_id)
+            .await?
+            .communication_disabled_until(Some(until))?
+            .reason(reason)?
+            .exec()
+            .await?;
+
+        let automation = ModlogAutomation {
+            rule: abuse.rule().to_owned(),
+            details: Some(format!("{} mention abuses", offenses.count)),
+        };
+        record_modlog(
+            guild_id,
+            message.author_id,
+            ModlogType::Timeout,
+            reason,
+            Some(automation),
+            state,
+        )
+        .await?;
+
+        embed = embed.field(EmbedFieldBuilder::new(
+            lang.mention_sanction(),
+            lang.mention_sanction_description(format_duration(duration, lang)),
+        ));
+        state.cache.delete(&offenses).await?;
+    } else {
+        state.cache.set(&offenses).await?;
+    }
+
+    let channel = guild_logs_channel(state, config, LogCategory::AntiSpam).await?;
+    state
+        .cache_http(guild_id)
+        .create_message(channel)
+        .await?
+        .embeds(&[embed.build()])?
+        .exec()
+        .await?;
+
+    Ok(())
+}
@@ -4,4 +4,28 @@
 //! wide to fit in another module, such as features involving both events and
 //! interactions.
 
+pub mod antinuke;
+pub mod antispam;
+pub mod attachments;
+pub mod audit_log;
 pub mod captcha;
+pub mod config_sync;
+pub mod cooldown;
+pub mod domains;
+pub mod filter;
+pub mod invite;
+pub mod join_source;
+pub mod kill_switch;
+pub mod live;
+pub mod logs;
+pub mod mention;
+pub mod modlogs;
+pub mod offboarding;
+pub mod phishing;
+pub mod quarantine;
+pub mod rpc;
+pub mod scheduler;
+pub mod security_alert;
+pub mod slowmode;
+pub mod webhook;
+pub mod webhooks;
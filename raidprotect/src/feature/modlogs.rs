@@ -0,0 +1,74 @@
+//! Moderation logs archival.
+//!
+//! Moderation logs older than the configured delay are moved to the
+//! `modlogs_archive` collection by a background task, to keep the collection
+//! read by the commands small. Archived moderation logs are still included in
+//! the exports of the `/modlogs export` command.
+//!
+//! The task runs in every process: archiving the same moderation logs twice
+//! has no effect.
+
+use std::time::Duration as StdDuration;
+
+use time::{Duration, OffsetDateTime};
+use tracing::{error, info};
+
+use crate::{cluster::ClusterState, util::shutdown::ShutdownSubscriber};
+
+/// Interval between each run of the archival task.
+const ARCHIVE_INTERVAL: StdDuration = StdDuration::from_secs(24 * 60 * 60);
+
+/// Number of moderation logs moved at once.
+const ARCHIVE_BATCH_SIZE: i64 = 1000;
+
+/// Move the moderation logs older than `archive_after` to the archive
+/// collection periodically.
+pub async fn modlog_archive_task(
+    state: ClusterState,
+    archive_after: Duration,
+    mut shutdown: ShutdownSubscriber,
+) {
+    let mut interval = tokio::time::interval(ARCHIVE_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {},
+            _ = shutdown.wait_shutdown() => break,
+        }
+
+        // Interrupting the archival is safe since moderation logs are
+        // inserted in the archive before being deleted.
+        let before = OffsetDateTime::now_utc() - archive_after;
+        tokio::select! {
+            result = archive_modlogs(&state, before) => {
+                match result {
+                    Ok(0) => {}
+                    Ok(count) => info!(count, "moderation logs archived"),
+                    Err(error) => error!(error = ?error, "failed to archive moderation logs"),
+                }
+            }
+            _ = shutdown.wait_shutdown() => break,
+        }
+    }
+}
+
+/// Archive the moderation logs older than `before` by batches.
+///
+/// Returns the number of archived moderation logs.
+async fn archive_modlogs(
+    state: &ClusterState,
+    before: OffsetDateTime,
+) -> Result<u64, anyhow::Error> {
+    let mut count = 0;
+
+    loop {
+        match state
+            .store
+            .archive_modlogs(before, ARCHIVE_BATCH_SIZE)
+            .await?
+        {
+            0 => return Ok(count),
+            archived => count += archived,
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! Guild offboarding.
+//!
+//! When the bot is removed from a guild, the guild data is deleted in several
+//! stages (see [`GuildOffboarding`]):
+//!
+//! - the guild is immediately removed from the cache
+//! - the guild configuration is archived and its API keys are revoked after
+//!   30 days
+//! - the moderation logs are deleted after the configured retention period
+//!
+//! The stages are run by a background task. If the bot rejoins the guild, the
+//! remaining stages are cancelled and the archived configuration is restored,
+//! even if the offboarding is already completed.
+
+use std::time::Duration as StdDuration;
+
+use raidprotect_model::database::model::{GuildConfig, GuildOffboarding};
+use time::{Duration, OffsetDateTime};
+use tracing::{error, info};
+use twilight_model::id::{marker::GuildMarker, Id};
+
+use crate::{cluster::ClusterState, util::shutdown::ShutdownSubscriber};
+
+/// Interval between each run of the offboarding task.
+const OFFBOARDING_INTERVAL: StdDuration = StdDuration::from_secs(60 * 60);
+
+/// Start the offboarding of a guild that removed the bot.
+///
+/// The guild is removed from the cache when processing the event, before
+/// this function is called.
+pub async fn guild_removed(guild_id: Id<GuildMarker>, state: &ClusterState) {
+    match state.store.create_offboarding(guild_id).await {
+        Ok(offboarding) => {
+            info!(guild = ?guild_id, removed_at = %offboarding.removed_at, "guild offboarding started");
+        }
+        Err(error) => {
+            error!(error = ?error, guild = ?guild_id, "failed to start guild offboarding")
+        }
+    }
+}
+
+/// Cancel the offboarding of a guild and restore its archived configuration
+/// if the bot rejoined it.
+pub async fn guild_joined(guild_id: Id<GuildMarker>, state: &ClusterState) {
+    if let Err(error) = cancel_offboarding(guild_id, state).await {
+        error!(error = ?error, guild = ?guild_id, "failed to cancel guild offboarding");
+    }
+}
+
+async fn cancel_offboarding(
+    guild_id: Id<GuildMarker>,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let cancelled = state.store.delete_offboarding(guild_id).await?.is_some();
+
+    // The offboarding document is deleted once all the stages are completed,
+    // so the archive is always checked.
+    let restored = state.store.restore_guild(guild_id).await?;
+    if restored {
+        state.cache.delete_from::<GuildConfig>(&guild_id).await?;
+    }
+
+    if cancelled || restored {
+        info!(guild = ?guild_id, restored, "guild offboarding cancelled");
+    }
+
+    Ok(())
+}
+
+/// Run the pending offboarding stages periodically.
+///
+/// Moderation logs are deleted after `modlog_retention`.
+pub async fn offboarding_task(
+    state: ClusterState,
+    modlog_retention: Duration,
+    mut shutdown: ShutdownSubscriber,
+) {
+    let mut interval = tokio::time::interval(OFFBOARDING_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {},
+            _ = shutdown.wait_shutdown() => break,
+        }
+
+        if let Err(error) = run_offboardings(&state, modlog_retention).await {
+            error!(error = ?error, "failed to run guild offboardings");
+        }
+    }
+}
+
+async fn run_offboardings(
+    state: &ClusterState,
+    modlog_retention: Duration,
+) -> Result<(), anyhow::Error> {
+    for offboarding in state.store.pending_offboardings().await? {
+        let guild_id = offboarding.guild_id;
+
+        if let Err(error) = run_offboarding(offboarding, state, modlog_retention).await {
+            error!(error = ?error, guild = ?guild_id, "failed to run guild offboarding");
+        }
+    }
+
+    Ok(())
+}
+
+/// Run the pending stages of a guild offboarding.
+async fn run_offboarding(
+    mut offboarding: GuildOffboarding,
+    state: &ClusterState,
+    modlog_retention: Duration,
+) -> Result<(), anyhow::Error> {
+    let guild_id = offboarding.guild_id;
+    let now = OffsetDateTime::now_utc();
+    let elapsed = now - offboarding.removed_at;
+    let mut updated = false;
+
+    if offboarding.config_archived_at.is_none() && elapsed >= GuildOffboarding::CONFIG_ARCHIVE_DELAY
+    {
+        let archived = state.store.archive_guild(guild_id).await?;
+        offboarding.config_archived_at = Some(now);
+
+        // If the bot rejoined the guild while the configuration was archived,
+        // the restore may have run before the configuration was deleted. The
+        // configuration is put back and the offboarding is stopped.
+        if !state.store.update_offboarding(&offboarding).await? {
+            if let Some(config) = &archived {
+                state.store.update_guild(config).await?;
+                state.store.restore_guild(guild_id).await?;
+            }
+
+            info!(guild = ?guild_id, "guild offboarding cancelled while archiving");
+
+            return Ok(());
+        }
+
+        state.cache.delete_from::<GuildConfig>(&guild_id).await?;
+        state.store.delete_guild_api_keys(guild_id).await?;
+
+        info!(guild = ?guild_id, archived = archived.is_some(), "guild configuration archived");
+    }
+
+    if offboarding.modlogs_deleted_at.is_none() && elapsed >= modlog_retention {
+        let count = state.store.delete_guild_modlogs(guild_id).await?;
+        state.store.delete_guild_stats(guild_id).await?;
+        offboarding.modlogs_deleted_at = Some(now);
+        updated = true;
+
+        info!(guild = ?guild_id, count, "guild moderation logs deleted");
+    }
+
+    if offboarding.is_completed() {
+        state.store.delete_offboarding(guild_id).await?;
+
+        info!(guild = ?guild_id, "guild offboarding completed");
+    } else if updated {
+        state.store.update_offboarding(&offboarding).await?;
+    }
+
+    Ok(())
+}
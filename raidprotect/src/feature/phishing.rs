@@ -0,0 +1,201 @@
+//! Phishing detection.
+//!
+//! The phishing score of incoming messages is computed with the heuristic of
+//! the [`phishing`] module of the anti-spam. Messages reaching the threshold
+//! configured with `/config phishing` are deleted, and their author is
+//! placed in [`quarantine`], as phishing messages are usually sent from
+//! compromised accounts. If no quarantine role is configured, the author is
+//! timed out for [`QUARANTINE_DURATION`] instead.
+//!
+//! The action is recorded in the moderation logs and the moderators are
+//! notified in the logs channel with the signals found in the message.
+//!
+//! [`phishing`]: raidprotect_anti_spam::phishing
+//! [`quarantine`]: super::quarantine
+
+use anyhow::Context;
+use raidprotect_anti_spam::phishing::{phishing_score, PhishingMessage, PhishingScore};
+use raidprotect_model::{
+    cache::model::message::CachedMessage,
+    database::model::{GuildConfig, LogCategory, ModlogAutomation, ModlogType},
+};
+use time::{Duration, OffsetDateTime};
+use tracing::{error, info};
+use twilight_http::request::AuditLogReason;
+use twilight_mention::Mention;
+use twilight_model::{
+    id::{
+        marker::{GuildMarker, RoleMarker},
+        Id,
+    },
+    util::Timestamp,
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+use super::{
+    antispam::{delete_message, is_exempt, record_modlog},
+    quarantine::quarantine,
+};
+use crate::{
+    cluster::ClusterState,
+    interaction::{embed::COLOR_RED, util::GuildConfigExt},
+    util::{guild_logs_channel, TextProcessExt},
+};
+
+/// Duration of the timeout applied to the author of a phishing message when
+/// no quarantine role is configured.
+pub const QUARANTINE_DURATION: Duration = Duration::days(1);
+
+/// Check an incoming message for phishing.
+///
+/// `roles` are the roles of the message author.
+///
+/// Returns whether the message has been deleted.
+pub async fn check_message(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    roles: &[Id<RoleMarker>],
+    state: &ClusterState,
+) -> Result<bool, anyhow::Error> {
+    if message.content.is_empty() {
+        return Ok(false);
+    }
+
+    let config = state.guild_config(guild_id).await?;
+    if !config.phishing.enabled {
+        return Ok(false);
+    }
+
+    if is_exempt(&config.antispam, message.channel_id, roles) {
+        return Ok(false);
+    }
+
+    let score = phishing_score(PhishingMessage {
+        author_id: message.author_id,
+        timestamp: message.timestamp,
+        content: &message.content,
+        mention_everyone: message.mention_everyone,
+    });
+
+    if !score.exceeds(config.phishing.threshold) {
+        return Ok(false);
+    }
+
+    info!(guild = ?guild_id, user = ?message.author_id, score = score.score(), "phishing detected");
+
+    if let Err(error) = delete_message(message, &state.cache_http(guild_id), state).await {
+        error!(error = ?error, "failed to delete phishing message");
+    }
+
+    if let Err(error) = punish_author(message, guild_id, &score, &config, state).await {
+        error!(error = ?error, "failed to punish phishing message author");
+    }
+
+    Ok(true)
+}
+
+/// Quarantine the author of a phishing message and log the sanction.
+async fn punish_author(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    score: &PhishingScore,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let lang = config.lang();
+    let reason = lang.phishing_reason();
+
+    let kind = if quarantine(guild_id, message.author_id, reason, config, state).await? {
+        ModlogType::Quarantine
+    } else {
+        let until = OffsetDateTime::now_utc() + QUARANTINE_DURATION;
+        let until =
+            Timestamp::from_secs(until.unix_timestamp()).context("invalid timeout timestamp")?;
+
+        state
+            .cache_http(guild_id)
+            .update_guild_member(message.author_id)
+            .await?
+            .communication_disabled_until(Some(until))?
+            .reason(reason)?
+            .exec()
+            .await?;
+
+        ModlogType::Timeout
+    };
+
+    let automation = ModlogAutomation {
+        rule: "antispam:phishing".to_owned(),
+        details: Some(format!("score {} ({})", score.score(), signals(score))),
+    };
+    record_modlog(
+        guild_id,
+        message.author_id,
+        kind,
+        reason,
+        Some(automation),
+        state,
+    )
+    .await?;
+
+    log_message(message, guild_id, score, config, state).await
+}
+
+/// Format the signals of a phishing score.
+fn signals(score: &PhishingScore) -> String {
+    score
+        .signals
+        .iter()
+        .map(|signal| signal.name())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Log a phishing message in the logs channel.
+async fn log_message(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    score: &PhishingScore,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let lang = config.lang();
+
+    let embed = EmbedBuilder::new()
+        .title(lang.phishing_deleted_title())
+        .color(COLOR_RED)
+        .description(lang.phishing_deleted_description(
+            message.channel_id.mention(),
+            message.author_id.mention(),
+        ))
+        .field(
+            EmbedFieldBuilder::new(
+                lang.phishing_deleted_score(),
+                lang.phishing_deleted_score_value(score.score(), config.phishing.threshold),
+            )
+            .inline(),
+        )
+        .field(
+            EmbedFieldBuilder::new(
+                lang.phishing_deleted_signals(),
+                format!("`{}`", signals(score)),
+            )
+            .inline(),
+        )
+        .field(EmbedFieldBuilder::new(
+            lang.phishing_deleted_content(),
+            message.content.max_len(1024),
+        ))
+        .build();
+
+    let channel = guild_logs_channel(state, config, LogCategory::Messages).await?;
+    state
+        .cache_http(guild_id)
+        .create_message(channel)
+        .await?
+        .embeds(&[embed])?
+        .exec()
+        .await?;
+
+    Ok(())
+}
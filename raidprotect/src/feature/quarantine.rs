@@ -0,0 +1,263 @@
+//! Quarantine.
+//!
+//! Members flagged by the automations (such as the [`phishing`] detection)
+//! are placed in quarantine if a quarantine role is configured with
+//! `/config quarantine`. Their roles are replaced by the quarantine role, and
+//! the removed roles are saved in Redis (see [`QuarantinedMember`]) to be
+//! restored if the member is released.
+//!
+//! Roles that cannot be removed by the bot (managed roles and roles higher
+//! than the bot's highest role) are kept.
+//!
+//! A review message is sent in the logs channel with buttons to release or
+//! ban the member, handled by the [`QuarantineButton`] component.
+//!
+//! [`phishing`]: super::phishing
+//! [`QuarantineButton`]: crate::interaction::component::QuarantineButton
+
+use anyhow::{bail, Context};
+use raidprotect_model::{
+    cache::{discord::permission::RoleOrdering, model::quarantine::QuarantinedMember},
+    database::model::{GuildConfig, LogCategory},
+};
+use time::OffsetDateTime;
+use tracing::info;
+use twilight_mention::Mention;
+use twilight_model::{
+    application::component::{button::ButtonStyle, ActionRow, Button, Component},
+    guild::Permissions,
+    id::{
+        marker::{GuildMarker, RoleMarker, UserMarker},
+        Id,
+    },
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+use crate::{
+    cluster::ClusterState,
+    interaction::{
+        embed::COLOR_RED,
+        util::{CustomId, GuildConfigExt},
+    },
+    util::{guild_logs_channel, TextProcessExt},
+};
+
+/// Place a member in quarantine.
+///
+/// Returns `false` if no quarantine role is configured, in which case the
+/// caller should apply another sanction. Members already in quarantine are
+/// not updated.
+pub async fn quarantine(
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    reason: &str,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<bool, anyhow::Error> {
+    let quarantine_role = match config.quarantine.role {
+        Some(role) => role,
+        None => return Ok(false),
+    };
+
+    if state
+        .cache
+        .get::<QuarantinedMember>(&(guild_id, user_id))
+        .await?
+        .is_some()
+    {
+        return Ok(true);
+    }
+
+    let member = state
+        .cache_http(guild_id)
+        .member(user_id)
+        .await?
+        .context("member not found")?;
+
+    let highest_role = check_quarantine_role(guild_id, quarantine_role, state).await?;
+    let guild_roles = state.cache.guild_roles(guild_id).await?;
+
+    // Split the member roles between the roles that can be removed and the
+    // roles that must be kept.
+    let (removed, mut kept): (Vec<_>, Vec<_>) = member.roles.into_iter().partition(|role_id| {
+        guild_roles.iter().any(|role| {
+            role.id == *role_id && !role.managed && RoleOrdering::from(role) < highest_role
+        })
+    });
+
+    if !kept.contains(&quarantine_role) {
+        kept.push(quarantine_role);
+    }
+
+    let quarantined = QuarantinedMember {
+        guild_id,
+        user_id,
+        roles: removed,
+        reason: reason.to_owned(),
+        quarantined_at: OffsetDateTime::now_utc(),
+    };
+    state.cache.set(&quarantined).await?;
+
+    state
+        .http
+        .update_guild_member(guild_id, user_id)
+        .roles(&kept)
+        .exec()
+        .await?;
+
+    info!(guild = ?guild_id, user = ?user_id, "member quarantined");
+
+    review_message(&quarantined, config, state).await?;
+
+    Ok(true)
+}
+
+/// Release a member from quarantine.
+///
+/// The quarantine role is removed and the saved roles are restored, except
+/// the roles that have been deleted or can no longer be managed by the bot.
+pub async fn release(
+    quarantined: &QuarantinedMember,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let guild_id = quarantined.guild_id;
+    let member = state
+        .cache_http(guild_id)
+        .member(quarantined.user_id)
+        .await?
+        .context("member not found")?;
+
+    let guild_roles = state.cache.guild_roles(guild_id).await?;
+    let mut roles = member.roles;
+
+    if let Some(quarantine_role) = config.quarantine.role {
+        let highest_role = check_quarantine_role(guild_id, quarantine_role, state).await?;
+        roles.retain(|role| role != &quarantine_role);
+
+        for role_id in &quarantined.roles {
+            let manageable = guild_roles.iter().any(|role| {
+                role.id == *role_id && !role.managed && RoleOrdering::from(role) < highest_role
+            });
+
+            if manageable && !roles.contains(role_id) {
+                roles.push(*role_id);
+            }
+        }
+    }
+
+    state
+        .http
+        .update_guild_member(guild_id, quarantined.user_id)
+        .roles(&roles)
+        .exec()
+        .await?;
+
+    state.cache.delete(quarantined).await?;
+    info!(guild = ?guild_id, user = ?quarantined.user_id, "member released from quarantine");
+
+    Ok(())
+}
+
+/// Ensure the bot can manage the quarantine role.
+///
+/// Returns the bot's highest role, roles lower than it can be managed.
+async fn check_quarantine_role(
+    guild_id: Id<GuildMarker>,
+    quarantine_role: Id<RoleMarker>,
+    state: &ClusterState,
+) -> Result<RoleOrdering, anyhow::Error> {
+    let permissions = state
+        .cache
+        .permissions(guild_id)
+        .await?
+        .current_member()
+        .await?;
+
+    if !permissions.guild().contains(Permissions::MANAGE_ROLES) {
+        bail!("missing permission to manage roles");
+    }
+
+    let role = state
+        .cache_http(guild_id)
+        .role(quarantine_role)
+        .await?
+        .context("quarantine role not found")?;
+
+    let highest_role = permissions.highest_role();
+    if RoleOrdering::from(&role) >= highest_role {
+        bail!("missing permission to manage quarantine role");
+    }
+
+    Ok(highest_role)
+}
+
+/// Send the review message of a quarantined member in the logs channel.
+async fn review_message(
+    quarantined: &QuarantinedMember,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let lang = config.lang();
+    let guild_id = quarantined.guild_id;
+
+    let roles = if quarantined.roles.is_empty() {
+        lang.quarantine_review_no_roles().to_owned()
+    } else {
+        quarantined
+            .roles
+            .iter()
+            .map(|role| role.mention().to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+            .max_len(1024)
+    };
+
+    let embed = EmbedBuilder::new()
+        .title(lang.quarantine_review_title())
+        .color(COLOR_RED)
+        .description(lang.quarantine_review_description(quarantined.user_id.mention()))
+        .field(EmbedFieldBuilder::new(
+            lang.quarantine_review_reason(),
+            quarantined.reason.max_len(1024),
+        ))
+        .field(EmbedFieldBuilder::new(
+            lang.quarantine_review_roles(),
+            roles,
+        ))
+        .build();
+
+    let user_id = quarantined.user_id.to_string();
+    let components = Component::ActionRow(ActionRow {
+        components: vec![
+            Component::Button(Button {
+                custom_id: Some(CustomId::new("quarantine-release", user_id.clone()).to_string()),
+                disabled: false,
+                emoji: None,
+                label: Some(lang.quarantine_release_button().to_owned()),
+                style: ButtonStyle::Success,
+                url: None,
+            }),
+            Component::Button(Button {
+                custom_id: Some(CustomId::new("quarantine-ban", user_id).to_string()),
+                disabled: false,
+                emoji: None,
+                label: Some(lang.quarantine_ban_button().to_owned()),
+                style: ButtonStyle::Danger,
+                url: None,
+            }),
+        ],
+    });
+
+    let channel = guild_logs_channel(state, config, LogCategory::Moderation).await?;
+    state
+        .cache_http(guild_id)
+        .create_message(channel)
+        .await?
+        .embeds(&[embed])?
+        .components(&[components])?
+        .exec()
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,223 @@
+//! Remote actions requested by other services.
+//!
+//! The web service publishes [`RpcRequest`]s when the dashboard or an API key
+//! asks the bot to perform an action. Each bot process receives all the
+//! requests, and only handles the ones of guilds on its shards. The result is
+//! published on the reply channel of the request.
+//!
+//! The permissions of the user responsible for the action are checked as if
+//! the action was performed with a command. Bans are recorded in the
+//! moderation logs, and every handled request is logged.
+
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use raidprotect_model::{
+    cache::{
+        discord::CachedMember,
+        model::rpc::{RpcAction, RpcCaller, RpcError, RpcRequest, RpcResponse},
+    },
+    database::model::{Modlog, ModlogAutomation, ModlogType, ModlogUser},
+};
+use time::OffsetDateTime;
+use tracing::{error, info, instrument};
+use twilight_http::request::AuditLogReason;
+use twilight_model::{
+    guild::Permissions,
+    id::{
+        marker::{GuildMarker, UserMarker},
+        Id,
+    },
+};
+
+use crate::cluster::ClusterState;
+
+/// Listen for requests published by other services.
+///
+/// If the connection to Redis is lost, the listener is restarted after a short
+/// delay.
+pub async fn rpc_listener(state: ClusterState) {
+    loop {
+        if let Err(error) = listen(&state).await {
+            error!(error = ?error, "error while listening for rpc requests");
+        }
+
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
+
+async fn listen(state: &ClusterState) -> Result<(), anyhow::Error> {
+    let mut requests = state
+        .cache
+        .subscribe::<RpcRequest>(RpcRequest::CHANNEL)
+        .await?;
+
+    while let Some(request) = requests.next().await {
+        match request {
+            Ok(request) if state.runs_guild(request.guild_id) => {
+                tokio::spawn(handle_request(request, state.clone()));
+            }
+            Ok(_) => {}
+            Err(error) => error!(error = ?error, "failed to deserialize rpc request"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Perform a requested action and publish the response.
+#[instrument(skip(state))]
+async fn handle_request(request: RpcRequest, state: ClusterState) {
+    let result = match perform(&request, &state).await {
+        Ok(result) => result,
+        Err(error) => {
+            error!(error = ?error, "error while processing rpc request");
+            Err(RpcError::Internal)
+        }
+    };
+
+    info!(
+        guild = ?request.guild_id,
+        caller = ?request.caller,
+        action = ?request.action,
+        result = ?result,
+        "processed rpc request"
+    );
+
+    let response = RpcResponse {
+        id: request.id.clone(),
+        result,
+    };
+
+    if let Err(error) = state
+        .cache
+        .publish(&request.reply_channel(), &response)
+        .await
+    {
+        error!(error = ?error, "failed to publish rpc response");
+    }
+}
+
+async fn perform(
+    request: &RpcRequest,
+    state: &ClusterState,
+) -> Result<Result<(), RpcError>, anyhow::Error> {
+    match &request.action {
+        RpcAction::Ban { user_id, reason } => {
+            ban(request, *user_id, reason.as_deref(), state).await
+        }
+        RpcAction::InvalidateCache => {
+            state.filters.remove(request.guild_id);
+
+            Ok(Ok(()))
+        }
+    }
+}
+
+/// Get the member targeted by a moderation action.
+///
+/// Members of large guilds may not be cached yet, in which case the member is
+/// requested from the gateway to check the role hierarchy.
+async fn target_member(
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    state: &ClusterState,
+) -> Result<Option<CachedMember>, anyhow::Error> {
+    if let Some(member) = state
+        .cache
+        .get::<CachedMember>(&(guild_id, user_id))
+        .await?
+    {
+        return Ok(Some(member));
+    }
+
+    let requested = state.request_members_by_id(guild_id, vec![user_id]).await?;
+    if !requested.members.contains(&user_id) {
+        return Ok(None);
+    }
+
+    state.cache.get::<CachedMember>(&(guild_id, user_id)).await
+}
+
+/// Ban a user and record the ban in the moderation logs.
+async fn ban(
+    request: &RpcRequest,
+    user_id: Id<UserMarker>,
+    reason: Option<&str>,
+    state: &ClusterState,
+) -> Result<Result<(), RpcError>, anyhow::Error> {
+    let guild_id = request.guild_id;
+    let moderator_id = request.caller.user_id();
+
+    let moderator = match state
+        .cache
+        .get::<CachedMember>(&(guild_id, moderator_id))
+        .await?
+    {
+        Some(member) => member,
+        None => return Ok(Err(RpcError::Forbidden)),
+    };
+
+    let permissions = state.cache.permissions(guild_id).await?;
+    let moderator_permissions = permissions.member(moderator_id, &moderator.roles).await?;
+    let bot_permissions = permissions.current_member().await?;
+
+    if !moderator_permissions
+        .guild()
+        .contains(Permissions::BAN_MEMBERS)
+    {
+        return Ok(Err(RpcError::Forbidden));
+    }
+
+    if !bot_permissions.guild().contains(Permissions::BAN_MEMBERS) {
+        return Ok(Err(RpcError::BotMissingPermissions));
+    }
+
+    // Users that are not members of the guild can be banned without checking
+    // the role hierarchy.
+    if let Some(member) = target_member(guild_id, user_id, state).await? {
+        let member_permissions = permissions.member(user_id, &member.roles).await?;
+
+        if member_permissions.is_owner() || !moderator_permissions.can_target(&member_permissions) {
+            return Ok(Err(RpcError::InvalidTarget));
+        }
+
+        if !bot_permissions.can_target(&member_permissions) {
+            return Ok(Err(RpcError::BotMissingPermissions));
+        }
+    }
+
+    let user = state.http.user(user_id).exec().await?.model().await?;
+    let moderator = state.http.user(moderator_id).exec().await?.model().await?;
+
+    let ban = state.http.create_ban(guild_id, user_id);
+    match reason {
+        Some(reason) => ban.reason(reason)?.exec().await?,
+        None => ban.exec().await?,
+    };
+
+    let automation = match &request.caller {
+        RpcCaller::User(_) => None,
+        RpcCaller::ApiKey { name, .. } => Some(ModlogAutomation {
+            rule: "api_key".to_owned(),
+            details: Some(name.clone()),
+        }),
+    };
+
+    let modlog = Modlog {
+        id: None,
+        kind: ModlogType::Ban,
+        guild_id,
+        user: ModlogUser::from(&user),
+        moderator: ModlogUser::from(&moderator),
+        date: OffsetDateTime::now_utc(),
+        reason: reason.map(ToOwned::to_owned),
+        notes: None,
+        automation,
+    };
+
+    state.store.create_modlog(&modlog).await?;
+    super::live::sanction(&modlog, state).await;
+
+    Ok(Ok(()))
+}
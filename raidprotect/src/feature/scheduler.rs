@@ -0,0 +1,288 @@
+//! Delayed jobs scheduler.
+//!
+//! Actions that must be run at a given time (such as removing a temporary ban
+//! or kicking a member that failed the captcha verification) are stored as
+//! [`ScheduledJob`] in the database with [`schedule`], so that they are run
+//! even if the bot restarts in the meantime.
+//!
+//! Due jobs are claimed and run by a background task. Running a job is
+//! idempotent: a job whose target no longer exists (unbanned user, member that
+//! left the guild, deleted channel) is considered completed. Failed jobs are
+//! retried with an exponential backoff, and abandoned after
+//! [`ScheduledJob::MAX_ATTEMPTS`].
+
+use std::time::Duration as StdDuration;
+
+use raidprotect_model::{
+    cache::{
+        discord::http::is_not_found,
+        model::interaction::{PendingCaptcha, WebCaptcha},
+    },
+    database::model::{JobAction, ScheduledJob},
+};
+use time::{Duration, OffsetDateTime};
+use tracing::{debug, error, info, warn};
+use twilight_http::request::AuditLogReason;
+use twilight_model::{
+    guild::Permissions,
+    http::permission_overwrite::{
+        PermissionOverwrite as HttpPermissionOverwrite,
+        PermissionOverwriteType as HttpPermissionOverwriteType,
+    },
+    id::{
+        marker::{ChannelMarker, GuildMarker, UserMarker},
+        Id,
+    },
+};
+
+use crate::{
+    cluster::ClusterState, feature::captcha::update_roles, interaction::util::GuildConfigExt,
+    translations::Lang, util::shutdown::ShutdownSubscriber,
+};
+
+/// Interval between each run of the scheduler task.
+const SCHEDULER_INTERVAL: StdDuration = StdDuration::from_secs(5);
+
+/// Maximum number of jobs run at each run of the scheduler task.
+const MAX_JOBS_PER_RUN: usize = 50;
+
+/// Schedule an action to be run after a delay.
+///
+/// A job previously scheduled for the same action is replaced.
+pub async fn schedule(
+    state: &ClusterState,
+    guild_id: Id<GuildMarker>,
+    action: JobAction,
+    delay: Duration,
+) -> Result<(), anyhow::Error> {
+    let job = ScheduledJob::new(guild_id, action, OffsetDateTime::now_utc() + delay);
+    state.store.schedule_job(&job).await?;
+
+    debug!(job = %job.key, run_at = %job.run_at, "job scheduled");
+
+    Ok(())
+}
+
+/// Run the due jobs periodically.
+pub async fn scheduler_task(state: ClusterState, mut shutdown: ShutdownSubscriber) {
+    let mut interval = tokio::time::interval(SCHEDULER_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {},
+            _ = shutdown.wait_shutdown() => break,
+        }
+
+        if let Err(error) = run_jobs(&state).await {
+            error!(error = ?error, "failed to run scheduled jobs");
+        }
+    }
+}
+
+async fn run_jobs(state: &ClusterState) -> Result<(), anyhow::Error> {
+    for _ in 0..MAX_JOBS_PER_RUN {
+        let job = match state.store.claim_job().await? {
+            Some(job) => job,
+            None => break,
+        };
+
+        match run_job(&job, state).await {
+            Ok(()) => {
+                state.store.complete_job(&job).await?;
+                info!(job = %job.key, "scheduled job completed");
+            }
+            Err(error) if job.attempts >= ScheduledJob::MAX_ATTEMPTS => {
+                state.store.complete_job(&job).await?;
+                error!(error = ?error, job = %job.key, "scheduled job abandoned");
+            }
+            Err(error) => {
+                state.store.retry_job(&job, error.to_string()).await?;
+                warn!(error = ?error, job = %job.key, attempts = job.attempts, "scheduled job failed");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run the action of a job.
+///
+/// Actions whose target no longer exists are considered successful.
+async fn run_job(job: &ScheduledJob, state: &ClusterState) -> Result<(), anyhow::Error> {
+    let lang = match state.store.get_guild(job.guild_id).await? {
+        Some(config) => config.lang(),
+        None => Lang::DEFAULT,
+    };
+
+    let result = match job.action {
+        JobAction::Unban { user_id } => unban(job.guild_id, user_id, lang, state).await,
+        JobAction::Unmute { user_id } => unmute(job.guild_id, user_id, lang, state).await,
+        JobAction::UnlockChannel { channel_id } => {
+            unlock_channel(job.guild_id, channel_id, lang, state).await
+        }
+        JobAction::CaptchaDeadline { user_id } => {
+            captcha_kick(job.guild_id, user_id, lang, state).await
+        }
+        JobAction::WebCaptchaCompleted { user_id } => {
+            web_captcha_completed(job.guild_id, user_id, state).await
+        }
+    };
+
+    match result {
+        Err(error)
+            if error
+                .downcast_ref::<twilight_http::Error>()
+                .is_some_and(is_not_found) =>
+        {
+            debug!(job = %job.key, "scheduled job target not found");
+
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+async fn unban(
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    lang: Lang,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    state
+        .cache_http(guild_id)
+        .delete_ban(user_id)
+        .await?
+        .reason(lang.scheduler_unban_reason())?
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+async fn unmute(
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    lang: Lang,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    state
+        .cache_http(guild_id)
+        .update_guild_member(user_id)
+        .await?
+        .communication_disabled_until(None)?
+        .reason(lang.scheduler_unmute_reason())?
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+/// Allow `@everyone` to send messages in a channel.
+async fn unlock_channel(
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    lang: Lang,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let http = state.cache_http(guild_id);
+    let channel = match http.channel(channel_id).await? {
+        Some(channel) => channel,
+        None => return Ok(()),
+    };
+
+    let everyone = channel
+        .permission_overwrites
+        .unwrap_or_default()
+        .into_iter()
+        .find(|overwrite| overwrite.id == guild_id.cast());
+
+    let everyone = match everyone {
+        Some(everyone) if everyone.deny.contains(Permissions::SEND_MESSAGES) => everyone,
+        _ => return Ok(()),
+    };
+
+    let permission_overwrite = HttpPermissionOverwrite {
+        id: guild_id.cast(),
+        kind: HttpPermissionOverwriteType::Role,
+        allow: Some(everyone.allow),
+        deny: Some(everyone.deny - Permissions::SEND_MESSAGES),
+    };
+
+    http.update_channel_permission(channel_id, &permission_overwrite)
+        .await?
+        .reason(lang.scheduler_unlock_reason())?
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+/// Kick a member that failed or did not complete the captcha verification.
+///
+/// Members that completed the verification in the meantime no longer have a
+/// [`PendingCaptcha`] and are not kicked. Members that solved the captcha code
+/// but did not answer the questionnaire yet are not kicked either.
+async fn captcha_kick(
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    lang: Lang,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let captcha = match state
+        .cache
+        .get::<PendingCaptcha>(&(guild_id, user_id))
+        .await?
+    {
+        Some(captcha) if !captcha.solved => captcha,
+        _ => return Ok(()),
+    };
+
+    let reason = if captcha.expires_at <= OffsetDateTime::now_utc() {
+        lang.captcha_expired_reason()
+    } else {
+        lang.captcha_kick_reason()
+    };
+
+    state
+        .cache_http(guild_id)
+        .remove_guild_member(user_id)
+        .await?
+        .reason(reason)?
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+/// Verify a member that solved the captcha on the web verification page.
+///
+/// The pending captcha and the web token are only deleted once the roles have
+/// been updated, so that a failed attempt is retried. Members that are no
+/// longer pending have already been verified.
+async fn web_captcha_completed(
+    guild_id: Id<GuildMarker>,
+    user_id: Id<UserMarker>,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let captcha = match state
+        .cache
+        .get::<PendingCaptcha>(&(guild_id, user_id))
+        .await?
+    {
+        Some(captcha) => captcha,
+        None => {
+            debug!(guild = ?guild_id, user = ?user_id, "web captcha completed but no pending captcha found");
+            return Ok(());
+        }
+    };
+
+    let config = state.guild_config(guild_id).await?;
+    update_roles(user_id, &config, state).await?;
+
+    state.cache.delete(&captcha).await?;
+    if let Some(token) = &captcha.web_token {
+        state.cache.delete_from::<WebCaptcha>(token).await?;
+    }
+
+    Ok(())
+}
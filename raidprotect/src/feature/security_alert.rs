@@ -0,0 +1,270 @@
+//! Security alerts.
+//!
+//! Adding a bot or granting dangerous permissions to a role are common steps
+//! of a server takeover. If enabled with `/config logs`, an alert is sent in
+//! the moderation logs channel when:
+//!
+//! - a bot account joins the guild, with a button to kick it
+//! - a role gains one of the [`DANGEROUS_PERMISSIONS`], with a button to
+//!   remove the added permissions
+//!
+//! The buttons are handled by the [`SecurityAlertButton`] component. The guild
+//! audit log is used to find the member who performed the action.
+//!
+//! [`SecurityAlertButton`]: crate::interaction::component::SecurityAlertButton
+
+use raidprotect_model::{
+    cache::discord::CachedRole,
+    database::model::{GuildConfig, LogCategory},
+};
+use time::OffsetDateTime;
+use tracing::error;
+use twilight_mention::Mention;
+use twilight_model::{
+    application::component::{button::ButtonStyle, ActionRow, Button, Component},
+    channel::embed::Embed,
+    gateway::payload::incoming::RoleUpdate,
+    guild::{audit_log::AuditLogEventType, Member, Permissions},
+    id::{
+        marker::{GenericMarker, GuildMarker, UserMarker},
+        Id,
+    },
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder};
+
+use super::audit_log::{is_recent, recent_entries};
+use crate::{
+    cluster::ClusterState,
+    interaction::{
+        embed::COLOR_RED,
+        util::{CustomId, GuildConfigExt},
+    },
+    util::guild_logs_channel,
+};
+
+/// Permissions that trigger an alert when granted to a role.
+pub const DANGEROUS_PERMISSIONS: Permissions = Permissions::ADMINISTRATOR
+    .union(Permissions::MANAGE_GUILD)
+    .union(Permissions::MANAGE_ROLES)
+    .union(Permissions::MANAGE_CHANNELS)
+    .union(Permissions::MANAGE_WEBHOOKS)
+    .union(Permissions::BAN_MEMBERS)
+    .union(Permissions::KICK_MEMBERS)
+    .union(Permissions::MENTION_EVERYONE);
+
+/// Names of the [`DANGEROUS_PERMISSIONS`].
+const PERMISSION_NAMES: [(Permissions, &str); 8] = [
+    (Permissions::ADMINISTRATOR, "ADMINISTRATOR"),
+    (Permissions::MANAGE_GUILD, "MANAGE_GUILD"),
+    (Permissions::MANAGE_ROLES, "MANAGE_ROLES"),
+    (Permissions::MANAGE_CHANNELS, "MANAGE_CHANNELS"),
+    (Permissions::MANAGE_WEBHOOKS, "MANAGE_WEBHOOKS"),
+    (Permissions::BAN_MEMBERS, "BAN_MEMBERS"),
+    (Permissions::KICK_MEMBERS, "KICK_MEMBERS"),
+    (Permissions::MENTION_EVERYONE, "MENTION_EVERYONE"),
+];
+
+/// Send an alert when a bot joins a guild.
+pub async fn bot_added(member: &Member, state: &ClusterState) {
+    if !member.user.bot {
+        return;
+    }
+
+    if let Err(error) = bot_added_inner(member, state).await {
+        error!(error = ?error, guild = ?member.guild_id, "failed to send bot added alert");
+    }
+}
+
+async fn bot_added_inner(member: &Member, state: &ClusterState) -> Result<(), anyhow::Error> {
+    let config = state.guild_config(member.guild_id).await?;
+    if !config.logs.security_alerts {
+        return Ok(());
+    }
+
+    let lang = config.lang();
+    let bot = &member.user;
+
+    let mut embed = EmbedBuilder::new()
+        .title(lang.security_bot_added_title())
+        .color(COLOR_RED)
+        .description(lang.security_bot_added_description(
+            bot.id.mention(),
+            format!("{}#{}", bot.name, bot.discriminator()),
+        ));
+
+    let target = Some(bot.id.cast());
+    if let Some(author) = author(member.guild_id, AuditLogEventType::BotAdd, target, state).await? {
+        embed = embed.field(EmbedFieldBuilder::new(
+            lang.security_performed_by(),
+            author.mention().to_string(),
+        ));
+    }
+
+    let embed = embed
+        .footer(EmbedFooterBuilder::new(format!("ID: {}", bot.id)))
+        .build();
+
+    let button = Button {
+        custom_id: Some(CustomId::new("security-kick-bot", bot.id.to_string()).to_string()),
+        disabled: false,
+        emoji: None,
+        label: Some(lang.security_kick_bot_button().to_owned()),
+        style: ButtonStyle::Danger,
+        url: None,
+    };
+
+    send_alert(member.guild_id, embed, button, &config, state).await
+}
+
+/// Send an alert when a role gains dangerous permissions.
+///
+/// The previous cached role is used to find the added permissions, and must
+/// be retrieved before the role is updated in the cache.
+pub async fn role_updated(event: &RoleUpdate, previous: Option<CachedRole>, state: &ClusterState) {
+    let previous = match previous {
+        Some(previous) => previous,
+        None => return,
+    };
+
+    let added = event.role.permissions & DANGEROUS_PERMISSIONS & !previous.permissions;
+    if added.is_empty() {
+        return;
+    }
+
+    if let Err(error) = role_updated_inner(event, added, state).await {
+        error!(error = ?error, guild = ?event.guild_id, "failed to send role permissions alert");
+    }
+}
+
+async fn role_updated_inner(
+    event: &RoleUpdate,
+    added: Permissions,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let config = state.guild_config(event.guild_id).await?;
+    if !config.logs.security_alerts {
+        return Ok(());
+    }
+
+    let lang = config.lang();
+    let role = &event.role;
+
+    let mut embed = EmbedBuilder::new()
+        .title(lang.security_role_permissions_title())
+        .color(COLOR_RED)
+        .description(lang.security_role_permissions_description(role.id.mention()))
+        .field(EmbedFieldBuilder::new(
+            lang.security_added_permissions(),
+            permission_names(added),
+        ));
+
+    let target = Some(role.id.cast());
+    if let Some(author) =
+        author(event.guild_id, AuditLogEventType::RoleUpdate, target, state).await?
+    {
+        embed = embed.field(EmbedFieldBuilder::new(
+            lang.security_performed_by(),
+            author.mention().to_string(),
+        ));
+    }
+
+    let embed = embed
+        .footer(EmbedFooterBuilder::new(format!("ID: {}", role.id)))
+        .build();
+
+    let button = Button {
+        custom_id: Some(
+            CustomId::new(
+                "security-revert-role",
+                format!("{}:{}", role.id, added.bits()),
+            )
+            .to_string(),
+        ),
+        disabled: false,
+        emoji: None,
+        label: Some(lang.security_revert_role_button().to_owned()),
+        style: ButtonStyle::Danger,
+        url: None,
+    };
+
+    send_alert(event.guild_id, embed, button, &config, state).await
+}
+
+/// Find the member who performed an action with the audit log.
+async fn author(
+    guild_id: Id<GuildMarker>,
+    action_type: AuditLogEventType,
+    target: Option<Id<GenericMarker>>,
+    state: &ClusterState,
+) -> Result<Option<Id<UserMarker>>, anyhow::Error> {
+    let audit_log = match recent_entries(guild_id, action_type, state).await? {
+        Some(audit_log) => audit_log,
+        None => return Ok(None),
+    };
+
+    let now = OffsetDateTime::now_utc();
+    let author = audit_log
+        .entries
+        .iter()
+        .find(|entry| entry.target_id == target && is_recent(entry, now))
+        .and_then(|entry| entry.user_id);
+
+    Ok(author)
+}
+
+/// Send an alert in the moderation logs channel.
+async fn send_alert(
+    guild_id: Id<GuildMarker>,
+    embed: Embed,
+    button: Button,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let components = Component::ActionRow(ActionRow {
+        components: vec![Component::Button(button)],
+    });
+
+    let channel = guild_logs_channel(state, config, LogCategory::Moderation).await?;
+    state
+        .cache_http(guild_id)
+        .create_message(channel)
+        .await?
+        .embeds(&[embed])?
+        .components(&[components])?
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+/// Format the names of dangerous permissions.
+fn permission_names(permissions: Permissions) -> String {
+    PERMISSION_NAMES
+        .iter()
+        .filter(|(permission, _)| permissions.contains(*permission))
+        .map(|(_, name)| format!("`{name}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_names() {
+        let permissions = Permissions::ADMINISTRATOR | Permissions::BAN_MEMBERS;
+
+        assert_eq!(
+            permission_names(permissions),
+            "`ADMINISTRATOR`, `BAN_MEMBERS`"
+        );
+        assert_eq!(
+            PERMISSION_NAMES
+                .iter()
+                .fold(Permissions::empty(), |all, (permission, _)| all
+                    | *permission),
+            DANGEROUS_PERMISSIONS
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Emergency slowmode.
+//!
+//! When the anti-spam detects a spam wave (similar messages sent by many
+//! accounts), the slowmode of the affected channels is raised to the
+//! configured `emergency_slowmode`. The previous slowmode is restored once no
+//! spam wave has been detected in the channel for [`SLOWMODE_DURATION`].
+//!
+//! The state of each emergency slowmode is stored in Redis, and the previous
+//! slowmodes are restored by a background task, so restores survive restarts.
+
+use std::time::Duration as StdDuration;
+
+use anyhow::Context;
+use raidprotect_model::{cache::model::channel::EmergencySlowmode, database::model::GuildConfig};
+use time::{Duration, OffsetDateTime};
+use tracing::{error, info};
+use twilight_http::request::AuditLogReason;
+use twilight_model::id::{
+    marker::{ChannelMarker, GuildMarker},
+    Id,
+};
+
+use crate::{
+    cluster::ClusterState, interaction::util::GuildConfigExt, translations::Lang,
+    util::shutdown::ShutdownSubscriber,
+};
+
+/// Duration without spam wave before the previous slowmode is restored.
+pub const SLOWMODE_DURATION: Duration = Duration::minutes(10);
+
+/// Interval between each run of the restore task.
+const RESTORE_INTERVAL: StdDuration = StdDuration::from_secs(60);
+
+/// Enable the emergency slowmode in channels affected by a spam wave.
+///
+/// If the emergency slowmode is already enabled in a channel, its restore is
+/// delayed. Channels with a higher slowmode are not updated.
+pub async fn enable(
+    guild_id: Id<GuildMarker>,
+    channels: &[Id<ChannelMarker>],
+    config: &GuildConfig,
+    state: &ClusterState,
+) {
+    let slowmode = config.antispam.emergency_slowmode;
+    if slowmode == 0 {
+        return;
+    }
+
+    let restore_at = OffsetDateTime::now_utc() + SLOWMODE_DURATION;
+
+    for channel_id in channels {
+        if let Err(error) = enable_channel(
+            guild_id,
+            *channel_id,
+            slowmode,
+            restore_at,
+            config.lang(),
+            state,
+        )
+        .await
+        {
+            error!(error = ?error, channel = ?channel_id, "failed to enable emergency slowmode");
+        }
+    }
+}
+
+async fn enable_channel(
+    guild_id: Id<GuildMarker>,
+    channel_id: Id<ChannelMarker>,
+    slowmode: u16,
+    restore_at: OffsetDateTime,
+    lang: Lang,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    // Delay the restore if the slowmode is already enabled.
+    if let Some(mut emergency) = state.cache.get::<EmergencySlowmode>(&channel_id).await? {
+        emergency.restore_at = restore_at;
+        state.cache.set(&emergency).await?;
+
+        return Ok(());
+    }
+
+    let channel = state
+        .cache_http(guild_id)
+        .channel(channel_id)
+        .await?
+        .context("channel not found")?;
+
+    let previous = channel.rate_limit_per_user.unwrap_or(0);
+    if previous >= slowmode {
+        return Ok(());
+    }
+
+    // The state is stored before updating the channel to ensure the previous
+    // slowmode is restored.
+    let emergency = EmergencySlowmode {
+        channel_id,
+        guild_id,
+        previous,
+        restore_at,
+    };
+    state.cache.set(&emergency).await?;
+
+    state
+        .cache_http(guild_id)
+        .update_channel(channel_id)
+        .await?
+        .rate_limit_per_user(slowmode)?
+        .reason(lang.slowmode_enable_reason())?
+        .exec()
+        .await?;
+
+    info!(channel = ?channel_id, slowmode, "emergency slowmode enabled");
+
+    Ok(())
+}
+
+/// Restore the previous slowmode of channels periodically.
+pub async fn slowmode_task(state: ClusterState, mut shutdown: ShutdownSubscriber) {
+    let mut interval = tokio::time::interval(RESTORE_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {},
+            _ = shutdown.wait_shutdown() => break,
+        }
+
+        if let Err(error) = restore_slowmodes(&state).await {
+            error!(error = ?error, "failed to restore emergency slowmodes");
+        }
+    }
+}
+
+async fn restore_slowmodes(state: &ClusterState) -> Result<(), anyhow::Error> {
+    let now = OffsetDateTime::now_utc();
+    let slowmodes = state
+        .cache
+        .scan::<EmergencySlowmode>(EmergencySlowmode::KEY_PATTERN)
+        .await?;
+
+    for emergency in slowmodes {
+        if emergency.restore_at > now {
+            continue;
+        }
+
+        // The restore is not retried if it fails, since most errors are caused
+        // by the channel being deleted or missing permissions.
+        if let Err(error) = restore_slowmode(&emergency, state).await {
+            error!(error = ?error, channel = ?emergency.channel_id, "failed to restore slowmode");
+        }
+
+        state.cache.delete(&emergency).await?;
+    }
+
+    Ok(())
+}
+
+/// Restore the previous slowmode of a channel.
+async fn restore_slowmode(
+    emergency: &EmergencySlowmode,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let lang = state.guild_config(emergency.guild_id).await?.lang();
+
+    state
+        .cache_http(emergency.guild_id)
+        .update_channel(emergency.channel_id)
+        .await?
+        .rate_limit_per_user(emergency.previous)?
+        .reason(lang.slowmode_restore_reason())?
+        .exec()
+        .await?;
+
+    info!(channel = ?emergency.channel_id, "emergency slowmode restored");
+
+    Ok(())
+}
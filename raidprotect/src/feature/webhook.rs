@@ -0,0 +1,247 @@
+//! Webhook messages checks.
+//!
+//! Messages sent by webhooks are usually ignored like other bot messages, but
+//! leaked webhook URLs are commonly abused to spam servers with phishing
+//! links. If enabled with `/config webhooks`, webhook messages are checked
+//! with:
+//!
+//! - the spam engine, using the anti-spam thresholds of the guild
+//! - the [domain filter], if enabled
+//! - the [phishing] heuristic, using the configured threshold
+//!
+//! Webhooks cannot be sanctioned like members, so abusive messages are deleted
+//! and the webhook is also deleted if the configured action is
+//! [`WebhookAction::Disable`]. Channels exempted from the anti-spam are also
+//! exempted from these checks.
+//!
+//! [domain filter]: super::domains
+//! [phishing]: super::phishing
+
+use raidprotect_anti_spam::{
+    engine::SpamMessage,
+    language::Language,
+    phishing::{phishing_score, PhishingMessage},
+    SpamVerdict,
+};
+use raidprotect_model::{
+    cache::model::message::{CachedMessage, MessageLink},
+    database::model::{GuildConfig, LogCategory, WebhookAction},
+};
+use tracing::{error, info};
+use twilight_mention::Mention;
+use twilight_model::id::{
+    marker::{GuildMarker, WebhookMarker},
+    Id,
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+use super::{
+    antispam::{delete_message, is_exempt, spam_config},
+    domains::{blocked_domain, BlockedList},
+};
+use crate::{
+    cluster::ClusterState,
+    interaction::{embed::COLOR_RED, util::GuildConfigExt},
+    translations::Lang,
+    util::{guild_logs_channel, TextProcessExt},
+};
+
+/// Reason why a webhook message is considered as abusive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Abuse {
+    /// Message detected by the spam engine.
+    Spam(SpamVerdict),
+    /// Message with a link to a blocked domain.
+    Domain { domain: String, list: BlockedList },
+    /// Message reaching the phishing threshold.
+    Phishing { score: u8 },
+}
+
+impl Abuse {
+    /// Description of the abuse.
+    fn description(&self, lang: Lang) -> String {
+        match self {
+            Abuse::Spam(verdict) => lang.webhooks_abuse_spam(verdict.rule().unwrap_or_default()),
+            Abuse::Domain { domain, list } => match list {
+                BlockedList::Guild => lang.webhooks_abuse_domain(domain),
+                BlockedList::Phishing => lang.webhooks_abuse_phishing_domain(domain),
+            },
+            Abuse::Phishing { score } => lang.webhooks_abuse_phishing(score),
+        }
+    }
+}
+
+/// Check an incoming message sent by a webhook.
+///
+/// `webhook_name` is the name displayed as the message author.
+///
+/// Returns whether the message has been deleted.
+pub async fn check_message(
+    message: &CachedMessage,
+    webhook_id: Id<WebhookMarker>,
+    webhook_name: &str,
+    guild_id: Id<GuildMarker>,
+    state: &ClusterState,
+) -> Result<bool, anyhow::Error> {
+    let config = state.guild_config(guild_id).await?;
+    if !config.webhooks.enabled {
+        return Ok(false);
+    }
+
+    // Webhooks have no roles, only exempted channels are ignored.
+    if is_exempt(&config.antispam, message.channel_id, &[]) {
+        return Ok(false);
+    }
+
+    let abuse = match find_abuse(message, guild_id, &config, state) {
+        Some(abuse) => abuse,
+        None => return Ok(false),
+    };
+
+    info!(guild = ?guild_id, webhook = ?webhook_id, abuse = ?abuse, "webhook abuse detected");
+
+    let http = state.cache_http(guild_id);
+    if let Err(error) = delete_message(message, &http, state).await {
+        error!(error = ?error, "failed to delete webhook message");
+    }
+
+    let mut deleted = false;
+    if config.webhooks.action == WebhookAction::Disable {
+        match http
+            .delete_webhook(message.channel_id, webhook_id)
+            .await?
+            .exec()
+            .await
+        {
+            Ok(_) => deleted = true,
+            Err(error) => error!(error = ?error, "failed to delete webhook"),
+        }
+    }
+
+    let webhook = Webhook {
+        id: webhook_id,
+        name: webhook_name,
+        deleted,
+    };
+
+    if let Err(error) = log_message(message, guild_id, &webhook, &abuse, &config, state).await {
+        error!(error = ?error, "failed to log webhook message");
+    }
+
+    Ok(true)
+}
+
+/// Webhook that sent an abusive message.
+struct Webhook<'a> {
+    id: Id<WebhookMarker>,
+    name: &'a str,
+    /// Whether the webhook has been deleted.
+    deleted: bool,
+}
+
+/// Find the reason why a webhook message is abusive, if any.
+fn find_abuse(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Option<Abuse> {
+    let verdict = state.antispam.check(
+        SpamMessage {
+            guild_id,
+            author_id: message.author_id,
+            channel_id: message.channel_id,
+            timestamp: message.timestamp,
+            content: &message.content,
+            mentions: message.mention_users.len()
+                + message.mention_roles.len()
+                + usize::from(message.mention_everyone),
+            default_language: Language::from_code(&config.lang),
+        },
+        &spam_config(&config.antispam),
+    );
+
+    if verdict.is_spam() {
+        return Some(Abuse::Spam(verdict));
+    }
+
+    if config.domains.enabled {
+        let blocked = message
+            .links
+            .iter()
+            .filter_map(|link| match link {
+                MessageLink::Media(url) | MessageLink::Other(url) => url.host_str(),
+                MessageLink::Invite(_) => None,
+            })
+            .find_map(|host| blocked_domain(host, config, state));
+
+        if let Some((domain, list)) = blocked {
+            return Some(Abuse::Domain { domain, list });
+        }
+    }
+
+    let score = phishing_score(PhishingMessage {
+        author_id: message.author_id,
+        timestamp: message.timestamp,
+        content: &message.content,
+        mention_everyone: message.mention_everyone,
+    });
+
+    if score.exceeds(config.phishing.threshold) {
+        return Some(Abuse::Phishing {
+            score: score.score(),
+        });
+    }
+
+    None
+}
+
+/// Log a deleted webhook message in the logs channel.
+async fn log_message(
+    message: &CachedMessage,
+    guild_id: Id<GuildMarker>,
+    webhook: &Webhook<'_>,
+    abuse: &Abuse,
+    config: &GuildConfig,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let lang = config.lang();
+
+    let action = if webhook.deleted {
+        lang.webhooks_action_disable()
+    } else {
+        lang.webhooks_action_delete()
+    };
+
+    let mut embed = EmbedBuilder::new()
+        .title(lang.webhooks_deleted_title())
+        .color(COLOR_RED)
+        .description(lang.webhooks_deleted_description(
+            message.channel_id.mention(),
+            webhook.id,
+            webhook.name.max_len(100),
+        ))
+        .field(
+            EmbedFieldBuilder::new(lang.webhooks_deleted_reason(), abuse.description(lang))
+                .inline(),
+        )
+        .field(EmbedFieldBuilder::new(lang.webhooks_deleted_action(), action).inline());
+
+    if !message.content.is_empty() {
+        embed = embed.field(EmbedFieldBuilder::new(
+            lang.webhooks_deleted_content(),
+            message.content.max_len(1024),
+        ));
+    }
+
+    let channel = guild_logs_channel(state, config, LogCategory::Messages).await?;
+    state
+        .cache_http(guild_id)
+        .create_message(channel)
+        .await?
+        .embeds(&[embed.build()])?
+        .exec()
+        .await?;
+
+    Ok(())
+}
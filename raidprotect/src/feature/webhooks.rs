@@ -0,0 +1,427 @@
+//! Outgoing webhook notifications.
+//!
+//! Guilds can register an HTTPS URL (see [`NotificationsConfig`]) that
+//! receives a JSON [`Payload`] for each sanction, raid alert and captcha
+//! failure. This is unrelated to the protection against abusive Discord
+//! webhooks (see [`webhook`](super::webhook)).
+//!
+//! Payloads are signed with HMAC-SHA256 using the secret of the guild, the
+//! hex-encoded signature is sent in the [`SIGNATURE_HEADER`] header.
+//!
+//! Deliveries are sent to the [`WebhookQueue`] and run by a background task.
+//! Failed deliveries are retried with an exponential backoff, and abandoned
+//! after [`MAX_ATTEMPTS`]. Client errors other than timeouts and rate limits
+//! are not retried. The queue is kept in memory: pending deliveries are lost
+//! if the bot restarts.
+//!
+//! The webhook host is resolved with a [`PublicResolver`], so that requests
+//! are never sent to the internal network of the bot, even if the domain
+//! resolves to a private address.
+
+use std::{
+    future::Future,
+    io,
+    net::{IpAddr, SocketAddr},
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+    vec,
+};
+
+use anyhow::anyhow;
+use hmac::{Hmac, Mac};
+use hyper::{
+    client::{
+        connect::dns::{GaiResolver, Name},
+        HttpConnector,
+    },
+    header,
+    service::Service,
+    Body, Client, Method, Request, StatusCode,
+};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use raidprotect_model::{
+    cache::model::live::{CaptchaOutcome, LiveEvent},
+    database::model::NotificationsConfig,
+};
+use serde::Serialize;
+use sha2::Sha256;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use tracing::{debug, error, warn};
+use twilight_model::id::{marker::GuildMarker, Id};
+use url::{Host, Url};
+
+use crate::{cluster::ClusterState, util::shutdown::ShutdownSubscriber};
+
+/// Maximum number of deliveries waiting in the queue.
+const QUEUE_CAPACITY: usize = 1000;
+
+/// Maximum number of attempts of a delivery.
+pub const MAX_ATTEMPTS: u32 = 5;
+
+/// Maximum duration of a delivery request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Header containing the signature of the payload.
+pub const SIGNATURE_HEADER: &str = "X-RaidProtect-Signature";
+
+/// HTTP client used to deliver the payloads.
+type WebhookClient = Client<HttpsConnector<HttpConnector<PublicResolver>>>;
+
+/// Payload sent to the webhooks.
+#[derive(Debug, Serialize)]
+pub struct Payload<'a> {
+    /// Guild where the event happened.
+    guild_id: Id<GuildMarker>,
+    /// Unix timestamp of the event, used to detect replayed payloads.
+    timestamp: i64,
+    /// The notified event.
+    event: &'a LiveEvent,
+}
+
+/// Queue of pending deliveries.
+///
+/// Created with [`WebhookQueue::new`], along with the [`WebhookDeliveries`]
+/// consumed by [`webhooks_task`].
+#[derive(Debug, Clone)]
+pub struct WebhookQueue {
+    sender: mpsc::Sender<Delivery>,
+}
+
+/// Receiving end of the [`WebhookQueue`].
+#[derive(Debug)]
+pub struct WebhookDeliveries {
+    receiver: mpsc::Receiver<Delivery>,
+}
+
+/// Payload to deliver to a webhook.
+#[derive(Debug)]
+struct Delivery {
+    url: String,
+    secret: String,
+    body: Vec<u8>,
+}
+
+impl WebhookQueue {
+    /// Initialize a new [`WebhookQueue`].
+    pub fn new() -> (Self, WebhookDeliveries) {
+        let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+
+        (Self { sender }, WebhookDeliveries { receiver })
+    }
+
+    /// Queue a delivery.
+    ///
+    /// The delivery is dropped if the queue is full.
+    fn push(&self, delivery: Delivery) {
+        if let Err(error) = self.sender.try_send(delivery) {
+            warn!(error = %error, "failed to queue webhook delivery");
+        }
+    }
+}
+
+/// Notify the webhook of a guild of an event.
+///
+/// Only sanctions, raid alerts and captcha failures are notified.
+pub async fn notify(guild_id: Id<GuildMarker>, event: &LiveEvent, state: &ClusterState) {
+    if let LiveEvent::Captcha {
+        outcome: CaptchaOutcome::Passed,
+        ..
+    } = event
+    {
+        return;
+    }
+
+    if let Err(error) = notify_inner(guild_id, event, state).await {
+        error!(error = ?error, guild = ?guild_id, "failed to notify webhook");
+    }
+}
+
+async fn notify_inner(
+    guild_id: Id<GuildMarker>,
+    event: &LiveEvent,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    let config = state.guild_config(guild_id).await?.notifications;
+
+    let (url, secret) = match config {
+        NotificationsConfig {
+            url: Some(url),
+            secret: Some(secret),
+        } => (url, secret),
+        _ => return Ok(()),
+    };
+
+    let payload = Payload {
+        guild_id,
+        timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+        event,
+    };
+
+    state.webhooks.push(Delivery {
+        url,
+        secret,
+        body: serde_json::to_vec(&payload)?,
+    });
+
+    Ok(())
+}
+
+/// Run the queued deliveries.
+///
+/// Each delivery is run in a separate task, so that retries do not delay the
+/// other deliveries.
+pub async fn webhooks_task(mut deliveries: WebhookDeliveries, mut shutdown: ShutdownSubscriber) {
+    let mut http = HttpConnector::new_with_resolver(PublicResolver::new());
+    http.enforce_http(false);
+
+    let connector = HttpsConnectorBuilder::new()
+        .with_webpki_roots()
+        .https_only()
+        .enable_http1()
+        .wrap_connector(http);
+    let client = Client::builder().build(connector);
+
+    loop {
+        let delivery = tokio::select! {
+            delivery = deliveries.receiver.recv() => delivery,
+            _ = shutdown.wait_shutdown() => break,
+        };
+
+        match delivery {
+            Some(delivery) => {
+                tokio::spawn(deliver(client.clone(), delivery));
+            }
+            None => break,
+        }
+    }
+}
+
+/// Deliver a payload, retrying failed attempts.
+async fn deliver(client: WebhookClient, delivery: Delivery) {
+    for attempt in 1..=MAX_ATTEMPTS {
+        let error = match tokio::time::timeout(REQUEST_TIMEOUT, send(&client, &delivery)).await {
+            Ok(Ok(status)) if status.is_success() => return,
+            Ok(Ok(status)) if !is_retryable(status) => {
+                warn!(url = %delivery.url, status = %status, "webhook delivery rejected");
+                return;
+            }
+            Ok(Ok(status)) => anyhow!("webhook responded with status {status}"),
+            Ok(Err(error)) => error,
+            Err(_) => anyhow!("request timed out"),
+        };
+
+        debug!(error = ?error, url = %delivery.url, attempt, "webhook delivery failed");
+
+        if attempt < MAX_ATTEMPTS {
+            tokio::time::sleep(retry_delay(attempt)).await;
+        }
+    }
+
+    warn!(url = %delivery.url, "webhook delivery abandoned after {MAX_ATTEMPTS} attempts");
+}
+
+/// Send a payload to a webhook.
+///
+/// Returns the status of the response.
+async fn send(client: &WebhookClient, delivery: &Delivery) -> Result<StatusCode, anyhow::Error> {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(&delivery.url)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::USER_AGENT, "RaidProtect-Webhooks")
+        .header(
+            SIGNATURE_HEADER,
+            format!("sha256={}", signature(&delivery.secret, &delivery.body)),
+        )
+        .body(Body::from(delivery.body.clone()))?;
+
+    let response = client.request(request).await?;
+
+    Ok(response.status())
+}
+
+/// Whether a delivery that failed with a status should be retried.
+///
+/// Client errors are not retried, except timeouts and rate limits.
+fn is_retryable(status: StatusCode) -> bool {
+    !status.is_client_error()
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Delay before the next attempt of a failed delivery.
+///
+/// The delay doubles after each attempt, starting at 2 seconds.
+fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_secs(2_u64.pow(attempt))
+}
+
+/// Compute the hex-encoded HMAC-SHA256 signature of a payload.
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts keys of any size");
+    mac.update(body);
+
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Parse and validate a webhook URL.
+///
+/// Only HTTPS URLs with a domain name are allowed. The addresses the domain
+/// resolves to are checked when the payloads are delivered (see
+/// [`PublicResolver`]).
+pub fn parse_url(url: &str) -> Option<Url> {
+    let url = Url::parse(url).ok()?;
+
+    if url.scheme() != "https" {
+        return None;
+    }
+
+    match url.host()? {
+        Host::Domain(domain) if domain.contains('.') && domain.parse::<IpAddr>().is_err() => {}
+        _ => return None,
+    }
+
+    Some(url)
+}
+
+/// DNS resolver ignoring the non-public addresses.
+///
+/// Webhook URLs are set by the guilds, so their domain could resolve to the
+/// internal network of the bot. Only the public addresses are returned, and
+/// the resolution fails if there is none.
+#[derive(Debug, Clone)]
+struct PublicResolver(GaiResolver);
+
+impl PublicResolver {
+    /// Initialize a new [`PublicResolver`] using `getaddrinfo`.
+    fn new() -> Self {
+        Self(GaiResolver::new())
+    }
+}
+
+impl Service<Name> for PublicResolver {
+    type Response = vec::IntoIter<SocketAddr>;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        let resolving = self.0.call(name);
+
+        Box::pin(async move {
+            let addrs: Vec<_> = resolving
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect();
+
+            if addrs.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "webhook host does not resolve to a public address",
+                ));
+            }
+
+            Ok(addrs.into_iter())
+        })
+    }
+}
+
+/// Whether an address is publicly routable.
+///
+/// Private, loopback, link-local, shared (carrier-grade NAT) and unspecified
+/// addresses are rejected.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                let segment = ip.segments()[0];
+
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || (segment & 0xfe00) == 0xfc00 // unique local
+                    || (segment & 0xffc0) == 0xfe80) // link-local
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            signature("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_parse_url() {
+        assert!(parse_url("https://example.com/webhook").is_some());
+        assert!(parse_url("http://example.com/webhook").is_none());
+        assert!(parse_url("https://localhost/webhook").is_none());
+        assert!(parse_url("https://127.0.0.1/webhook").is_none());
+        assert!(parse_url("https://[::1]/webhook").is_none());
+        assert!(parse_url("not a url").is_none());
+    }
+
+    #[test]
+    fn test_is_public() {
+        for ip in ["93.184.216.34", "2606:2800:220:1:248:1893:25c8:1946"] {
+            assert!(is_public(ip.parse().unwrap()), "{ip}");
+        }
+
+        for ip in [
+            "10.0.0.1",
+            "172.16.0.1",
+            "192.168.1.1",
+            "127.0.0.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(is_retryable(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(is_retryable(StatusCode::REQUEST_TIMEOUT));
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retryable(StatusCode::NOT_FOUND));
+        assert!(!is_retryable(StatusCode::GONE));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(1).as_secs(), 2);
+        assert_eq!(retry_delay(4).as_secs(), 16);
+    }
+}
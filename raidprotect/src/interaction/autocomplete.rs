@@ -0,0 +1,158 @@
+//! Autocomplete interactions.
+//!
+//! Command options registered with `autocomplete = true` send an autocomplete
+//! interaction each time the user types in them. These interactions are
+//! dispatched by command and option name (see `handle_autocomplete`), and the
+//! handler responds with a list of suggestions.
+
+use anyhow::bail;
+use twilight_model::application::{
+    command::CommandOptionChoice,
+    interaction::{
+        application_command::{CommandDataOption, CommandOptionValue},
+        Interaction, InteractionData,
+    },
+};
+
+/// Maximum number of choices allowed by Discord.
+const MAX_CHOICES: usize = 25;
+
+/// Context of an autocomplete interaction.
+#[derive(Debug, Clone)]
+pub struct AutocompleteContext {
+    /// Name of the command.
+    pub command: String,
+    /// Name of the subcommand of the focused option, if any.
+    pub subcommand: Option<String>,
+    /// Name of the focused option.
+    pub option: String,
+    /// Value entered by the user in the focused option.
+    pub value: String,
+    /// Other options of the (sub)command of the focused option.
+    pub options: Vec<CommandDataOption>,
+}
+
+impl AutocompleteContext {
+    /// Initialize a new [`AutocompleteContext`] from an [`Interaction`].
+    pub fn from_interaction(interaction: &Interaction) -> Result<Self, anyhow::Error> {
+        let data = match &interaction.data {
+            Some(InteractionData::ApplicationCommand(data)) => data,
+            _ => bail!("expected application command data"),
+        };
+
+        let mut options = &data.options;
+        let mut subcommand = None;
+        while let Some((name, nested)) = options.iter().find_map(|option| match &option.value {
+            CommandOptionValue::SubCommand(options)
+            | CommandOptionValue::SubCommandGroup(options) => Some((&option.name, options)),
+            _ => None,
+        }) {
+            subcommand = Some(name.clone());
+            options = nested;
+        }
+
+        let (option, value) = match options.iter().find_map(|option| match &option.value {
+            CommandOptionValue::Focused(value, _) => Some((option.name.clone(), value.clone())),
+            _ => None,
+        }) {
+            Some(focused) => focused,
+            None => bail!("missing focused option"),
+        };
+
+        Ok(Self {
+            command: data.name.clone(),
+            subcommand,
+            option,
+            value,
+            options: options.clone(),
+        })
+    }
+
+    /// Get the value of a string option entered by the user.
+    pub fn string_option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .find(|option| option.name == name)
+            .and_then(|option| match &option.value {
+                CommandOptionValue::String(value) => Some(&**value),
+                _ => None,
+            })
+    }
+}
+
+/// Build the choices matching the value entered by the user.
+///
+/// Values containing the entered value (case insensitive) are returned, up to
+/// the maximum number of choices allowed by Discord.
+pub fn string_choices<'a>(
+    values: impl IntoIterator<Item = &'a str>,
+    input: &str,
+) -> Vec<CommandOptionChoice> {
+    let input = input.trim().to_lowercase();
+
+    values
+        .into_iter()
+        .filter(|value| value.to_lowercase().contains(&input))
+        .take(MAX_CHOICES)
+        .map(|value| CommandOptionChoice::String {
+            name: value.to_owned(),
+            name_localizations: None,
+            value: value.to_owned(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_context_subcommand() {
+        let interaction: Interaction = serde_json::from_value(json!({
+            "id": "3",
+            "application_id": "1",
+            "type": 4,
+            "data": {
+                "id": "4",
+                "name": "killswitch",
+                "type": 1,
+                "options": [{
+                    "name": "enable",
+                    "type": 1,
+                    "options": [
+                        { "name": "kind", "type": 3, "value": "automation" },
+                        { "name": "name", "type": 3, "value": "anti", "focused": true },
+                    ],
+                }],
+            },
+            "token": "token",
+            "version": 1,
+        }))
+        .unwrap();
+
+        let ctx = AutocompleteContext::from_interaction(&interaction).unwrap();
+
+        assert_eq!(ctx.command, "killswitch");
+        assert_eq!(ctx.subcommand.as_deref(), Some("enable"));
+        assert_eq!(ctx.option, "name");
+        assert_eq!(ctx.value, "anti");
+        assert_eq!(ctx.string_option("kind"), Some("automation"));
+    }
+
+    #[test]
+    fn test_string_choices() {
+        let choices = string_choices(["kick", "killswitch", "help"], " KI");
+        let names = choices
+            .iter()
+            .map(|choice| match choice {
+                CommandOptionChoice::String { name, .. } => &**name,
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, ["kick", "killswitch"]);
+        assert_eq!(string_choices((0..50).map(|_| "help"), "").len(), 25);
+    }
+}
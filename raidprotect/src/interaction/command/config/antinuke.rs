@@ -0,0 +1,254 @@
+//! Anti-nuke configuration commands.
+
+use raidprotect_model::database::model::AntiNukeConfig;
+use time::Duration;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::Mention;
+use twilight_model::id::{marker::UserMarker, Id};
+use twilight_util::builder::embed::EmbedBuilder;
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations,
+    interaction::{
+        embed::{self, COLOR_SUCCESS},
+        response::InteractionResponse,
+        util::GuildInteractionContext,
+    },
+    util::format_duration,
+};
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "antinuke",
+    desc = "Configure the RaidProtect anti-nuke protection",
+    desc_localizations = "antinuke_description"
+)]
+pub enum AntiNukeConfigCommand {
+    #[command(name = "enable")]
+    Enable(AntiNukeEnableCommand),
+    #[command(name = "disable")]
+    Disable(AntiNukeDisableCommand),
+    #[command(name = "limit")]
+    Limit(AntiNukeLimitCommand),
+    #[command(name = "trust")]
+    Trust(AntiNukeTrustCommand),
+    #[command(name = "untrust")]
+    Untrust(AntiNukeUntrustCommand),
+}
+
+desc_localizations!(antinuke_description);
+
+impl AntiNukeConfigCommand {
+    pub(super) async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            AntiNukeConfigCommand::Enable(command) => command.exec(ctx, state).await,
+            AntiNukeConfigCommand::Disable(command) => command.exec(ctx, state).await,
+            AntiNukeConfigCommand::Limit(command) => command.exec(ctx, state).await,
+            AntiNukeConfigCommand::Trust(command) => command.exec(ctx, state).await,
+            AntiNukeConfigCommand::Untrust(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "enable",
+    desc = "Enable the RaidProtect anti-nuke protection",
+    desc_localizations = "antinuke_enable_description"
+)]
+pub struct AntiNukeEnableCommand;
+
+desc_localizations!(antinuke_enable_description);
+
+impl AntiNukeEnableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if config.antinuke.enabled {
+            return Ok(embed::antinuke::already_enabled(ctx.lang));
+        }
+
+        config.antinuke.enabled = true;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.antinuke_enable_confirm_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "disable",
+    desc = "Disable the RaidProtect anti-nuke protection",
+    desc_localizations = "antinuke_disable_description"
+)]
+pub struct AntiNukeDisableCommand;
+
+desc_localizations!(antinuke_disable_description);
+
+impl AntiNukeDisableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if !config.antinuke.enabled {
+            return Ok(embed::antinuke::not_enabled(ctx.lang));
+        }
+
+        config.antinuke.enabled = false;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.antinuke_disable_confirm_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "limit",
+    desc = "Set the maximum number of destructive actions a member can perform",
+    desc_localizations = "antinuke_limit_description"
+)]
+pub struct AntiNukeLimitCommand {
+    /// Maximum number of destructive actions.
+    #[command(min_value = 1, max_value = 50)]
+    max: i64,
+    /// Interval in which the actions are counted (in seconds).
+    #[command(min_value = 10, max_value = 3600)]
+    interval: i64,
+}
+
+desc_localizations!(antinuke_limit_description);
+
+impl AntiNukeLimitCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        config.antinuke.max_actions = self.max.try_into()?;
+        config.antinuke.interval = self.interval.try_into()?;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.antinuke_limit_confirm_description(
+                format_duration(Duration::seconds(self.interval), ctx.lang),
+                self.max,
+            ))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "trust",
+    desc = "Allow a member to perform destructive actions without limits",
+    desc_localizations = "antinuke_trust_description"
+)]
+pub struct AntiNukeTrustCommand {
+    /// Member to trust.
+    user: Id<UserMarker>,
+}
+
+desc_localizations!(antinuke_trust_description);
+
+impl AntiNukeTrustCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        let antinuke = &mut config.antinuke;
+
+        if antinuke.trusted_users.contains(&self.user) {
+            return Ok(embed::antinuke::trusted_already_added(ctx.lang));
+        }
+
+        if antinuke.trusted_users.len() >= AntiNukeConfig::MAX_TRUSTED_LEN {
+            return Ok(embed::antinuke::trusted_too_many(ctx.lang));
+        }
+
+        antinuke.trusted_users.push(self.user);
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(
+                ctx.lang
+                    .antinuke_trust_confirm_description(self.user.mention()),
+            )
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "untrust",
+    desc = "Remove a member from the trusted members",
+    desc_localizations = "antinuke_untrust_description"
+)]
+pub struct AntiNukeUntrustCommand {
+    /// Member to remove from the trusted members.
+    user: Id<UserMarker>,
+}
+
+desc_localizations!(antinuke_untrust_description);
+
+impl AntiNukeUntrustCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        let antinuke = &mut config.antinuke;
+
+        if !antinuke.trusted_users.contains(&self.user) {
+            return Ok(embed::antinuke::trusted_not_configured(ctx.lang));
+        }
+
+        antinuke.trusted_users.retain(|user| user != &self.user);
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(
+                ctx.lang
+                    .antinuke_untrust_confirm_description(self.user.mention()),
+            )
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
@@ -0,0 +1,724 @@
+//! Anti-spam configuration commands.
+
+use raidprotect_model::database::model::{AntiSpamConfig, AntiSpamPunishment};
+use time::Duration;
+use twilight_interactions::command::{CommandModel, CommandOption, CreateCommand, CreateOption};
+use twilight_mention::Mention;
+use twilight_model::id::{
+    marker::{ChannelMarker, RoleMarker},
+    Id,
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations,
+    interaction::{
+        embed::{self, COLOR_SUCCESS, COLOR_TRANSPARENT},
+        response::InteractionResponse,
+        util::GuildInteractionContext,
+    },
+    translations::Lang,
+    util::{format_duration, format_number},
+};
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "antispam",
+    desc = "Configure the RaidProtect anti-spam",
+    desc_localizations = "antispam_description"
+)]
+pub enum AntiSpamConfigCommand {
+    #[command(name = "enable")]
+    Enable(AntiSpamEnableCommand),
+    #[command(name = "disable")]
+    Disable(AntiSpamDisableCommand),
+    #[command(name = "messages")]
+    Messages(AntiSpamMessagesCommand),
+    #[command(name = "mentions")]
+    Mentions(AntiSpamMentionsCommand),
+    #[command(name = "duplicates")]
+    Duplicates(AntiSpamDuplicatesCommand),
+    #[command(name = "punishment")]
+    Punishment(AntiSpamPunishmentCommand),
+    #[command(name = "mention-sanction")]
+    MentionSanction(AntiSpamMentionSanctionCommand),
+    #[command(name = "detector")]
+    Detector(AntiSpamDetectorCommand),
+    #[command(name = "slowmode")]
+    Slowmode(AntiSpamSlowmodeCommand),
+    #[command(name = "exempt-add")]
+    ExemptAdd(AntiSpamExemptAddCommand),
+    #[command(name = "exempt-remove")]
+    ExemptRemove(AntiSpamExemptRemoveCommand),
+    #[command(name = "show")]
+    Show(AntiSpamShowCommand),
+}
+
+desc_localizations!(antispam_description);
+
+impl AntiSpamConfigCommand {
+    pub(super) async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            AntiSpamConfigCommand::Enable(command) => command.exec(ctx, state).await,
+            AntiSpamConfigCommand::Disable(command) => command.exec(ctx, state).await,
+            AntiSpamConfigCommand::Messages(command) => command.exec(ctx, state).await,
+            AntiSpamConfigCommand::Mentions(command) => command.exec(ctx, state).await,
+            AntiSpamConfigCommand::Duplicates(command) => command.exec(ctx, state).await,
+            AntiSpamConfigCommand::Punishment(command) => command.exec(ctx, state).await,
+            AntiSpamConfigCommand::MentionSanction(command) => command.exec(ctx, state).await,
+            AntiSpamConfigCommand::Detector(command) => command.exec(ctx, state).await,
+            AntiSpamConfigCommand::Slowmode(command) => command.exec(ctx, state).await,
+            AntiSpamConfigCommand::ExemptAdd(command) => command.exec(ctx, state).await,
+            AntiSpamConfigCommand::ExemptRemove(command) => command.exec(ctx, state).await,
+            AntiSpamConfigCommand::Show(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "enable",
+    desc = "Enable the RaidProtect anti-spam",
+    desc_localizations = "antispam_enable_description"
+)]
+pub struct AntiSpamEnableCommand;
+
+desc_localizations!(antispam_enable_description);
+
+impl AntiSpamEnableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if config.antispam.enabled {
+            return Ok(embed::antispam::already_enabled(ctx.lang));
+        }
+
+        config.antispam.enabled = true;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.antispam_enable_confirm_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "disable",
+    desc = "Disable the RaidProtect anti-spam",
+    desc_localizations = "antispam_disable_description"
+)]
+pub struct AntiSpamDisableCommand;
+
+desc_localizations!(antispam_disable_description);
+
+impl AntiSpamDisableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if !config.antispam.enabled {
+            return Ok(embed::antispam::not_enabled(ctx.lang));
+        }
+
+        config.antispam.enabled = false;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.antispam_disable_confirm_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "messages",
+    desc = "Set the maximum number of messages a member can send",
+    desc_localizations = "antispam_messages_description"
+)]
+pub struct AntiSpamMessagesCommand {
+    /// Maximum number of messages.
+    #[command(min_value = 2, max_value = 20)]
+    max: i64,
+    /// Interval in which the messages are counted (in seconds).
+    #[command(min_value = 1, max_value = 60)]
+    interval: i64,
+}
+
+desc_localizations!(antispam_messages_description);
+
+impl AntiSpamMessagesCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        config.antispam.max_messages = self.max.try_into()?;
+        config.antispam.message_interval = self.interval.try_into()?;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.antispam_messages_confirm_description(
+                format_duration(Duration::seconds(self.interval), ctx.lang),
+                self.max,
+            ))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "mentions",
+    desc = "Set the maximum number of mentions in a message",
+    desc_localizations = "antispam_mentions_description"
+)]
+pub struct AntiSpamMentionsCommand {
+    /// Maximum number of mentions.
+    #[command(min_value = 1, max_value = 50)]
+    max: i64,
+}
+
+desc_localizations!(antispam_mentions_description);
+
+impl AntiSpamMentionsCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        config.antispam.max_mentions = self.max.try_into()?;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.antispam_mentions_confirm_description(self.max))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "duplicates",
+    desc = "Set the maximum number of similar messages a member can send",
+    desc_localizations = "antispam_duplicates_description"
+)]
+pub struct AntiSpamDuplicatesCommand {
+    /// Maximum number of similar messages.
+    #[command(min_value = 2, max_value = 10)]
+    max: i64,
+}
+
+desc_localizations!(antispam_duplicates_description);
+
+impl AntiSpamDuplicatesCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        config.antispam.max_duplicates = self.max.try_into()?;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.antispam_duplicates_confirm_description(self.max))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "punishment",
+    desc = "Set the punishment applied to spammers",
+    desc_localizations = "antispam_punishment_description"
+)]
+pub struct AntiSpamPunishmentCommand {
+    /// Punishment applied to spammers.
+    punishment: PunishmentOption,
+    /// Duration of the timeout (in minutes).
+    #[command(min_value = 1, max_value = 40320)]
+    duration: Option<i64>,
+}
+
+/// Punishment command option.
+#[derive(Debug, Clone, Copy, CommandOption, CreateOption)]
+pub enum PunishmentOption {
+    #[option(name = "Delete messages", value = "delete")]
+    Delete,
+    #[option(name = "Timeout", value = "timeout")]
+    Timeout,
+    #[option(name = "Kick", value = "kick")]
+    Kick,
+    #[option(name = "Ban", value = "ban")]
+    Ban,
+}
+
+impl From<PunishmentOption> for AntiSpamPunishment {
+    fn from(option: PunishmentOption) -> Self {
+        match option {
+            PunishmentOption::Delete => AntiSpamPunishment::Delete,
+            PunishmentOption::Timeout => AntiSpamPunishment::Timeout,
+            PunishmentOption::Kick => AntiSpamPunishment::Kick,
+            PunishmentOption::Ban => AntiSpamPunishment::Ban,
+        }
+    }
+}
+
+desc_localizations!(antispam_punishment_description);
+
+impl AntiSpamPunishmentCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        config.antispam.punishment = self.punishment.into();
+
+        if let Some(duration) = self.duration {
+            let duration = u32::try_from(duration * 60)?;
+            config.antispam.punishment_duration =
+                duration.min(AntiSpamConfig::MAX_PUNISHMENT_DURATION);
+        }
+
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(
+                ctx.lang
+                    .antispam_punishment_confirm_description(punishment_name(
+                        &config.antispam,
+                        ctx.lang,
+                    )),
+            )
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "mention-sanction",
+    desc = "Set whether members repeatedly abusing mentions are punished",
+    desc_localizations = "antispam_mention_sanction_description"
+)]
+pub struct AntiSpamMentionSanctionCommand {
+    /// Whether to time out members sending ghost pings or mass role pings.
+    enabled: bool,
+}
+
+desc_localizations!(antispam_mention_sanction_description);
+
+impl AntiSpamMentionSanctionCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        config.antispam.mention_sanction = self.enabled;
+        state.update_guild_config(&config).await?;
+
+        let description = if self.enabled {
+            ctx.lang.antispam_mention_sanction_enabled_description()
+        } else {
+            ctx.lang.antispam_mention_sanction_disabled_description()
+        };
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(description)
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "detector",
+    desc = "Enable or disable a spam detector",
+    desc_localizations = "antispam_detector_description"
+)]
+pub struct AntiSpamDetectorCommand {
+    /// Identifier of the detector (e.g. antispam:rate).
+    #[command(max_length = 50)]
+    detector: String,
+    /// Whether the detector is enabled.
+    enabled: bool,
+}
+
+desc_localizations!(antispam_detector_description);
+
+impl AntiSpamDetectorCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let detectors = state.antispam.detectors();
+        if !detectors.ids().any(|id| id == self.detector) {
+            return Ok(embed::antispam::unknown_detector(
+                detectors.ids().collect(),
+                ctx.lang,
+            ));
+        }
+
+        let mut config = ctx.config(state).await?;
+        let disabled = &mut config.antispam.disabled_detectors;
+
+        disabled.retain(|id| id != &self.detector);
+        if !self.enabled {
+            disabled.push(self.detector.clone());
+        }
+
+        state.update_guild_config(&config).await?;
+
+        let description = if self.enabled {
+            ctx.lang
+                .antispam_detector_enabled_description(&self.detector)
+        } else {
+            ctx.lang
+                .antispam_detector_disabled_description(&self.detector)
+        };
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(description)
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "slowmode",
+    desc = "Set the slowmode applied to channels during spam waves",
+    desc_localizations = "antispam_slowmode_description"
+)]
+pub struct AntiSpamSlowmodeCommand {
+    /// Slowmode duration (in seconds, 0 to disable).
+    #[command(min_value = 0, max_value = 21600)]
+    seconds: i64,
+}
+
+desc_localizations!(antispam_slowmode_description);
+
+impl AntiSpamSlowmodeCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        let slowmode = u16::try_from(self.seconds)?;
+        config.antispam.emergency_slowmode = slowmode.min(AntiSpamConfig::MAX_EMERGENCY_SLOWMODE);
+        state.update_guild_config(&config).await?;
+
+        let description = match config.antispam.emergency_slowmode {
+            0 => ctx.lang.antispam_slowmode_disabled_description().to_owned(),
+            slowmode => ctx
+                .lang
+                .antispam_slowmode_enabled_description(format_duration(
+                    Duration::seconds(slowmode.into()),
+                    ctx.lang,
+                )),
+        };
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(description)
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "exempt-add",
+    desc = "Exempt a role or a channel from the RaidProtect anti-spam",
+    desc_localizations = "antispam_exempt_add_description"
+)]
+pub struct AntiSpamExemptAddCommand {
+    /// Role to exempt from the anti-spam.
+    role: Option<Id<RoleMarker>>,
+    /// Channel to exempt from the anti-spam.
+    #[command(channel_types = "guild_text")]
+    channel: Option<Id<ChannelMarker>>,
+}
+
+desc_localizations!(antispam_exempt_add_description);
+
+impl AntiSpamExemptAddCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        let antispam = &mut config.antispam;
+
+        let target = match (self.role, self.channel) {
+            (Some(role), _) => {
+                if antispam.exempt_roles.contains(&role) {
+                    return Ok(embed::antispam::exempt_already_added(ctx.lang));
+                }
+
+                if antispam.exempt_roles.len() >= AntiSpamConfig::MAX_EXEMPT_LEN {
+                    return Ok(embed::antispam::exempt_too_many(ctx.lang));
+                }
+
+                antispam.exempt_roles.push(role);
+                role.mention().to_string()
+            }
+            (None, Some(channel)) => {
+                if antispam.exempt_channels.contains(&channel) {
+                    return Ok(embed::antispam::exempt_already_added(ctx.lang));
+                }
+
+                if antispam.exempt_channels.len() >= AntiSpamConfig::MAX_EXEMPT_LEN {
+                    return Ok(embed::antispam::exempt_too_many(ctx.lang));
+                }
+
+                antispam.exempt_channels.push(channel);
+                channel.mention().to_string()
+            }
+            (None, None) => return Ok(embed::antispam::exempt_missing(ctx.lang)),
+        };
+
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.antispam_exempt_add_confirm_description(target))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "exempt-remove",
+    desc = "Remove an exemption from the RaidProtect anti-spam",
+    desc_localizations = "antispam_exempt_remove_description"
+)]
+pub struct AntiSpamExemptRemoveCommand {
+    /// Role to remove from the exemptions.
+    role: Option<Id<RoleMarker>>,
+    /// Channel to remove from the exemptions.
+    #[command(channel_types = "guild_text")]
+    channel: Option<Id<ChannelMarker>>,
+}
+
+desc_localizations!(antispam_exempt_remove_description);
+
+impl AntiSpamExemptRemoveCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        let antispam = &mut config.antispam;
+
+        let target = match (self.role, self.channel) {
+            (Some(role), _) => {
+                if !antispam.exempt_roles.contains(&role) {
+                    return Ok(embed::antispam::exempt_not_configured(ctx.lang));
+                }
+
+                antispam.exempt_roles.retain(|r| r != &role);
+                role.mention().to_string()
+            }
+            (None, Some(channel)) => {
+                if !antispam.exempt_channels.contains(&channel) {
+                    return Ok(embed::antispam::exempt_not_configured(ctx.lang));
+                }
+
+                antispam.exempt_channels.retain(|c| c != &channel);
+                channel.mention().to_string()
+            }
+            (None, None) => return Ok(embed::antispam::exempt_missing(ctx.lang)),
+        };
+
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.antispam_exempt_remove_confirm_description(target))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "show",
+    desc = "Show the RaidProtect anti-spam configuration",
+    desc_localizations = "antispam_show_description"
+)]
+pub struct AntiSpamShowCommand;
+
+desc_localizations!(antispam_show_description);
+
+impl AntiSpamShowCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let config = ctx.config(state).await?;
+        let antispam = &config.antispam;
+
+        let status = if antispam.enabled {
+            ctx.lang.antispam_show_enabled()
+        } else {
+            ctx.lang.antispam_show_disabled()
+        };
+
+        let limits = ctx.lang.antispam_show_limits_value(
+            format_number(antispam.max_duplicates.into(), ctx.lang),
+            format_duration(
+                Duration::seconds(antispam.message_interval.into()),
+                ctx.lang,
+            ),
+            format_number(antispam.max_mentions.into(), ctx.lang),
+            format_number(antispam.max_messages.into(), ctx.lang),
+        );
+
+        let exempt = antispam
+            .exempt_roles
+            .iter()
+            .map(|id| id.mention().to_string())
+            .chain(
+                antispam
+                    .exempt_channels
+                    .iter()
+                    .map(|id| id.mention().to_string()),
+            )
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mention_sanction = if antispam.mention_sanction {
+            ctx.lang.antispam_show_mention_sanction_enabled()
+        } else {
+            ctx.lang.antispam_show_mention_sanction_disabled()
+        };
+
+        let slowmode = match antispam.emergency_slowmode {
+            0 => ctx.lang.antispam_show_slowmode_disabled().to_owned(),
+            slowmode => format_duration(Duration::seconds(slowmode.into()), ctx.lang),
+        };
+
+        let exempt = if exempt.is_empty() {
+            ctx.lang.antispam_show_exempt_none().to_owned()
+        } else {
+            exempt
+        };
+
+        let detectors = if antispam.disabled_detectors.is_empty() {
+            ctx.lang.antispam_show_detectors_all().to_owned()
+        } else {
+            antispam
+                .disabled_detectors
+                .iter()
+                .map(|id| format!("`{id}`"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_TRANSPARENT)
+            .title(ctx.lang.antispam_show_title())
+            .description(status)
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.antispam_show_limits(),
+                limits,
+            ))
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.antispam_show_punishment(),
+                punishment_name(antispam, ctx.lang),
+            ))
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.antispam_show_mention_sanction(),
+                mention_sanction,
+            ))
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.antispam_show_detectors(),
+                detectors,
+            ))
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.antispam_show_slowmode(),
+                slowmode,
+            ))
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.antispam_show_exempt(),
+                exempt,
+            ))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+/// Get the localized name of the configured punishment.
+fn punishment_name(config: &AntiSpamConfig, lang: Lang) -> String {
+    match config.punishment {
+        AntiSpamPunishment::Delete => lang.antispam_punishment_delete().to_owned(),
+        AntiSpamPunishment::Timeout => lang.antispam_punishment_timeout(format_duration(
+            Duration::seconds(config.punishment_duration.into()),
+            lang,
+        )),
+        AntiSpamPunishment::Kick => lang.antispam_punishment_kick().to_owned(),
+        AntiSpamPunishment::Ban => lang.antispam_punishment_ban().to_owned(),
+    }
+}
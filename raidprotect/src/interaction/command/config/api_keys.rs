@@ -0,0 +1,360 @@
+//! API keys configuration commands.
+//!
+//! API keys are used by third-party integrations to access the web API of a
+//! guild. Only the guild owner can manage them.
+
+use raidprotect_model::{
+    cache::discord::CachedGuild,
+    database::model::{ApiKey, ApiScope},
+};
+use rand::{distributions::Alphanumeric, Rng};
+use time::OffsetDateTime;
+use twilight_interactions::command::{CommandModel, CommandOption, CreateCommand, CreateOption};
+use twilight_util::builder::embed::EmbedBuilder;
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations,
+    interaction::{
+        embed::{self, COLOR_SUCCESS, COLOR_TRANSPARENT},
+        response::InteractionResponse,
+        util::GuildInteractionContext,
+    },
+    translations::Lang,
+    util::TextProcessExt,
+};
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "apikeys",
+    desc = "Manage the API keys used by third-party integrations",
+    desc_localizations = "api_keys_description"
+)]
+pub enum ApiKeysConfigCommand {
+    #[command(name = "create")]
+    Create(ApiKeysCreateCommand),
+    #[command(name = "revoke")]
+    Revoke(ApiKeysRevokeCommand),
+    #[command(name = "list")]
+    List(ApiKeysListCommand),
+}
+
+desc_localizations!(api_keys_description);
+
+impl ApiKeysConfigCommand {
+    pub(super) async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let owner_id = state
+            .cache
+            .get::<CachedGuild>(&ctx.guild_id)
+            .await?
+            .map(|guild| guild.owner_id);
+
+        if owner_id != Some(ctx.author.id) {
+            return Ok(embed::api_keys::not_guild_owner(ctx.lang));
+        }
+
+        match self {
+            ApiKeysConfigCommand::Create(command) => command.exec(ctx, state).await,
+            ApiKeysConfigCommand::Revoke(command) => command.exec(ctx, state).await,
+            ApiKeysConfigCommand::List(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+/// API key scope command option.
+#[derive(Debug, Clone, Copy, CommandOption, CreateOption)]
+pub enum ApiScopeOption {
+    #[option(name = "Guild information", value = "guild")]
+    Guild,
+    #[option(name = "Live events", value = "events")]
+    Events,
+    #[option(name = "Bot actions", value = "actions")]
+    Actions,
+    #[option(name = "All", value = "all")]
+    All,
+}
+
+impl ApiScopeOption {
+    /// Get the scopes given by the option.
+    fn scopes(self) -> Vec<ApiScope> {
+        match self {
+            ApiScopeOption::Guild => vec![ApiScope::Guild],
+            ApiScopeOption::Events => vec![ApiScope::Events],
+            ApiScopeOption::Actions => vec![ApiScope::Actions],
+            ApiScopeOption::All => ApiScope::ALL.to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "create",
+    desc = "Create a new API key",
+    desc_localizations = "api_keys_create_description"
+)]
+pub struct ApiKeysCreateCommand {
+    /// Name of the key.
+    name: String,
+    /// Routes the key gives access to.
+    scope: ApiScopeOption,
+}
+
+desc_localizations!(api_keys_create_description);
+
+impl ApiKeysCreateCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let name = self.name.trim();
+        if name.is_empty() || name.chars().count() > ApiKey::MAX_NAME_LEN {
+            return Ok(embed::api_keys::invalid_name(ctx.lang));
+        }
+
+        let keys = state.store.get_guild_api_keys(ctx.guild_id).await?;
+        if keys.iter().any(|key| key.name == name) {
+            return Ok(embed::api_keys::already_exists(ctx.lang));
+        }
+
+        if keys.len() >= ApiKey::MAX_LEN {
+            return Ok(embed::api_keys::too_many(ctx.lang));
+        }
+
+        let token: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(40)
+            .map(char::from)
+            .collect();
+        let token = format!("{}{token}", ApiKey::TOKEN_PREFIX);
+
+        let key = ApiKey {
+            id: None,
+            guild_id: ctx.guild_id,
+            name: name.to_owned(),
+            hash: ApiKey::hash_token(&token),
+            scopes: self.scope.scopes(),
+            created_by: ctx.author.id,
+            created_at: OffsetDateTime::now_utc(),
+        };
+        state.store.create_api_key(&key).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.api_keys_create_title())
+            .description(ctx.lang.api_keys_create_confirm_description(name, token))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "revoke",
+    desc = "Revoke an API key",
+    desc_localizations = "api_keys_revoke_description"
+)]
+pub struct ApiKeysRevokeCommand {
+    /// Name of the key.
+    name: String,
+}
+
+desc_localizations!(api_keys_revoke_description);
+
+impl ApiKeysRevokeCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let name = self.name.trim();
+        if !state.store.delete_api_key(ctx.guild_id, name).await? {
+            return Ok(embed::api_keys::not_found(ctx.lang));
+        }
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.api_keys_revoke_confirm_description(name))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "list",
+    desc = "List the API keys of the server",
+    desc_localizations = "api_keys_list_description"
+)]
+pub struct ApiKeysListCommand;
+
+desc_localizations!(api_keys_list_description);
+
+impl ApiKeysListCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let keys = state.store.get_guild_api_keys(ctx.guild_id).await?;
+
+        let description = if keys.is_empty() {
+            ctx.lang.api_keys_list_empty().to_owned()
+        } else {
+            keys.iter()
+                .map(|key| {
+                    let scopes = key
+                        .scopes
+                        .iter()
+                        .map(|scope| scope_name(*scope, ctx.lang))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+
+                    format!(
+                        "**{}** — {scopes} (<t:{}:d>)",
+                        key.name,
+                        key.created_at.unix_timestamp()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+                .max_len(4096)
+        };
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_TRANSPARENT)
+            .title(ctx.lang.api_keys_list_title())
+            .description(description)
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+/// Get the translated name of a scope.
+fn scope_name(scope: ApiScope, lang: Lang) -> &'static str {
+    match scope {
+        ApiScope::Guild => lang.api_keys_scope_guild(),
+        ApiScope::Events => lang.api_keys_scope_events(),
+        ApiScope::Actions => lang.api_keys_scope_actions(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use serde_json::json;
+    use twilight_model::{
+        application::interaction::Interaction,
+        id::{marker::UserMarker, Id},
+    };
+
+    use super::*;
+
+    const OWNER_ID: Id<UserMarker> = Id::new(2);
+
+    fn context(user_id: Id<UserMarker>) -> GuildInteractionContext {
+        let interaction: Interaction = serde_json::from_value(json!({
+            "id": "3",
+            "application_id": "1",
+            "type": 2,
+            "data": {
+                "id": "4",
+                "name": "config",
+                "type": 1,
+            },
+            "guild_id": "1",
+            "token": "token",
+            "locale": "en-US",
+            "member": {
+                "user": {
+                    "id": user_id.to_string(),
+                    "username": "user",
+                    "discriminator": "0001",
+                    "avatar": null,
+                },
+                "roles": [],
+                "joined_at": "2021-08-10T11:16:37.020000+00:00",
+                "deaf": false,
+                "mute": false,
+            },
+            "version": 1,
+        }))
+        .unwrap();
+
+        GuildInteractionContext::new(interaction).unwrap()
+    }
+
+    async fn state() -> ClusterState {
+        let state = ClusterState::for_tests().await;
+        let guild = CachedGuild {
+            id: Id::new(1),
+            unavailable: false,
+            name: "guild".to_owned(),
+            icon: None,
+            owner_id: OWNER_ID,
+            current_member: None,
+            roles: HashSet::new(),
+            channels: HashSet::new(),
+        };
+        state.cache.set(&guild).await.unwrap();
+
+        state
+    }
+
+    fn description(response: InteractionResponse) -> String {
+        match response {
+            InteractionResponse::EphemeralEmbed(embed) => embed.description.unwrap(),
+            response => panic!("unexpected response {response:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_and_list() {
+        let state = state().await;
+        let create = ApiKeysConfigCommand::Create(ApiKeysCreateCommand {
+            name: " integration ".to_owned(),
+            scope: ApiScopeOption::Events,
+        });
+
+        let response = create.clone().exec(context(OWNER_ID), &state).await;
+        let confirm = description(response.unwrap());
+        assert!(confirm.contains(ApiKey::TOKEN_PREFIX));
+
+        let keys = state.store.get_guild_api_keys(Id::new(1)).await.unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].name, "integration");
+        assert_eq!(keys[0].scopes, vec![ApiScope::Events]);
+        assert_eq!(keys[0].created_by, OWNER_ID);
+
+        let ctx = context(OWNER_ID);
+        let lang = ctx.lang;
+        let response = create.exec(ctx, &state).await.unwrap();
+        assert_eq!(response, embed::api_keys::already_exists(lang));
+
+        let list = ApiKeysConfigCommand::List(ApiKeysListCommand);
+        let response = list.exec(context(OWNER_ID), &state).await;
+        let description = description(response.unwrap());
+        assert!(description.starts_with("**integration** — "));
+    }
+
+    #[tokio::test]
+    async fn test_not_guild_owner() {
+        let state = state().await;
+        let list = ApiKeysConfigCommand::List(ApiKeysListCommand);
+
+        let ctx = context(Id::new(3));
+        let lang = ctx.lang;
+        let response = list.exec(ctx, &state).await.unwrap();
+
+        assert_eq!(response, embed::api_keys::not_guild_owner(lang));
+    }
+}
@@ -0,0 +1,404 @@
+//! Attachment policy configuration commands.
+
+use raidprotect_model::database::model::AttachmentPolicyConfig;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::Mention;
+use twilight_model::id::{
+    marker::{ChannelMarker, RoleMarker},
+    Id,
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations,
+    interaction::{
+        embed::{self, COLOR_SUCCESS, COLOR_TRANSPARENT},
+        response::InteractionResponse,
+        util::GuildInteractionContext,
+    },
+    translations::Lang,
+};
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "attachments",
+    desc = "Configure the RaidProtect attachment policy",
+    desc_localizations = "attachments_description"
+)]
+pub enum AttachmentsConfigCommand {
+    #[command(name = "enable")]
+    Enable(AttachmentsEnableCommand),
+    #[command(name = "disable")]
+    Disable(AttachmentsDisableCommand),
+    #[command(name = "executables")]
+    Executables(AttachmentsExecutablesCommand),
+    #[command(name = "max")]
+    Max(AttachmentsMaxCommand),
+    #[command(name = "image-only")]
+    ImageOnly(AttachmentsImageOnlyCommand),
+    #[command(name = "exempt")]
+    Exempt(AttachmentsExemptCommand),
+    #[command(name = "show")]
+    Show(AttachmentsShowCommand),
+}
+
+desc_localizations!(attachments_description);
+
+impl AttachmentsConfigCommand {
+    pub(super) async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            AttachmentsConfigCommand::Enable(command) => command.exec(ctx, state).await,
+            AttachmentsConfigCommand::Disable(command) => command.exec(ctx, state).await,
+            AttachmentsConfigCommand::Executables(command) => command.exec(ctx, state).await,
+            AttachmentsConfigCommand::Max(command) => command.exec(ctx, state).await,
+            AttachmentsConfigCommand::ImageOnly(command) => command.exec(ctx, state).await,
+            AttachmentsConfigCommand::Exempt(command) => command.exec(ctx, state).await,
+            AttachmentsConfigCommand::Show(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "enable",
+    desc = "Enable the RaidProtect attachment policy",
+    desc_localizations = "attachments_enable_description"
+)]
+pub struct AttachmentsEnableCommand;
+
+desc_localizations!(attachments_enable_description);
+
+impl AttachmentsEnableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if config.attachments.enabled {
+            return Ok(embed::attachments::already_enabled(ctx.lang));
+        }
+
+        config.attachments.enabled = true;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.attachments_enable_confirm_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "disable",
+    desc = "Disable the RaidProtect attachment policy",
+    desc_localizations = "attachments_disable_description"
+)]
+pub struct AttachmentsDisableCommand;
+
+desc_localizations!(attachments_disable_description);
+
+impl AttachmentsDisableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if !config.attachments.enabled {
+            return Ok(embed::attachments::not_enabled(ctx.lang));
+        }
+
+        config.attachments.enabled = false;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.attachments_disable_confirm_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "executables",
+    desc = "Set whether executable files are blocked",
+    desc_localizations = "attachments_executables_description"
+)]
+pub struct AttachmentsExecutablesCommand {
+    /// Whether to block executable files.
+    enabled: bool,
+}
+
+desc_localizations!(attachments_executables_description);
+
+impl AttachmentsExecutablesCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        config.attachments.block_executables = self.enabled;
+        state.update_guild_config(&config).await?;
+
+        let description = if self.enabled {
+            ctx.lang.attachments_executables_enabled_description()
+        } else {
+            ctx.lang.attachments_executables_disabled_description()
+        };
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(description)
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "max",
+    desc = "Set the maximum number of attachments per message",
+    desc_localizations = "attachments_max_description"
+)]
+pub struct AttachmentsMaxCommand {
+    /// Maximum number of attachments (0 to disable).
+    #[command(min_value = 0, max_value = 10)]
+    count: i64,
+}
+
+desc_localizations!(attachments_max_description);
+
+impl AttachmentsMaxCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        let count = u8::try_from(self.count)?;
+        config.attachments.max_attachments = count.min(AttachmentPolicyConfig::MAX_ATTACHMENTS);
+        state.update_guild_config(&config).await?;
+
+        let description = match config.attachments.max_attachments {
+            0 => ctx.lang.attachments_max_disabled_description().to_owned(),
+            count => ctx.lang.attachments_max_enabled_description(count),
+        };
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(description)
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "image-only",
+    desc = "Set whether a channel only allows images",
+    desc_localizations = "attachments_image_only_description"
+)]
+pub struct AttachmentsImageOnlyCommand {
+    /// Channel to update.
+    #[command(channel_types = "guild_text")]
+    channel: Id<ChannelMarker>,
+    /// Whether the channel only allows images.
+    enabled: bool,
+}
+
+desc_localizations!(attachments_image_only_description);
+
+impl AttachmentsImageOnlyCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        let channels = &mut config.attachments.image_only_channels;
+
+        let description = if self.enabled {
+            if !channels.contains(&self.channel) {
+                if channels.len() >= AttachmentPolicyConfig::MAX_LIST_LEN {
+                    return Ok(embed::attachments::too_many(ctx.lang));
+                }
+
+                channels.push(self.channel);
+            }
+
+            ctx.lang
+                .attachments_image_only_enabled_description(self.channel.mention())
+        } else {
+            channels.retain(|channel| channel != &self.channel);
+
+            ctx.lang
+                .attachments_image_only_disabled_description(self.channel.mention())
+        };
+
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(description)
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "exempt",
+    desc = "Set whether a role is exempted from the attachment policy",
+    desc_localizations = "attachments_exempt_description"
+)]
+pub struct AttachmentsExemptCommand {
+    /// Role to update.
+    role: Id<RoleMarker>,
+    /// Whether the role is exempted.
+    exempt: bool,
+}
+
+desc_localizations!(attachments_exempt_description);
+
+impl AttachmentsExemptCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        let roles = &mut config.attachments.exempt_roles;
+
+        let description = if self.exempt {
+            if !roles.contains(&self.role) {
+                if roles.len() >= AttachmentPolicyConfig::MAX_LIST_LEN {
+                    return Ok(embed::attachments::too_many(ctx.lang));
+                }
+
+                roles.push(self.role);
+            }
+
+            ctx.lang
+                .attachments_exempt_added_description(self.role.mention())
+        } else {
+            roles.retain(|role| role != &self.role);
+
+            ctx.lang
+                .attachments_exempt_removed_description(self.role.mention())
+        };
+
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(description)
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "show",
+    desc = "Show the RaidProtect attachment policy configuration",
+    desc_localizations = "attachments_show_description"
+)]
+pub struct AttachmentsShowCommand;
+
+desc_localizations!(attachments_show_description);
+
+impl AttachmentsShowCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let config = ctx.config(state).await?;
+        let attachments = &config.attachments;
+
+        let status = if attachments.enabled {
+            ctx.lang.attachments_show_enabled()
+        } else {
+            ctx.lang.attachments_show_disabled()
+        };
+
+        let executables = if attachments.block_executables {
+            ctx.lang.attachments_show_executables_blocked()
+        } else {
+            ctx.lang.attachments_show_executables_allowed()
+        };
+
+        let max = match attachments.max_attachments {
+            0 => ctx.lang.attachments_show_unlimited().to_owned(),
+            count => count.to_string(),
+        };
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_TRANSPARENT)
+            .title(ctx.lang.attachments_show_title())
+            .description(status)
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.attachments_show_executables(),
+                executables,
+            ))
+            .field(EmbedFieldBuilder::new(ctx.lang.attachments_show_max(), max))
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.attachments_show_image_only(),
+                format_mentions(
+                    attachments
+                        .image_only_channels
+                        .iter()
+                        .map(|channel| channel.mention().to_string())
+                        .collect(),
+                    ctx.lang,
+                ),
+            ))
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.attachments_show_exempt(),
+                format_mentions(
+                    attachments
+                        .exempt_roles
+                        .iter()
+                        .map(|role| role.mention().to_string())
+                        .collect(),
+                    ctx.lang,
+                ),
+            ))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+/// Format a list of channel or role mentions in an embed field.
+fn format_mentions(mentions: Vec<String>, lang: Lang) -> String {
+    if mentions.is_empty() {
+        return lang.attachments_show_none().to_owned();
+    }
+
+    mentions.join(", ")
+}
@@ -1,11 +1,16 @@
 //! Captcha configuration commands.
 
 use anyhow::bail;
-use raidprotect_model::{cache::discord::permission::RoleOrdering, database::model::CaptchaConfig};
+use raidprotect_model::{
+    cache::discord::permission::RoleOrdering,
+    database::model::{CaptchaConfig, QuestionnaireConfig},
+};
 use twilight_interactions::command::{CommandModel, CreateCommand};
 use twilight_mention::Mention;
 use twilight_model::{
-    application::component::{button::ButtonStyle, ActionRow, Button, Component},
+    application::component::{
+        button::ButtonStyle, text_input::TextInputStyle, ActionRow, Button, Component, TextInput,
+    },
     channel::message::MessageFlags,
     guild::{Permissions, Role},
     http::interaction::InteractionResponseType,
@@ -20,9 +25,12 @@ use crate::{
     cluster::ClusterState,
     desc_localizations,
     interaction::{
+        component::captcha::{
+            CaptchaDisable, CaptchaEnable, CaptchaMigrate, CaptchaQuestionnaireConfigId,
+        },
         embed::{self, COLOR_RED, COLOR_SUCCESS, COLOR_TRANSPARENT},
         response::InteractionResponse,
-        util::{CustomId, GuildInteractionContext},
+        util::{ComponentId, GuildInteractionContext},
     },
 };
 
@@ -45,6 +53,10 @@ pub enum CaptchaConfigCommand {
     AutoroleRemove(CaptchaAutoroleRemoveCommand),
     #[command(name = "autorole-list")]
     AutoroleList(CaptchaAutoroleListCommand),
+    #[command(name = "questionnaire")]
+    Questionnaire(CaptchaQuestionnaireCommand),
+    #[command(name = "migrate")]
+    Migrate(CaptchaMigrateCommand),
 }
 
 desc_localizations!(captcha_description);
@@ -62,6 +74,8 @@ impl CaptchaConfigCommand {
             CaptchaConfigCommand::AutoroleAdd(command) => command.exec(ctx, state).await,
             CaptchaConfigCommand::AutoroleRemove(command) => command.exec(ctx, state).await,
             CaptchaConfigCommand::AutoroleList(command) => command.exec(ctx, state).await,
+            CaptchaConfigCommand::Questionnaire(command) => command.exec(ctx, state).await,
+            CaptchaConfigCommand::Migrate(command) => command.exec(ctx, state).await,
         }
     }
 }
@@ -93,11 +107,11 @@ impl CaptchaEnableCommand {
             .description(ctx.lang.captcha_confirm_description())
             .build();
 
-        let custom_id = CustomId::name("captcha-enable");
+        let custom_id = CaptchaEnable.encode()?;
         let components = Component::ActionRow(ActionRow {
             components: vec![
                 Component::Button(Button {
-                    custom_id: Some(custom_id.to_string()),
+                    custom_id: Some(custom_id),
                     disabled: false,
                     emoji: None,
                     label: Some(ctx.lang.captcha_confirm_button().to_owned()),
@@ -167,10 +181,10 @@ impl CaptchaDisableCommand {
             )
             .build();
 
-        let custom_id = CustomId::name("captcha-disable");
+        let custom_id = CaptchaDisable.encode()?;
         let components = Component::ActionRow(ActionRow {
             components: vec![Component::Button(Button {
-                custom_id: Some(custom_id.to_string()),
+                custom_id: Some(custom_id),
                 disabled: false,
                 emoji: None,
                 label: Some(ctx.lang.captcha_disable_confirm_button().to_owned()),
@@ -233,7 +247,7 @@ impl CaptchaLogsCommand {
 
         // Update the config.
         config.captcha.logs = Some(self.channel);
-        state.database.update_guild(&config).await?;
+        state.update_guild_config(&config).await?;
 
         // Send the embed.
         let embed = EmbedBuilder::new()
@@ -300,7 +314,7 @@ impl CaptchaAutoroleAddCommand {
         }
 
         config.captcha.verified_roles.push(self.role.id);
-        state.database.update_guild(&config).await?;
+        state.update_guild_config(&config).await?;
 
         // Send the embed.
         let embed = EmbedBuilder::new()
@@ -346,7 +360,7 @@ impl CaptchaAutoroleRemoveCommand {
         }
 
         config.captcha.verified_roles.retain(|r| r != &self.role);
-        state.database.update_guild(&config).await?;
+        state.update_guild_config(&config).await?;
 
         // Send the embed.
         let embed = EmbedBuilder::new()
@@ -410,3 +424,156 @@ impl CaptchaAutoroleListCommand {
         Ok(InteractionResponse::EphemeralEmbed(embed))
     }
 }
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "questionnaire",
+    desc = "Configure the RaidProtect captcha questionnaire",
+    desc_localizations = "captcha_questionnaire_description"
+)]
+pub struct CaptchaQuestionnaireCommand {
+    /// Channel where the answers are sent for review.
+    #[command(rename = "review-channel", channel_types = "guild_text")]
+    review_channel: Id<ChannelMarker>,
+    /// Whether the questionnaire replaces the captcha image.
+    #[command(rename = "replace-captcha")]
+    replace_captcha: Option<bool>,
+}
+
+desc_localizations!(captcha_questionnaire_description);
+
+impl CaptchaQuestionnaireCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let config = ctx.config(state).await?;
+        if !config.captcha.enabled {
+            return Ok(embed::captcha::not_enabled(ctx.lang));
+        }
+
+        // Ensure RaidProtect has permissions to send messages in the channel.
+        let (permissions, _) = state
+            .cache
+            .permissions(ctx.guild_id)
+            .await?
+            .current_member()
+            .await?
+            .channel(self.review_channel)
+            .await?;
+
+        if !permissions.contains(Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS) {
+            return Ok(embed::captcha::missing_review_permission(ctx.lang));
+        }
+
+        // Send the questions modal, filled with the current questions.
+        //
+        // The review channel and the replace option are stored in the modal
+        // custom id, and the configuration is updated when the modal is
+        // submitted.
+        let questions = config
+            .captcha
+            .questionnaire
+            .map(|questionnaire| questionnaire.questions)
+            .unwrap_or_default();
+
+        let components = (0..QuestionnaireConfig::MAX_QUESTIONS_LEN)
+            .map(|index| {
+                Component::ActionRow(ActionRow {
+                    components: vec![Component::TextInput(TextInput {
+                        custom_id: format!("question-{index}"),
+                        label: ctx.lang.captcha_questionnaire_config_label(index + 1),
+                        max_length: Some(QuestionnaireConfig::MAX_QUESTION_LEN as u16),
+                        min_length: None,
+                        placeholder: Some(
+                            ctx.lang
+                                .captcha_questionnaire_config_placeholder()
+                                .to_owned(),
+                        ),
+                        required: Some(false),
+                        style: TextInputStyle::Short,
+                        value: questions.get(index).cloned(),
+                    })],
+                })
+            })
+            .collect();
+
+        let replace_captcha = self.replace_captcha.unwrap_or(false);
+        let custom_id = CaptchaQuestionnaireConfigId {
+            review_channel: self.review_channel,
+            replace_captcha,
+        }
+        .encode()?;
+
+        Ok(InteractionResponse::Modal {
+            custom_id,
+            title: ctx.lang.captcha_questionnaire_config_title().to_owned(),
+            components,
+        })
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "migrate",
+    desc = "Give the verified roles to all the current members",
+    desc_localizations = "captcha_migrate_description"
+)]
+pub struct CaptchaMigrateCommand;
+
+desc_localizations!(captcha_migrate_description);
+
+impl CaptchaMigrateCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let config = ctx.config(state).await?;
+        if !config.captcha.enabled {
+            return Ok(embed::captcha::not_enabled(ctx.lang));
+        }
+
+        if config.captcha.verified_roles.is_empty() {
+            return Ok(embed::captcha::migrate_no_roles(ctx.lang));
+        }
+
+        let roles = config
+            .captcha
+            .verified_roles
+            .iter()
+            .map(|id| id.mention().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_RED)
+            .title(ctx.lang.captcha_migrate_confirm_title())
+            .description(ctx.lang.captcha_migrate_confirm_description(roles))
+            .build();
+
+        let custom_id = CaptchaMigrate.encode()?;
+        let components = Component::ActionRow(ActionRow {
+            components: vec![Component::Button(Button {
+                custom_id: Some(custom_id),
+                disabled: false,
+                emoji: None,
+                label: Some(ctx.lang.captcha_migrate_confirm_button().to_owned()),
+                style: ButtonStyle::Success,
+                url: None,
+            })],
+        });
+
+        let response = InteractionResponseDataBuilder::new()
+            .embeds([embed])
+            .components([components])
+            .flags(MessageFlags::EPHEMERAL)
+            .build();
+
+        Ok(InteractionResponse::Raw {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: Some(response),
+        })
+    }
+}
@@ -0,0 +1,362 @@
+//! Domain filter configuration commands.
+
+use raidprotect_anti_spam::domains::normalize_domain;
+use raidprotect_model::database::model::DomainFilterConfig;
+use twilight_interactions::command::{CommandModel, CommandOption, CreateCommand, CreateOption};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations,
+    interaction::{
+        embed::{self, COLOR_SUCCESS, COLOR_TRANSPARENT},
+        response::InteractionResponse,
+        util::GuildInteractionContext,
+    },
+    translations::Lang,
+    util::TextProcessExt,
+};
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "domains",
+    desc = "Configure the RaidProtect domain filter",
+    desc_localizations = "domains_description"
+)]
+pub enum DomainsConfigCommand {
+    #[command(name = "enable")]
+    Enable(DomainsEnableCommand),
+    #[command(name = "disable")]
+    Disable(DomainsDisableCommand),
+    #[command(name = "add")]
+    Add(DomainsAddCommand),
+    #[command(name = "remove")]
+    Remove(DomainsRemoveCommand),
+    #[command(name = "phishing")]
+    Phishing(DomainsPhishingCommand),
+    #[command(name = "show")]
+    Show(DomainsShowCommand),
+}
+
+desc_localizations!(domains_description);
+
+impl DomainsConfigCommand {
+    pub(super) async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            DomainsConfigCommand::Enable(command) => command.exec(ctx, state).await,
+            DomainsConfigCommand::Disable(command) => command.exec(ctx, state).await,
+            DomainsConfigCommand::Add(command) => command.exec(ctx, state).await,
+            DomainsConfigCommand::Remove(command) => command.exec(ctx, state).await,
+            DomainsConfigCommand::Phishing(command) => command.exec(ctx, state).await,
+            DomainsConfigCommand::Show(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "enable",
+    desc = "Enable the RaidProtect domain filter",
+    desc_localizations = "domains_enable_description"
+)]
+pub struct DomainsEnableCommand;
+
+desc_localizations!(domains_enable_description);
+
+impl DomainsEnableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if config.domains.enabled {
+            return Ok(embed::domains::already_enabled(ctx.lang));
+        }
+
+        config.domains.enabled = true;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.domains_enable_confirm_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "disable",
+    desc = "Disable the RaidProtect domain filter",
+    desc_localizations = "domains_disable_description"
+)]
+pub struct DomainsDisableCommand;
+
+desc_localizations!(domains_disable_description);
+
+impl DomainsDisableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if !config.domains.enabled {
+            return Ok(embed::domains::not_enabled(ctx.lang));
+        }
+
+        config.domains.enabled = false;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.domains_disable_confirm_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+/// Domain list command option.
+#[derive(Debug, Clone, Copy, CommandOption, CreateOption)]
+pub enum DomainListOption {
+    #[option(name = "Blocked domains", value = "blocked")]
+    Blocked,
+    #[option(name = "Allowed domains", value = "allowed")]
+    Allowed,
+}
+
+impl DomainListOption {
+    /// Get the configured domains of the list.
+    fn list(self, config: &mut DomainFilterConfig) -> &mut Vec<String> {
+        match self {
+            DomainListOption::Blocked => &mut config.blocked,
+            DomainListOption::Allowed => &mut config.allowed,
+        }
+    }
+
+    /// Get the translated name of the list.
+    fn name(self, lang: Lang) -> &'static str {
+        match self {
+            DomainListOption::Blocked => lang.domains_list_blocked(),
+            DomainListOption::Allowed => lang.domains_list_allowed(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "add",
+    desc = "Add a domain to the blocked or allowed domains",
+    desc_localizations = "domains_add_description"
+)]
+pub struct DomainsAddCommand {
+    /// List to update.
+    list: DomainListOption,
+    /// Domain to add (subdomains are also matched).
+    domain: String,
+}
+
+desc_localizations!(domains_add_description);
+
+impl DomainsAddCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        let domain = match normalize_domain(&self.domain) {
+            Some(domain) => domain,
+            None => return Ok(embed::domains::invalid_domain(ctx.lang)),
+        };
+
+        let list = self.list.list(&mut config.domains);
+        if list.contains(&domain) {
+            return Ok(embed::domains::already_added(ctx.lang));
+        }
+
+        if list.len() >= DomainFilterConfig::MAX_DOMAINS_LEN {
+            return Ok(embed::domains::too_many(ctx.lang));
+        }
+
+        list.push(domain.clone());
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(
+                ctx.lang
+                    .domains_add_confirm_description(domain, self.list.name(ctx.lang)),
+            )
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "remove",
+    desc = "Remove a domain from the blocked or allowed domains",
+    desc_localizations = "domains_remove_description"
+)]
+pub struct DomainsRemoveCommand {
+    /// List to update.
+    list: DomainListOption,
+    /// Domain to remove.
+    domain: String,
+}
+
+desc_localizations!(domains_remove_description);
+
+impl DomainsRemoveCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        let domain = match normalize_domain(&self.domain) {
+            Some(domain) => domain,
+            None => return Ok(embed::domains::invalid_domain(ctx.lang)),
+        };
+
+        let list = self.list.list(&mut config.domains);
+        if !list.contains(&domain) {
+            return Ok(embed::domains::not_configured(ctx.lang));
+        }
+
+        list.retain(|d| d != &domain);
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(
+                ctx.lang
+                    .domains_remove_confirm_description(domain, self.list.name(ctx.lang)),
+            )
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "phishing",
+    desc = "Set whether known phishing domains are blocked",
+    desc_localizations = "domains_phishing_description"
+)]
+pub struct DomainsPhishingCommand {
+    /// Whether to block known phishing domains.
+    enabled: bool,
+}
+
+desc_localizations!(domains_phishing_description);
+
+impl DomainsPhishingCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        config.domains.block_phishing = self.enabled;
+        state.update_guild_config(&config).await?;
+
+        let description = if self.enabled {
+            ctx.lang.domains_phishing_enabled_description()
+        } else {
+            ctx.lang.domains_phishing_disabled_description()
+        };
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(description)
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "show",
+    desc = "Show the RaidProtect domain filter configuration",
+    desc_localizations = "domains_show_description"
+)]
+pub struct DomainsShowCommand;
+
+desc_localizations!(domains_show_description);
+
+impl DomainsShowCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let config = ctx.config(state).await?;
+        let domains = &config.domains;
+
+        let status = if domains.enabled {
+            ctx.lang.domains_show_enabled()
+        } else {
+            ctx.lang.domains_show_disabled()
+        };
+
+        let phishing = if domains.block_phishing {
+            ctx.lang.domains_show_phishing_enabled()
+        } else {
+            ctx.lang.domains_show_phishing_disabled()
+        };
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_TRANSPARENT)
+            .title(ctx.lang.domains_show_title())
+            .description(status)
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.domains_show_phishing(),
+                phishing,
+            ))
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.domains_show_blocked(),
+                format_domains(&domains.blocked, ctx.lang),
+            ))
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.domains_show_allowed(),
+                format_domains(&domains.allowed, ctx.lang),
+            ))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+/// Format a list of domains in an embed field.
+fn format_domains(domains: &[String], lang: Lang) -> String {
+    if domains.is_empty() {
+        return lang.domains_show_none().to_owned();
+    }
+
+    domains
+        .iter()
+        .map(|domain| format!("`{domain}`"))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .max_len(1024)
+}
@@ -0,0 +1,235 @@
+//! Word filter configuration commands.
+
+use raidprotect_anti_spam::filter::WordFilter;
+use raidprotect_model::database::model::{
+    PremiumFeature, WordFilterConfig, WordFilterRule, WordFilterRuleKind,
+};
+use twilight_interactions::command::{CommandModel, CommandOption, CreateCommand, CreateOption};
+use twilight_util::builder::embed::EmbedBuilder;
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations,
+    feature::filter::rule_kind,
+    interaction::{
+        embed::{self, COLOR_SUCCESS, COLOR_TRANSPARENT},
+        response::InteractionResponse,
+        util::GuildInteractionContext,
+    },
+    translations::Lang,
+    util::TextProcessExt,
+};
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "filter",
+    desc = "Configure the RaidProtect word filter",
+    desc_localizations = "filter_description"
+)]
+pub enum FilterConfigCommand {
+    #[command(name = "add")]
+    Add(FilterAddCommand),
+    #[command(name = "remove")]
+    Remove(FilterRemoveCommand),
+    #[command(name = "list")]
+    List(FilterListCommand),
+}
+
+desc_localizations!(filter_description);
+
+impl FilterConfigCommand {
+    pub(super) async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            FilterConfigCommand::Add(command) => command.exec(ctx, state).await,
+            FilterConfigCommand::Remove(command) => command.exec(ctx, state).await,
+            FilterConfigCommand::List(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+/// Rule kind command option.
+#[derive(Debug, Clone, Copy, CommandOption, CreateOption)]
+pub enum RuleKindOption {
+    #[option(name = "Term", value = "term")]
+    Term,
+    #[option(name = "Wildcard", value = "wildcard")]
+    Wildcard,
+    #[option(name = "Regex", value = "regex")]
+    Regex,
+}
+
+impl From<RuleKindOption> for WordFilterRuleKind {
+    fn from(option: RuleKindOption) -> Self {
+        match option {
+            RuleKindOption::Term => WordFilterRuleKind::Term,
+            RuleKindOption::Wildcard => WordFilterRuleKind::Wildcard,
+            RuleKindOption::Regex => WordFilterRuleKind::Regex,
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "add",
+    desc = "Add a rule to the word filter",
+    desc_localizations = "filter_add_description"
+)]
+pub struct FilterAddCommand {
+    /// Kind of rule.
+    kind: RuleKindOption,
+    /// Word, phrase or regex to filter.
+    pattern: String,
+}
+
+desc_localizations!(filter_add_description);
+
+impl FilterAddCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        let kind = WordFilterRuleKind::from(self.kind);
+        let pattern = self.pattern.trim().to_owned();
+
+        if kind == WordFilterRuleKind::Regex
+            && !config.entitlements.has_feature(PremiumFeature::RegexFilter)
+        {
+            return Ok(embed::error::premium_required(ctx.lang));
+        }
+
+        if pattern.chars().count() > WordFilterConfig::MAX_PATTERN_LEN {
+            return Ok(embed::filter::rule_too_long(ctx.lang));
+        }
+
+        if let Err(error) = WordFilter::validate(rule_kind(kind), &pattern) {
+            return Ok(embed::filter::invalid_rule(ctx.lang, &error));
+        }
+
+        let rules = &mut config.filter.rules;
+        if rules
+            .iter()
+            .any(|rule| rule.kind == kind && rule.pattern == pattern)
+        {
+            return Ok(embed::filter::already_added(ctx.lang));
+        }
+
+        if rules.len() >= WordFilterConfig::MAX_RULES_LEN {
+            return Ok(embed::filter::too_many(ctx.lang));
+        }
+
+        rules.push(WordFilterRule {
+            kind,
+            pattern: pattern.clone(),
+        });
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.filter_add_confirm_description(pattern))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "remove",
+    desc = "Remove a rule from the word filter",
+    desc_localizations = "filter_remove_description"
+)]
+pub struct FilterRemoveCommand {
+    /// Word, phrase or regex to remove.
+    pattern: String,
+}
+
+desc_localizations!(filter_remove_description);
+
+impl FilterRemoveCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        let pattern = self.pattern.trim();
+
+        let rules = &mut config.filter.rules;
+        if !rules.iter().any(|rule| rule.pattern == pattern) {
+            return Ok(embed::filter::not_configured(ctx.lang));
+        }
+
+        rules.retain(|rule| rule.pattern != pattern);
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.filter_remove_confirm_description(pattern))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "list",
+    desc = "List the rules of the word filter",
+    desc_localizations = "filter_list_description"
+)]
+pub struct FilterListCommand;
+
+desc_localizations!(filter_list_description);
+
+impl FilterListCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let config = ctx.config(state).await?;
+        let rules = &config.filter.rules;
+
+        let description = if rules.is_empty() {
+            ctx.lang.filter_list_empty().to_owned()
+        } else {
+            rules
+                .iter()
+                .map(|rule| {
+                    format!(
+                        "**{}** — `{}`",
+                        kind_name(rule.kind, ctx.lang),
+                        rule.pattern
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+                .max_len(4096)
+        };
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_TRANSPARENT)
+            .title(ctx.lang.filter_list_title())
+            .description(description)
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+/// Get the translated name of a rule kind.
+fn kind_name(kind: WordFilterRuleKind, lang: Lang) -> &'static str {
+    match kind {
+        WordFilterRuleKind::Term => lang.filter_kind_term(),
+        WordFilterRuleKind::Wildcard => lang.filter_kind_wildcard(),
+        WordFilterRuleKind::Regex => lang.filter_kind_regex(),
+    }
+}
@@ -0,0 +1,377 @@
+//! Invite filter configuration commands.
+
+use raidprotect_model::database::model::{AntiSpamConfig, InviteFilterConfig};
+use time::Duration;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::id::{marker::GuildMarker, Id};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations,
+    interaction::{
+        embed::{self, COLOR_SUCCESS, COLOR_TRANSPARENT},
+        response::InteractionResponse,
+        util::GuildInteractionContext,
+    },
+    util::{format_duration, format_number},
+};
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "invites",
+    desc = "Configure the RaidProtect invite filter",
+    desc_localizations = "invites_description"
+)]
+pub enum InvitesConfigCommand {
+    #[command(name = "enable")]
+    Enable(InvitesEnableCommand),
+    #[command(name = "disable")]
+    Disable(InvitesDisableCommand),
+    #[command(name = "resolve")]
+    Resolve(InvitesResolveCommand),
+    #[command(name = "allow-add")]
+    AllowAdd(InvitesAllowAddCommand),
+    #[command(name = "allow-remove")]
+    AllowRemove(InvitesAllowRemoveCommand),
+    #[command(name = "warnings")]
+    Warnings(InvitesWarningsCommand),
+    #[command(name = "show")]
+    Show(InvitesShowCommand),
+}
+
+desc_localizations!(invites_description);
+
+impl InvitesConfigCommand {
+    pub(super) async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            InvitesConfigCommand::Enable(command) => command.exec(ctx, state).await,
+            InvitesConfigCommand::Disable(command) => command.exec(ctx, state).await,
+            InvitesConfigCommand::Resolve(command) => command.exec(ctx, state).await,
+            InvitesConfigCommand::AllowAdd(command) => command.exec(ctx, state).await,
+            InvitesConfigCommand::AllowRemove(command) => command.exec(ctx, state).await,
+            InvitesConfigCommand::Warnings(command) => command.exec(ctx, state).await,
+            InvitesConfigCommand::Show(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "enable",
+    desc = "Enable the RaidProtect invite filter",
+    desc_localizations = "invites_enable_description"
+)]
+pub struct InvitesEnableCommand;
+
+desc_localizations!(invites_enable_description);
+
+impl InvitesEnableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if config.invites.enabled {
+            return Ok(embed::invites::already_enabled(ctx.lang));
+        }
+
+        config.invites.enabled = true;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.invites_enable_confirm_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "disable",
+    desc = "Disable the RaidProtect invite filter",
+    desc_localizations = "invites_disable_description"
+)]
+pub struct InvitesDisableCommand;
+
+desc_localizations!(invites_disable_description);
+
+impl InvitesDisableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if !config.invites.enabled {
+            return Ok(embed::invites::not_enabled(ctx.lang));
+        }
+
+        config.invites.enabled = false;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.invites_disable_confirm_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "resolve",
+    desc = "Set whether invites to this server and allowed servers are kept",
+    desc_localizations = "invites_resolve_description"
+)]
+pub struct InvitesResolveCommand {
+    /// Whether to check the server of each invite.
+    enabled: bool,
+}
+
+desc_localizations!(invites_resolve_description);
+
+impl InvitesResolveCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        config.invites.resolve = self.enabled;
+        state.update_guild_config(&config).await?;
+
+        let description = if self.enabled {
+            ctx.lang.invites_resolve_enabled_description()
+        } else {
+            ctx.lang.invites_resolve_disabled_description()
+        };
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(description)
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "allow-add",
+    desc = "Allow invites to another server",
+    desc_localizations = "invites_allow_add_description"
+)]
+pub struct InvitesAllowAddCommand {
+    /// Id of the server to allow.
+    server: String,
+}
+
+desc_localizations!(invites_allow_add_description);
+
+impl InvitesAllowAddCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        let guild = match parse_guild(&self.server) {
+            Some(guild) => guild,
+            None => return Ok(embed::invites::invalid_guild(ctx.lang)),
+        };
+
+        if config.invites.allowed_guilds.contains(&guild) {
+            return Ok(embed::invites::allowed_already_added(ctx.lang));
+        }
+
+        if config.invites.allowed_guilds.len() >= InviteFilterConfig::MAX_ALLOWED_GUILDS_LEN {
+            return Ok(embed::invites::allowed_too_many(ctx.lang));
+        }
+
+        config.invites.allowed_guilds.push(guild);
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.invites_allow_add_confirm_description(guild))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "allow-remove",
+    desc = "Remove a server from the allowed invites",
+    desc_localizations = "invites_allow_remove_description"
+)]
+pub struct InvitesAllowRemoveCommand {
+    /// Id of the server to remove.
+    server: String,
+}
+
+desc_localizations!(invites_allow_remove_description);
+
+impl InvitesAllowRemoveCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        let guild = match parse_guild(&self.server) {
+            Some(guild) => guild,
+            None => return Ok(embed::invites::invalid_guild(ctx.lang)),
+        };
+
+        if !config.invites.allowed_guilds.contains(&guild) {
+            return Ok(embed::invites::allowed_not_configured(ctx.lang));
+        }
+
+        config.invites.allowed_guilds.retain(|g| g != &guild);
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.invites_allow_remove_confirm_description(guild))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "warnings",
+    desc = "Set the number of warnings before a member is timed out",
+    desc_localizations = "invites_warnings_description"
+)]
+pub struct InvitesWarningsCommand {
+    /// Number of warnings before the timeout.
+    #[command(min_value = 1, max_value = 10)]
+    max: i64,
+    /// Duration of the timeout (in minutes).
+    #[command(min_value = 1, max_value = 40320)]
+    duration: Option<i64>,
+}
+
+desc_localizations!(invites_warnings_description);
+
+impl InvitesWarningsCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        config.invites.max_warnings = u8::try_from(self.max)?;
+
+        if let Some(duration) = self.duration {
+            let duration = u32::try_from(duration * 60)?;
+            config.invites.timeout_duration = duration.min(AntiSpamConfig::MAX_PUNISHMENT_DURATION);
+        }
+
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.invites_warnings_confirm_description(
+                format_duration(
+                    Duration::seconds(config.invites.timeout_duration.into()),
+                    ctx.lang,
+                ),
+                config.invites.max_warnings,
+            ))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "show",
+    desc = "Show the RaidProtect invite filter configuration",
+    desc_localizations = "invites_show_description"
+)]
+pub struct InvitesShowCommand;
+
+desc_localizations!(invites_show_description);
+
+impl InvitesShowCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let config = ctx.config(state).await?;
+        let invites = &config.invites;
+
+        let status = if invites.enabled {
+            ctx.lang.invites_show_enabled()
+        } else {
+            ctx.lang.invites_show_disabled()
+        };
+
+        let allowed = if !invites.resolve {
+            ctx.lang.invites_show_allowed_none().to_owned()
+        } else if invites.allowed_guilds.is_empty() {
+            ctx.lang.invites_show_allowed_current().to_owned()
+        } else {
+            let guilds = invites
+                .allowed_guilds
+                .iter()
+                .map(|id| format!("`{id}`"))
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            format!("{}\n{guilds}", ctx.lang.invites_show_allowed_current())
+        };
+
+        let warnings = ctx.lang.invites_show_warnings_value(
+            format_duration(Duration::seconds(invites.timeout_duration.into()), ctx.lang),
+            format_number(invites.max_warnings.into(), ctx.lang),
+        );
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_TRANSPARENT)
+            .title(ctx.lang.invites_show_title())
+            .description(status)
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.invites_show_allowed(),
+                allowed,
+            ))
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.invites_show_warnings(),
+                warnings,
+            ))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+/// Parse a server id entered by the user.
+fn parse_guild(value: &str) -> Option<Id<GuildMarker>> {
+    value.trim().parse().ok().and_then(Id::new_checked)
+}
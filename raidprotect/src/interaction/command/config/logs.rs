@@ -0,0 +1,285 @@
+//! Logged events configuration commands.
+
+use raidprotect_model::database::model::{LogCategory, LogsConfig};
+use twilight_interactions::command::{CommandModel, CommandOption, CreateCommand, CreateOption};
+use twilight_mention::Mention;
+use twilight_model::{
+    guild::Permissions,
+    id::{marker::ChannelMarker, Id},
+};
+use twilight_util::builder::embed::EmbedBuilder;
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations,
+    interaction::{
+        embed::{self, COLOR_SUCCESS},
+        response::InteractionResponse,
+        util::GuildInteractionContext,
+    },
+    translations::Lang,
+};
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "logs",
+    desc = "Configure the events logged by RaidProtect",
+    desc_localizations = "logs_description"
+)]
+pub enum LogsConfigCommand {
+    #[command(name = "enable")]
+    Enable(LogsEnableCommand),
+    #[command(name = "disable")]
+    Disable(LogsDisableCommand),
+    #[command(name = "channel")]
+    Channel(LogsChannelCommand),
+}
+
+desc_localizations!(logs_description);
+
+impl LogsConfigCommand {
+    pub(super) async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            LogsConfigCommand::Enable(command) => command.exec(ctx, state).await,
+            LogsConfigCommand::Disable(command) => command.exec(ctx, state).await,
+            LogsConfigCommand::Channel(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+/// Logged event command option.
+#[derive(Debug, Clone, Copy, CommandOption, CreateOption)]
+pub enum LogEventOption {
+    #[option(name = "Deleted messages", value = "message-delete")]
+    MessageDelete,
+    #[option(name = "Member joins", value = "member-join")]
+    MemberJoin,
+    #[option(name = "Member leaves", value = "member-leave")]
+    MemberLeave,
+    #[option(name = "Role and nickname changes", value = "member-update")]
+    MemberUpdate,
+    #[option(
+        name = "Added bots and dangerous permissions",
+        value = "security-alerts"
+    )]
+    SecurityAlerts,
+    #[option(name = "Voice channel activity", value = "voice")]
+    Voice,
+}
+
+impl LogEventOption {
+    /// Get the configuration toggle of the event.
+    fn toggle(self, config: &mut LogsConfig) -> &mut bool {
+        match self {
+            LogEventOption::MessageDelete => &mut config.message_delete,
+            LogEventOption::MemberJoin => &mut config.member_join,
+            LogEventOption::MemberLeave => &mut config.member_leave,
+            LogEventOption::MemberUpdate => &mut config.member_update,
+            LogEventOption::SecurityAlerts => &mut config.security_alerts,
+            LogEventOption::Voice => &mut config.voice,
+        }
+    }
+
+    /// Get the name of the event.
+    fn name(self, lang: Lang) -> &'static str {
+        match self {
+            LogEventOption::MessageDelete => lang.logs_event_message_delete(),
+            LogEventOption::MemberJoin => lang.logs_event_member_join(),
+            LogEventOption::MemberLeave => lang.logs_event_member_leave(),
+            LogEventOption::MemberUpdate => lang.logs_event_member_update(),
+            LogEventOption::SecurityAlerts => lang.logs_event_security_alerts(),
+            LogEventOption::Voice => lang.logs_event_voice(),
+        }
+    }
+}
+
+/// Logs category command option.
+#[derive(Debug, Clone, Copy, CommandOption, CreateOption)]
+pub enum LogCategoryOption {
+    #[option(name = "Moderation", value = "moderation")]
+    Moderation,
+    #[option(name = "Messages", value = "messages")]
+    Messages,
+    #[option(name = "Members", value = "members")]
+    Members,
+    #[option(name = "Captcha", value = "captcha")]
+    Captcha,
+    #[option(name = "Anti-spam", value = "antispam")]
+    AntiSpam,
+    #[option(name = "Voice", value = "voice")]
+    Voice,
+}
+
+impl LogCategoryOption {
+    /// Get the corresponding [`LogCategory`].
+    fn category(self) -> LogCategory {
+        match self {
+            LogCategoryOption::Moderation => LogCategory::Moderation,
+            LogCategoryOption::Messages => LogCategory::Messages,
+            LogCategoryOption::Members => LogCategory::Members,
+            LogCategoryOption::Captcha => LogCategory::Captcha,
+            LogCategoryOption::AntiSpam => LogCategory::AntiSpam,
+            LogCategoryOption::Voice => LogCategory::Voice,
+        }
+    }
+
+    /// Get the name of the category.
+    fn name(self, lang: Lang) -> &'static str {
+        match self {
+            LogCategoryOption::Moderation => lang.logs_category_moderation(),
+            LogCategoryOption::Messages => lang.logs_category_messages(),
+            LogCategoryOption::Members => lang.logs_category_members(),
+            LogCategoryOption::Captcha => lang.logs_category_captcha(),
+            LogCategoryOption::AntiSpam => lang.logs_category_antispam(),
+            LogCategoryOption::Voice => lang.logs_category_voice(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "enable",
+    desc = "Log an event in the logs channel",
+    desc_localizations = "logs_enable_description"
+)]
+pub struct LogsEnableCommand {
+    /// Event to log.
+    event: LogEventOption,
+}
+
+desc_localizations!(logs_enable_description);
+
+impl LogsEnableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        let event = self.event.name(ctx.lang);
+
+        let toggle = self.event.toggle(&mut config.logs);
+        if *toggle {
+            return Ok(embed::logs::already_enabled(ctx.lang, event));
+        }
+
+        *toggle = true;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.logs_enable_confirm_description(event))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "disable",
+    desc = "Stop logging an event in the logs channel",
+    desc_localizations = "logs_disable_description"
+)]
+pub struct LogsDisableCommand {
+    /// Event to stop logging.
+    event: LogEventOption,
+}
+
+desc_localizations!(logs_disable_description);
+
+impl LogsDisableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        let event = self.event.name(ctx.lang);
+
+        let toggle = self.event.toggle(&mut config.logs);
+        if !*toggle {
+            return Ok(embed::logs::not_enabled(ctx.lang, event));
+        }
+
+        *toggle = false;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.logs_disable_confirm_description(event))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "channel",
+    desc = "Set the logs channel of a category",
+    desc_localizations = "logs_channel_description"
+)]
+pub struct LogsChannelCommand {
+    /// Category of logs.
+    category: LogCategoryOption,
+    /// Channel to send the logs to (the default logs channel if not set).
+    #[command(channel_types = "guild_text")]
+    channel: Option<Id<ChannelMarker>>,
+}
+
+desc_localizations!(logs_channel_description);
+
+impl LogsChannelCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        let category = self.category.name(ctx.lang);
+
+        // Ensure RaidProtect has permissions to send messages in the channel.
+        if let Some(channel) = self.channel {
+            let (permissions, _) = state
+                .cache
+                .permissions(ctx.guild_id)
+                .await?
+                .current_member()
+                .await?
+                .channel(channel)
+                .await?;
+
+            if !permissions.contains(Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS) {
+                return Ok(embed::logs::missing_permission(ctx.lang));
+            }
+        }
+
+        config
+            .logs
+            .channels
+            .set(self.category.category(), self.channel);
+        state.update_guild_config(&config).await?;
+
+        let description = match self.channel {
+            Some(channel) => ctx
+                .lang
+                .logs_channel_confirm_description(category, channel.mention()),
+            None => ctx.lang.logs_channel_reset_description(category),
+        };
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(description)
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
@@ -3,11 +3,37 @@
 //! The configuration command allows the user to change the configuration of the
 //! bot.
 
+mod antinuke;
+mod antispam;
+mod api_keys;
+mod attachments;
 mod captcha;
+mod domains;
+mod filter;
+mod invites;
+mod logs;
+mod notifications;
+mod phishing;
+mod quarantine;
+mod stats;
+mod webhooks;
 
+pub use antinuke::AntiNukeConfigCommand;
+pub use antispam::AntiSpamConfigCommand;
+pub use api_keys::ApiKeysConfigCommand;
+pub use attachments::AttachmentsConfigCommand;
 pub use captcha::CaptchaConfigCommand;
+pub use domains::DomainsConfigCommand;
+pub use filter::FilterConfigCommand;
+pub use invites::InvitesConfigCommand;
+pub use logs::LogsConfigCommand;
+pub use notifications::NotificationsConfigCommand;
+pub use phishing::PhishingConfigCommand;
+pub use quarantine::QuarantineConfigCommand;
+pub use stats::StatsConfigCommand;
 use twilight_interactions::command::{CommandModel, CreateCommand};
 use twilight_model::guild::Permissions;
+pub use webhooks::WebhooksConfigCommand;
 
 use crate::{
     cluster::ClusterState,
@@ -27,8 +53,34 @@ use crate::{
     dm_permission = false
 )]
 pub enum ConfigCommand {
+    #[command(name = "antinuke")]
+    AntiNuke(AntiNukeConfigCommand),
+    #[command(name = "apikeys")]
+    ApiKeys(ApiKeysConfigCommand),
+    #[command(name = "antispam")]
+    AntiSpam(AntiSpamConfigCommand),
+    #[command(name = "attachments")]
+    Attachments(AttachmentsConfigCommand),
     #[command(name = "captcha")]
     Captcha(CaptchaConfigCommand),
+    #[command(name = "domains")]
+    Domains(DomainsConfigCommand),
+    #[command(name = "filter")]
+    Filter(FilterConfigCommand),
+    #[command(name = "invites")]
+    Invites(InvitesConfigCommand),
+    #[command(name = "logs")]
+    Logs(LogsConfigCommand),
+    #[command(name = "notifications")]
+    Notifications(NotificationsConfigCommand),
+    #[command(name = "phishing")]
+    Phishing(PhishingConfigCommand),
+    #[command(name = "quarantine")]
+    Quarantine(QuarantineConfigCommand),
+    #[command(name = "stats")]
+    Stats(StatsConfigCommand),
+    #[command(name = "webhooks")]
+    Webhooks(WebhooksConfigCommand),
 }
 
 impl_guild_command_handle!(ConfigCommand);
@@ -45,7 +97,20 @@ impl ConfigCommand {
         state: &ClusterState,
     ) -> Result<InteractionResponse, anyhow::Error> {
         match self {
+            Self::AntiNuke(command) => command.exec(ctx, state).await,
+            Self::ApiKeys(command) => command.exec(ctx, state).await,
+            Self::AntiSpam(command) => command.exec(ctx, state).await,
+            Self::Attachments(command) => command.exec(ctx, state).await,
             Self::Captcha(command) => command.exec(ctx, state).await,
+            Self::Domains(command) => command.exec(ctx, state).await,
+            Self::Filter(command) => command.exec(ctx, state).await,
+            Self::Invites(command) => command.exec(ctx, state).await,
+            Self::Logs(command) => command.exec(ctx, state).await,
+            Self::Notifications(command) => command.exec(ctx, state).await,
+            Self::Phishing(command) => command.exec(ctx, state).await,
+            Self::Quarantine(command) => command.exec(ctx, state).await,
+            Self::Stats(command) => command.exec(ctx, state).await,
+            Self::Webhooks(command) => command.exec(ctx, state).await,
         }
     }
 }
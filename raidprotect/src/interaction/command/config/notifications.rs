@@ -0,0 +1,129 @@
+//! Outgoing webhook notifications configuration commands.
+
+use rand::{distributions::Alphanumeric, Rng};
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_util::builder::embed::EmbedBuilder;
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations,
+    feature::webhooks::{self, SIGNATURE_HEADER},
+    interaction::{
+        embed::{self, COLOR_SUCCESS},
+        response::InteractionResponse,
+        util::GuildInteractionContext,
+    },
+};
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "notifications",
+    desc = "Configure the notifications sent to an external webhook",
+    desc_localizations = "notifications_description"
+)]
+pub enum NotificationsConfigCommand {
+    #[command(name = "set")]
+    Set(NotificationsSetCommand),
+    #[command(name = "remove")]
+    Remove(NotificationsRemoveCommand),
+}
+
+desc_localizations!(notifications_description);
+
+impl NotificationsConfigCommand {
+    pub(super) async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            NotificationsConfigCommand::Set(command) => command.exec(ctx, state).await,
+            NotificationsConfigCommand::Remove(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "set",
+    desc = "Set the webhook that receives the notifications",
+    desc_localizations = "notifications_set_description"
+)]
+pub struct NotificationsSetCommand {
+    /// HTTPS URL of the webhook.
+    url: String,
+}
+
+desc_localizations!(notifications_set_description);
+
+impl NotificationsSetCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let url = match webhooks::parse_url(self.url.trim()) {
+            Some(url) => url,
+            None => return Ok(embed::notifications::invalid_url(ctx.lang)),
+        };
+
+        // A new secret is generated each time the webhook is set.
+        let secret: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(32)
+            .map(char::from)
+            .collect();
+
+        let mut config = ctx.config(state).await?;
+        config.notifications.url = Some(url.to_string());
+        config.notifications.secret = Some(secret.clone());
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.notifications_set_confirm_description(
+                SIGNATURE_HEADER,
+                secret,
+                url,
+            ))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "remove",
+    desc = "Stop sending notifications to the webhook",
+    desc_localizations = "notifications_remove_description"
+)]
+pub struct NotificationsRemoveCommand;
+
+desc_localizations!(notifications_remove_description);
+
+impl NotificationsRemoveCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if config.notifications.url.is_none() {
+            return Ok(embed::notifications::not_configured(ctx.lang));
+        }
+
+        config.notifications.url = None;
+        config.notifications.secret = None;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.notifications_remove_confirm_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
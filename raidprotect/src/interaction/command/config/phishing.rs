@@ -0,0 +1,156 @@
+//! Phishing detection configuration commands.
+
+use raidprotect_model::database::model::PhishingConfig;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_util::builder::embed::EmbedBuilder;
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations,
+    interaction::{
+        embed::{self, COLOR_SUCCESS},
+        response::InteractionResponse,
+        util::GuildInteractionContext,
+    },
+};
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "phishing",
+    desc = "Configure the RaidProtect phishing detection",
+    desc_localizations = "phishing_description"
+)]
+pub enum PhishingConfigCommand {
+    #[command(name = "enable")]
+    Enable(PhishingEnableCommand),
+    #[command(name = "disable")]
+    Disable(PhishingDisableCommand),
+    #[command(name = "threshold")]
+    Threshold(PhishingThresholdCommand),
+}
+
+desc_localizations!(phishing_description);
+
+impl PhishingConfigCommand {
+    pub(super) async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            PhishingConfigCommand::Enable(command) => command.exec(ctx, state).await,
+            PhishingConfigCommand::Disable(command) => command.exec(ctx, state).await,
+            PhishingConfigCommand::Threshold(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "enable",
+    desc = "Enable the RaidProtect phishing detection",
+    desc_localizations = "phishing_enable_description"
+)]
+pub struct PhishingEnableCommand;
+
+desc_localizations!(phishing_enable_description);
+
+impl PhishingEnableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if config.phishing.enabled {
+            return Ok(embed::phishing::already_enabled(ctx.lang));
+        }
+
+        config.phishing.enabled = true;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(
+                ctx.lang
+                    .phishing_enable_confirm_description(config.phishing.threshold),
+            )
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "disable",
+    desc = "Disable the RaidProtect phishing detection",
+    desc_localizations = "phishing_disable_description"
+)]
+pub struct PhishingDisableCommand;
+
+desc_localizations!(phishing_disable_description);
+
+impl PhishingDisableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if !config.phishing.enabled {
+            return Ok(embed::phishing::not_enabled(ctx.lang));
+        }
+
+        config.phishing.enabled = false;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.phishing_disable_confirm_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "threshold",
+    desc = "Set the score above which a message is considered as phishing",
+    desc_localizations = "phishing_threshold_description"
+)]
+pub struct PhishingThresholdCommand {
+    /// Phishing score threshold (lower values are stricter).
+    #[command(min_value = 1, max_value = 100)]
+    score: i64,
+}
+
+desc_localizations!(phishing_threshold_description);
+
+impl PhishingThresholdCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        let threshold = u8::try_from(self.score)?;
+        config.phishing.threshold = threshold.min(PhishingConfig::MAX_THRESHOLD);
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(
+                ctx.lang
+                    .phishing_threshold_confirm_description(config.phishing.threshold),
+            )
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
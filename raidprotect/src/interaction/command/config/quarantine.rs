@@ -0,0 +1,132 @@
+//! Quarantine configuration commands.
+
+use raidprotect_model::cache::discord::permission::RoleOrdering;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::Mention;
+use twilight_model::guild::{Permissions, Role};
+use twilight_util::builder::embed::EmbedBuilder;
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations,
+    interaction::{
+        embed::{self, COLOR_SUCCESS},
+        response::InteractionResponse,
+        util::GuildInteractionContext,
+    },
+};
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "quarantine",
+    desc = "Configure the RaidProtect quarantine",
+    desc_localizations = "quarantine_description"
+)]
+pub enum QuarantineConfigCommand {
+    #[command(name = "role")]
+    Role(QuarantineRoleCommand),
+    #[command(name = "disable")]
+    Disable(QuarantineDisableCommand),
+}
+
+desc_localizations!(quarantine_description);
+
+impl QuarantineConfigCommand {
+    pub(super) async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            QuarantineConfigCommand::Role(command) => command.exec(ctx, state).await,
+            QuarantineConfigCommand::Disable(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "role",
+    desc = "Set the role given to quarantined members",
+    desc_localizations = "quarantine_role_description"
+)]
+pub struct QuarantineRoleCommand {
+    /// Role given to quarantined members.
+    role: Role,
+}
+
+desc_localizations!(quarantine_role_description);
+
+impl QuarantineRoleCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        // Ensure RaidProtect has permissions to give this role.
+        let permissions = state
+            .cache
+            .permissions(ctx.guild_id)
+            .await?
+            .current_member()
+            .await?;
+
+        if !permissions.guild().contains(Permissions::MANAGE_ROLES) {
+            return Ok(embed::quarantine::missing_role_permission(ctx.lang));
+        }
+
+        if RoleOrdering::from(&self.role) >= permissions.highest_role() {
+            return Ok(embed::quarantine::role_hierarchy(ctx.lang));
+        }
+
+        config.quarantine.role = Some(self.role.id);
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(
+                ctx.lang
+                    .quarantine_role_confirm_description(self.role.mention()),
+            )
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "disable",
+    desc = "Disable the RaidProtect quarantine",
+    desc_localizations = "quarantine_disable_description"
+)]
+pub struct QuarantineDisableCommand;
+
+desc_localizations!(quarantine_disable_description);
+
+impl QuarantineDisableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if config.quarantine.role.is_none() {
+            return Ok(embed::quarantine::not_enabled(ctx.lang));
+        }
+
+        config.quarantine.role = None;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.quarantine_disable_confirm_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
@@ -0,0 +1,114 @@
+//! Public statistics configuration commands.
+
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_util::builder::embed::EmbedBuilder;
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations,
+    interaction::{
+        embed::{self, COLOR_SUCCESS},
+        response::InteractionResponse,
+        util::GuildInteractionContext,
+    },
+};
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "stats",
+    desc = "Configure the public statistics of the server",
+    desc_localizations = "stats_description"
+)]
+pub enum StatsConfigCommand {
+    #[command(name = "enable")]
+    Enable(StatsEnableCommand),
+    #[command(name = "disable")]
+    Disable(StatsDisableCommand),
+}
+
+desc_localizations!(stats_description);
+
+impl StatsConfigCommand {
+    pub(super) async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            StatsConfigCommand::Enable(command) => command.exec(ctx, state).await,
+            StatsConfigCommand::Disable(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "enable",
+    desc = "Make the statistics of the server public",
+    desc_localizations = "stats_enable_description"
+)]
+pub struct StatsEnableCommand;
+
+desc_localizations!(stats_enable_description);
+
+impl StatsEnableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if config.stats.public {
+            return Ok(embed::stats::already_enabled(ctx.lang));
+        }
+
+        config.stats.public = true;
+        state.update_guild_config(&config).await?;
+
+        let embed =
+            EmbedBuilder::new()
+                .color(COLOR_SUCCESS)
+                .title(ctx.lang.config_updated_title())
+                .description(ctx.lang.stats_enable_confirm_description(format!(
+                    "/api/guilds/{}/stats",
+                    ctx.guild_id
+                )))
+                .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "disable",
+    desc = "Make the statistics of the server private",
+    desc_localizations = "stats_disable_description"
+)]
+pub struct StatsDisableCommand;
+
+desc_localizations!(stats_disable_description);
+
+impl StatsDisableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if !config.stats.public {
+            return Ok(embed::stats::not_enabled(ctx.lang));
+        }
+
+        config.stats.public = false;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.stats_disable_confirm_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
@@ -0,0 +1,171 @@
+//! Webhook messages checks configuration commands.
+
+use raidprotect_model::database::model::WebhookAction;
+use twilight_interactions::command::{CommandModel, CommandOption, CreateCommand, CreateOption};
+use twilight_util::builder::embed::EmbedBuilder;
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations,
+    interaction::{
+        embed::{self, COLOR_SUCCESS},
+        response::InteractionResponse,
+        util::GuildInteractionContext,
+    },
+};
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "webhooks",
+    desc = "Configure the RaidProtect webhook messages checks",
+    desc_localizations = "webhooks_description"
+)]
+pub enum WebhooksConfigCommand {
+    #[command(name = "enable")]
+    Enable(WebhooksEnableCommand),
+    #[command(name = "disable")]
+    Disable(WebhooksDisableCommand),
+    #[command(name = "action")]
+    Action(WebhooksActionCommand),
+}
+
+desc_localizations!(webhooks_description);
+
+impl WebhooksConfigCommand {
+    pub(super) async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            WebhooksConfigCommand::Enable(command) => command.exec(ctx, state).await,
+            WebhooksConfigCommand::Disable(command) => command.exec(ctx, state).await,
+            WebhooksConfigCommand::Action(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "enable",
+    desc = "Enable the RaidProtect webhook messages checks",
+    desc_localizations = "webhooks_enable_description"
+)]
+pub struct WebhooksEnableCommand;
+
+desc_localizations!(webhooks_enable_description);
+
+impl WebhooksEnableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if config.webhooks.enabled {
+            return Ok(embed::webhooks::already_enabled(ctx.lang));
+        }
+
+        config.webhooks.enabled = true;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.webhooks_enable_confirm_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "disable",
+    desc = "Disable the RaidProtect webhook messages checks",
+    desc_localizations = "webhooks_disable_description"
+)]
+pub struct WebhooksDisableCommand;
+
+desc_localizations!(webhooks_disable_description);
+
+impl WebhooksDisableCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+        if !config.webhooks.enabled {
+            return Ok(embed::webhooks::not_enabled(ctx.lang));
+        }
+
+        config.webhooks.enabled = false;
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(ctx.lang.webhooks_disable_confirm_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "action",
+    desc = "Set the action applied to abusive webhook messages",
+    desc_localizations = "webhooks_action_description"
+)]
+pub struct WebhooksActionCommand {
+    /// Action applied to abusive webhook messages.
+    action: ActionOption,
+}
+
+/// Webhook action command option.
+#[derive(Debug, Clone, Copy, CommandOption, CreateOption)]
+pub enum ActionOption {
+    #[option(name = "Delete messages", value = "delete")]
+    Delete,
+    #[option(name = "Delete messages and webhook", value = "disable")]
+    Disable,
+}
+
+impl From<ActionOption> for WebhookAction {
+    fn from(option: ActionOption) -> Self {
+        match option {
+            ActionOption::Delete => WebhookAction::Delete,
+            ActionOption::Disable => WebhookAction::Disable,
+        }
+    }
+}
+
+desc_localizations!(webhooks_action_description);
+
+impl WebhooksActionCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut config = ctx.config(state).await?;
+
+        config.webhooks.action = self.action.into();
+        state.update_guild_config(&config).await?;
+
+        let description = match config.webhooks.action {
+            WebhookAction::Delete => ctx.lang.webhooks_action_delete_description(),
+            WebhookAction::Disable => ctx.lang.webhooks_action_disable_description(),
+        };
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(description)
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
@@ -0,0 +1,504 @@
+//! Debug command.
+//!
+//! This command allows the bot owners to inspect the internal state of the bot
+//! for operational debugging. It is only registered in the administration
+//! guild.
+
+use raidprotect_model::cache::{
+    model::{
+        gateway::{EventTap, ShardStatus},
+        metrics::ProcessMetrics,
+    },
+    stats::{CacheReport, ModelStats},
+};
+use time::OffsetDateTime;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_mention::{
+    timestamp::{Timestamp, TimestampStyle},
+    Mention,
+};
+use twilight_model::{
+    guild::Permissions,
+    id::{marker::GuildMarker, Id},
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations, impl_command_handle,
+    interaction::{
+        embed::{self, COLOR_SUCCESS, COLOR_TRANSPARENT},
+        middleware::{command_usage, CommandUsage},
+        response::InteractionResponse,
+        util::InteractionContext,
+    },
+    util::TextProcessExt,
+};
+
+/// Maximum number of key groups shown in the cache report.
+const MAX_KEY_GROUPS: usize = 15;
+
+/// Maximum number of commands shown in the usage report.
+const MAX_COMMANDS: usize = 20;
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "debug",
+    desc = "Inspect the internal state of RaidProtect",
+    desc_localizations = "debug_description",
+    default_permissions = "debug_permissions",
+    dm_permission = false
+)]
+pub enum DebugCommand {
+    #[command(name = "cache")]
+    Cache(DebugCacheCommand),
+    #[command(name = "commands")]
+    Commands(DebugCommandsCommand),
+    #[command(name = "shards")]
+    Shards(DebugShardsCommand),
+    #[command(name = "tap")]
+    Tap(DebugTapCommand),
+}
+
+impl_command_handle!(DebugCommand);
+desc_localizations!(debug_description);
+
+fn debug_permissions() -> Permissions {
+    Permissions::ADMINISTRATOR
+}
+
+impl DebugCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            DebugCommand::Cache(command) => command.exec(ctx, state).await,
+            DebugCommand::Commands(command) => command.exec(ctx, state).await,
+            DebugCommand::Shards(command) => command.exec(ctx, state).await,
+            DebugCommand::Tap(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "cache",
+    desc = "Show the cache size and usage statistics",
+    desc_localizations = "debug_cache_description"
+)]
+pub struct DebugCacheCommand;
+
+desc_localizations!(debug_cache_description);
+
+impl DebugCacheCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let report = state.cache_report().await?;
+
+        let used_memory = report
+            .used_memory
+            .map(format_bytes)
+            .unwrap_or_else(|| "?".to_owned());
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_TRANSPARENT)
+            .title(ctx.lang.debug_cache_title())
+            .description(ctx.lang.debug_cache_memory(used_memory))
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.debug_cache_keys(),
+                format_keys(&report),
+            ))
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.debug_cache_models(),
+                format_models(&report),
+            ))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "commands",
+    desc = "Show the usage, latency and error rate of the commands",
+    desc_localizations = "debug_commands_description"
+)]
+pub struct DebugCommandsCommand;
+
+desc_localizations!(debug_commands_description);
+
+impl DebugCommandsCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let processes = state
+            .cache
+            .scan::<ProcessMetrics>(ProcessMetrics::KEY_PATTERN)
+            .await?;
+        let families = processes
+            .into_iter()
+            .flat_map(|process| process.metrics)
+            .collect::<Vec<_>>();
+
+        let usage = command_usage(&families);
+        let invocations = usage.iter().map(|usage| usage.invocations).sum::<u64>();
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_TRANSPARENT)
+            .title(ctx.lang.debug_commands_title())
+            .description(ctx.lang.debug_commands_total(invocations))
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.debug_commands_usage(),
+                format_usage(&usage),
+            ))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "shards",
+    desc = "Show the connection status of the shards",
+    desc_localizations = "debug_shards_description"
+)]
+pub struct DebugShardsCommand;
+
+desc_localizations!(debug_shards_description);
+
+impl DebugShardsCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let status = state.shard_status().await?;
+        let connected = status.iter().filter(|status| status.connected).count();
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_TRANSPARENT)
+            .title(ctx.lang.debug_shards_title())
+            .description(ctx.lang.debug_shards_connected(connected, status.len()))
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.debug_shards_status(),
+                format_shards(&status),
+            ))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "tap",
+    desc = "Record the raw gateway events",
+    desc_localizations = "debug_tap_description"
+)]
+pub enum DebugTapCommand {
+    #[command(name = "start")]
+    Start(DebugTapStartCommand),
+    #[command(name = "stop")]
+    Stop(DebugTapStopCommand),
+}
+
+desc_localizations!(debug_tap_description);
+
+impl DebugTapCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            DebugTapCommand::Start(command) => command.exec(ctx, state).await,
+            DebugTapCommand::Stop(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "start",
+    desc = "Start recording the raw gateway events",
+    desc_localizations = "debug_tap_start_description"
+)]
+pub struct DebugTapStartCommand {
+    /// Recording duration (in minutes).
+    #[command(min_value = 1, max_value = 60)]
+    duration: i64,
+    /// Only record the events of this server (id).
+    guild: Option<String>,
+    /// Only record these event types, separated by commas (e.g. MESSAGE_CREATE).
+    events: Option<String>,
+}
+
+desc_localizations!(debug_tap_start_description);
+
+impl DebugTapStartCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = match self.guild.as_deref().map(parse_guild_id) {
+            Some(Some(guild_id)) => Some(guild_id),
+            Some(None) => return Ok(embed::debug::invalid_guild(ctx.lang)),
+            None => None,
+        };
+
+        let tap = EventTap {
+            guild_id,
+            event_types: self
+                .events
+                .as_deref()
+                .map(parse_event_types)
+                .unwrap_or_default(),
+            started_by: ctx.author.id,
+            expires_at: OffsetDateTime::now_utc() + time::Duration::minutes(self.duration),
+        };
+        state.cache.set(&tap).await?;
+
+        let expires = Timestamp::new(
+            tap.expires_at.unix_timestamp() as u64,
+            Some(TimestampStyle::RelativeTime),
+        );
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .description(
+                ctx.lang
+                    .debug_tap_started(expires.mention(), EventTap::STREAM_KEY),
+            )
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "stop",
+    desc = "Stop recording the raw gateway events",
+    desc_localizations = "debug_tap_stop_description"
+)]
+pub struct DebugTapStopCommand;
+
+desc_localizations!(debug_tap_stop_description);
+
+impl DebugTapStopCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let tap = match state.cache.get::<EventTap>(&()).await? {
+            Some(tap) => tap,
+            None => return Ok(embed::debug::tap_not_running(ctx.lang)),
+        };
+        state.cache.delete(&tap).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .description(ctx.lang.debug_tap_stopped())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+/// Parse a guild id entered by the user.
+pub(super) fn parse_guild_id(value: &str) -> Option<Id<GuildMarker>> {
+    value.trim().parse().ok()
+}
+
+/// Parse a comma-separated list of event types.
+///
+/// Event types are converted to the gateway format (`MESSAGE_CREATE`).
+fn parse_event_types(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|kind| kind.trim().to_uppercase().replace([' ', '-'], "_"))
+        .filter(|kind| !kind.is_empty())
+        .collect()
+}
+
+/// Format the status of the shards.
+fn format_shards(status: &[ShardStatus]) -> String {
+    if status.is_empty() {
+        return "-".to_owned();
+    }
+
+    status
+        .iter()
+        .map(|status| {
+            let latency = match status.latency {
+                Some(latency) => format!("{latency} ms"),
+                None => "-".to_owned(),
+            };
+
+            let mut line = format!(
+                "`#{}` {} — {latency}, {} events/min, {} queued",
+                status.shard_id, status.stage, status.events_per_minute, status.queue_depth
+            );
+
+            if let Some(bytes) = status.payload_bytes_per_minute {
+                line.push_str(&format!(", {}/min", format_bytes(bytes)));
+            }
+
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .max_len(1024)
+}
+
+/// Format the usage of the commands.
+fn format_usage(usage: &[CommandUsage]) -> String {
+    if usage.is_empty() {
+        return "-".to_owned();
+    }
+
+    usage
+        .iter()
+        .take(MAX_COMMANDS)
+        .map(|usage| {
+            let error_rate = match usage.error_rate() {
+                Some(rate) => format!("{:.1}%", rate * 100.0),
+                None => "-".to_owned(),
+            };
+            let average = match usage.average_duration() {
+                Some(average) => format!("{:.0} ms", average * 1000.0),
+                None => "-".to_owned(),
+            };
+            let p95 = match usage.duration_quantile(0.95) {
+                Some(p95) => format!("≤ {:.0} ms", p95 * 1000.0),
+                None => "-".to_owned(),
+            };
+
+            format!(
+                "`/{}` — {} uses, {error_rate} errors, avg {average}, p95 {p95}",
+                usage.name, usage.invocations
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .max_len(1024)
+}
+
+/// Format the size of the key groups.
+fn format_keys(report: &CacheReport) -> String {
+    if report.keys.is_empty() {
+        return "-".to_owned();
+    }
+
+    report
+        .keys
+        .iter()
+        .take(MAX_KEY_GROUPS)
+        .map(|group| {
+            format!(
+                "`{}` — {} keys, ~{}",
+                group.group,
+                group.count,
+                format_bytes(group.memory)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .max_len(1024)
+}
+
+/// Format the counters of the cached models.
+fn format_models(report: &CacheReport) -> String {
+    if report.models.is_empty() {
+        return "-".to_owned();
+    }
+
+    report
+        .models
+        .iter()
+        .map(|(name, stats)| format!("`{name}` — {}", format_stats(stats)))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .max_len(1024)
+}
+
+fn format_stats(stats: &ModelStats) -> String {
+    let hit_rate = match stats.hit_rate() {
+        Some(rate) => format!("{:.1}%", rate * 100.0),
+        None => "-".to_owned(),
+    };
+
+    format!(
+        "{hit_rate} ({}/{}), +{} -{}",
+        stats.hits,
+        stats.hits + stats.misses,
+        stats.inserts,
+        stats.evictions
+    )
+}
+
+/// Format a size in bytes with a binary unit.
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+        assert_eq!(format_bytes(u64::MAX), "17179869184.0 GiB");
+    }
+
+    #[test]
+    fn test_parse_event_types() {
+        assert_eq!(
+            parse_event_types("message_create, GUILD-MEMBER-ADD,,"),
+            vec!["MESSAGE_CREATE", "GUILD_MEMBER_ADD"]
+        );
+        assert!(parse_event_types(" ").is_empty());
+        assert_eq!(parse_guild_id(" 123 "), Some(Id::new(123)));
+        assert_eq!(parse_guild_id("0"), None);
+    }
+
+    #[test]
+    fn test_format_stats() {
+        let stats = ModelStats {
+            hits: 3,
+            misses: 1,
+            inserts: 2,
+            evictions: 1,
+        };
+
+        assert_eq!(format_stats(&stats), "75.0% (3/4), +2 -1");
+        assert_eq!(format_stats(&ModelStats::default()), "- (0/0), +0 -0");
+    }
+}
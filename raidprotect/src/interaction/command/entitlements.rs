@@ -0,0 +1,258 @@
+//! Entitlements command.
+//!
+//! This command allows the bot owners to grant and revoke the premium
+//! entitlements of guilds (see [`Entitlements`]). It is only registered in the
+//! administration guild.
+
+use raidprotect_model::{
+    cache::discord::CachedGuild,
+    database::model::{Entitlements, PremiumFeature, PremiumTier},
+};
+use time::OffsetDateTime;
+use twilight_interactions::command::{CommandModel, CommandOption, CreateCommand, CreateOption};
+use twilight_mention::{
+    timestamp::{Timestamp, TimestampStyle},
+    Mention,
+};
+use twilight_model::{
+    guild::Permissions,
+    id::{marker::GuildMarker, Id},
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+use super::debug::parse_guild_id;
+use crate::{
+    cluster::ClusterState,
+    desc_localizations, impl_command_handle,
+    interaction::{
+        embed::{self, COLOR_SUCCESS, COLOR_TRANSPARENT},
+        response::InteractionResponse,
+        util::InteractionContext,
+    },
+    translations::Lang,
+};
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "entitlements",
+    desc = "Manage the premium entitlements of servers",
+    desc_localizations = "entitlements_description",
+    default_permissions = "entitlements_permissions",
+    dm_permission = false
+)]
+pub enum EntitlementsCommand {
+    #[command(name = "grant")]
+    Grant(EntitlementsGrantCommand),
+    #[command(name = "revoke")]
+    Revoke(EntitlementsRevokeCommand),
+    #[command(name = "show")]
+    Show(EntitlementsShowCommand),
+}
+
+impl_command_handle!(EntitlementsCommand);
+desc_localizations!(entitlements_description);
+
+fn entitlements_permissions() -> Permissions {
+    Permissions::ADMINISTRATOR
+}
+
+impl EntitlementsCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            EntitlementsCommand::Grant(command) => command.exec(ctx, state).await,
+            EntitlementsCommand::Revoke(command) => command.exec(ctx, state).await,
+            EntitlementsCommand::Show(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+/// Entitlement command option.
+#[derive(Debug, Clone, Copy, CommandOption, CreateOption)]
+pub enum EntitlementOption {
+    #[option(name = "Premium tier", value = "premium")]
+    Premium,
+    #[option(name = "Regex filter", value = "regex_filter")]
+    RegexFilter,
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "grant",
+    desc = "Grant premium entitlements to a server",
+    desc_localizations = "entitlements_grant_description"
+)]
+pub struct EntitlementsGrantCommand {
+    /// Id of the server.
+    guild: String,
+    /// Entitlement to grant.
+    entitlement: EntitlementOption,
+    /// Duration of the entitlements (in days). They never expire if not set.
+    #[command(min_value = 1, max_value = 3650)]
+    days: Option<i64>,
+}
+
+desc_localizations!(entitlements_grant_description);
+
+impl EntitlementsGrantCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = match find_guild(&self.guild, state).await? {
+            Some(guild_id) => guild_id,
+            None => return Ok(embed::entitlements::invalid_guild(ctx.lang)),
+        };
+
+        let mut config = state.guild_config(guild_id).await?;
+        let entitlements = &mut config.entitlements;
+
+        match self.entitlement {
+            EntitlementOption::Premium => entitlements.tier = PremiumTier::Premium,
+            EntitlementOption::RegexFilter => {
+                if !entitlements.features.contains(&PremiumFeature::RegexFilter) {
+                    entitlements.features.push(PremiumFeature::RegexFilter);
+                }
+            }
+        }
+
+        entitlements.expires_at = self
+            .days
+            .map(|days| OffsetDateTime::now_utc() + time::Duration::days(days));
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .description(ctx.lang.entitlements_granted(guild_id.to_string()))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "revoke",
+    desc = "Revoke all the premium entitlements of a server",
+    desc_localizations = "entitlements_revoke_description"
+)]
+pub struct EntitlementsRevokeCommand {
+    /// Id of the server.
+    guild: String,
+}
+
+desc_localizations!(entitlements_revoke_description);
+
+impl EntitlementsRevokeCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = match find_guild(&self.guild, state).await? {
+            Some(guild_id) => guild_id,
+            None => return Ok(embed::entitlements::invalid_guild(ctx.lang)),
+        };
+
+        let mut config = state.guild_config(guild_id).await?;
+        config.entitlements = Entitlements::default();
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .description(ctx.lang.entitlements_revoked(guild_id.to_string()))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "show",
+    desc = "Show the premium entitlements of a server",
+    desc_localizations = "entitlements_show_description"
+)]
+pub struct EntitlementsShowCommand {
+    /// Id of the server.
+    guild: String,
+}
+
+desc_localizations!(entitlements_show_description);
+
+impl EntitlementsShowCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let guild_id = match find_guild(&self.guild, state).await? {
+            Some(guild_id) => guild_id,
+            None => return Ok(embed::entitlements::invalid_guild(ctx.lang)),
+        };
+
+        let entitlements = state.guild_config(guild_id).await?.entitlements;
+
+        let tier = match entitlements.tier {
+            PremiumTier::Free => ctx.lang.entitlements_tier_free(),
+            PremiumTier::Premium => ctx.lang.entitlements_tier_premium(),
+        };
+
+        let features = if entitlements.features.is_empty() {
+            ctx.lang.entitlements_none().to_owned()
+        } else {
+            entitlements
+                .features
+                .iter()
+                .map(|feature| feature_name(*feature, ctx.lang))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let expires = match entitlements.expires_at {
+            Some(expires_at) => Timestamp::new(
+                expires_at.unix_timestamp() as u64,
+                Some(TimestampStyle::RelativeTime),
+            )
+            .mention()
+            .to_string(),
+            None => ctx.lang.entitlements_never().to_owned(),
+        };
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_TRANSPARENT)
+            .title(ctx.lang.entitlements_show_title(guild_id.to_string()))
+            .field(EmbedFieldBuilder::new(ctx.lang.entitlements_tier(), tier).inline())
+            .field(EmbedFieldBuilder::new(ctx.lang.entitlements_features(), features).inline())
+            .field(EmbedFieldBuilder::new(ctx.lang.entitlements_expires(), expires).inline())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+/// Parse a guild id and ensure the bot is in the guild.
+async fn find_guild(
+    value: &str,
+    state: &ClusterState,
+) -> Result<Option<Id<GuildMarker>>, anyhow::Error> {
+    let guild_id = match parse_guild_id(value) {
+        Some(guild_id) => guild_id,
+        None => return Ok(None),
+    };
+
+    let guild = state.cache.get::<CachedGuild>(&guild_id).await?;
+
+    Ok(guild.map(|guild| guild.id))
+}
+
+/// Get the translated name of a premium feature.
+fn feature_name(feature: PremiumFeature, lang: Lang) -> &'static str {
+    match feature {
+        PremiumFeature::RegexFilter => lang.entitlements_feature_regex_filter(),
+    }
+}
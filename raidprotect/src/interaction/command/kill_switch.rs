@@ -0,0 +1,298 @@
+//! Kill switch command.
+//!
+//! This command allows the bot owners to manage the kill switches (see the
+//! [`kill_switch`] module). It is only registered in the administration guild.
+//!
+//! [`kill_switch`]: crate::feature::kill_switch
+
+use raidprotect_model::cache::model::kill_switch::KillSwitch;
+use time::OffsetDateTime;
+use twilight_interactions::command::{CommandModel, CommandOption, CreateCommand, CreateOption};
+use twilight_mention::{
+    timestamp::{Timestamp, TimestampStyle},
+    Mention,
+};
+use twilight_model::{application::command::CommandOptionChoice, guild::Permissions};
+use twilight_util::builder::embed::EmbedBuilder;
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations,
+    feature::kill_switch::{automation_target, command_target, Automation},
+    impl_command_handle,
+    interaction::{
+        autocomplete::{string_choices, AutocompleteContext},
+        embed::{self, COLOR_SUCCESS, COLOR_TRANSPARENT},
+        handle::command_names,
+        response::InteractionResponse,
+        util::InteractionContext,
+    },
+    util::TextProcessExt,
+};
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "killswitch",
+    desc = "Manage the RaidProtect kill switches",
+    desc_localizations = "kill_switch_description",
+    default_permissions = "kill_switch_permissions",
+    dm_permission = false
+)]
+pub enum KillSwitchCommand {
+    #[command(name = "enable")]
+    Enable(KillSwitchEnableCommand),
+    #[command(name = "disable")]
+    Disable(KillSwitchDisableCommand),
+    #[command(name = "list")]
+    List(KillSwitchListCommand),
+}
+
+impl_command_handle!(KillSwitchCommand);
+desc_localizations!(kill_switch_description);
+
+fn kill_switch_permissions() -> Permissions {
+    Permissions::ADMINISTRATOR
+}
+
+impl KillSwitchCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            KillSwitchCommand::Enable(command) => command.exec(ctx, state).await,
+            KillSwitchCommand::Disable(command) => command.exec(ctx, state).await,
+            KillSwitchCommand::List(command) => command.exec(ctx, state).await,
+        }
+    }
+
+    /// Suggest values for the `name` option.
+    ///
+    /// The `enable` subcommand suggests all the commands or automations, and
+    /// the `disable` subcommand only the ones with an enabled kill switch.
+    pub async fn autocomplete_name(
+        ctx: &AutocompleteContext,
+        state: &ClusterState,
+    ) -> Result<Vec<CommandOptionChoice>, anyhow::Error> {
+        let kind = match ctx.string_option("kind") {
+            Some("command") => KillSwitchKindOption::Command,
+            Some("automation") => KillSwitchKindOption::Automation,
+            _ => return Ok(Vec::new()),
+        };
+
+        let mut names = match ctx.subcommand.as_deref() {
+            Some("disable") => {
+                let prefix = kind.target_prefix();
+                state
+                    .cache
+                    .scan::<KillSwitch>(KillSwitch::KEY_PATTERN)
+                    .await?
+                    .into_iter()
+                    .filter_map(|kill_switch| {
+                        let name = kill_switch.target.strip_prefix(prefix)?;
+                        Some(name.to_owned())
+                    })
+                    .collect()
+            }
+            _ => match kind {
+                KillSwitchKindOption::Command => command_names(),
+                KillSwitchKindOption::Automation => Automation::ALL
+                    .iter()
+                    .map(|automation| automation.name().to_owned())
+                    .collect(),
+            },
+        };
+        names.sort();
+
+        Ok(string_choices(names.iter().map(|name| &**name), &ctx.value))
+    }
+}
+
+/// Kill switch kind command option.
+#[derive(Debug, Clone, Copy, CommandOption, CreateOption)]
+pub enum KillSwitchKindOption {
+    #[option(name = "Command", value = "command")]
+    Command,
+    #[option(name = "Automation", value = "automation")]
+    Automation,
+}
+
+impl KillSwitchKindOption {
+    /// Prefix of the kill switch targets of this kind.
+    fn target_prefix(self) -> &'static str {
+        match self {
+            KillSwitchKindOption::Command => "command:",
+            KillSwitchKindOption::Automation => "automation:",
+        }
+    }
+
+    /// Get the kill switch target from the name entered by the user.
+    ///
+    /// Returns [`None`] if the name is invalid.
+    fn target(self, name: &str) -> Option<String> {
+        let name = name.trim().to_lowercase();
+
+        match self {
+            KillSwitchKindOption::Command => {
+                let valid = !name.is_empty()
+                    && name.len() <= 32
+                    && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+
+                valid.then(|| command_target(&name))
+            }
+            KillSwitchKindOption::Automation => Automation::from_name(&name).map(automation_target),
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "enable",
+    desc = "Disable a command or an automation globally",
+    desc_localizations = "kill_switch_enable_description"
+)]
+pub struct KillSwitchEnableCommand {
+    /// Kind of feature to disable.
+    kind: KillSwitchKindOption,
+    /// Name of the command (or component) or of the automation.
+    #[command(autocomplete = true)]
+    name: String,
+    /// Reason shown to the users.
+    reason: Option<String>,
+}
+
+desc_localizations!(kill_switch_enable_description);
+
+impl KillSwitchEnableCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let target = match self.kind.target(&self.name) {
+            Some(target) => target,
+            None => return Ok(embed::kill_switch::invalid_target(ctx.lang)),
+        };
+
+        if state.cache.get::<KillSwitch>(&target).await?.is_some() {
+            return Ok(embed::kill_switch::already_enabled(ctx.lang));
+        }
+
+        let kill_switch = KillSwitch {
+            target,
+            reason: self.reason.map(|reason| reason.max_len(200)),
+            enabled_by: ctx.author.id,
+            enabled_at: OffsetDateTime::now_utc(),
+        };
+        state.cache.set(&kill_switch).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .description(
+                ctx.lang
+                    .kill_switch_enable_confirm_description(&kill_switch.target),
+            )
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "disable",
+    desc = "Enable again a disabled command or automation",
+    desc_localizations = "kill_switch_disable_description"
+)]
+pub struct KillSwitchDisableCommand {
+    /// Kind of feature to enable.
+    kind: KillSwitchKindOption,
+    /// Name of the command (or component) or of the automation.
+    #[command(autocomplete = true)]
+    name: String,
+}
+
+desc_localizations!(kill_switch_disable_description);
+
+impl KillSwitchDisableCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let target = match self.kind.target(&self.name) {
+            Some(target) => target,
+            None => return Ok(embed::kill_switch::invalid_target(ctx.lang)),
+        };
+
+        let kill_switch = match state.cache.get::<KillSwitch>(&target).await? {
+            Some(kill_switch) => kill_switch,
+            None => return Ok(embed::kill_switch::not_enabled(ctx.lang)),
+        };
+        state.cache.delete(&kill_switch).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .description(ctx.lang.kill_switch_disable_confirm_description(target))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "list",
+    desc = "List the enabled kill switches",
+    desc_localizations = "kill_switch_list_description"
+)]
+pub struct KillSwitchListCommand;
+
+desc_localizations!(kill_switch_list_description);
+
+impl KillSwitchListCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let mut kill_switches = state
+            .cache
+            .scan::<KillSwitch>(KillSwitch::KEY_PATTERN)
+            .await?;
+        kill_switches.sort_by(|a, b| a.target.cmp(&b.target));
+
+        let description = if kill_switches.is_empty() {
+            ctx.lang.kill_switch_list_empty().to_owned()
+        } else {
+            kill_switches
+                .iter()
+                .map(|kill_switch| {
+                    let enabled_at = Timestamp::new(
+                        kill_switch.enabled_at.unix_timestamp() as u64,
+                        Some(TimestampStyle::RelativeTime),
+                    );
+
+                    format!(
+                        "`{}` — {} {} {}",
+                        kill_switch.target,
+                        kill_switch.enabled_by.mention(),
+                        enabled_at.mention(),
+                        kill_switch.reason.as_deref().unwrap_or_default()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+                .max_len(4096)
+        };
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_TRANSPARENT)
+            .title(ctx.lang.kill_switch_list_title())
+            .description(description)
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
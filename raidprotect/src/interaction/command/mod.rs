@@ -3,6 +3,12 @@
 //! This module contains implementations of the bot slash commands.
 
 pub mod config;
+pub mod debug;
+pub mod entitlements;
 pub mod help;
+pub mod kill_switch;
 pub mod moderation;
+pub mod modlogs;
+pub mod privacy;
 pub mod profile;
+pub mod setup;
@@ -11,7 +11,6 @@
 use raidprotect_model::{cache::model::interaction::PendingSanction, database::model::ModlogType};
 use twilight_interactions::command::{CommandModel, CreateCommand, ResolvedUser};
 use twilight_model::{
-    application::component::{text_input::TextInputStyle, ActionRow, Component, TextInput},
     guild::Permissions,
     id::{marker::InteractionMarker, Id},
     user::User,
@@ -21,9 +20,11 @@ use crate::{
     cluster::ClusterState,
     desc_localizations, impl_guild_command_handle,
     interaction::{
+        component::{SanctionForm, SanctionFormOptions, SanctionModalId},
         embed,
+        modal::ModalForm,
         response::InteractionResponse,
-        util::{CustomId, GuildInteractionContext},
+        util::{ComponentId, GuildInteractionContext},
     },
     translations::Lang,
     util::TextProcessExt,
@@ -69,7 +70,7 @@ impl KickCommand {
 
         // Fetch the author and the bot permissions.
         let permissions = state.cache.permissions(ctx.guild_id).await?;
-        let author_permissions = permissions.member(ctx.author.id, &member.roles).await?;
+        let author_permissions = permissions.member(ctx.author.id, &ctx.member.roles).await?;
         let member_permissions = permissions.member(user.id, &member.roles).await?;
         let bot_permissions = permissions.current_member().await?;
 
@@ -84,20 +85,17 @@ impl KickCommand {
 
         // Check if the role hierarchy allow the author and the bot to perform
         // the kick.
-        let member_highest_role = member_permissions.highest_role();
-
-        if member_highest_role >= author_permissions.highest_role() {
+        if !author_permissions.can_target(&member_permissions) {
             return Ok(embed::kick::user_hierarchy(ctx.lang));
         }
 
-        if member_highest_role >= bot_permissions.highest_role() {
+        if !bot_permissions.can_target(&member_permissions) {
             return Ok(embed::kick::bot_hierarchy(ctx.lang));
         }
 
         // Send reason modal.
         let enforce_reason = state
-            .database
-            .get_guild_or_create(ctx.guild_id)
+            .guild_config(ctx.guild_id)
             .await?
             .moderation
             .enforce_reason;
@@ -123,35 +121,13 @@ impl KickCommand {
         lang: Lang,
     ) -> Result<InteractionResponse, anyhow::Error> {
         let username = user.name.max_len(15);
-        let components = vec![
-            Component::ActionRow(ActionRow {
-                components: vec![Component::TextInput(TextInput {
-                    custom_id: "reason".to_owned(),
-                    label: lang.modal_kick_reason_label().to_owned(),
-                    max_length: Some(100),
-                    min_length: None,
-                    placeholder: Some(lang.modal_reason_placeholder().to_owned()),
-                    required: Some(enforce_reason),
-                    style: TextInputStyle::Short,
-                    value: None,
-                })],
-            }),
-            Component::ActionRow(ActionRow {
-                components: vec![Component::TextInput(TextInput {
-                    custom_id: "notes".to_owned(),
-                    label: lang.modal_notes_label().to_owned(),
-                    max_length: Some(1000),
-                    min_length: None,
-                    placeholder: Some(lang.modal_notes_placeholder().to_owned()),
-                    required: Some(false),
-                    style: TextInputStyle::Paragraph,
-                    value: None,
-                })],
-            }),
-        ];
+        let options = SanctionFormOptions {
+            kind: ModlogType::Kick,
+            enforce_reason,
+        };
 
         // Add pending component in Redis
-        let custom_id = CustomId::new("sanction", interaction_id.to_string());
+        let custom_id = SanctionModalId { interaction_id }.encode()?;
         let pending = PendingSanction {
             interaction_id,
             kind: ModlogType::Kick,
@@ -160,10 +136,11 @@ impl KickCommand {
 
         state.cache.set(&pending).await?;
 
-        Ok(InteractionResponse::Modal {
-            custom_id: custom_id.to_string(),
-            title: lang.modal_kick_title(username),
-            components,
-        })
+        Ok(SanctionForm::modal(
+            custom_id,
+            lang.modal_kick_title(username),
+            &options,
+            lang,
+        ))
     }
 }
@@ -0,0 +1,381 @@
+//! Moderation logs command.
+//!
+//! The `/modlogs export` command sends all the moderation logs of the server
+//! as JSON or CSV files, including the archived ones if requested. The
+//! moderation logs are read in a background task and sent as follow-up
+//! messages, split in several files for large servers.
+
+use std::time::Duration;
+
+use anyhow::bail;
+use futures_util::TryStreamExt;
+use raidprotect_model::database::model::Modlog;
+use tracing::error;
+use twilight_http::client::InteractionClient;
+use twilight_interactions::command::{CommandModel, CommandOption, CreateCommand, CreateOption};
+use twilight_model::{
+    channel::message::MessageFlags,
+    guild::Permissions,
+    http::attachment::Attachment,
+    id::{marker::GuildMarker, Id},
+};
+use twilight_util::builder::embed::EmbedBuilder;
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations, impl_guild_command_handle,
+    interaction::{
+        embed::{COLOR_RED, COLOR_SUCCESS},
+        response::{InteractionResponder, InteractionResponse},
+        util::GuildInteractionContext,
+    },
+    translations::Lang,
+};
+
+/// Maximum size of an exported file.
+///
+/// Discord limits the size of the attachments sent by bots to 8 MiB.
+const MAX_FILE_SIZE: usize = 7 * 1024 * 1024;
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "modlogs",
+    desc = "Manage the moderation logs of the server",
+    desc_localizations = "modlogs_description",
+    default_permissions = "modlogs_permissions",
+    dm_permission = false
+)]
+pub enum ModlogsCommand {
+    #[command(name = "export")]
+    Export(ModlogsExportCommand),
+}
+
+impl_guild_command_handle!(ModlogsCommand);
+desc_localizations!(modlogs_description);
+
+fn modlogs_permissions() -> Permissions {
+    Permissions::ADMINISTRATOR
+}
+
+impl ModlogsCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            Self::Export(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+/// Export format command option.
+#[derive(Debug, Clone, Copy, CommandOption, CreateOption)]
+pub enum ExportFormat {
+    #[option(name = "JSON", value = "json")]
+    Json,
+    #[option(name = "CSV", value = "csv")]
+    Csv,
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "export",
+    desc = "Export the moderation logs of the server",
+    desc_localizations = "modlogs_export_description"
+)]
+pub struct ModlogsExportCommand {
+    /// Format of the exported files.
+    format: ExportFormat,
+    /// Include the archived moderation logs.
+    archived: Option<bool>,
+}
+
+desc_localizations!(modlogs_export_description);
+
+impl ModlogsExportCommand {
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let responder = InteractionResponder::from_interaction(&ctx.interaction);
+
+        tokio::spawn(export(
+            responder,
+            ctx.guild_id,
+            self.format,
+            self.archived.unwrap_or(false),
+            ctx.lang,
+            state.clone(),
+        ));
+
+        Ok(InteractionResponse::EphemeralDeferredMessage)
+    }
+}
+
+/// Export the moderation logs and update the deferred response with the
+/// result.
+async fn export(
+    responder: InteractionResponder,
+    guild_id: Id<GuildMarker>,
+    format: ExportFormat,
+    archived: bool,
+    lang: Lang,
+    state: ClusterState,
+) {
+    let client = state.http.interaction(responder.application_id);
+    let mut writer = ExportWriter::new(guild_id, format);
+
+    let embed = match run_export(&mut writer, &client, &responder, archived, &state).await {
+        Ok(()) if writer.count == 0 => EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .description(lang.modlogs_export_empty())
+            .build(),
+        Ok(()) => EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .description(lang.modlogs_exported(writer.count, writer.files))
+            .build(),
+        Err(error) => {
+            error!(error = ?error, guild = ?guild_id, "failed to export moderation logs");
+
+            EmbedBuilder::new()
+                .color(COLOR_RED)
+                .description(lang.modlogs_export_failed())
+                .build()
+        }
+    };
+
+    let result = match client
+        .update_response(&responder.token)
+        .embeds(Some(&[embed]))
+    {
+        Ok(request) => request
+            .exec()
+            .await
+            .map(|_| ())
+            .map_err(anyhow::Error::from),
+        Err(error) => Err(error.into()),
+    };
+
+    if let Err(error) = result {
+        error!(error = ?error, "failed to update moderation logs export response");
+    }
+}
+
+async fn run_export(
+    writer: &mut ExportWriter,
+    client: &InteractionClient<'_>,
+    responder: &InteractionResponder,
+    archived: bool,
+    state: &ClusterState,
+) -> Result<(), anyhow::Error> {
+    wait_deferred_response(client, responder).await?;
+
+    // Archived moderation logs are older, so they are exported first.
+    let collections: &[bool] = match archived {
+        true => &[true, false],
+        false => &[false],
+    };
+
+    for archived in collections {
+        let mut modlogs = state
+            .store
+            .find_guild_modlogs(writer.guild_id, *archived)
+            .await?;
+
+        while let Some(modlog) = modlogs.try_next().await? {
+            if let Some(file) = writer.push(&modlog)? {
+                send_file(file, client, responder).await?;
+            }
+        }
+    }
+
+    if let Some(file) = writer.finish() {
+        send_file(file, client, responder).await?;
+    }
+
+    Ok(())
+}
+
+/// Wait until the deferred response has been sent.
+///
+/// The export task is started before the response is sent, and follow-up
+/// messages cannot be sent before it.
+async fn wait_deferred_response(
+    client: &InteractionClient<'_>,
+    responder: &InteractionResponder,
+) -> Result<(), anyhow::Error> {
+    for _ in 0..5 {
+        if client.response(&responder.token).exec().await.is_ok() {
+            return Ok(());
+        }
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+
+    bail!("deferred response not sent")
+}
+
+/// Send an exported file as an ephemeral follow-up message.
+async fn send_file(
+    file: Attachment,
+    client: &InteractionClient<'_>,
+    responder: &InteractionResponder,
+) -> Result<(), anyhow::Error> {
+    client
+        .create_followup(&responder.token)
+        .attachments(&[file])?
+        .flags(MessageFlags::EPHEMERAL)
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+/// Writer splitting the exported moderation logs in files.
+///
+/// JSON files contain an array of moderation logs, and CSV files start with a
+/// header, so that each file can be read independently.
+struct ExportWriter {
+    guild_id: Id<GuildMarker>,
+    format: ExportFormat,
+    buffer: Vec<u8>,
+    /// Number of exported moderation logs.
+    count: u64,
+    /// Number of completed files.
+    files: u64,
+}
+
+impl ExportWriter {
+    fn new(guild_id: Id<GuildMarker>, format: ExportFormat) -> Self {
+        Self {
+            guild_id,
+            format,
+            buffer: Vec::new(),
+            count: 0,
+            files: 0,
+        }
+    }
+
+    /// Add a moderation log to the current file.
+    ///
+    /// Returns the completed file if the moderation log does not fit in it.
+    fn push(&mut self, modlog: &Modlog) -> Result<Option<Attachment>, anyhow::Error> {
+        let record = match self.format {
+            ExportFormat::Json => modlog.to_json()?,
+            ExportFormat::Csv => modlog.to_csv_record(),
+        };
+
+        let completed = match self.buffer.len() + record.len() > MAX_FILE_SIZE {
+            true => self.finish(),
+            false => None,
+        };
+
+        match (self.format, self.buffer.is_empty()) {
+            (ExportFormat::Json, true) => self.buffer.extend_from_slice(b"[\n"),
+            (ExportFormat::Json, false) => self.buffer.extend_from_slice(b",\n"),
+            (ExportFormat::Csv, true) => {
+                self.buffer.extend_from_slice(Modlog::CSV_HEADER.as_bytes());
+                self.buffer.push(b'\n');
+            }
+            (ExportFormat::Csv, false) => {}
+        }
+
+        self.buffer.extend_from_slice(record.as_bytes());
+        if let ExportFormat::Csv = self.format {
+            self.buffer.push(b'\n');
+        }
+
+        self.count += 1;
+
+        Ok(completed)
+    }
+
+    /// Complete the current file.
+    ///
+    /// Returns [`None`] if the file is empty.
+    fn finish(&mut self) -> Option<Attachment> {
+        if self.buffer.is_empty() {
+            return None;
+        }
+
+        let mut file = std::mem::take(&mut self.buffer);
+        let extension = match self.format {
+            ExportFormat::Json => {
+                file.extend_from_slice(b"\n]\n");
+                "json"
+            }
+            ExportFormat::Csv => "csv",
+        };
+
+        self.files += 1;
+
+        Some(Attachment {
+            file,
+            filename: format!("modlogs-{}-{}.{extension}", self.guild_id, self.files),
+            id: 0,
+            description: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use raidprotect_model::database::model::{ModlogType, ModlogUser};
+    use time::OffsetDateTime;
+
+    use super::*;
+
+    fn modlog() -> Modlog {
+        let user = ModlogUser {
+            id: Id::new(2),
+            name: "user".to_owned(),
+            discriminator: 1,
+            avatar: None,
+        };
+
+        Modlog {
+            id: None,
+            kind: ModlogType::Ban,
+            guild_id: Id::new(1),
+            user: user.clone(),
+            moderator: user,
+            date: OffsetDateTime::from_unix_timestamp(1_628_594_197).unwrap(),
+            reason: None,
+            notes: None,
+            automation: None,
+        }
+    }
+
+    #[test]
+    fn test_export_writer_json() {
+        let mut writer = ExportWriter::new(Id::new(1), ExportFormat::Json);
+
+        assert!(writer.push(&modlog()).unwrap().is_none());
+        assert!(writer.push(&modlog()).unwrap().is_none());
+
+        let file = writer.finish().unwrap();
+        let json = String::from_utf8(file.file).unwrap();
+
+        assert_eq!(file.filename, "modlogs-1-1.json");
+        assert!(json.starts_with("[\n{") && json.ends_with("}\n]\n"));
+        assert_eq!(json.matches("},\n{").count(), 1);
+        assert_eq!(writer.count, 2);
+        assert!(writer.finish().is_none());
+    }
+
+    #[test]
+    fn test_export_writer_csv() {
+        let mut writer = ExportWriter::new(Id::new(1), ExportFormat::Csv);
+
+        writer.push(&modlog()).unwrap();
+
+        let file = writer.finish().unwrap();
+        let csv = String::from_utf8(file.file).unwrap();
+
+        assert_eq!(file.filename, "modlogs-1-1.csv");
+        assert_eq!(csv.lines().count(), 2);
+        assert!(csv.starts_with(Modlog::CSV_HEADER));
+    }
+}
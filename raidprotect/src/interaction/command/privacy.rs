@@ -0,0 +1,163 @@
+//! Privacy command.
+//!
+//! This command allows the bot owners to process data-protection requests by
+//! exporting or erasing the data stored about a user (see the
+//! [`privacy`](raidprotect_model::privacy) module). It is only registered in
+//! the administration guild.
+
+use raidprotect_model::privacy::{erase_user_data, export_user_data};
+use tracing::info;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::{
+    channel::message::MessageFlags,
+    guild::Permissions,
+    http::{attachment::Attachment, interaction::InteractionResponseType},
+    id::{marker::UserMarker, Id},
+};
+use twilight_util::builder::{embed::EmbedBuilder, InteractionResponseDataBuilder};
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations, impl_command_handle,
+    interaction::{
+        embed::{self, COLOR_SUCCESS},
+        response::InteractionResponse,
+        util::InteractionContext,
+    },
+};
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "privacy",
+    desc = "Process data-protection requests",
+    desc_localizations = "privacy_description",
+    default_permissions = "privacy_permissions",
+    dm_permission = false
+)]
+pub enum PrivacyCommand {
+    #[command(name = "export")]
+    Export(PrivacyExportCommand),
+    #[command(name = "erase")]
+    Erase(PrivacyEraseCommand),
+}
+
+impl_command_handle!(PrivacyCommand);
+desc_localizations!(privacy_description);
+
+fn privacy_permissions() -> Permissions {
+    Permissions::ADMINISTRATOR
+}
+
+impl PrivacyCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        match self {
+            PrivacyCommand::Export(command) => command.exec(ctx, state).await,
+            PrivacyCommand::Erase(command) => command.exec(ctx, state).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "export",
+    desc = "Export the data stored about a user",
+    desc_localizations = "privacy_export_description"
+)]
+pub struct PrivacyExportCommand {
+    /// Id of the user.
+    user: String,
+}
+
+desc_localizations!(privacy_export_description);
+
+impl PrivacyExportCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let user_id = match parse_user_id(&self.user) {
+            Some(user_id) => user_id,
+            None => return Ok(embed::privacy::invalid_user(ctx.lang)),
+        };
+
+        let data = export_user_data(user_id, &*state.store, &state.cache).await?;
+        let attachment = Attachment {
+            file: data.to_json()?.into_bytes(),
+            filename: format!("user-data-{user_id}.json"),
+            id: 0,
+            description: None,
+        };
+
+        info!(user = ?user_id, owner = ?ctx.author.id, "exported user data");
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .description(ctx.lang.privacy_exported(user_id.to_string()))
+            .build();
+
+        let response = InteractionResponseDataBuilder::new()
+            .embeds([embed])
+            .attachments([attachment])
+            .flags(MessageFlags::EPHEMERAL)
+            .build();
+
+        Ok(InteractionResponse::Raw {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: Some(response),
+        })
+    }
+}
+
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "erase",
+    desc = "Irreversibly erase the data stored about a user",
+    desc_localizations = "privacy_erase_description"
+)]
+pub struct PrivacyEraseCommand {
+    /// Id of the user.
+    user: String,
+}
+
+desc_localizations!(privacy_erase_description);
+
+impl PrivacyEraseCommand {
+    async fn exec(
+        self,
+        ctx: InteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let user_id = match parse_user_id(&self.user) {
+            Some(user_id) => user_id,
+            None => return Ok(embed::privacy::invalid_user(ctx.lang)),
+        };
+
+        let summary = erase_user_data(user_id, &*state.store, &state.cache).await?;
+
+        info!(user = ?user_id, owner = ?ctx.author.id, summary = ?summary, "erased user data");
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .description(ctx.lang.privacy_erased(
+                summary.api_keys,
+                summary.cache,
+                summary.antispam_feedback,
+                summary.guilds,
+                summary.modlogs,
+                user_id.to_string(),
+            ))
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+/// Parse a user id entered by the user.
+fn parse_user_id(value: &str) -> Option<Id<UserMarker>> {
+    value.trim().parse().ok()
+}
@@ -0,0 +1,57 @@
+//! Setup command.
+//!
+//! This command starts an interactive wizard for the first-time configuration
+//! of the bot (see the [`setup`](crate::interaction::component::setup) module).
+
+use raidprotect_model::cache::model::interaction::PendingSetup;
+use twilight_interactions::command::{CommandModel, CreateCommand};
+use twilight_model::guild::Permissions;
+
+use crate::{
+    cluster::ClusterState,
+    desc_localizations, impl_guild_command_handle,
+    interaction::{
+        component::setup::setup_message, response::InteractionResponse,
+        util::GuildInteractionContext,
+    },
+};
+
+/// Setup command model.
+///
+/// See the [`module`][self] documentation for more information.
+#[derive(Debug, Clone, CommandModel, CreateCommand)]
+#[command(
+    name = "setup",
+    desc = "Configure RaidProtect step by step",
+    desc_localizations = "setup_description",
+    default_permissions = "SetupCommand::default_permissions",
+    dm_permission = false
+)]
+pub struct SetupCommand;
+
+impl_guild_command_handle!(SetupCommand);
+desc_localizations!(setup_description);
+
+impl SetupCommand {
+    fn default_permissions() -> Permissions {
+        Permissions::ADMINISTRATOR
+    }
+
+    async fn exec(
+        self,
+        ctx: GuildInteractionContext,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let pending = PendingSetup {
+            guild_id: ctx.guild_id,
+            author_id: ctx.author.id,
+            logs_chan: None,
+            captcha: false,
+            antispam: None,
+        };
+
+        state.cache.set(&pending).await?;
+
+        setup_message(ctx.guild_id, state, ctx.lang).await
+    }
+}
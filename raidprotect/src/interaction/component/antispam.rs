@@ -0,0 +1,131 @@
+//! Anti-spam false positive button.
+//!
+//! Anti-spam decisions are explained in the logs channel with a button to
+//! report them as false positives. The punishment is reverted when possible
+//! (timeouts and bans), and the report is stored in the database to tune the
+//! detector thresholds.
+
+use anyhow::Context;
+use raidprotect_model::{
+    cache::model::interaction::PendingSpamDecision,
+    database::model::{AntiSpamFeedback, AntiSpamPunishment},
+};
+use time::OffsetDateTime;
+use tracing::instrument;
+use twilight_http::request::AuditLogReason;
+use twilight_mention::Mention;
+use twilight_model::{
+    application::interaction::Interaction, guild::Permissions,
+    http::interaction::InteractionResponseType,
+};
+use twilight_util::builder::{embed::EmbedFieldBuilder, InteractionResponseDataBuilder};
+
+use crate::{
+    cluster::ClusterState,
+    interaction::{
+        embed::{self, COLOR_SUCCESS},
+        response::InteractionResponse,
+        util::{CustomId, GuildConfigExt, GuildInteractionContext},
+    },
+};
+
+/// "False positive" button of anti-spam decision logs.
+///
+/// The id of the message detected as spam is stored in the button custom id.
+pub struct FalsePositiveButton;
+
+impl FalsePositiveButton {
+    #[instrument(skip(state))]
+    pub async fn handle(
+        interaction: Interaction,
+        custom_id: CustomId,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let ctx = GuildInteractionContext::new(interaction)?;
+
+        let decision_id = custom_id.id.context("missing spam decision id")?;
+        let decision = match state.cache.get::<PendingSpamDecision>(&decision_id).await? {
+            Some(decision) if decision.guild_id == ctx.guild_id => decision,
+            _ => return Ok(embed::error::expired_interaction(ctx.lang)),
+        };
+
+        // Ensure the user is allowed to revert the punishment.
+        let required = match decision.punishment {
+            AntiSpamPunishment::Ban => Permissions::BAN_MEMBERS,
+            _ => Permissions::MODERATE_MEMBERS,
+        };
+        let permissions = ctx.member.permissions.unwrap_or_else(Permissions::empty);
+        if !permissions.contains(required) {
+            return Ok(embed::antispam::false_positive_missing_permission(ctx.lang));
+        }
+
+        let config = ctx.config(state).await?;
+        let guild_lang = config.lang();
+        let reason = guild_lang.antispam_false_positive_reason();
+        let http = state.cache_http(ctx.guild_id);
+
+        let reverted = match decision.punishment {
+            AntiSpamPunishment::Timeout => {
+                http.update_guild_member(decision.user_id)
+                    .await?
+                    .communication_disabled_until(None)?
+                    .reason(reason)?
+                    .exec()
+                    .await?;
+
+                true
+            }
+            AntiSpamPunishment::Ban => {
+                http.delete_ban(decision.user_id)
+                    .await?
+                    .reason(reason)?
+                    .exec()
+                    .await?;
+
+                true
+            }
+            AntiSpamPunishment::Delete | AntiSpamPunishment::Kick => false,
+        };
+
+        let feedback = AntiSpamFeedback {
+            id: None,
+            guild_id: ctx.guild_id,
+            user_id: decision.user_id,
+            rules: decision.rules.clone(),
+            punishment: decision.punishment,
+            reported_by: ctx.author.id,
+            date: OffsetDateTime::now_utc(),
+        };
+        state.store.create_antispam_feedback(&feedback).await?;
+        state.cache.delete(&decision).await?;
+
+        // Update the decision log with the report.
+        let mut embed = ctx
+            .interaction
+            .message
+            .as_ref()
+            .and_then(|message| message.embeds.first().cloned())
+            .context("missing spam decision embed")?;
+
+        let description = if reverted {
+            guild_lang.antispam_false_positive_reverted(ctx.author.id.mention())
+        } else {
+            guild_lang.antispam_false_positive_recorded(ctx.author.id.mention())
+        };
+
+        embed.color = Some(COLOR_SUCCESS);
+        embed.fields.push(
+            EmbedFieldBuilder::new(guild_lang.antispam_false_positive_title(), description).build(),
+        );
+
+        let response = InteractionResponseDataBuilder::new()
+            .embeds([embed])
+            .components(Vec::new())
+            .build();
+
+        Ok(InteractionResponse::Raw {
+            kind: InteractionResponseType::UpdateMessage,
+            data: Some(response),
+        })
+    }
+}
@@ -1,14 +1,12 @@
 //! Captcha disable button.
 
+use raidprotect_model::database::model::{GuildConfig, LogCategory};
 use tracing::{error, warn};
 use twilight_http::request::AuditLogReason;
 use twilight_mention::Mention;
 use twilight_model::{
     application::interaction::Interaction,
-    id::{
-        marker::{ChannelMarker, GuildMarker, UserMarker},
-        Id,
-    },
+    id::{marker::UserMarker, Id},
 };
 use twilight_util::builder::embed::EmbedBuilder;
 
@@ -32,6 +30,8 @@ use crate::{
 /// the verification channel and the unverified role.
 pub struct CaptchaDisable;
 
+impl_component_id!(CaptchaDisable, "captcha-disable");
+
 impl CaptchaDisable {
     pub async fn handle(
         interaction: Interaction,
@@ -73,19 +73,12 @@ impl CaptchaDisable {
 
         // Update the configuration.
         config.captcha = Default::default();
-        state.database.update_guild(&config).await?;
+        state.update_guild_config(&config).await?;
 
         // Send message in logs channel.
         let state_clone = state.clone();
         tokio::spawn(async move {
-            if let Err(error) = logs_message(
-                &state_clone,
-                ctx.guild_id,
-                config.logs_chan,
-                ctx.author.id,
-                guild_lang,
-            )
-            .await
+            if let Err(error) = logs_message(&state_clone, &config, ctx.author.id, guild_lang).await
             {
                 error!(error = ?error, guild = ?ctx.guild_id, "failed to send captcha disable logs message");
             }
@@ -104,12 +97,11 @@ impl CaptchaDisable {
 
 async fn logs_message(
     state: &ClusterState,
-    guild: Id<GuildMarker>,
-    logs_channel: Option<Id<ChannelMarker>>,
+    config: &GuildConfig,
     user: Id<UserMarker>,
     lang: Lang,
 ) -> Result<(), anyhow::Error> {
-    let channel = guild_logs_channel(state, guild, logs_channel, lang).await?;
+    let channel = guild_logs_channel(state, config, LogCategory::Captcha).await?;
 
     let embed = EmbedBuilder::new()
         .color(COLOR_RED)
@@ -3,7 +3,10 @@
 use std::time::Duration;
 
 use anyhow::Context;
-use raidprotect_model::cache::discord::{CachedChannel, CachedGuild};
+use raidprotect_model::{
+    cache::discord::{CachedChannel, CachedGuild},
+    database::model::{GuildConfig, LogCategory},
+};
 use tracing::{debug, error, trace};
 use twilight_http::request::AuditLogReason;
 use twilight_mention::Mention;
@@ -28,15 +31,17 @@ use twilight_model::{
 };
 use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
 
+use super::verify::CaptchaVerifyButton;
 use crate::{
     cluster::ClusterState,
+    feature::captcha,
     interaction::{
         embed::{self, COLOR_RED, COLOR_SUCCESS},
         response::InteractionResponse,
-        util::{CustomId, GuildConfigExt, GuildInteractionContext},
+        util::{ComponentId, GuildConfigExt, GuildInteractionContext},
     },
     translations::Lang,
-    util::{guild_logs_channel, TextProcessExt},
+    util::{format_duration, guild_logs_channel, TextProcessExt},
 };
 
 /// Captcha enabling button.
@@ -52,6 +57,8 @@ use crate::{
 ///   role.
 pub struct CaptchaEnable;
 
+impl_component_id!(CaptchaEnable, "captcha-enable");
+
 impl CaptchaEnable {
     pub async fn handle(
         interaction: Interaction,
@@ -169,7 +176,7 @@ impl CaptchaEnable {
             Err(err) => {
                 error!(error = ?err, "failed to send the verification message");
 
-                return Ok(embed::error::internal_error(ctx.lang));
+                return Ok(embed::error::internal_error(ctx.lang, None));
             }
         };
 
@@ -179,7 +186,7 @@ impl CaptchaEnable {
         config.captcha.message = Some(message.id);
         config.captcha.role = Some(unverified_role.id);
 
-        state.database.update_guild(&config).await?;
+        state.update_guild_config(&config).await?;
 
         // Start the configuration of channels permissions.
         let state_clone = state.clone();
@@ -199,14 +206,7 @@ impl CaptchaEnable {
         // Send message in logs channel.
         let state_clone = state.clone();
         tokio::spawn(async move {
-            if let Err(error) = logs_message(
-                &state_clone,
-                ctx.guild_id,
-                config.logs_chan,
-                ctx.author.id,
-                guild_lang,
-            )
-            .await
+            if let Err(error) = logs_message(&state_clone, &config, ctx.author.id, guild_lang).await
             {
                 error!(error = ?error, guild = ?ctx.guild_id, "failed to send captcha enable logs message");
             }
@@ -224,6 +224,10 @@ impl CaptchaEnable {
                 ctx.lang.captcha_enabled_roles_title(),
                 ctx.lang.captcha_enabled_roles_description(),
             ))
+            .field(EmbedFieldBuilder::new(
+                ctx.lang.captcha_enabled_members_title(),
+                ctx.lang.captcha_enabled_members_description(),
+            ))
             .field(EmbedFieldBuilder::new(
                 ctx.lang.captcha_enabled_rename_title(),
                 ctx.lang.captcha_enabled_rename_description(
@@ -247,14 +251,17 @@ pub async fn verification_message(
 ) -> Result<Message, anyhow::Error> {
     let embed = EmbedBuilder::new()
         .title(guild_lang.captcha_verification_title(guild_name.max_len(30)))
-        .description(guild_lang.captcha_verification_description())
+        .description(guild_lang.captcha_verification_description(format_duration(
+            captcha::DEFAULT_DURATION,
+            guild_lang,
+        )))
         .color(COLOR_RED)
         .build();
 
-    let custom_id = CustomId::name("captcha-verify");
+    let custom_id = CaptchaVerifyButton.encode()?;
     let components = Component::ActionRow(ActionRow {
         components: vec![Component::Button(Button {
-            custom_id: Some(custom_id.to_string()),
+            custom_id: Some(custom_id),
             disabled: false,
             emoji: None,
             label: Some(guild_lang.captcha_verification_button().to_owned()),
@@ -281,12 +288,11 @@ pub async fn verification_message(
 /// enabled.
 async fn logs_message(
     state: &ClusterState,
-    guild: Id<GuildMarker>,
-    logs_channel: Option<Id<ChannelMarker>>,
+    config: &GuildConfig,
     user: Id<UserMarker>,
     lang: Lang,
 ) -> Result<(), anyhow::Error> {
-    let channel = guild_logs_channel(state, guild, logs_channel, lang).await?;
+    let channel = guild_logs_channel(state, config, LogCategory::Captcha).await?;
 
     let embed = EmbedBuilder::new()
         .color(COLOR_RED)
@@ -0,0 +1,88 @@
+//! Captcha migration button.
+
+use raidprotect_model::cache::model::interaction::PendingCaptchaMigration;
+use twilight_model::{application::interaction::Interaction, guild::Permissions};
+use twilight_util::builder::embed::EmbedBuilder;
+
+use crate::{
+    cluster::ClusterState,
+    feature::captcha,
+    interaction::{
+        embed::{self, COLOR_SUCCESS},
+        response::InteractionResponse,
+        util::GuildInteractionContext,
+    },
+};
+
+/// Captcha migration button.
+///
+/// This type handle the button used to start the captcha migration (sent by
+/// the `/config captcha migrate` command).
+///
+/// The migration gives the verified roles to all the current members of the
+/// guild, so they do not have to complete the verification. It is run in the
+/// background and its progress is displayed in the logs channel.
+pub struct CaptchaMigrate;
+
+impl_component_id!(CaptchaMigrate, "captcha-migrate");
+
+impl CaptchaMigrate {
+    pub async fn handle(
+        interaction: Interaction,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let ctx = GuildInteractionContext::new(interaction)?;
+        let config = ctx.config(state).await?;
+
+        // The configuration could have changed since the command was sent.
+        if !config.captcha.enabled {
+            return Ok(embed::captcha::not_enabled(ctx.lang));
+        }
+
+        if config.captcha.verified_roles.is_empty() {
+            return Ok(embed::captcha::migrate_no_roles(ctx.lang));
+        }
+
+        // Ensure the bot has the required permissions.
+        let permissions = state
+            .cache
+            .permissions(ctx.guild_id)
+            .await?
+            .current_member()
+            .await?;
+
+        if !permissions.guild().contains(Permissions::MANAGE_ROLES) {
+            return Ok(embed::captcha::missing_role_permission(ctx.lang));
+        }
+
+        // Ensure the migration is not already running.
+        if state
+            .cache
+            .get::<PendingCaptchaMigration>(&ctx.guild_id)
+            .await?
+            .is_some()
+        {
+            return Ok(embed::captcha::migrate_running(ctx.lang));
+        }
+
+        let pending = PendingCaptchaMigration {
+            guild_id: ctx.guild_id,
+        };
+        state.cache.set(&pending).await?;
+
+        // Start the migration.
+        tokio::spawn(captcha::migrate_members(
+            config,
+            ctx.author.id,
+            state.clone(),
+        ));
+
+        let embed = EmbedBuilder::new()
+            .title(ctx.lang.captcha_migrate_started_title())
+            .color(COLOR_SUCCESS)
+            .description(ctx.lang.captcha_migrate_started_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
@@ -2,12 +2,39 @@
 //!
 //! This module handle the various interaction components used by the captcha.
 
+/// Implement [`ComponentId`] for a component without payload.
+///
+/// [`ComponentId`]: crate::interaction::util::ComponentId
+macro_rules! impl_component_id {
+    ($component:ty, $name:literal) => {
+        impl $crate::interaction::util::ComponentId for $component {
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn from_fields(
+                _name: &str,
+                _fields: &mut $crate::interaction::util::CustomIdFields<'_>,
+            ) -> Result<Self, anyhow::Error> {
+                Ok(Self)
+            }
+        }
+    };
+}
+
 mod disable;
 mod enable;
+mod migrate;
 mod modal;
+mod questionnaire;
 mod verify;
 
 pub use disable::CaptchaDisable;
 pub use enable::{verification_message, CaptchaEnable};
+pub use migrate::CaptchaMigrate;
 pub use modal::CaptchaModal;
+pub use questionnaire::{
+    CaptchaQuestionnaireButton, CaptchaQuestionnaireConfigId, CaptchaQuestionnaireConfigModal,
+    CaptchaQuestionnaireModal, CaptchaReviewButton,
+};
 pub use verify::{CaptchaValidateButton, CaptchaVerifyButton};
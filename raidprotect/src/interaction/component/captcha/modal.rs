@@ -2,35 +2,26 @@
 
 use std::time::Duration;
 
-use anyhow::{bail, Context};
-use raidprotect_model::{
-    cache::discord::{
-        permission::{CachePermissions, RoleOrdering},
-        CachedRole,
-    },
-    database::model::GuildConfig,
-};
-use tracing::{error, info, instrument};
-use twilight_model::{
-    application::interaction::Interaction,
-    guild::Permissions,
-    id::{
-        marker::{RoleMarker, UserMarker},
-        Id,
-    },
-};
+use raidprotect_captcha::code::validate_code;
+use raidprotect_model::cache::model::live::{CaptchaOutcome, LiveEvent};
+use tracing::{error, instrument};
+use twilight_model::application::interaction::Interaction;
 use twilight_util::builder::embed::EmbedBuilder;
 
-use super::verify::{get_captcha, kick_after};
+use super::{
+    questionnaire::questionnaire_pending,
+    verify::{get_captcha, schedule_kick},
+};
 use crate::{
     cluster::ClusterState,
+    feature::{captcha::update_roles, live},
     interaction::{
         embed,
+        modal::{ModalField, ModalFieldError, ModalForm, ModalValues},
         response::InteractionResponse,
-        util::{
-            parse_modal_data, parse_modal_field_required, GuildConfigExt, GuildInteractionContext,
-        },
+        util::{parse_modal_data, GuildInteractionContext},
     },
+    translations::Lang,
 };
 
 /// Captcha verification modal.
@@ -38,6 +29,37 @@ use crate::{
 /// This modal is used to ask the user to solve the captcha.
 pub struct CaptchaModal;
 
+impl_component_id!(CaptchaModal, "captcha-modal");
+
+/// Form of the captcha verification modal.
+///
+/// The options of the form are the length of the captcha code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptchaForm {
+    /// Code entered by the user.
+    pub code: String,
+}
+
+impl ModalForm for CaptchaForm {
+    type Options = usize;
+
+    fn fields(code_length: &usize, lang: Lang) -> Vec<ModalField> {
+        vec![
+            ModalField::short("captcha-input", lang.captcha_input_label())
+                .required(true)
+                .min_length(*code_length as u16)
+                .max_length(*code_length as u16)
+                .placeholder("-".repeat(*code_length)),
+        ]
+    }
+
+    fn parse(values: &ModalValues<'_>) -> Result<Self, ModalFieldError> {
+        Ok(Self {
+            code: values.required("captcha-input")?.to_owned(),
+        })
+    }
+}
+
 impl CaptchaModal {
     #[instrument(skip(state))]
     pub async fn handle(
@@ -49,7 +71,7 @@ impl CaptchaModal {
         let config = ctx.config(state).await?;
 
         // Get the pending captcha from the cache.
-        let captcha = match get_captcha(&ctx, state).await? {
+        let mut captcha = match get_captcha(&ctx, state).await? {
             Some(captcha) => captcha,
             None => {
                 return Ok(embed::captcha::captcha_not_found(ctx.lang));
@@ -57,17 +79,32 @@ impl CaptchaModal {
         };
 
         // Check if the entered code is correct.
-        let code = parse_modal_field_required(&data, "captcha-input")?;
+        let valid = match CaptchaForm::from_modal(&data, &captcha.code.len(), ctx.lang) {
+            Ok(form) => validate_code(&form.code, &captcha.code),
+            Err(_) => false,
+        };
 
-        if !validate_code(code, &captcha.code) {
-            let state_clone = state.clone();
-            tokio::spawn(async move {
-                kick_after(&state_clone, ctx.guild_id, ctx.author.id, config.lang()).await
-            });
+        if !valid {
+            schedule_kick(state, ctx.guild_id, ctx.author.id).await;
+
+            let event = LiveEvent::Captcha {
+                user_id: ctx.author.id,
+                outcome: CaptchaOutcome::Failed,
+            };
+            live::publish(ctx.guild_id, event, state).await;
 
             return Ok(embed::captcha::captcha_invalid_code(ctx.lang));
         }
 
+        // If the guild has a questionnaire, the member must answer it before
+        // being verified.
+        if config.captcha.questionnaire.is_some() {
+            captcha.solved = true;
+            state.cache.set(&captcha).await?;
+
+            return questionnaire_pending(ctx.lang);
+        }
+
         // Delete the captcha from the cache and update the user roles.
         state.cache.delete(&captcha).await?;
 
@@ -91,111 +128,3 @@ impl CaptchaModal {
         Ok(InteractionResponse::EphemeralEmbed(embed))
     }
 }
-
-/// Update the user roles.
-async fn update_roles(
-    user_id: Id<UserMarker>,
-    config: &GuildConfig,
-    state: &ClusterState,
-) -> Result<(), anyhow::Error> {
-    // Get the current member roles.
-    let member = state
-        .http
-        .guild_member(config.id, user_id)
-        .exec()
-        .await?
-        .model()
-        .await?;
-    let mut roles = member.roles;
-
-    // Ensure the bot has required permissions.
-    let permissions = state
-        .cache
-        .permissions(config.id)
-        .await?
-        .current_member()
-        .await?;
-
-    if !permissions.guild().contains(Permissions::MANAGE_ROLES) {
-        bail!("missing permission to manage roles");
-    }
-
-    // Remove the captcha role.
-    let role = config
-        .captcha
-        .role
-        .context("missing captcha role in config")?;
-
-    if !check_role_permission(&permissions, role, state).await {
-        bail!("missing permission to manage captcha role");
-    }
-
-    if let Some(index) = roles.iter().position(|r| r == &role) {
-        roles.remove(index);
-    }
-
-    // Add the verified roles.
-    for role in &config.captcha.verified_roles {
-        if !check_role_permission(&permissions, *role, state).await {
-            info!("missing permission to manage verified role");
-            continue;
-        }
-
-        if !roles.contains(role) {
-            roles.push(*role);
-        }
-    }
-
-    // Update the member roles.
-    state
-        .http
-        .update_guild_member(config.id, user_id)
-        .roles(&roles)
-        .exec()
-        .await?;
-
-    Ok(())
-}
-
-/// Ensure the bot has the required permission to update a role.
-async fn check_role_permission(
-    permissions: &CachePermissions<'_>,
-    role_id: Id<RoleMarker>,
-    state: &ClusterState,
-) -> bool {
-    let role = match state.cache.get::<CachedRole>(&role_id).await {
-        Ok(Some(role)) => role,
-        _ => return false,
-    };
-
-    if RoleOrdering::from(&role) >= permissions.highest_role() {
-        return false;
-    }
-
-    true
-}
-
-/// Validate the captcha code, accepting at most one error.
-fn validate_code(a: &str, b: &str) -> bool {
-    let mut errors: u8 = 0;
-
-    for (a, b) in a.chars().zip(b.chars()) {
-        if a != b {
-            errors += 1;
-        }
-    }
-
-    errors <= 1
-}
-
-#[cfg(test)]
-mod tests {
-    use super::validate_code;
-
-    #[test]
-    fn test_validate_code() {
-        assert!(validate_code("abc", "abc")); // no errors
-        assert!(validate_code("abc", "abd")); // one error
-        assert!(!validate_code("abc", "ade")); // two errors (fail)
-    }
-}
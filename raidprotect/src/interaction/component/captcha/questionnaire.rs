@@ -0,0 +1,438 @@
+//! Captcha join-gate questionnaire.
+//!
+//! If configured, new members must answer a few questions before being
+//! verified, either after completing the captcha or instead of it. The answers
+//! are sent to a review channel, where moderators can approve or deny the
+//! member with buttons.
+
+use anyhow::{bail, Context};
+use raidprotect_model::database::model::QuestionnaireConfig;
+use tracing::{error, instrument};
+use twilight_http::request::AuditLogReason;
+use twilight_mention::Mention;
+use twilight_model::{
+    application::{
+        component::{
+            button::ButtonStyle, text_input::TextInputStyle, ActionRow, Button, Component,
+            TextInput,
+        },
+        interaction::Interaction,
+    },
+    channel::message::MessageFlags,
+    guild::Permissions,
+    http::interaction::InteractionResponseType,
+    id::{
+        marker::{ChannelMarker, UserMarker},
+        Id,
+    },
+};
+use twilight_util::builder::{
+    embed::{EmbedBuilder, EmbedFieldBuilder},
+    InteractionResponseDataBuilder,
+};
+
+use super::verify::get_captcha;
+use crate::{
+    cluster::ClusterState,
+    feature::captcha::update_roles,
+    interaction::{
+        embed::{self, COLOR_RED, COLOR_SUCCESS, COLOR_TRANSPARENT},
+        response::InteractionResponse,
+        util::{
+            parse_modal_data, parse_modal_field, parse_modal_field_required, ComponentId, CustomId,
+            CustomIdFields, GuildConfigExt, GuildInteractionContext,
+        },
+    },
+    translations::Lang,
+    util::TextProcessExt,
+};
+
+/// Questionnaire modal sent to new members.
+pub fn questionnaire_modal(
+    questionnaire: &QuestionnaireConfig,
+    lang: Lang,
+) -> Result<InteractionResponse, anyhow::Error> {
+    let components = questionnaire
+        .questions
+        .iter()
+        .enumerate()
+        .map(|(index, question)| {
+            Component::ActionRow(ActionRow {
+                components: vec![Component::TextInput(TextInput {
+                    custom_id: format!("answer-{index}"),
+                    label: question.clone(),
+                    max_length: Some(1000),
+                    min_length: None,
+                    placeholder: None,
+                    required: Some(true),
+                    style: TextInputStyle::Paragraph,
+                    value: None,
+                })],
+            })
+        })
+        .collect();
+
+    Ok(InteractionResponse::Modal {
+        custom_id: CaptchaQuestionnaireModal.encode()?,
+        title: lang.captcha_questionnaire_title().to_owned(),
+        components,
+    })
+}
+
+/// Response sent after the captcha has been completed, when the member must
+/// answer the questionnaire.
+pub fn questionnaire_pending(lang: Lang) -> Result<InteractionResponse, anyhow::Error> {
+    let embed = EmbedBuilder::new()
+        .title(lang.captcha_success_title())
+        .color(COLOR_SUCCESS)
+        .description(lang.captcha_questionnaire_pending_description())
+        .build();
+
+    let custom_id = CaptchaQuestionnaireButton.encode()?;
+    let components = Component::ActionRow(ActionRow {
+        components: vec![Component::Button(Button {
+            custom_id: Some(custom_id),
+            disabled: false,
+            emoji: None,
+            label: Some(lang.captcha_questionnaire_button().to_owned()),
+            style: ButtonStyle::Success,
+            url: None,
+        })],
+    });
+
+    let response = InteractionResponseDataBuilder::new()
+        .embeds([embed])
+        .components([components])
+        .flags(MessageFlags::EPHEMERAL)
+        .build();
+
+    Ok(InteractionResponse::Raw {
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(response),
+    })
+}
+
+/// Questionnaire button.
+///
+/// This button send the questionnaire modal to a member that has completed
+/// the captcha.
+pub struct CaptchaQuestionnaireButton;
+
+impl_component_id!(CaptchaQuestionnaireButton, "captcha-questionnaire");
+
+impl CaptchaQuestionnaireButton {
+    #[instrument(skip(state))]
+    pub async fn handle(
+        interaction: Interaction,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let ctx = GuildInteractionContext::new(interaction)?;
+        let config = ctx.config(state).await?;
+
+        let captcha = get_captcha(&ctx, state).await?;
+        let questionnaire = match (captcha, config.captcha.questionnaire) {
+            (Some(captcha), Some(questionnaire))
+                if captcha.solved || questionnaire.replace_captcha =>
+            {
+                questionnaire
+            }
+            _ => return Ok(embed::captcha::captcha_not_found(ctx.lang)),
+        };
+
+        questionnaire_modal(&questionnaire, ctx.lang)
+    }
+}
+
+/// Questionnaire modal.
+///
+/// The answers are sent to the review channel, and the pending captcha is
+/// removed from the cache so the member is not kicked while waiting for the
+/// review.
+pub struct CaptchaQuestionnaireModal;
+
+impl_component_id!(CaptchaQuestionnaireModal, "captcha-questionnaire-modal");
+
+impl CaptchaQuestionnaireModal {
+    #[instrument(skip(state))]
+    pub async fn handle(
+        mut interaction: Interaction,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let data = parse_modal_data(&mut interaction)?;
+        let ctx = GuildInteractionContext::new(interaction)?;
+        let config = ctx.config(state).await?;
+
+        let captcha = get_captcha(&ctx, state).await?;
+        let (captcha, questionnaire) = match (captcha, config.captcha.questionnaire.as_ref()) {
+            (Some(captcha), Some(questionnaire))
+                if captcha.solved || questionnaire.replace_captcha =>
+            {
+                (captcha, questionnaire)
+            }
+            _ => return Ok(embed::captcha::captcha_not_found(ctx.lang)),
+        };
+
+        // Build the review message.
+        let guild_lang = config.lang();
+        let mut embed = EmbedBuilder::new()
+            .title(guild_lang.captcha_review_title())
+            .color(COLOR_TRANSPARENT)
+            .description(guild_lang.captcha_review_description(ctx.author.id.mention()));
+
+        for (index, question) in questionnaire.questions.iter().enumerate() {
+            let answer = parse_modal_field_required(&data, &format!("answer-{index}"))?;
+
+            embed = embed.field(EmbedFieldBuilder::new(question, answer.max_len(1024)));
+        }
+
+        let components = Component::ActionRow(ActionRow {
+            components: vec![
+                Component::Button(Button {
+                    custom_id: Some(CaptchaReviewId::Approve(ctx.author.id).encode()?),
+                    disabled: false,
+                    emoji: None,
+                    label: Some(guild_lang.captcha_review_approve().to_owned()),
+                    style: ButtonStyle::Success,
+                    url: None,
+                }),
+                Component::Button(Button {
+                    custom_id: Some(CaptchaReviewId::Deny(ctx.author.id).encode()?),
+                    disabled: false,
+                    emoji: None,
+                    label: Some(guild_lang.captcha_review_deny().to_owned()),
+                    style: ButtonStyle::Danger,
+                    url: None,
+                }),
+            ],
+        });
+
+        state
+            .cache_http(ctx.guild_id)
+            .create_message(questionnaire.review_channel)
+            .await?
+            .embeds(&[embed.build()])?
+            .components(&[components])?
+            .exec()
+            .await?;
+
+        // Delete the captcha from the cache, the member is now waiting for
+        // the review.
+        state.cache.delete(&captcha).await?;
+
+        let embed = EmbedBuilder::new()
+            .title(ctx.lang.captcha_questionnaire_submitted_title())
+            .color(COLOR_SUCCESS)
+            .description(ctx.lang.captcha_questionnaire_submitted_description())
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
+
+/// Questionnaire review buttons.
+///
+/// This type handle both the approve and deny buttons sent with the
+/// questionnaire answers. The member id is stored in the button custom id
+/// (see [`CaptchaReviewId`]).
+pub struct CaptchaReviewButton;
+
+/// Custom id of the questionnaire review buttons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptchaReviewId {
+    /// Approve the member.
+    Approve(Id<UserMarker>),
+    /// Deny the member.
+    Deny(Id<UserMarker>),
+}
+
+impl ComponentId for CaptchaReviewId {
+    fn name(&self) -> &'static str {
+        match self {
+            CaptchaReviewId::Approve(_) => "captcha-approve",
+            CaptchaReviewId::Deny(_) => "captcha-deny",
+        }
+    }
+
+    fn fields(&self) -> Vec<String> {
+        match self {
+            CaptchaReviewId::Approve(user_id) | CaptchaReviewId::Deny(user_id) => {
+                vec![user_id.to_string()]
+            }
+        }
+    }
+
+    fn from_fields(name: &str, fields: &mut CustomIdFields<'_>) -> Result<Self, anyhow::Error> {
+        match name {
+            "captcha-approve" => Ok(CaptchaReviewId::Approve(fields.next()?)),
+            "captcha-deny" => Ok(CaptchaReviewId::Deny(fields.next()?)),
+            name => bail!("unknown review button: {name}"),
+        }
+    }
+}
+
+impl CaptchaReviewButton {
+    #[instrument(skip(state))]
+    pub async fn handle(
+        interaction: Interaction,
+        custom_id: CustomId,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let ctx = GuildInteractionContext::new(interaction)?;
+        let config = ctx.config(state).await?;
+        let guild_lang = config.lang();
+
+        // Ensure the user is allowed to review members.
+        let permissions = ctx.member.permissions.unwrap_or_else(Permissions::empty);
+        if !permissions.contains(Permissions::KICK_MEMBERS) {
+            return Ok(embed::captcha::review_missing_permission(ctx.lang));
+        }
+
+        let (decision, color) = match CaptchaReviewId::decode(&custom_id)? {
+            CaptchaReviewId::Approve(user_id) => {
+                update_roles(user_id, &config, state).await?;
+
+                (
+                    guild_lang.captcha_review_approved(ctx.author.id.mention()),
+                    COLOR_SUCCESS,
+                )
+            }
+            CaptchaReviewId::Deny(user_id) => {
+                if let Err(error) = state
+                    .cache_http(ctx.guild_id)
+                    .remove_guild_member(user_id)
+                    .await?
+                    .reason(guild_lang.captcha_review_denied_reason())?
+                    .exec()
+                    .await
+                {
+                    error!(error = ?error, "failed to kick member after questionnaire review");
+                }
+
+                (
+                    guild_lang.captcha_review_denied(ctx.author.id.mention()),
+                    COLOR_RED,
+                )
+            }
+        };
+
+        // Update the review message with the decision.
+        let mut embed = ctx
+            .interaction
+            .message
+            .as_ref()
+            .and_then(|message| message.embeds.first().cloned())
+            .context("missing review message embed")?;
+
+        embed.color = Some(color);
+        embed
+            .fields
+            .push(EmbedFieldBuilder::new(guild_lang.captcha_review_decision(), decision).build());
+
+        let response = InteractionResponseDataBuilder::new()
+            .embeds([embed])
+            .components(Vec::new())
+            .build();
+
+        Ok(InteractionResponse::Raw {
+            kind: InteractionResponseType::UpdateMessage,
+            data: Some(response),
+        })
+    }
+}
+
+/// Questionnaire configuration modal.
+///
+/// This modal is sent by the `/config captcha questionnaire` command. The
+/// review channel and whether the questionnaire replaces the captcha are
+/// stored in the modal custom id (see [`CaptchaQuestionnaireConfigId`]).
+pub struct CaptchaQuestionnaireConfigModal;
+
+/// Custom id of the questionnaire configuration modal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaptchaQuestionnaireConfigId {
+    /// Channel where the answers are sent for review.
+    pub review_channel: Id<ChannelMarker>,
+    /// Whether the questionnaire replaces the captcha.
+    pub replace_captcha: bool,
+}
+
+impl ComponentId for CaptchaQuestionnaireConfigId {
+    fn name(&self) -> &'static str {
+        "captcha-questionnaire-config"
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.review_channel.to_string(),
+            self.replace_captcha.to_string(),
+        ]
+    }
+
+    fn from_fields(_name: &str, fields: &mut CustomIdFields<'_>) -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            review_channel: fields.next()?,
+            replace_captcha: fields.next()?,
+        })
+    }
+}
+
+impl CaptchaQuestionnaireConfigModal {
+    #[instrument(skip(state))]
+    pub async fn handle(
+        mut interaction: Interaction,
+        custom_id: CustomId,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let data = parse_modal_data(&mut interaction)?;
+        let ctx = GuildInteractionContext::new(interaction)?;
+        let mut config = ctx.config(state).await?;
+
+        if !config.captcha.enabled {
+            return Ok(embed::captcha::not_enabled(ctx.lang));
+        }
+
+        let CaptchaQuestionnaireConfigId {
+            review_channel,
+            replace_captcha,
+        } = CaptchaQuestionnaireConfigId::decode(&custom_id)?;
+
+        // Collect non-empty questions.
+        let mut questions = Vec::with_capacity(QuestionnaireConfig::MAX_QUESTIONS_LEN);
+
+        for index in 0..QuestionnaireConfig::MAX_QUESTIONS_LEN {
+            let question = parse_modal_field(&data, &format!("question-{index}"))?;
+
+            if let Some(question) = question.map(str::trim).filter(|q| !q.is_empty()) {
+                questions.push(question.max_len(QuestionnaireConfig::MAX_QUESTION_LEN));
+            }
+        }
+
+        // The questionnaire is disabled if no question is set.
+        let description = if questions.is_empty() {
+            config.captcha.questionnaire = None;
+
+            ctx.lang
+                .captcha_questionnaire_disabled_description()
+                .to_owned()
+        } else {
+            config.captcha.questionnaire = Some(QuestionnaireConfig {
+                questions,
+                review_channel,
+                replace_captcha,
+            });
+
+            ctx.lang
+                .captcha_questionnaire_confirm_description(review_channel.mention())
+        };
+
+        state.update_guild_config(&config).await?;
+
+        let embed = EmbedBuilder::new()
+            .color(COLOR_SUCCESS)
+            .title(ctx.lang.config_updated_title())
+            .description(description)
+            .build();
+
+        Ok(InteractionResponse::EphemeralEmbed(embed))
+    }
+}
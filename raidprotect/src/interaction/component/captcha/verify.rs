@@ -1,15 +1,15 @@
 //! Captcha verification button and modal.
 
-use raidprotect_captcha::{code::random_human_code, generate_captcha_png};
-use raidprotect_model::cache::model::interaction::PendingCaptcha;
+use raidprotect_captcha::code::random_human_code;
+use raidprotect_model::{
+    cache::model::interaction::{PendingCaptcha, WebCaptcha},
+    database::model::JobAction,
+};
+use rand::{distributions::Alphanumeric, Rng};
 use tracing::{error, instrument};
-use twilight_http::request::AuditLogReason;
 use twilight_model::{
     application::{
-        component::{
-            button::ButtonStyle, text_input::TextInputStyle, ActionRow, Button, Component,
-            TextInput,
-        },
+        component::{button::ButtonStyle, ActionRow, Button, Component},
         interaction::Interaction,
     },
     channel::message::MessageFlags,
@@ -24,15 +24,19 @@ use twilight_util::builder::{
     InteractionResponseDataBuilder,
 };
 
+use super::{
+    modal::{CaptchaForm, CaptchaModal},
+    questionnaire::questionnaire_modal,
+};
 use crate::{
     cluster::ClusterState,
-    feature::captcha,
+    feature::{captcha, scheduler},
     interaction::{
         embed::{self, COLOR_TRANSPARENT},
+        modal::ModalForm,
         response::InteractionResponse,
-        util::{CustomId, GuildConfigExt, GuildInteractionContext},
+        util::{ComponentId, GuildInteractionContext},
     },
-    translations::Lang,
 };
 
 /// Captcha verification button.
@@ -41,6 +45,8 @@ use crate::{
 /// a generated captcha image.
 pub struct CaptchaVerifyButton;
 
+impl_component_id!(CaptchaVerifyButton, "captcha-verify");
+
 impl CaptchaVerifyButton {
     #[instrument(skip(state))]
     pub async fn handle(
@@ -57,24 +63,71 @@ impl CaptchaVerifyButton {
             }
         };
 
-        // Kick the user if the captcha has been regenerated too many times.
-        if captcha.regenerate_count >= captcha::MAX_RETRY {
-            let config = ctx.config(state).await?;
-            let state_clone = state.clone();
+        // Send the questionnaire directly if it replaces the captcha image.
+        let config = ctx.config(state).await?;
+
+        if let Some(questionnaire) = &config.captcha.questionnaire {
+            if questionnaire.replace_captcha {
+                return questionnaire_modal(questionnaire, ctx.lang);
+            }
+        }
+
+        // The strict mode uses longer codes that cannot be regenerated.
+        let (length, max_retry) = if captcha.strict {
+            (captcha::STRICT_LENGTH, captcha::STRICT_MAX_RETRY)
+        } else {
+            (captcha::DEFAULT_LENGTH, captcha::MAX_RETRY)
+        };
 
-            tokio::spawn(async move {
-                kick_after(&state_clone, ctx.guild_id, ctx.author.id, config.lang()).await
-            });
+        // Kick the user if the captcha has been regenerated too many times.
+        if captcha.regenerate_count >= max_retry {
+            schedule_kick(state, ctx.guild_id, ctx.author.id).await;
 
             return Ok(embed::captcha::regenerate_error(ctx.lang));
         }
 
         // Generate the captcha image.
-        let code = random_human_code(captcha::DEFAULT_LENGTH);
+        let code = random_human_code(length, &mut rand::thread_rng());
+
+        let image = match state
+            .captcha_renderer
+            .render(ctx.guild_id, code.clone())
+            .await?
+        {
+            Some(image) => image,
+            None => return Ok(embed::captcha::render_busy(ctx.lang)),
+        };
+
+        // Create a link to the web verification page if the web service is
+        // configured. The questionnaire cannot be answered on the web page.
+        //
+        // The image is stored with the token so that the page always serves
+        // the same rendering of the code.
+        if let Some(token) = captcha.web_token.take() {
+            state.cache.delete_from::<WebCaptcha>(&token).await?;
+        }
+
+        let web_url = match (&state.web_url, &config.captcha.questionnaire) {
+            (Some(web_url), None) => {
+                let web_captcha = WebCaptcha {
+                    token: rand::thread_rng()
+                        .sample_iter(&Alphanumeric)
+                        .take(32)
+                        .map(char::from)
+                        .collect(),
+                    guild_id: ctx.guild_id,
+                    member_id: ctx.author.id,
+                    code: code.clone(),
+                    image: image.clone(),
+                };
+
+                state.cache.set(&web_captcha).await?;
+                captcha.web_token = Some(web_captcha.token.clone());
 
-        let code_clone = code.clone();
-        let image =
-            tokio::task::spawn_blocking(move || generate_captcha_png(&code_clone)).await??;
+                Some(format!("{web_url}/verify/{}", web_captcha.token))
+            }
+            _ => None,
+        };
 
         // Update the captcha in the cache.
         captcha.code = code;
@@ -90,9 +143,9 @@ impl CaptchaVerifyButton {
             .image(ImageSource::attachment("captcha.png")?)
             .build();
 
-        let continue_id = CustomId::name("captcha-validate");
+        let continue_id = CaptchaValidateButton.encode()?;
         let mut components = vec![Component::Button(Button {
-            custom_id: Some(continue_id.to_string()),
+            custom_id: Some(continue_id),
             label: Some(ctx.lang.captcha_image_button().to_owned()),
             style: ButtonStyle::Success,
             disabled: false,
@@ -100,12 +153,12 @@ impl CaptchaVerifyButton {
             url: None,
         })];
 
-        // Add regenerate button if MAX_RETRY is not reached.
+        // Add regenerate button if the maximum number of retries is not reached.
         // The button will re-trigger the current interaction.
-        if captcha.regenerate_count < captcha::MAX_RETRY {
-            let regenerate_id = CustomId::name("captcha-verify");
+        if captcha.regenerate_count < max_retry {
+            let regenerate_id = CaptchaVerifyButton.encode()?;
             components.push(Component::Button(Button {
-                custom_id: Some(regenerate_id.to_string()),
+                custom_id: Some(regenerate_id),
                 label: Some(ctx.lang.captcha_image_regenerate().to_owned()),
                 style: ButtonStyle::Secondary,
                 disabled: false,
@@ -114,6 +167,18 @@ impl CaptchaVerifyButton {
             }));
         }
 
+        // Add the link to the web verification page.
+        if let Some(web_url) = web_url {
+            components.push(Component::Button(Button {
+                custom_id: None,
+                label: Some(ctx.lang.captcha_image_web_button().to_owned()),
+                style: ButtonStyle::Link,
+                disabled: false,
+                emoji: None,
+                url: Some(web_url),
+            }));
+        }
+
         let component = Component::ActionRow(ActionRow { components });
         let attachment = Attachment {
             file: image,
@@ -136,31 +201,14 @@ impl CaptchaVerifyButton {
     }
 }
 
-/// Kick user that failed to verify after 10 seconds.
-pub async fn kick_after(
-    state: &ClusterState,
-    guild: Id<GuildMarker>,
-    user: Id<UserMarker>,
-    guild_lang: Lang,
-) {
-    tokio::time::sleep(captcha::KICK_AFTER).await;
-
-    let http = state.cache_http(guild);
-    let req = match http.remove_guild_member(user).await {
-        Ok(req) => req,
-        Err(error) => {
-            error!(error = ?error, "missing permissions to kick user after captcha");
-            return;
-        }
-    };
-
-    if let Err(error) = req
-        .reason(guild_lang.captcha_kick_reason())
-        .unwrap()
-        .exec()
-        .await
-    {
-        error!(error = ?error, "failed to kick user after captcha");
+/// Schedule the kick of a user that failed to verify.
+///
+/// The user is kicked after [`captcha::KICK_AFTER`] by the scheduler.
+pub async fn schedule_kick(state: &ClusterState, guild: Id<GuildMarker>, user: Id<UserMarker>) {
+    let action = JobAction::CaptchaDeadline { user_id: user };
+
+    if let Err(error) = scheduler::schedule(state, guild, action, captcha::KICK_AFTER).await {
+        error!(error = ?error, "failed to schedule kick after captcha");
     }
 }
 
@@ -169,6 +217,8 @@ pub async fn kick_after(
 /// This button send the captcha modal to the user.
 pub struct CaptchaValidateButton;
 
+impl_component_id!(CaptchaValidateButton, "captcha-validate");
+
 impl CaptchaValidateButton {
     #[instrument(skip(state))]
     pub async fn handle(
@@ -186,27 +236,12 @@ impl CaptchaValidateButton {
         };
 
         // Send the captcha modal.
-        let input_custom_id = CustomId::name("captcha-input");
-        let modal_custom_id = CustomId::name("captcha-modal");
-
-        let components = vec![Component::ActionRow(ActionRow {
-            components: vec![Component::TextInput(TextInput {
-                custom_id: input_custom_id.to_string(),
-                label: ctx.lang.captcha_input_label().to_owned(),
-                max_length: Some(code_length as u16),
-                min_length: Some(code_length as u16),
-                placeholder: Some("-".repeat(code_length)),
-                required: Some(true),
-                style: TextInputStyle::Short,
-                value: None,
-            })],
-        })];
-
-        Ok(InteractionResponse::Modal {
-            custom_id: modal_custom_id.to_string(),
-            title: ctx.lang.captcha_image_title().to_owned(),
-            components,
-        })
+        Ok(CaptchaForm::modal(
+            CaptchaModal.encode()?,
+            ctx.lang.captcha_image_title().to_owned(),
+            &code_length,
+            ctx.lang,
+        ))
     }
 }
 
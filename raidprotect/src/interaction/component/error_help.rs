@@ -0,0 +1,141 @@
+//! "Get help" button of error messages.
+//!
+//! When an error occurs while processing an interaction, a report is stored
+//! with the interaction ID as reference. The button included in the error
+//! message opens a thread in the support server channel with the report, so
+//! the support team gets the context of the error. The report only contains
+//! the error code: error messages may contain user data, and are only logged
+//! with the reference.
+
+use anyhow::{anyhow, Context};
+use raidprotect_model::cache::{discord::CachedGuild, model::interaction::ErrorReport};
+use twilight_model::{
+    application::interaction::{Interaction, InteractionData},
+    channel::ChannelType,
+    id::{marker::InteractionMarker, Id},
+};
+
+use crate::{
+    cluster::ClusterState,
+    interaction::{
+        embed::{self, error::ErrorKind},
+        response::InteractionResponse,
+        util::{CustomId, InteractionContext},
+    },
+};
+
+/// "Get help" button of error messages.
+pub struct ErrorHelpButton;
+
+impl ErrorHelpButton {
+    /// Store the report of an interaction error.
+    ///
+    /// Returns the error reference, or [`None`] if no support channel is
+    /// configured.
+    pub async fn report(
+        interaction: &Interaction,
+        state: &ClusterState,
+    ) -> Result<Option<Id<InteractionMarker>>, anyhow::Error> {
+        if state.support_channel.is_none() {
+            return Ok(None);
+        }
+
+        let name = match &interaction.data {
+            Some(InteractionData::ApplicationCommand(data)) => Some(data.name.clone()),
+            Some(InteractionData::MessageComponent(data)) => Some(data.custom_id.clone()),
+            Some(InteractionData::ModalSubmit(data)) => Some(data.custom_id.clone()),
+            _ => None,
+        };
+
+        let report = ErrorReport {
+            reference: interaction.id,
+            guild_id: interaction.guild_id,
+            user_id: interaction.author_id(),
+            name,
+            code: ErrorKind::Internal.code().to_owned(),
+            thread_url: None,
+        };
+
+        state.cache.set(&report).await?;
+
+        Ok(Some(interaction.id))
+    }
+
+    /// Handle the button click.
+    pub async fn handle(
+        interaction: Interaction,
+        custom_id: CustomId,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let ctx = InteractionContext::new(interaction)?;
+
+        let reference = custom_id
+            .id
+            .ok_or_else(|| anyhow!("missing error reference in custom_id"))?;
+        let mut report = match state.cache.get::<ErrorReport>(&reference).await? {
+            Some(report) => report,
+            None => return Ok(embed::error::expired_interaction(ctx.lang)),
+        };
+
+        // Only create one thread for each error.
+        if let Some(thread_url) = &report.thread_url {
+            return Ok(embed::error::help_thread(ctx.lang, thread_url));
+        }
+
+        let channel = state.support_channel.context("missing support channel")?;
+        let name = format!("Error {reference}");
+
+        let thread = state
+            .http
+            .create_thread(channel, &name, ChannelType::GuildPublicThread)?
+            .exec()
+            .await?
+            .model()
+            .await?;
+        let guild_id = thread.guild_id.context("missing thread guild id")?;
+
+        let content = thread_message(&report, state).await?;
+        state
+            .http
+            .create_message(thread.id)
+            .content(&content)?
+            .exec()
+            .await?;
+
+        let thread_url = format!("https://discord.com/channels/{guild_id}/{}", thread.id);
+        report.thread_url = Some(thread_url.clone());
+        state.cache.set(&report).await?;
+
+        Ok(embed::error::help_thread(ctx.lang, &thread_url))
+    }
+}
+
+/// Content of the first message of the help thread.
+///
+/// This message is intended for the support team and is not translated.
+async fn thread_message(
+    report: &ErrorReport,
+    state: &ClusterState,
+) -> Result<String, anyhow::Error> {
+    let mut content = format!("**Reference:** `{}`\n", report.reference);
+
+    if let Some(user_id) = report.user_id {
+        content.push_str(&format!("**User:** <@{user_id}> (`{user_id}`)\n"));
+    }
+
+    if let Some(guild_id) = report.guild_id {
+        let guild = state.cache.get::<CachedGuild>(&guild_id).await?;
+        let guild_name = guild.map(|guild| guild.name).unwrap_or_default();
+
+        content.push_str(&format!("**Guild:** {guild_name} (`{guild_id}`)\n"));
+    }
+
+    if let Some(name) = &report.name {
+        content.push_str(&format!("**Interaction:** `{name}`\n"));
+    }
+
+    content.push_str(&format!("**Error code:** `{}`\n", report.code));
+    content.push_str("The full error is logged with the reference.");
+
+    Ok(content)
+}
@@ -1,6 +1,20 @@
 //! Component interactions handling.
 
+mod antispam;
 pub mod captcha;
+mod error_help;
 mod post_in_chat;
+mod quarantine;
+mod raid;
+mod sanction;
+mod security_alert;
+pub mod setup;
 
+pub use antispam::FalsePositiveButton;
+pub use error_help::ErrorHelpButton;
 pub use post_in_chat::PostInChat;
+pub use quarantine::QuarantineButton;
+pub use raid::RaidBanButton;
+pub use sanction::{SanctionForm, SanctionFormOptions, SanctionModal, SanctionModalId};
+pub use security_alert::SecurityAlertButton;
+pub use setup::SetupComponent;
@@ -0,0 +1,110 @@
+//! Quarantine review buttons.
+//!
+//! A review message is sent in the logs channel when a member is placed in
+//! quarantine, with buttons to release or ban the member.
+
+use anyhow::Context;
+use raidprotect_model::cache::model::quarantine::QuarantinedMember;
+use tracing::instrument;
+use twilight_http::request::AuditLogReason;
+use twilight_mention::Mention;
+use twilight_model::{
+    application::interaction::Interaction, guild::Permissions,
+    http::interaction::InteractionResponseType, id::Id,
+};
+use twilight_util::builder::{embed::EmbedFieldBuilder, InteractionResponseDataBuilder};
+
+use crate::{
+    cluster::ClusterState,
+    feature::quarantine,
+    interaction::{
+        embed::{self, COLOR_SUCCESS},
+        response::InteractionResponse,
+        util::{CustomId, GuildConfigExt, GuildInteractionContext},
+    },
+};
+
+/// "Release" and "Ban" buttons of quarantine review messages.
+///
+/// The id of the quarantined member is stored in the button custom id.
+pub struct QuarantineButton;
+
+impl QuarantineButton {
+    #[instrument(skip(state))]
+    pub async fn handle(
+        interaction: Interaction,
+        custom_id: CustomId,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let ctx = GuildInteractionContext::new(interaction)?;
+        let ban = custom_id.name == "quarantine-ban";
+
+        // Ensure the user is allowed to release or ban members.
+        let required = match ban {
+            true => Permissions::BAN_MEMBERS,
+            false => Permissions::MANAGE_ROLES,
+        };
+        let permissions = ctx.member.permissions.unwrap_or_else(Permissions::empty);
+        if !permissions.contains(required) {
+            return Ok(embed::quarantine::missing_permission(ctx.lang));
+        }
+
+        let user_id = Id::new(
+            custom_id
+                .id
+                .context("missing quarantined user id")?
+                .parse()?,
+        );
+        let quarantined = match state
+            .cache
+            .get::<QuarantinedMember>(&(ctx.guild_id, user_id))
+            .await?
+        {
+            Some(quarantined) => quarantined,
+            None => return Ok(embed::error::expired_interaction(ctx.lang)),
+        };
+
+        let config = ctx.config(state).await?;
+        let guild_lang = config.lang();
+
+        let decision = if ban {
+            state
+                .cache_http(ctx.guild_id)
+                .create_ban(user_id)
+                .await?
+                .reason(&quarantined.reason)?
+                .exec()
+                .await?;
+            state.cache.delete(&quarantined).await?;
+
+            guild_lang.quarantine_banned(ctx.author.id.mention())
+        } else {
+            quarantine::release(&quarantined, &config, state).await?;
+
+            guild_lang.quarantine_released(ctx.author.id.mention())
+        };
+
+        // Update the review message with the decision.
+        let mut embed = ctx
+            .interaction
+            .message
+            .as_ref()
+            .and_then(|message| message.embeds.first().cloned())
+            .context("missing quarantine review embed")?;
+
+        embed.color = Some(COLOR_SUCCESS);
+        embed
+            .fields
+            .push(EmbedFieldBuilder::new(guild_lang.quarantine_decision(), decision).build());
+
+        let response = InteractionResponseDataBuilder::new()
+            .embeds([embed])
+            .components(Vec::new())
+            .build();
+
+        Ok(InteractionResponse::Raw {
+            kind: InteractionResponseType::UpdateMessage,
+            data: Some(response),
+        })
+    }
+}
@@ -0,0 +1,112 @@
+//! Raid alert button.
+//!
+//! Raid alerts are sent by the anti-spam in the logs channel when many
+//! accounts send similar messages. The alert has a button to ban all the
+//! accounts that participated in the raid.
+
+use anyhow::Context;
+use raidprotect_model::cache::model::interaction::PendingRaidAlert;
+use tracing::{error, instrument};
+use twilight_http::request::AuditLogReason;
+use twilight_mention::Mention;
+use twilight_model::{
+    application::interaction::Interaction, guild::Permissions,
+    http::interaction::InteractionResponseType,
+};
+use twilight_util::builder::{embed::EmbedFieldBuilder, InteractionResponseDataBuilder};
+
+use crate::{
+    cluster::ClusterState,
+    interaction::{
+        embed::{self, COLOR_SUCCESS},
+        response::InteractionResponse,
+        util::{CustomId, GuildConfigExt, GuildInteractionContext},
+    },
+};
+
+/// "Ban accounts" button of raid alerts.
+///
+/// The id of the message that triggered the alert is stored in the button
+/// custom id.
+pub struct RaidBanButton;
+
+impl RaidBanButton {
+    #[instrument(skip(state))]
+    pub async fn handle(
+        interaction: Interaction,
+        custom_id: CustomId,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let ctx = GuildInteractionContext::new(interaction)?;
+
+        // Ensure the user is allowed to ban members.
+        let permissions = ctx.member.permissions.unwrap_or_else(Permissions::empty);
+        if !permissions.contains(Permissions::BAN_MEMBERS) {
+            return Ok(embed::antispam::raid_missing_permission(ctx.lang));
+        }
+
+        let alert_id = custom_id.id.context("missing raid alert id")?;
+        let alert = match state.cache.get::<PendingRaidAlert>(&alert_id).await? {
+            Some(alert) if alert.guild_id == ctx.guild_id => alert,
+            _ => return Ok(embed::error::expired_interaction(ctx.lang)),
+        };
+
+        let config = ctx.config(state).await?;
+        let guild_lang = config.lang();
+        let http = state.cache_http(ctx.guild_id);
+
+        let mut banned = 0;
+        for account in &alert.accounts {
+            state.ratelimit.acquire(ctx.guild_id).await;
+
+            let result = match http.create_ban(*account).await {
+                Ok(request) => {
+                    request
+                        .reason(guild_lang.antispam_raid_ban_reason())?
+                        .exec()
+                        .await
+                }
+                Err(error) => {
+                    error!(error = ?error, "missing permissions to ban raid accounts");
+                    break;
+                }
+            };
+
+            match result {
+                Ok(_) => banned += 1,
+                Err(error) => {
+                    error!(error = ?error, account = ?account, "failed to ban raid account")
+                }
+            }
+        }
+
+        state.cache.delete(&alert).await?;
+
+        // Update the alert message with the decision.
+        let mut embed = ctx
+            .interaction
+            .message
+            .as_ref()
+            .and_then(|message| message.embeds.first().cloned())
+            .context("missing raid alert embed")?;
+
+        embed.color = Some(COLOR_SUCCESS);
+        embed.fields.push(
+            EmbedFieldBuilder::new(
+                guild_lang.antispam_raid_decision(),
+                guild_lang.antispam_raid_banned(banned, ctx.author.id.mention()),
+            )
+            .build(),
+        );
+
+        let response = InteractionResponseDataBuilder::new()
+            .embeds([embed])
+            .components(Vec::new())
+            .build();
+
+        Ok(InteractionResponse::Raw {
+            kind: InteractionResponseType::UpdateMessage,
+            data: Some(response),
+        })
+    }
+}
@@ -0,0 +1,153 @@
+//! Sanction reason modal.
+//!
+//! This modal is sent by the moderation commands when no reason has been
+//! specified in the command. The sanction is stored in the cache as a
+//! [`PendingSanction`] until the modal is submitted.
+
+use raidprotect_model::{cache::model::interaction::PendingSanction, database::model::ModlogType};
+use tracing::instrument;
+use twilight_model::{
+    application::interaction::Interaction,
+    id::{marker::InteractionMarker, Id},
+};
+
+use crate::{
+    cluster::ClusterState,
+    interaction::{
+        embed,
+        modal::{ModalField, ModalFieldError, ModalForm, ModalValues},
+        response::InteractionResponse,
+        util::{parse_modal_data, ComponentId, CustomId, CustomIdFields, GuildInteractionContext},
+    },
+    translations::Lang,
+};
+
+/// Sanction reason modal.
+///
+/// The custom id of the modal contains the id of the initial interaction, used
+/// to retrieve the [`PendingSanction`] (see [`SanctionModalId`]).
+pub struct SanctionModal;
+
+/// Custom id of the sanction reason modal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SanctionModalId {
+    /// Id of the initial interaction.
+    pub interaction_id: Id<InteractionMarker>,
+}
+
+impl ComponentId for SanctionModalId {
+    fn name(&self) -> &'static str {
+        "sanction"
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![self.interaction_id.to_string()]
+    }
+
+    fn from_fields(_name: &str, fields: &mut CustomIdFields<'_>) -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            interaction_id: fields.next()?,
+        })
+    }
+}
+
+/// Form of the sanction reason modal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SanctionForm {
+    /// Reason sent to the sanctioned user.
+    pub reason: Option<String>,
+    /// Notes visible to the moderators.
+    pub notes: Option<String>,
+}
+
+/// Options of the [`SanctionForm`].
+#[derive(Debug, Clone, Copy)]
+pub struct SanctionFormOptions {
+    /// Type of the sanction.
+    pub kind: ModlogType,
+    /// Whether the reason is required.
+    pub enforce_reason: bool,
+}
+
+impl ModalForm for SanctionForm {
+    type Options = SanctionFormOptions;
+
+    fn fields(options: &Self::Options, lang: Lang) -> Vec<ModalField> {
+        let reason_label = match options.kind {
+            ModlogType::Kick => lang.modal_kick_reason_label(),
+            _ => lang.modal_reason_label(),
+        };
+
+        vec![
+            ModalField::short("reason", reason_label)
+                .required(options.enforce_reason)
+                .max_length(100)
+                .placeholder(lang.modal_reason_placeholder()),
+            ModalField::paragraph("notes", lang.modal_notes_label())
+                .max_length(1000)
+                .placeholder(lang.modal_notes_placeholder()),
+        ]
+    }
+
+    fn parse(values: &ModalValues<'_>) -> Result<Self, ModalFieldError> {
+        Ok(Self {
+            reason: values.text("reason").map(ToOwned::to_owned),
+            notes: values.text("notes").map(ToOwned::to_owned),
+        })
+    }
+}
+
+impl SanctionModal {
+    #[instrument(skip(state))]
+    pub async fn handle(
+        mut interaction: Interaction,
+        custom_id: CustomId,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let data = parse_modal_data(&mut interaction)?;
+        let ctx = GuildInteractionContext::new(interaction)?;
+        let SanctionModalId { interaction_id } = SanctionModalId::decode(&custom_id)?;
+
+        let pending = match state
+            .cache
+            .get::<PendingSanction>(&interaction_id.to_string())
+            .await?
+        {
+            Some(pending) => pending,
+            None => return Ok(embed::error::expired_interaction(ctx.lang)),
+        };
+
+        let options = SanctionFormOptions {
+            kind: pending.kind,
+            enforce_reason: ctx.config(state).await?.moderation.enforce_reason,
+        };
+
+        let _form = match SanctionForm::from_modal(&data, &options, ctx.lang) {
+            Ok(form) => form,
+            Err(error) => return Ok(embed::error::invalid_field(ctx.lang, &error)),
+        };
+
+        state.cache.delete(&pending).await?;
+
+        Ok(InteractionResponse::EphemeralDeferredMessage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanction_modal_id() {
+        let id = SanctionModalId {
+            interaction_id: Id::new(42),
+        };
+        let custom_id = id.encode().unwrap();
+
+        assert_eq!(custom_id, "sanction:42");
+        assert_eq!(
+            SanctionModalId::decode(&custom_id.parse().unwrap()).unwrap(),
+            id
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! Security alert buttons.
+//!
+//! Security alerts are sent in the logs channel when a bot is added or when a
+//! role gains dangerous permissions (see [`security_alert`]), with a button to
+//! kick the bot or remove the added permissions.
+//!
+//! [`security_alert`]: crate::feature::security_alert
+
+use anyhow::Context;
+use tracing::instrument;
+use twilight_http::request::AuditLogReason;
+use twilight_mention::Mention;
+use twilight_model::{
+    application::interaction::Interaction, guild::Permissions,
+    http::interaction::InteractionResponseType, id::Id,
+};
+use twilight_util::builder::{embed::EmbedFieldBuilder, InteractionResponseDataBuilder};
+
+use crate::{
+    cluster::ClusterState,
+    interaction::{
+        embed::{self, COLOR_SUCCESS},
+        response::InteractionResponse,
+        util::{CustomId, GuildConfigExt, GuildInteractionContext},
+    },
+};
+
+/// "Kick bot" and "Revert role" buttons of security alerts.
+///
+/// The id of the bot is stored in the custom id of the "Kick bot" button. The
+/// id of the role and the added permissions are stored in the custom id of the
+/// "Revert role" button.
+pub struct SecurityAlertButton;
+
+impl SecurityAlertButton {
+    #[instrument(skip(state))]
+    pub async fn handle(
+        interaction: Interaction,
+        custom_id: CustomId,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let ctx = GuildInteractionContext::new(interaction)?;
+
+        // Only administrators are allowed to use the buttons.
+        let permissions = ctx.member.permissions.unwrap_or_else(Permissions::empty);
+        if !permissions.contains(Permissions::ADMINISTRATOR) {
+            return Ok(embed::security_alert::missing_permission(ctx.lang));
+        }
+
+        let id = custom_id.id.context("missing security alert target")?;
+        let config = ctx.config(state).await?;
+        let guild_lang = config.lang();
+        let http = state.cache_http(ctx.guild_id);
+
+        let decision = if custom_id.name == "security-kick-bot" {
+            let bot_id = Id::new(id.parse()?);
+
+            http.remove_guild_member(bot_id)
+                .await?
+                .reason(guild_lang.security_kick_bot_reason())?
+                .exec()
+                .await?;
+
+            guild_lang.security_bot_kicked(ctx.author.id.mention())
+        } else {
+            let (role_id, added) = id.split_once(':').context("invalid role alert id")?;
+            let added = Permissions::from_bits_truncate(added.parse()?);
+
+            let role = match http.role(Id::new(role_id.parse()?)).await? {
+                Some(role) => role,
+                None => return Ok(embed::error::expired_interaction(ctx.lang)),
+            };
+
+            http.update_role(role.id)
+                .await?
+                .permissions(role.permissions - added)
+                .reason(guild_lang.security_revert_role_reason())?
+                .exec()
+                .await?;
+
+            guild_lang.security_role_reverted(ctx.author.id.mention())
+        };
+
+        // Update the alert message with the decision.
+        let mut embed = ctx
+            .interaction
+            .message
+            .as_ref()
+            .and_then(|message| message.embeds.first().cloned())
+            .context("missing security alert embed")?;
+
+        embed.color = Some(COLOR_SUCCESS);
+        embed
+            .fields
+            .push(EmbedFieldBuilder::new(guild_lang.security_decision(), decision).build());
+
+        let response = InteractionResponseDataBuilder::new()
+            .embeds([embed])
+            .components(Vec::new())
+            .build();
+
+        Ok(InteractionResponse::Raw {
+            kind: InteractionResponseType::UpdateMessage,
+            data: Some(response),
+        })
+    }
+}
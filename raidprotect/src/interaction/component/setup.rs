@@ -0,0 +1,534 @@
+//! Setup wizard components.
+//!
+//! The `/setup` command sends a message that is updated at each step of the
+//! wizard:
+//! 1. Select the logs channel.
+//! 2. Choose whether the captcha should be enabled.
+//! 3. Choose an anti-spam profile.
+//!
+//! The choices are stored in a [`PendingSetup`] until the summary is
+//! confirmed, where they are written to the guild configuration. The captcha
+//! is enabled with the same button as the `/config captcha enable` command,
+//! since it creates channels and roles.
+
+use anyhow::{bail, Context};
+use raidprotect_model::{
+    cache::model::interaction::PendingSetup,
+    database::model::{AntiSpamProfile, GuildConfig},
+};
+use twilight_mention::Mention;
+use twilight_model::{
+    application::{
+        component::{
+            button::ButtonStyle, select_menu::SelectMenuOption, ActionRow, Button, Component,
+            SelectMenu,
+        },
+        interaction::Interaction,
+    },
+    channel::{embed::Embed, message::MessageFlags, ChannelType},
+    guild::Permissions,
+    http::interaction::InteractionResponseType,
+    id::{
+        marker::{ChannelMarker, GuildMarker},
+        Id,
+    },
+};
+use twilight_util::builder::{
+    embed::{EmbedBuilder, EmbedFieldBuilder},
+    InteractionResponseDataBuilder,
+};
+
+use super::captcha::CaptchaEnable;
+use crate::{
+    cluster::ClusterState,
+    interaction::{
+        embed::{self, COLOR_SUCCESS, COLOR_TRANSPARENT},
+        response::InteractionResponse,
+        util::{
+            parse_component_data, ComponentId, CustomId, CustomIdFields, GuildInteractionContext,
+        },
+    },
+    translations::Lang,
+};
+
+/// Number of steps of the wizard, excluding the summary.
+const STEPS: u8 = 3;
+
+/// Maximum number of options in a select menu.
+const MAX_OPTIONS: usize = 25;
+
+/// Anti-spam profiles, in the order shown to the user.
+const PROFILES: [AntiSpamProfile; 4] = [
+    AntiSpamProfile::Standard,
+    AntiSpamProfile::Relaxed,
+    AntiSpamProfile::Strict,
+    AntiSpamProfile::Disabled,
+];
+
+/// Components of the setup wizard.
+///
+/// See the [module documentation](self) for more information.
+pub struct SetupComponent;
+
+/// Custom id of the setup wizard components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetupId {
+    /// Logs channel select menu.
+    LogsChannel,
+    /// Button to skip the logs channel step.
+    SkipLogs,
+    /// Captcha step buttons.
+    Captcha(bool),
+    /// Anti-spam profile select menu.
+    AntiSpam,
+    /// Summary confirmation button.
+    Confirm,
+    /// Button to cancel the wizard.
+    Cancel,
+}
+
+impl ComponentId for SetupId {
+    fn name(&self) -> &'static str {
+        match self {
+            SetupId::LogsChannel => "setup-logs",
+            SetupId::SkipLogs => "setup-logs-skip",
+            SetupId::Captcha(_) => "setup-captcha",
+            SetupId::AntiSpam => "setup-antispam",
+            SetupId::Confirm => "setup-confirm",
+            SetupId::Cancel => "setup-cancel",
+        }
+    }
+
+    fn fields(&self) -> Vec<String> {
+        match self {
+            SetupId::Captcha(enable) => vec![enable.to_string()],
+            _ => Vec::new(),
+        }
+    }
+
+    fn from_fields(name: &str, fields: &mut CustomIdFields<'_>) -> Result<Self, anyhow::Error> {
+        match name {
+            "setup-logs" => Ok(SetupId::LogsChannel),
+            "setup-logs-skip" => Ok(SetupId::SkipLogs),
+            "setup-captcha" => Ok(SetupId::Captcha(fields.next()?)),
+            "setup-antispam" => Ok(SetupId::AntiSpam),
+            "setup-confirm" => Ok(SetupId::Confirm),
+            "setup-cancel" => Ok(SetupId::Cancel),
+            name => bail!("unknown setup component: {name}"),
+        }
+    }
+}
+
+impl SetupComponent {
+    pub async fn handle(
+        mut interaction: Interaction,
+        custom_id: CustomId,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let data = parse_component_data(&mut interaction)?;
+        let ctx = GuildInteractionContext::new(interaction)?;
+
+        let mut pending = match state
+            .cache
+            .get::<PendingSetup>(&(ctx.guild_id, ctx.author.id))
+            .await?
+        {
+            Some(pending) => pending,
+            None => return Ok(embed::error::expired_interaction(ctx.lang)),
+        };
+
+        let (embed, components) = match SetupId::decode(&custom_id)? {
+            SetupId::LogsChannel => {
+                let channel = data
+                    .values
+                    .first()
+                    .context("missing selected channel")?
+                    .parse()?;
+
+                if !can_send_logs(ctx.guild_id, channel, state).await? {
+                    return Ok(embed::logs::missing_permission(ctx.lang));
+                }
+
+                pending.logs_chan = Some(channel);
+                captcha_step(ctx.lang)?
+            }
+            SetupId::SkipLogs => {
+                pending.logs_chan = None;
+                captcha_step(ctx.lang)?
+            }
+            SetupId::Captcha(enable) => {
+                pending.captcha = enable;
+                antispam_step(ctx.lang)?
+            }
+            SetupId::AntiSpam => {
+                let value = data.values.first().context("missing selected profile")?;
+                let profile = PROFILES
+                    .into_iter()
+                    .find(|profile| profile_value(*profile) == value)
+                    .context("unknown anti-spam profile")?;
+
+                pending.antispam = Some(profile);
+                summary_step(&pending, &ctx.config(state).await?, ctx.lang)?
+            }
+            SetupId::Confirm => return confirm(&ctx, &pending, state).await,
+            SetupId::Cancel => {
+                state.cache.delete(&pending).await?;
+
+                let embed = EmbedBuilder::new()
+                    .color(COLOR_TRANSPARENT)
+                    .description(ctx.lang.setup_cancelled())
+                    .build();
+
+                return Ok(update_message(embed, Vec::new()));
+            }
+        };
+
+        state.cache.set(&pending).await?;
+
+        Ok(update_message(embed, components))
+    }
+}
+
+/// Initial message of the setup wizard.
+pub async fn setup_message(
+    guild_id: Id<GuildMarker>,
+    state: &ClusterState,
+    lang: Lang,
+) -> Result<InteractionResponse, anyhow::Error> {
+    let (embed, components) = logs_step(guild_id, state, lang).await?;
+
+    let response = InteractionResponseDataBuilder::new()
+        .embeds([embed])
+        .components(components)
+        .flags(MessageFlags::EPHEMERAL)
+        .build();
+
+    Ok(InteractionResponse::Raw {
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(response),
+    })
+}
+
+/// Save the configuration at the end of the wizard.
+async fn confirm(
+    ctx: &GuildInteractionContext,
+    pending: &PendingSetup,
+    state: &ClusterState,
+) -> Result<InteractionResponse, anyhow::Error> {
+    let mut config = ctx.config(state).await?;
+
+    if let Some(channel) = pending.logs_chan {
+        config.logs_chan = Some(channel);
+    }
+
+    if let Some(profile) = pending.antispam {
+        profile.apply(&mut config.antispam);
+    }
+
+    state.update_guild_config(&config).await?;
+    state.cache.delete(pending).await?;
+
+    let mut embed = EmbedBuilder::new()
+        .color(COLOR_SUCCESS)
+        .title(ctx.lang.setup_done_title())
+        .description(ctx.lang.setup_done_description());
+    let mut components = Vec::new();
+
+    // The captcha is enabled with the button of the `/config captcha enable`
+    // command, which creates the verification channel and role.
+    if pending.captcha && !config.captcha.enabled {
+        embed = embed.field(EmbedFieldBuilder::new(
+            ctx.lang.setup_captcha(),
+            ctx.lang.setup_done_captcha(),
+        ));
+        components.push(action_row(vec![button(
+            CaptchaEnable.encode()?,
+            ctx.lang.captcha_confirm_button(),
+            ButtonStyle::Success,
+        )]));
+    }
+
+    Ok(update_message(embed.build(), components))
+}
+
+/// Logs channel step.
+///
+/// The select menu contains the text channels where the bot can send logs.
+async fn logs_step(
+    guild_id: Id<GuildMarker>,
+    state: &ClusterState,
+    lang: Lang,
+) -> Result<(Embed, Vec<Component>), anyhow::Error> {
+    let mut channels = state
+        .cache
+        .guild_channels(guild_id)
+        .await?
+        .into_iter()
+        .filter(|channel| channel.kind == ChannelType::GuildText)
+        .collect::<Vec<_>>();
+    channels.sort_by_key(|channel| channel.position);
+
+    let mut options = Vec::new();
+    for channel in channels {
+        if options.len() == MAX_OPTIONS {
+            break;
+        }
+
+        if can_send_logs(guild_id, channel.id, state).await? {
+            options.push(SelectMenuOption {
+                default: false,
+                description: None,
+                emoji: None,
+                label: format!("#{}", channel.name),
+                value: channel.id.to_string(),
+            });
+        }
+    }
+
+    let mut components = Vec::new();
+    if !options.is_empty() {
+        components.push(action_row(vec![select_menu(
+            SetupId::LogsChannel.encode()?,
+            lang.setup_logs_placeholder(),
+            options,
+        )]));
+    }
+    components.push(action_row(vec![
+        button(
+            SetupId::SkipLogs.encode()?,
+            lang.setup_skip(),
+            ButtonStyle::Secondary,
+        ),
+        cancel_button(lang)?,
+    ]));
+
+    let embed = EmbedBuilder::new()
+        .color(COLOR_TRANSPARENT)
+        .title(lang.setup_title(1, STEPS))
+        .description(lang.setup_logs_description())
+        .build();
+
+    Ok((embed, components))
+}
+
+/// Captcha step.
+fn captcha_step(lang: Lang) -> Result<(Embed, Vec<Component>), anyhow::Error> {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_TRANSPARENT)
+        .title(lang.setup_title(2, STEPS))
+        .description(lang.setup_captcha_description())
+        .build();
+
+    let components = vec![action_row(vec![
+        button(
+            SetupId::Captcha(true).encode()?,
+            lang.setup_captcha_enable(),
+            ButtonStyle::Success,
+        ),
+        button(
+            SetupId::Captcha(false).encode()?,
+            lang.setup_skip(),
+            ButtonStyle::Secondary,
+        ),
+        cancel_button(lang)?,
+    ])];
+
+    Ok((embed, components))
+}
+
+/// Anti-spam profile step.
+fn antispam_step(lang: Lang) -> Result<(Embed, Vec<Component>), anyhow::Error> {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_TRANSPARENT)
+        .title(lang.setup_title(3, STEPS))
+        .description(lang.setup_antispam_description())
+        .build();
+
+    let options = PROFILES
+        .into_iter()
+        .map(|profile| SelectMenuOption {
+            default: false,
+            description: Some(profile_description(profile, lang).to_owned()),
+            emoji: None,
+            label: profile_name(profile, lang).to_owned(),
+            value: profile_value(profile).to_owned(),
+        })
+        .collect();
+
+    let components = vec![
+        action_row(vec![select_menu(
+            SetupId::AntiSpam.encode()?,
+            lang.setup_antispam_placeholder(),
+            options,
+        )]),
+        action_row(vec![cancel_button(lang)?]),
+    ];
+
+    Ok((embed, components))
+}
+
+/// Summary of the choices, with the confirmation button.
+fn summary_step(
+    pending: &PendingSetup,
+    config: &GuildConfig,
+    lang: Lang,
+) -> Result<(Embed, Vec<Component>), anyhow::Error> {
+    let logs = match pending.logs_chan.or(config.logs_chan) {
+        Some(channel) => channel.mention().to_string(),
+        None => lang.setup_summary_none().to_owned(),
+    };
+
+    let captcha = match (config.captcha.enabled, pending.captcha) {
+        (true, _) => lang.setup_summary_captcha_already(),
+        (false, true) => lang.setup_summary_captcha_enable(),
+        (false, false) => lang.setup_summary_none(),
+    };
+
+    let antispam = match pending.antispam {
+        Some(profile) => profile_name(profile, lang),
+        None => lang.setup_summary_none(),
+    };
+
+    let embed = EmbedBuilder::new()
+        .color(COLOR_TRANSPARENT)
+        .title(lang.setup_summary_title())
+        .description(lang.setup_summary_description())
+        .field(EmbedFieldBuilder::new(lang.setup_logs(), logs).inline())
+        .field(EmbedFieldBuilder::new(lang.setup_captcha(), captcha).inline())
+        .field(EmbedFieldBuilder::new(lang.setup_antispam(), antispam).inline())
+        .build();
+
+    let components = vec![action_row(vec![
+        button(
+            SetupId::Confirm.encode()?,
+            lang.setup_confirm(),
+            ButtonStyle::Success,
+        ),
+        cancel_button(lang)?,
+    ])];
+
+    Ok((embed, components))
+}
+
+/// Check whether the bot can send logs in a channel.
+async fn can_send_logs(
+    guild_id: Id<GuildMarker>,
+    channel: Id<ChannelMarker>,
+    state: &ClusterState,
+) -> Result<bool, anyhow::Error> {
+    let (permissions, _) = state
+        .cache
+        .permissions(guild_id)
+        .await?
+        .current_member()
+        .await?
+        .channel(channel)
+        .await?;
+
+    Ok(permissions.contains(Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS))
+}
+
+/// Value of an anti-spam profile in the select menu.
+fn profile_value(profile: AntiSpamProfile) -> &'static str {
+    match profile {
+        AntiSpamProfile::Disabled => "disabled",
+        AntiSpamProfile::Relaxed => "relaxed",
+        AntiSpamProfile::Standard => "standard",
+        AntiSpamProfile::Strict => "strict",
+    }
+}
+
+/// Name of an anti-spam profile.
+fn profile_name(profile: AntiSpamProfile, lang: Lang) -> &'static str {
+    match profile {
+        AntiSpamProfile::Disabled => lang.setup_antispam_disabled(),
+        AntiSpamProfile::Relaxed => lang.setup_antispam_relaxed(),
+        AntiSpamProfile::Standard => lang.setup_antispam_standard(),
+        AntiSpamProfile::Strict => lang.setup_antispam_strict(),
+    }
+}
+
+/// Description of an anti-spam profile.
+fn profile_description(profile: AntiSpamProfile, lang: Lang) -> &'static str {
+    match profile {
+        AntiSpamProfile::Disabled => lang.setup_antispam_disabled_description(),
+        AntiSpamProfile::Relaxed => lang.setup_antispam_relaxed_description(),
+        AntiSpamProfile::Standard => lang.setup_antispam_standard_description(),
+        AntiSpamProfile::Strict => lang.setup_antispam_strict_description(),
+    }
+}
+
+/// Update the wizard message.
+fn update_message(embed: Embed, components: Vec<Component>) -> InteractionResponse {
+    let response = InteractionResponseDataBuilder::new()
+        .embeds([embed])
+        .components(components)
+        .build();
+
+    InteractionResponse::Raw {
+        kind: InteractionResponseType::UpdateMessage,
+        data: Some(response),
+    }
+}
+
+fn action_row(components: Vec<Component>) -> Component {
+    Component::ActionRow(ActionRow { components })
+}
+
+fn button(custom_id: String, label: &str, style: ButtonStyle) -> Component {
+    Component::Button(Button {
+        custom_id: Some(custom_id),
+        disabled: false,
+        emoji: None,
+        label: Some(label.to_owned()),
+        style,
+        url: None,
+    })
+}
+
+fn cancel_button(lang: Lang) -> Result<Component, anyhow::Error> {
+    Ok(button(
+        SetupId::Cancel.encode()?,
+        lang.setup_cancel(),
+        ButtonStyle::Danger,
+    ))
+}
+
+fn select_menu(custom_id: String, placeholder: &str, options: Vec<SelectMenuOption>) -> Component {
+    Component::SelectMenu(SelectMenu {
+        custom_id,
+        disabled: false,
+        max_values: Some(1),
+        min_values: Some(1),
+        options,
+        placeholder: Some(placeholder.to_owned()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_setup_id() {
+        for id in [
+            SetupId::LogsChannel,
+            SetupId::SkipLogs,
+            SetupId::Captcha(true),
+            SetupId::Captcha(false),
+            SetupId::AntiSpam,
+            SetupId::Confirm,
+            SetupId::Cancel,
+        ] {
+            let custom_id = id.encode().unwrap().parse().unwrap();
+
+            assert_eq!(SetupId::decode(&custom_id).unwrap(), id);
+        }
+    }
+
+    #[test]
+    fn test_steps() {
+        captcha_step(Lang::DEFAULT).unwrap();
+        antispam_step(Lang::DEFAULT).unwrap();
+    }
+}
@@ -0,0 +1,61 @@
+//! Embeds for the anti-nuke configuration commands.
+use raidprotect_model::database::model::AntiNukeConfig;
+use twilight_util::builder::embed::EmbedBuilder;
+
+use super::{
+    error::{error_embed, ErrorKind},
+    COLOR_RED,
+};
+use crate::{interaction::response::InteractionResponse, translations::Lang, util::format_number};
+
+/// Anti-nuke already enabled on the server.
+pub fn already_enabled(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.antinuke_already_enabled())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Anti-nuke not enabled on the server.
+pub fn not_enabled(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::ConfigMissing, lang)
+        .description(lang.antinuke_not_enabled())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Member already trusted.
+pub fn trusted_already_added(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.antinuke_trusted_already_added())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Member not trusted.
+pub fn trusted_not_configured(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::ConfigMissing, lang)
+        .description(lang.antinuke_trusted_not_configured())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Too many trusted members.
+pub fn trusted_too_many(lang: Lang) -> InteractionResponse {
+    let embed =
+        EmbedBuilder::new()
+            .color(COLOR_RED)
+            .description(lang.antinuke_trusted_too_many(format_number(
+                AntiNukeConfig::MAX_TRUSTED_LEN as u64,
+                lang,
+            )))
+            .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
@@ -0,0 +1,105 @@
+//! Embeds for the anti-spam configuration commands.
+use raidprotect_model::database::model::AntiSpamConfig;
+use twilight_util::builder::embed::EmbedBuilder;
+
+use super::{
+    error::{error_embed, ErrorKind},
+    COLOR_RED,
+};
+use crate::{interaction::response::InteractionResponse, translations::Lang, util::format_number};
+
+/// Anti-spam already enabled on the server.
+pub fn already_enabled(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.antispam_already_enabled())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Anti-spam not enabled on the server.
+pub fn not_enabled(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::ConfigMissing, lang)
+        .description(lang.antispam_not_enabled())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// No role or channel specified for an exemption.
+pub fn exempt_missing(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.antispam_exempt_missing())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Role or channel already exempted.
+pub fn exempt_already_added(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.antispam_exempt_already_added())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Role or channel not exempted.
+pub fn exempt_not_configured(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::ConfigMissing, lang)
+        .description(lang.antispam_exempt_not_configured())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Too many roles or channels exempted.
+pub fn exempt_too_many(lang: Lang) -> InteractionResponse {
+    let embed =
+        EmbedBuilder::new()
+            .color(COLOR_RED)
+            .description(lang.antispam_exempt_too_many(format_number(
+                AntiSpamConfig::MAX_EXEMPT_LEN as u64,
+                lang,
+            )))
+            .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Missing permission to ban the accounts of a raid alert.
+pub fn raid_missing_permission(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::MissingPermission, lang)
+        .description(lang.antispam_raid_missing_permission())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Missing permission to revert an anti-spam punishment.
+pub fn false_positive_missing_permission(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::MissingPermission, lang)
+        .description(lang.antispam_false_positive_missing_permission())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Unknown spam detector.
+pub fn unknown_detector(detectors: Vec<&str>, lang: Lang) -> InteractionResponse {
+    let detectors = detectors
+        .into_iter()
+        .map(|id| format!("`{id}`"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.antispam_unknown_detector(detectors))
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
@@ -0,0 +1,56 @@
+//! Embeds for the API keys configuration commands.
+use raidprotect_model::database::model::ApiKey;
+use twilight_util::builder::embed::EmbedBuilder;
+
+use super::COLOR_RED;
+use crate::{interaction::response::InteractionResponse, translations::Lang, util::format_number};
+
+/// User is not the owner of the guild.
+pub fn not_guild_owner(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.api_keys_not_guild_owner())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Invalid key name.
+pub fn invalid_name(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.api_keys_invalid_name(format_number(ApiKey::MAX_NAME_LEN as u64, lang)))
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Key with the same name already exists.
+pub fn already_exists(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.api_keys_already_exists())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Too many keys.
+pub fn too_many(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.api_keys_too_many(format_number(ApiKey::MAX_LEN as u64, lang)))
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Key not found.
+pub fn not_found(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.api_keys_not_found())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
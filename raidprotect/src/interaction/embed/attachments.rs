@@ -0,0 +1,41 @@
+//! Embeds for the attachment policy configuration commands.
+use raidprotect_model::database::model::AttachmentPolicyConfig;
+use twilight_util::builder::embed::EmbedBuilder;
+
+use super::{
+    error::{error_embed, ErrorKind},
+    COLOR_RED,
+};
+use crate::{interaction::response::InteractionResponse, translations::Lang, util::format_number};
+
+/// Attachment policy already enabled on the server.
+pub fn already_enabled(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.attachments_already_enabled())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Attachment policy not enabled on the server.
+pub fn not_enabled(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::ConfigMissing, lang)
+        .description(lang.attachments_not_enabled())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Too many image-only channels or exempted roles.
+pub fn too_many(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.attachments_too_many(format_number(
+            AttachmentPolicyConfig::MAX_LIST_LEN as u64,
+            lang,
+        )))
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
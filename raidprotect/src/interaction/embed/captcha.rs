@@ -1,13 +1,16 @@
 //! Embeds for the captcha configuration commands.
+use raidprotect_model::database::model::CaptchaConfig;
 use twilight_util::builder::embed::EmbedBuilder;
 
-use super::COLOR_RED;
-use crate::{interaction::response::InteractionResponse, translations::Lang};
+use super::{
+    error::{error_embed, ErrorKind},
+    COLOR_RED,
+};
+use crate::{interaction::response::InteractionResponse, translations::Lang, util::format_number};
 
 /// Captcha not enabled on the server.
 pub fn not_enabled(lang: Lang) -> InteractionResponse {
-    let embed = EmbedBuilder::new()
-        .color(COLOR_RED)
+    let embed = error_embed(ErrorKind::ConfigMissing, lang)
         .title(lang.captcha_not_enabled_title())
         .description(lang.captcha_not_enabled_description())
         .build();
@@ -28,8 +31,7 @@ pub fn already_enabled(lang: Lang) -> InteractionResponse {
 
 /// Missing permission to send message in the logs channel.
 pub fn missing_logs_permission(lang: Lang) -> InteractionResponse {
-    let embed = EmbedBuilder::new()
-        .color(COLOR_RED)
+    let embed = error_embed(ErrorKind::BotMissingPermission, lang)
         .title(lang.captcha_missing_logs_permission_title())
         .description(lang.bot_missing_permission())
         .build();
@@ -37,10 +39,19 @@ pub fn missing_logs_permission(lang: Lang) -> InteractionResponse {
     InteractionResponse::EphemeralEmbed(embed)
 }
 
+/// Missing permission to send message in the questionnaire review channel.
+pub fn missing_review_permission(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::BotMissingPermission, lang)
+        .title(lang.captcha_missing_review_permission_title())
+        .description(lang.bot_missing_permission())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
 /// Missing permission to give a role to new members.
 pub fn missing_role_permission(lang: Lang) -> InteractionResponse {
-    let embed = EmbedBuilder::new()
-        .color(COLOR_RED)
+    let embed = error_embed(ErrorKind::BotMissingPermission, lang)
         .title(lang.captcha_missing_role_permission_title())
         .description(lang.bot_missing_permission())
         .build();
@@ -50,8 +61,7 @@ pub fn missing_role_permission(lang: Lang) -> InteractionResponse {
 
 /// Missing permissions to enable the captcha.
 pub fn missing_enable_permission(lang: Lang) -> InteractionResponse {
-    let embed = EmbedBuilder::new()
-        .color(COLOR_RED)
+    let embed = error_embed(ErrorKind::BotMissingPermission, lang)
         .title(lang.captcha_missing_enable_permission_title())
         .description(lang.bot_missing_permission())
         .build();
@@ -61,8 +71,7 @@ pub fn missing_enable_permission(lang: Lang) -> InteractionResponse {
 
 /// Missing permission to give a role due to the role hierarchy.
 pub fn role_hierarchy(lang: Lang) -> InteractionResponse {
-    let embed = EmbedBuilder::new()
-        .color(COLOR_RED)
+    let embed = error_embed(ErrorKind::Hierarchy, lang)
         .title(lang.captcha_missing_role_permission_title())
         .description(lang.hierarchy_bot_role())
         .build();
@@ -84,7 +93,10 @@ pub fn role_already_added(lang: Lang) -> InteractionResponse {
 pub fn role_too_many(lang: Lang) -> InteractionResponse {
     let embed = EmbedBuilder::new()
         .color(COLOR_RED)
-        .description(lang.captcha_role_too_many())
+        .description(lang.captcha_role_too_many(format_number(
+            CaptchaConfig::MAX_VERIFIED_ROLES_LEN as u64,
+            lang,
+        )))
         .build();
 
     InteractionResponse::EphemeralEmbed(embed)
@@ -92,8 +104,7 @@ pub fn role_too_many(lang: Lang) -> InteractionResponse {
 
 /// Role not configured as a verified role.
 pub fn role_not_configured(lang: Lang) -> InteractionResponse {
-    let embed = EmbedBuilder::new()
-        .color(COLOR_RED)
+    let embed = error_embed(ErrorKind::ConfigMissing, lang)
         .description(lang.captcha_role_not_configured())
         .build();
 
@@ -142,6 +153,17 @@ pub fn captcha_not_found(lang: Lang) -> InteractionResponse {
     InteractionResponse::EphemeralEmbed(embed)
 }
 
+/// Too many captcha images are being generated.
+pub fn render_busy(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .title(lang.captcha_error_title())
+        .color(COLOR_RED)
+        .description(lang.captcha_render_busy_description())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
 /// Entered code is invalid.
 pub fn captcha_invalid_code(lang: Lang) -> InteractionResponse {
     let embed = EmbedBuilder::new()
@@ -152,3 +174,34 @@ pub fn captcha_invalid_code(lang: Lang) -> InteractionResponse {
 
     InteractionResponse::EphemeralEmbed(embed)
 }
+
+/// Missing permission to review a questionnaire.
+pub fn review_missing_permission(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::MissingPermission, lang)
+        .description(lang.captcha_review_missing_permission())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// No verified roles configured for the migration.
+pub fn migrate_no_roles(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .title(lang.captcha_migrate_no_roles_title())
+        .description(lang.captcha_migrate_no_roles_description())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// A migration is already running on the server.
+pub fn migrate_running(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .title(lang.captcha_migrate_running_title())
+        .description(lang.captcha_migrate_running_description())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
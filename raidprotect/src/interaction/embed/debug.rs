@@ -0,0 +1,25 @@
+//! Embeds for the debug command.
+use twilight_util::builder::embed::EmbedBuilder;
+
+use super::COLOR_RED;
+use crate::{interaction::response::InteractionResponse, translations::Lang};
+
+/// Invalid guild id.
+pub fn invalid_guild(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.debug_tap_invalid_guild())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// No event tap running.
+pub fn tap_not_running(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.debug_tap_not_running())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
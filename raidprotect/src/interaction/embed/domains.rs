@@ -0,0 +1,70 @@
+//! Embeds for the domain filter configuration commands.
+use raidprotect_model::database::model::DomainFilterConfig;
+use twilight_util::builder::embed::EmbedBuilder;
+
+use super::{
+    error::{error_embed, ErrorKind},
+    COLOR_RED,
+};
+use crate::{interaction::response::InteractionResponse, translations::Lang, util::format_number};
+
+/// Domain filter already enabled on the server.
+pub fn already_enabled(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.domains_already_enabled())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Domain filter not enabled on the server.
+pub fn not_enabled(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::ConfigMissing, lang)
+        .description(lang.domains_not_enabled())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Invalid domain.
+pub fn invalid_domain(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.domains_invalid_domain())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Domain already in the list.
+pub fn already_added(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.domains_already_added())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Domain not in the list.
+pub fn not_configured(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::ConfigMissing, lang)
+        .description(lang.domains_not_configured())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Too many domains in the list.
+pub fn too_many(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.domains_too_many(format_number(
+            DomainFilterConfig::MAX_DOMAINS_LEN as u64,
+            lang,
+        )))
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
@@ -0,0 +1,15 @@
+//! Embeds for the entitlements command.
+use twilight_util::builder::embed::EmbedBuilder;
+
+use super::COLOR_RED;
+use crate::{interaction::response::InteractionResponse, translations::Lang};
+
+/// Invalid guild id, or guild without the bot.
+pub fn invalid_guild(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.entitlements_invalid_guild())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
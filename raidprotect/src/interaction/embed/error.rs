@@ -1,19 +1,146 @@
 //! Error embeds.
+//!
+//! Errors shown to the users are classified with an [`ErrorKind`]. Each kind
+//! has a stable short code shown in the footer of the embed, which is also
+//! recorded in the `error_code` field of the interaction span. This allows the
+//! support team to find the logs of an error from a screenshot.
 
-use twilight_util::builder::embed::{EmbedBuilder, EmbedFooterBuilder};
+use tracing::{debug, Span};
+use twilight_model::{
+    application::component::{button::ButtonStyle, ActionRow, Button, Component},
+    channel::{message::MessageFlags, ReactionType},
+    http::interaction::InteractionResponseType,
+    id::{marker::InteractionMarker, Id},
+};
+use twilight_util::builder::{
+    embed::{EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder},
+    InteractionResponseDataBuilder,
+};
 
 use super::COLOR_RED;
-use crate::{interaction::response::InteractionResponse, translations::Lang};
+use crate::{
+    interaction::{
+        modal::{ModalFieldError, ModalFieldErrorKind},
+        response::InteractionResponse,
+        util::CustomId,
+    },
+    translations::Lang,
+};
+
+/// Kind of error shown to the users.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The user is missing a permission.
+    MissingPermission,
+    /// The bot is missing a permission.
+    BotMissingPermission,
+    /// The action is prevented by the role hierarchy.
+    Hierarchy,
+    /// Unexpected error while processing the interaction.
+    Internal,
+    /// The command has been used too frequently.
+    RateLimited,
+    /// The feature is not enabled or configured in the guild.
+    ConfigMissing,
+    /// The feature is disabled by a kill switch.
+    Maintenance,
+    /// The feature requires premium entitlements.
+    PremiumRequired,
+    /// The command or component is unknown.
+    UnknownCommand,
+    /// The interaction has expired.
+    Expired,
+    /// A value entered by the user is invalid.
+    InvalidInput,
+}
+
+impl ErrorKind {
+    /// Short code of the error.
+    ///
+    /// Codes are stable, and must not be changed once released.
+    pub fn code(self) -> &'static str {
+        match self {
+            ErrorKind::MissingPermission => "E-PERM",
+            ErrorKind::BotMissingPermission => "E-BOTPERM",
+            ErrorKind::Hierarchy => "E-HIER",
+            ErrorKind::Internal => "E-INT",
+            ErrorKind::RateLimited => "E-RATE",
+            ErrorKind::ConfigMissing => "E-CONF",
+            ErrorKind::Maintenance => "E-MAINT",
+            ErrorKind::PremiumRequired => "E-PREM",
+            ErrorKind::UnknownCommand => "E-UNKNOWN",
+            ErrorKind::Expired => "E-EXPIRED",
+            ErrorKind::InvalidInput => "E-INPUT",
+        }
+    }
+}
+
+/// Initialize an error embed of the given kind.
+///
+/// The embed has the error color and the error code in its footer. The code is
+/// recorded in the current span.
+pub fn error_embed(kind: ErrorKind, lang: Lang) -> EmbedBuilder {
+    let code = kind.code();
+
+    Span::current().record("error_code", code);
+    debug!(error_code = code, "responding with error");
+
+    EmbedBuilder::new()
+        .color(COLOR_RED)
+        .footer(EmbedFooterBuilder::new(lang.error_code(code)))
+}
 
 /// Internal error embed
-pub fn internal_error(lang: Lang) -> InteractionResponse {
-    let embed = EmbedBuilder::new()
+///
+/// If a `reference` is provided, it is shown in the embed along with a "Get
+/// help" button that opens a thread in the support server.
+pub fn internal_error(lang: Lang, reference: Option<Id<InteractionMarker>>) -> InteractionResponse {
+    let mut embed = error_embed(ErrorKind::Internal, lang)
         .title(lang.internal_error_title())
+        .description(lang.internal_error_description());
+
+    let reference = match reference {
+        Some(reference) => reference,
+        None => return InteractionResponse::EphemeralEmbed(embed.build()),
+    };
+
+    embed = embed.field(EmbedFieldBuilder::new(
+        lang.internal_error_reference(),
+        format!("`{reference}`"),
+    ));
+
+    let custom_id = CustomId::new("error-help", reference.to_string());
+    let components = Component::ActionRow(ActionRow {
+        components: vec![Component::Button(Button {
+            custom_id: Some(custom_id.to_string()),
+            disabled: false,
+            emoji: Some(ReactionType::Unicode {
+                name: "🛟".to_owned(),
+            }),
+            label: Some(lang.internal_error_help_button().to_owned()),
+            style: ButtonStyle::Secondary,
+            url: None,
+        })],
+    });
+
+    let response = InteractionResponseDataBuilder::new()
+        .embeds([embed.build()])
+        .components([components])
+        .flags(MessageFlags::EPHEMERAL)
+        .build();
+
+    InteractionResponse::Raw {
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(response),
+    }
+}
+
+/// Link to the help thread created with the "Get help" button.
+pub fn help_thread(lang: Lang, thread_url: &str) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .title(lang.internal_error_help_title())
         .color(COLOR_RED)
-        .description(lang.internal_error_description())
-        .footer(EmbedFooterBuilder::new(
-            "Okay, Houston, I believe we've had a problem here ...", // No translation here
-        ))
+        .description(lang.internal_error_help_description(thread_url))
         .build();
 
     InteractionResponse::EphemeralEmbed(embed)
@@ -21,9 +148,8 @@ pub fn internal_error(lang: Lang) -> InteractionResponse {
 
 /// Unknown command received
 pub fn unknown_command(lang: Lang) -> InteractionResponse {
-    let embed = EmbedBuilder::new()
+    let embed = error_embed(ErrorKind::UnknownCommand, lang)
         .title(lang.unknown_command_title())
-        .color(COLOR_RED)
         .description(lang.unknown_command_description())
         .build();
 
@@ -31,22 +157,93 @@ pub fn unknown_command(lang: Lang) -> InteractionResponse {
 }
 
 pub fn expired_interaction(lang: Lang) -> InteractionResponse {
-    let embed = EmbedBuilder::new()
+    let embed = error_embed(ErrorKind::Expired, lang)
         .title(lang.expired_interaction_title())
-        .color(COLOR_RED)
         .description(lang.expired_interaction_description())
         .build();
 
     InteractionResponse::EphemeralEmbed(embed)
 }
 
+/// Command disabled by a kill switch.
+pub fn maintenance(lang: Lang, reason: Option<&str>) -> InteractionResponse {
+    let mut embed = error_embed(ErrorKind::Maintenance, lang)
+        .title(lang.maintenance_title())
+        .description(lang.maintenance_description());
+
+    if let Some(reason) = reason {
+        embed = embed.field(EmbedFieldBuilder::new(lang.maintenance_reason(), reason));
+    }
+
+    InteractionResponse::EphemeralEmbed(embed.build())
+}
+
+/// Command used again before the end of its cooldown.
+pub fn cooldown(lang: Lang, remaining: u64) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::RateLimited, lang)
+        .description(lang.cooldown(remaining))
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// User is not a bot owner.
+pub fn not_owner(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::MissingPermission, lang)
+        .description(lang.not_owner())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Feature restricted to guilds with premium entitlements.
+pub fn premium_required(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::PremiumRequired, lang)
+        .description(lang.premium_required())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Invalid value submitted in a modal form.
+pub fn invalid_field(lang: Lang, error: &ModalFieldError) -> InteractionResponse {
+    let field = &error.label;
+    let description = match error.kind {
+        ModalFieldErrorKind::Missing => lang.modal_field_missing(field),
+        ModalFieldErrorKind::TooShort(min) => lang.modal_field_too_short(field, min),
+        ModalFieldErrorKind::TooLong(max) => lang.modal_field_too_long(field, max),
+        ModalFieldErrorKind::Invalid => lang.modal_field_invalid(field),
+    };
+
+    let embed = error_embed(ErrorKind::InvalidInput, lang)
+        .title(lang.modal_field_error_title())
+        .description(description)
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_error_embed() {
+        let embed = error_embed(ErrorKind::Hierarchy, Lang::En).build();
+
+        assert_eq!(embed.color, Some(COLOR_RED));
+        assert_eq!(embed.footer.unwrap().text, "Error code: E-HIER");
+    }
+
     #[test]
     fn test_internal_error() {
-        internal_error(Lang::DEFAULT);
+        internal_error(Lang::DEFAULT, None);
+        internal_error(Lang::DEFAULT, Some(Id::new(1)));
+    }
+
+    #[test]
+    fn test_help_thread() {
+        help_thread(Lang::DEFAULT, "https://discord.com/channels/1/2");
     }
 
     #[test]
@@ -58,4 +255,30 @@ mod tests {
     fn test_expired_component() {
         expired_interaction(Lang::DEFAULT);
     }
+
+    #[test]
+    fn test_maintenance() {
+        maintenance(Lang::DEFAULT, None);
+        maintenance(Lang::DEFAULT, Some("reason"));
+    }
+
+    #[test]
+    fn test_cooldown() {
+        cooldown(Lang::DEFAULT, 5);
+    }
+
+    #[test]
+    fn test_not_owner() {
+        not_owner(Lang::DEFAULT);
+    }
+
+    #[test]
+    fn test_invalid_field() {
+        let error = ModalFieldError {
+            label: "Reason".to_owned(),
+            kind: ModalFieldErrorKind::TooLong(100),
+        };
+
+        invalid_field(Lang::DEFAULT, &error);
+    }
 }
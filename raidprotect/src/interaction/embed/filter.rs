@@ -0,0 +1,69 @@
+//! Embeds for the word filter configuration commands.
+use raidprotect_anti_spam::filter::FilterError;
+use raidprotect_model::database::model::WordFilterConfig;
+use twilight_util::builder::embed::EmbedBuilder;
+
+use super::{
+    error::{error_embed, ErrorKind},
+    COLOR_RED,
+};
+use crate::{interaction::response::InteractionResponse, translations::Lang, util::format_number};
+
+/// Invalid rule.
+pub fn invalid_rule(lang: Lang, error: &FilterError) -> InteractionResponse {
+    let description = match error {
+        FilterError::Empty => lang.filter_invalid_empty().to_owned(),
+        FilterError::Regex(error) => lang.filter_invalid_regex(error),
+    };
+
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(description)
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Rule pattern too long.
+pub fn rule_too_long(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.filter_rule_too_long(format_number(
+            WordFilterConfig::MAX_PATTERN_LEN as u64,
+            lang,
+        )))
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Rule already configured.
+pub fn already_added(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.filter_already_added())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Rule not configured.
+pub fn not_configured(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::ConfigMissing, lang)
+        .description(lang.filter_not_configured())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Too many rules.
+pub fn too_many(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(
+            lang.filter_too_many(format_number(WordFilterConfig::MAX_RULES_LEN as u64, lang)),
+        )
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
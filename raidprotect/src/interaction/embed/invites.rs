@@ -0,0 +1,70 @@
+//! Embeds for the invite filter configuration commands.
+use raidprotect_model::database::model::InviteFilterConfig;
+use twilight_util::builder::embed::EmbedBuilder;
+
+use super::{
+    error::{error_embed, ErrorKind},
+    COLOR_RED,
+};
+use crate::{interaction::response::InteractionResponse, translations::Lang, util::format_number};
+
+/// Invite filter already enabled on the server.
+pub fn already_enabled(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.invites_already_enabled())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Invite filter not enabled on the server.
+pub fn not_enabled(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::ConfigMissing, lang)
+        .description(lang.invites_not_enabled())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Invalid server id.
+pub fn invalid_guild(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.invites_invalid_guild())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Server already allowed.
+pub fn allowed_already_added(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.invites_allowed_already_added())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Server not allowed.
+pub fn allowed_not_configured(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::ConfigMissing, lang)
+        .description(lang.invites_allowed_not_configured())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Too many allowed servers.
+pub fn allowed_too_many(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.invites_allowed_too_many(format_number(
+            InviteFilterConfig::MAX_ALLOWED_GUILDS_LEN as u64,
+            lang,
+        )))
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
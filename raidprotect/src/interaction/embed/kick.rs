@@ -2,7 +2,10 @@
 
 use twilight_util::builder::embed::EmbedBuilder;
 
-use super::COLOR_RED;
+use super::{
+    error::{error_embed, ErrorKind},
+    COLOR_RED,
+};
 use crate::{interaction::response::InteractionResponse, translations::Lang, util::TextProcessExt};
 
 /// User is not a server member.
@@ -17,8 +20,7 @@ pub fn not_member(user: String, lang: Lang) -> InteractionResponse {
 
 /// Bot is missing the `KICK_MEMBERS` permission
 pub fn bot_missing_permission(lang: Lang) -> InteractionResponse {
-    let embed = EmbedBuilder::new()
-        .color(COLOR_RED)
+    let embed = error_embed(ErrorKind::BotMissingPermission, lang)
         .title(lang.kick_bot_missing_permission_title())
         .description(lang.bot_missing_permission())
         .build();
@@ -28,8 +30,7 @@ pub fn bot_missing_permission(lang: Lang) -> InteractionResponse {
 
 /// User cannot kick due to the role hierarchy
 pub fn user_hierarchy(lang: Lang) -> InteractionResponse {
-    let embed = EmbedBuilder::new()
-        .color(COLOR_RED)
+    let embed = error_embed(ErrorKind::Hierarchy, lang)
         .title(lang.kick_missing_permission_title())
         .description(lang.hierarchy_user())
         .build();
@@ -39,8 +40,7 @@ pub fn user_hierarchy(lang: Lang) -> InteractionResponse {
 
 /// Bot cannot kick due to the role hierarchy
 pub fn bot_hierarchy(lang: Lang) -> InteractionResponse {
-    let embed = EmbedBuilder::new()
-        .color(COLOR_RED)
+    let embed = error_embed(ErrorKind::Hierarchy, lang)
         .title(lang.kick_bot_missing_permission_title())
         .description(lang.hierarchy_bot())
         .build();
@@ -50,8 +50,7 @@ pub fn bot_hierarchy(lang: Lang) -> InteractionResponse {
 
 /// Member is the guild owner, and thus cannot be kicked
 pub fn member_owner(lang: Lang) -> InteractionResponse {
-    let embed = EmbedBuilder::new()
-        .color(COLOR_RED)
+    let embed = error_embed(ErrorKind::Hierarchy, lang)
         .title(lang.kick_missing_permission_title())
         .description(lang.hierarchy_owner())
         .build();
@@ -0,0 +1,44 @@
+//! Embeds for the kill switch command.
+use twilight_util::builder::embed::EmbedBuilder;
+
+use super::COLOR_RED;
+use crate::{
+    feature::kill_switch::Automation, interaction::response::InteractionResponse,
+    translations::Lang,
+};
+
+/// Invalid command or automation name.
+pub fn invalid_target(lang: Lang) -> InteractionResponse {
+    let automations = Automation::ALL
+        .iter()
+        .map(|automation| format!("`{}`", automation.name()))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.kill_switch_invalid_target(automations))
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Kill switch already enabled.
+pub fn already_enabled(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.kill_switch_already_enabled())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Kill switch not enabled.
+pub fn not_enabled(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.kill_switch_not_enabled())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
@@ -0,0 +1,37 @@
+//! Embeds for the logged events configuration commands.
+use twilight_util::builder::embed::EmbedBuilder;
+
+use super::{
+    error::{error_embed, ErrorKind},
+    COLOR_RED,
+};
+use crate::{interaction::response::InteractionResponse, translations::Lang};
+
+/// Event already logged on the server.
+pub fn already_enabled(lang: Lang, event: &str) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.logs_already_enabled(event))
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Event not logged on the server.
+pub fn not_enabled(lang: Lang, event: &str) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::ConfigMissing, lang)
+        .description(lang.logs_not_enabled(event))
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Missing permission to send messages in the logs channel.
+pub fn missing_permission(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::BotMissingPermission, lang)
+        .title(lang.logs_missing_permission_title())
+        .description(lang.bot_missing_permission())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
@@ -2,9 +2,27 @@
 //!
 //! This crate contains types used to generate embeds used as bot responses.
 
+pub mod antinuke;
+pub mod antispam;
+pub mod api_keys;
+pub mod attachments;
 pub mod captcha;
+pub mod debug;
+pub mod domains;
+pub mod entitlements;
 pub mod error;
+pub mod filter;
+pub mod invites;
 pub mod kick;
+pub mod kill_switch;
+pub mod logs;
+pub mod notifications;
+pub mod phishing;
+pub mod privacy;
+pub mod quarantine;
+pub mod security_alert;
+pub mod stats;
+pub mod webhooks;
 
 /// RaidProtect's red color.
 pub const COLOR_RED: u32 = 0xd35f5f;
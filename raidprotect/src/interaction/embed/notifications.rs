@@ -0,0 +1,27 @@
+//! Embeds for the outgoing webhook notifications configuration commands.
+use twilight_util::builder::embed::EmbedBuilder;
+
+use super::{
+    error::{error_embed, ErrorKind},
+    COLOR_RED,
+};
+use crate::{interaction::response::InteractionResponse, translations::Lang};
+
+/// Invalid webhook URL.
+pub fn invalid_url(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.notifications_invalid_url())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// No webhook configured.
+pub fn not_configured(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::ConfigMissing, lang)
+        .description(lang.notifications_not_configured())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
@@ -0,0 +1,15 @@
+//! Embeds for the privacy command.
+use twilight_util::builder::embed::EmbedBuilder;
+
+use super::COLOR_RED;
+use crate::{interaction::response::InteractionResponse, translations::Lang};
+
+/// Invalid user id.
+pub fn invalid_user(lang: Lang) -> InteractionResponse {
+    let embed = EmbedBuilder::new()
+        .color(COLOR_RED)
+        .description(lang.privacy_invalid_user())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
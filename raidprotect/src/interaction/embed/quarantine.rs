@@ -0,0 +1,39 @@
+//! Embeds for the quarantine.
+use super::error::{error_embed, ErrorKind};
+use crate::{interaction::response::InteractionResponse, translations::Lang};
+
+/// Quarantine not enabled on the server.
+pub fn not_enabled(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::ConfigMissing, lang)
+        .description(lang.quarantine_not_enabled())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Missing permission to give the quarantine role.
+pub fn missing_role_permission(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::BotMissingPermission, lang)
+        .description(lang.quarantine_missing_role_permission())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Quarantine role higher than the bot's highest role.
+pub fn role_hierarchy(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::Hierarchy, lang)
+        .description(lang.quarantine_role_hierarchy())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
+
+/// Missing permission to release or ban a quarantined member.
+pub fn missing_permission(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::MissingPermission, lang)
+        .description(lang.quarantine_missing_permission())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
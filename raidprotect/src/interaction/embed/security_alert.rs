@@ -0,0 +1,12 @@
+//! Embeds for the security alerts.
+use super::error::{error_embed, ErrorKind};
+use crate::{interaction::response::InteractionResponse, translations::Lang};
+
+/// Missing permission to use the security alert buttons.
+pub fn missing_permission(lang: Lang) -> InteractionResponse {
+    let embed = error_embed(ErrorKind::MissingPermission, lang)
+        .description(lang.security_missing_permission())
+        .build();
+
+    InteractionResponse::EphemeralEmbed(embed)
+}
@@ -1,55 +1,109 @@
-use std::str::FromStr;
+use std::{collections::BTreeMap, str::FromStr};
 
 use anyhow::bail;
-use tracing::{debug, error, warn};
+use once_cell::sync::Lazy;
+use raidprotect_model::{cache::model::interaction::RegisteredCommands, config::BotConfig};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::{debug, error, field, info_span, warn, Instrument};
 use twilight_interactions::command::CreateCommand;
 use twilight_model::{
     application::{
         command::Command,
         interaction::{Interaction, InteractionData, InteractionType},
     },
-    id::{marker::ApplicationMarker, Id},
+    id::{
+        marker::{ApplicationMarker, GuildMarker},
+        Id,
+    },
 };
 
 use super::{
+    autocomplete::AutocompleteContext,
     command::{
-        config::ConfigCommand, help::HelpCommand, moderation::KickCommand, profile::ProfileCommand,
+        config::ConfigCommand, debug::DebugCommand, entitlements::EntitlementsCommand,
+        help::HelpCommand, kill_switch::KillSwitchCommand, moderation::KickCommand,
+        modlogs::ModlogsCommand, privacy::PrivacyCommand, profile::ProfileCommand,
+        setup::SetupCommand,
+    },
+    component::{
+        captcha::*, ErrorHelpButton, FalsePositiveButton, PostInChat, QuarantineButton,
+        RaidBanButton, SanctionModal, SecurityAlertButton, SetupComponent,
     },
-    component::{captcha::*, PostInChat},
     embed,
+    middleware::{
+        Cooldown, ErrorReport, GuildCache, InteractionRequest, KillSwitch, Metrics, OwnerOnly,
+        Pipeline,
+    },
     response::{InteractionResponder, InteractionResponse},
     util::{CustomId, InteractionExt},
 };
-use crate::{cluster::ClusterState, translations::Lang};
+use crate::cluster::ClusterState;
+
+/// Commands restricted to the bot owners.
+///
+/// These commands are only registered in the administration guild.
+const OWNER_COMMANDS: &[&str] = &["debug", "entitlements", "killswitch", "privacy"];
+
+/// Middlewares run around the interaction handlers.
+static PIPELINE: Lazy<Pipeline> = Lazy::new(|| {
+    Pipeline::new()
+        .with(ErrorReport)
+        .with(GuildCache)
+        .with(Metrics)
+        .with(KillSwitch)
+        .with(OwnerOnly::new(OWNER_COMMANDS))
+        .with(Cooldown)
+});
 
 /// Handle incoming [`Interaction`].
 pub async fn handle_interaction(interaction: Interaction, state: &ClusterState) {
     let responder = InteractionResponder::from_interaction(&interaction);
     debug!(id = ?interaction.id, "received {} interaction", interaction.kind.kind());
 
-    let lang = interaction.lang().unwrap_or(Lang::DEFAULT);
+    let kind = interaction.kind;
+    if !matches!(
+        kind,
+        InteractionType::ApplicationCommand
+            | InteractionType::ApplicationCommandAutocomplete
+            | InteractionType::MessageComponent
+            | InteractionType::ModalSubmit
+    ) {
+        warn!("received unexpected {} interaction", kind.kind());
 
-    let response = match interaction.kind {
-        InteractionType::ApplicationCommand => handle_command(interaction, state).await,
-        InteractionType::MessageComponent => handle_component(interaction, state).await,
-        InteractionType::ModalSubmit => handle_modal(interaction, state).await,
-        other => {
-            warn!("received unexpected {} interaction", other.kind());
+        return;
+    }
 
-            return;
-        }
-    };
+    let request = InteractionRequest::new(interaction);
+    let span = info_span!(
+        "interaction",
+        id = %request.interaction.id,
+        name = %request.name,
+        user = ?request.interaction.author_id(),
+        error_code = field::Empty,
+    );
 
-    match response {
-        Ok(response) => responder.respond(state, response).await,
-        Err(error) => {
-            error!(error = ?error, "error while processing interaction");
+    async {
+        let response = PIPELINE
+            .run(&request, state, |interaction| async move {
+                match kind {
+                    InteractionType::ApplicationCommand => handle_command(interaction, state).await,
+                    InteractionType::ApplicationCommandAutocomplete => {
+                        handle_autocomplete(interaction, state).await
+                    }
+                    InteractionType::MessageComponent => handle_component(interaction, state).await,
+                    _ => handle_modal(interaction, state).await,
+                }
+            })
+            .await;
 
-            responder
-                .respond(state, embed::error::internal_error(lang))
-                .await;
+        match response {
+            Ok(response) => responder.respond(state, response).await,
+            Err(error) => error!(error = ?error, "unhandled error while processing interaction"),
         }
     }
+    .instrument(span)
+    .await
 }
 
 /// Handle incoming command interaction.
@@ -64,9 +118,15 @@ async fn handle_command(
 
     match name {
         "config" => ConfigCommand::handle(interaction, state).await,
+        "debug" => DebugCommand::handle(interaction, state).await,
+        "entitlements" => EntitlementsCommand::handle(interaction, state).await,
         "help" => HelpCommand::handle(interaction, state).await,
         "kick" => KickCommand::handle(interaction, state).await,
+        "killswitch" => KillSwitchCommand::handle(interaction, state).await,
+        "modlogs" => ModlogsCommand::handle(interaction, state).await,
+        "privacy" => PrivacyCommand::handle(interaction, state).await,
         "profile" => ProfileCommand::handle(interaction, state).await,
+        "setup" => SetupCommand::handle(interaction, state).await,
         name => {
             warn!(name = name, "received unknown command");
 
@@ -75,6 +135,32 @@ async fn handle_command(
     }
 }
 
+/// Handle incoming autocomplete interaction.
+///
+/// Autocomplete handlers are dispatched by command and focused option name.
+/// Unknown options are responded with an empty list of suggestions.
+async fn handle_autocomplete(
+    interaction: Interaction,
+    state: &ClusterState,
+) -> Result<InteractionResponse, anyhow::Error> {
+    let ctx = AutocompleteContext::from_interaction(&interaction)?;
+
+    let choices = match (&*ctx.command, &*ctx.option) {
+        ("killswitch", "name") => KillSwitchCommand::autocomplete_name(&ctx, state).await?,
+        (command, option) => {
+            warn!(
+                command = command,
+                option = option,
+                "received unknown autocomplete"
+            );
+
+            Vec::new()
+        }
+    };
+
+    Ok(InteractionResponse::Autocomplete(choices))
+}
+
 /// Handle incoming component interaction
 async fn handle_component(
     interaction: Interaction,
@@ -86,11 +172,29 @@ async fn handle_component(
     };
 
     match &*custom_id.name {
+        "antispam-false-positive" => {
+            FalsePositiveButton::handle(interaction, custom_id, state).await
+        }
+        "captcha-approve" | "captcha-deny" => {
+            CaptchaReviewButton::handle(interaction, custom_id, state).await
+        }
         "captcha-disable" => CaptchaDisable::handle(interaction, state).await,
         "captcha-enable" => CaptchaEnable::handle(interaction, state).await,
+        "captcha-migrate" => CaptchaMigrate::handle(interaction, state).await,
+        "captcha-questionnaire" => CaptchaQuestionnaireButton::handle(interaction, state).await,
         "captcha-validate" => CaptchaValidateButton::handle(interaction, state).await,
         "captcha-verify" => CaptchaVerifyButton::handle(interaction, state).await,
+        "error-help" => ErrorHelpButton::handle(interaction, custom_id, state).await,
         "post-in-chat" => PostInChat::handle(interaction, custom_id, state).await,
+        "quarantine-ban" | "quarantine-release" => {
+            QuarantineButton::handle(interaction, custom_id, state).await
+        }
+        "raid-ban" => RaidBanButton::handle(interaction, custom_id, state).await,
+        "security-kick-bot" | "security-revert-role" => {
+            SecurityAlertButton::handle(interaction, custom_id, state).await
+        }
+        "setup-logs" | "setup-logs-skip" | "setup-captcha" | "setup-antispam" | "setup-confirm"
+        | "setup-cancel" => SetupComponent::handle(interaction, custom_id, state).await,
         name => {
             warn!(name = name, "received unknown component");
 
@@ -111,7 +215,13 @@ async fn handle_modal(
 
     match &*custom_id.name {
         "captcha-modal" => CaptchaModal::handle(interaction, state).await,
-        // "sanction" => bail!("not implemented"),
+        "captcha-questionnaire-config" => {
+            CaptchaQuestionnaireConfigModal::handle(interaction, custom_id, state).await
+        }
+        "captcha-questionnaire-modal" => {
+            CaptchaQuestionnaireModal::handle(interaction, state).await
+        }
+        "sanction" => SanctionModal::handle(interaction, custom_id, state).await,
         name => {
             warn!(name = name, "received unknown modal");
 
@@ -121,17 +231,224 @@ async fn handle_modal(
 }
 
 /// Register commands to the Discord API.
-pub async fn register_commands(state: &ClusterState, application_id: Id<ApplicationMarker>) {
-    let commands: Vec<Command> = vec![
+///
+/// Administration commands are only registered in the `admin_guild`, and beta
+/// commands only in the `beta_guilds`, if set. The commands of a scope are
+/// only registered when they changed since the last registration (see
+/// [`RegisteredCommands`]), and are removed from the guilds that are no longer
+/// configured.
+pub async fn register_commands(
+    state: &ClusterState,
+    application_id: Id<ApplicationMarker>,
+    config: &BotConfig,
+) {
+    let scopes = command_scopes(
+        config.admin_guild,
+        &config.beta_guilds,
+        &config.beta_commands,
+    );
+    let client = state.http.interaction(application_id);
+
+    let registered = match state
+        .cache
+        .scan::<RegisteredCommands>(RegisteredCommands::KEY_PATTERN)
+        .await
+    {
+        Ok(registered) => registered,
+        Err(error) => {
+            error!(error = ?error, "failed to get registered commands");
+            Vec::new()
+        }
+    };
+
+    for stale in &registered {
+        if scopes.keys().any(|scope| scope.name() == stale.scope) {
+            continue;
+        }
+
+        let guild_id = match stale.scope.parse() {
+            Ok(guild_id) => guild_id,
+            Err(_) => continue,
+        };
+
+        if let Err(error) = client.set_guild_commands(guild_id, &[]).exec().await {
+            error!(error = ?error, guild = ?guild_id, "failed to remove guild commands");
+        } else if let Err(error) = state.cache.delete(stale).await {
+            error!(error = ?error, guild = ?guild_id, "failed to delete registered commands");
+        }
+    }
+
+    for (scope, commands) in scopes {
+        let registration = RegisteredCommands {
+            scope: scope.name(),
+            hash: commands_hash(&commands),
+        };
+
+        if registered.contains(&registration) {
+            debug!(scope = %registration.scope, "commands are up to date");
+            continue;
+        }
+
+        let result = match scope {
+            CommandScope::Global => client.set_global_commands(&commands).exec().await,
+            CommandScope::Guild(guild_id) => {
+                client.set_guild_commands(guild_id, &commands).exec().await
+            }
+        };
+
+        if let Err(error) = result {
+            error!(error = ?error, scope = %registration.scope, "failed to register commands");
+        } else if let Err(error) = state.cache.set(&registration).await {
+            error!(error = ?error, "failed to store registered commands");
+        }
+    }
+}
+
+/// Scope in which commands are registered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum CommandScope {
+    Global,
+    Guild(Id<GuildMarker>),
+}
+
+impl CommandScope {
+    /// Name of the scope (`global` or the guild id).
+    fn name(self) -> String {
+        match self {
+            CommandScope::Global => "global".to_owned(),
+            CommandScope::Guild(guild_id) => guild_id.to_string(),
+        }
+    }
+}
+
+/// Get the commands registered in each scope.
+///
+/// Beta commands are removed from the global commands and added to the
+/// commands of each beta guild. The global scope is always included, even if
+/// it has no commands.
+fn command_scopes(
+    admin_guild: Option<Id<GuildMarker>>,
+    beta_guilds: &[Id<GuildMarker>],
+    beta_commands: &[String],
+) -> BTreeMap<CommandScope, Vec<Command>> {
+    let (beta, global): (Vec<_>, Vec<_>) = global_commands()
+        .into_iter()
+        .partition(|command| beta_commands.contains(&command.name));
+
+    let mut scopes = BTreeMap::new();
+    scopes.insert(CommandScope::Global, global);
+
+    if let Some(guild_id) = admin_guild {
+        scopes.insert(CommandScope::Guild(guild_id), admin_commands());
+    }
+
+    if !beta.is_empty() {
+        for guild_id in beta_guilds {
+            scopes
+                .entry(CommandScope::Guild(*guild_id))
+                .or_insert_with(Vec::new)
+                .extend(beta.iter().cloned());
+        }
+    }
+
+    scopes
+}
+
+/// Compute the hash of a list of commands.
+///
+/// The commands are serialized with their object keys sorted, since the
+/// localizations are stored in hash maps.
+fn commands_hash(commands: &[Command]) -> String {
+    fn sort_keys(value: Value) -> Value {
+        match value {
+            Value::Object(map) => {
+                let mut entries = map.into_iter().collect::<Vec<_>>();
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+                Value::Object(
+                    entries
+                        .into_iter()
+                        .map(|(key, value)| (key, sort_keys(value)))
+                        .collect(),
+                )
+            }
+            Value::Array(values) => Value::Array(values.into_iter().map(sort_keys).collect()),
+            value => value,
+        }
+    }
+
+    let value = serde_json::to_value(commands).unwrap_or_default();
+    let hash = Sha256::digest(sort_keys(value).to_string());
+
+    hex::encode(hash)
+}
+
+/// Commands registered globally.
+fn global_commands() -> Vec<Command> {
+    vec![
         ConfigCommand::create_command().into(),
         HelpCommand::create_command().into(),
         KickCommand::create_command().into(),
+        ModlogsCommand::create_command().into(),
         ProfileCommand::create_command().into(),
-    ];
+        SetupCommand::create_command().into(),
+    ]
+}
 
-    let client = state.http.interaction(application_id);
+/// Commands registered in the administration guild.
+fn admin_commands() -> Vec<Command> {
+    vec![
+        DebugCommand::create_command().into(),
+        EntitlementsCommand::create_command().into(),
+        KillSwitchCommand::create_command().into(),
+        PrivacyCommand::create_command().into(),
+    ]
+}
+
+/// Names of all the registered commands.
+pub(crate) fn command_names() -> Vec<String> {
+    global_commands()
+        .into_iter()
+        .chain(admin_commands())
+        .map(|command| command.name)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_scopes() {
+        let admin = Id::new(1);
+        let beta = Id::new(2);
+        let scopes = command_scopes(Some(admin), &[admin, beta], &["profile".to_owned()]);
+        let names = |scope| {
+            scopes[&scope]
+                .iter()
+                .map(|command| &*command.name)
+                .collect::<Vec<_>>()
+        };
+
+        assert!(!names(CommandScope::Global).contains(&"profile"));
+        assert!(names(CommandScope::Guild(admin)).contains(&"debug"));
+        assert!(names(CommandScope::Guild(admin)).contains(&"profile"));
+        assert_eq!(names(CommandScope::Guild(beta)), ["profile"]);
+    }
+
+    #[test]
+    fn test_command_scopes_no_beta() {
+        let scopes = command_scopes(None, &[Id::new(2)], &[]);
+
+        assert_eq!(scopes.len(), 1);
+        assert_eq!(scopes[&CommandScope::Global].len(), global_commands().len());
+    }
+
+    #[test]
+    fn test_commands_hash() {
+        let commands = global_commands();
 
-    if let Err(error) = client.set_global_commands(&commands).exec().await {
-        error!(error = ?error, "failed to register commands");
+        assert_eq!(commands_hash(&commands), commands_hash(&global_commands()));
+        assert_ne!(commands_hash(&commands), commands_hash(&commands[1..]));
     }
 }
@@ -0,0 +1,725 @@
+//! Interaction middlewares.
+//!
+//! Incoming interactions are processed by a [`Pipeline`] of [`Middleware`]s
+//! wrapped around the command, component or modal handler. Middlewares run
+//! checks before the handler (and may respond instead of it), and process its
+//! result afterwards. This is used for concerns shared by all interactions,
+//! such as the kill switch or the error reports, instead of implementing them
+//! in each handler.
+//!
+//! Middlewares are run in the order they are added to the pipeline, and their
+//! [`after`](Middleware::after) hooks in the reverse order. When a middleware
+//! responds in [`before`](Middleware::before), the following middlewares and
+//! the handler are skipped, and only the hooks of the previous middlewares are
+//! run.
+
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    future::Future,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use raidprotect_model::metrics::{self, MetricFamily, SeriesValue, BUCKETS};
+use tracing::{error, warn};
+use twilight_model::application::interaction::{Interaction, InteractionData, InteractionType};
+
+use super::{
+    component::ErrorHelpButton,
+    embed,
+    response::InteractionResponse,
+    util::{CustomId, InteractionExt},
+};
+use crate::{
+    cluster::ClusterState,
+    feature::{
+        cooldown::{command_cooldown, start_cooldown},
+        kill_switch::command_disabled,
+    },
+    translations::Lang,
+    util::ensure_guild_cached,
+};
+
+/// Name of the interaction latency metric.
+const INTERACTION_DURATION: &str = "raidprotect_interaction_duration_seconds";
+
+/// Name of the interaction errors metric.
+const INTERACTION_ERRORS: &str = "raidprotect_interaction_errors_total";
+
+/// Name of the interaction invocations metric.
+const INTERACTIONS: &str = "raidprotect_interactions_total";
+
+/// Maximum time an interaction waits for its missing guild to be fetched.
+///
+/// Interactions must be answered within three seconds, so the handler is run
+/// after this delay even if the guild is still being fetched.
+const GUILD_FETCH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Interaction processed by a [`Pipeline`].
+#[derive(Debug)]
+pub struct InteractionRequest {
+    /// The processed interaction.
+    pub interaction: Interaction,
+    /// Name of the command, or custom id name of the component or modal.
+    pub name: String,
+    /// Lang of the user that invoked the interaction.
+    pub lang: Lang,
+    /// Time at which the processing started.
+    pub started: Instant,
+}
+
+impl InteractionRequest {
+    /// Initialize a new [`InteractionRequest`].
+    ///
+    /// The name is empty if the interaction data is invalid, in which case the
+    /// error is returned by the handler.
+    pub fn new(interaction: Interaction) -> Self {
+        let name = match &interaction.data {
+            Some(InteractionData::ApplicationCommand(data)) => data.name.clone(),
+            Some(InteractionData::MessageComponent(data)) => CustomId::from_str(&data.custom_id)
+                .map(|custom_id| custom_id.name)
+                .unwrap_or_default(),
+            Some(InteractionData::ModalSubmit(data)) => CustomId::from_str(&data.custom_id)
+                .map(|custom_id| custom_id.name)
+                .unwrap_or_default(),
+            _ => String::new(),
+        };
+
+        Self {
+            lang: interaction.lang().unwrap_or(Lang::DEFAULT),
+            name,
+            interaction,
+            started: Instant::now(),
+        }
+    }
+}
+
+/// Step of a [`Pipeline`].
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    /// Run before the handler.
+    ///
+    /// If a response is returned, the handler is not run and the response is
+    /// sent instead.
+    async fn before(
+        &self,
+        _request: &InteractionRequest,
+        _state: &ClusterState,
+    ) -> Result<Option<InteractionResponse>, anyhow::Error> {
+        Ok(None)
+    }
+
+    /// Run after the handler, with its result.
+    ///
+    /// The returned result replaces the result of the handler.
+    async fn after(
+        &self,
+        _request: &InteractionRequest,
+        result: Result<InteractionResponse, anyhow::Error>,
+        _state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        result
+    }
+}
+
+/// Chain of [`Middleware`]s run around interaction handlers.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Default)]
+pub struct Pipeline {
+    middlewares: Vec<Box<dyn Middleware>>,
+}
+
+impl Pipeline {
+    /// Initialize a new empty [`Pipeline`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a middleware at the end of the pipeline.
+    pub fn with(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middlewares.push(Box::new(middleware));
+
+        self
+    }
+
+    /// Process an interaction with a handler.
+    pub async fn run<F, Fut>(
+        &self,
+        request: &InteractionRequest,
+        state: &ClusterState,
+        handler: F,
+    ) -> Result<InteractionResponse, anyhow::Error>
+    where
+        F: FnOnce(Interaction) -> Fut,
+        Fut: Future<Output = Result<InteractionResponse, anyhow::Error>>,
+    {
+        let mut entered = 0;
+        let mut response = None;
+
+        for middleware in &self.middlewares {
+            match middleware.before(request, state).await {
+                Ok(None) => entered += 1,
+                Ok(Some(early)) => {
+                    response = Some(Ok(early));
+                    break;
+                }
+                Err(error) => {
+                    response = Some(Err(error));
+                    break;
+                }
+            }
+        }
+
+        let mut result = match response {
+            Some(response) => response,
+            None => handler(request.interaction.clone()).await,
+        };
+
+        for middleware in self.middlewares[..entered].iter().rev() {
+            result = middleware.after(request, result, state).await;
+        }
+
+        result
+    }
+}
+
+/// Fetch the guild of the interaction if it is missing from the cache.
+///
+/// The guild is fetched in a background task, and the interaction waits for
+/// it at most [`GUILD_FETCH_TIMEOUT`].
+pub struct GuildCache;
+
+#[async_trait]
+impl Middleware for GuildCache {
+    async fn before(
+        &self,
+        request: &InteractionRequest,
+        state: &ClusterState,
+    ) -> Result<Option<InteractionResponse>, anyhow::Error> {
+        if let Some(guild_id) = request.interaction.guild_id {
+            let state = state.clone();
+            let fetch = tokio::spawn(async move {
+                if let Err(error) = ensure_guild_cached(&state, guild_id).await {
+                    error!(error = ?error, guild = ?guild_id, "failed to fetch missing guild");
+                }
+            });
+
+            if tokio::time::timeout(GUILD_FETCH_TIMEOUT, fetch)
+                .await
+                .is_err()
+            {
+                warn!(guild = ?guild_id, "guild still being fetched, running interaction without it");
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Record the invocations, processing time and errors of interactions.
+///
+/// Interactions are labelled with their kind and their name. If enabled, the
+/// usage of the commands is also recorded in the database (see
+/// [`StatsStore::record_command_usage`]).
+///
+/// [`StatsStore::record_command_usage`]: raidprotect_model::database::StatsStore::record_command_usage
+pub struct Metrics;
+
+#[async_trait]
+impl Middleware for Metrics {
+    async fn after(
+        &self,
+        request: &InteractionRequest,
+        result: Result<InteractionResponse, anyhow::Error>,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let kind = request.interaction.kind.kind();
+        let labels = [("kind", kind), ("name", &*request.name)];
+        let duration = request.started.elapsed();
+
+        metrics::registry().inc_counter(INTERACTIONS, "Number of interactions processed", &labels);
+        metrics::registry().observe(
+            INTERACTION_DURATION,
+            "Time to process interactions",
+            &labels,
+            duration.as_secs_f64(),
+        );
+
+        if result.is_err() {
+            metrics::registry().inc_counter(
+                INTERACTION_ERRORS,
+                "Number of interactions that failed to process",
+                &labels,
+            );
+        }
+
+        if state.command_usage_stats
+            && request.interaction.kind == InteractionType::ApplicationCommand
+        {
+            let store = state.store.clone();
+            let (name, failed) = (request.name.clone(), result.is_err());
+
+            tokio::spawn(async move {
+                if let Err(error) = store.record_command_usage(&name, duration, failed).await {
+                    error!(error = ?error, command = %name, "failed to record command usage");
+                }
+            });
+        }
+
+        result
+    }
+}
+
+/// Usage of a command, summarized from the interaction metrics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandUsage {
+    /// Name of the command.
+    pub name: String,
+    /// Number of invocations.
+    pub invocations: u64,
+    /// Number of invocations that failed.
+    pub errors: u64,
+    /// Total processing time of the invocations (in seconds).
+    pub duration_total: f64,
+    /// Number of invocations in each latency bucket (see [`BUCKETS`]).
+    pub buckets: Vec<u64>,
+}
+
+impl CommandUsage {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            invocations: 0,
+            errors: 0,
+            duration_total: 0.0,
+            buckets: vec![0; BUCKETS.len()],
+        }
+    }
+
+    /// Ratio of invocations that failed.
+    pub fn error_rate(&self) -> Option<f64> {
+        (self.invocations > 0).then(|| self.errors as f64 / self.invocations as f64)
+    }
+
+    /// Average processing time (in seconds).
+    pub fn average_duration(&self) -> Option<f64> {
+        (self.invocations > 0).then(|| self.duration_total / self.invocations as f64)
+    }
+
+    /// Upper bound of the processing time of a given quantile of the
+    /// invocations (in seconds).
+    ///
+    /// Returns [`None`] if there are no invocations, or if the quantile is
+    /// above the highest bucket.
+    pub fn duration_quantile(&self, quantile: f64) -> Option<f64> {
+        let target = (self.invocations as f64 * quantile).ceil() as u64;
+        let mut cumulative = 0;
+
+        for (bound, count) in BUCKETS.iter().zip(&self.buckets) {
+            cumulative += count;
+
+            if target > 0 && cumulative >= target {
+                return Some(*bound);
+            }
+        }
+
+        None
+    }
+}
+
+/// Summarize the usage of the commands from metrics snapshots.
+///
+/// The snapshots of multiple processes are merged. Commands are sorted by
+/// number of invocations, the most used first.
+pub fn command_usage(families: &[MetricFamily]) -> Vec<CommandUsage> {
+    let mut usage: BTreeMap<String, CommandUsage> = BTreeMap::new();
+    let command_kind = InteractionType::ApplicationCommand.kind();
+
+    let series = families.iter().flat_map(|family| {
+        family
+            .series
+            .iter()
+            .map(move |(labels, value)| (&*family.name, labels, value))
+    });
+
+    for (metric, labels, value) in series {
+        let label = |name: &str| {
+            labels
+                .iter()
+                .find(|(label, _)| label == name)
+                .map(|(_, value)| &**value)
+        };
+
+        let name = match (label("kind"), label("name")) {
+            (Some(kind), Some(name)) if kind == command_kind && !name.is_empty() => name,
+            _ => continue,
+        };
+        let entry = usage
+            .entry(name.to_owned())
+            .or_insert_with(|| CommandUsage::new(name.to_owned()));
+
+        match (metric, value) {
+            (INTERACTIONS, SeriesValue::Counter(count)) => entry.invocations += count,
+            (INTERACTION_ERRORS, SeriesValue::Counter(count)) => entry.errors += count,
+            (INTERACTION_DURATION, SeriesValue::Histogram { buckets, sum, .. }) => {
+                entry.duration_total += sum;
+
+                for (total, count) in entry.buckets.iter_mut().zip(buckets) {
+                    *total += count;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut usage = usage.into_values().collect::<Vec<_>>();
+    usage.sort_by_key(|usage| Reverse(usage.invocations));
+
+    usage
+}
+
+/// Respond with a maintenance message to the interactions disabled with a
+/// kill switch.
+///
+/// The `/killswitch` command cannot be disabled. Autocomplete interactions
+/// are not checked, since disabled commands cannot be run anyway.
+pub struct KillSwitch;
+
+#[async_trait]
+impl Middleware for KillSwitch {
+    async fn before(
+        &self,
+        request: &InteractionRequest,
+        state: &ClusterState,
+    ) -> Result<Option<InteractionResponse>, anyhow::Error> {
+        let kind = request.interaction.kind;
+        if kind == InteractionType::ApplicationCommandAutocomplete
+            || (kind == InteractionType::ApplicationCommand && request.name == "killswitch")
+        {
+            return Ok(None);
+        }
+
+        let response = command_disabled(&request.name, state)
+            .await
+            .map(|kill_switch| {
+                embed::error::maintenance(request.lang, kill_switch.reason.as_deref())
+            });
+
+        Ok(response)
+    }
+}
+
+/// Restrict commands to the bot owners.
+///
+/// Autocomplete interactions of the restricted commands are responded with no
+/// suggestions.
+pub struct OwnerOnly {
+    /// Names of the restricted commands.
+    commands: &'static [&'static str],
+}
+
+impl OwnerOnly {
+    /// Initialize a new [`OwnerOnly`] middleware for the given commands.
+    pub fn new(commands: &'static [&'static str]) -> Self {
+        Self { commands }
+    }
+}
+
+#[async_trait]
+impl Middleware for OwnerOnly {
+    async fn before(
+        &self,
+        request: &InteractionRequest,
+        state: &ClusterState,
+    ) -> Result<Option<InteractionResponse>, anyhow::Error> {
+        let kind = request.interaction.kind;
+        if !matches!(
+            kind,
+            InteractionType::ApplicationCommand | InteractionType::ApplicationCommandAutocomplete
+        ) || !self.commands.contains(&&*request.name)
+        {
+            return Ok(None);
+        }
+
+        match request.interaction.author_id() {
+            Some(user_id) if state.owners.contains(&user_id) => Ok(None),
+            _ if kind == InteractionType::ApplicationCommandAutocomplete => {
+                Ok(Some(InteractionResponse::Autocomplete(Vec::new())))
+            }
+            _ => Ok(Some(embed::error::not_owner(request.lang))),
+        }
+    }
+}
+
+/// Respond with the remaining time to the commands used during their cooldown.
+///
+/// See the [`cooldown`](crate::feature::cooldown) module for more information.
+pub struct Cooldown;
+
+#[async_trait]
+impl Middleware for Cooldown {
+    async fn before(
+        &self,
+        request: &InteractionRequest,
+        state: &ClusterState,
+    ) -> Result<Option<InteractionResponse>, anyhow::Error> {
+        if request.interaction.kind != InteractionType::ApplicationCommand {
+            return Ok(None);
+        }
+
+        let (cooldown, user_id) = match (
+            command_cooldown(&request.name),
+            request.interaction.author_id(),
+        ) {
+            (Some(cooldown), Some(user_id)) => (cooldown, user_id),
+            _ => return Ok(None),
+        };
+
+        let guild_id = request.interaction.guild_id;
+        let remaining = start_cooldown(guild_id, user_id, &request.name, cooldown, state).await?;
+
+        Ok(remaining.map(|remaining| embed::error::cooldown(request.lang, remaining)))
+    }
+}
+
+/// Respond with an error message when an interaction fails.
+///
+/// The error is reported to the support server if configured (see
+/// [`ErrorHelpButton::report`]). Failed autocomplete interactions are only
+/// logged, and responded with no suggestions.
+pub struct ErrorReport;
+
+#[async_trait]
+impl Middleware for ErrorReport {
+    async fn after(
+        &self,
+        request: &InteractionRequest,
+        result: Result<InteractionResponse, anyhow::Error>,
+        state: &ClusterState,
+    ) -> Result<InteractionResponse, anyhow::Error> {
+        let error = match result {
+            Ok(response) => return Ok(response),
+            Err(error) => error,
+        };
+
+        let interaction = &request.interaction;
+        error!(error = ?error, reference = %interaction.id, "error while processing interaction");
+
+        if interaction.kind == InteractionType::ApplicationCommandAutocomplete {
+            return Ok(InteractionResponse::Autocomplete(Vec::new()));
+        }
+
+        let reference = match ErrorHelpButton::report(interaction, state).await {
+            Ok(reference) => reference,
+            Err(error) => {
+                error!(error = ?error, "failed to store error report");
+                None
+            }
+        };
+
+        Ok(embed::error::internal_error(request.lang, reference))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use anyhow::anyhow;
+    use serde_json::json;
+
+    use super::*;
+
+    /// Middleware recording the calls of its hooks.
+    struct Recorder {
+        name: &'static str,
+        calls: Arc<Mutex<Vec<String>>>,
+        respond: bool,
+    }
+
+    #[async_trait]
+    impl Middleware for Recorder {
+        async fn before(
+            &self,
+            _request: &InteractionRequest,
+            _state: &ClusterState,
+        ) -> Result<Option<InteractionResponse>, anyhow::Error> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("before {}", self.name));
+
+            Ok(self.respond.then(|| embed::error::not_owner(Lang::DEFAULT)))
+        }
+
+        async fn after(
+            &self,
+            _request: &InteractionRequest,
+            result: Result<InteractionResponse, anyhow::Error>,
+            _state: &ClusterState,
+        ) -> Result<InteractionResponse, anyhow::Error> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("after {}", self.name));
+
+            result
+        }
+    }
+
+    fn interaction() -> Interaction {
+        serde_json::from_value(json!({
+            "id": "3",
+            "application_id": "1",
+            "type": 2,
+            "data": {
+                "id": "4",
+                "name": "help",
+                "type": 1,
+            },
+            "token": "token",
+            "locale": "en-US",
+            "user": {
+                "id": "2",
+                "username": "user",
+                "discriminator": "0001",
+                "avatar": null,
+            },
+            "version": 1,
+        }))
+        .unwrap()
+    }
+
+    fn recorder(name: &'static str, calls: &Arc<Mutex<Vec<String>>>, respond: bool) -> Recorder {
+        Recorder {
+            name,
+            calls: calls.clone(),
+            respond,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_order() {
+        let state = ClusterState::for_tests().await;
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let pipeline = Pipeline::new()
+            .with(recorder("first", &calls, false))
+            .with(recorder("second", &calls, false));
+
+        let request = InteractionRequest::new(interaction());
+        let handler_calls = calls.clone();
+        let result = pipeline
+            .run(&request, &state, |_| async move {
+                handler_calls.lock().unwrap().push("handler".to_owned());
+
+                Ok(embed::error::not_owner(Lang::DEFAULT))
+            })
+            .await;
+
+        assert!(result.is_ok());
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [
+                "before first",
+                "before second",
+                "handler",
+                "after second",
+                "after first"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_early_response() {
+        let state = ClusterState::for_tests().await;
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let pipeline = Pipeline::new()
+            .with(recorder("first", &calls, false))
+            .with(recorder("second", &calls, true))
+            .with(recorder("third", &calls, false));
+
+        let request = InteractionRequest::new(interaction());
+        let result = pipeline
+            .run(&request, &state, |_| async {
+                Err(anyhow!("handler called"))
+            })
+            .await;
+
+        assert!(result.is_ok());
+        assert_eq!(
+            *calls.lock().unwrap(),
+            ["before first", "before second", "after first"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_owner_only() {
+        let state = ClusterState::for_tests().await;
+        let pipeline = Pipeline::new().with(OwnerOnly::new(&["debug"]));
+
+        let mut request = InteractionRequest::new(interaction());
+        request.name = "debug".to_owned();
+        let result = pipeline
+            .run(&request, &state, |_| async {
+                Err(anyhow!("handler called"))
+            })
+            .await;
+        assert!(result.is_ok());
+
+        request.name = "help".to_owned();
+        let result = pipeline
+            .run(&request, &state, |_| async {
+                Err(anyhow!("handler called"))
+            })
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_command_usage() {
+        let registry = metrics::Registry::default();
+        let command = [("kind", "ApplicationCommand"), ("name", "kick")];
+        let component = [("kind", "MessageComponent"), ("name", "raid-ban")];
+
+        registry.inc_counter(INTERACTIONS, "", &command);
+        registry.inc_counter(INTERACTIONS, "", &command);
+        registry.inc_counter(INTERACTIONS, "", &component);
+        registry.inc_counter(INTERACTION_ERRORS, "", &command);
+        registry.observe(INTERACTION_DURATION, "", &command, 0.02);
+        registry.observe(INTERACTION_DURATION, "", &command, 0.2);
+
+        let mut families = registry.snapshot();
+        families.extend(registry.snapshot());
+        let usage = command_usage(&families);
+
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].name, "kick");
+        assert_eq!(usage[0].invocations, 4);
+        assert_eq!(usage[0].error_rate(), Some(0.5));
+        assert_eq!(usage[0].duration_quantile(0.5), Some(0.025));
+        assert_eq!(usage[0].duration_quantile(0.95), Some(0.25));
+    }
+
+    #[tokio::test]
+    async fn test_error_report_autocomplete() {
+        let state = ClusterState::for_tests().await;
+        let pipeline = Pipeline::new().with(ErrorReport);
+
+        let mut interaction = interaction();
+        interaction.kind = InteractionType::ApplicationCommandAutocomplete;
+        let request = InteractionRequest::new(interaction);
+        let result = pipeline
+            .run(&request, &state, |_| async {
+                Err(anyhow!("handler failed"))
+            })
+            .await;
+
+        assert_eq!(
+            result.unwrap(),
+            InteractionResponse::Autocomplete(Vec::new())
+        );
+    }
+}
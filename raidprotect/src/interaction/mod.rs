@@ -5,9 +5,12 @@
 
 mod handle;
 
+pub mod autocomplete;
 pub mod command;
 pub mod component;
 pub mod embed;
+pub mod middleware;
+pub mod modal;
 pub mod response;
 pub mod util;
 
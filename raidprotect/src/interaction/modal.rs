@@ -0,0 +1,344 @@
+//! Modal forms.
+//!
+//! Modals are declared as types implementing [`ModalForm`]. The form describes
+//! its fields with [`ModalField`], which are used both to generate the modal
+//! components and to validate the submitted values (required fields and
+//! length limits) before the form is parsed. Validation errors are returned
+//! as [`ModalFieldError`], and can be shown to the user with
+//! [`embed::error::invalid_field`].
+//!
+//! [`embed::error::invalid_field`]: super::embed::error::invalid_field
+
+use std::str::FromStr;
+
+use twilight_model::application::{
+    component::{text_input::TextInputStyle, ActionRow, Component, TextInput},
+    interaction::modal::ModalInteractionData,
+};
+
+use super::response::InteractionResponse;
+use crate::translations::Lang;
+
+/// Form sent as a modal.
+///
+/// See the [module documentation](self) for more information.
+pub trait ModalForm: Sized {
+    /// Options used to build the fields of the form.
+    type Options;
+
+    /// Fields of the form, in display order.
+    ///
+    /// Discord allows up to five fields in a modal.
+    fn fields(options: &Self::Options, lang: Lang) -> Vec<ModalField>;
+
+    /// Read the form from the submitted values.
+    ///
+    /// The values have already been validated against the [`fields`].
+    ///
+    /// [`fields`]: ModalForm::fields
+    fn parse(values: &ModalValues<'_>) -> Result<Self, ModalFieldError>;
+
+    /// Generate the modal response.
+    fn modal(
+        custom_id: String,
+        title: String,
+        options: &Self::Options,
+        lang: Lang,
+    ) -> InteractionResponse {
+        let components = Self::fields(options, lang)
+            .into_iter()
+            .map(ModalField::into_component)
+            .collect();
+
+        InteractionResponse::Modal {
+            custom_id,
+            title,
+            components,
+        }
+    }
+
+    /// Validate and parse the submitted modal data.
+    ///
+    /// The `options` should be the same as the ones used to send the modal.
+    fn from_modal(
+        data: &ModalInteractionData,
+        options: &Self::Options,
+        lang: Lang,
+    ) -> Result<Self, ModalFieldError> {
+        let fields = Self::fields(options, lang);
+        let values = ModalValues {
+            fields: &fields,
+            data,
+        };
+
+        values.validate()?;
+        Self::parse(&values)
+    }
+}
+
+/// Text field of a [`ModalForm`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModalField {
+    /// Custom id of the field.
+    pub id: &'static str,
+    /// Label shown to the user.
+    pub label: String,
+    /// Style of the text input.
+    pub style: TextInputStyle,
+    /// Whether the field must be filled.
+    pub required: bool,
+    /// Minimum length of the value (in characters).
+    pub min_length: Option<u16>,
+    /// Maximum length of the value (in characters).
+    pub max_length: Option<u16>,
+    /// Placeholder shown when the field is empty.
+    pub placeholder: Option<String>,
+    /// Initial value of the field.
+    pub value: Option<String>,
+}
+
+impl ModalField {
+    /// Initialize a new single-line field.
+    pub fn short(id: &'static str, label: impl Into<String>) -> Self {
+        Self::new(id, label.into(), TextInputStyle::Short)
+    }
+
+    /// Initialize a new multi-line field.
+    pub fn paragraph(id: &'static str, label: impl Into<String>) -> Self {
+        Self::new(id, label.into(), TextInputStyle::Paragraph)
+    }
+
+    fn new(id: &'static str, label: String, style: TextInputStyle) -> Self {
+        Self {
+            id,
+            label,
+            style,
+            required: false,
+            min_length: None,
+            max_length: None,
+            placeholder: None,
+            value: None,
+        }
+    }
+
+    /// Set whether the field must be filled.
+    pub fn required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Set the minimum length of the value.
+    pub fn min_length(mut self, min_length: u16) -> Self {
+        self.min_length = Some(min_length);
+        self
+    }
+
+    /// Set the maximum length of the value.
+    pub fn max_length(mut self, max_length: u16) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Set the placeholder of the field.
+    pub fn placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = Some(placeholder.into());
+        self
+    }
+
+    /// Convert the field into a modal component.
+    fn into_component(self) -> Component {
+        Component::ActionRow(ActionRow {
+            components: vec![Component::TextInput(TextInput {
+                custom_id: self.id.to_owned(),
+                label: self.label,
+                max_length: self.max_length,
+                min_length: self.min_length,
+                placeholder: self.placeholder,
+                required: Some(self.required),
+                style: self.style,
+                value: self.value,
+            })],
+        })
+    }
+}
+
+/// Values submitted in a [`ModalForm`].
+#[derive(Debug)]
+pub struct ModalValues<'a> {
+    fields: &'a [ModalField],
+    data: &'a ModalInteractionData,
+}
+
+impl<'a> ModalValues<'a> {
+    /// Get the value of a field.
+    ///
+    /// The value is trimmed, and [`None`] is returned if it is empty.
+    pub fn text(&self, id: &str) -> Option<&'a str> {
+        self.data
+            .components
+            .iter()
+            .flat_map(|row| &row.components)
+            .find(|component| component.custom_id == id)
+            .and_then(|component| component.value.as_deref())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Get the value of a required field.
+    pub fn required(&self, id: &str) -> Result<&'a str, ModalFieldError> {
+        self.text(id)
+            .ok_or_else(|| self.error(id, ModalFieldErrorKind::Missing))
+    }
+
+    /// Parse the value of a field.
+    #[allow(unused)]
+    pub fn parse<T: FromStr>(&self, id: &str) -> Result<Option<T>, ModalFieldError> {
+        self.text(id)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| self.error(id, ModalFieldErrorKind::Invalid))
+            })
+            .transpose()
+    }
+
+    /// Build an error for a field.
+    pub fn error(&self, id: &str, kind: ModalFieldErrorKind) -> ModalFieldError {
+        let label = self
+            .fields
+            .iter()
+            .find(|field| field.id == id)
+            .map(|field| field.label.clone())
+            .unwrap_or_else(|| id.to_owned());
+
+        ModalFieldError { label, kind }
+    }
+
+    /// Check the values against the required fields and length limits.
+    fn validate(&self) -> Result<(), ModalFieldError> {
+        for field in self.fields {
+            let length = match self.text(field.id) {
+                Some(value) => value.chars().count(),
+                None if field.required => {
+                    return Err(self.error(field.id, ModalFieldErrorKind::Missing))
+                }
+                None => continue,
+            };
+
+            if let Some(min) = field.min_length.filter(|min| length < *min as usize) {
+                return Err(self.error(field.id, ModalFieldErrorKind::TooShort(min)));
+            }
+
+            if let Some(max) = field.max_length.filter(|max| length > *max as usize) {
+                return Err(self.error(field.id, ModalFieldErrorKind::TooLong(max)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Invalid value submitted in a [`ModalForm`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModalFieldError {
+    /// Label of the invalid field.
+    pub label: String,
+    /// Kind of error.
+    pub kind: ModalFieldErrorKind,
+}
+
+/// Kind of [`ModalFieldError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModalFieldErrorKind {
+    /// The field is required but empty.
+    Missing,
+    /// The value is shorter than the minimum length.
+    TooShort(u16),
+    /// The value is longer than the maximum length.
+    TooLong(u16),
+    /// The value cannot be parsed.
+    #[allow(unused)]
+    Invalid,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct TestForm {
+        name: String,
+        age: Option<u8>,
+    }
+
+    impl ModalForm for TestForm {
+        type Options = ();
+
+        fn fields(_options: &(), _lang: Lang) -> Vec<ModalField> {
+            vec![
+                ModalField::short("name", "Name")
+                    .required(true)
+                    .min_length(2)
+                    .max_length(5),
+                ModalField::short("age", "Age"),
+            ]
+        }
+
+        fn parse(values: &ModalValues<'_>) -> Result<Self, ModalFieldError> {
+            Ok(Self {
+                name: values.required("name")?.to_owned(),
+                age: values.parse("age")?,
+            })
+        }
+    }
+
+    fn data(name: &str, age: &str) -> ModalInteractionData {
+        serde_json::from_value(json!({
+            "custom_id": "test",
+            "components": [
+                { "type": 1, "components": [{ "type": 4, "custom_id": "name", "value": name }] },
+                { "type": 1, "components": [{ "type": 4, "custom_id": "age", "value": age }] },
+            ],
+        }))
+        .unwrap()
+    }
+
+    fn parse(name: &str, age: &str) -> Result<TestForm, ModalFieldError> {
+        TestForm::from_modal(&data(name, age), &(), Lang::DEFAULT)
+    }
+
+    #[test]
+    fn test_modal_components() {
+        let response = TestForm::modal("test".to_owned(), "Test".to_owned(), &(), Lang::DEFAULT);
+
+        match response {
+            InteractionResponse::Modal { components, .. } => assert_eq!(components.len(), 2),
+            _ => panic!("expected modal response"),
+        }
+    }
+
+    #[test]
+    fn test_from_modal() {
+        assert_eq!(
+            parse(" Bob ", ""),
+            Ok(TestForm {
+                name: "Bob".to_owned(),
+                age: None
+            })
+        );
+        assert_eq!(parse("Bob", "42").unwrap().age, Some(42));
+    }
+
+    #[test]
+    fn test_from_modal_errors() {
+        let kind = |name, age| parse(name, age).unwrap_err().kind;
+
+        assert_eq!(kind(" ", ""), ModalFieldErrorKind::Missing);
+        assert_eq!(kind("B", ""), ModalFieldErrorKind::TooShort(2));
+        assert_eq!(kind("Bobby Tables", ""), ModalFieldErrorKind::TooLong(5));
+        assert_eq!(kind("Bob", "old"), ModalFieldErrorKind::Invalid);
+        assert_eq!(parse("", "").unwrap_err().label, "Name");
+    }
+}
@@ -4,7 +4,7 @@
 
 use tracing::error;
 use twilight_model::{
-    application::{component::Component, interaction::Interaction},
+    application::{command::CommandOptionChoice, component::Component, interaction::Interaction},
     channel::{embed::Embed, message::MessageFlags},
     http::interaction::{
         InteractionResponse as HttpInteractionResponse, InteractionResponseData,
@@ -74,6 +74,8 @@ pub enum InteractionResponse {
     ///
     /// [`DeferredChannelMessageWithSource`]: InteractionResponseType::DeferredChannelMessageWithSource
     EphemeralDeferredMessage,
+    /// Respond with autocomplete suggestions.
+    Autocomplete(Vec<CommandOptionChoice>),
     /// Respond with a raw [`HttpInteractionResponse`].
     Raw {
         kind: InteractionResponseType,
@@ -89,6 +91,7 @@ impl InteractionResponse {
             Self::EphemeralDeferredMessage => {
                 InteractionResponseType::DeferredChannelMessageWithSource
             }
+            Self::Autocomplete(_) => InteractionResponseType::ApplicationCommandAutocompleteResult,
             Self::Raw { kind, .. } => kind,
             _ => InteractionResponseType::ChannelMessageWithSource,
         };
@@ -121,6 +124,11 @@ impl InteractionResponse {
                     .flags(MessageFlags::EPHEMERAL)
                     .build(),
             ),
+            Self::Autocomplete(choices) => Some(
+                InteractionResponseDataBuilder::new()
+                    .choices(choices)
+                    .build(),
+            ),
             Self::Raw { data, .. } => data,
         };
 
@@ -1,9 +1,10 @@
 //! Utility function to handle incoming interactions.
 
 use std::{
+    error::Error,
     fmt::{self, Display},
     mem,
-    str::FromStr,
+    str::{FromStr, Split},
 };
 
 use anyhow::{anyhow, bail, Context};
@@ -11,7 +12,10 @@ use raidprotect_model::database::model::GuildConfig;
 use tracing::instrument;
 use twilight_interactions::command::CommandModel;
 use twilight_model::{
-    application::interaction::{modal::ModalInteractionData, Interaction, InteractionData},
+    application::interaction::{
+        message_component::MessageComponentInteractionData, modal::ModalInteractionData,
+        Interaction, InteractionData,
+    },
     guild::PartialMember,
     id::{marker::GuildMarker, Id},
     user::User,
@@ -114,8 +118,7 @@ impl GuildInteractionContext {
     /// Get the [`GuildConfig`] for the guild the interaction was invoked in.
     pub async fn config(&self, state: &ClusterState) -> Result<GuildConfig, anyhow::Error> {
         let config = state
-            .database
-            .get_guild_or_create(self.guild_id)
+            .guild_config(self.guild_id)
             .await
             .context("failed to get guild config")?;
 
@@ -155,6 +158,7 @@ impl GuildConfigExt for GuildConfig {
 /// This type is used to hold component identifiers, used in buttons or modals.
 /// Each custom id must have a `name` which correspond to the component type,
 /// and optionally an `id` used to store component state.
+#[derive(Debug)]
 pub struct CustomId {
     /// Name of the component.
     pub name: String,
@@ -213,6 +217,100 @@ impl Display for CustomId {
     }
 }
 
+/// Maximum length of a component custom id allowed by Discord.
+pub const CUSTOM_ID_MAX_LEN: usize = 100;
+
+/// Typed component custom id.
+///
+/// Types implementing this trait are encoded as a [`CustomId`] with the
+/// component name and the payload fields separated by `:` (for example
+/// `captcha-approve:123456`). Fields are written without their name to keep
+/// the custom id short, and must not contain `:`.
+///
+/// Component handlers should decode their custom id with [`decode`] instead
+/// of parsing [`CustomId::id`] themselves.
+///
+/// [`decode`]: ComponentId::decode
+pub trait ComponentId: Sized {
+    /// Name of the component.
+    fn name(&self) -> &'static str;
+
+    /// Payload fields of the custom id.
+    fn fields(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Read the custom id from the component name and the payload fields.
+    fn from_fields(name: &str, fields: &mut CustomIdFields<'_>) -> Result<Self, anyhow::Error>;
+
+    /// Encode the custom id.
+    ///
+    /// Returns an error if a field contains `:` or if the custom id is longer
+    /// than [`CUSTOM_ID_MAX_LEN`].
+    fn encode(&self) -> Result<String, anyhow::Error> {
+        let fields = self.fields();
+
+        if let Some(field) = fields.iter().find(|field| field.contains(':')) {
+            bail!("custom id field contains a separator: {field}");
+        }
+
+        let custom_id = match fields.is_empty() {
+            true => CustomId::name(self.name()),
+            false => CustomId::new(self.name(), fields.join(":")),
+        }
+        .to_string();
+
+        if custom_id.len() > CUSTOM_ID_MAX_LEN {
+            bail!("custom id is too long: {custom_id}");
+        }
+
+        Ok(custom_id)
+    }
+
+    /// Decode a [`CustomId`].
+    ///
+    /// Returns an error if a field is missing or invalid, or if the custom id
+    /// has more fields than expected.
+    fn decode(custom_id: &CustomId) -> Result<Self, anyhow::Error> {
+        let mut fields = CustomIdFields {
+            fields: custom_id.id.as_deref().map(|id| id.split(':')),
+        };
+
+        let decoded = Self::from_fields(&custom_id.name, &mut fields)?;
+
+        if fields.fields.and_then(|mut fields| fields.next()).is_some() {
+            bail!("unexpected field in custom id {custom_id}");
+        }
+
+        Ok(decoded)
+    }
+}
+
+/// Payload fields of a [`ComponentId`] being decoded.
+#[derive(Debug)]
+pub struct CustomIdFields<'a> {
+    fields: Option<Split<'a, char>>,
+}
+
+impl CustomIdFields<'_> {
+    /// Parse the next field.
+    pub fn next<T>(&mut self) -> Result<T, anyhow::Error>
+    where
+        T: FromStr,
+        T::Err: Error + Send + Sync + 'static,
+    {
+        let field = self
+            .fields
+            .as_mut()
+            .and_then(|fields| fields.next())
+            .context("missing custom id field")?;
+
+        field
+            .parse()
+            .with_context(|| format!("invalid custom id field: {field}"))
+    }
+}
+
 /// Parse incoming [`ApplicationCommand`] or [`ApplicationCommandAutocomplete`]
 /// interactions into typed struct.
 ///
@@ -250,6 +348,22 @@ pub fn parse_modal_data(
     }
 }
 
+/// Parse incoming [`MessageComponent`] interaction and return the inner data.
+///
+/// This takes a mutable [`Interaction`] since the inner
+/// [`MessageComponentInteractionData`] is replaced with [`None`] to avoid
+/// useless clones.
+///
+/// [`MessageComponent`]: twilight_model::application::interaction::InteractionType::MessageComponent
+pub fn parse_component_data(
+    interaction: &mut Interaction,
+) -> Result<MessageComponentInteractionData, anyhow::Error> {
+    match mem::take(&mut interaction.data) {
+        Some(InteractionData::MessageComponent(data)) => Ok(data),
+        _ => bail!("unable to parse component data, received unknown data type"),
+    }
+}
+
 /// Parse a field from [`ModalInteractionData`].
 ///
 /// This function try to find a field with the given name in the modal data and
@@ -359,3 +473,55 @@ macro_rules! desc_localizations {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use twilight_model::id::marker::UserMarker;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct TestId {
+        user_id: Id<UserMarker>,
+        flag: bool,
+    }
+
+    impl ComponentId for TestId {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        fn fields(&self) -> Vec<String> {
+            vec![self.user_id.to_string(), self.flag.to_string()]
+        }
+
+        fn from_fields(
+            _name: &str,
+            fields: &mut CustomIdFields<'_>,
+        ) -> Result<Self, anyhow::Error> {
+            Ok(Self {
+                user_id: fields.next()?,
+                flag: fields.next()?,
+            })
+        }
+    }
+
+    #[test]
+    fn test_component_id_roundtrip() {
+        let id = TestId {
+            user_id: Id::new(42),
+            flag: true,
+        };
+        let encoded = id.encode().unwrap();
+
+        assert_eq!(encoded, "test:42:true");
+        assert_eq!(TestId::decode(&encoded.parse().unwrap()).unwrap(), id);
+    }
+
+    #[test]
+    fn test_component_id_invalid() {
+        assert!(TestId::decode(&"test:42".parse().unwrap()).is_err());
+        assert!(TestId::decode(&"test:42:true:1".parse().unwrap()).is_err());
+        assert!(TestId::decode(&"test:abc:true".parse().unwrap()).is_err());
+    }
+}
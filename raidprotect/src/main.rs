@@ -11,6 +11,7 @@
 //! - `util`: contain utilities such as logging and shutdown
 
 mod cluster;
+mod coordinator;
 mod event;
 mod feature;
 mod interaction;
@@ -35,7 +36,7 @@ async fn main() -> Result<()> {
         .context("failed to start shard cluster")?;
 
     // Start the shard cluster
-    let cluster_run = tokio::spawn(cluster.start(shutdown.subscriber()));
+    let cluster_run = cluster.start(&shutdown);
     info!("started shard cluster");
 
     // Wait for shutdown
@@ -45,7 +46,7 @@ async fn main() -> Result<()> {
     };
 
     info!("shutting down ...");
-    shutdown.shutdown(5).await;
+    shutdown.shutdown().await;
 
     Ok(())
 }
@@ -0,0 +1,126 @@
+//! Localized formatting utilities.
+//!
+//! These functions format values such as durations or numbers in the
+//! requested [`Lang`], so they can be directly included in embeds.
+
+use time::Duration;
+
+use crate::translations::Lang;
+
+/// Format a [`Duration`] in a human-readable way.
+///
+/// The duration is split into days, hours, minutes and seconds, omitting the
+/// units that are zero (e.g. "2 hours 5 minutes"). Negative durations are
+/// formatted as zero seconds.
+pub fn format_duration(duration: Duration, lang: Lang) -> String {
+    if duration <= Duration::ZERO {
+        return lang.duration_seconds(0);
+    }
+
+    let days = duration.whole_days();
+    let hours = duration.whole_hours() % 24;
+    let minutes = duration.whole_minutes() % 60;
+    let seconds = duration.whole_seconds() % 60;
+
+    let mut parts = Vec::with_capacity(4);
+
+    if days > 0 {
+        parts.push(plural(
+            days,
+            lang.duration_day(days),
+            lang.duration_days(days),
+        ));
+    }
+    if hours > 0 {
+        parts.push(plural(
+            hours,
+            lang.duration_hour(hours),
+            lang.duration_hours(hours),
+        ));
+    }
+    if minutes > 0 {
+        parts.push(plural(
+            minutes,
+            lang.duration_minute(minutes),
+            lang.duration_minutes(minutes),
+        ));
+    }
+    if seconds > 0 {
+        parts.push(plural(
+            seconds,
+            lang.duration_second(seconds),
+            lang.duration_seconds(seconds),
+        ));
+    }
+
+    // Durations below one second are rounded up.
+    if parts.is_empty() {
+        return lang.duration_second(1);
+    }
+
+    parts.join(" ")
+}
+
+/// Format a number with the thousands separator of the given [`Lang`].
+pub fn format_number(number: u64, lang: Lang) -> String {
+    let digits = number.to_string();
+    let separator = lang.number_thousands_separator();
+
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3 * separator.len());
+    let mut group_len = match digits.len() % 3 {
+        0 => 3,
+        len => len,
+    };
+
+    for digit in digits.chars() {
+        if group_len == 0 {
+            formatted.push_str(separator);
+            group_len = 3;
+        }
+
+        formatted.push(digit);
+        group_len -= 1;
+    }
+
+    formatted
+}
+
+/// Choose between singular and plural form.
+fn plural(count: i64, singular: String, plural: String) -> String {
+    if count == 1 {
+        singular
+    } else {
+        plural
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        let duration = Duration::hours(2) + Duration::minutes(5);
+
+        assert_eq!(format_duration(duration, Lang::En), "2 hours 5 minutes");
+        assert_eq!(format_duration(duration, Lang::Fr), "2 heures 5 minutes");
+        assert_eq!(
+            format_duration(Duration::days(1) + Duration::seconds(1), Lang::En),
+            "1 day 1 second"
+        );
+        assert_eq!(format_duration(Duration::ZERO, Lang::En), "0 seconds");
+        assert_eq!(
+            format_duration(Duration::milliseconds(10), Lang::En),
+            "1 second"
+        );
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(0, Lang::En), "0");
+        assert_eq!(format_number(999, Lang::En), "999");
+        assert_eq!(format_number(1000, Lang::En), "1,000");
+        assert_eq!(format_number(1234567, Lang::En), "1,234,567");
+        assert_eq!(format_number(1234567, Lang::Fr), "1\u{202f}234\u{202f}567");
+    }
+}
@@ -0,0 +1,181 @@
+//! Size of the compressed gateway payloads.
+//!
+//! twilight-gateway reports the total size of the payloads received by the
+//! inflater of each shard, before and after decompression, with gauges of the
+//! [`metrics`] crate. The [`InflaterRecorder`] receives these gauges and adds
+//! the received bytes to counters of the bot metrics registry, to compare the
+//! bandwidth used by the gateway with the size of the decompressed payloads.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use metrics::{Counter, Gauge, GaugeFn, Histogram, Key, KeyName, Recorder, Unit};
+use once_cell::sync::Lazy;
+use raidprotect_model::metrics::registry;
+use tracing::warn;
+
+/// Name of the compressed bytes metric.
+const COMPRESSED_BYTES: &str = "raidprotect_gateway_compressed_bytes_total";
+
+/// Name of the decompressed bytes metric.
+const DECOMPRESSED_BYTES: &str = "raidprotect_gateway_decompressed_bytes_total";
+
+/// Global recorder of the inflater metrics.
+static RECORDER: Lazy<InflaterRecorder> = Lazy::new(InflaterRecorder::default);
+
+/// Install the [`InflaterRecorder`] as the global recorder of the [`metrics`]
+/// crate.
+///
+/// This must be called before the shards are started.
+pub fn install() {
+    if let Err(error) = metrics::set_recorder(&*RECORDER) {
+        warn!(error = %error, "failed to install the gateway metrics recorder");
+    }
+}
+
+/// Recorder of the inflater metrics of twilight-gateway.
+///
+/// Other metrics reported by twilight are ignored.
+#[derive(Debug, Default)]
+struct InflaterRecorder {
+    /// Last total reported by each gauge
+    totals: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl Recorder for InflaterRecorder {
+    fn describe_counter(&self, _key: KeyName, _unit: Option<Unit>, _description: &'static str) {}
+
+    fn describe_gauge(&self, _key: KeyName, _unit: Option<Unit>, _description: &'static str) {}
+
+    fn describe_histogram(&self, _key: KeyName, _unit: Option<Unit>, _description: &'static str) {}
+
+    fn register_counter(&self, _key: &Key) -> Counter {
+        Counter::noop()
+    }
+
+    fn register_gauge(&self, key: &Key) -> Gauge {
+        match inflater_metric(key.name()) {
+            Some((metric, help, shard)) => Gauge::from_arc(Arc::new(InflaterGauge {
+                key: key.name().to_owned(),
+                metric,
+                help,
+                shard: shard.to_owned(),
+                totals: self.totals.clone(),
+            })),
+            None => Gauge::noop(),
+        }
+    }
+
+    fn register_histogram(&self, _key: &Key) -> Histogram {
+        Histogram::noop()
+    }
+}
+
+/// Get the counter corresponding to an inflater gauge.
+///
+/// The gauges are named `Inflater-In-{shard}` for the compressed bytes and
+/// `Inflater-Out-{shard}` for the decompressed bytes. Returns the name of the
+/// counter, its description and the shard id.
+fn inflater_metric(name: &str) -> Option<(&'static str, &'static str, &str)> {
+    let (kind, shard) = name.strip_prefix("Inflater-")?.split_once('-')?;
+
+    match kind {
+        "In" => Some((
+            COMPRESSED_BYTES,
+            "Size of the gateway payloads before decompression",
+            shard,
+        )),
+        "Out" => Some((
+            DECOMPRESSED_BYTES,
+            "Size of the gateway payloads after decompression",
+            shard,
+        )),
+        _ => None,
+    }
+}
+
+/// Gauge of the total size of the payloads received by an inflater.
+struct InflaterGauge {
+    key: String,
+    metric: &'static str,
+    help: &'static str,
+    shard: String,
+    totals: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl GaugeFn for InflaterGauge {
+    fn increment(&self, _value: f64) {}
+
+    fn decrement(&self, _value: f64) {}
+
+    fn set(&self, value: f64) {
+        let value = value as u64;
+        let last = self
+            .totals
+            .lock()
+            .expect("inflater totals poisoned")
+            .insert(self.key.clone(), value)
+            .unwrap_or_default();
+
+        // The inflater is reset when the shard reconnects, the total then
+        // starts again from zero.
+        let received = value.checked_sub(last).unwrap_or(value);
+
+        registry().add_counter(self.metric, self.help, &[("shard", &self.shard)], received);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use raidprotect_model::metrics::SeriesValue;
+
+    use super::*;
+
+    fn counter(metric: &str, shard: &str) -> Option<u64> {
+        let families = registry().snapshot();
+        let family = families.into_iter().find(|family| family.name == metric)?;
+
+        family
+            .series
+            .into_iter()
+            .find_map(|(labels, value)| match (labels.as_slice(), value) {
+                ([(name, value)], SeriesValue::Counter(count))
+                    if name == "shard" && value == shard =>
+                {
+                    Some(count)
+                }
+                _ => None,
+            })
+    }
+
+    #[test]
+    fn test_inflater_metric() {
+        assert_eq!(
+            inflater_metric("Inflater-In-3").map(|(metric, _, shard)| (metric, shard)),
+            Some((COMPRESSED_BYTES, "3"))
+        );
+        assert_eq!(
+            inflater_metric("Inflater-Out-3").map(|(metric, _, shard)| (metric, shard)),
+            Some((DECOMPRESSED_BYTES, "3"))
+        );
+        assert!(inflater_metric("Inflater-Capacity-3").is_none());
+        assert!(inflater_metric("GatewayEvent").is_none());
+    }
+
+    #[test]
+    fn test_inflater_gauge() {
+        let recorder = InflaterRecorder::default();
+        let compressed = recorder.register_gauge(&Key::from_name("Inflater-In-42"));
+        let decompressed = recorder.register_gauge(&Key::from_name("Inflater-Out-42"));
+
+        compressed.set(100.0);
+        decompressed.set(1000.0);
+        compressed.set(150.0);
+        compressed.set(20.0); // shard reconnected
+
+        assert_eq!(counter(COMPRESSED_BYTES, "42"), Some(170));
+        assert_eq!(counter(DECOMPRESSED_BYTES, "42"), Some(1000));
+    }
+}
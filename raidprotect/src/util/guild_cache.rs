@@ -0,0 +1,105 @@
+//! Ensure a guild is present in the cache.
+//!
+//! Guilds are inserted in the cache when the gateway sends a `GuildCreate`
+//! event. If an interaction is received before (for example just after a
+//! restart), the guild is missing from the cache and most operations would
+//! fail. This module exports [`ensure_guild_cached`] that fetch the missing
+//! guild from the Discord API. Interactions only wait for it for a short time
+//! (see [`GuildCache`]), the guild keeps being fetched in the background.
+//!
+//! [`GuildCache`]: crate::interaction::middleware::GuildCache
+//!
+//! Like for the logs channel, a simple locking mechanism is used to fetch each
+//! guild only once when multiple interactions are received at the same time.
+//!
+//! The `GuildCreate` event of large guilds only contains some of their members.
+//! [`warm_up_guild`] requests the other members from the gateway, so that they
+//! are cached before the first moderation command is used in the guild.
+
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use once_cell::sync::Lazy;
+use raidprotect_model::cache::discord::CachedGuild;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{debug, error, trace};
+use twilight_model::id::{marker::GuildMarker, Id};
+
+use crate::cluster::ClusterState;
+
+type PendingGuildsMap = HashMap<Id<GuildMarker>, broadcast::Sender<bool>>;
+
+/// Guild hydration queue.
+///
+/// This hold a list of guilds being fetched from the Discord API. A
+/// [`broadcast::Sender`] is hold to notify whether the guild has been cached.
+static PENDING_GUILDS: Lazy<RwLock<PendingGuildsMap>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Ensure a guild is present in the cache, fetching it if missing.
+///
+/// See the [module documentation](self) for more information.
+pub async fn ensure_guild_cached(
+    state: &ClusterState,
+    guild: Id<GuildMarker>,
+) -> Result<(), anyhow::Error> {
+    if state.cache.get::<CachedGuild>(&guild).await?.is_some() {
+        return Ok(());
+    }
+
+    // If the guild is already being fetched, wait for the result. Otherwise,
+    // add a lock to the pending guilds map.
+    let sender = {
+        let mut pending_guilds = PENDING_GUILDS.write().await;
+
+        match pending_guilds.get(&guild) {
+            Some(sender) => Err(sender.subscribe()),
+            None => {
+                let (sender, _) = broadcast::channel(1);
+                pending_guilds.insert(guild, sender.clone());
+
+                Ok(sender)
+            }
+        }
+    };
+
+    let sender = match sender {
+        Ok(sender) => sender,
+        Err(mut rx) => {
+            trace!(guild = ?guild, "waiting for guild to be cached");
+
+            return match rx.recv().await {
+                Ok(true) => Ok(()),
+                _ => Err(anyhow!("error while waiting for guild hydration")),
+            };
+        }
+    };
+
+    debug!(guild = ?guild, "guild missing from cache, fetching it");
+    let result = state
+        .cache
+        .hydrate_guild(&state.http, guild, state.current_user)
+        .await;
+
+    // Notify pending tasks and remove the lock.
+    sender.send(result.is_ok()).ok();
+    PENDING_GUILDS.write().await.remove(&guild);
+
+    result
+}
+
+/// Request all the members of a large guild from the gateway.
+///
+/// The members are sent in `MemberChunk` events, which are inserted in the
+/// cache like other member events. The request is rate-limited by the shard
+/// and waits for all the chunks, so this function should be run in a
+/// background task.
+pub async fn warm_up_guild(state: &ClusterState, guild: Id<GuildMarker>) {
+    match state.request_members(guild).await {
+        Ok(members) => debug!(
+            guild = ?guild,
+            "received {} guild members",
+            members.members.len()
+        ),
+        Err(error) => error!(error = ?error, guild = ?guild, "failed to request guild members"),
+    }
+}
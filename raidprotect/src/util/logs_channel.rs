@@ -6,6 +6,10 @@
 //! be automatically created. If a channel named `raidprotect-logs` is already
 //! present, it will be reused.
 //!
+//! Each category of logs can be sent to a dedicated channel. If a dedicated
+//! channel is deleted, a new one named `raidprotect-logs-<category>` is
+//! created.
+//!
 //! A simple locking mechanism is used to prevent multiple channels to be created
 //! at the same time.
 
@@ -13,7 +17,10 @@ use std::collections::HashMap;
 
 use anyhow::{anyhow, Context};
 use once_cell::sync::Lazy;
-use raidprotect_model::cache::discord::CachedChannel;
+use raidprotect_model::{
+    cache::discord::CachedChannel,
+    database::model::{GuildConfig, LogCategory},
+};
 use tokio::sync::{broadcast, RwLock};
 use tracing::{error, trace, warn};
 use twilight_model::{
@@ -33,12 +40,17 @@ use twilight_model::{
 };
 use twilight_util::builder::embed::EmbedBuilder;
 
-use crate::{cluster::ClusterState, interaction::embed::COLOR_RED, translations::Lang};
+use crate::{
+    cluster::ClusterState,
+    interaction::{embed::COLOR_RED, util::GuildConfigExt},
+    translations::Lang,
+};
 
 /// Default logs channel name.
 const DEFAULT_LOGS_NAME: &str = "raidprotect-logs";
 
-type PendingChannelsMap = HashMap<Id<GuildMarker>, broadcast::Sender<Id<ChannelMarker>>>;
+type PendingChannelsMap =
+    HashMap<(Id<GuildMarker>, Option<LogCategory>), broadcast::Sender<Id<ChannelMarker>>>;
 
 /// Logs channel creation queue.
 ///
@@ -47,20 +59,40 @@ type PendingChannelsMap = HashMap<Id<GuildMarker>, broadcast::Sender<Id<ChannelM
 static PENDING_CHANNELS: Lazy<RwLock<PendingChannelsMap>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
 
-/// Get the logs channel of a guild.
+/// Get the logs channel of a guild for a category of logs.
 ///
-/// The `lang` argument should be the guild language, not the user language.
+/// If the category has a dedicated channel (see [`LogChannels`]), it is
+/// returned or created again if it has been deleted. Otherwise, the default
+/// logs channel is used.
 ///
 /// See the [module documentation](super) for more information.
+///
+/// [`LogChannels`]: raidprotect_model::database::model::LogChannels
 pub async fn guild_logs_channel(
     state: &ClusterState,
-    guild: Id<GuildMarker>,
-    logs_channel: Option<Id<ChannelMarker>>,
-    lang: Lang,
+    config: &GuildConfig,
+    category: LogCategory,
 ) -> Result<Id<ChannelMarker>, anyhow::Error> {
+    match config.logs.channels.get(category) {
+        Some(channel) => logs_channel(state, config, Some(channel), Some(category)).await,
+        None => logs_channel(state, config, config.logs_chan, None).await,
+    }
+}
+
+/// Get a logs channel, creating it if it does not exist.
+///
+/// The `category` is [`None`] for the default logs channel.
+async fn logs_channel(
+    state: &ClusterState,
+    config: &GuildConfig,
+    channel: Option<Id<ChannelMarker>>,
+    category: Option<LogCategory>,
+) -> Result<Id<ChannelMarker>, anyhow::Error> {
+    let guild = config.id;
+
     // If a channel is already configured, ensure it exists and return it.
-    if let Some(channel) = logs_channel {
-        let cached = state.cache.get::<CachedChannel>(&channel).await?;
+    if let Some(channel) = channel {
+        let cached = state.cache_http(guild).channel(channel).await?;
 
         if cached.is_some() {
             return Ok(channel);
@@ -72,14 +104,14 @@ pub async fn guild_logs_channel(
     // send the created channel to all the pending tasks.
     let receiver = {
         let pending_channels = PENDING_CHANNELS.read().await;
-        let sender = pending_channels.get(&guild);
+        let sender = pending_channels.get(&(guild, category));
 
         sender.map(|s| s.subscribe())
     };
 
     // If a channel is being created, wait and return it's id
     if let Some(mut rx) = receiver {
-        trace!(guild = ?guild, "waiting for logs channel to be created");
+        trace!(guild = ?guild, category = ?category, "waiting for logs channel to be created");
 
         match rx.recv().await {
             Ok(channel) => return Ok(channel),
@@ -88,45 +120,58 @@ pub async fn guild_logs_channel(
     }
 
     // Create a new logs channel
-    trace!(guild = ?guild, "creating logs channel");
+    trace!(guild = ?guild, category = ?category, "creating logs channel");
+
+    let sender = {
+        let mut pending_channels = PENDING_CHANNELS.write().await;
+        let (sender, _) = broadcast::channel(1);
 
-    configure_logs_channel(state, guild, lang).await
+        pending_channels.insert((guild, category), sender.clone());
+
+        sender
+    };
+
+    let result = configure_logs_channel(state, guild, category, config.lang()).await;
+
+    // Notify pending tasks that the channel has been created.
+    PENDING_CHANNELS.write().await.remove(&(guild, category));
+    if let Ok(channel) = result {
+        sender.send(channel).ok();
+    }
+
+    result
 }
 
 /// Try to find an existing logs channel, or create a new one.
 async fn configure_logs_channel(
     state: &ClusterState,
     guild: Id<GuildMarker>,
+    category: Option<LogCategory>,
     lang: Lang,
 ) -> Result<Id<ChannelMarker>, anyhow::Error> {
-    // Add a lock to the pending channels map.
-    let sender = {
-        let mut pending_channels = PENDING_CHANNELS.write().await;
-        let (sender, _) = broadcast::channel(1);
-
-        pending_channels.insert(guild, sender.clone());
-
-        sender
+    let name = match category {
+        Some(category) => format!("{DEFAULT_LOGS_NAME}-{}", category.name()),
+        None => DEFAULT_LOGS_NAME.to_owned(),
     };
 
     // Try to find an existing channel .
     let guild_channels = state.cache.guild_channels(guild).await?;
-    let logs_channel = guild_channels.iter().find(|channel| {
-        channel.kind == ChannelType::GuildText && channel.name == DEFAULT_LOGS_NAME
-    });
+    let logs_channel = guild_channels
+        .iter()
+        .find(|channel| channel.kind == ChannelType::GuildText && channel.name == name);
 
     let logs_channel = match logs_channel {
         Some(channel) => update_logs_permissions(state, channel, guild).await,
-        None => create_logs_channel(state, guild, lang).await?,
+        None => create_logs_channel(state, guild, &name, lang).await?,
     };
 
     // Update the guild configuration
-    let mut config = state.database.get_guild_or_create(guild).await?;
-    config.logs_chan = Some(logs_channel);
-    state.database.update_guild(&config).await?;
-
-    // Notify pending tasks that the channel has been created.
-    sender.send(logs_channel).ok();
+    let mut config = state.guild_config(guild).await?;
+    match category {
+        Some(category) => config.logs.channels.set(category, Some(logs_channel)),
+        None => config.logs_chan = Some(logs_channel),
+    }
+    state.update_guild_config(&config).await?;
 
     Ok(logs_channel)
 }
@@ -161,6 +206,7 @@ async fn update_logs_permissions(
 async fn create_logs_channel(
     state: &ClusterState,
     guild: Id<GuildMarker>,
+    name: &str,
     lang: Lang,
 ) -> Result<Id<ChannelMarker>, anyhow::Error> {
     // Hide the channel to the everyone role.
@@ -184,7 +230,7 @@ async fn create_logs_channel(
 
     let channel = match state
         .cache_http(guild)
-        .create_guild_channel(DEFAULT_LOGS_NAME)
+        .create_guild_channel(name)
         .await?
         .kind(ChannelType::GuildText)
         .permission_overwrites(&permission_overwrite)
@@ -0,0 +1,255 @@
+//! Request guild members from the gateway.
+//!
+//! Guild members can be requested with the `RequestGuildMembers` gateway
+//! command, and are sent by Discord in one or more `MemberChunk` events. The
+//! members are inserted in the cache when the events are processed.
+//!
+//! [`MemberRequests`] tracks the pending requests with a unique nonce, so that
+//! the caller can wait until all the chunks of its request are received. This
+//! is used to warm up the cache of large guilds and to check which users of a
+//! list are members of a guild.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use tokio::sync::oneshot;
+use tracing::{debug, trace};
+use twilight_gateway::Cluster;
+use twilight_model::{
+    gateway::payload::{incoming::MemberChunk, outgoing::RequestGuildMembers},
+    id::{
+        marker::{GuildMarker, UserMarker},
+        Id,
+    },
+};
+
+/// Maximum time to wait for all the chunks of a request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Members received in response to a request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestedMembers {
+    /// Ids of the received members.
+    pub members: Vec<Id<UserMarker>>,
+    /// Ids of the requested users that are not members of the guild.
+    pub not_found: Vec<Id<UserMarker>>,
+}
+
+/// Pending member request.
+#[derive(Debug)]
+struct PendingRequest {
+    /// Members received in the previous chunks.
+    received: RequestedMembers,
+    /// Number of received chunks.
+    chunks: u32,
+    /// Sender notified once all the chunks are received.
+    sender: oneshot::Sender<RequestedMembers>,
+}
+
+/// Tracker of the pending member requests.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug, Clone, Default)]
+pub struct MemberRequests {
+    pending: Arc<Mutex<HashMap<String, PendingRequest>>>,
+    counter: Arc<AtomicU64>,
+}
+
+impl MemberRequests {
+    /// Request all the members of a guild.
+    pub async fn all(
+        &self,
+        cluster: &Cluster,
+        guild_id: Id<GuildMarker>,
+    ) -> Result<RequestedMembers, anyhow::Error> {
+        let nonce = self.nonce();
+        let request = RequestGuildMembers::builder(guild_id)
+            .nonce(nonce.clone())
+            .query("", None);
+
+        self.send(cluster, guild_id, nonce, request).await
+    }
+
+    /// Request members of a guild by id.
+    ///
+    /// Up to 100 users can be requested at once.
+    pub async fn by_id(
+        &self,
+        cluster: &Cluster,
+        guild_id: Id<GuildMarker>,
+        user_ids: Vec<Id<UserMarker>>,
+    ) -> Result<RequestedMembers, anyhow::Error> {
+        let nonce = self.nonce();
+        let request = RequestGuildMembers::builder(guild_id)
+            .nonce(nonce.clone())
+            .user_ids(user_ids)?;
+
+        self.send(cluster, guild_id, nonce, request).await
+    }
+
+    /// Handle a received [`MemberChunk`].
+    ///
+    /// Chunks that were not requested with [`MemberRequests`] are ignored.
+    pub fn receive(&self, chunk: &MemberChunk) {
+        let nonce = match &chunk.nonce {
+            Some(nonce) => nonce,
+            None => return,
+        };
+
+        let mut pending = self.pending.lock().expect("member requests poisoned");
+        let request = match pending.get_mut(nonce) {
+            Some(request) => request,
+            None => return,
+        };
+
+        request
+            .received
+            .members
+            .extend(chunk.members.iter().map(|member| member.user.id));
+        request.received.not_found.extend(&chunk.not_found);
+        request.chunks += 1;
+
+        trace!(
+            nonce,
+            "received member chunk {}/{}",
+            request.chunks,
+            chunk.chunk_count
+        );
+
+        if request.chunks >= chunk.chunk_count {
+            if let Some(request) = pending.remove(nonce) {
+                request.sender.send(request.received).ok();
+            }
+        }
+    }
+
+    /// Send a request and wait for all its chunks.
+    async fn send(
+        &self,
+        cluster: &Cluster,
+        guild_id: Id<GuildMarker>,
+        nonce: String,
+        request: RequestGuildMembers,
+    ) -> Result<RequestedMembers, anyhow::Error> {
+        let total_shards = cluster.config().shard_scheme().total();
+
+        let (sender, receiver) = oneshot::channel();
+        self.pending
+            .lock()
+            .expect("member requests poisoned")
+            .insert(
+                nonce.clone(),
+                PendingRequest {
+                    received: RequestedMembers::default(),
+                    chunks: 0,
+                    sender,
+                },
+            );
+
+        debug!(guild = ?guild_id, nonce, "requesting guild members");
+        let result = cluster
+            .command(shard_id(guild_id, total_shards), &request)
+            .await;
+
+        if let Err(error) = result {
+            self.remove(&nonce);
+            return Err(error.into());
+        }
+
+        match tokio::time::timeout(REQUEST_TIMEOUT, receiver).await {
+            Ok(Ok(members)) => Ok(members),
+            _ => {
+                self.remove(&nonce);
+                Err(anyhow!("timed out waiting for member chunks"))
+            }
+        }
+    }
+
+    /// Generate a unique request nonce.
+    fn nonce(&self) -> String {
+        format!("rp-{}", self.counter.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Remove a pending request.
+    fn remove(&self, nonce: &str) {
+        self.pending
+            .lock()
+            .expect("member requests poisoned")
+            .remove(nonce);
+    }
+}
+
+/// Get the id of the shard receiving the events of a guild.
+fn shard_id(guild: Id<GuildMarker>, total_shards: u64) -> u64 {
+    (guild.get() >> 22) % total_shards
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(nonce: &str, index: u32, count: u32, not_found: Vec<u64>) -> MemberChunk {
+        MemberChunk {
+            chunk_count: count,
+            chunk_index: index,
+            guild_id: Id::new(1),
+            members: Vec::new(),
+            nonce: Some(nonce.to_owned()),
+            not_found: not_found.into_iter().map(Id::new).collect(),
+            presences: Vec::new(),
+        }
+    }
+
+    fn pending(requests: &MemberRequests, nonce: &str) -> oneshot::Receiver<RequestedMembers> {
+        let (sender, receiver) = oneshot::channel();
+        requests.pending.lock().unwrap().insert(
+            nonce.to_owned(),
+            PendingRequest {
+                received: RequestedMembers::default(),
+                chunks: 0,
+                sender,
+            },
+        );
+
+        receiver
+    }
+
+    #[test]
+    fn test_shard_id() {
+        let guild = Id::new(197038439483310086);
+
+        assert_eq!(shard_id(guild, 1), 0);
+        assert_eq!(shard_id(guild, 2), (197038439483310086 >> 22) % 2);
+        assert_eq!(shard_id(Id::new(1), 16), 0);
+    }
+
+    #[test]
+    fn test_receive_chunks() {
+        let requests = MemberRequests::default();
+        let mut receiver = pending(&requests, "rp-0");
+
+        requests.receive(&chunk("rp-0", 0, 2, vec![2]));
+        assert!(receiver.try_recv().is_err());
+
+        requests.receive(&chunk("rp-1", 1, 2, vec![3]));
+        requests.receive(&chunk("rp-0", 1, 2, vec![4]));
+
+        let received = receiver.try_recv().unwrap();
+        assert_eq!(received.not_found, vec![Id::new(2), Id::new(4)]);
+        assert!(requests.pending.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_nonce_unique() {
+        let requests = MemberRequests::default();
+
+        assert_ne!(requests.nonce(), requests.nonce());
+    }
+}
@@ -2,10 +2,21 @@
 //!
 //! This module provides various utilities that doesn't fit in other modules.
 
+mod format;
+#[cfg(feature = "gateway-compression")]
+pub mod gateway_metrics;
+mod guild_cache;
 mod logs_channel;
+mod member_request;
+pub mod ratelimit;
 pub mod resource;
+mod role_batch;
 pub mod shutdown;
 mod text;
 
+pub use format::{format_duration, format_number};
+pub use guild_cache::{ensure_guild_cached, warm_up_guild};
 pub use logs_channel::guild_logs_channel;
+pub use member_request::{MemberRequests, RequestedMembers};
+pub use role_batch::{BatchProgress, RoleBatch};
 pub use text::TextProcessExt;
@@ -0,0 +1,193 @@
+//! Rate-limit budget of mass actions.
+//!
+//! Mass actions (such as banning the accounts of a raid or assigning roles to
+//! all the members of a guild) send bursts of HTTP requests. The HTTP client
+//! handles the per-route rate limits, but a single guild cleaning up a raid
+//! could consume the whole global rate limit of the bot and get all the other
+//! requests rejected.
+//!
+//! The [`RateLimiter`] distributes a budget of requests between guilds using
+//! token buckets: a global bucket limits the requests of all the mass actions,
+//! and a bucket per guild prevents a single guild from using the whole global
+//! budget. Mass actions must call [`RateLimiter::acquire`] before each request.
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tracing::trace;
+use twilight_model::id::{marker::GuildMarker, Id};
+
+/// Requests per second allowed for all the mass actions.
+///
+/// Discord allows 50 requests per second, half of them are kept for other
+/// requests.
+const GLOBAL_RATE: f64 = 25.0;
+
+/// Requests per second allowed for the mass actions of a single guild.
+const GUILD_RATE: f64 = 5.0;
+
+/// Maximum number of requests a guild can send at once.
+const GUILD_BURST: f64 = 10.0;
+
+/// Number of guild buckets after which idle buckets are removed.
+const MAX_IDLE_BUCKETS: usize = 256;
+
+/// Token bucket.
+#[derive(Debug, Clone)]
+struct Bucket {
+    /// Maximum number of tokens.
+    capacity: f64,
+    /// Tokens added per second.
+    rate: f64,
+    /// Available tokens.
+    tokens: f64,
+    /// Last time the tokens were refilled.
+    updated_at: Instant,
+}
+
+impl Bucket {
+    /// Initialize a new full [`Bucket`].
+    fn new(capacity: f64, rate: f64, now: Instant) -> Self {
+        Self {
+            capacity,
+            rate,
+            tokens: capacity,
+            updated_at: now,
+        }
+    }
+
+    /// Refill the bucket according to the elapsed time.
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at);
+
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.rate).min(self.capacity);
+        self.updated_at = now;
+    }
+
+    /// Time to wait until a token is available.
+    fn wait_time(&self) -> Duration {
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / self.rate)
+        }
+    }
+
+    /// Whether the bucket is full, meaning it has not been used recently.
+    fn is_idle(&self) -> bool {
+        self.tokens >= self.capacity
+    }
+}
+
+/// Rate-limit budget of mass actions.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug)]
+pub struct RateLimiter {
+    global: Mutex<Bucket>,
+    guilds: Mutex<HashMap<Id<GuildMarker>, Bucket>>,
+}
+
+impl RateLimiter {
+    /// Initialize a new [`RateLimiter`].
+    pub fn new() -> Self {
+        Self {
+            global: Mutex::new(Bucket::new(GLOBAL_RATE, GLOBAL_RATE, Instant::now())),
+            guilds: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait until a request of a mass action can be sent in a guild.
+    pub async fn acquire(&self, guild_id: Id<GuildMarker>) {
+        loop {
+            let wait = self.try_acquire(guild_id, Instant::now());
+
+            if wait.is_zero() {
+                return;
+            }
+
+            trace!(guild = ?guild_id, wait = ?wait, "mass action rate-limited");
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take a token from the global and guild buckets.
+    ///
+    /// Returns the time to wait before retrying if a bucket is empty, or
+    /// [`Duration::ZERO`] if the tokens were taken.
+    fn try_acquire(&self, guild_id: Id<GuildMarker>, now: Instant) -> Duration {
+        let mut global = self.global.lock().expect("rate limiter poisoned");
+        let mut guilds = self.guilds.lock().expect("rate limiter poisoned");
+
+        if guilds.len() > MAX_IDLE_BUCKETS {
+            guilds.retain(|_, bucket| {
+                bucket.refill(now);
+                !bucket.is_idle()
+            });
+        }
+
+        let guild = guilds
+            .entry(guild_id)
+            .or_insert_with(|| Bucket::new(GUILD_BURST, GUILD_RATE, now));
+
+        global.refill(now);
+        guild.refill(now);
+
+        let wait = global.wait_time().max(guild.wait_time());
+        if wait.is_zero() {
+            global.tokens -= 1.0;
+            guild.tokens -= 1.0;
+        }
+
+        wait
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guild_burst() {
+        let limiter = RateLimiter::new();
+        let now = Instant::now();
+        let guild = Id::new(1);
+
+        for _ in 0..GUILD_BURST as usize {
+            assert_eq!(limiter.try_acquire(guild, now), Duration::ZERO);
+        }
+
+        assert_eq!(
+            limiter.try_acquire(guild, now),
+            Duration::from_secs_f64(1.0 / GUILD_RATE)
+        );
+        assert_eq!(
+            limiter.try_acquire(guild, now + Duration::from_secs_f64(1.0 / GUILD_RATE)),
+            Duration::ZERO
+        );
+
+        // Other guilds are not limited by the guild bucket.
+        assert_eq!(limiter.try_acquire(Id::new(2), now), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_global_limit() {
+        let limiter = RateLimiter::new();
+        let now = Instant::now();
+
+        for guild in 1..=GLOBAL_RATE as u64 {
+            assert_eq!(limiter.try_acquire(Id::new(guild), now), Duration::ZERO);
+        }
+
+        assert!(!limiter.try_acquire(Id::new(100), now).is_zero());
+    }
+}
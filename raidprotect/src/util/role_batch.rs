@@ -0,0 +1,124 @@
+//! Batch role assignment.
+//!
+//! The [`RoleBatch`] executor adds roles to all the members of a guild. The
+//! members are fetched by pages from the Discord API, and the roles of each
+//! member are updated with a single request. Rate limits are handled by the
+//! HTTP client, and the updates share the budget of mass actions (see
+//! [`ratelimit`](super::ratelimit)).
+//!
+//! The progress is reported with a [`watch`] channel, so that the caller can
+//! display it without being notified for each member.
+
+use tokio::sync::watch;
+use tracing::{debug, error};
+use twilight_http::request::AuditLogReason;
+use twilight_model::id::{
+    marker::{GuildMarker, RoleMarker, UserMarker},
+    Id,
+};
+
+use crate::cluster::ClusterState;
+
+/// Number of members fetched per request.
+const PAGE_SIZE: u16 = 1000;
+
+/// Progress of a [`RoleBatch`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchProgress {
+    /// Number of processed members.
+    pub processed: u64,
+    /// Number of members whose roles have been updated.
+    pub updated: u64,
+    /// Number of members whose roles could not be updated.
+    pub failed: u64,
+}
+
+/// Batch role assignment executor.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug, Clone)]
+pub struct RoleBatch {
+    /// Guild in which the roles are assigned.
+    pub guild_id: Id<GuildMarker>,
+    /// Roles added to each member.
+    pub roles: Vec<Id<RoleMarker>>,
+    /// Members with one of these roles are skipped.
+    pub exclude: Vec<Id<RoleMarker>>,
+    /// Reason displayed in the audit log.
+    pub reason: String,
+}
+
+impl RoleBatch {
+    /// Add the roles to all the members of the guild.
+    ///
+    /// Bots and members that already have all the roles are skipped. Errors
+    /// on a single member are counted in [`BatchProgress::failed`], only
+    /// errors when fetching the members stop the execution.
+    pub async fn run(
+        &self,
+        state: &ClusterState,
+        progress: &watch::Sender<BatchProgress>,
+    ) -> Result<BatchProgress, anyhow::Error> {
+        let mut current = BatchProgress::default();
+        let mut after: Option<Id<UserMarker>> = None;
+
+        loop {
+            let mut request = state.http.guild_members(self.guild_id).limit(PAGE_SIZE)?;
+            if let Some(after) = after {
+                request = request.after(after);
+            }
+
+            let members = request.exec().await?.model().await?;
+            let last_page = members.len() < PAGE_SIZE.into();
+            after = members.last().map(|member| member.user.id);
+
+            for member in members {
+                current.processed += 1;
+
+                if member.user.bot || member.roles.iter().any(|role| self.exclude.contains(role)) {
+                    continue;
+                }
+
+                let mut roles = member.roles;
+                let missing = self
+                    .roles
+                    .iter()
+                    .filter(|role| !roles.contains(role))
+                    .copied()
+                    .collect::<Vec<_>>();
+
+                if missing.is_empty() {
+                    continue;
+                }
+                roles.extend(missing);
+
+                state.ratelimit.acquire(self.guild_id).await;
+                let result = state
+                    .http
+                    .update_guild_member(self.guild_id, member.user.id)
+                    .roles(&roles)
+                    .reason(&self.reason)?
+                    .exec()
+                    .await;
+
+                match result {
+                    Ok(_) => current.updated += 1,
+                    Err(error) => {
+                        error!(error = ?error, member = ?member.user.id, "failed to update member roles");
+                        current.failed += 1;
+                    }
+                }
+            }
+
+            progress.send_replace(current);
+
+            if last_page {
+                break;
+            }
+        }
+
+        debug!(guild = ?self.guild_id, progress = ?current, "role batch completed");
+
+        Ok(current)
+    }
+}
@@ -18,6 +18,13 @@
 //!
 //! Internally, a [`broadcast`] channel is used to emit shutdown signal, and a [`mpsc`]
 //! channel is used to know when all subtasks are dropped.
+//!
+//! ## Stages
+//! Subscribers are registered in a [`ShutdownStage`]. The stages are shut down
+//! one after the other, each with its own timeout: the tasks of a stage are
+//! only notified once the tasks of the previous stage have stopped. This
+//! ensures events being processed can still write to the cache and the
+//! database during a deploy.
 
 use std::{io, time::Duration};
 
@@ -25,6 +32,7 @@ use tokio::{
     sync::{broadcast, mpsc},
     time::sleep,
 };
+use tracing::{debug, warn};
 
 /// Wait until a shutdown signal is received.
 ///
@@ -55,6 +63,36 @@ pub async fn wait_shutdown() -> io::Result<()> {
     tokio::signal::ctrl_c().await
 }
 
+/// Stage of the shutdown.
+///
+/// Stages are shut down in the order of declaration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ShutdownStage {
+    /// Stop receiving gateway events and process the queued events.
+    Events,
+    /// Stop the anti-spam background tasks.
+    AntiSpam,
+    /// Stop the tasks writing to the cache.
+    Cache,
+    /// Stop the tasks writing to the database.
+    Database,
+}
+
+impl ShutdownStage {
+    /// All the stages, in shutdown order.
+    pub const ALL: [Self; 4] = [Self::Events, Self::AntiSpam, Self::Cache, Self::Database];
+
+    /// Maximum time to wait for the tasks of the stage to stop.
+    pub fn timeout(self) -> Duration {
+        match self {
+            Self::Events => Duration::from_secs(10),
+            Self::AntiSpam => Duration::from_secs(3),
+            Self::Cache => Duration::from_secs(3),
+            Self::Database => Duration::from_secs(5),
+        }
+    }
+}
+
 /// Shutdown manager.
 ///
 /// This type should be initialized by each task that depend on
@@ -62,6 +100,14 @@ pub async fn wait_shutdown() -> io::Result<()> {
 /// and also waiting until they gracefully stopped.
 #[derive(Debug)]
 pub struct Shutdown {
+    /// Channels of each stage, in shutdown order.
+    stages: Vec<StageChannels>,
+}
+
+/// Channels of a [`ShutdownStage`].
+#[derive(Debug)]
+struct StageChannels {
+    stage: ShutdownStage,
     /// This sender is used to emit shutdown signal. The sender is
     /// dropped on shutdown to send a signal to all remaining receivers.
     notify: broadcast::Sender<()>,
@@ -74,50 +120,78 @@ pub struct Shutdown {
 impl Shutdown {
     /// Initialize a [`Shutdown`] manager.
     pub fn new() -> Self {
-        let (notify, _) = broadcast::channel(1);
-        let (sender, receiver) = mpsc::channel(1);
+        let stages = ShutdownStage::ALL
+            .into_iter()
+            .map(|stage| {
+                let (notify, _) = broadcast::channel(1);
+                let (sender, receiver) = mpsc::channel(1);
 
-        Self {
-            notify,
-            sender,
-            receiver,
-        }
+                StageChannels {
+                    stage,
+                    notify,
+                    sender,
+                    receiver,
+                }
+            })
+            .collect();
+
+        Self { stages }
     }
 
-    /// Create a new [`ShutdownSubscriber`].
+    /// Create a new [`ShutdownSubscriber`] for a given stage.
     ///
     /// The returned notifier can be sent to subtasks to allow
     /// them to gracefully shutdown when requested. It is also used
     /// to track when all tasks are dropped.
-    pub fn subscriber(&self) -> ShutdownSubscriber {
+    pub fn subscriber(&self, stage: ShutdownStage) -> ShutdownSubscriber {
+        let channels = self
+            .stages
+            .iter()
+            .find(|channels| channels.stage == stage)
+            .expect("missing shutdown stage");
+
         ShutdownSubscriber {
             shutdown: false,
-            notify: self.notify.subscribe(),
-            _sender: self.sender.clone(),
+            notify: channels.notify.subscribe(),
+            _sender: channels.sender.clone(),
         }
     }
 
     /// Emit a shutdown signal.
     ///
-    /// When called, a shutdown signal is sent to all subtasks.
-    /// The function returns when all subtasks have gracefully
-    /// stopped or when the timeout is expired.
-    pub async fn shutdown(self, timeout: u64) -> bool {
-        // Extract channels to allow dropping them.
-        let Shutdown {
-            notify,
-            sender,
-            mut receiver,
-        } = self;
-
-        drop(notify); // Notify shutdown to subscribers
-        drop(sender); // Remaining senders are those held by subscribers
-
-        // Wait until all tasks are finished, or timeout is elapsed
-        tokio::select! {
-            _ = receiver.recv() => true,
-            _ = sleep(Duration::from_secs(timeout)) => false
+    /// When called, a shutdown signal is sent to the subtasks of each stage,
+    /// one stage after the other. The function returns when all subtasks have
+    /// gracefully stopped or when the timeouts are expired.
+    pub async fn shutdown(self) -> bool {
+        let mut graceful = true;
+
+        for channels in self.stages {
+            // Extract channels to allow dropping them.
+            let StageChannels {
+                stage,
+                notify,
+                sender,
+                mut receiver,
+            } = channels;
+
+            debug!(stage = ?stage, "shutting down stage");
+
+            drop(notify); // Notify shutdown to subscribers
+            drop(sender); // Remaining senders are those held by subscribers
+
+            // Wait until all tasks are finished, or timeout is elapsed
+            let stopped = tokio::select! {
+                _ = receiver.recv() => true,
+                _ = sleep(stage.timeout()) => false
+            };
+
+            if !stopped {
+                warn!(stage = ?stage, "shutdown stage timed out");
+                graceful = false;
+            }
         }
+
+        graceful
     }
 }
 
@@ -153,3 +227,36 @@ impl ShutdownSubscriber {
         self.shutdown = true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown_stages() {
+        let shutdown = Shutdown::new();
+        let events_stopped = Arc::new(AtomicBool::new(false));
+
+        let mut events = shutdown.subscriber(ShutdownStage::Events);
+        let stopped = events_stopped.clone();
+        tokio::spawn(async move {
+            events.wait_shutdown().await;
+            sleep(Duration::from_millis(50)).await;
+            stopped.store(true, Ordering::SeqCst);
+        });
+
+        let mut database = shutdown.subscriber(ShutdownStage::Database);
+        let database_task = tokio::spawn(async move {
+            database.wait_shutdown().await;
+            events_stopped.load(Ordering::SeqCst)
+        });
+
+        assert!(shutdown.shutdown().await);
+        assert!(database_task.await.unwrap());
+    }
+}
@@ -0,0 +1,331 @@
+//! Dashboard API.
+//!
+//! API routes require a logged in user or an API key (see the [`auth`]
+//! module). Routes scoped to a guild also require the user to have the
+//! `MANAGE_GUILD` permission in the guild, or the API key to be created for
+//! the guild with the route scope. This is checked by [`authorize_guild`].
+//!
+//! [`auth`]: crate::auth
+
+use axum::{
+    async_trait,
+    extract::{FromRequest, Path, RequestParts},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use raidprotect_model::{
+    cache::{
+        discord::{CachedGuild, CachedMember},
+        model::rpc::RpcError,
+        CacheClient,
+    },
+    database::model::ApiScope,
+};
+use serde::Serialize;
+use tracing::error;
+use twilight_model::{
+    guild::Permissions,
+    id::{marker::GuildMarker, Id},
+    util::ImageHash,
+};
+
+use crate::auth::Auth;
+
+/// Extract the `:guild_id` parameter of the route path.
+///
+/// The [`Id`] type cannot be deserialized by the [`Path`] extractor, so the
+/// parameter is parsed as an integer. Invalid ids are rejected with
+/// `404 Not Found`.
+#[derive(Debug, Clone, Copy)]
+pub struct GuildPath(pub Id<GuildMarker>);
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for GuildPath {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Path(guild_id) = Path::<u64>::from_request(req)
+            .await
+            .map_err(|_| ApiError::NotFound)?;
+
+        Id::new_checked(guild_id)
+            .map(GuildPath)
+            .ok_or(ApiError::NotFound)
+    }
+}
+
+/// Guild returned by the API.
+#[derive(Debug, Serialize)]
+pub struct GuildInfo {
+    id: Id<GuildMarker>,
+    name: String,
+    icon: Option<ImageHash>,
+}
+
+/// Get a guild managed by the user.
+pub async fn guild(
+    GuildPath(guild_id): GuildPath,
+    auth: Auth,
+    Extension(cache): Extension<CacheClient>,
+) -> Result<Json<GuildInfo>, ApiError> {
+    let guild = authorize_guild(&auth, guild_id, ApiScope::Guild, &cache).await?;
+
+    Ok(Json(GuildInfo {
+        id: guild.id,
+        name: guild.name,
+        icon: guild.icon,
+    }))
+}
+
+/// Check that the caller is allowed to access a guild.
+///
+/// Returns `404 Not Found` if the bot is not in the guild, and
+/// `403 Forbidden` if the user is not a member of the guild or is missing the
+/// `MANAGE_GUILD` permission. API keys must have been created for the guild
+/// and have the given `scope`.
+pub async fn authorize_guild(
+    auth: &Auth,
+    guild_id: Id<GuildMarker>,
+    scope: ApiScope,
+    cache: &CacheClient,
+) -> Result<CachedGuild, ApiError> {
+    if let Auth::ApiKey(key) = auth {
+        if key.guild_id != guild_id || !key.scopes.contains(&scope) {
+            return Err(ApiError::Forbidden);
+        }
+    }
+
+    let guild = match cache.get::<CachedGuild>(&guild_id).await? {
+        Some(guild) if !guild.unavailable => guild,
+        _ => return Err(ApiError::NotFound),
+    };
+
+    let session = match auth {
+        Auth::Session(session) => session,
+        Auth::ApiKey(_) => return Ok(guild),
+    };
+
+    let member = match cache
+        .get::<CachedMember>(&(guild_id, session.user_id))
+        .await?
+    {
+        Some(member) => member,
+        None => return Err(ApiError::Forbidden),
+    };
+
+    let permissions = cache
+        .permissions(guild_id)
+        .await?
+        .member(session.user_id, &member.roles)
+        .await?
+        .guild();
+
+    if !permissions.contains(Permissions::MANAGE_GUILD) {
+        return Err(ApiError::Forbidden);
+    }
+
+    Ok(guild)
+}
+
+/// Body of the `429 Too Many Requests` responses.
+#[derive(Debug, Serialize)]
+struct RateLimited {
+    error: &'static str,
+    /// Delay before the next allowed request (in seconds).
+    retry_after: u64,
+}
+
+/// Body of the `422 Unprocessable Entity` responses.
+#[derive(Debug, Serialize)]
+struct ActionFailed {
+    error: RpcError,
+}
+
+/// Error returned by the API routes.
+///
+/// Internal errors are logged and an internal server error is sent to the
+/// user.
+#[derive(Debug)]
+pub enum ApiError {
+    /// The request is invalid.
+    BadRequest,
+    /// The user is not logged in.
+    Unauthorized,
+    /// The user is not allowed to access the resource.
+    Forbidden,
+    /// The resource does not exist.
+    NotFound,
+    /// The request body is too large.
+    PayloadTooLarge,
+    /// The request body has an unknown size.
+    LengthRequired,
+    /// The client exceeded its rate limit.
+    TooManyRequests {
+        /// Delay before the next allowed request (in seconds).
+        retry_after: u64,
+    },
+    /// The bot did not respond in time.
+    Unavailable,
+    /// The bot failed to perform the requested action.
+    Action(RpcError),
+    /// An error occurred while processing the request.
+    Internal(anyhow::Error),
+}
+
+impl<E: Into<anyhow::Error>> From<E> for ApiError {
+    fn from(error: E) -> Self {
+        Self::Internal(error.into())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            ApiError::BadRequest => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden => StatusCode::FORBIDDEN,
+            ApiError::NotFound => StatusCode::NOT_FOUND,
+            ApiError::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::LengthRequired => StatusCode::LENGTH_REQUIRED,
+            ApiError::TooManyRequests { retry_after } => {
+                let body = RateLimited {
+                    error: "too_many_requests",
+                    retry_after,
+                };
+                let headers = [(header::RETRY_AFTER, retry_after.to_string())];
+
+                return (StatusCode::TOO_MANY_REQUESTS, headers, Json(body)).into_response();
+            }
+            ApiError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Action(RpcError::Forbidden) => StatusCode::FORBIDDEN,
+            ApiError::Action(RpcError::Internal) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::Action(error) => {
+                let body = ActionFailed { error };
+
+                return (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response();
+            }
+            ApiError::Internal(error) => {
+                error!(error = ?error, "error while processing api request");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+
+        status.into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use raidprotect_model::cache::{
+        discord::CachedRole, memory::MemoryCache, model::session::WebSession, CacheTtl,
+    };
+    use twilight_model::{id::marker::UserMarker, util::Timestamp};
+
+    use super::*;
+
+    const GUILD_ID: Id<GuildMarker> = Id::new(1);
+    const OWNER_ID: Id<UserMarker> = Id::new(10);
+    const MANAGER_ID: Id<UserMarker> = Id::new(11);
+    const MEMBER_ID: Id<UserMarker> = Id::new(12);
+
+    fn role(id: u64, permissions: Permissions) -> CachedRole {
+        CachedRole {
+            id: Id::new(id),
+            guild_id: GUILD_ID,
+            name: format!("role {id}"),
+            color: 0,
+            icon: None,
+            unicode_emoji: None,
+            position: id as i64,
+            permissions,
+            managed: false,
+            hoist: false,
+        }
+    }
+
+    fn member(user_id: Id<UserMarker>, roles: Vec<u64>) -> CachedMember {
+        CachedMember {
+            guild_id: GUILD_ID,
+            user_id,
+            roles: roles.into_iter().map(Id::new).collect(),
+            nick: None,
+            joined_at: Timestamp::from_secs(1_600_000_000).unwrap(),
+            pending: false,
+            communication_disabled_until: None,
+        }
+    }
+
+    /// Cache with a guild where only the owner and the manager have the
+    /// `MANAGE_GUILD` permission.
+    async fn cache() -> CacheClient {
+        let cache = CacheClient::with_backend(MemoryCache::default(), CacheTtl::default());
+
+        let guild = CachedGuild {
+            id: GUILD_ID,
+            unavailable: false,
+            name: "guild".to_owned(),
+            icon: None,
+            owner_id: OWNER_ID,
+            current_member: None,
+            roles: HashSet::from([Id::new(1), Id::new(2), Id::new(3)]),
+            channels: HashSet::new(),
+        };
+        cache.set(&guild).await.unwrap();
+
+        cache
+            .set(&role(1, Permissions::VIEW_CHANNEL))
+            .await
+            .unwrap();
+        cache
+            .set(&role(2, Permissions::MANAGE_GUILD))
+            .await
+            .unwrap();
+        cache.set(&role(3, Permissions::BAN_MEMBERS)).await.unwrap();
+
+        cache.set(&member(OWNER_ID, vec![])).await.unwrap();
+        cache.set(&member(MANAGER_ID, vec![2])).await.unwrap();
+        cache.set(&member(MEMBER_ID, vec![3])).await.unwrap();
+
+        cache
+    }
+
+    async fn authorize(
+        user_id: Id<UserMarker>,
+        guild_id: Id<GuildMarker>,
+        cache: &CacheClient,
+    ) -> Result<CachedGuild, ApiError> {
+        let auth = Auth::Session(WebSession {
+            token: "token".to_owned(),
+            user_id,
+        });
+
+        authorize_guild(&auth, guild_id, ApiScope::Guild, cache).await
+    }
+
+    #[tokio::test]
+    async fn test_authorize_guild_session() {
+        let cache = cache().await;
+
+        assert!(authorize(OWNER_ID, GUILD_ID, &cache).await.is_ok());
+        assert!(authorize(MANAGER_ID, GUILD_ID, &cache).await.is_ok());
+
+        // Members without the permission and users that are not members of
+        // the guild are rejected.
+        assert!(matches!(
+            authorize(MEMBER_ID, GUILD_ID, &cache).await,
+            Err(ApiError::Forbidden)
+        ));
+        assert!(matches!(
+            authorize(Id::new(13), GUILD_ID, &cache).await,
+            Err(ApiError::Forbidden)
+        ));
+
+        assert!(matches!(
+            authorize(OWNER_ID, Id::new(2), &cache).await,
+            Err(ApiError::NotFound)
+        ));
+    }
+}
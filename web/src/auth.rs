@@ -0,0 +1,638 @@
+//! Discord OAuth2 authentication.
+//!
+//! Dashboard users log in with their Discord account using the OAuth2
+//! authorization code flow:
+//!
+//! - `/auth/login` redirects the user to the Discord authorization page, with
+//!   a random state stored in the cache ([`OAuthState`]) and in a cookie
+//! - `/auth/callback` checks that the state matches the cookie, exchanges the
+//!   authorization code for an access token and gets the user with it
+//! - a [`WebSession`] is created and its token is sent in an `HttpOnly` cookie
+//!
+//! The access token is only used to identify the user and is not stored.
+//!
+//! Third-party integrations use an [`ApiKey`] created by the guild owner,
+//! sent in the `Authorization` header. API keys are checked and rate limited
+//! (see the [`ratelimit`] module) by the [`api_key`] middleware. API routes
+//! get the caller with the [`Auth`] extractor.
+
+use std::sync::Arc;
+
+use anyhow::{bail, Context};
+use axum::{
+    async_trait,
+    extract::{FromRequest, Query, RequestParts},
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::{AppendHeaders, IntoResponse, Redirect, Response},
+    Extension,
+};
+use hyper::{client::HttpConnector, Body, Client, Method, Request};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use raidprotect_model::{
+    cache::{
+        model::session::{OAuthState, WebSession},
+        CacheClient, RedisModel,
+    },
+    config::OAuthConfig,
+    database::{model::ApiKey, ApiKeyStore},
+};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::Deserialize;
+use twilight_model::id::{marker::ApplicationMarker, Id};
+use url::{form_urlencoded, Url};
+
+use crate::{api::ApiError, ratelimit::RateLimiter};
+
+/// Discord authorization page.
+const AUTHORIZE_URL: &str = "https://discord.com/oauth2/authorize";
+
+/// Discord token exchange endpoint.
+const TOKEN_URL: &str = "https://discord.com/api/v10/oauth2/token";
+
+/// Scopes requested to the user.
+const SCOPES: &str = "identify";
+
+/// Name of the session cookie.
+const SESSION_COOKIE: &str = "session";
+
+/// Name of the cookie that ties the OAuth2 state to the browser.
+const STATE_COOKIE: &str = "oauth_state";
+
+/// Client used to authenticate users with Discord.
+#[derive(Clone)]
+pub struct OAuthClient {
+    http: Client<HttpsConnector<HttpConnector>>,
+    credentials: Arc<OAuthCredentials>,
+}
+
+/// Credentials of the Discord application.
+struct OAuthCredentials {
+    client_id: Id<ApplicationMarker>,
+    client_secret: String,
+    redirect_url: String,
+    secure_cookie: bool,
+}
+
+impl OAuthClient {
+    /// Initialize a new [`OAuthClient`] from the configuration.
+    ///
+    /// Returns [`None`] if the OAuth2 credentials are not configured.
+    pub fn new(config: &OAuthConfig) -> Option<Self> {
+        let credentials = OAuthCredentials {
+            client_id: config.oauth_client_id?,
+            client_secret: config.oauth_client_secret.clone()?,
+            redirect_url: config.oauth_redirect_url.clone()?,
+            secure_cookie: config.oauth_secure_cookie,
+        };
+
+        let connector = HttpsConnectorBuilder::new()
+            .with_webpki_roots()
+            .https_only()
+            .enable_http1()
+            .build();
+
+        Some(Self {
+            http: Client::builder().build(connector),
+            credentials: Arc::new(credentials),
+        })
+    }
+
+    /// URL of the authorization page with a given state.
+    fn authorize_url(&self, state: &str) -> Result<Url, anyhow::Error> {
+        let credentials = &self.credentials;
+        let url = Url::parse_with_params(
+            AUTHORIZE_URL,
+            &[
+                ("client_id", credentials.client_id.to_string().as_str()),
+                ("redirect_uri", &credentials.redirect_url),
+                ("response_type", "code"),
+                ("scope", SCOPES),
+                ("state", state),
+                ("prompt", "none"),
+            ],
+        )?;
+
+        Ok(url)
+    }
+
+    /// Exchange an authorization code for an access token.
+    async fn exchange_code(&self, code: &str) -> Result<String, anyhow::Error> {
+        let credentials = &self.credentials;
+        let body = form_urlencoded::Serializer::new(String::new())
+            .append_pair("client_id", &credentials.client_id.to_string())
+            .append_pair("client_secret", &credentials.client_secret)
+            .append_pair("grant_type", "authorization_code")
+            .append_pair("code", code)
+            .append_pair("redirect_uri", &credentials.redirect_url)
+            .finish();
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(TOKEN_URL)
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(body))?;
+
+        let response = self.http.request(request).await?;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+
+        if !status.is_success() {
+            bail!(
+                "token exchange failed with status {status}: {}",
+                String::from_utf8_lossy(&body)
+            );
+        }
+
+        let token: TokenResponse = serde_json::from_slice(&body)?;
+
+        Ok(token.access_token)
+    }
+
+    /// Build the session cookie header.
+    ///
+    /// The cookie is removed if `token` is [`None`].
+    fn session_cookie(&self, token: Option<&str>) -> String {
+        self.cookie::<WebSession>(SESSION_COOKIE, token)
+    }
+
+    /// Build the OAuth2 state cookie header.
+    ///
+    /// The cookie is removed if `state` is [`None`].
+    fn state_cookie(&self, state: Option<&str>) -> String {
+        self.cookie::<OAuthState>(STATE_COOKIE, state)
+    }
+
+    /// Build a cookie header expiring with the model `T`.
+    fn cookie<T: RedisModel>(&self, name: &str, value: Option<&str>) -> String {
+        let max_age = match value {
+            Some(_) => T::EXPIRES_AFTER.unwrap_or_default(),
+            None => 0,
+        };
+
+        let mut cookie = format!(
+            "{name}={}; Max-Age={max_age}; Path=/; HttpOnly; SameSite=Lax",
+            value.unwrap_or_default()
+        );
+
+        if self.credentials.secure_cookie {
+            cookie.push_str("; Secure");
+        }
+
+        cookie
+    }
+}
+
+/// Response of the token exchange endpoint.
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+/// Query parameters of the authorization callback.
+#[derive(Debug, Deserialize)]
+pub struct CallbackQuery {
+    code: String,
+    state: String,
+}
+
+/// Redirect the user to the Discord authorization page.
+///
+/// The state is also sent in a cookie, so that the callback can only be
+/// completed by the browser that started the login.
+pub async fn login(
+    Extension(oauth): Extension<OAuthClient>,
+    Extension(cache): Extension<CacheClient>,
+) -> Result<Response, ApiError> {
+    let state = OAuthState {
+        state: random_token(),
+    };
+    cache.set(&state).await?;
+
+    let cookie = oauth.state_cookie(Some(&state.state));
+    let redirect = Redirect::to(oauth.authorize_url(&state.state)?.as_str());
+
+    Ok(([(header::SET_COOKIE, cookie)], redirect).into_response())
+}
+
+/// Create a session once the user has authorized the application.
+pub async fn callback(
+    Query(query): Query<CallbackQuery>,
+    headers: HeaderMap,
+    Extension(oauth): Extension<OAuthClient>,
+    Extension(cache): Extension<CacheClient>,
+) -> Result<Response, ApiError> {
+    consume_state(&query.state, cookie(&headers, STATE_COOKIE), &cache).await?;
+
+    let access_token = oauth.exchange_code(&query.code).await?;
+    let user = twilight_http::Client::new(format!("Bearer {access_token}"))
+        .current_user()
+        .exec()
+        .await?
+        .model()
+        .await?;
+
+    let session = WebSession {
+        token: random_token(),
+        user_id: user.id,
+    };
+    cache.set(&session).await?;
+
+    let cookies = AppendHeaders([
+        (
+            header::SET_COOKIE,
+            oauth.session_cookie(Some(&session.token)),
+        ),
+        (header::SET_COOKIE, oauth.state_cookie(None)),
+    ]);
+
+    Ok((cookies, Redirect::to("/")).into_response())
+}
+
+/// Check the state of an authorization callback.
+///
+/// The state must have been created by [`login`] in the same browser (the
+/// `cookie_state`), and can only be used once: it is deleted from the cache
+/// in the same operation that checks its existence. Invalid states are
+/// rejected with `400 Bad Request`.
+async fn consume_state(
+    state: &str,
+    cookie_state: Option<&str>,
+    cache: &CacheClient,
+) -> Result<(), ApiError> {
+    if cookie_state != Some(state) {
+        return Err(ApiError::BadRequest);
+    }
+
+    match cache.delete_from::<OAuthState>(state).await? {
+        true => Ok(()),
+        false => Err(ApiError::BadRequest),
+    }
+}
+
+/// Delete the session of the user.
+pub async fn logout(
+    session: Option<Session>,
+    Extension(oauth): Extension<OAuthClient>,
+    Extension(cache): Extension<CacheClient>,
+) -> Result<Response, ApiError> {
+    if let Some(Session(session)) = session {
+        cache.delete(&session).await?;
+    }
+
+    let cookie = oauth.session_cookie(None);
+
+    Ok(([(header::SET_COOKIE, cookie)], Redirect::to("/")).into_response())
+}
+
+/// Session of the logged in user.
+///
+/// Requests without a valid session cookie are rejected with
+/// `401 Unauthorized`.
+#[derive(Debug, Clone)]
+pub struct Session(pub WebSession);
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for Session {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let token = cookie(req.headers(), SESSION_COOKIE).ok_or(ApiError::Unauthorized)?;
+        let cache = req
+            .extensions()
+            .get::<CacheClient>()
+            .context("missing cache extension")?;
+
+        match cache.get::<WebSession>(token).await? {
+            Some(session) => Ok(Session(session)),
+            None => Err(ApiError::Unauthorized),
+        }
+    }
+}
+
+/// Caller of an API route.
+///
+/// Requests authenticated by the [`api_key`] middleware are made by an
+/// integration, other requests must have a valid session cookie.
+#[derive(Debug, Clone)]
+pub enum Auth {
+    /// Logged in user.
+    Session(WebSession),
+    /// Third-party integration.
+    ApiKey(ApiKey),
+}
+
+impl Auth {
+    /// Check that the session or the API key has not been revoked since the
+    /// request was authenticated.
+    pub async fn is_valid(
+        &self,
+        cache: &CacheClient,
+        keys: &dyn ApiKeyStore,
+    ) -> Result<bool, anyhow::Error> {
+        let valid = match self {
+            Auth::Session(session) => cache.get::<WebSession>(&session.token).await?.is_some(),
+            Auth::ApiKey(key) => keys.get_api_key_by_hash(&key.hash).await?.is_some(),
+        };
+
+        Ok(valid)
+    }
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for Auth {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        if let Some(key) = req.extensions().get::<ApiKey>() {
+            return Ok(Auth::ApiKey(key.clone()));
+        }
+
+        let Session(session) = Session::from_request(req).await?;
+
+        Ok(Auth::Session(session))
+    }
+}
+
+/// Authenticate requests made with an API key.
+///
+/// The token is read from the `Authorization: Bearer <token>` header, and the
+/// matching [`ApiKey`] is added to the request extensions. Requests without
+/// this header are passed through unchanged.
+pub async fn api_key<B>(mut req: Request<B>, next: Next<B>) -> Result<Response, ApiError> {
+    let hash = match bearer_token(req.headers()) {
+        Some(token) => ApiKey::hash_token(token),
+        None => return Ok(next.run(req).await),
+    };
+
+    let extensions = req.extensions();
+    let cache = extensions
+        .get::<CacheClient>()
+        .context("missing cache extension")?
+        .clone();
+    let keys = extensions
+        .get::<Arc<dyn ApiKeyStore>>()
+        .context("missing api key store extension")?
+        .clone();
+    let limiter = extensions
+        .get::<RateLimiter>()
+        .context("missing rate limiter extension")?
+        .clone();
+
+    let key = match keys.get_api_key_by_hash(&hash).await? {
+        Some(key) => key,
+        None => return Err(ApiError::Unauthorized),
+    };
+
+    let id = key.id.context("missing api key id")?;
+    limiter.check_api_key(&id.to_hex(), &cache).await?;
+
+    req.extensions_mut().insert(key);
+
+    Ok(next.run(req).await)
+}
+
+/// Get the bearer token from the `Authorization` header.
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+/// Get the value of a cookie from the request headers.
+fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(cookie, _)| *cookie == name)
+        .map(|(_, value)| value)
+}
+
+/// Generate a random token.
+pub fn random_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use axum::{http::HeaderValue, middleware, routing::get, Router};
+    use hyper::StatusCode;
+    use raidprotect_model::{
+        cache::{discord::CachedGuild, memory::MemoryCache, CacheTtl},
+        config::RateLimitConfig,
+        database::{model::ApiScope, MemoryStore},
+    };
+    use time::OffsetDateTime;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::api;
+
+    fn cache() -> CacheClient {
+        CacheClient::with_backend(MemoryCache::default(), CacheTtl::default())
+    }
+
+    fn headers(cookies: &[&'static str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for cookie in cookies {
+            headers.append(header::COOKIE, HeaderValue::from_static(cookie));
+        }
+
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_consume_state() {
+        let cache = cache();
+        let state = OAuthState {
+            state: random_token(),
+        };
+        cache.set(&state).await.unwrap();
+        let token = Some(state.state.as_str());
+
+        // The state must match the cookie of the browser that started the
+        // login.
+        assert!(matches!(
+            consume_state(&state.state, None, &cache).await,
+            Err(ApiError::BadRequest)
+        ));
+        assert!(matches!(
+            consume_state(&state.state, Some("other"), &cache).await,
+            Err(ApiError::BadRequest)
+        ));
+
+        // Only one of two concurrent callbacks can use the state.
+        let (first, second) = tokio::join!(
+            consume_state(&state.state, token, &cache),
+            consume_state(&state.state, token, &cache)
+        );
+        assert!(first.is_ok() != second.is_ok());
+
+        // The state cannot be reused, and unknown states are rejected.
+        assert!(matches!(
+            consume_state(&state.state, token, &cache).await,
+            Err(ApiError::BadRequest)
+        ));
+        let unknown = random_token();
+        assert!(matches!(
+            consume_state(&unknown, Some(&unknown), &cache).await,
+            Err(ApiError::BadRequest)
+        ));
+    }
+
+    #[test]
+    fn test_cookie() {
+        let token = |cookies| cookie(&headers(cookies), SESSION_COOKIE).map(str::to_owned);
+
+        assert_eq!(token(&["session=abc"]).as_deref(), Some("abc"));
+        assert_eq!(
+            token(&["theme=dark; session=abc; lang=fr"]).as_deref(),
+            Some("abc")
+        );
+        assert_eq!(
+            token(&["theme=dark", "session=abc"]).as_deref(),
+            Some("abc")
+        );
+        assert_eq!(token(&["sessionid=abc"]), None);
+        assert_eq!(token(&[]), None);
+    }
+
+    #[test]
+    fn test_session_cookie() {
+        let config = OAuthConfig {
+            oauth_client_id: Some(Id::new(1)),
+            oauth_client_secret: Some("secret".to_owned()),
+            oauth_redirect_url: Some("https://example.com/auth/callback".to_owned()),
+            oauth_secure_cookie: true,
+        };
+        let oauth = OAuthClient::new(&config).unwrap();
+
+        let cookie = oauth.session_cookie(Some("abc"));
+        assert!(cookie.starts_with("session=abc; "));
+        assert!(cookie.contains("HttpOnly"));
+        assert!(cookie.ends_with("; Secure"));
+
+        assert!(oauth.session_cookie(None).contains("Max-Age=0"));
+
+        let cookie = oauth.state_cookie(Some("state"));
+        assert!(cookie.starts_with("oauth_state=state; Max-Age=600; "));
+        assert!(cookie.contains("HttpOnly; SameSite=Lax; Secure"));
+    }
+
+    #[tokio::test]
+    async fn test_session_extractor() {
+        let cache = cache();
+        let session = WebSession {
+            token: random_token(),
+            user_id: Id::new(1),
+        };
+        cache.set(&session).await.unwrap();
+
+        let extract = |cookie: Option<String>| {
+            let mut request = Request::builder().extension(cache.clone());
+            if let Some(cookie) = cookie {
+                request = request.header(header::COOKIE, cookie);
+            }
+            let mut parts = RequestParts::new(request.body(()).unwrap());
+
+            async move { Session::from_request(&mut parts).await }
+        };
+
+        let Session(extracted) = extract(Some(format!("session={}", session.token)))
+            .await
+            .unwrap();
+        assert_eq!(extracted.user_id, session.user_id);
+
+        assert!(matches!(
+            extract(Some("session=unknown".to_owned())).await,
+            Err(ApiError::Unauthorized)
+        ));
+        assert!(matches!(extract(None).await, Err(ApiError::Unauthorized)));
+    }
+
+    #[tokio::test]
+    async fn test_api_key_scope() {
+        let cache = cache();
+        for id in [1, 2] {
+            let guild = CachedGuild {
+                id: Id::new(id),
+                unavailable: false,
+                name: format!("guild {id}"),
+                icon: None,
+                owner_id: Id::new(10),
+                current_member: None,
+                roles: HashSet::new(),
+                channels: HashSet::new(),
+            };
+            cache.set(&guild).await.unwrap();
+        }
+
+        let keys = MemoryStore::new();
+        for (token, scope) in [
+            ("guild-token", ApiScope::Guild),
+            ("events-token", ApiScope::Events),
+        ] {
+            let key = ApiKey {
+                id: None,
+                guild_id: Id::new(1),
+                name: token.to_owned(),
+                hash: ApiKey::hash_token(token),
+                scopes: vec![scope],
+                created_by: Id::new(10),
+                created_at: OffsetDateTime::now_utc(),
+            };
+            keys.create_api_key(&key).await.unwrap();
+        }
+
+        let app = Router::new()
+            .route("/api/guilds/:guild_id", get(api::guild))
+            .route_layer(middleware::from_fn(api_key))
+            .layer(Extension(RateLimiter::new(RateLimitConfig::default())))
+            .layer(Extension::<Arc<dyn ApiKeyStore>>(Arc::new(keys)))
+            .layer(Extension(cache));
+
+        let request = |guild_id: u64, token: String| {
+            let request = Request::get(format!("/api/guilds/{guild_id}"))
+                .header(header::AUTHORIZATION, format!("Bearer {token}"))
+                .body(Body::empty())
+                .unwrap();
+
+            let app = app.clone();
+            async move { app.oneshot(request).await.unwrap().status() }
+        };
+
+        assert_eq!(request(1, "guild-token".to_owned()).await, StatusCode::OK);
+
+        // Keys cannot access other guilds or routes outside of their scopes.
+        assert_eq!(
+            request(2, "guild-token".to_owned()).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            request(1, "events-token".to_owned()).await,
+            StatusCode::FORBIDDEN
+        );
+
+        // Keys are looked up with the hash of the token, which is not
+        // accepted as a token itself.
+        assert_eq!(
+            request(1, ApiKey::hash_token("guild-token")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            request(1, "unknown".to_owned()).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+}
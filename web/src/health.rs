@@ -0,0 +1,183 @@
+//! Health endpoints.
+//!
+//! The `/health` endpoint reports the status of the shards published by the
+//! bot processes (see [`ShardStatus`]). It responds with `503 Service
+//! Unavailable` if the cache is unreachable, if no shard status is published
+//! or if a shard is disconnected, so that it can be used by monitoring
+//! services.
+//!
+//! The `/healthz` and `/readyz` endpoints are intended for Kubernetes probes
+//! and respond with JSON. `/healthz` only reports that the web service is
+//! running, while `/readyz` checks each dependency: Redis, MongoDB and the
+//! shards connectivity. Each check fails after [`CHECK_TIMEOUT`].
+
+use std::{future::Future, time::Duration};
+
+use axum::{http::StatusCode, Extension, Json};
+use raidprotect_model::{
+    cache::{model::gateway::ShardStatus, CacheClient},
+    database::DbClient,
+};
+use serde::Serialize;
+use tracing::error;
+
+/// Maximum duration of a readiness check.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Status of the shards.
+pub async fn health(Extension(cache): Extension<CacheClient>) -> (StatusCode, String) {
+    let mut status = match cache.scan::<ShardStatus>(ShardStatus::KEY_PATTERN).await {
+        Ok(status) => status,
+        Err(error) => {
+            error!(error = ?error, "failed to get shards status");
+
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "cache unavailable".to_owned(),
+            );
+        }
+    };
+    status.sort_by_key(|status| status.shard_id);
+
+    (status_code(&status), format_status(&status))
+}
+
+/// Status code of the health endpoint.
+fn status_code(status: &[ShardStatus]) -> StatusCode {
+    if !status.is_empty() && status.iter().all(|status| status.connected) {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+}
+
+/// Format the status of the shards, one shard per line.
+fn format_status(status: &[ShardStatus]) -> String {
+    if status.is_empty() {
+        return "no shard status".to_owned();
+    }
+
+    status
+        .iter()
+        .map(|status| {
+            let latency = match status.latency {
+                Some(latency) => format!("{latency}ms"),
+                None => "-".to_owned(),
+            };
+
+            let mut line = format!(
+                "shard {}/{}: {} latency={latency} events_per_minute={} queue_depth={}",
+                status.shard_id,
+                status.total_shards,
+                status.stage,
+                status.events_per_minute,
+                status.queue_depth
+            );
+
+            if let Some(bytes) = status.payload_bytes_per_minute {
+                line.push_str(&format!(" payload_bytes_per_minute={bytes}"));
+            }
+
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Status of a readiness check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Error,
+}
+
+/// Result of a readiness check.
+#[derive(Debug, Serialize)]
+pub struct Check {
+    status: CheckStatus,
+    /// Error message if the check failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl Check {
+    fn ok() -> Self {
+        Self {
+            status: CheckStatus::Ok,
+            error: None,
+        }
+    }
+
+    fn error(error: impl ToString) -> Self {
+        Self {
+            status: CheckStatus::Error,
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+/// Readiness report of the web service dependencies.
+#[derive(Debug, Serialize)]
+pub struct Readiness {
+    status: CheckStatus,
+    redis: Check,
+    mongodb: Check,
+    shards: Check,
+}
+
+/// Liveness of the web service.
+pub async fn healthz() -> Json<Check> {
+    Json(Check::ok())
+}
+
+/// Readiness of the web service dependencies.
+pub async fn readyz(
+    Extension(cache): Extension<CacheClient>,
+    Extension(database): Extension<DbClient>,
+) -> (StatusCode, Json<Readiness>) {
+    let (redis, mongodb, shards) = tokio::join!(
+        check(cache.ping()),
+        check(database.ping()),
+        check(check_shards(&cache)),
+    );
+
+    let ready = [&redis, &mongodb, &shards]
+        .iter()
+        .all(|check| check.status == CheckStatus::Ok);
+    let (code, status) = if ready {
+        (StatusCode::OK, CheckStatus::Ok)
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, CheckStatus::Error)
+    };
+
+    let readiness = Readiness {
+        status,
+        redis,
+        mongodb,
+        shards,
+    };
+
+    (code, Json(readiness))
+}
+
+/// Check that all the shards are connected.
+async fn check_shards(cache: &CacheClient) -> Result<(), anyhow::Error> {
+    let status = cache.scan::<ShardStatus>(ShardStatus::KEY_PATTERN).await?;
+    let connected = status.iter().filter(|status| status.connected).count();
+
+    if status.is_empty() || connected < status.len() {
+        anyhow::bail!("{connected}/{} shards connected", status.len());
+    }
+
+    Ok(())
+}
+
+/// Run a readiness check with a timeout.
+async fn check(check: impl Future<Output = Result<(), anyhow::Error>>) -> Check {
+    match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(Ok(())) => Check::ok(),
+        Ok(Err(error)) => Check::error(error),
+        Err(_) => Check::error("timed out"),
+    }
+}
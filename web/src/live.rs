@@ -0,0 +1,156 @@
+//! Live events stream.
+//!
+//! The `/api/guilds/:guild_id/events` WebSocket endpoint sends the live events
+//! published by the bot (see [`LiveEvent`]) to the dashboard. Each event is
+//! sent as a JSON text message, with the id of its stream entry.
+//!
+//! The user must be allowed to manage the guild to open the connection. The
+//! permission is checked again after each read of the stream, and the
+//! connection is closed if the user is no longer allowed to manage the guild
+//! or if the session or API key has been revoked.
+
+use std::time::Duration;
+
+use anyhow::anyhow;
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        WebSocketUpgrade,
+    },
+    response::Response,
+    Extension,
+};
+use futures_util::{SinkExt, StreamExt};
+use raidprotect_model::{
+    cache::{model::live::LiveEvent, CacheClient, StreamReader},
+    database::{model::ApiScope, DbClient},
+};
+use serde::Serialize;
+use tracing::debug;
+use twilight_model::id::{marker::GuildMarker, Id};
+
+use crate::{
+    api::{authorize_guild, ApiError, GuildPath},
+    auth::Auth,
+};
+
+/// Maximum duration of a stream read.
+///
+/// This is also the maximum delay before a revoked permission is detected.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Message sent for each event.
+#[derive(Debug, Serialize)]
+struct EventMessage<'a> {
+    /// Id of the stream entry.
+    id: &'a str,
+    #[serde(flatten)]
+    event: &'a LiveEvent,
+}
+
+/// Client connected to the live events stream.
+struct Client {
+    auth: Auth,
+    cache: CacheClient,
+    database: DbClient,
+}
+
+impl Client {
+    /// Check that the client is still allowed to access the guild.
+    async fn is_authorized(&self, guild_id: Id<GuildMarker>) -> bool {
+        if !matches!(
+            self.auth.is_valid(&self.cache, &self.database).await,
+            Ok(true)
+        ) {
+            return false;
+        }
+
+        authorize_guild(&self.auth, guild_id, ApiScope::Events, &self.cache)
+            .await
+            .is_ok()
+    }
+}
+
+/// Open the live events stream of a guild.
+pub async fn events(
+    GuildPath(guild_id): GuildPath,
+    auth: Auth,
+    upgrade: WebSocketUpgrade,
+    Extension(cache): Extension<CacheClient>,
+    Extension(database): Extension<DbClient>,
+) -> Result<Response, ApiError> {
+    authorize_guild(&auth, guild_id, ApiScope::Events, &cache).await?;
+
+    let reader = cache
+        .stream_reader(&LiveEvent::stream_key(guild_id))
+        .await?;
+
+    Ok(upgrade.on_upgrade(move |socket| async move {
+        let client = Client {
+            auth,
+            cache,
+            database,
+        };
+
+        if let Err(error) = stream_events(socket, reader, guild_id, client).await {
+            debug!(error = ?error, guild = ?guild_id, "live events stream closed");
+        }
+    }))
+}
+
+async fn stream_events(
+    socket: WebSocket,
+    reader: StreamReader,
+    guild_id: Id<GuildMarker>,
+    client: Client,
+) -> Result<(), anyhow::Error> {
+    let (sender, mut receiver) = socket.split();
+
+    // Messages sent by the client are ignored, they are only read to detect
+    // when the connection is closed.
+    let mut receive = tokio::spawn(async move {
+        while let Some(Ok(message)) = receiver.next().await {
+            if let Message::Close(_) = message {
+                break;
+            }
+        }
+    });
+
+    // The reader is dropped with its connection if the client disconnects
+    // while reading the stream.
+    tokio::select! {
+        _ = &mut receive => Ok(()),
+        result = forward_events(sender, reader, guild_id, client) => {
+            receive.abort();
+            result
+        }
+    }
+}
+
+/// Forward the events of the stream to the client.
+async fn forward_events(
+    mut sender: impl SinkExt<Message, Error = axum::Error> + Unpin,
+    mut reader: StreamReader,
+    guild_id: Id<GuildMarker>,
+    client: Client,
+) -> Result<(), anyhow::Error> {
+    loop {
+        for (id, value) in reader.next_entries(LiveEvent::FIELD, READ_TIMEOUT).await? {
+            let event = LiveEvent::decode(&value)?;
+            let message = EventMessage {
+                id: &id,
+                event: &event,
+            };
+
+            sender
+                .send(Message::Text(serde_json::to_string(&message)?))
+                .await?;
+        }
+
+        if !client.is_authorized(guild_id).await {
+            sender.send(Message::Close(None)).await?;
+
+            return Err(anyhow!("client is no longer allowed to access the guild"));
+        }
+    }
+}
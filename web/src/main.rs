@@ -1,24 +1,106 @@
+mod api;
+mod auth;
+mod health;
+mod live;
+mod metrics;
+mod privacy;
+mod ratelimit;
+mod rpc;
+mod stats;
+mod verify;
+
+use std::{net::SocketAddr, sync::Arc};
+
 use anyhow::Context;
-use axum::{extract::Path, routing::get, Router};
-use raidprotect_model::config::{parse_config, WebConfig};
+use axum::{
+    extract::Path,
+    middleware,
+    routing::{get, post},
+    Extension, Router,
+};
+use raidprotect_model::{
+    cache::CacheClient,
+    config::{parse_config, WebConfig},
+    database::{ApiKeyStore, DbClient},
+};
 use tower_http::trace::TraceLayer;
-use tracing::info;
+use tracing::{info, warn};
+
+use crate::{auth::OAuthClient, ratelimit::RateLimiter};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let config = parse_config::<WebConfig>().context("failed to load configuration")?;
     let _guard = config.log.init("raidprotect-web");
 
-    let app = Router::new()
+    let cache = CacheClient::connect(
+        &config.database.redis_uri,
+        config.database.cache_ttl.clone(),
+    )
+    .await?;
+    cache.ping().await.context("failed to connect to redis")?;
+
+    let database = DbClient::connect(
+        &config.database.mongodb_uri,
+        config.database.mongodb_database.clone(),
+    )
+    .await?;
+
+    // API routes can be used with a session or an API key.
+    let api = Router::new()
+        .route("/api/guilds/:guild_id", get(api::guild))
+        .route("/api/guilds/:guild_id/events", get(live::events))
+        .route("/api/guilds/:guild_id/bans", post(rpc::ban))
+        .route(
+            "/api/guilds/:guild_id/cache/invalidate",
+            post(rpc::invalidate_cache),
+        )
+        .route("/api/users/@me/data", get(privacy::export))
+        .route_layer(middleware::from_fn(auth::api_key));
+
+    let mut app = Router::new()
         .route("/", get(|| async { "Hello, world!" }))
         .route("/:name", get(hello_name))
+        .route("/api/guilds/:guild_id/stats", get(stats::stats))
+        .route("/verify/:token", get(verify::page).post(verify::submit))
+        .route("/verify/:token/captcha.png", get(verify::image))
+        .merge(api);
+
+    // Login routes are only available if OAuth2 is configured.
+    match OAuthClient::new(&config.oauth) {
+        Some(oauth) => {
+            app = app
+                .route("/auth/login", get(auth::login))
+                .route("/auth/callback", get(auth::callback))
+                .route("/auth/logout", get(auth::logout))
+                .layer(Extension(oauth));
+        }
+        None => warn!("oauth2 is not configured, login routes are disabled"),
+    }
+
+    // Health and metrics routes are not rate limited since they are used by
+    // the monitoring services.
+    let app = app
+        .route_layer(middleware::from_fn(ratelimit::limit_ip))
+        .route("/health", get(health::health))
+        .route("/healthz", get(health::healthz))
+        .route("/readyz", get(health::readyz))
+        .route("/metrics", get(metrics::metrics))
+        .layer(middleware::from_fn(ratelimit::limit_body_size))
+        .layer(ratelimit::body_limit(&config.ratelimit))
+        .layer(Extension(RateLimiter::new(config.ratelimit.clone())))
+        .layer(Extension(cache))
+        .layer(Extension::<Arc<dyn ApiKeyStore>>(Arc::new(
+            database.clone(),
+        )))
+        .layer(Extension(database))
         // `TraceLayer` is provided by tower-http to trace http requests.
         .layer(TraceLayer::new_for_http());
 
     info!("listening on {}", &config.address);
     axum::Server::try_bind(&config.address)
         .context("failed to bind server address")?
-        .serve(app.into_make_service())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
         .await?;
 
     Ok(())
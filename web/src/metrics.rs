@@ -0,0 +1,57 @@
+//! Metrics endpoint.
+//!
+//! This endpoint exposes the metrics of the web service and the snapshots
+//! published by the bot processes (see [`ProcessMetrics`]) in the Prometheus
+//! text format. A `process` label is added to each series.
+
+use axum::{
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Extension,
+};
+use raidprotect_model::{
+    cache::{model::metrics::ProcessMetrics, CacheClient},
+    metrics,
+};
+use tracing::error;
+
+/// Content type of the Prometheus text format.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Metrics of all the processes.
+pub async fn metrics(Extension(cache): Extension<CacheClient>) -> impl IntoResponse {
+    let processes = match cache
+        .scan::<ProcessMetrics>(ProcessMetrics::KEY_PATTERN)
+        .await
+    {
+        Ok(processes) => processes,
+        Err(error) => {
+            error!(error = ?error, "failed to get process metrics");
+
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                [(header::CONTENT_TYPE, CONTENT_TYPE)],
+                "cache unavailable".to_owned(),
+            );
+        }
+    };
+
+    let web = metrics::registry()
+        .snapshot()
+        .into_iter()
+        .map(|family| family.with_label("process", "web"));
+    let bot = processes.into_iter().flat_map(|process| {
+        let name = process.process;
+
+        process
+            .metrics
+            .into_iter()
+            .map(move |family| family.with_label("process", &name))
+    });
+
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, CONTENT_TYPE)],
+        metrics::render(web.chain(bot)),
+    )
+}
@@ -0,0 +1,46 @@
+//! Export of the data stored about the logged in user.
+//!
+//! Users can download the data stored about them from the dashboard. Erasure
+//! requests are processed by the bot owners with the `/privacy` command, since
+//! erasing the data anonymizes the moderation logs of the guilds.
+
+use axum::{
+    http::header,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use raidprotect_model::{cache::CacheClient, database::DbClient, privacy::export_user_data};
+
+use crate::{api::ApiError, auth::Auth};
+
+/// Export the data stored about the logged in user.
+///
+/// The moderators and the private notes of the sanctions of the user are not
+/// included (see [`UserData::to_self_service_json`]). This route is not
+/// available to API keys.
+///
+/// [`UserData::to_self_service_json`]: raidprotect_model::privacy::UserData::to_self_service_json
+pub async fn export(
+    auth: Auth,
+    Extension(cache): Extension<CacheClient>,
+    Extension(database): Extension<DbClient>,
+) -> Result<Response, ApiError> {
+    let session = match auth {
+        Auth::Session(session) => session,
+        Auth::ApiKey(_) => return Err(ApiError::Forbidden),
+    };
+
+    let data = export_user_data(session.user_id, &database, &cache).await?;
+    let headers = [
+        (header::CONTENT_TYPE, "application/json".to_owned()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"user-data-{}.json\"",
+                session.user_id
+            ),
+        ),
+    ];
+
+    Ok((headers, data.to_self_service_json()?).into_response())
+}
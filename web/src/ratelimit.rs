@@ -0,0 +1,252 @@
+//! Rate limiting and abuse protection.
+//!
+//! Requests are rate limited per IP address with the [`limit_ip`] middleware
+//! and per API key (see [`api_key`]), using counters stored in the cache so
+//! that the limits are shared between the instances of the web service.
+//! Rejected requests get a `429 Too Many Requests` response with the delay
+//! before the next allowed request.
+//!
+//! Requests with a body larger than the configured maximum are rejected by
+//! the [`limit_body_size`] middleware, using the `Content-Length` header. The
+//! body stream itself is limited by the [`body_limit`] layer, since HTTP/2
+//! requests can send a body without this header.
+//!
+//! The limits are configured with the [`RateLimitConfig`].
+//!
+//! [`api_key`]: crate::auth::api_key
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use anyhow::Context;
+use axum::{
+    extract::ConnectInfo,
+    http::{header, HeaderMap, Request},
+    middleware::Next,
+    response::Response,
+};
+use raidprotect_model::{cache::CacheClient, config::RateLimitConfig};
+use tower_http::limit::RequestBodyLimitLayer;
+
+use crate::api::ApiError;
+
+/// Rate limiter of the web service.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    config: Arc<RateLimitConfig>,
+}
+
+impl RateLimiter {
+    /// Initialize a new [`RateLimiter`] from the configuration.
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+        }
+    }
+
+    /// Count a request of an API key.
+    pub async fn check_api_key(&self, id: &str, cache: &CacheClient) -> Result<(), ApiError> {
+        self.check(
+            &format!("ratelimit:api:{id}"),
+            self.config.ratelimit_api_key,
+            cache,
+        )
+        .await
+    }
+
+    /// Count a request from an IP address.
+    async fn check_ip(&self, ip: IpAddr, cache: &CacheClient) -> Result<(), ApiError> {
+        self.check(
+            &format!("ratelimit:ip:{ip}"),
+            self.config.ratelimit_ip,
+            cache,
+        )
+        .await
+    }
+
+    /// Count a request, and reject it if the limit is exceeded.
+    async fn check(&self, key: &str, limit: u64, cache: &CacheClient) -> Result<(), ApiError> {
+        let (count, expires_in) = cache.increment(key, self.config.ratelimit_window).await?;
+
+        if count > limit {
+            return Err(ApiError::TooManyRequests {
+                retry_after: expires_in,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Get the IP address of the client.
+    fn client_ip<B>(&self, req: &Request<B>) -> Option<IpAddr> {
+        if self.config.ratelimit_forwarded_for {
+            return forwarded_for(req.headers());
+        }
+
+        req.extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())
+    }
+}
+
+/// Rate limit the requests per IP address.
+pub async fn limit_ip<B>(req: Request<B>, next: Next<B>) -> Result<Response, ApiError> {
+    let extensions = req.extensions();
+    let limiter = extensions
+        .get::<RateLimiter>()
+        .context("missing rate limiter extension")?;
+    let cache = extensions
+        .get::<CacheClient>()
+        .context("missing cache extension")?;
+
+    let ip = limiter
+        .client_ip(&req)
+        .context("failed to get client ip address")?;
+    limiter.check_ip(ip, cache).await?;
+
+    Ok(next.run(req).await)
+}
+
+/// Limit the size of the request body streams.
+///
+/// Reading more than the configured maximum fails, and the body extractors
+/// respond with `413 Payload Too Large`.
+pub fn body_limit(config: &RateLimitConfig) -> RequestBodyLimitLayer {
+    RequestBodyLimitLayer::new(config.max_body_size as usize)
+}
+
+/// Reject requests with a body larger than the configured maximum.
+///
+/// The size is read from the `Content-Length` header, so that large requests
+/// are rejected before reading their body. Requests with a body of unknown
+/// size (using chunked transfer encoding) are rejected. Bodies sent without
+/// any of these headers are limited by the [`body_limit`] layer.
+pub async fn limit_body_size<B>(req: Request<B>, next: Next<B>) -> Result<Response, ApiError> {
+    let max_size = req
+        .extensions()
+        .get::<RateLimiter>()
+        .context("missing rate limiter extension")?
+        .config
+        .max_body_size;
+
+    let headers = req.headers();
+    if headers.contains_key(header::TRANSFER_ENCODING) {
+        return Err(ApiError::LengthRequired);
+    }
+
+    let size = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok()?.parse::<u64>().ok())
+        .unwrap_or_default();
+
+    if size > max_size {
+        return Err(ApiError::PayloadTooLarge);
+    }
+
+    Ok(next.run(req).await)
+}
+
+/// Get the client IP address from the `X-Forwarded-For` header.
+///
+/// The last address is used, since it has been added by the reverse proxy
+/// and cannot be forged by the client.
+fn forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .last()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{middleware, routing::post, Extension, Router};
+    use hyper::{Body, StatusCode};
+    use raidprotect_model::cache::{memory::MemoryCache, CacheTtl};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn app() -> Router {
+        let config = RateLimitConfig {
+            ratelimit_ip: 2,
+            max_body_size: 16,
+            ..Default::default()
+        };
+        let cache = CacheClient::with_backend(MemoryCache::default(), CacheTtl::default());
+
+        Router::new()
+            .route("/", post(|_: String| async {}))
+            .route_layer(middleware::from_fn(limit_ip))
+            .layer(middleware::from_fn(limit_body_size))
+            .layer(body_limit(&config))
+            .layer(Extension(RateLimiter::new(config)))
+            .layer(Extension(cache))
+    }
+
+    fn request(ip: [u8; 4], body: &'static str) -> Request<Body> {
+        Request::post("/")
+            .header(header::CONTENT_LENGTH, body.len())
+            .extension(ConnectInfo(SocketAddr::from((ip, 1234))))
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_limit_ip() {
+        let app = app();
+
+        for _ in 0..2 {
+            let response = app.clone().oneshot(request([10, 0, 0, 1], "")).await;
+            assert_eq!(response.unwrap().status(), StatusCode::OK);
+        }
+
+        let response = app
+            .clone()
+            .oneshot(request([10, 0, 0, 1], ""))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+
+        // The limit is counted per IP address.
+        let response = app.oneshot(request([10, 0, 0, 2], "")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_limit_body_size() {
+        let app = app();
+
+        let response = app
+            .clone()
+            .oneshot(request([10, 0, 0, 1], "small body"))
+            .await;
+        assert_eq!(response.unwrap().status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(request([10, 0, 0, 1], "body larger than the maximum"))
+            .await;
+        assert_eq!(response.unwrap().status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        // Bodies without a `Content-Length` header are limited when read.
+        let mut request = request([10, 0, 0, 1], "body larger than the maximum");
+        request.headers_mut().remove(header::CONTENT_LENGTH);
+        let response = app.clone().oneshot(request).await;
+        assert_eq!(response.unwrap().status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let chunked = Request::post("/")
+            .header(header::TRANSFER_ENCODING, "chunked")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(chunked).await;
+        assert_eq!(response.unwrap().status(), StatusCode::LENGTH_REQUIRED);
+    }
+}
@@ -0,0 +1,114 @@
+//! Actions performed by the bot.
+//!
+//! These routes ask the bot to perform an action in a guild by publishing a
+//! [`RpcRequest`], and wait for the [`RpcResponse`] of the bot process running
+//! the guild shard. They require the [`ApiScope::Actions`] scope.
+
+use std::time::Duration;
+
+use axum::{http::StatusCode, Extension, Json};
+use futures_util::StreamExt;
+use raidprotect_model::{
+    cache::{
+        model::rpc::{RpcAction, RpcCaller, RpcRequest, RpcResponse},
+        CacheClient,
+    },
+    database::model::ApiScope,
+};
+use serde::Deserialize;
+use twilight_model::id::{
+    marker::{GuildMarker, UserMarker},
+    Id,
+};
+
+use crate::{
+    api::{authorize_guild, ApiError, GuildPath},
+    auth::{random_token, Auth},
+};
+
+/// Delay after which the bot is considered unavailable.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximum length of a ban reason.
+const MAX_REASON_LEN: usize = 512;
+
+/// Body of the ban route.
+#[derive(Debug, Deserialize)]
+pub struct BanBody {
+    user_id: Id<UserMarker>,
+    reason: Option<String>,
+}
+
+/// Ban a user from a guild.
+pub async fn ban(
+    GuildPath(guild_id): GuildPath,
+    auth: Auth,
+    Extension(cache): Extension<CacheClient>,
+    Json(body): Json<BanBody>,
+) -> Result<StatusCode, ApiError> {
+    if body
+        .reason
+        .as_ref()
+        .is_some_and(|reason| reason.chars().count() > MAX_REASON_LEN)
+    {
+        return Err(ApiError::BadRequest);
+    }
+
+    let action = RpcAction::Ban {
+        user_id: body.user_id,
+        reason: body.reason,
+    };
+
+    call(&auth, guild_id, action, &cache).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Drop the values computed by the bot from the guild configuration.
+pub async fn invalidate_cache(
+    GuildPath(guild_id): GuildPath,
+    auth: Auth,
+    Extension(cache): Extension<CacheClient>,
+) -> Result<StatusCode, ApiError> {
+    call(&auth, guild_id, RpcAction::InvalidateCache, &cache).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Send a request to the bot and wait for its response.
+async fn call(
+    auth: &Auth,
+    guild_id: Id<GuildMarker>,
+    action: RpcAction,
+    cache: &CacheClient,
+) -> Result<(), ApiError> {
+    authorize_guild(auth, guild_id, ApiScope::Actions, cache).await?;
+
+    let caller = match auth {
+        Auth::Session(session) => RpcCaller::User(session.user_id),
+        Auth::ApiKey(key) => RpcCaller::ApiKey {
+            name: key.name.clone(),
+            created_by: key.created_by,
+        },
+    };
+
+    let request = RpcRequest {
+        id: random_token(),
+        guild_id,
+        caller,
+        action,
+    };
+
+    // Subscribe before publishing the request to not miss the response.
+    let mut responses = cache
+        .subscribe::<RpcResponse>(&request.reply_channel())
+        .await?;
+    cache.publish(RpcRequest::CHANNEL, &request).await?;
+
+    let response = match tokio::time::timeout(RESPONSE_TIMEOUT, responses.next()).await {
+        Ok(Some(response)) => response?,
+        Ok(None) | Err(_) => return Err(ApiError::Unavailable),
+    };
+
+    response.result.map_err(ApiError::Action)
+}
@@ -0,0 +1,73 @@
+//! Public guild statistics.
+//!
+//! Guilds that enabled public statistics (with `/config stats enable`) expose
+//! aggregate counters that can be embedded in external websites. The route
+//! does not require authentication and allows cross-origin requests.
+
+use axum::{
+    http::header,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use raidprotect_model::{
+    cache::{discord::CachedGuild, CacheClient},
+    database::{DbClient, GuildConfigStore},
+};
+use serde::Serialize;
+use time::{Duration, OffsetDateTime};
+use twilight_model::id::{marker::GuildMarker, Id};
+
+use crate::api::{ApiError, GuildPath};
+
+/// Duration during which the statistics can be cached by clients.
+const CACHE_MAX_AGE: u32 = 300;
+
+/// Public statistics of a guild.
+#[derive(Debug, Serialize)]
+pub struct PublicStats {
+    guild_id: Id<GuildMarker>,
+    name: Option<String>,
+    members_verified: i64,
+    sanctions_this_week: u64,
+    spam_blocked: i64,
+}
+
+/// Get the public statistics of a guild.
+///
+/// Returns `404 Not Found` if the guild has not enabled public statistics.
+pub async fn stats(
+    GuildPath(guild_id): GuildPath,
+    Extension(cache): Extension<CacheClient>,
+    Extension(database): Extension<DbClient>,
+) -> Result<Response, ApiError> {
+    match database.get_guild(guild_id).await? {
+        Some(config) if config.stats.public => {}
+        _ => return Err(ApiError::NotFound),
+    }
+
+    let name = cache
+        .get::<CachedGuild>(&guild_id)
+        .await?
+        .map(|guild| guild.name);
+    let counters = database.get_guild_stats(guild_id).await?;
+    let since = OffsetDateTime::now_utc() - Duration::weeks(1);
+    let sanctions_this_week = database.count_modlogs_since(guild_id, since).await?;
+
+    let stats = PublicStats {
+        guild_id,
+        name,
+        members_verified: counters.members_verified,
+        sanctions_this_week,
+        spam_blocked: counters.spam_blocked,
+    };
+
+    let headers = [
+        (header::ACCESS_CONTROL_ALLOW_ORIGIN, "*".to_string()),
+        (
+            header::CACHE_CONTROL,
+            format!("public, max-age={CACHE_MAX_AGE}"),
+        ),
+    ];
+
+    Ok((headers, Json(stats)).into_response())
+}
@@ -0,0 +1,157 @@
+//! Web captcha verification.
+//!
+//! This module implements a fallback verification page for users that cannot
+//! use Discord modals. The bot generates a token stored in Redis with the
+//! captcha code and image ([`WebCaptcha`]) and sends a link to this page. Once
+//! the code is validated, a [`JobAction::WebCaptchaCompleted`] job is scheduled
+//! so that a bot process updates the member roles.
+//!
+//! The job is claimed by a single bot process and retried if it fails. The
+//! token is deleted by the bot once the roles have been updated.
+
+use axum::{
+    extract::{Form, Path},
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+    Extension,
+};
+use raidprotect_captcha::code::validate_code;
+use raidprotect_model::{
+    cache::{model::interaction::WebCaptcha, CacheClient},
+    database::{
+        model::{JobAction, ScheduledJob},
+        DbClient, ScheduleStore,
+    },
+};
+use serde::Deserialize;
+use time::OffsetDateTime;
+use tracing::error;
+
+/// Verification page, with the captcha image and the code form.
+pub async fn page(
+    Path(token): Path<String>,
+    Extension(cache): Extension<CacheClient>,
+) -> Result<Response, VerifyError> {
+    if cache.get::<WebCaptcha>(&token).await?.is_none() {
+        return Ok(expired());
+    }
+
+    let body = format!(
+        r#"<img src="/verify/{token}/captcha.png" alt="Captcha">
+<p>Enter the code displayed in the image to access the server.</p>
+<form method="post">
+    <input type="text" name="code" autocomplete="off" autofocus required>
+    <button type="submit">Verify</button>
+</form>"#
+    );
+
+    Ok(html_page("Verification", &body).into_response())
+}
+
+/// Verification form content.
+#[derive(Debug, Deserialize)]
+pub struct VerifyForm {
+    code: String,
+}
+
+/// Validate the code entered in the form.
+pub async fn submit(
+    Path(token): Path<String>,
+    Extension(cache): Extension<CacheClient>,
+    Extension(database): Extension<DbClient>,
+    Form(form): Form<VerifyForm>,
+) -> Result<Response, VerifyError> {
+    let captcha = match cache.get::<WebCaptcha>(&token).await? {
+        Some(captcha) => captcha,
+        None => return Ok(expired()),
+    };
+
+    if !validate_code(form.code.trim(), &captcha.code) {
+        // The token can only be used for one attempt.
+        cache.delete(&captcha).await?;
+
+        let body = "<p>The code you entered does not match the one displayed in the image. \
+            Go back to Discord to start the verification again.</p>";
+
+        return Ok((StatusCode::BAD_REQUEST, html_page("Invalid code", body)).into_response());
+    }
+
+    // Submitting the form again replaces the job instead of adding a new one.
+    let action = JobAction::WebCaptchaCompleted {
+        user_id: captcha.member_id,
+    };
+    let job = ScheduledJob::new(captcha.guild_id, action, OffsetDateTime::now_utc());
+    database.schedule_job(&job).await?;
+
+    let body = "<p>You have successfully completed the verification! \
+        You can go back to Discord, you will get access to the server in a few seconds.</p>";
+
+    Ok(html_page("Captcha completed", body).into_response())
+}
+
+/// Captcha image.
+///
+/// The image is rendered by the bot when the token is created, so that each
+/// request returns the same image.
+pub async fn image(
+    Path(token): Path<String>,
+    Extension(cache): Extension<CacheClient>,
+) -> Result<Response, VerifyError> {
+    let captcha = match cache.get::<WebCaptcha>(&token).await? {
+        Some(captcha) => captcha,
+        None => return Ok(StatusCode::NOT_FOUND.into_response()),
+    };
+
+    let headers = [
+        (header::CONTENT_TYPE, "image/png"),
+        (header::CACHE_CONTROL, "no-store"),
+    ];
+
+    Ok((headers, captcha.image).into_response())
+}
+
+/// Response sent when the token is invalid or has expired.
+fn expired() -> Response {
+    let body = "<p>This verification link is invalid or has expired. \
+        Go back to Discord to start the verification again.</p>";
+
+    (StatusCode::NOT_FOUND, html_page("Link expired", body)).into_response()
+}
+
+/// Wrap the body in a minimal HTML page.
+fn html_page(title: &str, body: &str) -> Html<String> {
+    Html(format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <title>{title} - RaidProtect</title>
+</head>
+<body>
+<h1>{title}</h1>
+{body}
+</body>
+</html>"#
+    ))
+}
+
+/// Error returned by the verification routes.
+///
+/// The error is logged and an internal server error is sent to the user.
+#[derive(Debug)]
+pub struct VerifyError(anyhow::Error);
+
+impl<E: Into<anyhow::Error>> From<E> for VerifyError {
+    fn from(error: E) -> Self {
+        Self(error.into())
+    }
+}
+
+impl IntoResponse for VerifyError {
+    fn into_response(self) -> Response {
+        error!(error = ?self.0, "error while processing verification request");
+
+        (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+    }
+}